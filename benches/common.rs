@@ -1,7 +1,70 @@
 //! Shared helpers for criterion benchmarks.
 
-#![allow(dead_code)]
+#![allow(dead_code, unused_imports)]
 
 pub use gumol_viz_engine::utils::synthetic::{
     synthetic_atom_data, synthetic_positions, synthetic_trajectory,
 };
+
+/// The frame layout before dense per-atom arrays: positions keyed by atom ID.
+/// Kept so benches can report the old cost next to the current one.
+pub mod hashmap_layout {
+    use bevy::prelude::Vec3;
+    use gumol_viz_engine::core::trajectory::FrameData;
+    use std::collections::HashMap;
+
+    #[derive(Clone)]
+    pub struct HashMapFrame {
+        pub time: f32,
+        pub positions: HashMap<u32, Vec3>,
+    }
+
+    impl HashMapFrame {
+        /// Key a dense frame's positions by their index, as the IDs were.
+        pub fn from_dense(frame: &FrameData) -> Self {
+            Self {
+                time: frame.time,
+                positions: frame
+                    .positions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &p)| (i as u32, p))
+                    .collect(),
+            }
+        }
+
+        /// Heap footprint of the position table: one (key, value) slot and
+        /// one control byte per bucket, plus a group of trailing control bytes.
+        pub fn heap_bytes(&self) -> usize {
+            let capacity = self.positions.capacity();
+            if capacity == 0 {
+                return 0;
+            }
+            // Inverse of hashbrown's 7/8 load factor
+            let buckets = if capacity < 8 {
+                if capacity < 4 {
+                    4
+                } else {
+                    8
+                }
+            } else {
+                (capacity * 8 / 7).next_power_of_two()
+            };
+            buckets * (std::mem::size_of::<(u32, Vec3)>() + 1) + 16
+        }
+    }
+
+    /// Interpolation as it was for the map layout: look up each atom of `a` in `b`.
+    pub fn interpolate(a: &HashMapFrame, b: &HashMapFrame, alpha: f32) -> HashMapFrame {
+        let mut positions = HashMap::new();
+        for (atom_id, pos_a) in &a.positions {
+            if let Some(pos_b) = b.positions.get(atom_id) {
+                positions.insert(*atom_id, pos_a.lerp(*pos_b, alpha));
+            }
+        }
+        HashMapFrame {
+            time: a.time + (b.time - a.time) * alpha,
+            positions,
+        }
+    }
+}
@@ -1,7 +1,8 @@
 mod common;
 
+use common::hashmap_layout::HashMapFrame;
 use common::{synthetic_atom_data, synthetic_trajectory};
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use gumol_viz_engine::performance::memory::{estimate_simulation_bytes, format_bytes};
 use gumol_viz_engine::systems::loading::SimulationData;
 
@@ -34,5 +35,38 @@ fn bench_dense_positions(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_memory_estimate, bench_dense_positions);
+/// Cost of copying one frame into the frame cache, for dense per-atom arrays
+/// (`dense`) and the old ID-keyed map (`hashmap`). Throughput is reported
+/// against each layout's heap footprint, which is also printed per frame.
+fn bench_frame_clone(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame_clone");
+
+    for count in [1_000usize, 10_000, 100_000] {
+        let trajectory = synthetic_trajectory(count, 1);
+        let frame = trajectory.get_frame(0).unwrap();
+        let map_frame = HashMapFrame::from_dense(frame);
+        println!(
+            "frame_clone/{count}: memory per frame dense {} vs hashmap {}",
+            format_bytes(frame.heap_bytes() as u64),
+            format_bytes(map_frame.heap_bytes() as u64)
+        );
+
+        group.throughput(Throughput::Bytes(frame.heap_bytes() as u64));
+        group.bench_with_input(BenchmarkId::new("dense", count), &count, |b, _| {
+            b.iter(|| black_box(frame.clone()));
+        });
+        group.throughput(Throughput::Bytes(map_frame.heap_bytes() as u64));
+        group.bench_with_input(BenchmarkId::new("hashmap", count), &count, |b, _| {
+            b.iter(|| black_box(map_frame.clone()));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_memory_estimate,
+    bench_dense_positions,
+    bench_frame_clone
+);
 criterion_main!(benches);
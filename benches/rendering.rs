@@ -1,10 +1,11 @@
 mod common;
 
 use bevy::prelude::*;
+use common::hashmap_layout::{self, HashMapFrame};
 use common::{synthetic_atom_data, synthetic_positions, synthetic_trajectory};
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use gumol_viz_engine::core::atom::Element;
-use gumol_viz_engine::core::trajectory::FrameData;
+use gumol_viz_engine::core::trajectory::{interpolate_frames, FrameData};
 use gumol_viz_engine::core::visualization::VisualizationConfig;
 use gumol_viz_engine::performance::PerformanceSettings;
 use gumol_viz_engine::rendering::instanced::{
@@ -22,23 +23,26 @@ fn bench_instance_data_build(c: &mut Criterion) {
     for count in [1_000usize, 10_000, 100_000] {
         group.throughput(Throughput::Elements(count as u64));
         let atoms = synthetic_atom_data(count);
-        let mut frame = FrameData::new(0, 0.0);
-        for a in &atoms {
-            frame.set_position(a.id, Vec3::new(a.id as f32, 0.0, 0.0));
-        }
+        let frame = FrameData::with_positions(
+            0,
+            0.0,
+            atoms
+                .iter()
+                .map(|a| Vec3::new(a.id as f32, 0.0, 0.0))
+                .collect(),
+        );
 
         group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
             b.iter(|| {
-                let mut instances = Vec::with_capacity(atoms.len());
-                for a in &atoms {
-                    if let Some(pos) = frame.get_position(a.id) {
-                        instances.push(AtomInstanceData {
-                            position: pos,
-                            scale: 1.0,
-                            color: Vec4::ONE,
-                        });
-                    }
-                }
+                let instances: Vec<_> = frame
+                    .positions
+                    .iter()
+                    .map(|&position| AtomInstanceData {
+                        position,
+                        scale: 1.0,
+                        color: Vec4::ONE,
+                    })
+                    .collect();
                 black_box(instances.len())
             });
         });
@@ -53,10 +57,14 @@ fn bench_instanced_spawn(c: &mut Criterion) {
     for count in [1_000usize, 10_000, 100_000] {
         group.throughput(Throughput::Elements(count as u64));
         let atoms = synthetic_atom_data(count);
-        let mut frame = FrameData::new(0, 0.0);
-        for a in &atoms {
-            frame.set_position(a.id, Vec3::new(a.id as f32 * 0.1, 0.0, 0.0));
-        }
+        let frame = FrameData::with_positions(
+            0,
+            0.0,
+            atoms
+                .iter()
+                .map(|a| Vec3::new(a.id as f32 * 0.1, 0.0, 0.0))
+                .collect(),
+        );
 
         group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
             b.iter(|| {
@@ -108,12 +116,12 @@ fn bench_timeline_position_update(c: &mut Criterion) {
         let sim = SimulationData::new(trajectory, atoms.clone());
         let frame = sim.trajectory.get_frame(5).unwrap().clone();
 
-        let mut ids_by_element = std::collections::HashMap::new();
-        for atom in &atoms {
-            ids_by_element
+        let mut indices_by_element = std::collections::HashMap::new();
+        for (index, atom) in atoms.iter().enumerate() {
+            indices_by_element
                 .entry(atom.element)
                 .or_insert_with(Vec::new)
-                .push(atom.id);
+                .push(index);
         }
 
         group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
@@ -122,22 +130,21 @@ fn bench_timeline_position_update(c: &mut Criterion) {
                     Element,
                     Vec<AtomInstanceData>,
                 > = std::collections::HashMap::new();
-                for atom in &atoms {
-                    if let Some(pos) = frame.get_position(atom.id) {
-                        instances_by_element.entry(atom.element).or_default().push(
-                            AtomInstanceData {
-                                position: pos,
-                                scale: 1.0,
-                                color: Vec4::ONE,
-                            },
-                        );
-                    }
+                for (atom, &position) in atoms.iter().zip(&frame.positions) {
+                    instances_by_element
+                        .entry(atom.element)
+                        .or_default()
+                        .push(AtomInstanceData {
+                            position,
+                            scale: 1.0,
+                            color: Vec4::ONE,
+                        });
                 }
-                for (element, ids) in &ids_by_element {
+                for (element, indices) in &indices_by_element {
                     if let Some(instances) = instances_by_element.get_mut(element) {
-                        for (i, &atom_id) in ids.iter().enumerate() {
-                            if let Some(pos) = frame.get_position(atom_id) {
-                                instances[i].position = pos;
+                        for (instance, &index) in instances.iter_mut().zip(indices) {
+                            if let Some(pos) = frame.position(index) {
+                                instance.position = pos;
                             }
                         }
                     }
@@ -164,6 +171,33 @@ fn bench_frame_position_sync(c: &mut Criterion) {
     group.finish();
 }
 
+/// Playback between two frames: a single pass over both position arrays
+/// (`dense`), against the per-atom lookups of the old ID-keyed map (`hashmap`).
+fn bench_frame_interpolation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame_interpolation");
+
+    for count in [1_000usize, 10_000, 100_000] {
+        group.throughput(Throughput::Elements(count as u64));
+        let trajectory = synthetic_trajectory(count, 2);
+        let (a, b_frame) = (
+            trajectory.get_frame(0).unwrap(),
+            trajectory.get_frame(1).unwrap(),
+        );
+        let (map_a, map_b) = (
+            HashMapFrame::from_dense(a),
+            HashMapFrame::from_dense(b_frame),
+        );
+
+        group.bench_with_input(BenchmarkId::new("dense", count), &count, |b, _| {
+            b.iter(|| black_box(interpolate_frames(a, b_frame, 0.4)));
+        });
+        group.bench_with_input(BenchmarkId::new("hashmap", count), &count, |b, _| {
+            b.iter(|| black_box(hashmap_layout::interpolate(&map_a, &map_b, 0.4)));
+        });
+    }
+    group.finish();
+}
+
 fn bench_bond_detection(c: &mut Criterion) {
     let mut group = c.benchmark_group("bond_detection_spatial");
     let config = BondDetectionConfig::default();
@@ -197,6 +231,7 @@ criterion_group!(
     bench_draw_call_count,
     bench_timeline_position_update,
     bench_frame_position_sync,
+    bench_frame_interpolation,
     bench_bond_detection
 );
 criterion_main!(benches);
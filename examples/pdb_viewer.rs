@@ -124,9 +124,9 @@ fn setup_scene(
                 ..default()
             });
 
-            // Spawn atoms (simplified - just using atom ID to determine color)
-            for (atom_id, position) in frame.positions.iter() {
-                let material = match atom_id % 4 {
+            // Spawn atoms (simplified - just using atom index to determine color)
+            for (atom_index, position) in frame.positions.iter().enumerate() {
+                let material = match atom_index % 4 {
                     0 => carbon_material.clone(),
                     1 => nitrogen_material.clone(),
                     2 => oxygen_material.clone(),
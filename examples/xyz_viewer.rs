@@ -111,7 +111,7 @@ fn setup_scene(
             });
 
             // Spawn atoms
-            for (_atom_id, position) in frame.positions.iter() {
+            for position in &frame.positions {
                 commands.spawn(PbrBundle {
                     mesh: sphere_mesh.clone(),
                     material: gray_material.clone(),
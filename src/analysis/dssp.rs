@@ -36,7 +36,7 @@ impl DsspResult {
 /// Requires backbone atoms (N, CA, C, O) for standard amino-acid residues.
 /// Returns an empty assignment map when the input lacks sufficient backbone
 /// coordinates (callers should fall back to a heuristic in that case).
-pub fn assign_dssp(atom_data: &[AtomData], positions: &HashMap<u32, Vec3>) -> DsspResult {
+pub fn assign_dssp(atom_data: &[AtomData], positions: &[Vec3]) -> DsspResult {
     let pdb_atoms = atoms_for_dssp(atom_data, positions);
     if pdb_atoms.is_empty() {
         return DsspResult::empty_with_warnings(
@@ -89,17 +89,19 @@ pub fn map_dssp_ss(ss: pdbrust::dssp::SecondaryStructure) -> SecondaryStructure
     }
 }
 
-fn atoms_for_dssp(atom_data: &[AtomData], positions: &HashMap<u32, Vec3>) -> Vec<PdbAtom> {
+fn atoms_for_dssp(atom_data: &[AtomData], positions: &[Vec3]) -> Vec<PdbAtom> {
     atom_data
         .iter()
-        .filter(|atom| is_standard_amino_acid(&atom.residue_name))
-        .filter(|atom| is_backbone_atom_name(&atom.name))
-        .filter_map(|atom| atom_data_to_pdb_atom(atom, positions))
+        .enumerate()
+        .filter(|(_, atom)| is_standard_amino_acid(&atom.residue_name))
+        .filter(|(_, atom)| is_backbone_atom_name(&atom.name))
+        .filter_map(|(i, atom)| {
+            atom_data_to_pdb_atom(atom, positions.get(i).copied().unwrap_or(atom.position))
+        })
         .collect()
 }
 
-fn atom_data_to_pdb_atom(atom: &AtomData, positions: &HashMap<u32, Vec3>) -> Option<PdbAtom> {
-    let pos = positions.get(&atom.id).copied().unwrap_or(atom.position);
+fn atom_data_to_pdb_atom(atom: &AtomData, pos: Vec3) -> Option<PdbAtom> {
     if !pos.is_finite() {
         return None;
     }
@@ -170,10 +172,10 @@ mod tests {
     use crate::core::atom::{AtomData, Element};
     use pdbrust::parse_pdb_file;
 
-    fn load_1crn_atoms() -> (Vec<AtomData>, HashMap<u32, Vec3>) {
+    fn load_1crn_atoms() -> (Vec<AtomData>, Vec<Vec3>) {
         let structure = parse_pdb_file("tests/fixtures/1CRN.pdb").expect("parse 1CRN");
         let mut atom_data = Vec::new();
-        let mut positions = HashMap::new();
+        let mut positions = Vec::new();
 
         for atom in &structure.atoms {
             if atom.is_hetatm {
@@ -192,7 +194,7 @@ mod tests {
             data.position = Vec3::new(atom.x as f32, atom.y as f32, atom.z as f32);
             data.b_factor = atom.temp_factor as f32;
             data.occupancy = atom.occupancy as f32;
-            positions.insert(id, data.position);
+            positions.push(data.position);
             atom_data.push(data);
         }
 
@@ -231,12 +233,12 @@ mod tests {
     #[test]
     fn test_dssp_ca_only_falls_back() {
         let mut atoms = Vec::new();
-        let mut positions = HashMap::new();
-        for i in 0..30 {
-            let id = i as u32;
+        let mut positions = Vec::new();
+        for i in 0..30u32 {
+            let id = i;
             let pos = Vec3::new(i as f32 * 3.8, 0.0, 0.0);
             let atom = AtomData::new(id, Element::C, i + 1, "ALA".into(), "A".into(), "CA".into());
-            positions.insert(id, pos);
+            positions.push(pos);
             atoms.push(atom);
         }
 
@@ -1,10 +1,11 @@
-//! Structural analysis tools (DSSP secondary structure, etc.)
+//! Structural analysis tools (DSSP secondary structure, molecule segmentation, etc.)
 
 pub mod dssp;
+pub mod molecules;
 
 use bevy::prelude::*;
 
 /// Register analysis resources and systems.
 pub fn register(app: &mut App) {
-    let _ = app;
+    app.init_resource::<molecules::MoleculeTopology>();
 }
@@ -0,0 +1,455 @@
+//! Residue, chain and molecule segmentation.
+//!
+//! Atoms are grouped into residues (consecutive atoms sharing chain ID,
+//! residue number and residue name), chains (residues sharing a chain ID) and
+//! molecules (residues joined by bonds). Consecutive polymer residues of one
+//! chain are always joined, so proteins stay whole when a file only lists
+//! CONECT records for hetero groups. Ions are never merged into other
+//! molecules, even when distance detection bonds them to a neighbour.
+
+use crate::core::atom::AtomData;
+use crate::core::molecule::{AminoAcid, MoleculeData, MoleculeType};
+use crate::systems::bonds::BondEntities;
+use crate::systems::loading::SimulationData;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// One residue in file order.
+#[derive(Debug, Clone)]
+pub struct ResidueInfo {
+    pub name: String,
+    pub residue_id: u32,
+    /// Index into [`MoleculeTopology::chains`]
+    pub chain: usize,
+    /// Index into [`MoleculeTopology::molecules`]
+    pub molecule: usize,
+    /// Residue class; unknown residues are ligands unless they sit inside a
+    /// polymer sequence
+    pub molecule_type: MoleculeType,
+    pub atom_ids: Vec<u32>,
+}
+
+/// Residues sharing a chain ID, in order of first appearance.
+#[derive(Debug, Clone)]
+pub struct ChainInfo {
+    pub id: String,
+    pub residues: Vec<usize>,
+}
+
+/// Residues, chains and connected molecules of the loaded system.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MoleculeTopology {
+    residues: Vec<ResidueInfo>,
+    chains: Vec<ChainInfo>,
+    molecules: Vec<MoleculeData>,
+    molecule_residues: Vec<Vec<usize>>,
+    atom_residue: HashMap<u32, usize>,
+}
+
+impl MoleculeTopology {
+    /// Segment `atoms` using bonded atom ID pairs.
+    pub fn build(atoms: &[AtomData], bonds: impl IntoIterator<Item = (u32, u32)>) -> Self {
+        let mut residues: Vec<ResidueInfo> = Vec::new();
+        let mut chains: Vec<ChainInfo> = Vec::new();
+        let mut chain_index: HashMap<&str, usize> = HashMap::new();
+        let mut classes: HashMap<&str, MoleculeType> = HashMap::new();
+        let mut atom_residue = HashMap::with_capacity(atoms.len());
+
+        let mut previous: Option<&AtomData> = None;
+        for atom in atoms {
+            let same_residue = previous.is_some_and(|p| {
+                p.residue_id == atom.residue_id
+                    && p.residue_name == atom.residue_name
+                    && p.chain_id == atom.chain_id
+            });
+            if !same_residue {
+                let chain = *chain_index
+                    .entry(atom.chain_id.as_str())
+                    .or_insert_with(|| {
+                        chains.push(ChainInfo {
+                            id: atom.chain_id.clone(),
+                            residues: Vec::new(),
+                        });
+                        chains.len() - 1
+                    });
+                chains[chain].residues.push(residues.len());
+                let molecule_type = *classes
+                    .entry(atom.residue_name.as_str())
+                    .or_insert_with(|| MoleculeType::from_residue_name(&atom.residue_name));
+                residues.push(ResidueInfo {
+                    name: atom.residue_name.clone(),
+                    residue_id: atom.residue_id,
+                    chain,
+                    molecule: 0,
+                    molecule_type,
+                    atom_ids: Vec::new(),
+                });
+            }
+            let residue = residues.len() - 1;
+            residues[residue].atom_ids.push(atom.id);
+            atom_residue.insert(atom.id, residue);
+            previous = Some(atom);
+        }
+
+        // Join residues into molecules.
+        let mut sets = DisjointSet::new(residues.len());
+        for chain in &chains {
+            for pair in chain.residues.windows(2) {
+                let (a, b) = (&residues[pair[0]], &residues[pair[1]]);
+                let polymer = |t: MoleculeType| {
+                    matches!(t, MoleculeType::Protein | MoleculeType::NucleicAcid)
+                };
+                if polymer(a.molecule_type)
+                    && a.molecule_type == b.molecule_type
+                    && pair[1] == pair[0] + 1
+                {
+                    sets.union(pair[0], pair[1]);
+                }
+            }
+        }
+        for (a, b) in bonds {
+            let (Some(&ra), Some(&rb)) = (atom_residue.get(&a), atom_residue.get(&b)) else {
+                continue;
+            };
+            let is_ion = |r: usize| residues[r].molecule_type == MoleculeType::Ion;
+            if ra != rb && !is_ion(ra) && !is_ion(rb) {
+                sets.union(ra, rb);
+            }
+        }
+
+        // Number molecules by their first residue.
+        let mut molecule_of_root: HashMap<usize, usize> = HashMap::new();
+        let mut molecule_residues: Vec<Vec<usize>> = Vec::new();
+        for (r, residue) in residues.iter_mut().enumerate() {
+            let root = sets.find(r);
+            let molecule = *molecule_of_root.entry(root).or_insert_with(|| {
+                molecule_residues.push(Vec::new());
+                molecule_residues.len() - 1
+            });
+            molecule_residues[molecule].push(r);
+            residue.molecule = molecule;
+        }
+
+        let molecule_types: Vec<MoleculeType> = molecule_residues
+            .iter()
+            .map(|members| classify_molecule(members.iter().map(|&r| residues[r].molecule_type)))
+            .collect();
+
+        // Unknown residues inside a polymer sequence are modified monomers;
+        // any other unknown residue is a ligand, even when bonded to a chain.
+        let refined: Vec<MoleculeType> = (0..residues.len())
+            .map(|r| {
+                let residue = &residues[r];
+                if residue.molecule_type != MoleculeType::Unknown {
+                    return residue.molecule_type;
+                }
+                let molecule_type = molecule_types[residue.molecule];
+                let in_sequence = [r.checked_sub(1), Some(r + 1)]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|n| residues.get(n))
+                    .any(|n| {
+                        n.chain == residue.chain
+                            && n.molecule == residue.molecule
+                            && n.molecule_type == molecule_type
+                            && n.residue_id.abs_diff(residue.residue_id) == 1
+                    });
+                match molecule_type {
+                    MoleculeType::Protein | MoleculeType::NucleicAcid if in_sequence => {
+                        molecule_type
+                    }
+                    _ => MoleculeType::Ligand,
+                }
+            })
+            .collect();
+        for (residue, class) in residues.iter_mut().zip(refined) {
+            residue.molecule_type = class;
+        }
+
+        let molecules = molecule_residues
+            .iter()
+            .zip(molecule_types)
+            .map(|(members, molecule_type)| {
+                describe_molecule(molecule_type, members, &residues, &chains)
+            })
+            .collect();
+
+        Self {
+            residues,
+            chains,
+            molecules,
+            molecule_residues,
+            atom_residue,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.residues.is_empty()
+    }
+
+    /// Number of atoms covered by this topology.
+    pub fn atom_count(&self) -> usize {
+        self.atom_residue.len()
+    }
+
+    pub fn residues(&self) -> &[ResidueInfo] {
+        &self.residues
+    }
+
+    pub fn chains(&self) -> &[ChainInfo] {
+        &self.chains
+    }
+
+    pub fn molecules(&self) -> &[MoleculeData] {
+        &self.molecules
+    }
+
+    /// Residue indices of molecule `molecule`.
+    pub fn molecule_residues(&self, molecule: usize) -> &[usize] {
+        self.molecule_residues
+            .get(molecule)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Residue index of an atom.
+    pub fn residue_index(&self, atom_id: u32) -> Option<usize> {
+        self.atom_residue.get(&atom_id).copied()
+    }
+
+    /// Molecule index of an atom.
+    pub fn molecule_index(&self, atom_id: u32) -> Option<usize> {
+        self.residue_index(atom_id)
+            .map(|r| self.residues[r].molecule)
+    }
+
+    /// Residue class of an atom (`Unknown` for atoms outside the topology).
+    pub fn atom_class(&self, atom_id: u32) -> MoleculeType {
+        self.residue_index(atom_id)
+            .map(|r| self.residues[r].molecule_type)
+            .unwrap_or_default()
+    }
+
+    /// Molecule counts per class, in order of first appearance.
+    pub fn molecule_counts(&self) -> Vec<(MoleculeType, usize)> {
+        let mut counts: Vec<(MoleculeType, usize)> = Vec::new();
+        for molecule in &self.molecules {
+            match counts
+                .iter_mut()
+                .find(|(t, _)| *t == molecule.molecule_type)
+            {
+                Some((_, n)) => *n += 1,
+                None => counts.push((molecule.molecule_type, 1)),
+            }
+        }
+        counts
+    }
+}
+
+/// Class of a molecule from the classes of its residues.
+fn classify_molecule(classes: impl Iterator<Item = MoleculeType>) -> MoleculeType {
+    let classes: Vec<MoleculeType> = classes.collect();
+    let all = |t: MoleculeType| classes.iter().all(|c| *c == t);
+    if classes.contains(&MoleculeType::Protein) {
+        MoleculeType::Protein
+    } else if classes.contains(&MoleculeType::NucleicAcid) {
+        MoleculeType::NucleicAcid
+    } else if all(MoleculeType::Water) {
+        MoleculeType::Water
+    } else if all(MoleculeType::Ion) {
+        MoleculeType::Ion
+    } else if all(MoleculeType::Lipid) {
+        MoleculeType::Lipid
+    } else {
+        MoleculeType::Ligand
+    }
+}
+
+fn describe_molecule(
+    molecule_type: MoleculeType,
+    members: &[usize],
+    residues: &[ResidueInfo],
+    chains: &[ChainInfo],
+) -> MoleculeData {
+    let first = &residues[members[0]];
+    let chain_id = chains[first.chain].id.clone();
+    let name = match molecule_type {
+        MoleculeType::Protein | MoleculeType::NucleicAcid if chain_id.trim().is_empty() => {
+            molecule_type.name().to_string()
+        }
+        MoleculeType::Protein | MoleculeType::NucleicAcid => {
+            format!("{} {}", molecule_type.name(), chain_id.trim())
+        }
+        _ => first.name.clone(),
+    };
+
+    let mut molecule = MoleculeData::new(name, molecule_type, chain_id);
+    for &r in members {
+        let residue = &residues[r];
+        for &atom_id in &residue.atom_ids {
+            molecule.add_atom(atom_id);
+        }
+        match residue.molecule_type {
+            MoleculeType::Protein => molecule
+                .sequence
+                .push(AminoAcid::from_code3(&residue.name).code1()),
+            MoleculeType::NucleicAcid => molecule.sequence.push(nucleotide_code(&residue.name)),
+            _ => {}
+        }
+    }
+    molecule
+}
+
+/// One-letter base code of a nucleotide residue name (`DA5` → `A`, `CYT` → `C`).
+fn nucleotide_code(name: &str) -> char {
+    let name = name.trim().to_uppercase();
+    let base = match name.strip_prefix(['D', 'R']) {
+        Some(rest) if !rest.is_empty() => rest,
+        _ => name.as_str(),
+    };
+    base.chars().next().unwrap_or('X')
+}
+
+/// Union-find over residue indices.
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            // Keep the lower index as root so molecules stay in file order.
+            self.parent[ra.max(rb)] = ra.min(rb);
+        }
+    }
+}
+
+/// Rebuild the topology when atoms, file bonds or spawned bonds change.
+pub fn update_molecule_topology(
+    sim_data: Res<SimulationData>,
+    bond_entities: Res<BondEntities>,
+    mut topology: ResMut<MoleculeTopology>,
+    mut core_data: ResMut<crate::core::SimulationData>,
+) {
+    if !sim_data.is_changed() && !bond_entities.is_changed() {
+        return;
+    }
+    if !sim_data.loaded {
+        if !topology.is_empty() {
+            *topology = MoleculeTopology::default();
+            core_data.molecules.clear();
+        }
+        return;
+    }
+
+    let start = std::time::Instant::now();
+    let bonds = sim_data
+        .bond_data
+        .iter()
+        .map(|b| (b.atom_a_id, b.atom_b_id))
+        .chain(bond_entities.entities.keys().copied());
+    *topology = MoleculeTopology::build(sim_data.atom_data(), bonds);
+    core_data.molecules = topology.molecules().to_vec();
+
+    info!(
+        "Molecule topology: {} residues, {} chains, {} molecules in {:.1} ms",
+        topology.residues().len(),
+        topology.chains().len(),
+        topology.molecules().len(),
+        start.elapsed().as_secs_f32() * 1000.0
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::atom::Element;
+
+    fn atom(id: u32, residue_id: u32, resname: &str, chain: &str, name: &str) -> AtomData {
+        AtomData::new(
+            id,
+            Element::C,
+            residue_id,
+            resname.into(),
+            chain.into(),
+            name.into(),
+        )
+    }
+
+    #[test]
+    fn test_segments_residues_chains_and_molecules() {
+        let atoms = vec![
+            atom(0, 1, "ALA", "A", "N"),
+            atom(1, 1, "ALA", "A", "CA"),
+            atom(2, 2, "GLY", "A", "N"),
+            atom(3, 2, "GLY", "A", "CA"),
+            atom(4, 3, "SEP", "A", "CA"),
+            atom(5, 100, "LIG", "A", "C1"),
+            atom(6, 100, "LIG", "A", "C2"),
+            atom(7, 200, "HOH", "W", "O"),
+            atom(8, 201, "HOH", "W", "O"),
+            atom(9, 300, "NA", "I", "NA"),
+        ];
+        // SEP is joined to the chain by a bond; the ligand and water are not.
+        // The ion is bonded to a water but stays on its own.
+        let bonds = [(3, 4), (5, 6), (8, 9)];
+        let topology = MoleculeTopology::build(&atoms, bonds);
+
+        assert_eq!(topology.residues().len(), 7);
+        let chain_ids: Vec<&str> = topology.chains().iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(chain_ids, ["A", "W", "I"]);
+
+        let molecules: Vec<(&str, MoleculeType)> = topology
+            .molecules()
+            .iter()
+            .map(|m| (m.name.as_str(), m.molecule_type))
+            .collect();
+        assert_eq!(
+            molecules,
+            [
+                ("Protein A", MoleculeType::Protein),
+                ("LIG", MoleculeType::Ligand),
+                ("HOH", MoleculeType::Water),
+                ("HOH", MoleculeType::Water),
+                ("NA", MoleculeType::Ion),
+            ]
+        );
+        assert_eq!(topology.molecules()[0].atom_ids, [0, 1, 2, 3, 4]);
+        assert_eq!(topology.molecules()[0].sequence, "AGX");
+        assert_eq!(topology.atom_class(4), MoleculeType::Protein);
+        assert_eq!(topology.atom_class(5), MoleculeType::Ligand);
+        assert_eq!(topology.molecule_index(9), Some(4));
+        assert_eq!(
+            topology.molecule_counts(),
+            [
+                (MoleculeType::Protein, 1),
+                (MoleculeType::Ligand, 1),
+                (MoleculeType::Water, 2),
+                (MoleculeType::Ion, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nucleotide_codes() {
+        assert_eq!(nucleotide_code("DA5"), 'A');
+        assert_eq!(nucleotide_code("RU"), 'U');
+        assert_eq!(nucleotide_code("CYT"), 'C');
+        assert_eq!(nucleotide_code("G"), 'G');
+    }
+}
@@ -34,18 +34,9 @@ fn focus_on_molecule_key(
         return;
     };
 
-    let mut sum = Vec3::ZERO;
-    let mut count = 0;
-    for pos in frame.positions.values() {
-        sum += *pos;
-        count += 1;
-    }
-
-    if count == 0 {
+    let Some(center) = frame.centroid() else {
         return;
-    }
-
-    let center = sum / count as f32;
+    };
     for mut cam in camera_query.iter_mut() {
         cam.focus = center;
         cam.target_focus = center;
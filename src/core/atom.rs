@@ -2,6 +2,7 @@
 
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Atom component attached to Bevy entities
 #[derive(Component, Clone, Debug, Reflect, Default)]
@@ -81,6 +82,43 @@ impl AtomData {
     }
 }
 
+/// Atom ID → index into the topology's atom order, which is also the layout
+/// of the per-atom arrays in [`FrameData`](crate::core::trajectory::FrameData).
+#[derive(Debug, Clone, Default)]
+pub struct AtomIndexMap {
+    len: usize,
+    /// `None` when the IDs are `0..len` in order, so lookups need no hashing
+    ids: Option<HashMap<u32, usize>>,
+}
+
+impl AtomIndexMap {
+    /// Index the atoms of a topology.
+    pub fn build(atoms: &[AtomData]) -> Self {
+        let sequential = atoms.iter().enumerate().all(|(i, a)| a.id as usize == i);
+        let ids = (!sequential).then(|| atoms.iter().enumerate().map(|(i, a)| (a.id, i)).collect());
+        Self {
+            len: atoms.len(),
+            ids,
+        }
+    }
+
+    /// Index of the atom with `id`.
+    pub fn index_of(&self, id: u32) -> Option<usize> {
+        match &self.ids {
+            Some(ids) => ids.get(&id).copied(),
+            None => ((id as usize) < self.len).then_some(id as usize),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
 /// Chemical element enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
 #[reflect(Debug, PartialEq, Hash)]
@@ -669,6 +707,36 @@ impl Element {
         Element::Unknown
     }
 
+    /// Element with the given atomic number (1–103), or `Unknown`.
+    pub fn from_atomic_number(z: u32) -> Element {
+        match z {
+            1..=103 => Self::all_variants()[z as usize - 1],
+            _ => Element::Unknown,
+        }
+    }
+
+    /// Infer an element from a force-field atom name and mass.
+    ///
+    /// The mass disambiguates names such as `CA` (Cα vs. calcium) or `CL`
+    /// (chloride vs. a carbon); the name wins when its element's mass agrees.
+    pub fn from_name_and_mass(name: &str, mass: f32) -> Element {
+        const TOLERANCE: f32 = 0.6;
+        let from_name = Self::from_atom_name(name);
+        if from_name != Element::Unknown && (from_name.mass() - mass).abs() < TOLERANCE {
+            return from_name;
+        }
+        Self::all_variants()
+            .iter()
+            .copied()
+            // Elements without tabulated masses report carbon's 12.0
+            .filter(|e| *e != Element::Unknown && (*e == Element::C || e.mass() != 12.0))
+            .map(|e| (e, (e.mass() - mass).abs()))
+            .filter(|(_, diff)| *diff < TOLERANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(e, _)| e)
+            .unwrap_or(from_name)
+    }
+
     /// All element variants (103 named elements + `Unknown`).
     pub fn all_variants() -> &'static [Element] {
         use Element::*;
@@ -695,6 +763,30 @@ mod tests {
         assert_eq!(Element::from_atom_name("HW1"), Element::H);
     }
 
+    #[test]
+    fn test_from_atomic_number_and_mass() {
+        assert_eq!(Element::from_atomic_number(1), Element::H);
+        assert_eq!(Element::from_atomic_number(20), Element::Ca);
+        assert_eq!(Element::from_atomic_number(0), Element::Unknown);
+        assert_eq!(Element::from_name_and_mass("CA", 12.011), Element::C);
+        assert_eq!(Element::from_name_and_mass("CA", 40.08), Element::Ca);
+        assert_eq!(Element::from_name_and_mass("CLA", 35.45), Element::Cl);
+    }
+
+    #[test]
+    fn test_atom_index_map() {
+        let atom = |id| AtomData::new(id, Element::C, 1, "ALA".into(), "A".into(), "CA".into());
+        let sequential = AtomIndexMap::build(&[atom(0), atom(1), atom(2)]);
+        assert_eq!(sequential.index_of(2), Some(2));
+        assert_eq!(sequential.index_of(3), None);
+
+        // PDB serials with a gap
+        let serials = AtomIndexMap::build(&[atom(1), atom(2), atom(5)]);
+        assert_eq!(serials.index_of(5), Some(2));
+        assert_eq!(serials.index_of(0), None);
+        assert_eq!(serials.len(), 3);
+    }
+
     #[test]
     fn test_element_from_symbol() {
         assert_eq!(Element::from_symbol("C").unwrap(), Element::C);
@@ -101,7 +101,7 @@ pub enum BondType {
     Unknown,
 }
 
-/// Bond order (single, double, triple)
+/// Bond order (single, double, triple, aromatic)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
 #[reflect(Debug, PartialEq, Hash)]
 #[derive(Default)]
@@ -113,6 +113,8 @@ pub enum BondOrder {
     Double = 2,
     /// Triple bond
     Triple = 3,
+    /// Aromatic (delocalized) bond, as written by MOL2 `ar` and SDF type 4
+    Aromatic = 4,
 }
 
 /// Bond length lookup table for element pairs (in Angstroms)
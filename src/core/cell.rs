@@ -0,0 +1,272 @@
+//! Periodic simulation cell (orthorhombic or triclinic)
+//!
+//! A cell is stored both as its three box vectors (rows `a`, `b`, `c`, in Å)
+//! and as crystallographic lengths and angles, so parsers can fill it from
+//! whichever representation their format uses. Vectors follow the PDB /
+//! GROMACS convention: `a` along +x, `b` in the xy-plane, `c` anywhere with
+//! positive z.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Angles within this many degrees of 90° count as orthorhombic.
+const RIGHT_ANGLE_TOLERANCE: f32 = 1e-3;
+
+/// Periodic unit cell.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UnitCell {
+    /// Box vectors `a`, `b`, `c` (Å)
+    pub vectors: [Vec3; 3],
+    /// Lengths |a|, |b|, |c| (Å)
+    pub lengths: [f32; 3],
+    /// Angles α (b∠c), β (a∠c), γ (a∠b) in degrees
+    pub angles: [f32; 3],
+}
+
+impl UnitCell {
+    /// Rectangular box with the given edge lengths.
+    pub fn orthorhombic(lengths: [f32; 3]) -> Self {
+        Self {
+            vectors: [
+                Vec3::new(lengths[0], 0.0, 0.0),
+                Vec3::new(0.0, lengths[1], 0.0),
+                Vec3::new(0.0, 0.0, lengths[2]),
+            ],
+            lengths,
+            angles: [90.0; 3],
+        }
+    }
+
+    /// Build a cell from lengths (a, b, c) and angles (α, β, γ in degrees).
+    pub fn from_lengths_angles(lengths: [f32; 3], angles: [f32; 3]) -> Self {
+        let is_right = |deg: f32| (deg - 90.0).abs() < RIGHT_ANGLE_TOLERANCE;
+        if angles.iter().all(|a| is_right(*a)) {
+            return Self::orthorhombic(lengths);
+        }
+
+        let [a, b, c] = lengths;
+        let [alpha, beta, gamma] = angles.map(f32::to_radians);
+        let (cos_a, cos_b, cos_g) = (alpha.cos(), beta.cos(), gamma.cos());
+        let sin_g = gamma.sin();
+
+        let cx = cos_b;
+        let cy = (cos_a - cos_b * cos_g) / sin_g;
+        let cz = (1.0 - cx * cx - cy * cy).max(0.0).sqrt();
+
+        Self {
+            vectors: [
+                Vec3::new(a, 0.0, 0.0),
+                Vec3::new(b * cos_g, b * sin_g, 0.0),
+                Vec3::new(c * cx, c * cy, c * cz),
+            ],
+            lengths,
+            angles,
+        }
+    }
+
+    /// Build a cell from explicit box vectors; lengths and angles are derived.
+    pub fn from_vectors(vectors: [Vec3; 3]) -> Self {
+        let lengths = vectors.map(|v| v.length());
+        let angle = |u: Vec3, v: Vec3| {
+            if u.length_squared() == 0.0 || v.length_squared() == 0.0 {
+                90.0
+            } else {
+                u.angle_between(v).to_degrees()
+            }
+        };
+        Self {
+            vectors,
+            lengths,
+            angles: [
+                angle(vectors[1], vectors[2]),
+                angle(vectors[0], vectors[2]),
+                angle(vectors[0], vectors[1]),
+            ],
+        }
+    }
+
+    /// Parse a GRO box line: 3 values (orthorhombic) or 9 values in the order
+    /// `v1(x) v2(y) v3(z) v1(y) v1(z) v2(x) v2(z) v3(x) v3(y)`.
+    pub fn from_gro_box(values: &[f32]) -> Option<Self> {
+        match values.len() {
+            3 => Some(Self::orthorhombic([values[0], values[1], values[2]])),
+            n if n >= 9 => Some(Self::from_vectors([
+                Vec3::new(values[0], values[3], values[4]),
+                Vec3::new(values[5], values[1], values[6]),
+                Vec3::new(values[7], values[8], values[2]),
+            ])),
+            _ => None,
+        }
+    }
+
+    /// GRO box line values (3 for orthorhombic cells, 9 otherwise).
+    pub fn to_gro_box(&self) -> Vec<f32> {
+        let [a, b, c] = self.vectors;
+        if self.is_orthorhombic() {
+            vec![a.x, b.y, c.z]
+        } else {
+            vec![a.x, b.y, c.z, a.y, a.z, b.x, b.z, c.x, c.y]
+        }
+    }
+
+    /// Whether all off-diagonal box components are zero.
+    pub fn is_orthorhombic(&self) -> bool {
+        let [a, b, c] = self.vectors;
+        [a.y, a.z, b.x, b.z, c.x, c.y].iter().all(|v| *v == 0.0)
+    }
+
+    /// Whether the cell encloses a non-zero volume (a zero box means no PBC).
+    pub fn is_periodic(&self) -> bool {
+        self.volume() > 1e-6
+    }
+
+    /// Cell volume (Å³)
+    pub fn volume(&self) -> f32 {
+        self.matrix().determinant().abs()
+    }
+
+    /// Matrix whose columns are the box vectors (fractional → Cartesian).
+    pub fn matrix(&self) -> Mat3 {
+        Mat3::from_cols(self.vectors[0], self.vectors[1], self.vectors[2])
+    }
+
+    /// Uniformly scale the cell (e.g. nm → Å).
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            vectors: self.vectors.map(|v| v * factor),
+            lengths: self.lengths.map(|l| l * factor),
+            angles: self.angles,
+        }
+    }
+
+    /// Blend box vectors towards another cell (used for smooth playback).
+    pub fn lerp(&self, other: &UnitCell, t: f32) -> Self {
+        Self::from_vectors([
+            self.vectors[0].lerp(other.vectors[0], t),
+            self.vectors[1].lerp(other.vectors[1], t),
+            self.vectors[2].lerp(other.vectors[2], t),
+        ])
+    }
+
+    /// Cartesian position → fractional coordinates.
+    pub fn to_fractional(&self, position: Vec3) -> Vec3 {
+        self.matrix().inverse() * position
+    }
+
+    /// Fractional coordinates → Cartesian position.
+    pub fn to_cartesian(&self, fractional: Vec3) -> Vec3 {
+        self.matrix() * fractional
+    }
+
+    /// Wrap a position into the primary cell (fractional coordinates in [0, 1)).
+    pub fn wrap(&self, position: Vec3) -> Vec3 {
+        if !self.is_periodic() {
+            return position;
+        }
+        let f = self.to_fractional(position);
+        self.to_cartesian(Vec3::new(
+            f.x.rem_euclid(1.0),
+            f.y.rem_euclid(1.0),
+            f.z.rem_euclid(1.0),
+        ))
+    }
+
+    /// Shortest periodic image of a displacement vector.
+    ///
+    /// Rounding in fractional space is exact for orthorhombic cells; skewed
+    /// cells additionally check the 26 neighbouring images.
+    pub fn minimum_image(&self, delta: Vec3) -> Vec3 {
+        if !self.is_periodic() {
+            return delta;
+        }
+        let m = self.matrix();
+        let f = m.inverse() * delta;
+        let reduced = m * (f - f.round());
+        if self.is_orthorhombic() {
+            return reduced;
+        }
+
+        let mut best = reduced;
+        for i in -1..=1 {
+            for j in -1..=1 {
+                for k in -1..=1 {
+                    let candidate = reduced + m * Vec3::new(i as f32, j as f32, k as f32);
+                    if candidate.length_squared() < best.length_squared() {
+                        best = candidate;
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// The 8 cell corners, corner `i` = Σ bit(i) · vector.
+    pub fn corners(&self) -> [Vec3; 8] {
+        let [a, b, c] = self.vectors;
+        std::array::from_fn(|i| {
+            let mut p = Vec3::ZERO;
+            if i & 1 != 0 {
+                p += a;
+            }
+            if i & 2 != 0 {
+                p += b;
+            }
+            if i & 4 != 0 {
+                p += c;
+            }
+            p
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).abs().max_element() < 1e-3, "{a:?} vs {b:?}");
+    }
+
+    #[test]
+    fn test_lengths_angles_roundtrip() {
+        let cell = UnitCell::from_lengths_angles([30.0, 40.0, 50.0], [70.0, 80.0, 100.0]);
+        let rebuilt = UnitCell::from_vectors(cell.vectors);
+        for i in 0..3 {
+            assert!((rebuilt.lengths[i] - cell.lengths[i]).abs() < 1e-3);
+            assert!((rebuilt.angles[i] - cell.angles[i]).abs() < 1e-2);
+        }
+        assert!(!cell.is_orthorhombic());
+        assert!(UnitCell::from_lengths_angles([10.0; 3], [90.0; 3]).is_orthorhombic());
+    }
+
+    #[test]
+    fn test_gro_box_nine_components() {
+        // Rhombic dodecahedron (xy-square) as written by gmx editconf.
+        let values = [7.0, 7.0, 4.94975, 0.0, 0.0, 0.0, 0.0, 3.5, 3.5];
+        let cell = UnitCell::from_gro_box(&values).unwrap();
+        assert_close(cell.vectors[2], Vec3::new(3.5, 3.5, 4.94975));
+        assert_eq!(cell.to_gro_box(), values.to_vec());
+        assert!((cell.angles[0] - 60.0).abs() < 0.01);
+        assert!((cell.angles[2] - 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_triclinic_minimum_image_and_wrap() {
+        let cell = UnitCell::from_lengths_angles([10.0, 10.0, 10.0], [90.0, 90.0, 60.0]);
+        // Step of almost one `b` vector is really a short hop backwards.
+        let delta = cell.vectors[1] * 0.95;
+        let image = cell.minimum_image(delta);
+        assert_close(image, -cell.vectors[1] * 0.05);
+
+        let inside = Vec3::new(4.0, 2.0, 3.0);
+        let outside = inside + cell.vectors[0] - 2.0 * cell.vectors[1] + cell.vectors[2];
+        assert_close(cell.wrap(outside), inside);
+    }
+
+    #[test]
+    fn test_zero_box_is_not_periodic() {
+        let cell = UnitCell::orthorhombic([0.0; 3]);
+        assert!(!cell.is_periodic());
+        assert_eq!(cell.minimum_image(Vec3::X * 50.0), Vec3::X * 50.0);
+    }
+}
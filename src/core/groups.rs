@@ -0,0 +1,541 @@
+//! Named atom groups with per-group visual style
+//!
+//! Groups are built from selections or selection queries and keep their own
+//! render mode, color scheme, scale and visibility, so one scene can mix e.g.
+//! a cartoon protein, a ball-and-stick ligand and hidden solvent. Atoms that
+//! belong to no group follow the global `VisualizationConfig`; when groups
+//! overlap, the group added last wins.
+//!
+//! `HiddenAtoms` holds atoms hidden explicitly (hide selection, hide solvent);
+//! they override every group.
+//!
+//! Groups are saved to and restored from JSON groups files
+//! ([`AtomGroups::save`], [`AtomGroups::load`]).
+//!
+//! `AtomStyles` is the per-atom view of the groups and hidden atoms resolved
+//! against the global config. It only changes when a resolved style, group
+//! membership or the hidden set actually changes, so renderers can key their
+//! updates on `AtomStyles::is_changed`.
+
+use crate::core::atom::AtomData;
+use crate::core::visualization::{ColorScheme, RenderMode, VisualizationConfig};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Visual style shared by all atoms of one group.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GroupStyle {
+    pub render_mode: RenderMode,
+    pub color_scheme: ColorScheme,
+    /// Atom size multiplier (0.1 to 2.0)
+    pub atom_scale: f32,
+    /// Bond thickness multiplier (0.1 to 3.0)
+    pub bond_scale: f32,
+    pub visible: bool,
+}
+
+impl GroupStyle {
+    pub fn new(render_mode: RenderMode) -> Self {
+        Self {
+            render_mode,
+            color_scheme: ColorScheme::CPK,
+            atom_scale: 1.0,
+            bond_scale: 1.0,
+            visible: true,
+        }
+    }
+
+    /// Style matching the current global settings.
+    pub fn from_config(config: &VisualizationConfig) -> Self {
+        Self {
+            render_mode: config.render_mode,
+            color_scheme: config.color_scheme,
+            atom_scale: config.atom_scale,
+            bond_scale: config.bond_scale,
+            visible: true,
+        }
+    }
+
+    pub fn hidden() -> Self {
+        Self {
+            visible: false,
+            ..Self::new(RenderMode::CPK)
+        }
+    }
+}
+
+/// A named set of atoms drawn with its own style.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AtomGroup {
+    pub name: String,
+    /// Selection query the group was defined from; re-evaluated when a new
+    /// file is loaded. Groups built from a plain selection keep their IDs.
+    pub query: Option<String>,
+    pub atom_ids: Vec<u32>,
+    pub style: GroupStyle,
+}
+
+/// All user-defined groups, in priority order (later groups win).
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AtomGroups {
+    groups: Vec<AtomGroup>,
+    /// Atom ID -> index of the last group containing it
+    #[serde(skip)]
+    membership: HashMap<u32, usize>,
+    /// Bumped whenever membership changes
+    #[serde(skip)]
+    generation: u64,
+}
+
+impl AtomGroups {
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AtomGroup> {
+        self.groups.iter()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&AtomGroup> {
+        self.groups.get(index)
+    }
+
+    /// Index of the group that styles `atom_id`, if any.
+    pub fn group_of(&self, atom_id: u32) -> Option<usize> {
+        self.membership.get(&atom_id).copied()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Append a group; returns its index.
+    pub fn add(&mut self, group: AtomGroup) -> usize {
+        self.groups.push(group);
+        self.rebuild_membership();
+        self.groups.len() - 1
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<AtomGroup> {
+        if index >= self.groups.len() {
+            return None;
+        }
+        let group = self.groups.remove(index);
+        self.rebuild_membership();
+        Some(group)
+    }
+
+    pub fn set_style(&mut self, index: usize, style: GroupStyle) {
+        if let Some(group) = self.groups.get_mut(index) {
+            group.style = style;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.groups.clear();
+        self.rebuild_membership();
+    }
+
+    /// Re-resolve groups against a newly loaded structure.
+    ///
+    /// Query groups are re-evaluated with `select`. ID-based groups, and query
+    /// groups whose query fails, keep only the IDs present in the new structure.
+    pub fn refresh(
+        &mut self,
+        atoms: &[AtomData],
+        mut select: impl FnMut(&str) -> Result<Vec<u32>, String>,
+    ) {
+        let existing: HashSet<u32> = atoms.iter().map(|a| a.id).collect();
+        for group in &mut self.groups {
+            match group.query.as_deref().map(&mut select) {
+                Some(Ok(ids)) => group.atom_ids = ids,
+                Some(Err(err)) => {
+                    warn!("Group '{}': query failed on reload: {}", group.name, err);
+                    group.atom_ids.retain(|id| existing.contains(id));
+                }
+                None => group.atom_ids.retain(|id| existing.contains(id)),
+            }
+        }
+        self.rebuild_membership();
+    }
+
+    fn rebuild_membership(&mut self) {
+        self.membership.clear();
+        for (index, group) in self.groups.iter().enumerate() {
+            for &id in &group.atom_ids {
+                self.membership.insert(id, index);
+            }
+        }
+        self.generation += 1;
+    }
+
+    /// Serialize groups (names, queries, IDs and styles) to JSON.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let mut groups: Self = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        groups.rebuild_membership();
+        Ok(groups)
+    }
+
+    /// Write the groups to a JSON groups file.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_json()?).map_err(|e| e.to_string())
+    }
+
+    /// Replace the groups with those in a groups file written by [`Self::save`].
+    pub fn load(&mut self, path: &Path) -> Result<(), String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        self.groups = Self::from_json(&json)?.groups;
+        self.rebuild_membership();
+        Ok(())
+    }
+
+    /// Styles for ungrouped atoms (slot 0) followed by one slot per group.
+    fn style_table(&self, config: &VisualizationConfig) -> Vec<AtomStyle> {
+        std::iter::once(AtomStyle::from_config(config))
+            .chain(self.groups.iter().map(|g| AtomStyle::from_group(&g.style)))
+            .collect()
+    }
+}
+
+/// Atoms hidden from every renderer, pick proxies and exports.
+#[derive(Resource, Debug, Default)]
+pub struct HiddenAtoms {
+    hidden: HashSet<u32>,
+    /// Bumped whenever the hidden set changes
+    generation: u64,
+}
+
+impl HiddenAtoms {
+    pub fn contains(&self, atom_id: u32) -> bool {
+        self.hidden.contains(&atom_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.hidden.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hidden.is_empty()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn hide(&mut self, atom_ids: impl IntoIterator<Item = u32>) {
+        self.hidden.extend(atom_ids);
+        self.generation += 1;
+    }
+
+    pub fn show(&mut self, atom_ids: impl IntoIterator<Item = u32>) {
+        for id in atom_ids {
+            self.hidden.remove(&id);
+        }
+        self.generation += 1;
+    }
+
+    /// Replace the hidden set.
+    pub fn set(&mut self, atom_ids: impl IntoIterator<Item = u32>) {
+        self.hidden = atom_ids.into_iter().collect();
+        self.generation += 1;
+    }
+
+    pub fn clear(&mut self) {
+        if !self.hidden.is_empty() {
+            self.hidden.clear();
+            self.generation += 1;
+        }
+    }
+}
+
+/// Fully resolved style of a single atom, as consumed by the renderers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtomStyle {
+    pub render_mode: RenderMode,
+    pub color_scheme: ColorScheme,
+    /// Instance scale for the atom sphere (0 when spheres are hidden)
+    pub atom_scale: f32,
+    /// Whether bonds to this atom are drawn as cylinders
+    pub cylinder_bonds: bool,
+    /// Cylinder thickness multiplier
+    pub bond_thickness: f32,
+    /// Whether bonds to this atom are drawn as wireframe lines
+    pub wire_bonds: bool,
+    /// Whether the atom's residue is part of the backbone ribbon
+    pub ribbon: bool,
+    /// False for hidden atoms (hidden group or `HiddenAtoms`); such atoms
+    /// are not pickable and are left out of exports
+    pub visible: bool,
+}
+
+impl AtomStyle {
+    fn resolve(
+        mode: RenderMode,
+        color_scheme: ColorScheme,
+        atom_scale: f32,
+        bond_scale: f32,
+        show_atoms: bool,
+        show_bonds: bool,
+    ) -> Self {
+        let params = mode.mode_params();
+        Self {
+            render_mode: mode,
+            color_scheme,
+            atom_scale: if show_atoms && params.show_atoms {
+                (params.atom_scale * atom_scale).max(0.001)
+            } else {
+                0.0
+            },
+            cylinder_bonds: show_bonds && params.show_bonds && !params.use_wireframe_lines,
+            bond_thickness: params.bond_scale * bond_scale,
+            wire_bonds: show_bonds && params.use_wireframe_lines,
+            ribbon: params.show_ribbon,
+            visible: true,
+        }
+    }
+
+    /// Style of ungrouped atoms under the global settings.
+    pub fn from_config(config: &VisualizationConfig) -> Self {
+        Self::resolve(
+            config.render_mode,
+            config.color_scheme,
+            config.atom_scale,
+            config.bond_scale,
+            config.show_atoms,
+            config.show_bonds,
+        )
+    }
+
+    /// Style of a group member. The global show-atoms/bonds toggles only
+    /// apply to ungrouped atoms; groups use their own visibility.
+    pub fn from_group(style: &GroupStyle) -> Self {
+        let mut resolved = Self::resolve(
+            style.render_mode,
+            style.color_scheme,
+            style.atom_scale,
+            style.bond_scale,
+            style.visible,
+            style.visible,
+        );
+        resolved.ribbon &= style.visible;
+        resolved.visible = style.visible;
+        resolved
+    }
+
+    /// Style of an explicitly hidden atom.
+    pub fn hidden() -> Self {
+        Self::from_group(&GroupStyle::hidden())
+    }
+}
+
+/// Per-atom styles resolved from `AtomGroups`, `HiddenAtoms` and `VisualizationConfig`.
+#[derive(Resource, Debug)]
+pub struct AtomStyles {
+    generation: u64,
+    membership: HashMap<u32, usize>,
+    hidden_generation: u64,
+    hidden: HashSet<u32>,
+    table: Vec<AtomStyle>,
+}
+
+impl Default for AtomStyles {
+    fn default() -> Self {
+        Self {
+            generation: 0,
+            membership: HashMap::new(),
+            hidden_generation: 0,
+            hidden: HashSet::new(),
+            table: vec![AtomStyle::from_config(&VisualizationConfig::default())],
+        }
+    }
+}
+
+impl AtomStyles {
+    /// Resolve against the current groups, hidden atoms and config; returns
+    /// whether anything changed.
+    pub fn update(
+        &mut self,
+        groups: &AtomGroups,
+        hidden: &HiddenAtoms,
+        config: &VisualizationConfig,
+    ) -> bool {
+        let table = groups.style_table(config);
+        let membership_changed = self.generation != groups.generation;
+        let hidden_changed = self.hidden_generation != hidden.generation;
+        if table == self.table && !membership_changed && !hidden_changed {
+            return false;
+        }
+        if membership_changed {
+            self.membership = groups.membership.clone();
+            self.generation = groups.generation;
+        }
+        if hidden_changed {
+            self.hidden = hidden.hidden.clone();
+            self.hidden_generation = hidden.generation;
+        }
+        self.table = table;
+        true
+    }
+
+    pub fn get(&self, atom_id: u32) -> AtomStyle {
+        if self.hidden.contains(&atom_id) {
+            return AtomStyle::hidden();
+        }
+        let slot = self.membership.get(&atom_id).map_or(0, |g| g + 1);
+        self.table[slot]
+    }
+
+    /// Whether any style in use (global or group) satisfies `f`.
+    pub fn any(&self, f: impl Fn(&AtomStyle) -> bool) -> bool {
+        self.table.iter().any(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::atom::Element;
+
+    fn group(name: &str, ids: &[u32], style: GroupStyle) -> AtomGroup {
+        AtomGroup {
+            name: name.into(),
+            query: None,
+            atom_ids: ids.to_vec(),
+            style,
+        }
+    }
+
+    #[test]
+    fn test_group_styles_override_global_config() {
+        let config = VisualizationConfig {
+            render_mode: RenderMode::Cartoon,
+            show_atoms: false,
+            show_bonds: false,
+            ..Default::default()
+        };
+        let mut groups = AtomGroups::default();
+        groups.add(group(
+            "ligand",
+            &[1, 2],
+            GroupStyle::new(RenderMode::BallAndStick),
+        ));
+        groups.add(group("solvent", &[2, 3], GroupStyle::hidden()));
+
+        let mut styles = AtomStyles::default();
+        let hidden = HiddenAtoms::default();
+        assert!(styles.update(&groups, &hidden, &config));
+        assert!(!styles.update(&groups, &hidden, &config));
+
+        let protein = styles.get(0);
+        assert!(protein.ribbon);
+        assert_eq!(protein.atom_scale, 0.0);
+
+        let ligand = styles.get(1);
+        assert!(ligand.cylinder_bonds);
+        assert!((ligand.atom_scale - 0.3).abs() < 1e-6);
+
+        // Atom 2 is in both groups; the later (hidden) one wins.
+        assert_eq!(groups.group_of(2), Some(1));
+        let hidden = styles.get(2);
+        assert_eq!(hidden.atom_scale, 0.0);
+        assert!(!hidden.cylinder_bonds && !hidden.wire_bonds && !hidden.ribbon);
+    }
+
+    #[test]
+    fn test_hidden_atoms_override_groups() {
+        let config = VisualizationConfig::default();
+        let mut groups = AtomGroups::default();
+        groups.add(group(
+            "ligand",
+            &[1, 2],
+            GroupStyle::new(RenderMode::BallAndStick),
+        ));
+        let mut hidden = HiddenAtoms::default();
+        let mut styles = AtomStyles::default();
+        styles.update(&groups, &hidden, &config);
+        assert!(styles.get(2).visible);
+
+        hidden.hide([2, 5]);
+        assert!(styles.update(&groups, &hidden, &config));
+        assert_eq!(styles.get(2), AtomStyle::hidden());
+        assert!(!styles.get(5).visible);
+        assert!(styles.get(1).cylinder_bonds);
+
+        hidden.show([2]);
+        styles.update(&groups, &hidden, &config);
+        assert!(styles.get(2).visible && !styles.get(5).visible);
+    }
+
+    #[test]
+    fn test_refresh_reevaluates_queries_and_drops_missing_ids() {
+        let atoms: Vec<AtomData> = (0..4)
+            .map(|i| AtomData::new(i, Element::C, 1, "ALA".into(), "A".into(), "CA".into()))
+            .collect();
+        let mut groups = AtomGroups::default();
+        groups.add(group("picked", &[1, 7], GroupStyle::hidden()));
+        groups.add(AtomGroup {
+            query: Some("index 2 to 3".into()),
+            ..group("query", &[], GroupStyle::new(RenderMode::Licorice))
+        });
+
+        let generation = groups.generation();
+        groups.refresh(&atoms, |q| {
+            assert_eq!(q, "index 2 to 3");
+            Ok(vec![2, 3])
+        });
+
+        assert!(groups.generation() > generation);
+        assert_eq!(groups.get(0).unwrap().atom_ids, vec![1]);
+        assert_eq!(groups.group_of(3), Some(1));
+        assert_eq!(groups.group_of(7), None);
+    }
+
+    #[test]
+    fn test_groups_json_roundtrip() {
+        let mut groups = AtomGroups::default();
+        groups.add(group(
+            "ligand",
+            &[4, 5],
+            GroupStyle::new(RenderMode::BallAndStick),
+        ));
+
+        let restored = AtomGroups::from_json(&groups.to_json().unwrap()).unwrap();
+        assert_eq!(restored.get(0), groups.get(0));
+        assert_eq!(restored.group_of(5), Some(0));
+    }
+
+    #[test]
+    fn test_groups_file_roundtrip() {
+        let mut groups = AtomGroups::default();
+        groups.add(group("solvent", &[7], GroupStyle::hidden()));
+        groups.add(AtomGroup {
+            query: Some("resname LIG".into()),
+            ..group("ligand", &[4, 5], GroupStyle::new(RenderMode::Licorice))
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("groups.json");
+        groups.save(&path).unwrap();
+
+        let mut restored = AtomGroups::default();
+        restored.add(group("stale", &[1], GroupStyle::hidden()));
+        let generation = restored.generation();
+        restored.load(&path).unwrap();
+        assert!(restored.iter().eq(groups.iter()));
+        assert_eq!(restored.group_of(5), Some(1));
+        assert_eq!(restored.group_of(1), None);
+        assert!(restored.generation() > generation);
+
+        assert!(restored.load(&dir.path().join("missing.json")).is_err());
+        assert_eq!(restored.len(), 2);
+    }
+}
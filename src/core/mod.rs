@@ -6,8 +6,11 @@
 
 pub mod atom;
 pub mod bond;
+pub mod cell;
+pub mod groups;
 pub mod molecule;
 pub mod secondary_structure;
+pub mod symmetry;
 pub mod trajectory;
 pub mod visualization;
 
@@ -67,8 +70,8 @@ pub struct SimulationMetadata {
     pub num_frames: usize,
     /// Time step in femtoseconds
     pub time_step: f32,
-    /// Box dimensions (if periodic)
-    pub box_size: Option<[f32; 3]>,
+    /// Periodic cell (if any)
+    pub cell: Option<cell::UnitCell>,
     /// File format
     pub format: String,
 }
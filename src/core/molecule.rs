@@ -105,6 +105,47 @@ impl MoleculeType {
         }
     }
 
+    /// Classify one residue by its exact name (PDB, AMBER, CHARMM and GROMACS conventions).
+    ///
+    /// Unlike [`MoleculeType::from_name`], which matches substrings of molecule
+    /// names, this only recognises whole residue names, so `LYS` is not an ion.
+    pub fn from_residue_name(name: &str) -> Self {
+        let name = name.trim().to_uppercase();
+        let name = name.as_str();
+
+        if WATER_RESIDUES.contains(&name) {
+            MoleculeType::Water
+        } else if ION_RESIDUES.contains(&name) {
+            MoleculeType::Ion
+        } else if AminoAcid::from_code3(name) != AminoAcid::Unknown
+            || PROTEIN_RESIDUE_VARIANTS.contains(&name)
+        {
+            MoleculeType::Protein
+        } else if NUCLEIC_RESIDUES.contains(&name) {
+            MoleculeType::NucleicAcid
+        } else if LIPID_RESIDUES.contains(&name) {
+            MoleculeType::Lipid
+        } else {
+            MoleculeType::Unknown
+        }
+    }
+
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            MoleculeType::Protein => "Protein",
+            MoleculeType::NucleicAcid => "Nucleic acid",
+            MoleculeType::Ligand => "Ligand",
+            MoleculeType::Water => "Water",
+            MoleculeType::Ion => "Ion",
+            MoleculeType::Lipid => "Lipid",
+            MoleculeType::Carbohydrate => "Carbohydrate",
+            MoleculeType::SmallMolecule => "Small molecule",
+            MoleculeType::Polymer => "Polymer",
+            MoleculeType::Unknown => "Unknown",
+        }
+    }
+
     /// Check if this is a protein-like molecule
     pub fn is_protein(&self) -> bool {
         matches!(self, MoleculeType::Protein | MoleculeType::SmallMolecule)
@@ -121,6 +162,37 @@ impl MoleculeType {
     }
 }
 
+/// Water residue names across common force fields.
+const WATER_RESIDUES: &[&str] = &[
+    "HOH", "WAT", "H2O", "SOL", "TIP", "TIP3", "TIP4", "TIP5", "T3P", "T4P", "T4E", "SPC", "SPCE",
+    "DOD",
+];
+
+/// Monatomic ion residue names.
+const ION_RESIDUES: &[&str] = &[
+    "NA", "NA+", "SOD", "CL", "CL-", "CLA", "K", "K+", "POT", "MG", "MG2", "CA", "CAL", "ZN",
+    "ZN2", "FE", "FE2", "MN", "CU", "CO", "NI", "CD", "LI", "RB", "CS", "BR", "IOD", "F",
+];
+
+/// Protonation states and modified residues not covered by [`AminoAcid`].
+const PROTEIN_RESIDUE_VARIANTS: &[&str] = &[
+    "HID", "HIE", "HIP", "HSD", "HSE", "HSP", "CYX", "CYM", "ASH", "GLH", "LYN", "ARN", "MSE",
+    "SEC", "PYL", "ACE", "NME", "NMA",
+];
+
+/// DNA and RNA nucleotide residue names.
+const NUCLEIC_RESIDUES: &[&str] = &[
+    "A", "C", "G", "U", "T", "DA", "DC", "DG", "DT", "DU", "RA", "RC", "RG", "RU", "ADE", "CYT",
+    "GUA", "THY", "URA", "DA5", "DA3", "DC5", "DC3", "DG5", "DG3", "DT5", "DT3", "RA5", "RA3",
+    "RC5", "RC3", "RG5", "RG3", "RU5", "RU3",
+];
+
+/// Common lipid residue names (CHARMM / Lipid21).
+const LIPID_RESIDUES: &[&str] = &[
+    "POPC", "POPE", "POPG", "POPS", "DPPC", "DOPC", "DOPE", "DMPC", "DLPC", "CHL1", "CHOL", "PA",
+    "PC", "PE", "OL",
+];
+
 /// Secondary structure classification for proteins
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
 #[reflect(Debug, PartialEq, Hash)]
@@ -307,6 +379,28 @@ mod tests {
         assert_eq!(MoleculeType::from_name("LIG"), MoleculeType::Ligand);
     }
 
+    #[test]
+    fn test_molecule_type_from_residue_name() {
+        assert_eq!(MoleculeType::from_residue_name("TIP3"), MoleculeType::Water);
+        assert_eq!(MoleculeType::from_residue_name("SOD"), MoleculeType::Ion);
+        assert_eq!(
+            MoleculeType::from_residue_name("LYS"),
+            MoleculeType::Protein
+        );
+        assert_eq!(
+            MoleculeType::from_residue_name("HSD"),
+            MoleculeType::Protein
+        );
+        assert_eq!(
+            MoleculeType::from_residue_name("DG"),
+            MoleculeType::NucleicAcid
+        );
+        assert_eq!(
+            MoleculeType::from_residue_name("LIG"),
+            MoleculeType::Unknown
+        );
+    }
+
     #[test]
     fn test_amino_acid_codes() {
         assert_eq!(AminoAcid::Alanine.code3(), "ALA");
@@ -1,10 +1,13 @@
 //! Protein backbone extraction and secondary-structure assignment.
 //!
-//! Uses DSSP (Kabsch-Sander hydrogen-bond model via [`pdbrust`]) when backbone
-//! atoms (N, CA, C, O) are available; falls back to a distance-based heuristic
-//! for CA-only or incomplete structures.
+//! Curated annotations from the input file (PDB HELIX/SHEET, mmCIF
+//! `_struct_conf` / `_struct_sheet_range`) take precedence. Otherwise DSSP
+//! (Kabsch-Sander hydrogen-bond model via [`pdbrust`]) is used when backbone
+//! atoms (N, CA, C, O) are available, with a distance-based heuristic for
+//! CA-only or incomplete structures.
 
 use crate::analysis::dssp;
+use crate::analysis::molecules::MoleculeTopology;
 use crate::core::atom::AtomData;
 use crate::core::molecule::SecondaryStructure;
 use bevy::prelude::*;
@@ -14,6 +17,33 @@ use tracing::debug;
 /// Minimum CA atoms required before cartoon modes are offered in the UI.
 pub const MIN_CARTOON_RESIDUES: usize = 20;
 
+/// Secondary structure read from the input file, keyed by
+/// (chain id, residue id) as stored in [`AtomData`].
+pub type SecondaryStructureAnnotations = HashMap<(String, u32), SecondaryStructure>;
+
+/// Mark residues `first..=last` of `chain` in `annotations`.
+pub fn annotate_residue_range(
+    annotations: &mut SecondaryStructureAnnotations,
+    chain: &str,
+    first: u32,
+    last: u32,
+    structure: SecondaryStructure,
+) {
+    for residue_id in first.min(last)..=first.max(last) {
+        annotations.insert((chain.to_string(), residue_id), structure);
+    }
+}
+
+/// Map a PDB helix class (HELIX columns 39-40, mmCIF
+/// `pdbx_PDB_helix_class`) to a secondary structure.
+pub fn helix_class_structure(class: u32) -> SecondaryStructure {
+    match class {
+        3 => SecondaryStructure::PiHelix,
+        5 => SecondaryStructure::ThreeTenHelix,
+        _ => SecondaryStructure::AlphaHelix,
+    }
+}
+
 /// One residue along a protein backbone (CA trace).
 #[derive(Debug, Clone)]
 pub struct BackboneResidue {
@@ -38,20 +68,38 @@ impl ProteinBackbone {
         self.ca_count = 0;
         self.cartoon_available = false;
     }
+
+    /// Secondary structure indexed by topology residue (`Unknown` for residues
+    /// without a CA on the backbone), for per-atom coloring.
+    pub fn residue_secondary_structure(
+        &self,
+        topology: &MoleculeTopology,
+    ) -> Vec<SecondaryStructure> {
+        let mut per_residue = vec![SecondaryStructure::Unknown; topology.residues().len()];
+        for residue in &self.residues {
+            if let Some(r) = topology.residue_index(residue.ca_atom_id) {
+                per_residue[r] = residue.secondary_structure;
+            }
+        }
+        per_residue
+    }
 }
 
-/// Extract CA backbone atoms and assign secondary structure (DSSP or heuristic).
+/// Extract CA backbone atoms and assign secondary structure (file
+/// annotations, DSSP or heuristic).
 pub fn build_protein_backbone(
     atom_data: &[AtomData],
-    positions: &HashMap<u32, Vec3>,
+    positions: &[Vec3],
+    annotations: &SecondaryStructureAnnotations,
 ) -> ProteinBackbone {
-    let mut ca_atoms: Vec<&AtomData> = atom_data
+    let mut ca_atoms: Vec<(&AtomData, Vec3)> = atom_data
         .iter()
-        .filter(|a| a.name.eq_ignore_ascii_case("CA"))
-        .filter(|a| positions.contains_key(&a.id))
+        .zip(positions)
+        .filter(|(a, _)| a.name.eq_ignore_ascii_case("CA"))
+        .map(|(a, p)| (a, *p))
         .collect();
 
-    ca_atoms.sort_by(|a, b| {
+    ca_atoms.sort_by(|(a, _), (b, _)| {
         a.chain_id
             .cmp(&b.chain_id)
             .then(a.residue_id.cmp(&b.residue_id))
@@ -60,16 +108,18 @@ pub fn build_protein_backbone(
     let ca_count = ca_atoms.len();
     let mut residues: Vec<BackboneResidue> = ca_atoms
         .into_iter()
-        .map(|a| BackboneResidue {
+        .map(|(a, position)| BackboneResidue {
             residue_id: a.residue_id,
             ca_atom_id: a.id,
             chain_id: a.chain_id.clone(),
-            position: positions[&a.id],
+            position,
             secondary_structure: SecondaryStructure::Coil,
         })
         .collect();
 
-    apply_secondary_structure(atom_data, positions, &mut residues);
+    if !apply_file_annotations(annotations, &mut residues) {
+        apply_secondary_structure(atom_data, positions, &mut residues);
+    }
 
     ProteinBackbone {
         ca_count,
@@ -78,10 +128,30 @@ pub fn build_protein_backbone(
     }
 }
 
+/// Use the file's annotations when they cover any backbone residue; residues
+/// outside every annotated range are coil.
+fn apply_file_annotations(
+    annotations: &SecondaryStructureAnnotations,
+    residues: &mut [BackboneResidue],
+) -> bool {
+    let mut matched = 0;
+    for residue in residues.iter_mut() {
+        let key = (residue.chain_id.clone(), residue.residue_id);
+        if let Some(ss) = annotations.get(&key) {
+            residue.secondary_structure = *ss;
+            matched += 1;
+        }
+    }
+    if matched > 0 {
+        debug!("Using file secondary structure annotations for {matched} residues");
+    }
+    matched > 0
+}
+
 /// Assign secondary structure via DSSP, falling back to a distance heuristic.
 fn apply_secondary_structure(
     atom_data: &[AtomData],
-    positions: &HashMap<u32, Vec3>,
+    positions: &[Vec3],
     residues: &mut [BackboneResidue],
 ) {
     let dssp_result = dssp::assign_dssp(atom_data, positions);
@@ -179,8 +249,8 @@ mod tests {
             ca(1, 2, Vec3::X * 3.8),
             ca(2, 3, Vec3::X * 7.6),
         ];
-        let positions: HashMap<u32, Vec3> = atoms.iter().map(|a| (a.id, a.position)).collect();
-        let backbone = build_protein_backbone(&atoms, &positions);
+        let positions: Vec<Vec3> = atoms.iter().map(|a| a.position).collect();
+        let backbone = build_protein_backbone(&atoms, &positions, &HashMap::new());
         assert_eq!(backbone.ca_count, 3);
         assert!(!backbone.cartoon_available);
     }
@@ -188,14 +258,35 @@ mod tests {
     #[test]
     fn test_cartoon_available_threshold() {
         let mut atoms = Vec::new();
-        let mut positions = HashMap::new();
+        let mut positions = Vec::new();
         for i in 0..MIN_CARTOON_RESIDUES {
             let id = i as u32;
             let pos = Vec3::new(i as f32 * 3.8, 0.0, 0.0);
             atoms.push(ca(id, i as u32 + 1, pos));
-            positions.insert(id, pos);
+            positions.push(pos);
         }
-        let backbone = build_protein_backbone(&atoms, &positions);
+        let backbone = build_protein_backbone(&atoms, &positions, &HashMap::new());
         assert!(backbone.cartoon_available);
     }
+
+    #[test]
+    fn test_file_annotations_take_precedence() {
+        let atoms: Vec<AtomData> = (0..6)
+            .map(|i| ca(i, i + 1, Vec3::new(i as f32 * 3.8, 0.0, 0.0)))
+            .collect();
+        let positions: Vec<Vec3> = atoms.iter().map(|a| a.position).collect();
+        let mut annotations = SecondaryStructureAnnotations::new();
+        annotate_residue_range(&mut annotations, "A", 2, 4, helix_class_structure(5));
+
+        let backbone = build_protein_backbone(&atoms, &positions, &annotations);
+        let ss: Vec<_> = backbone
+            .residues
+            .iter()
+            .map(|r| r.secondary_structure)
+            .collect();
+        assert_eq!(ss[0], SecondaryStructure::Coil);
+        assert_eq!(ss[1], SecondaryStructure::ThreeTenHelix);
+        assert_eq!(ss[3], SecondaryStructure::ThreeTenHelix);
+        assert_eq!(ss[4], SecondaryStructure::Coil);
+    }
 }
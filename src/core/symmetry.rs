@@ -0,0 +1,1029 @@
+//! Crystal symmetry and biological assemblies
+//!
+//! Structure files describe a deposited asymmetric unit plus the operations
+//! that rebuild the rest: PDB `REMARK 350 BIOMT` / mmCIF
+//! `_pdbx_struct_assembly_gen` for biological assemblies, and the space group
+//! (CRYST1, `REMARK 290 SMTRY`, `_space_group_symop`) for the crystal
+//! lattice. Operators are stored in Cartesian Å so they apply directly to
+//! frame positions.
+//!
+//! [`expand_structure`] turns a list of [`StructureCopy`] entries into new
+//! atoms and bonds; [`ExpandedStructure::expand_frame`] maps frames of the
+//! original structure onto the expanded atom ids.
+
+use crate::core::atom::AtomData;
+use crate::core::bond::BondData;
+use crate::core::cell::UnitCell;
+use crate::core::secondary_structure::SecondaryStructureAnnotations;
+use crate::core::trajectory::FrameData;
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Tolerance for comparing operator components.
+const OPERATOR_TOLERANCE: f32 = 1e-4;
+
+/// Chain ids handed out to copies, in order.
+const CHAIN_ID_POOL: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Rigid operation `x' = rotation · x + translation` in Cartesian Å.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymmetryOperator {
+    /// Rotation (or rotoinversion) part
+    pub rotation: Mat3,
+    /// Translation part (Å)
+    pub translation: Vec3,
+}
+
+impl Default for SymmetryOperator {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl SymmetryOperator {
+    /// The identity operation.
+    pub const IDENTITY: Self = Self {
+        rotation: Mat3::IDENTITY,
+        translation: Vec3::ZERO,
+    };
+
+    /// Create an operator from its rotation and translation.
+    pub fn new(rotation: Mat3, translation: Vec3) -> Self {
+        Self {
+            rotation,
+            translation,
+        }
+    }
+
+    /// Build an operator from three `[r1, r2, r3, t]` rows (BIOMT, SMTRY).
+    pub fn from_rows(rows: [[f32; 4]; 3]) -> Self {
+        let row = |i: usize| Vec3::new(rows[i][0], rows[i][1], rows[i][2]);
+        Self {
+            rotation: Mat3::from_cols(row(0), row(1), row(2)).transpose(),
+            translation: Vec3::new(rows[0][3], rows[1][3], rows[2][3]),
+        }
+    }
+
+    /// Convert an operator given in fractional coordinates to Cartesian.
+    pub fn from_fractional(rotation: Mat3, translation: Vec3, cell: &UnitCell) -> Self {
+        let m = cell.matrix();
+        Self {
+            rotation: m * rotation * m.inverse(),
+            translation: m * translation,
+        }
+    }
+
+    /// Apply the operator to a position.
+    pub fn apply(&self, position: Vec3) -> Vec3 {
+        self.rotation * position + self.translation
+    }
+
+    /// Apply only the rotation (velocities, forces).
+    pub fn rotate(&self, vector: Vec3) -> Vec3 {
+        self.rotation * vector
+    }
+
+    /// The operator that applies `inner` first, then `self`.
+    pub fn compose(&self, inner: &SymmetryOperator) -> Self {
+        Self {
+            rotation: self.rotation * inner.rotation,
+            translation: self.rotation * inner.translation + self.translation,
+        }
+    }
+
+    /// Same operator shifted by a Cartesian translation.
+    pub fn translated(&self, shift: Vec3) -> Self {
+        Self {
+            rotation: self.rotation,
+            translation: self.translation + shift,
+        }
+    }
+
+    /// Whether this is the identity (within a small tolerance).
+    pub fn is_identity(&self) -> bool {
+        self.rotation
+            .abs_diff_eq(Mat3::IDENTITY, OPERATOR_TOLERANCE)
+            && self
+                .translation
+                .abs_diff_eq(Vec3::ZERO, OPERATOR_TOLERANCE * 10.0)
+    }
+}
+
+/// Parse a symmetry operation in `x,y,z` notation (e.g. `-x+1/2,y,-z`).
+///
+/// Returns the fractional rotation and translation. Coefficients may be
+/// integers, decimals or fractions; letters are case-insensitive.
+pub fn parse_xyz_operator(text: &str) -> Option<(Mat3, Vec3)> {
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\'' && *c != '"')
+        .collect();
+    let parts: Vec<&str> = text.split(',').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+
+    let mut rows = [Vec3::ZERO; 3];
+    let mut translation = Vec3::ZERO;
+    for (i, part) in parts.iter().enumerate() {
+        let (row, shift) = parse_xyz_component(part)?;
+        rows[i] = row;
+        translation[i] = shift;
+    }
+    Some((
+        Mat3::from_cols(rows[0], rows[1], rows[2]).transpose(),
+        translation,
+    ))
+}
+
+/// One component of an `x,y,z` operation: coefficients of x, y, z and a constant.
+fn parse_xyz_component(part: &str) -> Option<(Vec3, f32)> {
+    if part.is_empty() {
+        return None;
+    }
+
+    let mut row = Vec3::ZERO;
+    let mut constant = 0.0;
+    let mut term = String::new();
+    let mut sign = 1.0;
+
+    let mut flush = |term: &mut String, sign: f32| -> Option<()> {
+        if term.is_empty() {
+            return Some(());
+        }
+        let lower = term.to_ascii_lowercase();
+        let axis = lower.chars().last().and_then(|c| match c {
+            'x' => Some(0),
+            'y' => Some(1),
+            'z' => Some(2),
+            _ => None,
+        });
+        match axis {
+            Some(axis) => {
+                let coefficient = lower[..lower.len() - 1].trim_end_matches('*');
+                let value = if coefficient.is_empty() {
+                    1.0
+                } else {
+                    parse_fraction(coefficient)?
+                };
+                row[axis] += sign * value;
+            }
+            None => constant += sign * parse_fraction(&lower)?,
+        }
+        term.clear();
+        Some(())
+    };
+
+    for c in part.chars() {
+        match c {
+            '+' | '-' => {
+                flush(&mut term, sign)?;
+                sign = if c == '-' { -1.0 } else { 1.0 };
+            }
+            _ => term.push(c),
+        }
+    }
+    flush(&mut term, sign)?;
+    Some((row, constant))
+}
+
+/// Parse `1/2`, `0.5` or `1`.
+fn parse_fraction(text: &str) -> Option<f32> {
+    match text.split_once('/') {
+        Some((num, den)) => {
+            let den: f32 = den.parse().ok()?;
+            (den != 0.0).then_some(num.parse::<f32>().ok()? / den)
+        }
+        None => text.parse().ok(),
+    }
+}
+
+/// Expand an mmCIF `oper_expression` into operator id sequences.
+///
+/// `1,2,5` and `1-3` list operators; parenthesised groups such as
+/// `(X0)(1-60)` form a product. Each returned sequence is ordered left to
+/// right; the rightmost operator is applied first.
+pub fn expand_oper_expression(expression: &str) -> Vec<Vec<String>> {
+    let expression: String = expression.chars().filter(|c| !c.is_whitespace()).collect();
+    let groups: Vec<&str> = if expression.contains('(') {
+        expression
+            .split(['(', ')'])
+            .filter(|g| !g.is_empty())
+            .collect()
+    } else {
+        vec![expression.as_str()]
+    };
+
+    let mut sequences: Vec<Vec<String>> = vec![Vec::new()];
+    for group in groups {
+        let ids = expand_oper_group(group);
+        if ids.is_empty() {
+            continue;
+        }
+        sequences = sequences
+            .iter()
+            .flat_map(|prefix| {
+                ids.iter().map(move |id| {
+                    let mut sequence = prefix.clone();
+                    sequence.push(id.clone());
+                    sequence
+                })
+            })
+            .collect();
+    }
+    sequences.retain(|s| !s.is_empty());
+    sequences
+}
+
+/// Ids in one `1,3-5` group.
+fn expand_oper_group(group: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    for item in group.split(',').filter(|i| !i.is_empty()) {
+        let range = item
+            .split_once('-')
+            .and_then(|(a, b)| Some((a.parse::<u32>().ok()?, b.parse::<u32>().ok()?)));
+        match range {
+            Some((first, last)) if first <= last => {
+                ids.extend((first..=last).map(|i| i.to_string()));
+            }
+            _ => ids.push(item.to_string()),
+        }
+    }
+    ids
+}
+
+/// Operators applied to a set of chains.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssemblyGenerator {
+    /// Author chain ids (as in [`AtomData::chain_id`]) the operators act on
+    pub chains: Vec<String>,
+    /// Operators, one copy of the chains each
+    pub operators: Vec<SymmetryOperator>,
+}
+
+/// A biological assembly built from the asymmetric unit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BiologicalAssembly {
+    /// Assembly id (`BIOMOLECULE: 1`, `_pdbx_struct_assembly.id`)
+    pub id: String,
+    /// Description such as "author_defined_assembly" or "DIMERIC"
+    pub details: String,
+    /// Chain sets and their operators
+    pub generators: Vec<AssemblyGenerator>,
+}
+
+impl BiologicalAssembly {
+    /// Chain copies making up the assembly, in file order.
+    pub fn copies(&self) -> Vec<StructureCopy> {
+        self.generators
+            .iter()
+            .flat_map(|generator| {
+                generator.operators.iter().map(|operator| StructureCopy {
+                    operator: *operator,
+                    chains: Some(generator.chains.clone()),
+                })
+            })
+            .collect()
+    }
+
+    /// Total number of chain copies.
+    pub fn num_copies(&self) -> usize {
+        self.generators
+            .iter()
+            .map(|g| g.chains.len() * g.operators.len())
+            .sum()
+    }
+
+    /// Short label for menus, e.g. "1 (DIMERIC, 2 chains)".
+    pub fn label(&self) -> String {
+        let chains = self.num_copies();
+        let plural = if chains == 1 { "chain" } else { "chains" };
+        if self.details.is_empty() {
+            format!("{} ({chains} {plural})", self.id)
+        } else {
+            format!("{} ({}, {chains} {plural})", self.id, self.details)
+        }
+    }
+}
+
+/// Symmetry information read from a structure file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StructureSymmetry {
+    /// Hermann-Mauguin space group symbol as written in the file
+    pub space_group: Option<String>,
+    /// Crystallographic operators listed in the file (Cartesian)
+    pub operators: Vec<SymmetryOperator>,
+    /// Biological assemblies
+    pub assemblies: Vec<BiologicalAssembly>,
+}
+
+impl StructureSymmetry {
+    /// Whether the file described any symmetry at all.
+    pub fn is_empty(&self) -> bool {
+        self.space_group.is_none() && self.operators.is_empty() && self.assemblies.is_empty()
+    }
+
+    /// Look up an assembly by id.
+    pub fn assembly(&self, id: &str) -> Option<&BiologicalAssembly> {
+        self.assemblies.iter().find(|a| a.id == id)
+    }
+
+    /// Space group operators in Cartesian coordinates for `cell`.
+    ///
+    /// Operators listed in the file win; otherwise the space group symbol is
+    /// looked up in the built-in table.
+    pub fn crystal_operators(&self, cell: &UnitCell) -> Option<Vec<SymmetryOperator>> {
+        if !self.operators.is_empty() {
+            return Some(self.operators.clone());
+        }
+        let symbol = self.space_group.as_deref()?;
+        let operators = space_group_operators(symbol)?;
+        Some(
+            operators
+                .into_iter()
+                .map(|(rotation, translation)| {
+                    SymmetryOperator::from_fractional(rotation, translation, cell)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Centring translations of a lattice type.
+fn centring(lattice: char) -> &'static [[f32; 3]] {
+    const THIRD: f32 = 1.0 / 3.0;
+    match lattice {
+        'C' => &[[0.0; 3], [0.5, 0.5, 0.0]],
+        'I' => &[[0.0; 3], [0.5, 0.5, 0.5]],
+        'R' | 'H' => &[
+            [0.0; 3],
+            [2.0 * THIRD, THIRD, THIRD],
+            [THIRD, 2.0 * THIRD, 2.0 * THIRD],
+        ],
+        _ => &[[0.0; 3]],
+    }
+}
+
+/// General positions (without centring) of the supported space groups, in
+/// the International Tables standard setting (hexagonal axes for R3).
+fn space_group_table(symbol: &str) -> Option<(char, &'static [&'static str])> {
+    const P222: &[&str] = &["x,y,z", "-x,-y,z", "-x,y,-z", "x,-y,-z"];
+    const P4: &[&str] = &["x,y,z", "-x,-y,z", "-y,x,z", "y,-x,z"];
+    const P422: &[&str] = &[
+        "x,y,z", "-x,-y,z", "-y,x,z", "y,-x,z", "-x,y,-z", "x,-y,-z", "y,x,-z", "-y,-x,-z",
+    ];
+    const P3: &[&str] = &["x,y,z", "-y,x-y,z", "-x+y,-x,z"];
+    const P212121: &[&str] = &[
+        "x,y,z",
+        "-x+1/2,-y,z+1/2",
+        "-x,y+1/2,-z+1/2",
+        "x+1/2,-y+1/2,-z",
+    ];
+
+    let entry: (char, &'static [&'static str]) = match symbol {
+        "P1" => ('P', &["x,y,z"]),
+        "P-1" => ('P', &["x,y,z", "-x,-y,-z"]),
+        "P2" | "P121" => ('P', &["x,y,z", "-x,y,-z"]),
+        "P21" | "P1211" => ('P', &["x,y,z", "-x,y+1/2,-z"]),
+        "C2" | "C121" => ('C', &["x,y,z", "-x,y,-z"]),
+        "P222" => ('P', P222),
+        "P2221" => ('P', &["x,y,z", "-x,-y,z+1/2", "-x,y,-z+1/2", "x,-y,-z"]),
+        "P21212" => (
+            'P',
+            &["x,y,z", "-x,-y,z", "-x+1/2,y+1/2,-z", "x+1/2,-y+1/2,-z"],
+        ),
+        "P212121" => ('P', P212121),
+        "C2221" => ('C', &["x,y,z", "-x,-y,z+1/2", "-x,y,-z+1/2", "x,-y,-z"]),
+        "C222" => ('C', P222),
+        "I222" => ('I', P222),
+        "I212121" => ('I', P212121),
+        "P4" => ('P', P4),
+        "P41" => ('P', &["x,y,z", "-x,-y,z+1/2", "-y,x,z+1/4", "y,-x,z+3/4"]),
+        "P43" => ('P', &["x,y,z", "-x,-y,z+1/2", "-y,x,z+3/4", "y,-x,z+1/4"]),
+        "I4" => ('I', P4),
+        "P422" => ('P', P422),
+        "P4212" => (
+            'P',
+            &[
+                "x,y,z",
+                "-x,-y,z",
+                "-y+1/2,x+1/2,z",
+                "y+1/2,-x+1/2,z",
+                "-x+1/2,y+1/2,-z",
+                "x+1/2,-y+1/2,-z",
+                "y,x,-z",
+                "-y,-x,-z",
+            ],
+        ),
+        "P41212" => (
+            'P',
+            &[
+                "x,y,z",
+                "-x,-y,z+1/2",
+                "-y+1/2,x+1/2,z+1/4",
+                "y+1/2,-x+1/2,z+3/4",
+                "-x+1/2,y+1/2,-z+1/4",
+                "x+1/2,-y+1/2,-z+3/4",
+                "y,x,-z",
+                "-y,-x,-z+1/2",
+            ],
+        ),
+        "P43212" => (
+            'P',
+            &[
+                "x,y,z",
+                "-x,-y,z+1/2",
+                "-y+1/2,x+1/2,z+3/4",
+                "y+1/2,-x+1/2,z+1/4",
+                "-x+1/2,y+1/2,-z+3/4",
+                "x+1/2,-y+1/2,-z+1/4",
+                "y,x,-z",
+                "-y,-x,-z+1/2",
+            ],
+        ),
+        "I422" => ('I', P422),
+        "P3" => ('P', P3),
+        "P31" => ('P', &["x,y,z", "-y,x-y,z+1/3", "-x+y,-x,z+2/3"]),
+        "P32" => ('P', &["x,y,z", "-y,x-y,z+2/3", "-x+y,-x,z+1/3"]),
+        "R3" | "H3" => ('R', P3),
+        "P321" => (
+            'P',
+            &[
+                "x,y,z",
+                "-y,x-y,z",
+                "-x+y,-x,z",
+                "y,x,-z",
+                "x-y,-y,-z",
+                "-x,-x+y,-z",
+            ],
+        ),
+        "P3121" => (
+            'P',
+            &[
+                "x,y,z",
+                "-y,x-y,z+1/3",
+                "-x+y,-x,z+2/3",
+                "y,x,-z",
+                "x-y,-y,-z+2/3",
+                "-x,-x+y,-z+1/3",
+            ],
+        ),
+        "P3221" => (
+            'P',
+            &[
+                "x,y,z",
+                "-y,x-y,z+2/3",
+                "-x+y,-x,z+1/3",
+                "y,x,-z",
+                "x-y,-y,-z+1/3",
+                "-x,-x+y,-z+2/3",
+            ],
+        ),
+        "P6" => (
+            'P',
+            &[
+                "x,y,z",
+                "-y,x-y,z",
+                "-x+y,-x,z",
+                "-x,-y,z",
+                "y,-x+y,z",
+                "x-y,x,z",
+            ],
+        ),
+        "P61" => (
+            'P',
+            &[
+                "x,y,z",
+                "-y,x-y,z+1/3",
+                "-x+y,-x,z+2/3",
+                "-x,-y,z+1/2",
+                "y,-x+y,z+5/6",
+                "x-y,x,z+1/6",
+            ],
+        ),
+        "P65" => (
+            'P',
+            &[
+                "x,y,z",
+                "-y,x-y,z+2/3",
+                "-x+y,-x,z+1/3",
+                "-x,-y,z+1/2",
+                "y,-x+y,z+1/6",
+                "x-y,x,z+5/6",
+            ],
+        ),
+        "P63" => (
+            'P',
+            &[
+                "x,y,z",
+                "-y,x-y,z",
+                "-x+y,-x,z",
+                "-x,-y,z+1/2",
+                "y,-x+y,z+1/2",
+                "x-y,x,z+1/2",
+            ],
+        ),
+        "P23" => (
+            'P',
+            &[
+                "x,y,z", "-x,-y,z", "-x,y,-z", "x,-y,-z", "z,x,y", "z,-x,-y", "-z,-x,y", "-z,x,-y",
+                "y,z,x", "-y,z,-x", "y,-z,-x", "-y,-z,x",
+            ],
+        ),
+        "P213" => (
+            'P',
+            &[
+                "x,y,z",
+                "-x+1/2,-y,z+1/2",
+                "-x,y+1/2,-z+1/2",
+                "x+1/2,-y+1/2,-z",
+                "z,x,y",
+                "z+1/2,-x+1/2,-y",
+                "-z+1/2,-x,y+1/2",
+                "-z,x+1/2,-y+1/2",
+                "y,z,x",
+                "-y,z+1/2,-x+1/2",
+                "y+1/2,-z+1/2,-x",
+                "-y+1/2,-z,x+1/2",
+            ],
+        ),
+        _ => return None,
+    };
+    Some(entry)
+}
+
+/// Fractional operators (rotation, translation) of a space group.
+///
+/// The symbol is matched with spaces removed, so `P 1 21 1` and `P21` both
+/// resolve. Returns `None` for groups outside the built-in table.
+pub fn space_group_operators(symbol: &str) -> Option<Vec<(Mat3, Vec3)>> {
+    let normalized: String = symbol
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    let (lattice, operations) = space_group_table(&normalized)?;
+
+    let mut operators = Vec::new();
+    for shift in centring(lattice) {
+        for operation in operations {
+            let (rotation, translation) = parse_xyz_operator(operation)?;
+            operators.push((rotation, translation + Vec3::from_array(*shift)));
+        }
+    }
+    Some(operators)
+}
+
+/// Crystal neighbours of the asymmetric unit within `radius` Å.
+///
+/// Every space group operator is combined with the lattice translations
+/// that can bring its image within reach; an image is kept when any of its
+/// atoms lies within `radius` of an atom in `positions`. The identity is
+/// not returned.
+pub fn symmetry_mate_operators(
+    operators: &[SymmetryOperator],
+    cell: &UnitCell,
+    positions: &[Vec3],
+    radius: f32,
+) -> Vec<SymmetryOperator> {
+    if positions.is_empty() || !cell.is_periodic() {
+        return Vec::new();
+    }
+
+    let centre = positions.iter().copied().sum::<Vec3>() / positions.len() as f32;
+    let extent = positions
+        .iter()
+        .map(|p| p.distance(centre))
+        .fold(0.0, f32::max);
+    let reach = 2.0 * extent + radius;
+
+    let matrix = cell.matrix();
+    let inverse = matrix.inverse();
+    // |(M⁻¹ v)_i| ≤ |row_i(M⁻¹)| |v| bounds the lattice steps per axis.
+    let row_norms = Vec3::new(
+        inverse.row(0).length(),
+        inverse.row(1).length(),
+        inverse.row(2).length(),
+    );
+
+    let grid = NeighbourGrid::new(positions, radius.max(1.0));
+    let mut mates = Vec::new();
+    for operator in operators {
+        let offset = inverse * (centre - operator.apply(centre));
+        let low = (offset - row_norms * reach).ceil().as_ivec3();
+        let high = (offset + row_norms * reach).floor().as_ivec3();
+        for i in low.x..=high.x {
+            for j in low.y..=high.y {
+                for k in low.z..=high.z {
+                    let shift = matrix * Vec3::new(i as f32, j as f32, k as f32);
+                    let candidate = operator.translated(shift);
+                    if candidate.is_identity() {
+                        continue;
+                    }
+                    if (candidate.apply(centre) - centre).length() > reach {
+                        continue;
+                    }
+                    let touches = positions
+                        .iter()
+                        .any(|p| grid.any_within(candidate.apply(*p), radius));
+                    if touches {
+                        mates.push(candidate);
+                    }
+                }
+            }
+        }
+    }
+    mates
+}
+
+/// Uniform grid over positions for radius queries.
+struct NeighbourGrid<'a> {
+    positions: &'a [Vec3],
+    cell_size: f32,
+    cells: HashMap<IVec3, Vec<usize>>,
+}
+
+impl<'a> NeighbourGrid<'a> {
+    fn new(positions: &'a [Vec3], cell_size: f32) -> Self {
+        let mut cells: HashMap<IVec3, Vec<usize>> = HashMap::new();
+        for (i, p) in positions.iter().enumerate() {
+            cells
+                .entry((*p / cell_size).floor().as_ivec3())
+                .or_default()
+                .push(i);
+        }
+        Self {
+            positions,
+            cell_size,
+            cells,
+        }
+    }
+
+    fn any_within(&self, point: Vec3, radius: f32) -> bool {
+        let span = (radius / self.cell_size).ceil() as i32;
+        let key = (point / self.cell_size).floor().as_ivec3();
+        let radius_sq = radius * radius;
+        for dx in -span..=span {
+            for dy in -span..=span {
+                for dz in -span..=span {
+                    let Some(indices) = self.cells.get(&(key + IVec3::new(dx, dy, dz))) else {
+                        continue;
+                    };
+                    if indices
+                        .iter()
+                        .any(|&i| self.positions[i].distance_squared(point) <= radius_sq)
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// One transformed copy of (part of) the asymmetric unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureCopy {
+    /// Operator applied to the copy
+    pub operator: SymmetryOperator,
+    /// Chains to copy; `None` copies every atom
+    pub chains: Option<Vec<String>>,
+}
+
+impl StructureCopy {
+    /// Copy every atom with `operator`.
+    pub fn all(operator: SymmetryOperator) -> Self {
+        Self {
+            operator,
+            chains: None,
+        }
+    }
+
+    fn includes(&self, chain: &str) -> bool {
+        self.chains
+            .as_ref()
+            .map_or(true, |chains| chains.iter().any(|c| c == chain))
+    }
+}
+
+/// Atoms and bonds produced by [`expand_structure`].
+#[derive(Debug, Clone, Default)]
+pub struct ExpandedStructure {
+    /// New atoms with sequential ids starting at 1
+    pub atoms: Vec<AtomData>,
+    /// Bonds within each copy, remapped to the new ids
+    pub bonds: Vec<BondData>,
+    /// Copy index and original atom index of each new atom
+    pub sources: Vec<(usize, usize)>,
+    /// Operator of each copy
+    pub operators: Vec<SymmetryOperator>,
+    /// Original chain id → new chain id, per copy
+    pub chain_names: Vec<HashMap<String, String>>,
+}
+
+impl ExpandedStructure {
+    /// Map a frame of the original structure onto the expanded atoms.
+    ///
+    /// Positions are transformed, velocities and forces rotated, and
+    /// per-atom properties copied; frame-level data is kept as is.
+    pub fn expand_frame(&self, frame: &FrameData) -> FrameData {
+        let transformed = |values: &[Vec3], map: &dyn Fn(&SymmetryOperator, Vec3) -> Vec3| {
+            self.sources
+                .iter()
+                .map(|&(copy, source)| {
+                    let value = values.get(source).copied().unwrap_or(Vec3::ZERO);
+                    map(&self.operators[copy], value)
+                })
+                .collect::<Vec<Vec3>>()
+        };
+        let rotated = |values: &Option<Vec<Vec3>>| {
+            values
+                .as_deref()
+                .map(|values| transformed(values, &|op, v| op.rotate(v)))
+        };
+
+        FrameData {
+            positions: transformed(&frame.positions, &|op, p| op.apply(p)),
+            velocities: rotated(&frame.velocities),
+            forces: rotated(&frame.forces),
+            properties: frame
+                .properties
+                .iter()
+                .map(|(name, values)| {
+                    let copied = self
+                        .sources
+                        .iter()
+                        .map(|&(_, source)| values.get(source).copied().unwrap_or(0.0))
+                        .collect();
+                    (name.clone(), copied)
+                })
+                .collect(),
+            ..frame.clone()
+        }
+    }
+
+    /// Secondary structure annotations renamed to the copies' chains.
+    pub fn expand_annotations(
+        &self,
+        annotations: &SecondaryStructureAnnotations,
+    ) -> SecondaryStructureAnnotations {
+        let mut expanded = SecondaryStructureAnnotations::new();
+        for names in &self.chain_names {
+            for ((chain, residue), structure) in annotations {
+                if let Some(name) = names.get(chain) {
+                    expanded.insert((name.clone(), *residue), *structure);
+                }
+            }
+        }
+        expanded
+    }
+}
+
+/// Build the atoms and bonds of several transformed copies.
+///
+/// Each chain keeps its id the first time it appears; later copies get an
+/// unused single-character id (A–Z, a–z, 0–9), or the original id with a
+/// copy number once those run out.
+pub fn expand_structure(
+    atoms: &[AtomData],
+    bonds: &[BondData],
+    copies: &[StructureCopy],
+) -> ExpandedStructure {
+    let original: HashSet<&str> = atoms.iter().map(|a| a.chain_id.as_str()).collect();
+    let mut used: HashSet<String> = HashSet::new();
+    let mut pool = CHAIN_ID_POOL.chars().map(String::from);
+    let mut expanded = ExpandedStructure::default();
+
+    for (copy_index, copy) in copies.iter().enumerate() {
+        let mut names: HashMap<String, String> = HashMap::new();
+        let mut new_ids: HashMap<u32, u32> = HashMap::new();
+
+        for (source, atom) in atoms
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| copy.includes(&a.chain_id))
+        {
+            let chain = names.entry(atom.chain_id.clone()).or_insert_with(|| {
+                let name = if used.contains(&atom.chain_id) {
+                    pool.by_ref()
+                        .find(|c| !used.contains(c) && !original.contains(c.as_str()))
+                        .unwrap_or_else(|| format!("{}{}", atom.chain_id, copy_index + 1))
+                } else {
+                    atom.chain_id.clone()
+                };
+                used.insert(name.clone());
+                name
+            });
+
+            let id = expanded.atoms.len() as u32 + 1;
+            let mut new_atom = atom.clone();
+            new_atom.id = id;
+            new_atom.chain_id = chain.clone();
+            new_atom.position = copy.operator.apply(atom.position);
+            new_ids.insert(atom.id, id);
+            expanded.atoms.push(new_atom);
+            expanded.sources.push((copy_index, source));
+        }
+
+        for bond in bonds {
+            if let (Some(&a), Some(&b)) =
+                (new_ids.get(&bond.atom_a_id), new_ids.get(&bond.atom_b_id))
+            {
+                let mut new_bond = bond.clone();
+                new_bond.atom_a_id = a;
+                new_bond.atom_b_id = b;
+                expanded.bonds.push(new_bond);
+            }
+        }
+
+        expanded.operators.push(copy.operator);
+        expanded.chain_names.push(names);
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::atom::Element;
+    use crate::core::bond::{BondOrder, BondType};
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).abs().max_element() < 1e-3, "{a:?} vs {b:?}");
+    }
+
+    fn atom(id: u32, chain: &str, position: Vec3) -> AtomData {
+        let mut atom = AtomData::new(id, Element::C, 1, "ALA".into(), chain.into(), "CA".into());
+        atom.position = position;
+        atom
+    }
+
+    #[test]
+    fn test_parse_xyz_operator() {
+        let (rotation, translation) = parse_xyz_operator("-X+1/2, y-x, -z+0.25").unwrap();
+        assert_close(
+            rotation * Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(-1.0, 1.0, -3.0),
+        );
+        assert_close(translation, Vec3::new(0.5, 0.0, 0.25));
+        assert!(parse_xyz_operator("x,y").is_none());
+        assert!(parse_xyz_operator("x,y,w").is_none());
+    }
+
+    #[test]
+    fn test_expand_oper_expression() {
+        assert_eq!(expand_oper_expression("1"), vec![vec!["1".to_string()]]);
+        assert_eq!(expand_oper_expression("(1-3)").len(), 3);
+        assert_eq!(expand_oper_expression("1,2,5")[2], vec!["5".to_string()]);
+        let product = expand_oper_expression("(X0)(1-60)");
+        assert_eq!(product.len(), 60);
+        assert_eq!(product[59], vec!["X0".to_string(), "60".to_string()]);
+    }
+
+    #[test]
+    fn test_space_groups_are_closed() {
+        let groups = [
+            "P 1",
+            "P -1",
+            "P 1 2 1",
+            "P 1 21 1",
+            "C 1 2 1",
+            "P 2 2 2",
+            "P 2 2 21",
+            "P 21 21 2",
+            "P 21 21 21",
+            "C 2 2 21",
+            "C 2 2 2",
+            "I 2 2 2",
+            "I 21 21 21",
+            "P 4",
+            "P 41",
+            "P 43",
+            "I 4",
+            "P 4 2 2",
+            "P 4 21 2",
+            "P 41 21 2",
+            "P 43 21 2",
+            "I 4 2 2",
+            "P 3",
+            "P 31",
+            "P 32",
+            "H 3",
+            "P 3 2 1",
+            "P 31 2 1",
+            "P 32 2 1",
+            "P 6",
+            "P 61",
+            "P 65",
+            "P 63",
+            "P 2 3",
+            "P 21 3",
+        ];
+        let same = |a: &(Mat3, Vec3), b: &(Mat3, Vec3)| {
+            let d = a.1 - b.1;
+            a.0.abs_diff_eq(b.0, 1e-4) && (d - d.round()).abs().max_element() < 1e-4
+        };
+        for symbol in groups {
+            let operators = space_group_operators(symbol).unwrap_or_else(|| panic!("{symbol}"));
+            for a in &operators {
+                for b in &operators {
+                    let product = (a.0 * b.0, a.0 * b.1 + a.1);
+                    assert!(
+                        operators.iter().any(|op| same(op, &product)),
+                        "{symbol} is not closed"
+                    );
+                }
+            }
+        }
+        assert!(space_group_operators("F d -3 m").is_none());
+    }
+
+    #[test]
+    fn test_fractional_operator_matches_smtry() {
+        // 1CRN: P 1 21 1, SMTRY 2 = diag(-1, 1, -1) + (0, b/2, 0)
+        let cell = UnitCell::from_lengths_angles([40.96, 18.65, 22.52], [90.0, 90.77, 90.0]);
+        let operators = StructureSymmetry {
+            space_group: Some("P 1 21 1".into()),
+            ..Default::default()
+        }
+        .crystal_operators(&cell)
+        .unwrap();
+        let smtry = SymmetryOperator::from_rows([
+            [-1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 9.325],
+            [0.0, 0.0, -1.0, 0.0],
+        ]);
+        assert!(operators[0].is_identity());
+        assert!(operators[1].rotation.abs_diff_eq(smtry.rotation, 1e-3));
+        assert_close(operators[1].translation, smtry.translation);
+    }
+
+    #[test]
+    fn test_symmetry_mates_within_radius() {
+        // Two atoms 2 Å apart in a 10 Å P1 cell: neighbours along ±x only.
+        let cell = UnitCell::orthorhombic([10.0, 30.0, 30.0]);
+        let positions = [Vec3::new(1.0, 0.0, 0.0), Vec3::new(9.0, 0.0, 0.0)];
+        let mates = symmetry_mate_operators(&[SymmetryOperator::IDENTITY], &cell, &positions, 3.0);
+        assert_eq!(mates.len(), 2);
+        for mate in &mates {
+            assert!((mate.translation.x.abs() - 10.0).abs() < 1e-4);
+        }
+        let none = symmetry_mate_operators(&[SymmetryOperator::IDENTITY], &cell, &positions, 1.0);
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_expand_structure_renames_chains_and_bonds() {
+        let atoms = vec![
+            atom(10, "A", Vec3::new(1.0, 0.0, 0.0)),
+            atom(11, "A", Vec3::new(2.0, 0.0, 0.0)),
+            atom(20, "B", Vec3::new(0.0, 5.0, 0.0)),
+        ];
+        let bonds = vec![BondData::new(
+            10,
+            11,
+            BondType::Covalent,
+            BondOrder::Single,
+            1.0,
+        )];
+        let flip = SymmetryOperator::new(Mat3::from_diagonal(Vec3::new(-1.0, -1.0, 1.0)), Vec3::Z);
+        let copies = vec![
+            StructureCopy::all(SymmetryOperator::IDENTITY),
+            StructureCopy {
+                operator: flip,
+                chains: Some(vec!["A".into()]),
+            },
+        ];
+
+        let expanded = expand_structure(&atoms, &bonds, &copies);
+        let ids: Vec<u32> = expanded.atoms.iter().map(|a| a.id).collect();
+        let chains: Vec<&str> = expanded.atoms.iter().map(|a| a.chain_id.as_str()).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+        assert_eq!(chains, vec!["A", "A", "B", "C", "C"]);
+        assert_eq!(expanded.bonds.len(), 2);
+        assert_eq!(
+            (expanded.bonds[1].atom_a_id, expanded.bonds[1].atom_b_id),
+            (4, 5)
+        );
+        assert_close(expanded.atoms[3].position, Vec3::new(-1.0, 0.0, 1.0));
+
+        let mut frame = FrameData::new(0, 0.0);
+        for (i, a) in atoms.iter().enumerate() {
+            frame.set_position(i, a.position + Vec3::X);
+        }
+        frame.velocities = Some(vec![Vec3::ZERO, Vec3::X, Vec3::ZERO]);
+        let out = expanded.expand_frame(&frame);
+        assert_eq!(out.positions.len(), 5);
+        assert_close(out.position(4).unwrap(), Vec3::new(-3.0, 0.0, 1.0));
+        assert_close(out.velocities.as_ref().unwrap()[4], -Vec3::X);
+
+        let annotations = SecondaryStructureAnnotations::from([(
+            ("A".to_string(), 1),
+            crate::core::molecule::SecondaryStructure::AlphaHelix,
+        )]);
+        let renamed = expanded.expand_annotations(&annotations);
+        assert!(renamed.contains_key(&("C".to_string(), 1)));
+        assert_eq!(renamed.len(), 2);
+    }
+}
@@ -1,5 +1,8 @@
 //! Timeline and trajectory management
 
+use crate::core::cell::UnitCell;
+use crate::core::secondary_structure::SecondaryStructureAnnotations;
+use crate::core::symmetry::StructureSymmetry;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -114,19 +117,58 @@ impl TimelineState {
     }
 }
 
+/// Multiply by this factor to convert times in ps (the GROMACS and AMBER time
+/// unit) to fs, the unit of [`FrameData::time`].
+pub const PS_TO_FS: f32 = 1000.0;
+
+/// Length unit of stored coordinates, velocities and cells.
+///
+/// Parsers tag frames with the unit of their file format; loaders convert
+/// everything to Ångström (the engine's internal unit) before bond detection,
+/// rendering, measurements and exports see the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LengthUnit {
+    /// Ångström (PDB, mmCIF, XYZ, DCD; internal unit)
+    #[default]
+    Angstrom,
+    /// Nanometre (GROMACS GRO)
+    Nanometer,
+}
+
+impl LengthUnit {
+    /// Multiply by this factor to convert lengths in this unit to Å.
+    pub fn to_angstrom(&self) -> f32 {
+        match self {
+            LengthUnit::Angstrom => 1.0,
+            LengthUnit::Nanometer => 10.0,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            LengthUnit::Angstrom => "Å",
+            LengthUnit::Nanometer => "nm",
+        }
+    }
+}
+
 /// Data for a single trajectory frame
+///
+/// Per-atom arrays are dense and indexed by atom order: entry `i` belongs to
+/// the `i`-th atom of the topology (`SimulationData::atom_data[i]`). Atom IDs
+/// are mapped to indices with [`AtomIndexMap`](crate::core::atom::AtomIndexMap).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrameData {
     /// Frame index
     pub index: usize,
-    /// Atom positions (atom ID -> position)
-    pub positions: HashMap<u32, Vec3>,
-    /// Atom velocities (optional, atom ID -> velocity)
-    pub velocities: Option<HashMap<u32, Vec3>>,
-    /// Atom forces (optional, atom ID -> force)
-    pub forces: Option<HashMap<u32, Vec3>>,
-    /// Box dimensions for periodic systems (optional)
-    pub box_size: Option<[f32; 3]>,
+    /// Atom positions in atom order
+    pub positions: Vec<Vec3>,
+    /// Atom velocities (optional, atom order)
+    pub velocities: Option<Vec<Vec3>>,
+    /// Atom forces (optional, atom order)
+    pub forces: Option<Vec<Vec3>>,
+    /// Periodic cell for this frame (optional)
+    pub cell: Option<UnitCell>,
     /// Time of this frame (in femtoseconds)
     pub time: f32,
     /// Potential energy (optional)
@@ -139,40 +181,108 @@ pub struct FrameData {
     pub pressure: Option<f32>,
     /// Additional metadata
     pub metadata: HashMap<String, String>,
+    /// Unit of positions, velocities (per ps) and the cell
+    #[serde(default)]
+    pub length_unit: LengthUnit,
+    /// Named per-atom scalars from extra file columns (property -> values in atom order)
+    #[serde(default)]
+    pub properties: HashMap<String, Vec<f32>>,
 }
 
 impl FrameData {
     /// Create a new empty frame
     pub fn new(index: usize, time: f32) -> Self {
+        Self::with_positions(index, time, Vec::new())
+    }
+
+    /// Create a frame from positions in atom order
+    pub fn with_positions(index: usize, time: f32, positions: Vec<Vec3>) -> Self {
         Self {
             index,
             time,
-            positions: HashMap::new(),
+            positions,
             velocities: None,
             forces: None,
-            box_size: None,
+            cell: None,
             potential_energy: None,
             kinetic_energy: None,
             temperature: None,
             pressure: None,
             metadata: HashMap::new(),
+            length_unit: LengthUnit::default(),
+            properties: HashMap::new(),
         }
     }
 
-    /// Set the position of an atom
-    pub fn set_position(&mut self, atom_id: u32, position: Vec3) {
-        self.positions.insert(atom_id, position);
+    /// Convert positions, velocities, forces and the cell to Å.
+    pub fn normalize_units(&mut self) {
+        let factor = self.length_unit.to_angstrom();
+        if factor == 1.0 {
+            return;
+        }
+        for pos in &mut self.positions {
+            *pos *= factor;
+        }
+        for vel in self.velocities.iter_mut().flatten() {
+            *vel *= factor;
+        }
+        // Forces are energy per length.
+        for force in self.forces.iter_mut().flatten() {
+            *force /= factor;
+        }
+        self.cell = self.cell.map(|cell| cell.scaled(factor));
+        self.length_unit = LengthUnit::Angstrom;
+    }
+
+    /// Set the position of the atom at `index`, growing the frame with
+    /// zeros if it is shorter.
+    pub fn set_position(&mut self, index: usize, position: Vec3) {
+        set_atom_value(&mut self.positions, index, position);
+    }
+
+    /// Position of the atom at `index`
+    pub fn position(&self, index: usize) -> Option<Vec3> {
+        self.positions.get(index).copied()
+    }
+
+    /// Number of atoms with a position in this frame
+    pub fn num_atoms(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Heap memory held by the per-atom arrays, in bytes
+    pub fn heap_bytes(&self) -> usize {
+        let vectors = |v: &Vec<Vec3>| v.capacity() * std::mem::size_of::<Vec3>();
+        vectors(&self.positions)
+            + self.velocities.as_ref().map_or(0, vectors)
+            + self.forces.as_ref().map_or(0, vectors)
+            + self
+                .properties
+                .iter()
+                .map(|(name, values)| name.capacity() + values.capacity() * 4)
+                .sum::<usize>()
+    }
+
+    /// Mean position of all atoms (`None` for an empty frame)
+    pub fn centroid(&self) -> Option<Vec3> {
+        (!self.positions.is_empty())
+            .then(|| self.positions.iter().sum::<Vec3>() / self.positions.len() as f32)
     }
 
-    /// Get the position of an atom
-    pub fn get_position(&self, atom_id: u32) -> Option<Vec3> {
-        self.positions.get(&atom_id).copied()
+    /// Names of the per-atom properties in this frame, sorted
+    pub fn property_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.properties.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
     }
+}
 
-    /// Get all atom IDs in this frame
-    pub fn atom_ids(&self) -> impl Iterator<Item = &u32> {
-        self.positions.keys()
+/// Store `value` at `index` of a per-atom array, padding with defaults.
+pub fn set_atom_value<T: Clone + Default>(values: &mut Vec<T>, index: usize, value: T) {
+    if index >= values.len() {
+        values.resize(index + 1, T::default());
     }
+    values[index] = value;
 }
 
 /// Complete trajectory data
@@ -190,6 +300,8 @@ pub struct Trajectory {
     pub total_time: f32,
     /// Trajectory metadata
     pub metadata: TrajectoryMetadata,
+    /// Unit of the coordinates as read from the file
+    pub length_unit: LengthUnit,
 }
 
 impl Trajectory {
@@ -202,9 +314,18 @@ impl Trajectory {
             time_step,
             total_time: 0.0,
             metadata: TrajectoryMetadata::default(),
+            length_unit: LengthUnit::default(),
         }
     }
 
+    /// Convert all in-memory frames to Å.
+    pub fn normalize_units(&mut self) {
+        for frame in &mut self.frames {
+            frame.normalize_units();
+        }
+        self.length_unit = LengthUnit::Angstrom;
+    }
+
     /// Add a frame to the trajectory
     pub fn add_frame(&mut self, frame: FrameData) {
         self.total_time = frame.time;
@@ -252,32 +373,43 @@ pub struct TrajectoryMetadata {
     pub creation_date: Option<String>,
     /// Additional metadata
     pub extra: HashMap<String, String>,
+    /// Secondary structure annotated in the file (PDB HELIX/SHEET, mmCIF
+    /// `_struct_conf` / `_struct_sheet_range`); empty when absent
+    pub secondary_structure: SecondaryStructureAnnotations,
+    /// Space group, crystallographic operators and biological assemblies
+    pub symmetry: StructureSymmetry,
 }
 
 /// Interpolate between two frames
+///
+/// Frames with different atom counts have no atom-by-atom correspondence, so
+/// `frame_a` is returned unchanged instead of a blend, as the renderers hold
+/// the current frame until the next one is reached.
 pub fn interpolate_frames(frame_a: &FrameData, frame_b: &FrameData, alpha: f32) -> FrameData {
+    if frame_a.positions.len() != frame_b.positions.len() {
+        return frame_a.clone();
+    }
+
     let mut interpolated = FrameData::new(
         frame_a.index,
         frame_a.time + (frame_b.time - frame_a.time) * alpha,
     );
+    interpolated.length_unit = frame_a.length_unit;
 
     // Interpolate positions
-    for (atom_id, pos_a) in &frame_a.positions {
-        if let Some(pos_b) = frame_b.positions.get(atom_id) {
-            let pos = pos_a.lerp(*pos_b, alpha);
-            interpolated.set_position(*atom_id, pos);
-        }
-    }
+    interpolated.positions = lerp_slices(&frame_a.positions, &frame_b.positions, alpha);
+
+    // Blend the cell so NPT box changes stay in step with atoms
+    interpolated.cell = match (&frame_a.cell, &frame_b.cell) {
+        (Some(a), Some(b)) => Some(a.lerp(b, alpha)),
+        (cell, _) => *cell,
+    };
 
     // Interpolate velocities if available
     if let (Some(vel_a), Some(vel_b)) = (&frame_a.velocities, &frame_b.velocities) {
-        let mut velocities = HashMap::new();
-        for (atom_id, vel) in vel_a {
-            if let Some(vel_b) = vel_b.get(atom_id) {
-                velocities.insert(*atom_id, vel.lerp(*vel_b, alpha));
-            }
+        if vel_a.len() == vel_b.len() {
+            interpolated.velocities = Some(lerp_slices(vel_a, vel_b, alpha));
         }
-        interpolated.velocities = Some(velocities);
     }
 
     // Interpolate energies if available
@@ -291,20 +423,22 @@ pub fn interpolate_frames(frame_a: &FrameData, frame_b: &FrameData, alpha: f32)
     interpolated
 }
 
-/// Calculate RMSD between two frames
-pub fn calculate_rmsd(frame_a: &FrameData, frame_b: &FrameData, atom_ids: &[u32]) -> Option<f32> {
-    if atom_ids.is_empty() {
+/// Element-wise lerp of two equal-length slices.
+fn lerp_slices(a: &[Vec3], b: &[Vec3], alpha: f32) -> Vec<Vec3> {
+    a.iter().zip(b).map(|(a, b)| a.lerp(*b, alpha)).collect()
+}
+
+/// Calculate RMSD between two frames over the atoms at `indices`
+pub fn calculate_rmsd(frame_a: &FrameData, frame_b: &FrameData, indices: &[usize]) -> Option<f32> {
+    if indices.is_empty() {
         return None;
     }
 
     let mut sum_sq_diff = 0.0;
     let mut count = 0;
 
-    for atom_id in atom_ids {
-        if let (Some(pos_a), Some(pos_b)) = (
-            frame_a.get_position(*atom_id),
-            frame_b.get_position(*atom_id),
-        ) {
+    for &index in indices {
+        if let (Some(pos_a), Some(pos_b)) = (frame_a.position(index), frame_b.position(index)) {
             let diff = pos_a - pos_b;
             sum_sq_diff += diff.length_squared();
             count += 1;
@@ -329,7 +463,8 @@ mod tests {
         frame.set_position(1, Vec3::new(0.0, 1.0, 0.0));
 
         assert_eq!(frame.index, 0);
-        assert_eq!(frame.get_position(0), Some(Vec3::new(1.0, 0.0, 0.0)));
+        assert_eq!(frame.position(0), Some(Vec3::new(1.0, 0.0, 0.0)));
+        assert_eq!(frame.num_atoms(), 2);
     }
 
     #[test]
@@ -343,7 +478,23 @@ mod tests {
         let interpolated = interpolate_frames(&frame_a, &frame_b, 0.5);
         assert_eq!(interpolated.index, 0);
         assert_eq!(interpolated.time, 0.5);
-        assert_eq!(interpolated.get_position(0), Some(Vec3::new(0.5, 0.0, 0.0)));
+        assert_eq!(interpolated.position(0), Some(Vec3::new(0.5, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_interpolation_with_different_atom_counts() {
+        let mut frame_a = FrameData::new(0, 0.0);
+        frame_a.set_position(0, Vec3::ZERO);
+        frame_a.set_position(1, Vec3::X);
+
+        let mut frame_b = FrameData::new(1, 1.0);
+        frame_b.set_position(0, Vec3::Y);
+
+        for alpha in [0.25, 0.75] {
+            let held = interpolate_frames(&frame_a, &frame_b, alpha);
+            assert_eq!(held.positions, frame_a.positions);
+            assert_eq!(held.time, 0.0);
+        }
     }
 
     #[test]
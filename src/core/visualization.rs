@@ -1,5 +1,8 @@
 //! Visualization styles and rendering modes
 
+use crate::analysis::molecules::MoleculeTopology;
+use crate::core::atom::AtomIndexMap;
+use crate::core::molecule::{MoleculeType, SecondaryStructure};
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -280,7 +283,7 @@ pub enum ColorScheme {
     GradientZ,
     /// Charge-based coloring
     Charge,
-    /// Custom property
+    /// Per-atom property read from the trajectory (`VisualizationConfig::color_property`)
     Custom,
 }
 
@@ -298,7 +301,7 @@ impl ColorScheme {
             ColorScheme::GradientY => "Gradient Y",
             ColorScheme::GradientZ => "Gradient Z",
             ColorScheme::Charge => "Charge",
-            ColorScheme::Custom => "Custom",
+            ColorScheme::Custom => "Property",
         }
     }
 
@@ -308,16 +311,85 @@ impl ColorScheme {
         ColorScheme::Residue,
         ColorScheme::Chain,
         ColorScheme::BFactor,
+        ColorScheme::SecondaryStructure,
+        ColorScheme::Molecule,
+        ColorScheme::Custom,
     ];
+
+    /// Whether bonds take the color of their first atom under this scheme.
+    ///
+    /// Only schemes that color whole residues, chains or molecules qualify;
+    /// per-atom schemes (CPK, B-factor, gradients) keep neutral bonds.
+    pub fn colors_bonds(&self) -> bool {
+        matches!(
+            self,
+            ColorScheme::Residue
+                | ColorScheme::Chain
+                | ColorScheme::SecondaryStructure
+                | ColorScheme::Molecule
+        )
+    }
 }
 
 /// Context for resolving per-atom colors.
 #[derive(Debug, Clone, Copy, Default)]
-pub struct ColorContext {
+pub struct ColorContext<'a> {
     pub min_b_factor: f32,
     pub max_b_factor: f32,
     pub bounds_min: Vec3,
     pub bounds_max: Vec3,
+    /// Residue / molecule segmentation of the loaded system
+    pub topology: Option<&'a MoleculeTopology>,
+    /// Secondary structure per topology residue index
+    pub secondary_structure: Option<&'a [SecondaryStructure]>,
+    /// Atom ID → index into per-atom frame arrays such as `property`
+    pub atom_index: Option<&'a AtomIndexMap>,
+    /// Values of the selected per-atom property in the current frame
+    pub property: Option<&'a [f32]>,
+    pub min_property: f32,
+    pub max_property: f32,
+}
+
+impl<'a> ColorContext<'a> {
+    /// Color by `values`, spanning the palette over their range.
+    pub fn with_property(self, values: Option<&'a [f32]>) -> Self {
+        let (min, max) = values
+            .into_iter()
+            .flatten()
+            .filter(|v| v.is_finite())
+            .fold((f32::MAX, f32::MIN), |(min, max), &v| {
+                (min.min(v), max.max(v))
+            });
+        let (min_property, max_property) = if min > max { (0.0, 1.0) } else { (min, max) };
+        Self {
+            property: values,
+            min_property,
+            max_property,
+            ..self
+        }
+    }
+}
+
+impl ColorContext<'_> {
+    /// Value of the selected property for an atom.
+    fn property_of(&self, atom_id: u32) -> Option<f32> {
+        let index = self.atom_index?.index_of(atom_id)?;
+        self.property?.get(index).copied()
+    }
+
+    /// Secondary structure of a protein atom's residue (`None` outside proteins).
+    fn secondary_structure_of(&self, atom_id: u32) -> Option<SecondaryStructure> {
+        let topology = self.topology?;
+        let residue = topology.residue_index(atom_id)?;
+        if topology.residues()[residue].molecule_type != MoleculeType::Protein {
+            return None;
+        }
+        Some(
+            self.secondary_structure
+                .and_then(|ss| ss.get(residue).copied())
+                .unwrap_or(SecondaryStructure::Coil),
+        )
+    }
 }
 
 impl ColorScheme {
@@ -333,6 +405,14 @@ impl ColorScheme {
             ColorScheme::BFactor => {
                 ColorPalette::b_factor_color(atom.b_factor, ctx.min_b_factor, ctx.max_b_factor)
             }
+            ColorScheme::SecondaryStructure => match ctx.secondary_structure_of(atom.id) {
+                Some(ss) => ColorPalette::secondary_structure_color(ss),
+                None => ColorScheme::CPK.atom_color(atom, ctx),
+            },
+            ColorScheme::Molecule => match ctx.topology.and_then(|t| t.molecule_index(atom.id)) {
+                Some(molecule) => ColorPalette::molecule_color(molecule),
+                None => ColorScheme::CPK.atom_color(atom, ctx),
+            },
             ColorScheme::GradientX => {
                 let t = gradient_t(atom.position.x, ctx.bounds_min.x, ctx.bounds_max.x);
                 Color::hsla(0.66 * (1.0 - t), 0.8, 0.5, 1.0)
@@ -350,10 +430,12 @@ impl ColorScheme {
                 let t = (atom.charge.clamp(-1.0, 1.0) + 1.0) * 0.5;
                 Color::srgb(t, 0.2, 1.0 - t)
             }
-            _ => {
-                let rgb = atom.element.cpk_color();
-                Color::srgb(rgb[0], rgb[1], rgb[2])
-            }
+            ColorScheme::Custom => match ctx.property_of(atom.id) {
+                Some(value) => {
+                    ColorPalette::b_factor_color(value, ctx.min_property, ctx.max_property)
+                }
+                None => ColorScheme::CPK.atom_color(atom, ctx),
+            },
         }
     }
 }
@@ -491,6 +573,12 @@ impl ColorPalette {
         }
     }
 
+    /// Color of the `index`-th molecule (12 hues, neighbours far apart)
+    pub fn molecule_color(index: usize) -> Color {
+        let hue = ((index * 5) % 12) as f32 * 30.0;
+        Color::hsl(hue, 0.65, 0.55)
+    }
+
     /// Get chain color
     pub fn chain_color(chain_id: &str) -> Color {
         let hash = chain_id
@@ -527,6 +615,10 @@ pub struct VisualizationConfig {
     pub show_bonds: bool,
     /// Show atoms flag
     pub show_atoms: bool,
+    /// Draw the periodic simulation cell when the trajectory has one
+    pub show_unit_cell: bool,
+    /// Per-atom frame property colored by [`ColorScheme::Custom`]
+    pub color_property: Option<String>,
 }
 
 impl Default for VisualizationConfig {
@@ -538,6 +630,8 @@ impl Default for VisualizationConfig {
             bond_scale: 1.0,
             show_bonds: true,
             show_atoms: true,
+            show_unit_cell: true,
+            color_property: None,
         }
     }
 }
@@ -604,6 +698,7 @@ mod tests {
         assert_eq!(config.bond_scale, 1.0);
         assert!(config.show_bonds);
         assert!(config.show_atoms);
+        assert!(config.show_unit_cell);
     }
 
     #[test]
@@ -617,12 +712,79 @@ mod tests {
             max_b_factor: 80.0,
             bounds_min: Vec3::ZERO,
             bounds_max: Vec3::ONE,
+            ..Default::default()
         };
         let c_low = ColorScheme::BFactor.atom_color(&low, &ctx);
         let c_high = ColorScheme::BFactor.atom_color(&high, &ctx);
         assert_ne!(c_low, c_high);
     }
 
+    #[test]
+    fn test_secondary_structure_and_molecule_schemes() {
+        use crate::core::atom::{AtomData, Element};
+        let atoms = vec![
+            AtomData::new(0, Element::C, 1, "ALA".into(), "A".into(), "CA".into()),
+            AtomData::new(1, Element::C, 2, "GLY".into(), "A".into(), "CA".into()),
+            AtomData::new(2, Element::O, 50, "HOH".into(), "W".into(), "O".into()),
+        ];
+        let topology = MoleculeTopology::build(&atoms, []);
+        let ss = [
+            SecondaryStructure::AlphaHelix,
+            SecondaryStructure::BetaStrand,
+        ];
+        let ctx = ColorContext {
+            topology: Some(&topology),
+            secondary_structure: Some(&ss),
+            ..Default::default()
+        };
+
+        let color = |scheme: ColorScheme, i: usize| scheme.atom_color(&atoms[i], &ctx);
+        assert_eq!(
+            color(ColorScheme::SecondaryStructure, 0),
+            ColorPalette::secondary_structure_color(SecondaryStructure::AlphaHelix)
+        );
+        assert_eq!(
+            color(ColorScheme::SecondaryStructure, 1),
+            ColorPalette::secondary_structure_color(SecondaryStructure::BetaStrand)
+        );
+        // Non-protein atoms fall back to element colors.
+        assert_eq!(
+            color(ColorScheme::SecondaryStructure, 2),
+            color(ColorScheme::CPK, 2)
+        );
+        // Both residues belong to one chain molecule; the water is another.
+        assert_eq!(
+            color(ColorScheme::Molecule, 0),
+            color(ColorScheme::Molecule, 1)
+        );
+        assert_ne!(
+            color(ColorScheme::Molecule, 0),
+            color(ColorScheme::Molecule, 2)
+        );
+    }
+
+    #[test]
+    fn test_custom_property_scheme() {
+        use crate::core::atom::{AtomData, Element};
+        let atoms: Vec<AtomData> = (0..3)
+            .map(|i| AtomData::new(i, Element::C, 0, "UNK".into(), "A".into(), "C".into()))
+            .collect();
+        let values = [-3.0, 1.0];
+        let atom_index = AtomIndexMap::build(&atoms);
+        let ctx = ColorContext {
+            atom_index: Some(&atom_index),
+            ..Default::default()
+        }
+        .with_property(Some(&values));
+        assert_eq!((ctx.min_property, ctx.max_property), (-3.0, 1.0));
+
+        let color = |i: usize| ColorScheme::Custom.atom_color(&atoms[i], &ctx);
+        assert_eq!(color(0), ColorPalette::b_factor_color(0.0, 0.0, 1.0));
+        assert_eq!(color(1), ColorPalette::b_factor_color(1.0, 0.0, 1.0));
+        // Atoms without a value keep element colors
+        assert_eq!(color(2), ColorScheme::CPK.atom_color(&atoms[2], &ctx));
+    }
+
     #[test]
     fn test_surface_mode_hides_atoms() {
         let params = RenderMode::Surface.mode_params();
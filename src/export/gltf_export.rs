@@ -4,6 +4,7 @@
 //! Uses embedded buffer (base64) for a single-file output.
 
 use crate::core::bond::Bond;
+use crate::core::groups::AtomStyles;
 use crate::core::visualization::VisualizationConfig;
 use crate::export::mesh_export::{generate_cylinder_mesh, generate_sphere_mesh, transform_vertex};
 use crate::export::scene_snapshot::{capture_scene, SceneSnapshot};
@@ -29,6 +30,7 @@ pub struct RequestExportGltfEvent {
 }
 
 /// Handle glTF export requests
+#[allow(clippy::too_many_arguments)]
 pub fn handle_export_gltf(
     mut requests: EventReader<RequestExportGltfEvent>,
     sim_data: Res<SimulationData>,
@@ -37,15 +39,17 @@ pub fn handle_export_gltf(
     instanced: Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
     bond_query: Query<(&Transform, &Bond)>,
     bond_entities: Res<BondEntities>,
+    styles: Res<AtomStyles>,
 ) {
     for event in requests.read() {
         let snapshot = capture_scene(
             &index,
             &instanced,
-            &sim_data.atom_data,
+            sim_data.atom_data(),
             &bond_query,
             &bond_entities,
             &viz_config,
+            &styles,
         );
 
         let path = event.path.clone();
@@ -1,4 +1,4 @@
-//! Export functionality (screenshots, videos, 3D formats)
+//! Export functionality (screenshots, videos, 3D formats, trajectories)
 
 pub mod gltf_export;
 pub mod mesh_export;
@@ -6,6 +6,7 @@ pub mod obj;
 pub mod povray;
 pub mod scene_snapshot;
 pub mod screenshot;
+pub mod trajectory;
 pub mod video;
 
 use bevy::prelude::*;
@@ -17,5 +18,6 @@ pub fn register(app: &mut App) {
     gltf_export::register(app);
     povray::register(app);
     video::register(app);
+    trajectory::register(app);
     info!("Export module registered");
 }
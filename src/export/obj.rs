@@ -1,6 +1,7 @@
 //! OBJ format export
 
 use crate::core::bond::Bond;
+use crate::core::groups::AtomStyles;
 use crate::core::visualization::VisualizationConfig;
 use crate::export::mesh_export::{generate_cylinder_mesh, generate_sphere_mesh, transform_vertex};
 use crate::export::scene_snapshot::{capture_scene, SceneSnapshot};
@@ -18,6 +19,7 @@ pub struct RequestExportObjEvent {
     pub path: PathBuf,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handle_export_obj(
     mut requests: EventReader<RequestExportObjEvent>,
     sim_data: Res<SimulationData>,
@@ -26,15 +28,17 @@ pub fn handle_export_obj(
     instanced: Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
     bond_query: Query<(&Transform, &Bond)>,
     bond_entities: Res<BondEntities>,
+    styles: Res<AtomStyles>,
 ) {
     for event in requests.read() {
         let snapshot = capture_scene(
             &index,
             &instanced,
-            &sim_data.atom_data,
+            sim_data.atom_data(),
             &bond_query,
             &bond_entities,
             &viz_config,
+            &styles,
         );
 
         let path = event.path.clone();
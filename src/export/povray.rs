@@ -4,6 +4,7 @@
 //! with CPK colors and the active camera viewpoint.
 
 use crate::core::bond::Bond;
+use crate::core::groups::AtomStyles;
 use crate::core::visualization::VisualizationConfig;
 use crate::export::mesh_export::transform_vertex;
 use crate::export::scene_snapshot::{capture_scene, SceneSnapshot};
@@ -156,6 +157,7 @@ pub fn handle_export_povray(
     instanced: Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
     bond_query: Query<(&Transform, &Bond)>,
     bond_entities: Res<BondEntities>,
+    styles: Res<AtomStyles>,
     camera_query: Query<(&Transform, &PanOrbitCamera), With<Camera3d>>,
 ) {
     for event in requests.read() {
@@ -167,10 +169,11 @@ pub fn handle_export_povray(
         let snapshot = capture_scene(
             &index,
             &instanced,
-            &sim_data.atom_data,
+            sim_data.atom_data(),
             &bond_query,
             &bond_entities,
             &viz_config,
+            &styles,
         );
 
         let camera = camera_query
@@ -238,8 +241,10 @@ mod tests {
     #[test]
     fn test_capture_camera_uses_focus() {
         let transform = Transform::from_xyz(1.0, 2.0, 20.0);
-        let mut pan = PanOrbitCamera::default();
-        pan.focus = Vec3::new(3.0, 4.0, 5.0);
+        let pan = PanOrbitCamera {
+            focus: Vec3::new(3.0, 4.0, 5.0),
+            ..Default::default()
+        };
         let cam = capture_camera(&transform, &pan);
         assert_eq!(cam.location, transform.translation);
         assert_eq!(cam.look_at, pan.focus);
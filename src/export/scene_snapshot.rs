@@ -1,6 +1,7 @@
 //! Centralized scene data collection for export formats.
 
 use crate::core::bond::Bond;
+use crate::core::groups::AtomStyles;
 use crate::rendering::atom_index::InstancedAtomIndex;
 use crate::rendering::instanced::{InstancedAtomEntity, InstancedAtomMesh};
 use bevy::prelude::*;
@@ -30,6 +31,8 @@ pub struct SceneSnapshot {
 }
 
 /// Build export snapshot from instanced rendering + bond entities.
+/// Hidden atoms and bonds touching them are left out.
+#[allow(clippy::too_many_arguments)]
 pub fn capture_scene(
     index: &InstancedAtomIndex,
     instanced: &Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
@@ -37,9 +40,9 @@ pub fn capture_scene(
     bond_query: &Query<(&Transform, &Bond)>,
     bond_entities: &crate::systems::bonds::BondEntities,
     viz_config: &crate::core::visualization::VisualizationConfig,
+    styles: &AtomStyles,
 ) -> SceneSnapshot {
     let mut snapshot = SceneSnapshot::default();
-    let mode_scale = viz_config.render_mode.atom_scale() * viz_config.atom_scale;
 
     let atom_lookup: std::collections::HashMap<u32, &crate::core::atom::AtomData> =
         atom_data.iter().map(|a| (a.id, a)).collect();
@@ -54,7 +57,9 @@ pub fn capture_scene(
                 continue;
             };
 
-            if instance.scale <= 0.001 {
+            // Per-atom style scale, so off-screen (culled) atoms are still exported.
+            let scale = mesh.base_scales.get(idx).copied().unwrap_or(instance.scale);
+            if scale <= 0.001 {
                 continue;
             }
 
@@ -63,7 +68,7 @@ pub fn capture_scene(
                 .map(|a| a.element.vdw_radius())
                 .unwrap_or(element.vdw_radius());
 
-            let radius = vdw * 0.5 * instance.scale * mode_scale;
+            let radius = vdw * 0.5 * scale;
             let color = atom_lookup
                 .get(&atom_id)
                 .map(|a| a.element.cpk_color())
@@ -79,6 +84,9 @@ pub fn capture_scene(
 
     for (_, entity) in bond_entities.entities.iter() {
         if let Ok((transform, bond)) = bond_query.get(*entity) {
+            if !styles.get(bond.atom_a_id).visible || !styles.get(bond.atom_b_id).visible {
+                continue;
+            }
             snapshot.bonds.push(BondSnapshot {
                 translation: transform.translation,
                 rotation: transform.rotation,
@@ -0,0 +1,730 @@
+//! Trajectory export: a frame range, stride and atom subset of any
+//! [`FrameProvider`] written as DCD or multi-frame XYZ/PDB/GRO.
+//!
+//! Frames are read one at a time, so excerpts of streamed trajectories never
+//! load the whole run, and pass through the same PBC transforms as the
+//! timeline, so files match what is on screen. Exported atoms are renumbered
+//! from 1 in topology order; coordinate-only outputs (DCD, XYZ) can get a
+//! matching PDB topology written next to them.
+
+use crate::core::atom::AtomData;
+use crate::core::bond::BondData;
+use crate::core::trajectory::FrameData;
+use crate::io::dcd::DcdWriter;
+use crate::io::gro::GroWriter;
+use crate::io::pdb::PDBWriter;
+use crate::io::streaming::FrameProvider;
+use crate::io::xyz::XYZWriter;
+use crate::io::{registry, FileFormat, IOError, IOResult};
+use crate::systems::loading::{FileLoadedEvent, SimulationData};
+use crate::systems::pbc::{
+    PbcFrameProvider, PbcTransform, PbcTransformSettings, PbcTransformState,
+};
+use crate::ui::notifications::UiNotifications;
+use bevy::prelude::*;
+use crossbeam_channel::Receiver;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Output format of a trajectory export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrajectoryFormat {
+    /// CHARMM/NAMD binary trajectory (coordinates and cell only)
+    #[default]
+    Dcd,
+    /// Multi-frame extended XYZ
+    Xyz,
+    /// Multi-model PDB
+    Pdb,
+    /// Multi-frame GROMACS GRO
+    Gro,
+}
+
+impl TrajectoryFormat {
+    pub const ALL: [TrajectoryFormat; 4] = [Self::Dcd, Self::Xyz, Self::Pdb, Self::Gro];
+
+    /// Format for a file extension (case-insensitive), as registered in
+    /// [`crate::io::registry`].
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_file_format(FileFormat::from_path(path))
+    }
+
+    /// Export format writing `format`, if the exporter supports it.
+    pub fn from_file_format(format: FileFormat) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.file_format() == format)
+    }
+
+    /// The file format this export writes.
+    pub fn file_format(&self) -> FileFormat {
+        match self {
+            Self::Dcd => FileFormat::DCD,
+            Self::Xyz => FileFormat::XYZ,
+            Self::Pdb => FileFormat::PDB,
+            Self::Gro => FileFormat::GRO,
+        }
+    }
+
+    /// Export formats the registry marks writable, in registry order.
+    pub fn available() -> Vec<Self> {
+        registry::registry()
+            .formats()
+            .iter()
+            .filter(|d| d.capabilities.writable)
+            .filter_map(|d| Self::from_file_format(d.format))
+            .collect()
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Dcd => "dcd",
+            Self::Xyz => "xyz",
+            Self::Pdb => "pdb",
+            Self::Gro => "gro",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Dcd => "DCD",
+            Self::Xyz => "XYZ",
+            Self::Pdb => "PDB (models)",
+            Self::Gro => "GRO",
+        }
+    }
+
+    /// Whether the output lacks atom names, residues and chains.
+    pub fn needs_topology(&self) -> bool {
+        matches!(self, Self::Dcd | Self::Xyz)
+    }
+}
+
+/// What to write: frame range, stride and atom subset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrajectoryExportOptions {
+    pub format: TrajectoryFormat,
+    /// First frame to write (0-based)
+    pub first_frame: usize,
+    /// Last frame to write, inclusive; `None` for the end of the trajectory
+    pub last_frame: Option<usize>,
+    /// Write every `stride`-th frame of the range
+    pub stride: usize,
+    /// Atom ids to keep, written in topology order; `None` keeps every atom
+    pub atom_ids: Option<Vec<u32>>,
+    /// Write a PDB topology next to DCD and XYZ outputs
+    pub write_topology: bool,
+}
+
+impl Default for TrajectoryExportOptions {
+    fn default() -> Self {
+        Self {
+            format: TrajectoryFormat::default(),
+            first_frame: 0,
+            last_frame: None,
+            stride: 1,
+            atom_ids: None,
+            write_topology: true,
+        }
+    }
+}
+
+impl TrajectoryExportOptions {
+    /// Frame indices selected from a trajectory of `num_frames` frames.
+    pub fn frame_indices(&self, num_frames: usize) -> Vec<usize> {
+        let last = self
+            .last_frame
+            .unwrap_or(usize::MAX)
+            .min(num_frames.saturating_sub(1));
+        if num_frames == 0 || self.first_frame > last {
+            return Vec::new();
+        }
+        (self.first_frame..=last)
+            .step_by(self.stride.max(1))
+            .collect()
+    }
+}
+
+/// Result of a finished export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrajectoryExportSummary {
+    pub path: PathBuf,
+    pub frames: usize,
+    pub atoms: usize,
+    /// PDB topology written next to the trajectory
+    pub topology_path: Option<PathBuf>,
+}
+
+/// Atoms kept by an export, renumbered from 1, with the bonds between them.
+struct ExportedAtoms {
+    /// Original id of each exported atom
+    source_ids: Vec<u32>,
+    /// Original atom index of each exported atom
+    source_indices: Vec<usize>,
+    atoms: Vec<AtomData>,
+    bonds: Vec<BondData>,
+}
+
+impl ExportedAtoms {
+    fn new(atoms: &[AtomData], bonds: &[BondData], keep: Option<&[u32]>) -> IOResult<Self> {
+        let keep: Option<std::collections::HashSet<u32>> =
+            keep.map(|ids| ids.iter().copied().collect());
+        let (source_indices, kept): (Vec<usize>, Vec<&AtomData>) = atoms
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| keep.as_ref().map_or(true, |k| k.contains(&a.id)))
+            .unzip();
+        if kept.is_empty() {
+            return Err(IOError::InvalidFormat(
+                "No atoms selected for export".to_string(),
+            ));
+        }
+
+        let id_map: HashMap<u32, u32> = kept
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (a.id, i as u32 + 1))
+            .collect();
+        let bonds = bonds
+            .iter()
+            .filter_map(|bond| {
+                let mut bond = bond.clone();
+                bond.atom_a_id = *id_map.get(&bond.atom_a_id)?;
+                bond.atom_b_id = *id_map.get(&bond.atom_b_id)?;
+                Some(bond)
+            })
+            .collect();
+
+        Ok(Self {
+            source_ids: kept.iter().map(|a| a.id).collect(),
+            source_indices,
+            atoms: kept
+                .iter()
+                .enumerate()
+                .map(|(i, atom)| {
+                    let mut atom = (*atom).clone();
+                    atom.id = i as u32 + 1;
+                    atom
+                })
+                .collect(),
+            bonds,
+        })
+    }
+
+    /// Frame in Å with the exported atoms' positions, renumbered as frame
+    /// `index`. Fails if `frame` (source frame `source_index`) has no
+    /// position for an exported atom.
+    fn remap_frame(
+        &self,
+        mut frame: FrameData,
+        source_index: usize,
+        index: usize,
+    ) -> IOResult<FrameData> {
+        frame.normalize_units();
+        let positions = self
+            .source_indices
+            .iter()
+            .zip(&self.source_ids)
+            .map(|(&i, id)| {
+                frame.position(i).ok_or_else(|| {
+                    IOError::InvalidFormat(format!(
+                        "Frame {source_index} has no position for atom {id}"
+                    ))
+                })
+            })
+            .collect::<IOResult<_>>()?;
+        let mut out = FrameData::with_positions(index, frame.time, positions);
+        out.cell = frame.cell;
+        Ok(out)
+    }
+}
+
+/// Frames of the loaded structure (streamed or in memory, not copied) with
+/// `transform` applied.
+pub fn frame_source(
+    sim_data: &SimulationData,
+    transform: Arc<PbcTransform>,
+) -> Arc<dyn FrameProvider> {
+    Arc::new(PbcFrameProvider::new(sim_data.frames(), transform))
+}
+
+/// Write the frames and atoms selected by `options` to `path`.
+///
+/// `progress` is called with `(frames written, frames to write)` after each frame.
+pub fn export_trajectory(
+    provider: &dyn FrameProvider,
+    atoms: &[AtomData],
+    bonds: &[BondData],
+    path: &Path,
+    options: &TrajectoryExportOptions,
+    mut progress: impl FnMut(usize, usize),
+) -> IOResult<TrajectoryExportSummary> {
+    let indices = options.frame_indices(provider.num_frames());
+    if indices.is_empty() {
+        return Err(IOError::InvalidFormat(
+            "The frame range selects no frames".to_string(),
+        ));
+    }
+    let exported = ExportedAtoms::new(atoms, bonds, options.atom_ids.as_deref())?;
+    let total = indices.len();
+    let first = exported.remap_frame(provider.get_frame(indices[0])?, indices[0], 0)?;
+    let frames = indices
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, &index)| exported.remap_frame(provider.get_frame(index)?, index, i));
+
+    let title = match provider.metadata().title.trim() {
+        "" => "Exported by Gumol Viz Engine".to_string(),
+        title => title.to_string(),
+    };
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    match options.format {
+        TrajectoryFormat::Dcd => {
+            let time_step = provider.time_step() * options.stride.max(1) as f32;
+            let mut dcd = DcdWriter::new(
+                writer,
+                exported.atoms.len(),
+                time_step,
+                first.cell.is_some(),
+                &title,
+            )?;
+            let mut write_dcd =
+                |frame: &FrameData| dcd.write_frame(&frame.positions, frame.cell.as_ref());
+            write_dcd(&first)?;
+            progress(1, total);
+            for (done, frame) in frames.enumerate() {
+                write_dcd(&frame?)?;
+                progress(done + 2, total);
+            }
+            dcd.finish()?;
+        }
+        format => {
+            if let (TrajectoryFormat::Pdb, Some(cell)) = (format, &first.cell) {
+                PDBWriter::write_cryst1(&mut writer, cell)?;
+            }
+            let mut write = |frame: &FrameData| match format {
+                TrajectoryFormat::Pdb => {
+                    PDBWriter::write_model(&mut writer, frame.index + 1, frame, &exported.atoms)
+                }
+                TrajectoryFormat::Gro => {
+                    GroWriter::write_frame(&mut writer, &title, frame, &exported.atoms)
+                }
+                _ => XYZWriter::write_frame_with_atoms(&mut writer, frame, &exported.atoms),
+            };
+            write(&first)?;
+            progress(1, total);
+            for (done, frame) in frames.enumerate() {
+                write(&frame?)?;
+                progress(done + 2, total);
+            }
+            if format == TrajectoryFormat::Pdb {
+                PDBWriter::write_conect(&mut writer, &exported.bonds)?;
+                writeln!(writer, "END")?;
+            }
+            writer.flush()?;
+        }
+    }
+
+    let topology_path = if options.write_topology && options.format.needs_topology() {
+        let topology_path = path.with_extension("pdb");
+        let mut topology = BufWriter::new(File::create(&topology_path)?);
+        PDBWriter::write_structure(&mut topology, &first, &exported.atoms, &exported.bonds)?;
+        topology.flush()?;
+        Some(topology_path)
+    } else {
+        None
+    };
+
+    Ok(TrajectoryExportSummary {
+        path: path.to_path_buf(),
+        frames: total,
+        atoms: exported.atoms.len(),
+        topology_path,
+    })
+}
+
+/// Export the loaded structure's frames (streamed or in memory).
+pub fn export_simulation(
+    sim_data: &SimulationData,
+    pbc: &PbcTransformSettings,
+    path: &Path,
+    options: &TrajectoryExportOptions,
+) -> IOResult<TrajectoryExportSummary> {
+    let transform = PbcTransform::for_simulation(pbc.clone(), sim_data, []);
+    let provider = frame_source(sim_data, Arc::new(transform));
+    export_trajectory(
+        provider.as_ref(),
+        sim_data.atom_data(),
+        &sim_data.bond_data,
+        path,
+        options,
+        |_, _| {},
+    )
+}
+
+/// Request writing the loaded trajectory to `path`.
+#[derive(Event, Debug, Clone)]
+pub struct RequestTrajectoryExportEvent {
+    pub path: PathBuf,
+    pub options: TrajectoryExportOptions,
+}
+
+enum ExportMessage {
+    Progress(usize, usize),
+    Done(Result<TrajectoryExportSummary, String>),
+}
+
+/// Export panel choices and progress of the running export (read by UI).
+#[derive(Resource)]
+pub struct TrajectoryExportState {
+    /// Options edited in the Export panel (`atom_ids` is filled on export)
+    pub options: TrajectoryExportOptions,
+    /// Restrict the export to the selected atoms
+    pub selection_only: bool,
+    /// Progress in `[0.0, 1.0]` while an export runs
+    pub progress: f32,
+    receiver: Option<Receiver<ExportMessage>>,
+}
+
+impl Default for TrajectoryExportState {
+    fn default() -> Self {
+        Self {
+            options: TrajectoryExportOptions::default(),
+            selection_only: false,
+            progress: 0.0,
+            receiver: None,
+        }
+    }
+}
+
+impl TrajectoryExportState {
+    pub fn is_running(&self) -> bool {
+        self.receiver.is_some()
+    }
+}
+
+/// Start a background export for each request.
+pub fn handle_trajectory_export_requests(
+    mut requests: EventReader<RequestTrajectoryExportEvent>,
+    sim_data: Res<SimulationData>,
+    pbc_state: Res<PbcTransformState>,
+    mut state: ResMut<TrajectoryExportState>,
+    mut notifications: ResMut<UiNotifications>,
+) {
+    for request in requests.read() {
+        if !sim_data.loaded {
+            notifications.show("Load a trajectory before exporting", 180);
+            continue;
+        }
+        if state.is_running() {
+            notifications.show("A trajectory export is already running", 180);
+            continue;
+        }
+
+        let provider = frame_source(&sim_data, pbc_state.transform());
+        let atoms = sim_data.atom_data().to_vec();
+        let bonds = sim_data.bond_data.clone();
+        let request = request.clone();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        state.receiver = Some(rx);
+        state.progress = 0.0;
+
+        std::thread::spawn(move || {
+            let result = export_trajectory(
+                provider.as_ref(),
+                &atoms,
+                &bonds,
+                &request.path,
+                &request.options,
+                |done, total| {
+                    let _ = tx.send(ExportMessage::Progress(done, total));
+                },
+            );
+            let _ = tx.send(ExportMessage::Done(result.map_err(|e| e.to_string())));
+        });
+    }
+}
+
+/// Update progress and report finished exports.
+pub fn trajectory_export_poll(
+    mut state: ResMut<TrajectoryExportState>,
+    mut notifications: ResMut<UiNotifications>,
+) {
+    let Some(receiver) = state.receiver.take() else {
+        return;
+    };
+    loop {
+        match receiver.try_recv() {
+            Ok(ExportMessage::Progress(done, total)) => {
+                state.progress = done as f32 / total.max(1) as f32;
+            }
+            Ok(ExportMessage::Done(Ok(summary))) => {
+                let mut text = format!(
+                    "Exported {} frames × {} atoms to {}",
+                    summary.frames,
+                    summary.atoms,
+                    summary.path.display()
+                );
+                if let Some(topology) = &summary.topology_path {
+                    text.push_str(&format!(" (topology {})", topology.display()));
+                }
+                info!("{text}");
+                notifications.show(text, 240);
+                return;
+            }
+            Ok(ExportMessage::Done(Err(err))) => {
+                error!("Trajectory export failed: {err}");
+                notifications.show(format!("Trajectory export failed: {err}"), 300);
+                return;
+            }
+            Err(crossbeam_channel::TryRecvError::Empty) => break,
+            Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                notifications.show("Trajectory export failed: worker stopped", 300);
+                return;
+            }
+        }
+    }
+    state.receiver = Some(receiver);
+}
+
+/// Reset the frame range when a new file loads.
+pub fn reset_trajectory_export_on_load(
+    mut state: ResMut<TrajectoryExportState>,
+    mut events: EventReader<FileLoadedEvent>,
+) {
+    if events.read().next().is_some() {
+        state.options.first_frame = 0;
+        state.options.last_frame = None;
+    }
+}
+
+pub fn register(app: &mut App) {
+    app.init_resource::<TrajectoryExportState>()
+        .add_event::<RequestTrajectoryExportEvent>()
+        .add_systems(
+            Update,
+            (
+                reset_trajectory_export_on_load,
+                handle_trajectory_export_requests,
+                trajectory_export_poll,
+            )
+                .chain(),
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::atom::Element;
+    use crate::core::bond::{BondOrder, BondType};
+    use crate::core::cell::UnitCell;
+    use crate::core::trajectory::Trajectory;
+    use crate::io::dcd::DcdReader;
+    use crate::io::gro::GroParser;
+    use crate::io::pdb::PDBParser;
+    use crate::io::streaming::InMemoryFrameProvider;
+    use crate::io::xyz::XYZParser;
+
+    fn four_atom_trajectory() -> (InMemoryFrameProvider, Vec<AtomData>, Vec<BondData>) {
+        let atoms: Vec<AtomData> = [(10, Element::N), (11, Element::C), (12, Element::O)]
+            .iter()
+            .chain(&[(13, Element::Na)])
+            .map(|&(id, element)| {
+                let residue = if id == 13 { "NA" } else { "GLY" };
+                AtomData::new(
+                    id,
+                    element,
+                    1 + (id == 13) as u32,
+                    residue.into(),
+                    "A".into(),
+                    element.symbol().into(),
+                )
+            })
+            .collect();
+        let bonds = vec![
+            BondData::new(10, 11, BondType::Covalent, BondOrder::Single, 1.5),
+            BondData::new(11, 12, BondType::Covalent, BondOrder::Single, 1.5),
+        ];
+        let mut trajectory = Trajectory::new(PathBuf::from("run.dcd"), 4, 2.0);
+        for i in 0..10 {
+            let mut frame = FrameData::new(i, i as f32 * 2.0);
+            for (index, atom) in atoms.iter().enumerate() {
+                frame.set_position(index, Vec3::new(i as f32, atom.id as f32, 0.0));
+            }
+            frame.cell = Some(UnitCell::orthorhombic([20.0, 20.0, 20.0]));
+            trajectory.add_frame(frame);
+        }
+        (InMemoryFrameProvider::new(trajectory), atoms, bonds)
+    }
+
+    #[test]
+    fn test_frame_indices() {
+        let options = TrajectoryExportOptions {
+            first_frame: 2,
+            last_frame: Some(8),
+            stride: 3,
+            ..Default::default()
+        };
+        assert_eq!(options.frame_indices(10), vec![2, 5, 8]);
+        assert_eq!(options.frame_indices(6), vec![2, 5]);
+        assert!(options.frame_indices(2).is_empty());
+        assert_eq!(
+            TrajectoryExportOptions::default().frame_indices(3),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_dcd_subset_with_topology() {
+        let (provider, atoms, bonds) = four_atom_trajectory();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("excerpt.dcd");
+        let options = TrajectoryExportOptions {
+            first_frame: 1,
+            stride: 4,
+            atom_ids: Some(vec![13, 11, 12]),
+            ..Default::default()
+        };
+        let mut calls = Vec::new();
+        let summary = export_trajectory(&provider, &atoms, &bonds, &path, &options, |d, t| {
+            calls.push((d, t))
+        })
+        .unwrap();
+        assert_eq!(summary.frames, 3);
+        assert_eq!(summary.atoms, 3);
+        assert_eq!(calls.last(), Some(&(3, 3)));
+
+        let reader = DcdReader::open(&path).unwrap();
+        assert_eq!(reader.num_frames(), 3);
+        assert_eq!(reader.num_atoms(), 3);
+        assert!((reader.time_step() - 8.0).abs() < 1e-3);
+        // Frame 9 of the source; atoms in topology order 11, 12, 13
+        let last = reader.read_frame(2).unwrap();
+        assert_eq!(last.position(0), Some(Vec3::new(9.0, 11.0, 0.0)));
+        assert_eq!(last.position(2), Some(Vec3::new(9.0, 13.0, 0.0)));
+        assert!(last.cell.is_some());
+
+        let topology = summary.topology_path.unwrap();
+        let (_, topology_atoms, topology_bonds) =
+            PDBParser::parse_file_with_atoms(&topology).unwrap();
+        // Only the C-O bond has both atoms in the subset
+        assert_eq!(topology_bonds.len(), 1);
+        let names: Vec<_> = topology_atoms
+            .iter()
+            .map(|a| a.residue_name.as_str())
+            .collect();
+        assert_eq!(names, ["GLY", "GLY", "NA"]);
+    }
+
+    #[test]
+    fn test_export_applies_pbc_transforms() {
+        let (provider, atoms, bonds) = four_atom_trajectory();
+        let sim_data = SimulationData::with_bonds(provider.into_trajectory(), atoms, bonds);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("centered.dcd");
+        let pbc = PbcTransformSettings {
+            center: true,
+            ..Default::default()
+        };
+        let options = TrajectoryExportOptions {
+            last_frame: Some(0),
+            ..Default::default()
+        };
+        export_simulation(&sim_data, &pbc, &path, &options).unwrap();
+
+        // Centroid (0, 11.5, 0) moved to the middle of the 20 Å cell
+        let frame = DcdReader::open(&path).unwrap().read_frame(0).unwrap();
+        let centroid = frame.centroid().unwrap();
+        assert!((centroid - Vec3::splat(10.0)).length() < 1e-4);
+        assert!((frame.position(3).unwrap() - Vec3::new(10.0, 11.5, 10.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_missing_atom_position_fails_every_format() {
+        let (provider, atoms, bonds) = four_atom_trajectory();
+        let mut trajectory = provider.into_trajectory();
+        trajectory.frames[3].positions.truncate(2);
+        let provider = InMemoryFrameProvider::new(trajectory);
+        let dir = tempfile::tempdir().unwrap();
+        for format in [
+            TrajectoryFormat::Dcd,
+            TrajectoryFormat::Xyz,
+            TrajectoryFormat::Pdb,
+            TrajectoryFormat::Gro,
+        ] {
+            let path = dir.path().join(format!("short.{}", format.extension()));
+            let options = TrajectoryExportOptions {
+                format,
+                ..Default::default()
+            };
+            let err = export_trajectory(&provider, &atoms, &bonds, &path, &options, |_, _| {})
+                .unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("Frame 3 has no position for atom 12"),
+                "{format:?}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_frame_text_formats_roundtrip() {
+        let (provider, atoms, bonds) = four_atom_trajectory();
+        let dir = tempfile::tempdir().unwrap();
+        for format in [
+            TrajectoryFormat::Xyz,
+            TrajectoryFormat::Pdb,
+            TrajectoryFormat::Gro,
+        ] {
+            let path = dir.path().join(format!("excerpt.{}", format.extension()));
+            let options = TrajectoryExportOptions {
+                format,
+                last_frame: Some(4),
+                stride: 2,
+                write_topology: false,
+                ..Default::default()
+            };
+            let summary =
+                export_trajectory(&provider, &atoms, &bonds, &path, &options, |_, _| {}).unwrap();
+            assert_eq!(summary.topology_path, None);
+
+            let trajectory = match format {
+                TrajectoryFormat::Xyz => XYZParser::parse_file(&path),
+                TrajectoryFormat::Pdb => PDBParser::parse_file(&path),
+                _ => GroParser::parse_file(&path),
+            }
+            .unwrap();
+            assert_eq!(trajectory.num_frames(), 3, "{format:?}");
+            assert_eq!(trajectory.num_atoms, 4, "{format:?}");
+            let mut frame = trajectory.get_frame(2).unwrap().clone();
+            frame.normalize_units();
+            let mut xs: Vec<f32> = frame.positions.iter().map(|p| p.x).collect();
+            xs.dedup_by(|a, b| (*a - *b).abs() < 1e-3);
+            assert_eq!(xs.len(), 1, "{format:?}");
+            assert!((xs[0] - 4.0).abs() < 1e-3, "{format:?}");
+            assert!(frame.cell.is_some(), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_empty_selection_is_an_error() {
+        let (provider, atoms, bonds) = four_atom_trajectory();
+        let dir = tempfile::tempdir().unwrap();
+        let options = TrajectoryExportOptions {
+            atom_ids: Some(Vec::new()),
+            ..Default::default()
+        };
+        let result = export_trajectory(
+            &provider,
+            &atoms,
+            &bonds,
+            &dir.path().join("none.dcd"),
+            &options,
+            |_, _| {},
+        );
+        assert!(result.is_err());
+    }
+}
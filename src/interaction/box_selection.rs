@@ -3,6 +3,7 @@
 //! Middle-mouse drag selects all atoms whose projected positions fall inside
 //! the rectangle. Hold Shift to add to the current selection.
 
+use crate::core::groups::AtomStyles;
 use crate::interaction::pick_proxy::PickProxyEntities;
 use crate::interaction::selection::{
     AtomDeselectedEvent, AtomSelectedEvent, Selected, SelectionState,
@@ -70,13 +71,17 @@ pub fn atoms_in_screen_rect(
         return Vec::new();
     }
 
-    let positions = index.collect_positions(instanced);
     let mut hits = Vec::new();
 
-    for (&atom_id, &world) in &positions {
-        if let Some(screen) = world_to_window(camera, camera_transform, world) {
-            if rect.contains(screen) {
-                hits.push(atom_id);
+    for (entity_info, mesh) in instanced.iter() {
+        let Some(atom_ids) = index.element_atom_ids.get(&entity_info.element) else {
+            continue;
+        };
+        for (instance, &atom_id) in mesh.instances.iter().zip(atom_ids) {
+            if let Some(screen) = world_to_window(camera, camera_transform, instance.position) {
+                if rect.contains(screen) {
+                    hits.push(atom_id);
+                }
             }
         }
     }
@@ -131,6 +136,7 @@ pub fn handle_box_selection(
     index: Res<InstancedAtomIndex>,
     instanced: Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
     pick_entities: Res<PickProxyEntities>,
+    styles: Res<AtomStyles>,
     window: Query<&Window, With<PrimaryWindow>>,
     camera_q: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
 ) {
@@ -172,7 +178,8 @@ pub fn handle_box_selection(
             return;
         };
 
-        let hit_ids = atoms_in_screen_rect(rect, &index, &instanced, camera, camera_transform);
+        let mut hit_ids = atoms_in_screen_rect(rect, &index, &instanced, camera, camera_transform);
+        hit_ids.retain(|&id| styles.get(id).visible);
 
         if hit_ids.is_empty() {
             return;
@@ -214,7 +221,7 @@ pub fn handle_select_all(
         return;
     }
 
-    let count = sim_data.atom_data.len();
+    let count = sim_data.atom_data().len();
     if count > 10_000 {
         warn!("Selecting all {count} atoms — highlight updates may be slower");
     }
@@ -225,7 +232,7 @@ pub fn handle_select_all(
     }
     selection.clear();
 
-    for atom in &sim_data.atom_data {
+    for atom in sim_data.atom_data() {
         if let Some(&entity) = pick_entities.entities.get(&atom.id) {
             commands.entity(entity).insert(Selected);
             selection.add(entity, atom.id);
@@ -1,9 +1,11 @@
-//! User interaction systems (selection, measurement)
+//! User interaction systems (selection, selection queries, atom visibility, measurement)
 
 pub mod box_selection;
 pub mod measurement;
 pub mod pick_proxy;
 pub mod selection;
+pub mod selection_query;
+pub mod visibility;
 
 use bevy::prelude::*;
 
@@ -12,6 +14,8 @@ pub fn register(app: &mut App) {
     pick_proxy::register(app);
     box_selection::register(app);
     selection::register(app);
+    selection_query::register(app);
+    visibility::register(app);
     measurement::register(app);
 
     info!("Interaction module registered");
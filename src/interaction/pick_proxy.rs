@@ -1,6 +1,7 @@
 //! Invisible pick-proxy entities for atom selection with instanced rendering.
 
 use crate::core::atom::{Atom, AtomData};
+use crate::core::groups::AtomStyles;
 use crate::rendering::atom_index::InstancedAtomIndex;
 use crate::rendering::instanced::InstancedAtomMesh;
 use bevy::prelude::*;
@@ -26,7 +27,7 @@ pub fn spawn_pick_proxies(
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
     atom_data: &[AtomData],
-    frame_positions: &HashMap<u32, Vec3>,
+    frame_positions: &[Vec3],
     max_proxies: usize,
 ) -> (HashMap<u32, Entity>, bool) {
     if atom_data.len() > max_proxies {
@@ -47,11 +48,7 @@ pub fn spawn_pick_proxies(
 
     let mut map = HashMap::new();
 
-    for atom in atom_data {
-        let Some(position) = frame_positions.get(&atom.id).copied() else {
-            continue;
-        };
-
+    for (atom, &position) in atom_data.iter().zip(frame_positions) {
         let entity = commands
             .spawn((
                 PbrBundle {
@@ -87,8 +84,28 @@ pub fn register(app: &mut App) {
     info!("Pick proxy module registered");
 }
 
+/// Hide pick proxies of hidden atoms so they cannot be clicked.
+pub fn update_pick_proxy_visibility(
+    styles: Res<AtomStyles>,
+    pick_entities: Res<PickProxyEntities>,
+    mut pick_query: Query<(&PickProxy, &mut Visibility)>,
+) {
+    if !styles.is_changed() && !pick_entities.is_changed() {
+        return;
+    }
+
+    for (proxy, mut visibility) in pick_query.iter_mut() {
+        visibility.set_if_neq(if styles.get(proxy.atom_id).visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
 /// Move pick proxies when timeline updates instanced positions.
 pub fn update_pick_proxy_positions(
+    sim_data: Res<crate::systems::loading::SimulationData>,
     index: Res<InstancedAtomIndex>,
     instanced: Query<(
         &crate::rendering::instanced::InstancedAtomEntity,
@@ -103,7 +120,10 @@ pub fn update_pick_proxy_positions(
     let positions = index.collect_positions(&instanced);
 
     for (proxy, mut transform) in pick_query.iter_mut() {
-        if let Some(pos) = positions.get(&proxy.atom_id) {
+        if let Some(pos) = sim_data
+            .atom_index(proxy.atom_id)
+            .and_then(|i| positions.get(i))
+        {
             transform.translation = *pos;
         }
     }
@@ -238,14 +238,17 @@ pub fn sync_selection_markers(
     }
 }
 
-/// Clear selection when a new file is loaded
+/// Clear selection when a new file is loaded or its atoms are replaced
 pub fn clear_selection_on_load(
     mut commands: Commands,
     mut selection: ResMut<SelectionState>,
     mut file_loaded_events: EventReader<crate::systems::loading::FileLoadedEvent>,
+    mut topology_events: EventReader<crate::systems::loading::TopologyAppliedEvent>,
     mut cleared_event: EventWriter<SelectionClearedEvent>,
 ) {
-    if file_loaded_events.read().next().is_none() || selection.is_empty() {
+    let reload =
+        file_loaded_events.read().next().is_some() || topology_events.read().next().is_some();
+    if !reload || selection.is_empty() {
         return;
     }
 
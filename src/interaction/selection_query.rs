@@ -0,0 +1,842 @@
+//! Text selection queries (VMD / MDAnalysis style).
+//!
+//! Queries are parsed into a [`SelectionExpr`] tree and evaluated over
+//! `SimulationData::atom_data` with positions from the displayed frame:
+//!
+//! ```text
+//! protein and chain A
+//! resname HOH or (element C N and not backbone)
+//! resid 10 to 50 and name CA
+//! within 5 of resname LIG
+//! same residue as within 4 of resname LIG
+//! ```
+//!
+//! Property keywords (`name`, `resname`, `resid`, `chain`, `element`,
+//! `index`, `id`, `residue`, `fragment`) take one or more values; string
+//! values accept `*` and `?` wildcards and numeric values accept ranges
+//! (`10 to 50`, `10:50`). A value list ends at the next operator or selection
+//! keyword, so `chain A name CA` reads as `chain A and name CA`; quote a value
+//! that is also a keyword (`resname LIG "ION"`). `residue` and `fragment` are
+//! zero-based indices into the [`MoleculeTopology`] residues and
+//! bond-connected molecules, which also decide the `protein` / `ligand` /
+//! `water` classes.
+
+use crate::analysis::molecules::MoleculeTopology;
+use crate::core::atom::{AtomData, Element};
+use crate::core::molecule::MoleculeType;
+use crate::interaction::pick_proxy::PickProxyEntities;
+use crate::interaction::selection::{
+    AtomDeselectedEvent, AtomSelectedEvent, Selected, SelectionState,
+};
+use crate::systems::frame_cache::TimelineFrames;
+use crate::systems::loading::SimulationData;
+use crate::utils::spatial_index::AtomSpatialIndex;
+use bevy::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+/// Protein backbone atom names.
+const PROTEIN_BACKBONE: &[&str] = &["N", "CA", "C", "O", "OXT"];
+
+/// Nucleic acid backbone atom names.
+const NUCLEIC_BACKBONE: &[&str] = &[
+    "P", "OP1", "OP2", "O1P", "O2P", "O5'", "C5'", "C4'", "C3'", "O3'",
+];
+
+/// Parsed selection query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionExpr {
+    All,
+    None,
+    Protein,
+    Nucleic,
+    Backbone,
+    Sidechain,
+    Water,
+    Ion,
+    Solvent,
+    /// Residues that are not polymer, water or ion
+    Ligand,
+    Hydrogen,
+    Name(Vec<String>),
+    ResName(Vec<String>),
+    Chain(Vec<String>),
+    Element(Vec<Element>),
+    ResId(Vec<RangeInclusive<u32>>),
+    /// Zero-based position in the atom list
+    Index(Vec<RangeInclusive<u32>>),
+    /// Atom ID from the file
+    Id(Vec<RangeInclusive<u32>>),
+    /// Zero-based residue index in the molecule topology
+    Residue(Vec<RangeInclusive<u32>>),
+    /// Zero-based bond-connected molecule index
+    Fragment(Vec<RangeInclusive<u32>>),
+    /// Atoms within a distance (Å) of any atom in the inner selection
+    Within(f32, Box<SelectionExpr>),
+    SameResidue(Box<SelectionExpr>),
+    SameChain(Box<SelectionExpr>),
+    SameFragment(Box<SelectionExpr>),
+    Not(Box<SelectionExpr>),
+    And(Box<SelectionExpr>, Box<SelectionExpr>),
+    Or(Box<SelectionExpr>, Box<SelectionExpr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    /// Quoted text, always a value
+    Quoted(String),
+    Open,
+    Close,
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unterminated quote".to_string()),
+                    }
+                }
+                tokens.push(Token::Quoted(word));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Operator words; they never start a selection term.
+fn is_reserved(word: &str) -> bool {
+    matches!(
+        word.to_lowercase().as_str(),
+        "and" | "or" | "not" | "of" | "as" | "&&" | "||" | "!"
+    )
+}
+
+/// Words that start a selection term (see `Parser::parse_primary`).
+fn is_term_keyword(word: &str) -> bool {
+    matches!(
+        word.to_lowercase().as_str(),
+        "all"
+            | "everything"
+            | "none"
+            | "nothing"
+            | "protein"
+            | "nucleic"
+            | "backbone"
+            | "sidechain"
+            | "water"
+            | "waters"
+            | "ion"
+            | "ions"
+            | "solvent"
+            | "ligand"
+            | "hydrogen"
+            | "name"
+            | "resname"
+            | "chain"
+            | "chainid"
+            | "segid"
+            | "element"
+            | "resid"
+            | "resnum"
+            | "index"
+            | "id"
+            | "serial"
+            | "residue"
+            | "fragment"
+            | "within"
+            | "same"
+    )
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_word(&self) -> Option<String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) => Some(w.to_lowercase()),
+            _ => None,
+        }
+    }
+
+    fn next_word(&mut self) -> Option<String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) => {
+                self.pos += 1;
+                Some(w.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        match self.next_word() {
+            Some(w) if w.eq_ignore_ascii_case(keyword) => Ok(()),
+            Some(w) => Err(format!("expected '{keyword}', found '{w}'")),
+            None => Err(format!("expected '{keyword}'")),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<SelectionExpr, String> {
+        let mut expr = self.parse_and()?;
+        while matches!(self.peek_word().as_deref(), Some("or" | "||")) {
+            self.pos += 1;
+            expr = SelectionExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<SelectionExpr, String> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            match self.peek_word().as_deref() {
+                Some("and" | "&&") => {
+                    self.pos += 1;
+                }
+                // Juxtaposition (`protein chain A`) is an implicit `and`.
+                Some(w) if !is_reserved(w) => {}
+                None if matches!(self.tokens.get(self.pos), Some(Token::Open)) => {}
+                _ => break,
+            }
+            expr = SelectionExpr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<SelectionExpr, String> {
+        match self.peek_word().as_deref() {
+            Some("not" | "!") => {
+                self.pos += 1;
+                Ok(SelectionExpr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some("within") => {
+                self.pos += 1;
+                let distance = self
+                    .next_word()
+                    .and_then(|w| w.parse::<f32>().ok())
+                    .filter(|d| *d >= 0.0)
+                    .ok_or("'within' needs a distance in Å")?;
+                self.expect_keyword("of")?;
+                Ok(SelectionExpr::Within(
+                    distance,
+                    Box::new(self.parse_unary()?),
+                ))
+            }
+            Some("same") => {
+                self.pos += 1;
+                let scope = self.next_word().unwrap_or_default().to_lowercase();
+                self.expect_keyword("as")?;
+                let inner = Box::new(self.parse_unary()?);
+                match scope.as_str() {
+                    "residue" => Ok(SelectionExpr::SameResidue(inner)),
+                    "chain" => Ok(SelectionExpr::SameChain(inner)),
+                    "fragment" | "molecule" => Ok(SelectionExpr::SameFragment(inner)),
+                    other => Err(format!("'same {other} as' is not supported")),
+                }
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<SelectionExpr, String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Open) => {
+                self.pos += 1;
+                let expr = self.parse_or()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(expr)
+                    }
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(Token::Close) => Err("unexpected ')'".to_string()),
+            Some(Token::Quoted(w)) => Err(format!("unexpected value \"{w}\"")),
+            None => Err("unexpected end of query".to_string()),
+            Some(Token::Word(_)) => {
+                let word = self.next_word().unwrap_or_default();
+                let keyword = word.to_lowercase();
+                Ok(match keyword.as_str() {
+                    "all" | "everything" => SelectionExpr::All,
+                    "none" | "nothing" => SelectionExpr::None,
+                    "protein" => SelectionExpr::Protein,
+                    "nucleic" => SelectionExpr::Nucleic,
+                    "backbone" => SelectionExpr::Backbone,
+                    "sidechain" => SelectionExpr::Sidechain,
+                    "water" | "waters" => SelectionExpr::Water,
+                    "ion" | "ions" => SelectionExpr::Ion,
+                    "solvent" => SelectionExpr::Solvent,
+                    "ligand" => SelectionExpr::Ligand,
+                    "hydrogen" => SelectionExpr::Hydrogen,
+                    "name" => SelectionExpr::Name(self.string_values(&keyword)?),
+                    "resname" => SelectionExpr::ResName(self.string_values(&keyword)?),
+                    "chain" | "chainid" | "segid" => {
+                        SelectionExpr::Chain(self.string_values(&keyword)?)
+                    }
+                    "element" => SelectionExpr::Element(
+                        self.string_values(&keyword)?
+                            .iter()
+                            .map(|s| Element::from_symbol(s))
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    "resid" | "resnum" => SelectionExpr::ResId(self.numeric_values(&keyword)?),
+                    "index" => SelectionExpr::Index(self.numeric_values(&keyword)?),
+                    "id" | "serial" => SelectionExpr::Id(self.numeric_values(&keyword)?),
+                    "residue" => SelectionExpr::Residue(self.numeric_values(&keyword)?),
+                    "fragment" => SelectionExpr::Fragment(self.numeric_values(&keyword)?),
+                    _ => return Err(format!("unknown keyword '{word}'")),
+                })
+            }
+        }
+    }
+
+    /// Values following a property keyword, up to the next operator,
+    /// selection keyword or paren. The first value may be a keyword
+    /// (`resname ION`); quoted values never end the list.
+    fn raw_values(&mut self, keyword: &str) -> Result<Vec<String>, String> {
+        let mut values = Vec::new();
+        while let Some(token) = self.tokens.get(self.pos) {
+            let value = match token {
+                Token::Quoted(w) => w,
+                Token::Word(w) if !is_reserved(w) && (values.is_empty() || !is_term_keyword(w)) => {
+                    w
+                }
+                _ => break,
+            };
+            values.push(value.clone());
+            self.pos += 1;
+        }
+        if values.is_empty() {
+            return Err(format!("'{keyword}' needs at least one value"));
+        }
+        Ok(values)
+    }
+
+    fn string_values(&mut self, keyword: &str) -> Result<Vec<String>, String> {
+        self.raw_values(keyword)
+    }
+
+    fn numeric_values(&mut self, keyword: &str) -> Result<Vec<RangeInclusive<u32>>, String> {
+        let values = self.raw_values(keyword)?;
+        let parse = |s: &str| {
+            s.parse::<u32>()
+                .map_err(|_| format!("'{keyword}' expects numbers, found '{s}'"))
+        };
+
+        let mut ranges = Vec::new();
+        let mut i = 0;
+        while i < values.len() {
+            let value = &values[i];
+            if let Some((lo, hi)) = value.split_once(':') {
+                ranges.push(parse(lo)?..=parse(hi)?);
+            } else if values
+                .get(i + 1)
+                .is_some_and(|w| w.eq_ignore_ascii_case("to"))
+            {
+                let hi = values
+                    .get(i + 2)
+                    .ok_or_else(|| format!("'{value} to' needs an upper bound"))?;
+                ranges.push(parse(value)?..=parse(hi)?);
+                i += 2;
+            } else {
+                let n = parse(value)?;
+                ranges.push(n..=n);
+            }
+            i += 1;
+        }
+        Ok(ranges)
+    }
+}
+
+/// Match `text` against a pattern with `*` (any run) and `?` (one char).
+fn glob_match(pattern: &str, text: &str) -> bool {
+    if !pattern.contains(['*', '?']) {
+        return pattern == text;
+    }
+
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Shared per-evaluation data (topology, residue classes, spatial index).
+struct EvalContext<'a> {
+    atoms: &'a [AtomData],
+    positions: &'a [Vec3],
+    topology: Cow<'a, MoleculeTopology>,
+    classes: Vec<MoleculeType>,
+    spatial: Option<AtomSpatialIndex>,
+}
+
+impl<'a> EvalContext<'a> {
+    fn new(
+        atoms: &'a [AtomData],
+        positions: &'a [Vec3],
+        topology: Option<&'a MoleculeTopology>,
+    ) -> Self {
+        // A topology built for another structure (e.g. before bonds respawn
+        // after a load) is replaced by one segmented without bonds.
+        let topology = match topology {
+            Some(t) if t.atom_count() == atoms.len() => Cow::Borrowed(t),
+            _ => Cow::Owned(MoleculeTopology::build(atoms, [])),
+        };
+        let classes = atoms.iter().map(|a| topology.atom_class(a.id)).collect();
+        Self {
+            atoms,
+            positions,
+            topology,
+            classes,
+            spatial: None,
+        }
+    }
+
+    fn residue_index(&self, atom: &AtomData) -> Option<u32> {
+        self.topology.residue_index(atom.id).map(|r| r as u32)
+    }
+
+    fn fragment_index(&self, atom: &AtomData) -> Option<u32> {
+        self.topology.molecule_index(atom.id).map(|m| m as u32)
+    }
+
+    fn is_backbone(&self, i: usize) -> bool {
+        let name = self.atoms[i].name.as_str();
+        match self.classes[i] {
+            MoleculeType::Protein => PROTEIN_BACKBONE.contains(&name),
+            MoleculeType::NucleicAcid => NUCLEIC_BACKBONE.contains(&name),
+            _ => false,
+        }
+    }
+
+    fn mask(&mut self, expr: &SelectionExpr) -> Vec<bool> {
+        let atoms = self.atoms;
+        let by_atom = |f: &dyn Fn(usize, &AtomData) -> bool| -> Vec<bool> {
+            atoms.iter().enumerate().map(|(i, a)| f(i, a)).collect()
+        };
+        let in_ranges =
+            |ranges: &[RangeInclusive<u32>], v: u32| ranges.iter().any(|r| r.contains(&v));
+        let any_glob = |patterns: &[String], v: &str| patterns.iter().any(|p| glob_match(p, v));
+
+        match expr {
+            SelectionExpr::All => vec![true; atoms.len()],
+            SelectionExpr::None => vec![false; atoms.len()],
+            SelectionExpr::Protein => by_atom(&|i, _| self.classes[i] == MoleculeType::Protein),
+            SelectionExpr::Nucleic => by_atom(&|i, _| self.classes[i] == MoleculeType::NucleicAcid),
+            SelectionExpr::Backbone => by_atom(&|i, _| self.is_backbone(i)),
+            SelectionExpr::Sidechain => by_atom(&|i, a| {
+                self.classes[i] == MoleculeType::Protein
+                    && !self.is_backbone(i)
+                    && a.element != Element::H
+            }),
+            SelectionExpr::Water => by_atom(&|i, _| self.classes[i] == MoleculeType::Water),
+            SelectionExpr::Ion => by_atom(&|i, _| self.classes[i] == MoleculeType::Ion),
+            SelectionExpr::Solvent => by_atom(&|i, _| self.classes[i].is_solvent()),
+            SelectionExpr::Ligand => by_atom(&|i, _| {
+                matches!(
+                    self.classes[i],
+                    MoleculeType::Unknown | MoleculeType::Ligand | MoleculeType::SmallMolecule
+                )
+            }),
+            SelectionExpr::Hydrogen => by_atom(&|_, a| a.element == Element::H),
+            SelectionExpr::Name(p) => by_atom(&|_, a| any_glob(p, &a.name)),
+            SelectionExpr::ResName(p) => by_atom(&|_, a| any_glob(p, &a.residue_name)),
+            SelectionExpr::Chain(p) => by_atom(&|_, a| any_glob(p, &a.chain_id)),
+            SelectionExpr::Element(e) => by_atom(&|_, a| e.contains(&a.element)),
+            SelectionExpr::ResId(r) => by_atom(&|_, a| in_ranges(r, a.residue_id)),
+            SelectionExpr::Index(r) => by_atom(&|i, _| in_ranges(r, i as u32)),
+            SelectionExpr::Id(r) => by_atom(&|_, a| in_ranges(r, a.id)),
+            SelectionExpr::Residue(r) => {
+                by_atom(&|_, a| self.residue_index(a).is_some_and(|i| in_ranges(r, i)))
+            }
+            SelectionExpr::Fragment(r) => {
+                by_atom(&|_, a| self.fragment_index(a).is_some_and(|i| in_ranges(r, i)))
+            }
+            SelectionExpr::Not(inner) => self.mask(inner).into_iter().map(|m| !m).collect(),
+            SelectionExpr::And(a, b) => {
+                let a = self.mask(a);
+                let b = self.mask(b);
+                a.into_iter().zip(b).map(|(x, y)| x && y).collect()
+            }
+            SelectionExpr::Or(a, b) => {
+                let a = self.mask(a);
+                let b = self.mask(b);
+                a.into_iter().zip(b).map(|(x, y)| x || y).collect()
+            }
+            SelectionExpr::Within(distance, inner) => {
+                let inner = self.mask(inner);
+                let spatial = self
+                    .spatial
+                    .get_or_insert_with(|| AtomSpatialIndex::build(atoms, self.positions));
+                let mut out = vec![false; atoms.len()];
+                for (pos, _) in self.positions.iter().zip(&inner).filter(|(_, m)| **m) {
+                    for j in spatial.neighbor_indices_within(*pos, *distance) {
+                        out[j] = true;
+                    }
+                }
+                out
+            }
+            SelectionExpr::SameResidue(inner) => {
+                let inner = self.mask(inner);
+                let residues: HashSet<(&str, u32)> = atoms
+                    .iter()
+                    .zip(&inner)
+                    .filter(|(_, m)| **m)
+                    .map(|(a, _)| (a.chain_id.as_str(), a.residue_id))
+                    .collect();
+                by_atom(&|_, a| residues.contains(&(a.chain_id.as_str(), a.residue_id)))
+            }
+            SelectionExpr::SameChain(inner) => {
+                let inner = self.mask(inner);
+                let chains: HashSet<&str> = atoms
+                    .iter()
+                    .zip(&inner)
+                    .filter(|(_, m)| **m)
+                    .map(|(a, _)| a.chain_id.as_str())
+                    .collect();
+                by_atom(&|_, a| chains.contains(a.chain_id.as_str()))
+            }
+            SelectionExpr::SameFragment(inner) => {
+                let inner = self.mask(inner);
+                let fragments: HashSet<u32> = atoms
+                    .iter()
+                    .zip(&inner)
+                    .filter(|(_, m)| **m)
+                    .filter_map(|(a, _)| self.fragment_index(a))
+                    .collect();
+                by_atom(&|_, a| {
+                    self.fragment_index(a)
+                        .is_some_and(|i| fragments.contains(&i))
+                })
+            }
+        }
+    }
+}
+
+impl SelectionExpr {
+    /// Parse a query string.
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let tokens = tokenize(query)?;
+        if tokens.is_empty() {
+            return Err("empty query".to_string());
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(match &parser.tokens[parser.pos] {
+                Token::Word(w) => format!("unexpected '{w}'"),
+                Token::Quoted(w) => format!("unexpected value \"{w}\""),
+                Token::Open => "unexpected '('".to_string(),
+                Token::Close => "unexpected ')'".to_string(),
+            });
+        }
+        Ok(expr)
+    }
+
+    /// IDs of atoms matching this expression, in atom order.
+    ///
+    /// Residues and fragments are segmented from `atoms` alone (no bonds);
+    /// use [`SelectionExpr::select_in`] with the loaded topology instead when
+    /// bond-connected fragments matter.
+    pub fn select(&self, atoms: &[AtomData], positions: &[Vec3]) -> Vec<u32> {
+        self.evaluate(atoms, positions, None)
+    }
+
+    /// Like [`SelectionExpr::select`], with residues, fragments and molecule
+    /// classes taken from `topology`.
+    pub fn select_in(
+        &self,
+        atoms: &[AtomData],
+        positions: &[Vec3],
+        topology: &MoleculeTopology,
+    ) -> Vec<u32> {
+        self.evaluate(atoms, positions, Some(topology))
+    }
+
+    fn evaluate(
+        &self,
+        atoms: &[AtomData],
+        positions: &[Vec3],
+        topology: Option<&MoleculeTopology>,
+    ) -> Vec<u32> {
+        let mask = EvalContext::new(atoms, positions, topology).mask(self);
+        atoms
+            .iter()
+            .zip(mask)
+            .filter(|(_, m)| *m)
+            .map(|(a, _)| a.id)
+            .collect()
+    }
+}
+
+/// Query text and last result shown in the Selection panel.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct SelectionQueryState {
+    pub text: String,
+    pub error: Option<String>,
+    pub last_count: Option<usize>,
+}
+
+/// Request to replace (or extend) the selection with a query result.
+#[derive(Event, Debug, Clone)]
+pub struct ApplySelectionQueryEvent {
+    pub query: String,
+    pub add_to_selection: bool,
+}
+
+/// Evaluate queued queries against the displayed frame and update the selection.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_selection_query(
+    mut commands: Commands,
+    mut events: EventReader<ApplySelectionQueryEvent>,
+    mut query_state: ResMut<SelectionQueryState>,
+    mut selection: ResMut<SelectionState>,
+    mut selected_events: EventWriter<AtomSelectedEvent>,
+    mut deselected_events: EventWriter<AtomDeselectedEvent>,
+    sim_data: Res<SimulationData>,
+    frames: Res<TimelineFrames>,
+    pick_entities: Res<PickProxyEntities>,
+    topology: Res<MoleculeTopology>,
+) {
+    for event in events.read() {
+        if !sim_data.loaded {
+            continue;
+        }
+
+        let expr = match SelectionExpr::parse(&event.query) {
+            Ok(expr) => expr,
+            Err(err) => {
+                query_state.error = Some(err);
+                query_state.last_count = None;
+                continue;
+            }
+        };
+
+        let positions = frames
+            .current
+            .as_ref()
+            .map(|f| f.positions.clone())
+            .or_else(|| sim_data.get_frame(0).map(|f| f.positions))
+            .unwrap_or_default();
+        let start = std::time::Instant::now();
+        let hit_ids = expr.select_in(sim_data.atom_data(), &positions, &topology);
+
+        if !event.add_to_selection {
+            for entity in selection.entities().to_vec() {
+                commands.entity(entity).remove::<Selected>();
+                deselected_events.send(AtomDeselectedEvent { entity });
+            }
+            selection.clear();
+        }
+
+        let already: HashSet<u32> = selection.selected_atom_ids.iter().copied().collect();
+        for &atom_id in &hit_ids {
+            if already.contains(&atom_id) {
+                continue;
+            }
+            if let Some(&entity) = pick_entities.entities.get(&atom_id) {
+                commands.entity(entity).insert(Selected);
+                selection.selected_entities.push(entity);
+                selection.selected_atom_ids.push(atom_id);
+                selection.last_selected = Some(entity);
+                selected_events.send(AtomSelectedEvent { entity });
+            } else {
+                selection.selected_atom_ids.push(atom_id);
+            }
+        }
+
+        query_state.error = None;
+        query_state.last_count = Some(hit_ids.len());
+        info!(
+            "Selection query '{}': {} atoms in {:.1} ms",
+            event.query,
+            hit_ids.len(),
+            start.elapsed().as_secs_f32() * 1000.0
+        );
+    }
+}
+
+pub fn register(app: &mut App) {
+    app.init_resource::<SelectionQueryState>()
+        .add_event::<ApplySelectionQueryEvent>()
+        .add_systems(Update, handle_selection_query);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(
+        id: u32,
+        element: Element,
+        resid: u32,
+        resname: &str,
+        chain: &str,
+        name: &str,
+    ) -> AtomData {
+        AtomData::new(
+            id,
+            element,
+            resid,
+            resname.into(),
+            chain.into(),
+            name.into(),
+        )
+    }
+
+    fn system() -> (Vec<AtomData>, Vec<Vec3>) {
+        let atoms = vec![
+            atom(0, Element::N, 10, "ALA", "A", "N"),
+            atom(1, Element::C, 10, "ALA", "A", "CA"),
+            atom(2, Element::C, 10, "ALA", "A", "CB"),
+            atom(3, Element::C, 60, "GLY", "B", "CA"),
+            atom(4, Element::C, 100, "LIG", "A", "C1"),
+            atom(5, Element::O, 200, "HOH", "W", "O"),
+            atom(6, Element::Na, 300, "NA", "I", "NA"),
+        ];
+        let positions = atoms
+            .iter()
+            .map(|a| Vec3::new(a.id as f32 * 2.0, 0.0, 0.0))
+            .collect();
+        (atoms, positions)
+    }
+
+    fn select(query: &str) -> Vec<u32> {
+        let (atoms, positions) = system();
+        SelectionExpr::parse(query)
+            .unwrap()
+            .select(&atoms, &positions)
+    }
+
+    #[test]
+    fn test_keywords_and_properties() {
+        assert_eq!(select("protein and chain A"), vec![0, 1, 2]);
+        assert_eq!(select("resname HOH"), vec![5]);
+        assert_eq!(select("name CA"), vec![1, 3]);
+        assert_eq!(select("resid 10 to 50"), vec![0, 1, 2]);
+        assert_eq!(select("resid 50:300 and not ion"), vec![3, 4, 5]);
+        assert_eq!(select("element C N"), vec![0, 1, 2, 3, 4]);
+        assert_eq!(select("solvent"), vec![5, 6]);
+        assert_eq!(select("ligand"), vec![4]);
+        assert_eq!(select("backbone"), vec![0, 1, 3]);
+        assert_eq!(select("name C*"), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_boolean_precedence_and_parens() {
+        assert_eq!(select("water or ion and name NA"), vec![5, 6]);
+        assert_eq!(select("(water or ion) and not name NA"), vec![5]);
+        assert_eq!(select("protein chain B"), vec![3]);
+    }
+
+    #[test]
+    fn test_value_lists_end_at_keywords() {
+        assert_eq!(select("chain A name CA"), vec![1]);
+        assert_eq!(select("resid 10 to 50 name CA"), vec![1]);
+        assert_eq!(select("name CA CB resid 10"), vec![1, 2]);
+        assert_eq!(
+            SelectionExpr::parse("chain A name CA").unwrap(),
+            SelectionExpr::parse("chain A and name CA").unwrap()
+        );
+        // A keyword is a value in first place or when quoted.
+        assert_eq!(
+            SelectionExpr::parse("resname ION").unwrap(),
+            SelectionExpr::ResName(vec!["ION".into()])
+        );
+        assert_eq!(
+            SelectionExpr::parse("resname LIG \"ion\"").unwrap(),
+            SelectionExpr::ResName(vec!["LIG".into(), "ion".into()])
+        );
+    }
+
+    #[test]
+    fn test_within_and_same_residue() {
+        // LIG at x = 8; atoms 3 (x = 6) and 5 (x = 10) are 2 Å away.
+        assert_eq!(select("within 2.5 of resname LIG"), vec![3, 4, 5]);
+        assert_eq!(
+            select("not resname LIG and within 2.5 of resname LIG"),
+            vec![3, 5]
+        );
+        assert_eq!(select("same residue as name CB"), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_residue_and_fragment_keywords() {
+        let (atoms, positions) = system();
+        // Ligand covalently bound to the ALA side chain.
+        let topology = MoleculeTopology::build(&atoms, [(2, 4)]);
+        let select_in = |query: &str| {
+            SelectionExpr::parse(query)
+                .unwrap()
+                .select_in(&atoms, &positions, &topology)
+        };
+        assert_eq!(select_in("residue 1"), vec![3]);
+        assert_eq!(select_in("fragment 0"), vec![0, 1, 2, 4]);
+        assert_eq!(select_in("same fragment as resname LIG"), vec![0, 1, 2, 4]);
+        assert_eq!(select_in("ligand"), vec![4]);
+        // Without bonds every residue outside a polymer stays separate.
+        assert_eq!(select("same fragment as resname LIG"), vec![4]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(SelectionExpr::parse("").is_err());
+        assert!(SelectionExpr::parse("resid abc").is_err());
+        assert!(SelectionExpr::parse("(protein").is_err());
+        assert!(SelectionExpr::parse("frobnicate").is_err());
+        assert!(SelectionExpr::parse("within of protein").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! Hide / show atoms by selection or molecule class.
+//!
+//! Actions edit `HiddenAtoms`; the renderers, frustum culling, bonds, pick
+//! proxies and exports pick the change up through `AtomStyles`.
+
+use crate::core::atom::AtomData;
+use crate::core::groups::HiddenAtoms;
+use crate::core::molecule::MoleculeType;
+use crate::interaction::selection::SelectionState;
+use crate::systems::loading::SimulationData;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+/// Request to change which atoms are hidden.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtomVisibilityAction {
+    /// Hide the selected atoms
+    HideSelection,
+    /// Hide every atom that is not selected
+    ShowOnlySelection,
+    /// Hide water and ions
+    HideSolvent,
+    ShowAll,
+}
+
+/// IDs of water and ion atoms, classified by residue name.
+pub fn solvent_atom_ids(atoms: &[AtomData]) -> Vec<u32> {
+    atoms
+        .iter()
+        .filter(|a| MoleculeType::from_residue_name(&a.residue_name).is_solvent())
+        .map(|a| a.id)
+        .collect()
+}
+
+/// Apply queued hide/show actions.
+pub fn handle_atom_visibility_actions(
+    mut actions: EventReader<AtomVisibilityAction>,
+    selection: Res<SelectionState>,
+    sim_data: Res<SimulationData>,
+    mut hidden: ResMut<HiddenAtoms>,
+) {
+    for action in actions.read() {
+        match action {
+            AtomVisibilityAction::HideSelection => {
+                hidden.hide(selection.atom_ids().iter().copied());
+            }
+            AtomVisibilityAction::ShowOnlySelection => {
+                let keep: HashSet<u32> = selection.atom_ids().iter().copied().collect();
+                hidden.set(
+                    sim_data
+                        .atom_data()
+                        .iter()
+                        .map(|a| a.id)
+                        .filter(|id| !keep.contains(id)),
+                );
+            }
+            AtomVisibilityAction::HideSolvent => {
+                hidden.hide(solvent_atom_ids(sim_data.atom_data()));
+            }
+            AtomVisibilityAction::ShowAll => hidden.clear(),
+        }
+        info!("{:?}: {} atoms hidden", action, hidden.len());
+    }
+}
+
+/// Hidden atom IDs refer to the previous structure; show everything on load.
+pub fn clear_hidden_atoms_on_load(
+    mut hidden: ResMut<HiddenAtoms>,
+    mut file_loaded_events: EventReader<crate::systems::loading::FileLoadedEvent>,
+    mut topology_events: EventReader<crate::systems::loading::TopologyAppliedEvent>,
+) {
+    let reload =
+        file_loaded_events.read().next().is_some() || topology_events.read().next().is_some();
+    if reload && !hidden.is_empty() {
+        hidden.clear();
+    }
+}
+
+pub fn register(app: &mut App) {
+    app.add_event::<AtomVisibilityAction>()
+        .add_systems(Update, handle_atom_visibility_actions);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::atom::Element;
+
+    #[test]
+    fn test_solvent_atom_ids_picks_water_and_ions() {
+        let atoms: Vec<AtomData> = [
+            ("ALA", Element::C),
+            ("HOH", Element::O),
+            ("NA", Element::Na),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, (resname, element))| {
+            AtomData::new(
+                i as u32,
+                *element,
+                i as u32 + 1,
+                resname.to_string(),
+                "A".into(),
+                "X".into(),
+            )
+        })
+        .collect();
+        assert_eq!(solvent_atom_ids(&atoms), vec![1, 2]);
+    }
+}
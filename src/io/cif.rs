@@ -0,0 +1,594 @@
+//! CIF 1.1 / 2.0 tokenizer and category model
+//!
+//! A CIF file is a sequence of `data_` blocks holding tag/value pairs and
+//! `loop_` tables. Tags are `_category.column` in mmCIF (DDL2); tags without
+//! a dot (DDL1 small-molecule CIF) are stored under the empty category.
+//!
+//! Values can be:
+//! - bare tokens; a bare `.` (inapplicable) or `?` (unknown) is null
+//! - quoted with `'` or `"`; the closing quote must be followed by
+//!   whitespace, so `'O5'` and `'N,N'-dimethyl'` both work
+//! - `;`-delimited text fields spanning lines, opened and closed by a `;`
+//!   in the first column
+//! - CIF 2.0 triple-quoted strings (`'''`/`"""`) and `[...]` lists or
+//!   `{...}` tables, kept as raw text
+//!
+//! `#` starts a comment anywhere a token could start. `save_` frames and
+//! `global_` / `stop_` markers only appear in dictionaries and are ignored.
+
+use crate::io::{IOError, IOResult};
+use std::collections::HashMap;
+
+/// A single CIF value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CifValue {
+    /// Bare, quoted or text-field value
+    Text(String),
+    /// Bare `.`: not applicable
+    Inapplicable,
+    /// Bare `?`: unknown
+    Unknown,
+}
+
+impl CifValue {
+    /// Text of the value, or `None` for `.` and `?`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            CifValue::Text(s) => Some(s),
+            CifValue::Inapplicable | CifValue::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    DataBlock(String),
+    Loop,
+    Tag(String),
+    Value(CifValue),
+}
+
+/// Byte-level scanner; every delimiter is ASCII, so slicing at them keeps
+/// UTF-8 (allowed in CIF 2.0) intact.
+struct Tokenizer<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(text: &'a str) -> Self {
+        // CIF 2.0 files start with a magic comment; a BOM may precede it
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        Self {
+            text,
+            bytes: text.as_bytes(),
+            pos: 0,
+            line: 1,
+        }
+    }
+
+    fn error(&self, line: usize, message: impl Into<String>) -> IOError {
+        IOError::ParseError {
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn at_line_start(&self, pos: usize) -> bool {
+        pos == 0 || self.bytes[pos - 1] == b'\n'
+    }
+
+    fn is_space(b: u8) -> bool {
+        matches!(b, b' ' | b'\t' | b'\r' | b'\n')
+    }
+
+    /// Advance to `end`, counting newlines on the way.
+    fn advance_to(&mut self, end: usize) {
+        self.line += self.bytes[self.pos..end]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count();
+        self.pos = end;
+    }
+
+    fn find(&self, from: usize, pattern: &[u8]) -> Option<usize> {
+        self.bytes[from..]
+            .windows(pattern.len())
+            .position(|w| w == pattern)
+            .map(|p| from + p)
+    }
+
+    /// Next token and the line it starts on.
+    fn next_token(&mut self) -> IOResult<Option<(Token, usize)>> {
+        loop {
+            while self.pos < self.bytes.len() && Self::is_space(self.bytes[self.pos]) {
+                if self.bytes[self.pos] == b'\n' {
+                    self.line += 1;
+                }
+                self.pos += 1;
+            }
+            if self.pos >= self.bytes.len() {
+                return Ok(None);
+            }
+
+            let start = self.pos;
+            let line = self.line;
+            let c = self.bytes[start];
+
+            if c == b'#' {
+                self.pos = self.find(start, b"\n").unwrap_or(self.bytes.len());
+                continue;
+            }
+
+            if c == b';' && self.at_line_start(start) {
+                let end = self
+                    .find(start, b"\n;")
+                    .ok_or_else(|| self.error(line, "Unterminated ;-delimited text field"))?;
+                let raw = &self.text[start + 1..end];
+                let raw = raw
+                    .strip_prefix("\r\n")
+                    .or_else(|| raw.strip_prefix('\n'))
+                    .unwrap_or(raw);
+                let value = raw.trim_end().replace("\r\n", "\n");
+                self.advance_to(end + 2);
+                return Ok(Some((Token::Value(CifValue::Text(value)), line)));
+            }
+
+            if c == b'\'' || c == b'"' {
+                let triple = [c, c, c];
+                if self.bytes[start..].starts_with(&triple) {
+                    let end = self
+                        .find(start + 3, &triple)
+                        .ok_or_else(|| self.error(line, "Unterminated triple-quoted string"))?;
+                    let value = self.text[start + 3..end].to_string();
+                    self.advance_to(end + 3);
+                    return Ok(Some((Token::Value(CifValue::Text(value)), line)));
+                }
+
+                // The closing quote is one followed by whitespace or end of input
+                let mut end = start + 1;
+                loop {
+                    match self.bytes.get(end) {
+                        None | Some(b'\n') => {
+                            return Err(self.error(line, "Unterminated quoted string"));
+                        }
+                        Some(&b)
+                            if b == c
+                                && self
+                                    .bytes
+                                    .get(end + 1)
+                                    .map_or(true, |&next| Self::is_space(next)) =>
+                        {
+                            break;
+                        }
+                        _ => end += 1,
+                    }
+                }
+                let value = self.text[start + 1..end].to_string();
+                self.pos = end + 1;
+                return Ok(Some((Token::Value(CifValue::Text(value)), line)));
+            }
+
+            if c == b'[' || c == b'{' {
+                let end = self.bracketed_end(start, line)?;
+                let value = self.text[start..end].to_string();
+                self.advance_to(end);
+                return Ok(Some((Token::Value(CifValue::Text(value)), line)));
+            }
+
+            let mut end = start;
+            while end < self.bytes.len() && !Self::is_space(self.bytes[end]) {
+                end += 1;
+            }
+            self.pos = end;
+            let word = &self.text[start..end];
+
+            let token = if word.len() >= 5 && word[..5].eq_ignore_ascii_case("data_") {
+                Token::DataBlock(word[5..].to_string())
+            } else if word.eq_ignore_ascii_case("loop_") {
+                Token::Loop
+            } else if (word.len() >= 5 && word[..5].eq_ignore_ascii_case("save_"))
+                || word.eq_ignore_ascii_case("global_")
+                || word.eq_ignore_ascii_case("stop_")
+            {
+                continue;
+            } else if word.starts_with('_') {
+                Token::Tag(word.to_string())
+            } else if word == "." {
+                Token::Value(CifValue::Inapplicable)
+            } else if word == "?" {
+                Token::Value(CifValue::Unknown)
+            } else {
+                Token::Value(CifValue::Text(word.to_string()))
+            };
+            return Ok(Some((token, line)));
+        }
+    }
+
+    /// End (exclusive) of a CIF 2.0 list or table starting at `start`.
+    fn bracketed_end(&self, start: usize, line: usize) -> IOResult<usize> {
+        let mut depth = 0usize;
+        let mut quote: Option<u8> = None;
+        for (i, &b) in self.bytes.iter().enumerate().skip(start) {
+            match (quote, b) {
+                (Some(q), _) if b == q => quote = None,
+                (Some(_), _) => {}
+                (None, b'\'' | b'"') => quote = Some(b),
+                (None, b'[' | b'{') => depth += 1,
+                (None, b']' | b'}') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(i + 1);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(self.error(line, "Unterminated CIF 2.0 list or table"))
+    }
+}
+
+/// Split `_category.column` into its parts (without the leading `_`).
+fn split_tag(tag: &str) -> (&str, &str) {
+    let tag = tag.strip_prefix('_').unwrap_or(tag);
+    tag.split_once('.').unwrap_or(("", tag))
+}
+
+/// One category: a table with named columns, stored row-major.
+///
+/// Key-value items outside a `loop_` form a single-row category.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CifCategory {
+    /// Category name without the leading `_`
+    pub name: String,
+    /// Column names
+    pub columns: Vec<String>,
+    values: Vec<CifValue>,
+}
+
+impl CifCategory {
+    /// Number of rows.
+    pub fn num_rows(&self) -> usize {
+        if self.columns.is_empty() {
+            0
+        } else {
+            self.values.len() / self.columns.len()
+        }
+    }
+
+    /// Index of a column (tags are case-insensitive).
+    pub fn column_index(&self, column: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(column))
+    }
+
+    /// Value at a row and column index.
+    pub fn value_at(&self, row: usize, column: usize) -> &CifValue {
+        &self.values[row * self.columns.len() + column]
+    }
+
+    /// Text at a row and column, or `None` when missing, `.` or `?`.
+    pub fn get(&self, row: usize, column: &str) -> Option<&str> {
+        if row >= self.num_rows() {
+            return None;
+        }
+        let index = self.column_index(column)?;
+        self.value_at(row, index).as_str()
+    }
+
+    /// Text of a column in the first row, for key-value categories.
+    pub fn first(&self, column: &str) -> Option<&str> {
+        self.get(0, column)
+    }
+
+    /// Iterate over rows.
+    pub fn rows(&self) -> impl Iterator<Item = CifRow<'_>> {
+        (0..self.num_rows()).map(move |index| CifRow {
+            category: self,
+            index,
+        })
+    }
+}
+
+/// A row of a [`CifCategory`].
+#[derive(Debug, Clone, Copy)]
+pub struct CifRow<'a> {
+    category: &'a CifCategory,
+    index: usize,
+}
+
+impl<'a> CifRow<'a> {
+    /// Row number within the category.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Text of a column, or `None` when missing, `.` or `?`.
+    pub fn get(&self, column: &str) -> Option<&'a str> {
+        let column = self.category.column_index(column)?;
+        self.category.value_at(self.index, column).as_str()
+    }
+
+    /// Text of the first present column among `columns`.
+    pub fn get_any(&self, columns: &[&str]) -> Option<&'a str> {
+        columns.iter().find_map(|c| self.get(c))
+    }
+}
+
+/// A `data_` block.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CifBlock {
+    /// Block name (text after `data_`)
+    pub name: String,
+    categories: Vec<CifCategory>,
+    index: HashMap<String, usize>,
+}
+
+impl CifBlock {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            ..Default::default()
+        }
+    }
+
+    /// Category by name (without the leading `_`, case-insensitive).
+    pub fn category(&self, name: &str) -> Option<&CifCategory> {
+        self.index
+            .get(&name.to_ascii_lowercase())
+            .map(|&i| &self.categories[i])
+    }
+
+    /// Text of `_category.column` in the first row.
+    pub fn value(&self, category: &str, column: &str) -> Option<&str> {
+        self.category(category)?.first(column)
+    }
+
+    /// All categories in file order.
+    pub fn categories(&self) -> impl Iterator<Item = &CifCategory> {
+        self.categories.iter()
+    }
+
+    fn category_mut(&mut self, name: &str) -> &mut CifCategory {
+        let key = name.to_ascii_lowercase();
+        let i = match self.index.get(&key) {
+            Some(&i) => i,
+            None => {
+                self.categories.push(CifCategory {
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+                self.index.insert(key, self.categories.len() - 1);
+                self.categories.len() - 1
+            }
+        };
+        &mut self.categories[i]
+    }
+}
+
+/// Parse CIF content into its data blocks.
+///
+/// Items before the first `data_` line go into an unnamed block.
+pub fn parse_cif(content: &str) -> IOResult<Vec<CifBlock>> {
+    let mut tokenizer = Tokenizer::new(content);
+    let mut blocks: Vec<CifBlock> = Vec::new();
+    let mut pending = tokenizer.next_token()?;
+
+    fn current(blocks: &mut Vec<CifBlock>) -> &mut CifBlock {
+        if blocks.is_empty() {
+            blocks.push(CifBlock::default());
+        }
+        blocks.last_mut().unwrap()
+    }
+
+    while let Some((token, line)) = pending.take() {
+        match token {
+            Token::DataBlock(name) => {
+                blocks.push(CifBlock::new(name));
+                pending = tokenizer.next_token()?;
+            }
+            Token::Loop => {
+                let mut tags = Vec::new();
+                pending = tokenizer.next_token()?;
+                while let Some((Token::Tag(tag), _)) = &pending {
+                    tags.push(tag.clone());
+                    pending = tokenizer.next_token()?;
+                }
+                let mut values = Vec::new();
+                while let Some((Token::Value(value), _)) = &pending {
+                    values.push(value.clone());
+                    pending = tokenizer.next_token()?;
+                }
+
+                if tags.is_empty() {
+                    return Err(tokenizer.error(line, "loop_ without tags"));
+                }
+                if values.len() % tags.len() != 0 {
+                    return Err(tokenizer.error(
+                        line,
+                        format!(
+                            "loop_ has {} values for {} columns",
+                            values.len(),
+                            tags.len()
+                        ),
+                    ));
+                }
+
+                let (category_name, _) = split_tag(&tags[0]);
+                let category_name = category_name.to_string();
+                let mut columns = Vec::with_capacity(tags.len());
+                for tag in &tags {
+                    let (category, column) = split_tag(tag);
+                    if !category.eq_ignore_ascii_case(&category_name) {
+                        return Err(tokenizer.error(
+                            line,
+                            format!("loop_ mixes categories {category_name} and {category}"),
+                        ));
+                    }
+                    columns.push(column.to_string());
+                }
+
+                let category = current(&mut blocks).category_mut(&category_name);
+                if category.columns.is_empty() {
+                    category.columns = columns;
+                } else if category.columns != columns {
+                    return Err(
+                        tokenizer.error(line, format!("Category {category_name} is defined twice"))
+                    );
+                }
+                category.values.extend(values);
+            }
+            Token::Tag(tag) => {
+                let mut value = match tokenizer.next_token()? {
+                    Some((Token::Value(value), _)) => value,
+                    _ => {
+                        return Err(tokenizer.error(line, format!("Tag {tag} has no value")));
+                    }
+                };
+                pending = tokenizer.next_token()?;
+
+                // Leniency for hand-written files: unquoted text with spaces
+                // (`_struct.title Test structure`) is rejoined.
+                while let Some((Token::Value(extra), extra_line)) = &pending {
+                    if *extra_line != line {
+                        break;
+                    }
+                    let joined = format!(
+                        "{} {}",
+                        value.as_str().unwrap_or(""),
+                        extra.as_str().unwrap_or("")
+                    );
+                    value = CifValue::Text(joined);
+                    pending = tokenizer.next_token()?;
+                }
+
+                let (category_name, column) = split_tag(&tag);
+                let category = current(&mut blocks).category_mut(category_name);
+                if category.num_rows() > 1 {
+                    return Err(
+                        tokenizer.error(line, format!("Tag {tag} repeats a looped category"))
+                    );
+                }
+                if category.column_index(column).is_some() {
+                    return Err(tokenizer.error(line, format!("Tag {tag} is defined twice")));
+                }
+                category.columns.push(column.to_string());
+                category.values.push(value);
+            }
+            Token::Value(_) => {
+                return Err(tokenizer.error(line, "Value without a tag"));
+            }
+        }
+    }
+
+    Ok(blocks)
+}
+
+/// Format a value for writing, quoting it when needed.
+pub fn format_value(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.chars().any(char::is_whitespace)
+        || value.starts_with(['_', '#', '$', '\'', '"', '[', ']', '{', '}', ';'])
+        || value == "."
+        || value == "?"
+        || ["data_", "loop_", "save_", "global_", "stop_"]
+            .iter()
+            .any(|w| value.len() >= w.len() && value[..w.len()].eq_ignore_ascii_case(w));
+    if !needs_quotes {
+        return value.to_string();
+    }
+    if value.contains('\n') {
+        format!("\n;{value}\n;")
+    } else if !value.contains("' ") && !value.ends_with('\'') {
+        format!("'{value}'")
+    } else if !value.contains("\" ") && !value.ends_with('"') {
+        format!("\"{value}\"")
+    } else {
+        format!("\n;{value}\n;")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted_values_and_nulls() {
+        let blocks = parse_cif(
+            "data_q\n\
+             _struct.title 'It''s a \"test\" structure'\n\
+             _chem_comp.name \"N,N'-dimethyl\" # comment\n\
+             _chem_comp.id  ?\n\
+             _chem_comp.type .\n\
+             _chem_comp.formula '?'\n",
+        )
+        .unwrap();
+        let block = &blocks[0];
+        assert_eq!(block.name, "q");
+        assert_eq!(
+            block.value("struct", "title"),
+            Some("It''s a \"test\" structure")
+        );
+        assert_eq!(block.value("chem_comp", "name"), Some("N,N'-dimethyl"));
+        assert_eq!(block.value("chem_comp", "id"), None);
+        assert_eq!(block.value("chem_comp", "type"), None);
+        assert_eq!(block.value("chem_comp", "formula"), Some("?"));
+    }
+
+    #[test]
+    fn test_text_fields_and_loops() {
+        let content = "data_t\n\
+            loop_\n\
+            _citation.id\n\
+            _citation.title\n\
+            primary\n\
+            ;Water structure of a hydrophobic protein\n\
+            at atomic resolution\n\
+            ;\n\
+            2 'Second paper'\n\
+            loop_\n\
+            _atom_site.id\n\
+            _atom_site.label_atom_id\n\
+            _atom_site.Cartn_x\n\
+            1 \"O5'\" 1.0 2 C5' -2.5\n";
+        let blocks = parse_cif(content).unwrap();
+        let citation = blocks[0].category("citation").unwrap();
+        assert_eq!(citation.num_rows(), 2);
+        assert_eq!(
+            citation.get(0, "title"),
+            Some("Water structure of a hydrophobic protein\nat atomic resolution")
+        );
+        assert_eq!(citation.get(1, "title"), Some("Second paper"));
+
+        let atoms = blocks[0].category("ATOM_SITE").unwrap();
+        let names: Vec<_> = atoms.rows().map(|r| r.get("label_atom_id")).collect();
+        assert_eq!(names, vec![Some("O5'"), Some("C5'")]);
+        assert_eq!(atoms.get(1, "cartn_x"), Some("-2.5"));
+    }
+
+    #[test]
+    fn test_cif2_and_errors() {
+        let blocks =
+            parse_cif("#\\#CIF_2.0\ndata_a\n_x.list [1 'a b' [2 3]]\n_x.text '''multi\nline'''\n")
+                .unwrap();
+        assert_eq!(blocks[0].value("x", "list"), Some("[1 'a b' [2 3]]"));
+        assert_eq!(blocks[0].value("x", "text"), Some("multi\nline"));
+
+        assert!(parse_cif("data_a\nloop_\n_a.x\n_a.y\n1 2 3\n").is_err());
+        assert!(parse_cif("data_a\n_a.x\n").is_err());
+        assert!(parse_cif("data_a\n_a.x ;never closed\n").is_ok());
+        assert!(parse_cif("data_a\n_a.x\n;never closed\n").is_err());
+    }
+
+    #[test]
+    fn test_format_value_round_trip() {
+        for text in ["plain", "two words", "O5'", "it' s", "", "?", "data_x"] {
+            let content = format!("data_r\n_r.v {}\n", format_value(text));
+            let blocks = parse_cif(&content).unwrap();
+            assert_eq!(blocks[0].value("r", "v"), Some(text), "{content}");
+        }
+    }
+}
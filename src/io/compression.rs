@@ -0,0 +1,352 @@
+//! Transparent decompression of gzip, bzip2 and xz input files
+//!
+//! Compression is detected from magic bytes, so a gzipped file loads whether
+//! it is called `1abc.pdb.gz` or `1abc.pdb`; the format itself comes from the
+//! name with the compression suffix removed ([`strip_compression_extension`]).
+//! Text parsers read through [`open`], which decompresses on the fly.
+//! Seekable readers (streamed trajectories, memory-mapped parsers) use
+//! [`seekable_path`], which decompresses once into a cache file under the
+//! system temp directory and reuses it while the source is unchanged. The
+//! cache is capped at [`CACHE_BUDGET_BYTES`]; the least recently used copies
+//! are deleted when a new one pushes it over.
+
+use crate::io::{IOError, IOResult};
+use std::borrow::Cow;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Compression wrapper around an input file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    /// Compression indicated by the first bytes of a file.
+    pub fn from_magic(data: &[u8]) -> Self {
+        if data.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if data.starts_with(b"BZh") {
+            Compression::Bzip2
+        } else if data.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Compression indicated by the last extension (`.gz`, `.bz2`, `.xz`).
+    pub fn from_extension(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match ext.as_deref() {
+            Some("gz") | Some("gzip") => Compression::Gzip,
+            Some("bz2") | Some("bzip2") => Compression::Bzip2,
+            Some("xz") => Compression::Xz,
+            _ => Compression::None,
+        }
+    }
+
+    /// Detect compression from the file's magic bytes.
+    pub fn detect(path: &Path) -> IOResult<Self> {
+        let mut file =
+            File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+        let mut magic = [0u8; 6];
+        let mut len = 0;
+        while len < magic.len() {
+            match file.read(&mut magic[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        Ok(Self::from_magic(&magic[..len]))
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        *self != Compression::None
+    }
+
+    /// Wrap a reader of compressed bytes in the matching decoder.
+    pub fn decoder<'a, R: BufRead + Send + 'a>(
+        &self,
+        reader: R,
+    ) -> IOResult<Box<dyn BufRead + Send + 'a>> {
+        Ok(match self {
+            Compression::None => Box::new(reader),
+            Compression::Gzip => {
+                Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader)))
+            }
+            Compression::Bzip2 => {
+                Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(reader)))
+            }
+            Compression::Xz => Box::new(BufReader::new(
+                xz2::bufread::XzDecoder::new_multi_decoder(reader),
+            )),
+        })
+    }
+}
+
+/// Path without its compression suffix (`1abc.pdb.gz` → `1abc.pdb`), used
+/// for extension-based format detection.
+pub fn strip_compression_extension(path: &Path) -> Cow<'_, Path> {
+    if Compression::from_extension(path).is_compressed() {
+        if let Some(stem) = path.file_stem() {
+            return Cow::Owned(path.with_file_name(stem));
+        }
+    }
+    Cow::Borrowed(path)
+}
+
+/// Open a file for reading, decompressing it if needed.
+pub fn open(path: &Path) -> IOResult<Box<dyn BufRead + Send>> {
+    let compression = Compression::detect(path)?;
+    let file = File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+    compression.decoder(BufReader::new(file))
+}
+
+/// Read a whole (possibly compressed) text file.
+pub fn read_to_string(path: &Path) -> IOResult<String> {
+    let mut content = String::new();
+    open(path)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// First `len` decompressed bytes of a file (fewer if it is shorter).
+pub fn read_prefix(path: &Path, len: usize) -> IOResult<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(len);
+    open(path)?.take(len as u64).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Total size of decompressed copies kept in [`cache_dir`].
+pub const CACHE_BUDGET_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Directory holding decompressed copies for seekable readers.
+pub fn cache_dir() -> PathBuf {
+    std::env::temp_dir().join("gumol_decompressed")
+}
+
+/// A path that can be seeked and memory-mapped: `path` itself when it is not
+/// compressed, otherwise a decompressed copy in [`cache_dir`].
+///
+/// The copy keeps the uncompressed file name, so extension-based detection
+/// still works on it, and is reused while the source's size and modification
+/// time are unchanged. Reusing a copy marks it as recently used for
+/// [`prune_cache`].
+pub fn seekable_path(path: &Path) -> IOResult<PathBuf> {
+    let compression = Compression::detect(path)?;
+    if !compression.is_compressed() {
+        return Ok(path.to_path_buf());
+    }
+
+    let metadata = std::fs::metadata(path)?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    metadata.modified().ok().hash(&mut hasher);
+    let name = strip_compression_extension(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let dir = cache_dir();
+    let cached = dir.join(format!("{:016x}-{}", hasher.finish(), name));
+    if cached.is_file() {
+        // Eviction is least recently used first, so record the reuse
+        if let Ok(file) = File::options().append(true).open(&cached) {
+            let _ = file.set_modified(std::time::SystemTime::now());
+        }
+        return Ok(cached);
+    }
+
+    std::fs::create_dir_all(&dir)?;
+    // Decompress under a private name so a concurrent reader never sees a partial file
+    let partial = dir.join(format!(
+        "{:016x}.partial-{}",
+        hasher.finish(),
+        std::process::id()
+    ));
+    let result = (|| -> IOResult<()> {
+        let mut out = std::io::BufWriter::new(File::create(&partial)?);
+        std::io::copy(&mut open(path)?, &mut out)?;
+        std::io::Write::flush(&mut out)?;
+        Ok(())
+    })();
+    if let Err(err) = result {
+        let _ = std::fs::remove_file(&partial);
+        return Err(err);
+    }
+    std::fs::rename(&partial, &cached)?;
+    prune_cache(&dir, CACHE_BUDGET_BYTES, &cached);
+    Ok(cached)
+}
+
+/// Delete the least recently used decompressed copies in `dir` until the
+/// rest fit in `budget` bytes. `keep` is never deleted, even when it alone is
+/// over budget, and neither are in-progress `.partial-` files.
+///
+/// Failures are ignored: a copy still open on a platform that forbids
+/// deleting open files simply stays until a later prune.
+pub fn prune_cache(dir: &Path, budget: u64, keep: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut copies: Vec<_> = entries
+        .flatten()
+        .filter(|entry| !entry.file_name().to_string_lossy().contains(".partial-"))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            let used = metadata.modified().unwrap_or(std::time::UNIX_EPOCH);
+            Some((used, metadata.len(), entry.path()))
+        })
+        .collect();
+
+    let mut total: u64 = copies.iter().map(|(_, len, _)| len).sum();
+    copies.sort_by_key(|(used, _, _)| *used);
+    for (_, len, path) in copies {
+        if total <= budget {
+            break;
+        }
+        if path == keep {
+            continue;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total -= len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const PDB: &str =
+        "ATOM      1  N   ALA A   1       0.000   0.000   0.000  1.00 20.00           N\nEND\n";
+
+    fn compress(compression: Compression, data: &[u8]) -> Vec<u8> {
+        match compression {
+            Compression::None => data.to_vec(),
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Xz => {
+                let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+        }
+    }
+
+    #[test]
+    fn test_strip_compression_extension() {
+        assert_eq!(
+            strip_compression_extension(Path::new("/data/1abc.pdb.gz")),
+            Path::new("/data/1abc.pdb")
+        );
+        assert_eq!(
+            strip_compression_extension(Path::new("1abc.cif.XZ")),
+            Path::new("1abc.cif")
+        );
+        assert_eq!(
+            strip_compression_extension(Path::new("1abc.pdb")),
+            Path::new("1abc.pdb")
+        );
+    }
+
+    #[test]
+    fn test_open_and_seekable_path_for_each_codec() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        for (compression, ext) in [
+            (Compression::Gzip, "gz"),
+            (Compression::Bzip2, "bz2"),
+            (Compression::Xz, "xz"),
+        ] {
+            let bytes = compress(compression, PDB.as_bytes());
+            assert_eq!(Compression::from_magic(&bytes), compression);
+
+            // Magic bytes win over a misleading name
+            let path = dir.join(format!("mini.{ext}.pdb"));
+            std::fs::write(&path, &bytes).unwrap();
+            assert_eq!(Compression::detect(&path).unwrap(), compression);
+            assert_eq!(read_to_string(&path).unwrap(), PDB);
+            assert_eq!(read_prefix(&path, 4).unwrap(), b"ATOM");
+
+            let path = dir.join(format!("mini.pdb.{ext}"));
+            std::fs::write(&path, &bytes).unwrap();
+            let cached = seekable_path(&path).unwrap();
+            assert_ne!(cached, path);
+            assert!(cached.to_string_lossy().ends_with("-mini.pdb"));
+            assert_eq!(std::fs::read_to_string(&cached).unwrap(), PDB);
+            assert_eq!(seekable_path(&path).unwrap(), cached);
+            let _ = std::fs::remove_file(cached);
+        }
+
+        let plain = dir.join("plain.pdb");
+        std::fs::write(&plain, PDB).unwrap();
+        assert_eq!(seekable_path(&plain).unwrap(), plain);
+    }
+
+    #[test]
+    fn test_xz_is_decoded_as_a_stream() {
+        // A stream cut short still yields its start, which a one-pass decoder
+        // would only return after decompressing (and holding) everything
+        let data: Vec<u8> = (0..4_000_000u32).flat_map(|i| i.to_le_bytes()).collect();
+        let bytes = compress(Compression::Xz, &data);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.dat.xz");
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+
+        assert_eq!(read_prefix(&path, 64).unwrap(), data[..64]);
+        assert!(open(&path).unwrap().read_to_end(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_prune_cache_drops_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = std::time::SystemTime::now();
+        let mut paths = Vec::new();
+        for (i, name) in ["old.xtc", "mid.xtc", "new.xtc"].into_iter().enumerate() {
+            let path = dir.path().join(name);
+            let file = File::create(&path).unwrap();
+            file.set_len(100).unwrap();
+            file.set_modified(now - std::time::Duration::from_secs(300 - 100 * i as u64))
+                .unwrap();
+            paths.push(path);
+        }
+        let partial = dir.path().join("0123.partial-1");
+        std::fs::write(&partial, [0u8; 100]).unwrap();
+
+        // The oldest copy is kept when it is the one just produced
+        prune_cache(dir.path(), 200, &paths[0]);
+        assert!(paths[0].exists());
+        assert!(!paths[1].exists());
+        assert!(paths[2].exists());
+        assert!(partial.exists());
+
+        prune_cache(dir.path(), 100, &paths[2]);
+        assert!(!paths[0].exists());
+        assert!(paths[2].exists());
+    }
+}
@@ -2,19 +2,155 @@
 //!
 //! The DCD format is a binary trajectory format used by CHARMM, NAMD, and others.
 //! Supports full load for small trajectories and seek-based streaming for large ones.
+//!
+//! Files are Fortran unformatted records. Byte order and the record marker
+//! width (4 bytes, or 8 on some 64-bit compilers) are detected from the
+//! first record. CHARMM extensions are honoured: the per-frame unit cell
+//! (`ICNTRL(11)`), the fourth coordinate record of 4D dynamics (`ICNTRL(12)`)
+//! and fixed atoms (`NAMNF`), where only the first frame stores every atom
+//! and later frames store the free atoms.
 
 use crate::core::atom::AtomData;
+use crate::core::cell::UnitCell;
 use crate::core::trajectory::{FrameData, Trajectory, TrajectoryMetadata};
-use crate::io::{IOError, IOResult};
+use crate::io::registry::{Confidence, FormatCapabilities, FormatDescriptor, LoadedFile};
+use crate::io::{FileFormat, IOError, IOResult};
 use bevy::prelude::*;
-use byteorder::{LittleEndian, ReadBytesExt};
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
 /// DCD format constants
 pub const DCD_MAGIC_NUMBER: i32 = 84;
 
+/// Byte order and Fortran record marker width of a DCD file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DcdLayout {
+    /// Numbers are stored big-endian
+    pub big_endian: bool,
+    /// Record marker size in bytes (4 or 8)
+    pub marker_bytes: usize,
+}
+
+impl Default for DcdLayout {
+    fn default() -> Self {
+        Self {
+            big_endian: false,
+            marker_bytes: 4,
+        }
+    }
+}
+
+impl DcdLayout {
+    /// Detect the layout from the first bytes of a file (the 84-byte header marker).
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        let magic = DCD_MAGIC_NUMBER as i64;
+        let word = |big_endian: bool| {
+            let bytes: [u8; 4] = data.get(0..4)?.try_into().ok()?;
+            Some(if big_endian {
+                i32::from_be_bytes(bytes)
+            } else {
+                i32::from_le_bytes(bytes)
+            } as i64)
+        };
+        let dword = |big_endian: bool| {
+            let bytes: [u8; 8] = data.get(0..8)?.try_into().ok()?;
+            Some(if big_endian {
+                i64::from_be_bytes(bytes)
+            } else {
+                i64::from_le_bytes(bytes)
+            })
+        };
+
+        // An 8-byte little-endian marker also reads as 84 in its first four
+        // bytes, so check the wide marker first.
+        [(false, 8), (false, 4), (true, 4), (true, 8)]
+            .into_iter()
+            .find(|&(big_endian, marker_bytes)| {
+                let value = if marker_bytes == 8 {
+                    dword(big_endian)
+                } else {
+                    word(big_endian)
+                };
+                value == Some(magic)
+            })
+            .map(|(big_endian, marker_bytes)| Self {
+                big_endian,
+                marker_bytes,
+            })
+    }
+
+    fn i32_at(&self, bytes: &[u8]) -> i32 {
+        let bytes: [u8; 4] = bytes[..4].try_into().expect("4 bytes");
+        if self.big_endian {
+            i32::from_be_bytes(bytes)
+        } else {
+            i32::from_le_bytes(bytes)
+        }
+    }
+
+    fn f32_at(&self, bytes: &[u8]) -> f32 {
+        f32::from_bits(self.i32_at(bytes) as u32)
+    }
+
+    fn f64_at(&self, bytes: &[u8]) -> f64 {
+        let bytes: [u8; 8] = bytes[..8].try_into().expect("8 bytes");
+        if self.big_endian {
+            f64::from_be_bytes(bytes)
+        } else {
+            f64::from_le_bytes(bytes)
+        }
+    }
+
+    fn read_marker<R: Read>(&self, reader: &mut R) -> IOResult<u64> {
+        let mut bytes = [0u8; 8];
+        let bytes = &mut bytes[..self.marker_bytes];
+        reader.read_exact(bytes)?;
+        let value = match (self.marker_bytes, self.big_endian) {
+            (8, false) => i64::from_le_bytes(bytes.try_into().expect("8 bytes")),
+            (8, true) => i64::from_be_bytes(bytes.try_into().expect("8 bytes")),
+            _ => self.i32_at(bytes) as i64,
+        };
+        u64::try_from(value).map_err(|_| IOError::ParseError {
+            line: 0,
+            message: format!("Invalid DCD record marker: {value}"),
+        })
+    }
+
+    /// Read one Fortran record, checking both markers (and the size when given).
+    fn read_record<R: Read>(
+        &self,
+        reader: &mut R,
+        what: &str,
+        expected: Option<usize>,
+    ) -> IOResult<Vec<u8>> {
+        let size = self.read_marker(reader)?;
+        if let Some(expected) = expected {
+            if size != expected as u64 {
+                return Err(IOError::ParseError {
+                    line: 0,
+                    message: format!("Invalid {what} record size: expected {expected}, got {size}"),
+                });
+            }
+        }
+        let mut body = vec![0u8; size as usize];
+        reader.read_exact(&mut body)?;
+        let trailer = self.read_marker(reader)?;
+        if trailer != size {
+            return Err(IOError::ParseError {
+                line: 0,
+                message: format!("Mismatched {what} record markers: {size} and {trailer}"),
+            });
+        }
+        Ok(body)
+    }
+
+    /// Bytes taken by a record with `body` bytes of payload.
+    fn record_size(&self, body: usize) -> u64 {
+        (body + 2 * self.marker_bytes) as u64
+    }
+}
+
 /// DCD header structure
 #[derive(Debug, Clone)]
 pub struct DcdHeader {
@@ -28,6 +164,16 @@ pub struct DcdHeader {
     pub has_pressure: bool,
     pub title: String,
     pub num_atoms: i32,
+    /// CHARMM version from ICNTRL(20); zero for X-PLOR files
+    pub charmm_version: i32,
+    /// Each frame carries a unit cell record (CHARMM ICNTRL(11))
+    pub has_unit_cell: bool,
+    /// Each frame carries a fourth coordinate record (CHARMM ICNTRL(12))
+    pub has_4d: bool,
+    /// Number of fixed atoms (NAMNF, ICNTRL(9))
+    pub num_fixed: i32,
+    /// Byte order and record marker width
+    pub layout: DcdLayout,
 }
 
 impl Default for DcdHeader {
@@ -43,6 +189,11 @@ impl Default for DcdHeader {
             has_pressure: false,
             title: String::new(),
             num_atoms: 0,
+            charmm_version: 0,
+            has_unit_cell: false,
+            has_4d: false,
+            num_fixed: 0,
+            layout: DcdLayout::default(),
         }
     }
 }
@@ -52,29 +203,64 @@ pub struct DcdReader {
     file: File,
     header: DcdHeader,
     first_frame_offset: u64,
+    /// Bytes of the first frame (all atoms)
+    first_frame_size: u64,
+    /// Bytes of every later frame (free atoms only when NAMNF > 0)
     frame_stride: u64,
+    /// 0-based indices of the free atoms; empty without fixed atoms
+    free_atoms: Vec<u32>,
+    /// First frame, which supplies fixed atom positions to later frames
+    first_frame: Option<FrameData>,
+    num_frames: usize,
     time_step: f32,
 }
 
 impl DcdReader {
     /// Open a DCD file and parse the header.
     pub fn open(path: &Path) -> IOResult<Self> {
+        let source = crate::io::compression::seekable_path(path)?;
         let file =
-            File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+            File::open(&source).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+        let file_len = file.metadata()?.len();
         let mut reader = BufReader::new(file);
-        let (header, first_frame_offset) = Self::read_header(&mut reader)?;
-        let frame_stride = Self::frame_stride(header.num_atoms as usize);
+        let (header, free_atoms, first_frame_offset) = Self::read_header(&mut reader)?;
+
+        let num_atoms = header.num_atoms as usize;
+        let num_free = if free_atoms.is_empty() {
+            num_atoms
+        } else {
+            free_atoms.len()
+        };
+        let first_frame_size = Self::frame_size(&header, num_atoms);
+        let frame_stride = Self::frame_size(&header, num_free);
         let time_step = header.delta * 20.0;
 
-        let file = reader.into_inner();
+        // Trust the file size over NSET when a run was cut short
+        let available = match file_len.checked_sub(first_frame_offset + first_frame_size) {
+            Some(rest) => 1 + (rest / frame_stride.max(1)) as usize,
+            None => 0,
+        };
+        let num_frames = if header.num_frames > 0 {
+            (header.num_frames as usize).min(available)
+        } else {
+            available
+        };
 
-        Ok(Self {
-            file,
+        let mut reader = Self {
+            file: reader.into_inner(),
             header,
             first_frame_offset,
+            first_frame_size,
             frame_stride,
+            free_atoms,
+            first_frame: None,
+            num_frames,
             time_step,
-        })
+        };
+        if !reader.free_atoms.is_empty() && num_frames > 0 {
+            reader.first_frame = Some(reader.read_frame(0)?);
+        }
+        Ok(reader)
     }
 
     pub fn header(&self) -> &DcdHeader {
@@ -82,11 +268,7 @@ impl DcdReader {
     }
 
     pub fn num_frames(&self) -> usize {
-        if self.header.num_frames > 0 {
-            self.header.num_frames as usize
-        } else {
-            1
-        }
+        self.num_frames
     }
 
     pub fn num_atoms(&self) -> usize {
@@ -99,175 +281,210 @@ impl DcdReader {
 
     /// Read a single frame by index (0-based).
     pub fn read_frame(&self, frame_index: usize) -> IOResult<FrameData> {
-        let offset = self
-            .first_frame_offset
-            .saturating_add(self.frame_stride.saturating_mul(frame_index as u64));
+        let offset = match frame_index {
+            0 => self.first_frame_offset,
+            _ => self
+                .first_frame_offset
+                .saturating_add(self.first_frame_size)
+                .saturating_add(self.frame_stride.saturating_mul(frame_index as u64 - 1)),
+        };
         let mut file = &self.file;
         file.seek(SeekFrom::Start(offset))?;
-        Self::read_frame_at(
-            &mut file,
-            frame_index,
-            self.header.num_atoms as usize,
-            frame_index as f32 * self.time_step,
-        )
+        let mut reader = BufReader::new(file);
+        let time = frame_index as f32 * self.time_step;
+
+        match &self.first_frame {
+            Some(first) if frame_index > 0 => {
+                // Fixed atoms keep their first-frame positions
+                self.read_frame_at(
+                    &mut reader,
+                    frame_index,
+                    &self.free_atoms,
+                    &first.positions,
+                    time,
+                )
+            }
+            Some(first) => Ok(first.clone()),
+            None => {
+                let all: Vec<u32> = (0..self.num_atoms() as u32).collect();
+                self.read_frame_at(&mut reader, frame_index, &all, &[], time)
+            }
+        }
     }
 
-    fn frame_stride(num_atoms: usize) -> u64 {
-        let coord_block = 4 + num_atoms * 4 + 4;
-        (coord_block * 3) as u64
+    /// Size in bytes of a frame storing `num_coords` atoms.
+    fn frame_size(header: &DcdHeader, num_coords: usize) -> u64 {
+        let layout = header.layout;
+        let records = if header.has_4d { 4 } else { 3 };
+        let cell = if header.has_unit_cell {
+            layout.record_size(6 * 8)
+        } else {
+            0
+        };
+        cell + records * layout.record_size(num_coords * 4)
     }
 
-    /// Read DCD header (Fortran unformatted format). Returns header and byte offset to first frame.
-    fn read_header<R: Read>(reader: &mut R) -> IOResult<(DcdHeader, u64)> {
-        let start = 0_u64;
+    /// Read DCD header (Fortran unformatted format).
+    ///
+    /// Returns the header, the free atom indices (empty without fixed atoms)
+    /// and the byte offset of the first frame.
+    fn read_header<R: Read>(reader: &mut R) -> IOResult<(DcdHeader, Vec<u32>, u64)> {
+        let mut peek = [0u8; 8];
+        reader.read_exact(&mut peek)?;
+        let layout = DcdLayout::detect(&peek).ok_or_else(|| IOError::ParseError {
+            line: 0,
+            message: format!(
+                "Invalid DCD header: expected magic {DCD_MAGIC_NUMBER} in the first record marker"
+            ),
+        })?;
+        // Put the peeked bytes back in front of the stream
+        let mut reader = std::io::Cursor::new(peek).chain(reader);
         let mut bytes_read = 0_u64;
 
-        let rec1_size = reader.read_i32::<LittleEndian>()?;
-        bytes_read += 4;
-
-        if rec1_size != DCD_MAGIC_NUMBER {
-            return Err(IOError::ParseError {
-                line: 0,
-                message: format!(
-                    "Invalid DCD header: expected magic {}, got {}",
-                    DCD_MAGIC_NUMBER, rec1_size
-                ),
-            });
-        }
+        let body = layout.read_record(&mut reader, "header", Some(DCD_MAGIC_NUMBER as usize))?;
+        bytes_read += layout.record_size(body.len());
 
-        let mut header = DcdHeader::default();
-
-        let mut cord = [0u8; 4];
-        reader.read_exact(&mut cord)?;
-        bytes_read += 4;
-        header.charmm = &cord == b"CORD";
-
-        header.num_frames = reader.read_i32::<LittleEndian>()?;
-        header.start_step = reader.read_i32::<LittleEndian>()?;
-        header.skip = reader.read_i32::<LittleEndian>()?;
-        header.num_sets = reader.read_i32::<LittleEndian>()?;
-        bytes_read += 16;
+        let mut header = DcdHeader {
+            layout,
+            charmm: &body[0..4] == b"CORD",
+            ..Default::default()
+        };
 
-        for _ in 0..5 {
-            let _ = reader.read_i32::<LittleEndian>()?;
-            bytes_read += 4;
+        // ICNTRL(1..20) control block
+        let icntrl: Vec<i32> = (0..20).map(|i| layout.i32_at(&body[4 + 4 * i..])).collect();
+        header.num_frames = icntrl[0];
+        header.start_step = icntrl[1];
+        header.skip = icntrl[2];
+        header.num_sets = icntrl[3];
+        header.num_fixed = icntrl[8];
+        header.charmm_version = icntrl[19];
+        if header.charmm_version != 0 {
+            // CHARMM: DELTA is a REAL*4, ICNTRL(11) flags the unit cell block
+            header.delta = layout.f32_at(&body[40..]);
+            header.has_unit_cell = icntrl[10] != 0;
+            header.has_4d = icntrl[11] != 0;
+        } else {
+            // X-PLOR: DELTA is a REAL*8 spanning ICNTRL(10..11)
+            header.delta = layout.f64_at(&body[40..]) as f32;
         }
-        let _ = reader.read_i32::<LittleEndian>()?;
-        bytes_read += 4;
 
-        header.delta = reader.read_f64::<LittleEndian>()? as f32;
-        bytes_read += 8;
-
-        for _ in 0..9 {
-            let _ = reader.read_i32::<LittleEndian>()?;
-            bytes_read += 4;
+        // Title record: NTITLE followed by NTITLE 80-character lines
+        let title = layout.read_record(&mut reader, "title", None)?;
+        bytes_read += layout.record_size(title.len());
+        let n_title = title.get(0..4).map_or(0, |b| layout.i32_at(b));
+        if n_title > 0 {
+            header.title = String::from_utf8_lossy(&title[4..])
+                .trim_matches(|c: char| c.is_whitespace() || c == '\0')
+                .to_string();
         }
-        let _ = reader.read_i32::<LittleEndian>()?;
-        bytes_read += 4;
-
-        let _ = reader.read_i32::<LittleEndian>()?;
-        bytes_read += 4;
-        let n_title = reader.read_i32::<LittleEndian>()?;
-        bytes_read += 4;
 
-        if n_title > 0 {
-            let title_rec_size = reader.read_i32::<LittleEndian>()?;
-            bytes_read += 4;
-            let title_len = title_rec_size as usize;
-            let mut title_bytes = vec![0u8; title_len];
-            reader.read_exact(&mut title_bytes)?;
-            bytes_read += title_len as u64;
-            header.title = String::from_utf8_lossy(&title_bytes).trim().to_string();
-            let _ = reader.read_i32::<LittleEndian>()?;
-            bytes_read += 4;
-        }
-
-        let natom_rec_size = reader.read_i32::<LittleEndian>()?;
-        bytes_read += 4;
-        if natom_rec_size != 4 {
-            return Err(IOError::ParseError {
-                line: 0,
-                message: format!("Invalid NATOM record size: {}", natom_rec_size),
-            });
+        let natom = layout.read_record(&mut reader, "NATOM", Some(4))?;
+        bytes_read += layout.record_size(4);
+        header.num_atoms = layout.i32_at(&natom);
+
+        let mut free_atoms = Vec::new();
+        if header.num_fixed > 0 {
+            let num_free = (header.num_atoms - header.num_fixed).max(0) as usize;
+            let record = layout.read_record(&mut reader, "free atom", Some(num_free * 4))?;
+            bytes_read += layout.record_size(record.len());
+            free_atoms = record
+                .chunks_exact(4)
+                .map(|b| (layout.i32_at(b) - 1).max(0) as u32)
+                .collect();
         }
-        header.num_atoms = reader.read_i32::<LittleEndian>()?;
-        let _ = reader.read_i32::<LittleEndian>()?;
-        bytes_read += 8;
 
-        Ok((header, start + bytes_read))
+        Ok((header, free_atoms, bytes_read))
     }
 
-    /// Read a single DCD frame from the current reader position.
+    /// Read a single DCD frame storing `atoms` (0-based indices) from the
+    /// current position; other atoms keep their positions in `base`.
     fn read_frame_at<R: Read>(
+        &self,
         reader: &mut R,
         frame_index: usize,
-        num_atoms: usize,
+        atoms: &[u32],
+        base: &[Vec3],
         time: f32,
     ) -> IOResult<FrameData> {
-        let mut frame = FrameData::new(frame_index, time);
+        let layout = self.header.layout;
+        let mut frame = FrameData::with_positions(frame_index, time, base.to_vec());
 
-        let nx_size = reader.read_i32::<LittleEndian>()?;
-        if nx_size != (num_atoms * 4) as i32 {
-            return Err(IOError::ParseError {
-                line: 0,
-                message: format!(
-                    "Invalid X coordinate record size: expected {}, got {}",
-                    num_atoms * 4,
-                    nx_size
-                ),
-            });
+        if self.header.has_unit_cell {
+            frame.cell = Self::read_unit_cell(layout, reader)?;
         }
 
-        let mut x_coords = vec![0.0f32; num_atoms];
-        for x in &mut x_coords {
-            *x = reader.read_f32::<LittleEndian>()?;
+        let size = Some(atoms.len() * 4);
+        let x = layout.read_record(reader, "X coordinate", size)?;
+        let y = layout.read_record(reader, "Y coordinate", size)?;
+        let z = layout.read_record(reader, "Z coordinate", size)?;
+        if self.header.has_4d {
+            layout.read_record(reader, "4D coordinate", size)?;
         }
-        reader.read_i32::<LittleEndian>()?;
 
-        let ny_size = reader.read_i32::<LittleEndian>()?;
-        if ny_size != (num_atoms * 4) as i32 {
-            return Err(IOError::ParseError {
-                line: 0,
-                message: format!(
-                    "Invalid Y coordinate record size: expected {}, got {}",
-                    num_atoms * 4,
-                    ny_size
+        frame.positions.resize(self.num_atoms(), Vec3::ZERO);
+        for (i, &id) in atoms.iter().enumerate() {
+            let at = 4 * i;
+            frame.set_position(
+                id as usize,
+                Vec3::new(
+                    layout.f32_at(&x[at..]),
+                    layout.f32_at(&y[at..]),
+                    layout.f32_at(&z[at..]),
                 ),
-            });
-        }
-
-        let mut y_coords = vec![0.0f32; num_atoms];
-        for y in &mut y_coords {
-            *y = reader.read_f32::<LittleEndian>()?;
+            );
         }
-        reader.read_i32::<LittleEndian>()?;
 
-        let nz_size = reader.read_i32::<LittleEndian>()?;
-        if nz_size != (num_atoms * 4) as i32 {
-            return Err(IOError::ParseError {
-                line: 0,
-                message: format!(
-                    "Invalid Z coordinate record size: expected {}, got {}",
-                    num_atoms * 4,
-                    nz_size
-                ),
-            });
-        }
+        Ok(frame)
+    }
 
-        let mut z_coords = vec![0.0f32; num_atoms];
-        for z in &mut z_coords {
-            *z = reader.read_f32::<LittleEndian>()?;
+    /// Read a CHARMM unit cell record: `[A, gamma, B, beta, alpha, C]`.
+    ///
+    /// NAMD and newer CHARMM store the angles as cosines; values that all lie
+    /// within [-1, 1] are treated as cosines and converted to degrees.
+    fn read_unit_cell<R: Read>(layout: DcdLayout, reader: &mut R) -> IOResult<Option<UnitCell>> {
+        let record = layout.read_record(reader, "unit cell", Some(48))?;
+        let values: [f64; 6] = std::array::from_fn(|i| layout.f64_at(&record[8 * i..]));
+
+        let [a, gamma, b, beta, alpha, c] = values.map(|v| v as f32);
+        let mut angles = [alpha, beta, gamma];
+        if angles.iter().all(|v| (-1.0..=1.0).contains(v)) {
+            angles = angles.map(|cos| cos.acos().to_degrees());
         }
-        reader.read_i32::<LittleEndian>()?;
 
-        for i in 0..num_atoms {
-            frame.set_position(i as u32, Vec3::new(x_coords[i], y_coords[i], z_coords[i]));
-        }
+        let cell = UnitCell::from_lengths_angles([a, b, c], angles);
+        Ok(cell.is_periodic().then_some(cell))
+    }
+}
 
-        Ok(frame)
+/// Registry entry: CHARMM/NAMD DCD trajectories, paired with a topology.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::DCD,
+    name: "DCD",
+    extensions: &["dcd"],
+    capabilities: FormatCapabilities {
+        topology: false,
+        trajectory: true,
+        streaming: true,
+        writable: true,
+    },
+    needs_topology: true,
+    sniff,
+    load: Some(load),
+    read_topology: None,
+};
+
+fn sniff(data: &[u8]) -> Confidence {
+    if DcdParser::is_dcd_bytes(data) {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
     }
 }
 
+fn load(path: &Path, topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    crate::io::topology::load_coordinates(topology_path, |_| crate::io::streaming::open_dcd(path))
+}
+
 /// DCD format parser
 pub struct DcdParser;
 
@@ -308,10 +525,122 @@ impl DcdParser {
         Self::parse_file(path)
     }
 
-    /// Check whether bytes look like a DCD file (magic number 84).
+    /// Check whether bytes look like a DCD file (magic number 84 in either
+    /// byte order, with 4- or 8-byte record markers).
     pub fn is_dcd_bytes(data: &[u8]) -> bool {
-        data.len() >= 4
-            && i32::from_le_bytes([data[0], data[1], data[2], data[3]]) == DCD_MAGIC_NUMBER
+        DcdLayout::detect(data).is_some()
+    }
+}
+
+/// DCD writer (CHARMM layout, little-endian, 4-byte record markers).
+///
+/// Frames are streamed to the output as they arrive; [`DcdWriter::finish`]
+/// patches the frame count into the header.
+pub struct DcdWriter<W: Write + Seek> {
+    writer: W,
+    num_atoms: usize,
+    has_unit_cell: bool,
+    num_frames: i32,
+}
+
+impl<W: Write + Seek> DcdWriter<W> {
+    /// Write the header for `num_atoms` atoms saved every `time_step` fs.
+    pub fn new(
+        mut writer: W,
+        num_atoms: usize,
+        time_step: f32,
+        has_unit_cell: bool,
+        title: &str,
+    ) -> IOResult<Self> {
+        let mut control = [0_i32; 20];
+        control[2] = 1; // NSAVC
+        control[9] = (time_step / 20.0).to_bits() as i32; // DELTA (REAL*4)
+        control[10] = i32::from(has_unit_cell);
+        control[19] = 24; // CHARMM version
+        let mut header = b"CORD".to_vec();
+        for value in control {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        Self::write_record(&mut writer, &header)?;
+
+        let mut line = [b' '; 80];
+        let title = title.as_bytes();
+        let len = title.len().min(80);
+        line[..len].copy_from_slice(&title[..len]);
+        let mut titles = 1_i32.to_le_bytes().to_vec();
+        titles.extend_from_slice(&line);
+        Self::write_record(&mut writer, &titles)?;
+
+        Self::write_record(&mut writer, &(num_atoms as i32).to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            num_atoms,
+            has_unit_cell,
+            num_frames: 0,
+        })
+    }
+
+    /// Append a frame; `positions` are in Å and in topology order.
+    pub fn write_frame(&mut self, positions: &[Vec3], cell: Option<&UnitCell>) -> IOResult<()> {
+        if positions.len() != self.num_atoms {
+            return Err(IOError::InvalidFormat(format!(
+                "DCD frame has {} atoms, header declares {}",
+                positions.len(),
+                self.num_atoms
+            )));
+        }
+
+        if self.has_unit_cell {
+            // [A, gamma, B, beta, alpha, C] with angles in degrees
+            let values = match cell {
+                Some(c) => [
+                    c.lengths[0],
+                    c.angles[2],
+                    c.lengths[1],
+                    c.angles[1],
+                    c.angles[0],
+                    c.lengths[2],
+                ],
+                None => [0.0, 90.0, 0.0, 90.0, 90.0, 0.0],
+            };
+            let body: Vec<u8> = values
+                .iter()
+                .flat_map(|&v| (v as f64).to_le_bytes())
+                .collect();
+            Self::write_record(&mut self.writer, &body)?;
+        }
+
+        for axis in 0..3 {
+            let body: Vec<u8> = positions
+                .iter()
+                .flat_map(|p| p[axis].to_le_bytes())
+                .collect();
+            Self::write_record(&mut self.writer, &body)?;
+        }
+        self.num_frames += 1;
+        Ok(())
+    }
+
+    /// Patch the frame count (NSET and NSTEP) into the header and return the output.
+    pub fn finish(mut self) -> IOResult<W> {
+        let end = self.writer.stream_position()?;
+        // ICNTRL(1) follows the 4-byte marker and "CORD"; ICNTRL(4) is 12 bytes later
+        for offset in [8, 20] {
+            self.writer.seek(SeekFrom::Start(offset))?;
+            self.writer.write_all(&self.num_frames.to_le_bytes())?;
+        }
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_record(writer: &mut W, body: &[u8]) -> IOResult<()> {
+        let marker = (body.len() as i32).to_le_bytes();
+        writer.write_all(&marker)?;
+        writer.write_all(body)?;
+        writer.write_all(&marker)?;
+        Ok(())
     }
 }
 
@@ -324,75 +653,261 @@ pub fn register(_app: &mut App) {
 mod tests {
     use super::*;
 
+    /// Builds DCD bytes in any layout for the reader tests.
+    struct RawDcd {
+        layout: DcdLayout,
+        bytes: Vec<u8>,
+    }
+
+    impl RawDcd {
+        fn new(layout: DcdLayout) -> Self {
+            Self {
+                layout,
+                bytes: Vec::new(),
+            }
+        }
+
+        fn word(&self, value: i32) -> [u8; 4] {
+            if self.layout.big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        }
+
+        fn record(&mut self, body: &[u8]) {
+            let marker = body.len() as i64;
+            let marker: Vec<u8> = match (self.layout.marker_bytes, self.layout.big_endian) {
+                (8, false) => marker.to_le_bytes().to_vec(),
+                (8, true) => marker.to_be_bytes().to_vec(),
+                _ => self.word(marker as i32).to_vec(),
+            };
+            self.bytes.extend_from_slice(&marker);
+            self.bytes.extend_from_slice(body);
+            self.bytes.extend_from_slice(&marker);
+        }
+
+        /// CHARMM header (version 24) with one title line; `icntrl` entries
+        /// are 1-based overrides such as `(11, 1)` for the unit cell flag.
+        fn charmm_header(&mut self, num_atoms: i32, num_frames: i32, icntrl: &[(usize, i32)]) {
+            let mut control = [0_i32; 20];
+            control[0] = num_frames;
+            control[2] = 1;
+            control[3] = num_frames;
+            control[9] = 0.02_f32.to_bits() as i32;
+            control[19] = 24;
+            for &(index, value) in icntrl {
+                control[index - 1] = value;
+            }
+            let mut body = b"CORD".to_vec();
+            for value in control {
+                body.extend_from_slice(&self.word(value));
+            }
+            self.record(&body);
+
+            let mut title = self.word(1).to_vec();
+            let mut line = [b' '; 80];
+            line[..9].copy_from_slice(b"unit test");
+            title.extend_from_slice(&line);
+            self.record(&title);
+
+            let natom = self.word(num_atoms);
+            self.record(&natom);
+        }
+
+        fn floats(&mut self, values: &[f32]) {
+            let body: Vec<u8> = values
+                .iter()
+                .flat_map(|v| self.word(v.to_bits() as i32))
+                .collect();
+            self.record(&body);
+        }
+
+        fn cell(&mut self, values: [f64; 6]) {
+            let body: Vec<u8> = values
+                .iter()
+                .flat_map(|v| {
+                    if self.layout.big_endian {
+                        v.to_be_bytes()
+                    } else {
+                        v.to_le_bytes()
+                    }
+                })
+                .collect();
+            self.record(&body);
+        }
+
+        fn save(&self, name: &str) -> std::path::PathBuf {
+            let dir = std::env::temp_dir().join(format!("gumol_dcd_unit_{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let path = dir.join(name);
+            std::fs::write(&path, &self.bytes).unwrap();
+            path
+        }
+    }
+
+    fn layouts() -> [DcdLayout; 4] {
+        [(false, 4), (false, 8), (true, 4), (true, 8)].map(|(big_endian, marker_bytes)| DcdLayout {
+            big_endian,
+            marker_bytes,
+        })
+    }
+
     #[test]
     fn test_dcd_magic_detection() {
         assert!(DcdParser::is_dcd_bytes(&84_i32.to_le_bytes()));
+        assert!(DcdParser::is_dcd_bytes(&84_i32.to_be_bytes()));
+        assert!(DcdParser::is_dcd_bytes(&84_i64.to_be_bytes()));
         assert!(!DcdParser::is_dcd_bytes(b"ATOM"));
+
+        for layout in layouts() {
+            let mut writer = RawDcd::new(layout);
+            writer.charmm_header(1, 1, &[]);
+            assert_eq!(DcdLayout::detect(&writer.bytes), Some(layout));
+        }
     }
 
     #[test]
-    fn test_frame_stride() {
-        assert_eq!(DcdReader::frame_stride(100), (8 + 100 * 4) * 3);
+    fn test_frame_size() {
+        let mut header = DcdHeader::default();
+        assert_eq!(DcdReader::frame_size(&header, 100), (8 + 100 * 4) * 3);
+        header.has_unit_cell = true;
+        assert_eq!(DcdReader::frame_size(&header, 100), 56 + (8 + 100 * 4) * 3);
+        header.has_4d = true;
+        header.layout.marker_bytes = 8;
+        assert_eq!(DcdReader::frame_size(&header, 100), 64 + (16 + 100 * 4) * 4);
     }
 
     #[test]
-    fn test_minimal_dcd_fixture_roundtrip() {
-        use byteorder::{LittleEndian, WriteBytesExt};
-        use std::io::Write;
-
-        let dir = std::env::temp_dir().join(format!("gumol_dcd_unit_{}", std::process::id()));
-        let _ = std::fs::remove_dir_all(&dir);
-        std::fs::create_dir_all(&dir).unwrap();
-        let path = dir.join("mini.dcd");
-
-        {
-            let mut file = std::fs::File::create(&path).unwrap();
-            file.write_i32::<LittleEndian>(84).unwrap();
-            file.write_all(b"CORD").unwrap();
-            file.write_i32::<LittleEndian>(2).unwrap();
-            file.write_i32::<LittleEndian>(0).unwrap();
-            file.write_i32::<LittleEndian>(1).unwrap();
-            file.write_i32::<LittleEndian>(2).unwrap();
-            for _ in 0..5 {
-                file.write_i32::<LittleEndian>(0).unwrap();
-            }
-            file.write_i32::<LittleEndian>(0).unwrap();
-            file.write_f64::<LittleEndian>(0.02).unwrap();
-            for _ in 0..9 {
-                file.write_i32::<LittleEndian>(0).unwrap();
-            }
-            file.write_i32::<LittleEndian>(0).unwrap();
-            file.write_i32::<LittleEndian>(0).unwrap();
-            file.write_i32::<LittleEndian>(0).unwrap();
-            file.write_i32::<LittleEndian>(4).unwrap();
-            file.write_i32::<LittleEndian>(3).unwrap();
-            file.write_i32::<LittleEndian>(4).unwrap();
-
+    fn test_minimal_dcd_roundtrip_all_layouts() {
+        for layout in layouts() {
+            let mut writer = RawDcd::new(layout);
+            writer.charmm_header(3, 2, &[]);
             for frame in 0..2 {
                 let shift = frame as f32 * 0.1;
-                for axis in 0..3 {
-                    let coords: Vec<f32> = match axis {
-                        0 => (0..3).map(|i| i as f32 + shift).collect(),
-                        _ => vec![0.0; 3],
-                    };
-                    let size = 12_i32;
-                    file.write_i32::<LittleEndian>(size).unwrap();
-                    for c in &coords {
-                        file.write_f32::<LittleEndian>(*c).unwrap();
-                    }
-                    file.write_i32::<LittleEndian>(size).unwrap();
-                }
+                writer.floats(&[shift, 1.0 + shift, 2.0 + shift]);
+                writer.floats(&[0.0; 3]);
+                writer.floats(&[-1.0; 3]);
+            }
+            let path = writer.save(&format!(
+                "mini_{}_{}.dcd",
+                layout.big_endian, layout.marker_bytes
+            ));
+
+            let reader = DcdReader::open(&path).expect("open minimal dcd");
+            assert_eq!(reader.header().layout, layout);
+            assert_eq!(reader.header().title, "unit test");
+            assert!((reader.header().delta - 0.02).abs() < 1e-6);
+            assert_eq!(reader.num_atoms(), 3);
+            assert_eq!(reader.num_frames(), 2);
+            let f0 = reader.read_frame(0).expect("frame 0");
+            assert_eq!(f0.position(2), Some(Vec3::new(2.0, 0.0, -1.0)));
+            let f1 = reader.read_frame(1).expect("frame 1");
+            assert!((f1.position(0).unwrap().x - 0.1).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_charmm_unit_cell_block() {
+        let mut writer = RawDcd::new(DcdLayout {
+            big_endian: true,
+            marker_bytes: 4,
+        });
+        writer.charmm_header(2, 2, &[(11, 1)]);
+        for frame in 0..2 {
+            // [A, cos(gamma), B, cos(beta), cos(alpha), C] as written by NAMD
+            writer.cell([40.0 + frame as f64, 0.5, 40.0, 0.0, 0.0, 60.0]);
+            for _ in 0..3 {
+                writer.floats(&[1.0, 2.0]);
             }
         }
+        let path = writer.save("cell.dcd");
 
-        let reader = DcdReader::open(&path).expect("open minimal dcd");
-        assert_eq!(reader.num_atoms(), 3);
-        assert_eq!(reader.num_frames(), 2);
-        let f0 = reader.read_frame(0).expect("frame 0");
-        assert!((f0.get_position(0).unwrap().x - 0.0).abs() < 1e-5);
+        let reader = DcdReader::open(&path).expect("open unit cell dcd");
+        assert!(reader.header().has_unit_cell);
         let f1 = reader.read_frame(1).expect("frame 1");
-        assert!((f1.get_position(0).unwrap().x - 0.1).abs() < 1e-5);
+        let cell = f1.cell.expect("unit cell");
+        assert!((cell.lengths[0] - 41.0).abs() < 1e-4);
+        assert!((cell.angles[2] - 60.0).abs() < 1e-3);
+        assert!((cell.angles[0] - 90.0).abs() < 1e-3);
+        assert_eq!(f1.position(1), Some(Vec3::splat(2.0)));
+    }
 
-        let _ = std::fs::remove_dir_all(&dir);
+    #[test]
+    fn test_fixed_atoms_and_4d_records() {
+        let mut writer = RawDcd::new(DcdLayout {
+            big_endian: false,
+            marker_bytes: 8,
+        });
+        // Four atoms, atoms 1 and 3 (1-based) fixed, 4D dynamics; NSET of
+        // zero makes the reader count frames from the file size.
+        writer.charmm_header(4, 0, &[(9, 2), (12, 1)]);
+        let free: Vec<u8> = [2, 4].iter().flat_map(|&i| writer.word(i)).collect();
+        writer.record(&free);
+
+        for axis in 0..4 {
+            writer.floats(&[10.0 + axis as f32, 1.0, 30.0, 3.0]);
+        }
+        for frame in 1..3 {
+            for _ in 0..4 {
+                writer.floats(&[frame as f32 + 1.0, frame as f32 + 3.0]);
+            }
+        }
+        let path = writer.save("fixed.dcd");
+
+        let reader = DcdReader::open(&path).expect("open fixed atom dcd");
+        assert_eq!(reader.header().num_fixed, 2);
+        assert!(reader.header().has_4d);
+        assert_eq!(reader.num_frames(), 3);
+
+        let f0 = reader.read_frame(0).unwrap();
+        assert_eq!(f0.position(0), Some(Vec3::new(10.0, 11.0, 12.0)));
+        for frame in 1..3 {
+            let f = reader.read_frame(frame).unwrap();
+            assert_eq!(f.positions.len(), 4);
+            assert_eq!(f.position(0), Some(Vec3::new(10.0, 11.0, 12.0)));
+            assert_eq!(f.position(1), Some(Vec3::splat(frame as f32 + 1.0)));
+            assert_eq!(f.position(2), Some(Vec3::splat(30.0)));
+            assert_eq!(f.position(3), Some(Vec3::splat(frame as f32 + 3.0)));
+        }
+        assert!(reader.read_frame(3).is_err());
+    }
+
+    #[test]
+    fn test_dcd_writer_roundtrip() {
+        let cell = UnitCell::from_lengths_angles([30.0, 31.0, 32.0], [90.0, 90.0, 120.0]);
+        let mut writer = DcdWriter::new(
+            std::io::Cursor::new(Vec::new()),
+            2,
+            40.0,
+            true,
+            "written by test",
+        )
+        .unwrap();
+        for frame in 0..3 {
+            let x = frame as f32;
+            writer
+                .write_frame(&[Vec3::new(x, 1.0, 2.0), Vec3::splat(-x)], Some(&cell))
+                .unwrap();
+        }
+        assert!(writer.write_frame(&[Vec3::ZERO], None).is_err());
+        let bytes = writer.finish().unwrap().into_inner();
+        let path = std::env::temp_dir()
+            .join(format!("gumol_dcd_unit_{}", std::process::id()))
+            .join("written.dcd");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, bytes).unwrap();
+
+        let reader = DcdReader::open(&path).unwrap();
+        assert_eq!(reader.header().title, "written by test");
+        assert_eq!(reader.header().num_frames, 3);
+        assert_eq!(reader.num_frames(), 3);
+        assert!((reader.time_step() - 40.0).abs() < 1e-4);
+        let f2 = reader.read_frame(2).unwrap();
+        assert_eq!(f2.position(0), Some(Vec3::new(2.0, 1.0, 2.0)));
+        assert_eq!(f2.position(1), Some(Vec3::splat(-2.0)));
+        let read_cell = f2.cell.unwrap();
+        assert!((read_cell.lengths[2] - 32.0).abs() < 1e-4);
+        assert!((read_cell.angles[2] - 120.0).abs() < 1e-3);
     }
 }
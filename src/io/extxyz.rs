@@ -0,0 +1,496 @@
+//! Extended XYZ (extxyz) comment-line parsing
+//!
+//! ASE, QUIP and most ML-potential workflows store per-frame metadata in the
+//! XYZ comment line as `key=value` pairs:
+//!
+//! ```text
+//! 2
+//! Lattice="10.0 0.0 0.0 0.0 10.0 0.0 0.0 0.0 10.0" Properties=species:S:1:pos:R:3:forces:R:3 energy=-12.5 pbc="T T T"
+//! O 0.0 0.0 0.0 0.1 0.0 0.0
+//! H 0.9 0.0 0.0 -0.1 0.0 0.0
+//! ```
+//!
+//! Values may be bare tokens or quoted with `"`, `'` or `{}`; a key without
+//! `=` is a boolean flag. `Properties` describes the atom line columns as
+//! `name:type:count` triples, where type is `S` (string), `R` (real),
+//! `I` (integer) or `L` (logical). Without it the plain XYZ layout
+//! `species:S:1:pos:R:3` is assumed, so ordinary XYZ files go through the
+//! same path.
+//!
+//! `Lattice` becomes the frame cell, `energy` its potential energy, `forces`
+//! and `velo` / `vel` / `velocities` its forces and velocities. Every other
+//! numeric or logical column is kept as a named per-atom property; vector
+//! columns are split into `name_0`, `name_1`, ... Remaining `key=value` pairs
+//! are copied into the frame metadata.
+
+use crate::core::cell::UnitCell;
+use crate::core::trajectory::{set_atom_value, FrameData};
+use crate::io::{IOError, IOResult};
+use bevy::prelude::*;
+
+/// Split a comment line into `key=value` pairs.
+///
+/// Keys without a value are returned with `None`. Malformed quoting never
+/// fails; an unterminated quote runs to the end of the line.
+pub fn parse_key_values(comment: &str) -> Vec<(String, Option<String>)> {
+    let chars: Vec<char> = comment.chars().collect();
+    let mut pairs = Vec::new();
+    let mut i = 0;
+
+    let skip_whitespace = |i: &mut usize| {
+        while *i < chars.len() && chars[*i].is_whitespace() {
+            *i += 1;
+        }
+    };
+
+    loop {
+        skip_whitespace(&mut i);
+        if i >= chars.len() {
+            break;
+        }
+
+        let key_start = i;
+        while i < chars.len() && !chars[i].is_whitespace() && chars[i] != '=' {
+            i += 1;
+        }
+        let key: String = chars[key_start..i].iter().collect();
+
+        let mut lookahead = i;
+        skip_whitespace(&mut lookahead);
+        if lookahead >= chars.len() || chars[lookahead] != '=' {
+            if !key.is_empty() {
+                pairs.push((key, None));
+            }
+            continue;
+        }
+        i = lookahead + 1;
+        skip_whitespace(&mut i);
+
+        let mut value = String::new();
+        match chars.get(i) {
+            Some(&open @ ('"' | '\'' | '{')) => {
+                let close = if open == '{' { '}' } else { open };
+                i += 1;
+                while i < chars.len() && chars[i] != close {
+                    if chars[i] == '\\' && i + 1 < chars.len() {
+                        i += 1;
+                    }
+                    value.push(chars[i]);
+                    i += 1;
+                }
+                i += 1;
+            }
+            _ => {
+                while i < chars.len() && !chars[i].is_whitespace() {
+                    value.push(chars[i]);
+                    i += 1;
+                }
+            }
+        }
+
+        if !key.is_empty() {
+            pairs.push((key, Some(value)));
+        }
+    }
+
+    pairs
+}
+
+/// Value type of a `Properties` column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    /// `S`
+    String,
+    /// `R`
+    Real,
+    /// `I`
+    Integer,
+    /// `L`, written as `T` / `F`
+    Logical,
+}
+
+impl ColumnKind {
+    fn from_code(code: &str) -> Option<Self> {
+        match code.to_ascii_uppercase().as_str() {
+            "S" => Some(ColumnKind::String),
+            "R" => Some(ColumnKind::Real),
+            "I" => Some(ColumnKind::Integer),
+            "L" => Some(ColumnKind::Logical),
+            _ => None,
+        }
+    }
+}
+
+/// What a column is loaded into.
+#[derive(Debug, Clone, PartialEq)]
+enum ColumnRole {
+    Species,
+    Position,
+    Velocity,
+    Force,
+    /// One property name per component.
+    Scalars(Vec<String>),
+    Ignored,
+}
+
+/// One `name:type:count` entry of the `Properties` schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyColumn {
+    /// Column name
+    pub name: String,
+    /// Value type
+    pub kind: ColumnKind,
+    /// Number of whitespace-separated fields
+    pub width: usize,
+    /// Index of the first field on the atom line
+    pub offset: usize,
+    role: ColumnRole,
+}
+
+/// Per-atom column layout from the `Properties` key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertySchema {
+    /// Columns in line order
+    pub columns: Vec<PropertyColumn>,
+    /// Total number of fields on an atom line
+    pub num_fields: usize,
+}
+
+impl PropertySchema {
+    /// Layout of a plain XYZ atom line, `species:S:1:pos:R:3`.
+    pub fn plain() -> Self {
+        Self::parse("species:S:1:pos:R:3").expect("built-in schema is valid")
+    }
+
+    /// Parse a `Properties` value such as `species:S:1:pos:R:3:forces:R:3`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = spec.split(':').collect();
+        if parts.len() % 3 != 0 {
+            return Err(format!(
+                "Properties must be name:type:count triples, got {spec}"
+            ));
+        }
+
+        let mut columns = Vec::with_capacity(parts.len() / 3);
+        let mut offset = 0;
+        for triple in parts.chunks(3) {
+            let name = triple[0].to_string();
+            let kind = ColumnKind::from_code(triple[1])
+                .ok_or_else(|| format!("Unknown type '{}' for property {name}", triple[1]))?;
+            let width = triple[2]
+                .parse::<usize>()
+                .ok()
+                .filter(|&w| w > 0)
+                .ok_or_else(|| format!("Invalid count '{}' for property {name}", triple[2]))?;
+
+            let is_vector = kind == ColumnKind::Real && width == 3;
+            let role = match name.as_str() {
+                "species" if kind == ColumnKind::String => ColumnRole::Species,
+                "pos" if is_vector => ColumnRole::Position,
+                "velo" | "vel" | "velocities" if is_vector => ColumnRole::Velocity,
+                "forces" | "force" if is_vector => ColumnRole::Force,
+                _ if kind == ColumnKind::String => ColumnRole::Ignored,
+                _ if width == 1 => ColumnRole::Scalars(vec![name.clone()]),
+                _ => ColumnRole::Scalars((0..width).map(|i| format!("{name}_{i}")).collect()),
+            };
+
+            columns.push(PropertyColumn {
+                name,
+                kind,
+                width,
+                offset,
+                role,
+            });
+            offset += width;
+        }
+
+        if !columns.iter().any(|c| c.role == ColumnRole::Position) {
+            return Err(format!("Properties has no pos:R:3 column: {spec}"));
+        }
+
+        Ok(Self {
+            columns,
+            num_fields: offset,
+        })
+    }
+
+    /// Field index of the `species` column, if any.
+    pub fn species_offset(&self) -> Option<usize> {
+        self.columns
+            .iter()
+            .find(|c| c.role == ColumnRole::Species)
+            .map(|c| c.offset)
+    }
+
+    /// Names of the per-atom properties stored in [`FrameData::properties`].
+    pub fn property_names(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .flat_map(|c| match &c.role {
+                ColumnRole::Scalars(names) => names.iter().map(String::as_str).collect(),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+}
+
+/// Parsed extxyz comment line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtXyzHeader {
+    /// Atom line layout
+    pub schema: PropertySchema,
+    /// Cell from `Lattice`
+    pub cell: Option<UnitCell>,
+    /// Periodicity along a, b, c from `pbc`
+    pub pbc: Option<[bool; 3]>,
+    /// Potential energy from `energy`
+    pub energy: Option<f32>,
+    /// Value of `time` / `t`
+    pub time: Option<f32>,
+    /// Remaining `key=value` pairs
+    pub info: Vec<(String, String)>,
+}
+
+fn parse_logical(token: &str) -> Option<bool> {
+    match token.to_ascii_lowercase().as_str() {
+        "t" | "true" | "1" => Some(true),
+        "f" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn numbers(value: &str) -> Vec<&str> {
+    value
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '[' || c == ']')
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+impl ExtXyzHeader {
+    /// Parse a comment line. `line` is used for error reporting.
+    pub fn parse(comment: &str, line: usize) -> IOResult<Self> {
+        let error = |message: String| IOError::ParseError { line, message };
+
+        let mut header = Self {
+            schema: PropertySchema::plain(),
+            cell: None,
+            pbc: None,
+            energy: None,
+            time: None,
+            info: Vec::new(),
+        };
+
+        for (key, value) in parse_key_values(comment) {
+            let Some(value) = value else {
+                continue;
+            };
+            match key.to_ascii_lowercase().as_str() {
+                "properties" => header.schema = PropertySchema::parse(&value).map_err(error)?,
+                "lattice" => {
+                    let values = numbers(&value)
+                        .iter()
+                        .map(|s| s.parse::<f32>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .ok()
+                        .filter(|v| v.len() == 9)
+                        .ok_or_else(|| {
+                            error(format!("Lattice must hold 9 numbers, got \"{value}\""))
+                        })?;
+                    header.cell = Some(UnitCell::from_vectors([
+                        Vec3::new(values[0], values[1], values[2]),
+                        Vec3::new(values[3], values[4], values[5]),
+                        Vec3::new(values[6], values[7], values[8]),
+                    ]));
+                }
+                "pbc" => {
+                    let flags: Vec<bool> = numbers(&value)
+                        .iter()
+                        .filter_map(|s| parse_logical(s))
+                        .collect();
+                    match flags.as_slice() {
+                        [a, b, c] => header.pbc = Some([*a, *b, *c]),
+                        _ => warn!("Ignoring malformed extxyz pbc=\"{value}\""),
+                    }
+                }
+                "energy" => match value.parse::<f32>() {
+                    Ok(e) => header.energy = Some(e),
+                    Err(_) => warn!("Ignoring non-numeric extxyz energy={value}"),
+                },
+                "time" | "t" => header.time = value.parse::<f32>().ok(),
+                _ => header.info.push((key, value)),
+            }
+        }
+
+        Ok(header)
+    }
+
+    /// Copy the frame-level fields into `frame`.
+    pub fn apply_to_frame(&self, frame: &mut FrameData) {
+        if let Some(cell) = self.cell {
+            frame.cell = Some(cell);
+        }
+        if let Some(energy) = self.energy {
+            frame.potential_energy = Some(energy);
+        }
+        if let Some(pbc) = self.pbc {
+            let flags: Vec<&str> = pbc.iter().map(|&p| if p { "T" } else { "F" }).collect();
+            frame.metadata.insert("pbc".to_string(), flags.join(" "));
+        }
+        for (key, value) in &self.info {
+            frame.metadata.insert(key.clone(), value.clone());
+        }
+    }
+
+    /// Species token of an atom line.
+    pub fn species<'a>(&self, atom_line: &'a str) -> Option<&'a str> {
+        let offset = self.schema.species_offset()?;
+        atom_line.split_whitespace().nth(offset)
+    }
+
+    /// Parse one atom line into `frame` as the atom at `index`.
+    pub fn read_atom(
+        &self,
+        frame: &mut FrameData,
+        index: usize,
+        atom_line: &str,
+        line: usize,
+    ) -> IOResult<()> {
+        let fields: Vec<&str> = atom_line.split_whitespace().collect();
+        if fields.len() < self.schema.num_fields {
+            return Err(IOError::ParseError {
+                line,
+                message: format!(
+                    "Expected at least {} fields ({}), got {}",
+                    self.schema.num_fields,
+                    self.schema
+                        .columns
+                        .iter()
+                        .map(|c| c.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    fields.len()
+                ),
+            });
+        }
+
+        let real = |column: &PropertyColumn, i: usize| -> IOResult<f32> {
+            let token = fields[column.offset + i];
+            let value = match column.kind {
+                ColumnKind::Logical => parse_logical(token).map(|b| if b { 1.0 } else { 0.0 }),
+                _ => token.parse::<f32>().ok(),
+            };
+            value.ok_or_else(|| IOError::ParseError {
+                line,
+                message: format!("Invalid {} value: {token}", column.name),
+            })
+        };
+        let vector = |column: &PropertyColumn| -> IOResult<Vec3> {
+            Ok(Vec3::new(
+                real(column, 0)?,
+                real(column, 1)?,
+                real(column, 2)?,
+            ))
+        };
+
+        for column in &self.schema.columns {
+            match &column.role {
+                ColumnRole::Position => frame.set_position(index, vector(column)?),
+                ColumnRole::Velocity => {
+                    let v = vector(column)?;
+                    set_atom_value(frame.velocities.get_or_insert_with(Vec::new), index, v);
+                }
+                ColumnRole::Force => {
+                    let f = vector(column)?;
+                    set_atom_value(frame.forces.get_or_insert_with(Vec::new), index, f);
+                }
+                ColumnRole::Scalars(names) => {
+                    for (i, name) in names.iter().enumerate() {
+                        let value = real(column, i)?;
+                        match frame.properties.get_mut(name) {
+                            Some(values) => set_atom_value(values, index, value),
+                            None => {
+                                let mut values = Vec::new();
+                                set_atom_value(&mut values, index, value);
+                                frame.properties.insert(name.clone(), values);
+                            }
+                        }
+                    }
+                }
+                ColumnRole::Species | ColumnRole::Ignored => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_values_quoting() {
+        let pairs = parse_key_values(
+            r#"Lattice="1 0 0 0 2 0 0 0 3" name='two words' arr={1 2} flag energy = -1.5"#,
+        );
+        assert_eq!(
+            pairs,
+            vec![
+                ("Lattice".into(), Some("1 0 0 0 2 0 0 0 3".into())),
+                ("name".into(), Some("two words".into())),
+                ("arr".into(), Some("1 2".into())),
+                ("flag".into(), None),
+                ("energy".into(), Some("-1.5".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extxyz_header_and_atom_line() {
+        let header = ExtXyzHeader::parse(
+            r#"Lattice="10.0 0.0 0.0 0.0 12.0 0.0 1.0 0.0 8.0" Properties=species:S:1:pos:R:3:forces:R:3:charge:R:1:fixed:L:1:dipole:R:2 energy=-12.5 pbc="T T F" config_type=md"#,
+            1,
+        )
+        .unwrap();
+
+        let cell = header.cell.unwrap();
+        assert_eq!(cell.vectors[2], Vec3::new(1.0, 0.0, 8.0));
+        assert_eq!(header.pbc, Some([true, true, false]));
+        assert_eq!(header.energy, Some(-12.5));
+        assert_eq!(
+            header.schema.property_names(),
+            vec!["charge", "fixed", "dipole_0", "dipole_1"]
+        );
+
+        let line = "O 1.0 2.0 3.0 0.1 0.2 0.3 -0.8 T 0.5 0.6";
+        assert_eq!(header.species(line), Some("O"));
+
+        let mut frame = FrameData::new(0, 0.0);
+        header.apply_to_frame(&mut frame);
+        header.read_atom(&mut frame, 0, line, 2).unwrap();
+
+        assert_eq!(frame.position(0), Some(Vec3::new(1.0, 2.0, 3.0)));
+        assert_eq!(frame.forces.as_ref().unwrap()[0], Vec3::new(0.1, 0.2, 0.3));
+        assert!(frame.velocities.is_none());
+        assert_eq!(frame.properties["charge"][0], -0.8);
+        assert_eq!(frame.properties["fixed"][0], 1.0);
+        assert_eq!(frame.properties["dipole_1"][0], 0.6);
+        assert_eq!(frame.potential_energy, Some(-12.5));
+        assert_eq!(frame.metadata["pbc"], "T T F");
+        assert_eq!(frame.metadata["config_type"], "md");
+        assert!(frame.cell.is_some());
+
+        assert!(header.read_atom(&mut frame, 1, "O 1.0 2.0 3.0", 3).is_err());
+    }
+
+    #[test]
+    fn test_plain_comment_falls_back_to_xyz_layout() {
+        let header = ExtXyzHeader::parse("water molecule time=2.5", 1).unwrap();
+        assert_eq!(header.schema, PropertySchema::plain());
+        assert_eq!(header.time, Some(2.5));
+        assert!(header.info.is_empty());
+
+        assert!(ExtXyzHeader::parse("Properties=species:S:1:pos:R", 1).is_err());
+        assert!(ExtXyzHeader::parse("Properties=species:S:1:mass:R:1", 1).is_err());
+    }
+}
@@ -5,12 +5,25 @@
 //! Line 2: Number of atoms
 //! Lines 3+: residue number (5) residue name (5) atom name (5) atom number (5) x y z (8.3 8.3 8.3) vx vy vz (8.4 8.4 8.4)
 //! Last line: box vectors (9 8.4)
+//!
+//! Trajectories written by `gmx trjconv -o out.gro` repeat this block once
+//! per frame, with the simulation time as `t=` (ps) in the title line.
+//! [`crate::io::gro_stream`] indexes such files for on-demand loading.
+//!
+//! Coordinates, velocities and the box are in nm. Parsed frames keep them as
+//! read and are tagged [`LengthUnit::Nanometer`]; the loader converts them to
+//! Å. The writer converts back from whatever unit a frame is tagged with.
 
 use crate::core::atom::{AtomData, Element};
-use crate::core::trajectory::{FrameData, Trajectory, TrajectoryMetadata};
-use crate::io::{IOError, IOResult};
+use crate::core::cell::UnitCell;
+use crate::core::trajectory::{
+    set_atom_value, FrameData, LengthUnit, Trajectory, TrajectoryMetadata, PS_TO_FS,
+};
+use crate::io::registry::{
+    sniff_text, Confidence, FormatCapabilities, FormatDescriptor, LoadedFile,
+};
+use crate::io::{FileFormat, IOError, IOResult};
 use bevy::prelude::*;
-use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
@@ -26,15 +39,79 @@ pub struct ParsedAtom {
     pub velocity: Option<Vec3>,
 }
 
+/// Registry entry: GROMACS GRO coordinates, single or multi-frame.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::GRO,
+    name: "GRO",
+    extensions: &["gro"],
+    capabilities: FormatCapabilities {
+        topology: true,
+        trajectory: true,
+        streaming: true,
+        writable: true,
+    },
+    needs_topology: false,
+    sniff,
+    load: Some(load),
+    read_topology: Some(read_topology),
+};
+
+/// A title line, an atom count, then fixed-column atom lines with
+/// coordinates in columns 21–44.
+fn sniff(data: &[u8]) -> Confidence {
+    let Some(text) = sniff_text(data) else {
+        return Confidence::NONE;
+    };
+    let mut lines = text.lines().skip(1);
+    if !lines
+        .next()
+        .is_some_and(|count| count.trim().parse::<usize>().is_ok())
+    {
+        return Confidence::NONE;
+    }
+    let Some(atom_line) = lines.next() else {
+        return Confidence::WEAK;
+    };
+    let has_coordinates = [(20, 28), (28, 36), (36, 44)].iter().all(|&(start, end)| {
+        atom_line
+            .get(start..end)
+            .is_some_and(|field| field.trim().parse::<f32>().is_ok())
+    });
+    if has_coordinates {
+        Confidence::LIKELY
+    } else {
+        Confidence::NONE
+    }
+}
+
+/// Atoms come from `topology_path` when given (e.g. a PDB with bonds and
+/// chains for a GRO trajectory), otherwise from the GRO atom lines.
+fn load(path: &Path, topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    let (trajectory, frame_provider) = crate::io::streaming::open_gro(path)?;
+    let (atom_data, bond_data) = match topology_path {
+        Some(topology_path) => {
+            let (atoms, bonds) = crate::io::load_topology(topology_path)?;
+            crate::io::topology::validate_atom_count(atoms.len(), trajectory.num_atoms)
+                .map_err(IOError::InvalidFormat)?;
+            (atoms, bonds)
+        }
+        None => (GroParser::parse_topology(path)?.0, Vec::new()),
+    };
+    Ok((trajectory, atom_data, bond_data, frame_provider, false))
+}
+
+fn read_topology(path: &Path) -> IOResult<(Vec<AtomData>, Vec<crate::core::bond::BondData>)> {
+    let (atoms, bonds) = GroParser::parse_topology(path)?;
+    Ok(crate::io::topology::normalize_topology(atoms, bonds))
+}
+
 /// GRO format parser
 pub struct GroParser;
 
 impl GroParser {
     /// Parse a GRO file and return trajectory data
     pub fn parse_file(path: &Path) -> IOResult<Trajectory> {
-        let file =
-            File::open(path).map_err(|_e| IOError::FileNotFound(path.display().to_string()))?;
-        let reader = BufReader::new(file);
+        let reader = crate::io::compression::open(path)?;
         Self::parse_reader(reader, path.to_path_buf())
     }
 
@@ -52,7 +129,10 @@ impl GroParser {
         Self::parse_lines(&lines, file_path)
     }
 
-    /// Parse GRO format from a vector of lines
+    /// Parse GRO format from a vector of lines.
+    ///
+    /// Frames concatenated by `gmx trjconv -o out.gro` are read one after
+    /// another; every frame must have the same atom count.
     fn parse_lines(lines: &[String], file_path: PathBuf) -> IOResult<Trajectory> {
         if lines.is_empty() {
             return Err(IOError::ParseError {
@@ -61,19 +141,77 @@ impl GroParser {
             });
         }
 
-        let mut line_iter = lines.iter().enumerate();
+        let time_step = Self::time_step_from_lines(lines);
+        let mut frames = Vec::new();
+        let mut title = String::new();
+        let mut num_atoms = 0;
+        let mut start = 0;
 
-        // Read title line (line 1)
+        while start < lines.len() {
+            if lines[start..].iter().all(|line| line.trim().is_empty()) {
+                break;
+            }
+
+            let (frame, frame_title, consumed) =
+                Self::parse_frame_block(lines, start, frames.len(), time_step)?;
+            if frames.is_empty() {
+                title = frame_title;
+                num_atoms = frame.positions.len();
+            } else if frame.positions.len() != num_atoms {
+                return Err(IOError::ParseError {
+                    line: start + 2,
+                    message: format!(
+                        "Atom count changed from {} to {} in frame {}",
+                        num_atoms,
+                        frame.positions.len(),
+                        frames.len()
+                    ),
+                });
+            }
+            frames.push(frame);
+            start += consumed;
+        }
+
+        // Create trajectory
+        let metadata = TrajectoryMetadata {
+            title,
+            software: "GROMACS".to_string(),
+            ..Default::default()
+        };
+
+        let mut trajectory = Trajectory::new(file_path, num_atoms, time_step);
+        trajectory.metadata = metadata;
+        trajectory.length_unit = LengthUnit::Nanometer;
+        for frame in frames {
+            trajectory.add_frame(frame);
+        }
+
+        Ok(trajectory)
+    }
+
+    /// Parse one frame block (title, atom count, atom lines and an optional
+    /// box line) starting at `start`.
+    ///
+    /// Returns the frame in nm, its title and the number of lines consumed.
+    pub(crate) fn parse_frame_block(
+        lines: &[String],
+        start: usize,
+        frame_index: usize,
+        time_step: f32,
+    ) -> IOResult<(FrameData, String, usize)> {
+        let mut line_iter = lines.iter().enumerate().skip(start);
+
+        // Read title line
         let title = if let Some((_, line)) = line_iter.next() {
             line.trim().to_string()
         } else {
             return Err(IOError::ParseError {
-                line: 1,
+                line: start + 1,
                 message: "Missing title line".to_string(),
             });
         };
 
-        // Read number of atoms (line 2)
+        // Read number of atoms
         let num_atoms = if let Some((line_num, line)) = line_iter.next() {
             line.trim()
                 .parse::<usize>()
@@ -83,21 +221,22 @@ impl GroParser {
                 })?
         } else {
             return Err(IOError::ParseError {
-                line: 2,
+                line: start + 2,
                 message: "Missing atom count line".to_string(),
             });
         };
 
         if num_atoms == 0 {
             return Err(IOError::ParseError {
-                line: 2,
+                line: start + 2,
                 message: "Number of atoms cannot be zero".to_string(),
             });
         }
 
         // Read atom lines
-        let mut frame = FrameData::new(0, 0.0);
-        let mut atom_data_map = HashMap::new();
+        let mut frame = FrameData::new(frame_index, frame_time(&title, frame_index, time_step));
+        frame.length_unit = LengthUnit::Nanometer;
+        frame.positions.reserve(num_atoms);
 
         for i in 0..num_atoms {
             if let Some((line_num, line)) = line_iter.next() {
@@ -105,110 +244,91 @@ impl GroParser {
                 // resid(5) resname(5) atomname(5) atomnr(5) x(8.3) y(8.3) z(8.3) vx(8.4) vy(8.4) vz(8.4)
                 let parsed = Self::parse_atom_line(line, line_num + 1, i)?;
 
-                // Create atom data
-                let atom_data = AtomData::new(
-                    i as u32,
-                    parsed.element,
-                    parsed.residue_id as u32,
-                    parsed.residue_name,
-                    "A".to_string(), // GRO doesn't have chain ID
-                    parsed.atom_name,
-                );
-                atom_data_map.insert(i as u32, atom_data.clone());
-
                 // Set position
-                frame.set_position(i as u32, parsed.position);
+                frame.set_position(i, parsed.position);
 
                 // Set velocity if available
                 if let Some(velocity) = parsed.velocity {
-                    if frame.velocities.is_none() {
-                        frame.velocities = Some(HashMap::new());
-                    }
-                    frame
-                        .velocities
-                        .as_mut()
-                        .unwrap()
-                        .insert(i as u32, velocity);
+                    set_atom_value(frame.velocities.get_or_insert_with(Vec::new), i, velocity);
                 }
             } else {
                 return Err(IOError::ParseError {
                     line: 0,
-                    message: format!("Expected {} atom lines, found {}", num_atoms, i),
+                    message: format!(
+                        "Expected {} atom lines in frame {}, found {}",
+                        num_atoms, frame_index, i
+                    ),
                 });
             }
         }
 
-        // Read box dimensions (last line) - optional
-        let mut box_size = None;
+        // Read box vectors - 3 (rectangular) or 9 (triclinic) values; optional
+        // only when it would be the last line of the file
+        let mut consumed = 2 + num_atoms;
         if let Some((line_num, line)) = line_iter.next() {
-            // Box vectors: xx yy zz
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 3 {
-                let xx = parts[0].parse::<f32>().map_err(|_| IOError::ParseError {
-                    line: line_num + 1,
-                    message: format!("Invalid box xx: {}", parts[0]),
-                })?;
-                let yy = parts[1].parse::<f32>().map_err(|_| IOError::ParseError {
-                    line: line_num + 1,
-                    message: format!("Invalid box yy: {}", parts[1]),
-                })?;
-                let zz = parts[2].parse::<f32>().map_err(|_| IOError::ParseError {
-                    line: line_num + 1,
-                    message: format!("Invalid box zz: {}", parts[2]),
-                })?;
-                box_size = Some([xx, yy, zz]);
-            }
+            consumed += 1;
+            let values = line
+                .split_whitespace()
+                .map(|part| {
+                    part.parse::<f32>().map_err(|_| IOError::ParseError {
+                        line: line_num + 1,
+                        message: format!("Invalid box value: {}", part),
+                    })
+                })
+                .collect::<IOResult<Vec<f32>>>()?;
+            frame.cell = UnitCell::from_gro_box(&values);
         }
 
-        frame.box_size = box_size;
-
-        // Create trajectory
-        let metadata = TrajectoryMetadata {
-            title,
-            software: "GROMACS".to_string(),
-            ..Default::default()
-        };
-
-        let mut trajectory = Trajectory::new(file_path, num_atoms, 1.0);
-        trajectory.metadata = metadata;
-        trajectory.add_frame(frame);
+        Ok((frame, title, consumed))
+    }
 
-        Ok(trajectory)
+    /// Time between frames in fs, from the `t=` of the first two titles.
+    fn time_step_from_lines(lines: &[String]) -> f32 {
+        let second_title = lines
+            .get(1)
+            .and_then(|count| count.trim().parse::<usize>().ok())
+            .and_then(|num_atoms| lines.get(num_atoms + 3));
+        match (lines.first(), second_title) {
+            (Some(first), Some(second)) => time_step_from_titles(first, second),
+            _ => 1.0,
+        }
     }
 
     /// Extract atom metadata from the first frame of a GRO file (topology for DCD pairing).
     pub fn parse_topology(
         path: &Path,
     ) -> IOResult<(Vec<AtomData>, Vec<crate::core::bond::BondData>)> {
-        let trajectory = Self::parse_file(path)?;
-        let mut atom_data = Vec::with_capacity(trajectory.num_atoms);
-
-        if trajectory.file_path.exists() {
-            let file = std::fs::File::open(&trajectory.file_path)?;
-            let reader = BufReader::new(file);
-            let lines: Vec<String> = reader.lines().collect::<Result<_, _>>()?;
-
-            if lines.len() >= 3 {
-                for (i, line) in lines.iter().skip(2).take(trajectory.num_atoms).enumerate() {
-                    let parsed = Self::parse_atom_line(line, i + 3, i)?;
-                    atom_data.push(AtomData::new(
-                        i as u32,
-                        parsed.element,
-                        parsed.residue_id as u32,
-                        parsed.residue_name,
-                        "A".to_string(),
-                        parsed.atom_name,
-                    ));
-                }
-            }
+        let mut lines = crate::io::compression::open(path)?.lines();
+
+        let _title = lines.next().transpose()?;
+        let count_line = lines.next().transpose()?.unwrap_or_default();
+        let num_atoms = count_line
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| IOError::ParseError {
+                line: 2,
+                message: format!("Expected number of atoms, got: {}", count_line),
+            })?;
+
+        let mut atom_data = Vec::with_capacity(num_atoms);
+        for (i, line) in lines.take(num_atoms).enumerate() {
+            let parsed = Self::parse_atom_line(&line?, i + 3, i)?;
+            atom_data.push(AtomData::new(
+                i as u32,
+                parsed.element,
+                parsed.residue_id as u32,
+                parsed.residue_name,
+                "A".to_string(),
+                parsed.atom_name,
+            ));
         }
 
-        if atom_data.len() != trajectory.num_atoms {
+        if atom_data.len() != num_atoms || num_atoms == 0 {
             return Err(IOError::ParseError {
                 line: 0,
                 message: format!(
                     "Expected {} GRO topology atoms, parsed {}",
-                    trajectory.num_atoms,
+                    num_atoms,
                     atom_data.len()
                 ),
             });
@@ -330,6 +450,41 @@ impl GroParser {
     }
 }
 
+/// Simulation time in ps from a GROMACS title such as
+/// `Generated by trjconv : Protein t=  10.00000 step= 5000`.
+pub fn parse_title_time(title: &str) -> Option<f32> {
+    let mut rest = title;
+    while let Some(pos) = rest.find("t=") {
+        let at_word_start = rest[..pos]
+            .chars()
+            .next_back()
+            .map_or(true, char::is_whitespace);
+        let value = rest[pos + 2..].split_whitespace().next();
+        if at_word_start {
+            if let Some(time) = value.and_then(|v| v.parse::<f32>().ok()) {
+                return Some(time);
+            }
+        }
+        rest = &rest[pos + 2..];
+    }
+    None
+}
+
+/// Frame time in fs: the title's `t=` if present, otherwise evenly spaced.
+pub(crate) fn frame_time(title: &str, frame_index: usize, time_step: f32) -> f32 {
+    parse_title_time(title)
+        .map(|t| t * PS_TO_FS)
+        .unwrap_or(frame_index as f32 * time_step)
+}
+
+/// Time step in fs between two consecutive frame titles (1.0 without times).
+pub(crate) fn time_step_from_titles(first: &str, second: &str) -> f32 {
+    match (parse_title_time(first), parse_title_time(second)) {
+        (Some(t0), Some(t1)) if t1 > t0 => (t1 - t0) * PS_TO_FS,
+        _ => 1.0,
+    }
+}
+
 /// Write trajectory to GRO format
 pub struct GroWriter;
 
@@ -347,8 +502,10 @@ impl GroWriter {
 
         // Write atoms
         if let Some(frame) = trajectory.frames.first() {
-            for atom_id in 0..trajectory.num_atoms as u32 {
-                if let Some(pos) = frame.get_position(atom_id) {
+            let to_nm = frame.length_unit.to_angstrom() / LengthUnit::Nanometer.to_angstrom();
+            for atom_id in 0..trajectory.num_atoms {
+                if let Some(pos) = frame.position(atom_id) {
+                    let pos = pos * to_nm;
                     let residue_name = "UNK"; // Would need atom data
                     let atom_name = "X";
                     let residue_id = (atom_id + 1) as i32;
@@ -368,13 +525,15 @@ impl GroWriter {
                 }
             }
 
-            // Write box dimensions
-            if let Some(box_size) = frame.box_size {
-                writeln!(
-                    writer,
-                    " {:8.4}{:8.4}{:8.4}",
-                    box_size[0], box_size[1], box_size[2]
-                )?;
+            // Write box vectors (9 values for triclinic cells)
+            if let Some(cell) = &frame.cell {
+                let values: String = cell
+                    .scaled(to_nm)
+                    .to_gro_box()
+                    .iter()
+                    .map(|v| format!("{:8.4}", v))
+                    .collect();
+                writeln!(writer, " {}", values)?;
             } else {
                 writeln!(writer, "  0.0000   0.0000   0.0000")?;
             }
@@ -382,6 +541,55 @@ impl GroWriter {
 
         Ok(())
     }
+
+    /// Write one frame block with atom names from `atom_data`.
+    ///
+    /// The frame time goes into the title as `t=` (ps) so multi-frame files
+    /// keep their timing; residue and atom numbers wrap at 100000 as in GROMACS.
+    pub fn write_frame<W: Write>(
+        writer: &mut W,
+        title: &str,
+        frame: &FrameData,
+        atom_data: &[AtomData],
+    ) -> IOResult<()> {
+        let to_nm = frame.length_unit.to_angstrom() / LengthUnit::Nanometer.to_angstrom();
+        let atoms: Vec<(&AtomData, Vec3)> = atom_data
+            .iter()
+            .zip(&frame.positions)
+            .map(|(atom, pos)| (atom, *pos))
+            .collect();
+
+        writeln!(writer, "{} t= {:.5}", title, frame.time / PS_TO_FS)?;
+        writeln!(writer, "{}", atoms.len())?;
+        for (i, (atom, pos)) in atoms.iter().enumerate() {
+            let pos = *pos * to_nm;
+            let residue_name: String = atom.residue_name.chars().take(5).collect();
+            let atom_name: String = atom.name.chars().take(5).collect();
+            writeln!(
+                writer,
+                "{:5}{:<5}{:>5}{:5}{:8.3}{:8.3}{:8.3}",
+                atom.residue_id % 100_000,
+                residue_name,
+                atom_name,
+                (i + 1) % 100_000,
+                pos.x,
+                pos.y,
+                pos.z
+            )?;
+        }
+
+        let values: String = match &frame.cell {
+            Some(cell) => cell
+                .scaled(to_nm)
+                .to_gro_box()
+                .iter()
+                .map(|v| format!("{:10.5}", v))
+                .collect(),
+            None => format!("{:10.5}{:10.5}{:10.5}", 0.0, 0.0, 0.0),
+        };
+        writeln!(writer, "{}", values)?;
+        Ok(())
+    }
 }
 
 /// Register GRO parsing systems with Bevy
@@ -410,6 +618,53 @@ mod tests {
         assert_eq!(trajectory.num_atoms, 3);
     }
 
+    #[test]
+    fn test_load_with_pdb_topology() {
+        let dir = tempfile::tempdir().unwrap();
+        let gro = dir.path().join("water.gro");
+        std::fs::write(
+            &gro,
+            r#"Water
+3
+    1SOL     OW    1   0.126   0.639   0.322
+    1SOL    HW1    2   0.187   0.713   0.394
+    1SOL    HW2    3   0.145   0.584   0.235
+   1.00000   1.00000   1.00000
+"#,
+        )
+        .unwrap();
+        let pdb = dir.path().join("water.pdb");
+        std::fs::write(
+            &pdb,
+            r#"HETATM    1  O   HOH W   1       1.260   6.390   3.220  1.00  0.00           O
+HETATM    2  H1  HOH W   1       1.870   7.130   3.940  1.00  0.00           H
+HETATM    3  H2  HOH W   1       1.450   5.840   2.350  1.00  0.00           H
+CONECT    1    2    3
+END
+"#,
+        )
+        .unwrap();
+
+        let (_, atoms, bonds, _, needs_topology) = load(&gro, Some(&pdb)).unwrap();
+        assert!(!needs_topology);
+        assert!(atoms
+            .iter()
+            .all(|a| a.residue_name == "HOH" && a.chain_id == "W"));
+        assert_eq!(bonds.len(), 2);
+
+        let (_, atoms, bonds, _, _) = load(&gro, None).unwrap();
+        assert_eq!(atoms[0].residue_name, "SOL");
+        assert!(bonds.is_empty());
+
+        let mismatched = dir.path().join("one.pdb");
+        std::fs::write(
+            &mismatched,
+            "HETATM    1  O   HOH W   1       1.260   6.390   3.220  1.00  0.00           O\nEND\n",
+        )
+        .unwrap();
+        assert!(load(&gro, Some(&mismatched)).is_err());
+    }
+
     #[test]
     fn test_parse_gro_with_velocities() {
         let gro_content = r#"Water with velocities
@@ -430,6 +685,85 @@ mod tests {
         assert!(frame.velocities.is_some());
     }
 
+    #[test]
+    fn test_parse_gro_triclinic_box() {
+        let gro_content = r#"Dodecahedron box
+1
+    1SOL     OW    1   0.126   0.639   0.322
+   7.00000   7.00000   4.94975   0.00000   0.00000   0.00000   0.00000   3.50000   3.50000"#;
+
+        let trajectory = GroParser::parse_string(gro_content, PathBuf::from("test.gro")).unwrap();
+        let cell = trajectory.get_frame(0).unwrap().cell.unwrap();
+        assert!(!cell.is_orthorhombic());
+        assert!((cell.vectors[2].x - 3.5).abs() < 1e-5);
+        assert!((cell.angles[0] - 60.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_units_normalize_to_angstrom_and_write_back_in_nm() {
+        let gro_content = r#"Water
+1
+    1SOL     OW    1   0.126   0.639   0.322   0.1000   0.0000   0.0000
+   1.86206   1.86206   1.86206"#;
+
+        let mut trajectory =
+            GroParser::parse_string(gro_content, PathBuf::from("test.gro")).unwrap();
+        assert_eq!(trajectory.length_unit, LengthUnit::Nanometer);
+        trajectory.normalize_units();
+
+        let frame = trajectory.get_frame(0).unwrap();
+        assert_eq!(frame.length_unit, LengthUnit::Angstrom);
+        assert!((frame.position(0).unwrap() - Vec3::new(1.26, 6.39, 3.22)).length() < 1e-4);
+        assert!((frame.velocities.as_ref().unwrap()[0].x - 1.0).abs() < 1e-5);
+        assert!((frame.cell.unwrap().lengths[0] - 18.6206).abs() < 1e-3);
+
+        let path = std::env::temp_dir().join("gumol_gro_units_test.gro");
+        GroWriter::write_trajectory(&path, &trajectory).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(written.contains("   0.126   0.639   0.322"), "{written}");
+        assert!(written.contains("  1.8621"), "{written}");
+    }
+
+    #[test]
+    fn test_parse_multi_frame_gro_with_times_and_boxes() {
+        let gro_content = r#"Generated by trjconv : Water t=   0.00000 step= 0
+1
+    1SOL     OW    1   0.126   0.639   0.322
+   1.00000   1.00000   1.00000
+Generated by trjconv : Water t=   5.00000 step= 2500
+1
+    1SOL     OW    1   0.226   0.639   0.322
+   1.10000   1.10000   1.10000
+"#;
+
+        let trajectory = GroParser::parse_string(gro_content, PathBuf::from("test.gro")).unwrap();
+        assert_eq!(trajectory.num_frames(), 2);
+        assert!((trajectory.time_step - 5000.0).abs() < 1e-3);
+        let second = trajectory.get_frame(1).unwrap();
+        assert_eq!(second.index, 1);
+        assert!((second.time - 5000.0).abs() < 1e-3);
+        assert!((second.position(0).unwrap().x - 0.226).abs() < 1e-6);
+        assert!((second.cell.unwrap().lengths[0] - 1.1).abs() < 1e-5);
+
+        let mismatched = gro_content.replace(
+            "\n1\n    1SOL     OW    1   0.226",
+            "\n2\n    1SOL     OW    1   0.226",
+        );
+        assert!(GroParser::parse_string(&mismatched, PathBuf::from("test.gro")).is_err());
+    }
+
+    #[test]
+    fn test_parse_title_time() {
+        assert_eq!(
+            parse_title_time("Generated by trjconv : Protein t=  10.00000 step= 5000"),
+            Some(10.0)
+        );
+        assert_eq!(parse_title_time("water t=2.5"), Some(2.5));
+        assert_eq!(parse_title_time("start=1 at=3"), None);
+        assert_eq!(parse_title_time("Water molecule"), None);
+    }
+
     #[test]
     fn test_element_from_atom_name() {
         // Test common element patterns
@@ -0,0 +1,319 @@
+//! Seek-based streaming for multi-frame GRO trajectories.
+//!
+//! Builds a byte-offset index of the frame blocks on open and parses single
+//! frames on demand through the shared [`FrameProvider`] interface. Frames
+//! are converted from nm to Å as they are read.
+
+use crate::core::trajectory::{FrameData, TrajectoryMetadata};
+use crate::io::gro::{time_step_from_titles, GroParser};
+use crate::io::streaming::FrameProvider;
+use crate::io::{IOError, IOResult};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Indexed metadata for a multi-frame GRO file.
+#[derive(Debug, Clone)]
+pub struct GroIndex {
+    pub num_atoms: usize,
+    pub num_frames: usize,
+    pub time_step: f32,
+    pub frame_offsets: Vec<u64>,
+    pub metadata: TrajectoryMetadata,
+}
+
+fn read_line(reader: &mut BufReader<File>, line: &mut String) -> IOResult<usize> {
+    line.clear();
+    reader.read_line(line).map_err(IOError::Io)
+}
+
+/// Scan a GRO file once and record the byte offset of each frame's title line.
+pub fn build_gro_index(path: &Path) -> IOResult<GroIndex> {
+    let file = File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+    let mut reader = BufReader::new(file);
+
+    let mut frame_offsets = Vec::new();
+    let mut titles: Vec<String> = Vec::new();
+    let mut num_atoms = 0usize;
+    let mut line = String::new();
+
+    loop {
+        let offset = reader.stream_position().map_err(IOError::Io)?;
+        let frame_index = frame_offsets.len();
+
+        if read_line(&mut reader, &mut line)? == 0 {
+            break;
+        }
+        let title = line.trim().to_string();
+
+        if read_line(&mut reader, &mut line)? == 0 {
+            if title.is_empty() {
+                break;
+            }
+            return Err(IOError::ParseError {
+                line: frame_index,
+                message: format!("Missing atom count in frame {frame_index}"),
+            });
+        }
+        let count = line
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| IOError::ParseError {
+                line: frame_index,
+                message: format!("Expected number of atoms, got: {}", line.trim()),
+            })?;
+
+        if frame_index == 0 {
+            num_atoms = count;
+        } else if count != num_atoms {
+            return Err(IOError::ParseError {
+                line: frame_index,
+                message: format!(
+                    "Atom count changed from {num_atoms} to {count} in frame {frame_index}"
+                ),
+            });
+        }
+
+        // Atom lines plus the box line
+        for atom_i in 0..num_atoms {
+            if read_line(&mut reader, &mut line)? == 0 {
+                return Err(IOError::ParseError {
+                    line: frame_index,
+                    message: format!("Unexpected EOF in frame {frame_index} at atom {atom_i}"),
+                });
+            }
+        }
+        read_line(&mut reader, &mut line)?;
+
+        frame_offsets.push(offset);
+        if titles.len() < 2 {
+            titles.push(title);
+        }
+    }
+
+    if frame_offsets.is_empty() || num_atoms == 0 {
+        return Err(IOError::ParseError {
+            line: 0,
+            message: "No frames found in GRO file".into(),
+        });
+    }
+
+    let time_step = match titles.as_slice() {
+        [first, second] => time_step_from_titles(first, second),
+        _ => 1.0,
+    };
+
+    Ok(GroIndex {
+        num_atoms,
+        num_frames: frame_offsets.len(),
+        time_step,
+        frame_offsets,
+        metadata: TrajectoryMetadata {
+            title: titles.into_iter().next().unwrap_or_default(),
+            software: "GROMACS".to_string(),
+            ..Default::default()
+        },
+    })
+}
+
+fn parse_frame_at_offset(
+    reader: &mut BufReader<File>,
+    offset: u64,
+    frame_index: usize,
+    index: &GroIndex,
+) -> IOResult<FrameData> {
+    reader.seek(SeekFrom::Start(offset)).map_err(IOError::Io)?;
+
+    let mut lines = Vec::with_capacity(index.num_atoms + 3);
+    for _ in 0..index.num_atoms + 3 {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(IOError::Io)? == 0 {
+            break;
+        }
+        lines.push(line.trim_end_matches(['\r', '\n']).to_string());
+    }
+
+    let (mut frame, _, _) = GroParser::parse_frame_block(&lines, 0, frame_index, index.time_step)?;
+    if frame.positions.len() != index.num_atoms {
+        return Err(IOError::ParseError {
+            line: frame_index,
+            message: format!(
+                "Frame {frame_index} atom count {} != {}",
+                frame.positions.len(),
+                index.num_atoms
+            ),
+        });
+    }
+    frame.normalize_units();
+    Ok(frame)
+}
+
+/// Random-access GRO frame provider backed by a seekable file handle.
+pub struct GroFrameProvider {
+    reader: Arc<Mutex<BufReader<File>>>,
+    index: GroIndex,
+    file_path: PathBuf,
+}
+
+impl GroFrameProvider {
+    pub fn open(path: &Path) -> IOResult<Self> {
+        let source = crate::io::compression::seekable_path(path)?;
+        let index = build_gro_index(&source)?;
+        let file =
+            File::open(&source).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+        Ok(Self {
+            reader: Arc::new(Mutex::new(BufReader::new(file))),
+            index,
+            file_path: path.to_path_buf(),
+        })
+    }
+
+    pub fn index(&self) -> &GroIndex {
+        &self.index
+    }
+
+    pub fn should_stream(num_atoms: usize, num_frames: usize) -> bool {
+        crate::io::streaming::should_stream_trajectory(num_atoms, num_frames)
+    }
+
+    pub fn load_all_frames(&self) -> IOResult<Vec<FrameData>> {
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("GRO reader lock poisoned".to_string()))?;
+        let mut frames = Vec::with_capacity(self.index.num_frames);
+        for (i, &offset) in self.index.frame_offsets.iter().enumerate() {
+            frames.push(parse_frame_at_offset(&mut reader, offset, i, &self.index)?);
+        }
+        Ok(frames)
+    }
+}
+
+impl FrameProvider for GroFrameProvider {
+    fn num_frames(&self) -> usize {
+        self.index.num_frames
+    }
+
+    fn num_atoms(&self) -> usize {
+        self.index.num_atoms
+    }
+
+    fn time_step(&self) -> f32 {
+        self.index.time_step
+    }
+
+    fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    fn metadata(&self) -> &TrajectoryMetadata {
+        &self.index.metadata
+    }
+
+    fn get_frame(&self, index: usize) -> IOResult<FrameData> {
+        let offset = *self
+            .index
+            .frame_offsets
+            .get(index)
+            .ok_or_else(|| IOError::ParseError {
+                line: 0,
+                message: format!(
+                    "Frame index {index} out of range ({} frames)",
+                    self.index.num_frames
+                ),
+            })?;
+
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("GRO reader lock poisoned".to_string()))?;
+
+        parse_frame_at_offset(&mut reader, offset, index, &self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::trajectory::LengthUnit;
+    use bevy::prelude::Vec3;
+    use std::io::Write;
+
+    fn write_temp_gro(path: &Path, frame_count: usize) -> std::io::Result<()> {
+        let mut file = File::create(path)?;
+        for f in 0..frame_count {
+            writeln!(
+                file,
+                "Generated by trjconv : water t= {:10.5} step= {}",
+                f as f32 * 2.0,
+                f * 1000
+            )?;
+            writeln!(file, "2")?;
+            writeln!(
+                file,
+                "    1SOL     OW    1{:8.3}   0.000   0.000",
+                f as f32 * 0.1
+            )?;
+            writeln!(file, "    1SOL    HW1    2   0.100   0.000   0.000")?;
+            writeln!(file, "{:10.5}{:10.5}{:10.5}", 3.0 + f as f32, 3.0, 3.0)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_gro_index_offsets_and_times() {
+        let dir = std::env::temp_dir().join(format!("gumol_gro_stream_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("traj.gro");
+        write_temp_gro(&path, 4).unwrap();
+
+        let index = build_gro_index(&path).unwrap();
+        assert_eq!(index.num_atoms, 2);
+        assert_eq!(index.num_frames, 4);
+        assert_eq!(index.frame_offsets[0], 0);
+        assert!((index.time_step - 2000.0).abs() < 1e-3);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_gro_provider_matches_full_parse() {
+        let dir = std::env::temp_dir().join(format!("gumol_gro_stream_cmp_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("traj.gro");
+        write_temp_gro(&path, 5).unwrap();
+
+        let mut full = GroParser::parse_file(&path).unwrap();
+        full.normalize_units();
+        let provider = GroFrameProvider::open(&path).unwrap();
+
+        assert_eq!(provider.num_frames(), full.num_frames());
+        assert_eq!(provider.num_atoms(), full.num_atoms);
+
+        for i in 0..full.num_frames() {
+            let expected = full.get_frame(i).unwrap();
+            let streamed = provider.get_frame(i).unwrap();
+            assert_eq!(streamed.length_unit, LengthUnit::Angstrom);
+            assert!((streamed.time - expected.time).abs() < 1e-3);
+            assert_eq!(streamed.cell, expected.cell);
+            for atom_id in 0..expected.num_atoms() {
+                let a = expected.position(atom_id).unwrap();
+                let b = streamed.position(atom_id).unwrap();
+                assert!(
+                    (a - b).length() < 1e-5,
+                    "frame {i} atom {atom_id}: {a:?} vs {b:?}"
+                );
+            }
+        }
+
+        let last = provider.get_frame(4).unwrap();
+        assert!((last.position(0).unwrap() - Vec3::new(4.0, 0.0, 0.0)).length() < 1e-4);
+        assert!((last.cell.unwrap().lengths[0] - 70.0).abs() < 1e-3);
+        assert!(provider.get_frame(5).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
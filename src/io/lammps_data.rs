@@ -0,0 +1,452 @@
+//! LAMMPS data file topology parser
+//!
+//! A data file (the input of `read_data`, or the output of `write_data`) is a
+//! title line, a header of counts and box bounds, and titled sections. Only
+//! `Masses`, `Atom Type Labels`, `Atoms` and `Bonds` are read.
+//!
+//! `Atoms` lines follow the atom style named in the section comment
+//! (`Atoms # full`). The `atomic`, `charge`, `bond`, `angle`, `molecular` and
+//! `full` styles are supported; without the comment the style is guessed
+//! from the column count.
+//!
+//! LAMMPS knows atom types, not elements. Each type is mapped to an element
+//! from its label (a type label, or a `Masses` comment such as
+//! `1 12.011 # C`) and its mass. Callers that know better can pass the
+//! mapping to [`LammpsDataParser::parse_string_with_types`].
+
+use crate::core::atom::{AtomData, Element};
+use crate::core::bond::{BondData, BondLengths, BondOrder, BondType};
+use crate::io::registry::{sniff_text, Confidence, FormatCapabilities, FormatDescriptor};
+use crate::io::{FileFormat, IOError, IOResult};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Element for a LAMMPS atom type from its label and/or mass.
+pub(crate) fn element_from_label_and_mass(label: Option<&str>, mass: Option<f32>) -> Element {
+    match (label, mass) {
+        (Some(label), Some(mass)) => Element::from_name_and_mass(label, mass),
+        (Some(label), None) => {
+            Element::from_symbol(label).unwrap_or_else(|_| Element::from_atom_name(label))
+        }
+        (None, Some(mass)) => Element::from_name_and_mass("", mass),
+        (None, None) => Element::Unknown,
+    }
+}
+
+/// Layout of `Atoms` section lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LammpsAtomStyle {
+    /// `id type x y z`
+    Atomic,
+    /// `id type q x y z`
+    Charge,
+    /// `id mol type x y z` (also `bond` and `angle`)
+    Molecular,
+    /// `id mol type q x y z`
+    Full,
+}
+
+impl LammpsAtomStyle {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "atomic" => Some(Self::Atomic),
+            "charge" => Some(Self::Charge),
+            "bond" | "angle" | "molecular" => Some(Self::Molecular),
+            "full" => Some(Self::Full),
+            _ => None,
+        }
+    }
+
+    /// Guess the style of an atom line, with or without image flags.
+    fn guess(fields: &[&str]) -> Option<Self> {
+        match fields.len() {
+            5 | 8 => Some(Self::Atomic),
+            7 | 10 => Some(Self::Full),
+            // The charge is a float where molecular has an integer type
+            6 | 9 if fields[2].contains(['.', 'e', 'E']) => Some(Self::Charge),
+            6 | 9 => Some(Self::Molecular),
+            _ => None,
+        }
+    }
+
+    /// Column indices of the molecule id, type and charge.
+    fn columns(self) -> (Option<usize>, usize, Option<usize>) {
+        match self {
+            Self::Atomic => (None, 1, None),
+            Self::Charge => (None, 1, Some(2)),
+            Self::Molecular => (Some(1), 2, None),
+            Self::Full => (Some(1), 2, Some(3)),
+        }
+    }
+
+    /// Number of columns before the image flags.
+    fn width(self) -> usize {
+        match self {
+            Self::Atomic => 5,
+            Self::Charge | Self::Molecular => 6,
+            Self::Full => 7,
+        }
+    }
+}
+
+/// One `Atoms` record.
+#[derive(Debug, Clone)]
+struct DataAtom {
+    id: i64,
+    molecule: u32,
+    atom_type: String,
+    charge: f32,
+}
+
+/// Split a line into content and an optional `#` comment.
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    match line.split_once('#') {
+        Some((content, comment)) => (content.trim(), Some(comment.trim())),
+        None => (line.trim(), None),
+    }
+}
+
+/// Registry entry: LAMMPS data files (topology only).
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::LammpsData,
+    name: "LAMMPS data",
+    extensions: &["data", "lmp"],
+    capabilities: FormatCapabilities {
+        topology: true,
+        trajectory: false,
+        streaming: false,
+        writable: false,
+    },
+    needs_topology: false,
+    sniff,
+    load: None,
+    read_topology: Some(LammpsDataParser::parse_topology),
+};
+
+fn sniff(data: &[u8]) -> Confidence {
+    if sniff_text(data).is_some_and(LammpsDataParser::is_lammps_data_content) {
+        Confidence::LIKELY
+    } else {
+        Confidence::NONE
+    }
+}
+
+/// LAMMPS data file parser
+pub struct LammpsDataParser;
+
+impl LammpsDataParser {
+    /// Parse a data file into atoms (ascending id order) and bonds.
+    pub fn parse_topology(path: &Path) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let content = crate::io::compression::read_to_string(path)?;
+        Self::parse_string(&content)
+    }
+
+    /// Parse data file content, mapping types to elements from labels and masses.
+    pub fn parse_string(content: &str) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        Self::parse_string_with_types(content, &HashMap::new())
+    }
+
+    /// Parse data file content with an explicit type → element mapping.
+    ///
+    /// Keys are numeric types (`"1"`) or type labels (`"CT"`); types missing
+    /// from the map fall back to their labels and masses.
+    pub fn parse_string_with_types(
+        content: &str,
+        type_elements: &HashMap<String, Element>,
+    ) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let mut num_atoms = None;
+        let mut section: Option<(String, Option<String>)> = None;
+        let mut masses: HashMap<String, (f32, Option<String>)> = HashMap::new();
+        let mut labels: HashMap<String, String> = HashMap::new();
+        let mut style = None;
+        let mut data_atoms = Vec::new();
+        let mut bond_pairs = Vec::new();
+
+        // The first line is a title
+        for (line_num, line) in content.lines().enumerate().skip(1) {
+            let (text, comment) = split_comment(line);
+            if text.is_empty() {
+                continue;
+            }
+            if Self::is_section_header(text) {
+                section = Some((text.to_string(), comment.map(str::to_string)));
+                continue;
+            }
+
+            let fields: Vec<&str> = text.split_whitespace().collect();
+            let parse_err = |what: &str| IOError::ParseError {
+                line: line_num + 1,
+                message: format!("Invalid LAMMPS data {what}: {}", line.trim()),
+            };
+
+            let Some((name, section_comment)) = &section else {
+                if fields.len() == 2 && fields[1] == "atoms" {
+                    num_atoms = Some(
+                        fields[0]
+                            .parse::<usize>()
+                            .map_err(|_| parse_err("header"))?,
+                    );
+                }
+                continue;
+            };
+
+            match name.as_str() {
+                "Masses" => {
+                    let mass = fields
+                        .get(1)
+                        .and_then(|m| m.parse::<f32>().ok())
+                        .ok_or_else(|| parse_err("mass"))?;
+                    masses.insert(fields[0].to_string(), (mass, comment.map(str::to_string)));
+                }
+                "Atom Type Labels" => {
+                    let label = fields.get(1).ok_or_else(|| parse_err("type label"))?;
+                    labels.insert(fields[0].to_string(), label.to_string());
+                }
+                "Atoms" => {
+                    let style = match style {
+                        Some(style) => style,
+                        None => {
+                            let hint = section_comment.as_deref().unwrap_or("");
+                            let chosen = if hint.is_empty() {
+                                LammpsAtomStyle::guess(&fields)
+                            } else {
+                                LammpsAtomStyle::from_name(hint)
+                            }
+                            .ok_or_else(|| {
+                                IOError::UnsupportedFormat(format!(
+                                    "LAMMPS atom style {:?}",
+                                    if hint.is_empty() { text } else { hint }
+                                ))
+                            })?;
+                            *style.insert(chosen)
+                        }
+                    };
+                    if fields.len() < style.width() {
+                        return Err(parse_err("atom line"));
+                    }
+                    let (mol_col, type_col, q_col) = style.columns();
+                    data_atoms.push(DataAtom {
+                        id: fields[0].parse().map_err(|_| parse_err("atom id"))?,
+                        molecule: match mol_col {
+                            Some(col) => {
+                                fields[col].parse().map_err(|_| parse_err("molecule id"))?
+                            }
+                            None => 0,
+                        },
+                        atom_type: fields[type_col].to_string(),
+                        charge: match q_col {
+                            Some(col) => fields[col].parse().map_err(|_| parse_err("charge"))?,
+                            None => 0.0,
+                        },
+                    });
+                }
+                "Bonds" => {
+                    if fields.len() < 4 {
+                        return Err(parse_err("bond line"));
+                    }
+                    let a: i64 = fields[2].parse().map_err(|_| parse_err("bond atom"))?;
+                    let b: i64 = fields[3].parse().map_err(|_| parse_err("bond atom"))?;
+                    bond_pairs.push((a, b));
+                }
+                _ => {}
+            }
+        }
+
+        if data_atoms.is_empty() {
+            return Err(IOError::InvalidFormat(
+                "LAMMPS data file has no Atoms section".to_string(),
+            ));
+        }
+        if let Some(expected) = num_atoms.filter(|n| *n != data_atoms.len()) {
+            return Err(IOError::InvalidFormat(format!(
+                "LAMMPS data file declares {expected} atoms but lists {}",
+                data_atoms.len()
+            )));
+        }
+
+        // Type labels may stand in for numeric types anywhere
+        let numeric_type: HashMap<&str, &str> = labels
+            .iter()
+            .map(|(id, label)| (label.as_str(), id.as_str()))
+            .collect();
+        let canonical = |t: &str| numeric_type.get(t).copied().unwrap_or(t).to_string();
+        let masses: HashMap<String, (f32, Option<String>)> = masses
+            .into_iter()
+            .map(|(t, value)| (canonical(&t), value))
+            .collect();
+
+        let mut type_info: HashMap<String, (Element, Option<String>)> = HashMap::new();
+        data_atoms.sort_by_key(|atom| atom.id);
+        let mut id_to_index = HashMap::with_capacity(data_atoms.len());
+        let mut atoms = Vec::with_capacity(data_atoms.len());
+
+        for (i, data_atom) in data_atoms.iter().enumerate() {
+            if id_to_index.insert(data_atom.id, i as u32).is_some() {
+                return Err(IOError::InvalidFormat(format!(
+                    "LAMMPS data file has duplicate atom id {}",
+                    data_atom.id
+                )));
+            }
+            let atom_type = canonical(&data_atom.atom_type);
+            let (element, label) = type_info
+                .entry(atom_type.clone())
+                .or_insert_with(|| {
+                    let mass = masses.get(&atom_type);
+                    let label = labels
+                        .get(&atom_type)
+                        .cloned()
+                        .or_else(|| mass.and_then(|(_, comment)| comment.clone()))
+                        .filter(|label| !label.is_empty());
+                    let element = type_elements
+                        .get(&atom_type)
+                        .or_else(|| label.as_ref().and_then(|l| type_elements.get(l)))
+                        .copied()
+                        .unwrap_or_else(|| {
+                            element_from_label_and_mass(
+                                label.as_deref(),
+                                mass.map(|(mass, _)| *mass),
+                            )
+                        });
+                    (element, label)
+                })
+                .clone();
+
+            let mut atom = AtomData::new(
+                i as u32,
+                element,
+                data_atom.molecule,
+                "UNK".to_string(),
+                "A".to_string(),
+                label.unwrap_or(atom_type.clone()),
+            );
+            atom.charge = data_atom.charge;
+            if let Some((mass, _)) = masses.get(&atom_type) {
+                atom.mass = *mass;
+            }
+            atoms.push(atom);
+        }
+
+        let bonds = bond_pairs
+            .into_iter()
+            .map(|(a, b)| {
+                let (Some(&a), Some(&b)) = (id_to_index.get(&a), id_to_index.get(&b)) else {
+                    return Err(IOError::InvalidFormat(format!(
+                        "LAMMPS bond references unknown atom ({a}, {b})"
+                    )));
+                };
+                Ok(BondData::new(
+                    a,
+                    b,
+                    BondType::Covalent,
+                    BondOrder::Single,
+                    BondLengths::get_length(atoms[a as usize].element, atoms[b as usize].element),
+                ))
+            })
+            .collect::<IOResult<Vec<_>>>()?;
+
+        Ok((atoms, bonds))
+    }
+
+    /// Whether a line opens a section. Lines inside `Masses` and the bond
+    /// lists may start with a type label, so keywords are matched exactly.
+    fn is_section_header(text: &str) -> bool {
+        const SECTIONS: &[&str] = &[
+            "Atoms",
+            "Velocities",
+            "Masses",
+            "Ellipsoids",
+            "Lines",
+            "Triangles",
+            "Bodies",
+            "Bonds",
+            "Angles",
+            "Dihedrals",
+            "Impropers",
+        ];
+        SECTIONS.contains(&text) || text.ends_with(" Coeffs") || text.ends_with(" Type Labels")
+    }
+
+    /// Check whether content looks like a data file (`N atoms` in the header).
+    pub fn is_lammps_data_content(content: &str) -> bool {
+        content.lines().skip(1).take(30).any(|line| {
+            let fields: Vec<&str> = split_comment(line).0.split_whitespace().collect();
+            fields.len() == 2 && fields[1] == "atoms" && fields[0].parse::<usize>().is_ok()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two waters in `full` style, written out of id order.
+    const WATER_DATA: &str = "\
+LAMMPS data file via write_data
+
+6 atoms
+2 atom types
+4 bonds
+1 bond types
+
+0.0 20.0 xlo xhi
+0.0 20.0 ylo yhi
+0.0 20.0 zlo zhi
+
+Masses
+
+1 15.9994 # OW
+2 1.008
+
+Atoms # full
+
+4 2 1 -0.834 10.0 10.0 10.0 0 0 0
+1 1 1 -0.834 1.0 1.0 1.0
+2 1 2 0.417 1.9 1.0 1.0
+3 1 2 0.417 0.8 1.9 1.0
+5 2 2 0.417 10.9 10.0 10.0
+6 2 2 0.417 9.8 10.9 10.0
+
+Bonds
+
+1 1 1 2
+2 1 1 3
+3 1 4 5
+4 1 4 6
+";
+
+    #[test]
+    fn test_parse_full_style_data_file() {
+        let (atoms, bonds) = LammpsDataParser::parse_string(WATER_DATA).unwrap();
+        assert_eq!(atoms.len(), 6);
+        assert_eq!(atoms[0].element, Element::O);
+        assert_eq!(atoms[0].name, "OW");
+        assert_eq!(atoms[1].element, Element::H);
+        assert_eq!(atoms[1].name, "2");
+        assert_eq!(atoms[3].residue_id, 2);
+        assert!((atoms[3].charge + 0.834).abs() < 1e-6);
+
+        assert_eq!(bonds.len(), 4);
+        assert_eq!((bonds[2].atom_a_id, bonds[2].atom_b_id), (3, 4));
+        assert!(LammpsDataParser::is_lammps_data_content(WATER_DATA));
+    }
+
+    #[test]
+    fn test_type_mapping_overrides_and_style_guess() {
+        // No style comment: six columns with an integer third field is molecular
+        let data = "title\n\n2 atoms\n\nAtoms\n\n1 7 1 0.0 0.0 0.0\n2 7 2 1.0 0.0 0.0\n";
+        let (atoms, _) = LammpsDataParser::parse_string(data).unwrap();
+        assert_eq!(atoms[0].residue_id, 7);
+        assert_eq!(atoms[0].element, Element::Unknown);
+
+        let types = HashMap::from([
+            ("1".to_string(), Element::Si),
+            ("2".to_string(), Element::O),
+        ]);
+        let (atoms, _) = LammpsDataParser::parse_string_with_types(data, &types).unwrap();
+        assert_eq!(atoms[0].element, Element::Si);
+        assert_eq!(atoms[1].element, Element::O);
+
+        let mismatched = data.replace("2 atoms", "3 atoms");
+        assert!(LammpsDataParser::parse_string(&mismatched).is_err());
+    }
+}
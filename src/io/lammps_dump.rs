@@ -0,0 +1,849 @@
+//! LAMMPS text dump (`.lammpstrj` / `.dump`) reader
+//!
+//! A dump is a sequence of snapshots, each a block of `ITEM:` sections:
+//!
+//! ```text
+//! ITEM: TIMESTEP
+//! 1000
+//! ITEM: NUMBER OF ATOMS
+//! 2
+//! ITEM: BOX BOUNDS xy xz yz pp pp pp
+//! -1.0 11.0 1.0
+//! 0.0 10.0 0.0
+//! 0.0 10.0 0.0
+//! ITEM: ATOMS id type xs ys zs vx vy vz c_pe
+//! 1 1 0.1 0.2 0.3 0.0 0.0 0.0 -3.2
+//! 2 2 0.5 0.5 0.5 0.0 0.0 0.0 -2.9
+//! ```
+//!
+//! Columns may come in any order. Positions are read from `x y z`
+//! (wrapped), `xu yu zu` (unwrapped) or their scaled variants `xs ys zs` /
+//! `xsu ysu zsu`, which are converted with the box; triclinic boxes are
+//! rebuilt from their tilt factors. `vx vy vz` and `fx fy fz` fill the frame's
+//! velocities and forces. Every other numeric column (`q`, `c_pe`,
+//! `v_stress`, ...) is kept as a named per-atom property of the frame.
+//!
+//! Positions are shifted so the box starts at the origin, like [`UnitCell`].
+//! Lengths are taken as Å (`units real` / `metal`); velocities and forces are
+//! kept as written. Dumps record step numbers rather than times, so frame
+//! times are MD steps.
+//!
+//! Atoms are numbered by ascending LAMMPS `id`, which keeps unsorted dumps
+//! consistent across frames and with the paired
+//! [`data` file](crate::io::lammps_data).
+
+use crate::core::atom::{AtomData, Element};
+use crate::core::cell::UnitCell;
+use crate::core::trajectory::{set_atom_value, FrameData, TrajectoryMetadata};
+use crate::io::lammps_data::element_from_label_and_mass;
+use crate::io::registry::{
+    sniff_text, Confidence, FormatCapabilities, FormatDescriptor, LoadedFile,
+};
+use crate::io::streaming::FrameProvider;
+use crate::io::{FileFormat, IOError, IOResult};
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Which coordinate columns a dump provides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateStyle {
+    /// `x y z`, wrapped into the box
+    Wrapped,
+    /// `xu yu zu`, unwrapped across periodic images
+    Unwrapped,
+    /// `xs ys zs`, fractional
+    Scaled,
+    /// `xsu ysu zsu`, fractional and unwrapped
+    ScaledUnwrapped,
+}
+
+impl CoordinateStyle {
+    /// Column names of each style, in order of preference.
+    const COLUMNS: [(CoordinateStyle, [&'static str; 3]); 4] = [
+        (CoordinateStyle::Wrapped, ["x", "y", "z"]),
+        (CoordinateStyle::Unwrapped, ["xu", "yu", "zu"]),
+        (CoordinateStyle::Scaled, ["xs", "ys", "zs"]),
+        (CoordinateStyle::ScaledUnwrapped, ["xsu", "ysu", "zsu"]),
+    ];
+
+    /// Whether coordinates are fractional and need the box to convert.
+    pub fn is_scaled(self) -> bool {
+        matches!(
+            self,
+            CoordinateStyle::Scaled | CoordinateStyle::ScaledUnwrapped
+        )
+    }
+}
+
+/// Column layout of an `ITEM: ATOMS` header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtomColumns {
+    pub id: Option<usize>,
+    pub atom_type: Option<usize>,
+    pub element: Option<usize>,
+    pub molecule: Option<usize>,
+    pub coordinates: CoordinateStyle,
+    pub position: [usize; 3],
+    pub velocity: Option<[usize; 3]>,
+    pub force: Option<[usize; 3]>,
+    /// Remaining columns, kept as named per-atom properties
+    pub properties: Vec<(String, usize)>,
+    /// Total number of columns per atom line
+    pub count: usize,
+}
+
+impl AtomColumns {
+    /// Parse the column names following `ITEM: ATOMS`.
+    pub fn parse(names: &str) -> IOResult<Self> {
+        let names: Vec<&str> = names.split_whitespace().collect();
+        let find = |name: &str| names.iter().position(|n| *n == name);
+        let triple = |cols: [&str; 3]| Some([find(cols[0])?, find(cols[1])?, find(cols[2])?]);
+
+        let (coordinates, position) = CoordinateStyle::COLUMNS
+            .iter()
+            .find_map(|(style, cols)| triple(*cols).map(|p| (*style, p)))
+            .ok_or_else(|| {
+                IOError::InvalidFormat(format!(
+                    "LAMMPS dump has no coordinate columns: ITEM: ATOMS {}",
+                    names.join(" ")
+                ))
+            })?;
+
+        let reserved = |name: &str| {
+            matches!(
+                name,
+                "id" | "type" | "element" | "mol" | "vx" | "vy" | "vz" | "fx" | "fy" | "fz"
+            ) || CoordinateStyle::COLUMNS
+                .iter()
+                .any(|(_, cols)| cols.contains(&name))
+        };
+        let properties = names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| !reserved(name))
+            .map(|(i, name)| (name.to_string(), i))
+            .collect();
+
+        Ok(Self {
+            id: find("id"),
+            atom_type: find("type"),
+            element: find("element"),
+            molecule: find("mol"),
+            coordinates,
+            position,
+            velocity: triple(["vx", "vy", "vz"]),
+            force: triple(["fx", "fy", "fz"]),
+            properties,
+            count: names.len(),
+        })
+    }
+}
+
+/// Simulation box from `ITEM: BOX BOUNDS`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DumpBox {
+    /// Lower corner `(xlo, ylo, zlo)`
+    pub origin: Vec3,
+    pub cell: UnitCell,
+}
+
+impl DumpBox {
+    /// Build the box from its three bound lines.
+    ///
+    /// Orthogonal boxes have `lo hi` per line; triclinic boxes add the tilt
+    /// factors `xy`, `xz` and `yz` as a third value and store the bounding
+    /// box of the tilted cell rather than `lo`/`hi` themselves.
+    pub fn from_bounds(bounds: [[f32; 3]; 3], triclinic: bool) -> Self {
+        let [[xlo_b, xhi_b, xy], [ylo_b, yhi_b, xz], [zlo, zhi, yz]] = bounds;
+        if !triclinic {
+            return Self {
+                origin: Vec3::new(xlo_b, ylo_b, zlo),
+                cell: UnitCell::orthorhombic([xhi_b - xlo_b, yhi_b - ylo_b, zhi - zlo]),
+            };
+        }
+
+        let xlo = xlo_b - 0.0f32.min(xy).min(xz).min(xy + xz);
+        let xhi = xhi_b - 0.0f32.max(xy).max(xz).max(xy + xz);
+        let ylo = ylo_b - 0.0f32.min(yz);
+        let yhi = yhi_b - 0.0f32.max(yz);
+        Self {
+            origin: Vec3::new(xlo, ylo, zlo),
+            cell: UnitCell::from_vectors([
+                Vec3::new(xhi - xlo, 0.0, 0.0),
+                Vec3::new(xy, yhi - ylo, 0.0),
+                Vec3::new(xz, yz, zhi - zlo),
+            ]),
+        }
+    }
+}
+
+/// The `ITEM:` sections of one snapshot, up to the atom lines.
+#[derive(Debug, Clone)]
+struct SnapshotHeader {
+    timestep: i64,
+    num_atoms: usize,
+    bounds: Option<DumpBox>,
+    columns: AtomColumns,
+}
+
+fn read_line<R: BufRead>(reader: &mut R, line: &mut String) -> IOResult<usize> {
+    line.clear();
+    reader.read_line(line).map_err(IOError::Io)
+}
+
+fn parse_field<T: std::str::FromStr>(field: &str, what: &str, frame_index: usize) -> IOResult<T> {
+    field.parse::<T>().map_err(|_| IOError::ParseError {
+        line: frame_index,
+        message: format!("Invalid LAMMPS {what} in frame {frame_index}: {field:?}"),
+    })
+}
+
+/// Read the value line following an `ITEM:` line.
+fn read_value<R: BufRead, T: std::str::FromStr>(
+    reader: &mut R,
+    line: &mut String,
+    what: &str,
+    frame_index: usize,
+) -> IOResult<T> {
+    read_line(reader, line)?;
+    parse_field(line.trim(), what, frame_index)
+}
+
+/// Read a snapshot header; `None` at end of file.
+///
+/// A header cut short by the end of the file (a running simulation) also
+/// yields `None`, with a warning.
+fn read_header<R: BufRead>(reader: &mut R, frame_index: usize) -> IOResult<Option<SnapshotHeader>> {
+    let mut line = String::new();
+    let mut timestep = None;
+    let mut num_atoms = None;
+    let mut bounds = None;
+
+    loop {
+        if read_line(reader, &mut line)? == 0 {
+            if timestep.is_some() {
+                warn!("LAMMPS dump: dropping truncated frame {frame_index}");
+            }
+            return Ok(None);
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let Some(item) = trimmed.strip_prefix("ITEM:").map(str::trim) else {
+            return Err(IOError::ParseError {
+                line: frame_index,
+                message: format!("Expected an ITEM: line in frame {frame_index}, got: {trimmed}"),
+            });
+        };
+
+        if item == "TIMESTEP" {
+            timestep = Some(read_value(reader, &mut line, "timestep", frame_index)?);
+        } else if item == "NUMBER OF ATOMS" {
+            num_atoms = Some(read_value(reader, &mut line, "atom count", frame_index)?);
+        } else if let Some(flags) = item.strip_prefix("BOX BOUNDS") {
+            if flags.split_whitespace().any(|flag| flag == "abc") {
+                return Err(IOError::UnsupportedFormat(
+                    "LAMMPS general triclinic boxes (BOX BOUNDS abc origin)".to_string(),
+                ));
+            }
+            let triclinic = flags.split_whitespace().any(|flag| flag == "xy");
+            let mut values = [[0.0f32; 3]; 3];
+            for row in values.iter_mut() {
+                read_line(reader, &mut line)?;
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() < if triclinic { 3 } else { 2 } {
+                    return Err(IOError::ParseError {
+                        line: frame_index,
+                        message: format!("Invalid LAMMPS box bounds line: {}", line.trim()),
+                    });
+                }
+                for (value, field) in row.iter_mut().zip(&fields) {
+                    *value = parse_field(field, "box bound", frame_index)?;
+                }
+            }
+            bounds = Some(DumpBox::from_bounds(values, triclinic));
+        } else if let Some(names) = item.strip_prefix("ATOMS") {
+            let (Some(timestep), Some(num_atoms)) = (timestep, num_atoms) else {
+                return Err(IOError::ParseError {
+                    line: frame_index,
+                    message: format!(
+                        "Frame {frame_index} lacks ITEM: TIMESTEP or ITEM: NUMBER OF ATOMS"
+                    ),
+                });
+            };
+            let columns = AtomColumns::parse(names)?;
+            if columns.coordinates.is_scaled() && bounds.is_none() {
+                return Err(IOError::InvalidFormat(
+                    "LAMMPS dump has scaled coordinates but no BOX BOUNDS".to_string(),
+                ));
+            }
+            return Ok(Some(SnapshotHeader {
+                timestep,
+                num_atoms,
+                bounds,
+                columns,
+            }));
+        } else {
+            // Single-value items such as ITEM: TIME and ITEM: UNITS
+            read_line(reader, &mut line)?;
+        }
+    }
+}
+
+/// Read the atom lines of a snapshot as whitespace-separated fields.
+fn read_atom_lines<R: BufRead>(
+    reader: &mut R,
+    header: &SnapshotHeader,
+    frame_index: usize,
+) -> IOResult<Vec<Vec<String>>> {
+    let mut line = String::new();
+    let mut rows = Vec::with_capacity(header.num_atoms);
+    while rows.len() < header.num_atoms {
+        if read_line(reader, &mut line)? == 0 {
+            return Err(IOError::ParseError {
+                line: frame_index,
+                message: format!(
+                    "Frame {frame_index} has {} atom lines, expected {}",
+                    rows.len(),
+                    header.num_atoms
+                ),
+            });
+        }
+        let fields: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+        if fields.is_empty() {
+            continue;
+        }
+        if fields.len() < header.columns.count {
+            return Err(IOError::ParseError {
+                line: frame_index,
+                message: format!(
+                    "Atom line in frame {frame_index} has {} columns, expected {}",
+                    fields.len(),
+                    header.columns.count
+                ),
+            });
+        }
+        rows.push(fields);
+    }
+    Ok(rows)
+}
+
+/// Number atoms by ascending LAMMPS id.
+fn id_map_from_rows(rows: &[Vec<String>], id_col: usize) -> IOResult<HashMap<i64, u32>> {
+    let mut ids = rows
+        .iter()
+        .map(|row| parse_field::<i64>(&row[id_col], "atom id", 0))
+        .collect::<IOResult<Vec<i64>>>()?;
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.len() != rows.len() {
+        return Err(IOError::InvalidFormat(
+            "LAMMPS dump has duplicate atom ids".to_string(),
+        ));
+    }
+    Ok(ids.into_iter().zip(0u32..).collect())
+}
+
+fn vector(row: &[String], cols: [usize; 3], what: &str, frame_index: usize) -> IOResult<Vec3> {
+    Ok(Vec3::new(
+        parse_field(&row[cols[0]], what, frame_index)?,
+        parse_field(&row[cols[1]], what, frame_index)?,
+        parse_field(&row[cols[2]], what, frame_index)?,
+    ))
+}
+
+/// Convert one snapshot's atom lines into a frame.
+fn frame_from_rows(
+    rows: &[Vec<String>],
+    header: &SnapshotHeader,
+    frame_index: usize,
+    id_map: &HashMap<i64, u32>,
+) -> IOResult<FrameData> {
+    let columns = &header.columns;
+    let mut frame = FrameData::new(frame_index, header.timestep as f32);
+    frame
+        .metadata
+        .insert("timestep".to_string(), header.timestep.to_string());
+    frame.cell = header.bounds.map(|b| b.cell);
+    let origin = header.bounds.map_or(Vec3::ZERO, |b| b.origin);
+
+    // Rows may come in any order; ids place them in atom order
+    let n = rows.len();
+    frame.positions = vec![Vec3::ZERO; n];
+    let mut velocities = columns.velocity.map(|_| vec![Vec3::ZERO; n]);
+    let mut forces = columns.force.map(|_| vec![Vec3::ZERO; n]);
+    let mut properties: Vec<Vec<f32>> = columns.properties.iter().map(|_| vec![0.0; n]).collect();
+
+    for (row_index, row) in rows.iter().enumerate() {
+        let index = match columns.id {
+            Some(col) => {
+                let id: i64 = parse_field(&row[col], "atom id", frame_index)?;
+                *id_map.get(&id).ok_or_else(|| IOError::ParseError {
+                    line: frame_index,
+                    message: format!("Frame {frame_index} has atom id {id} not in the first frame"),
+                })? as usize
+            }
+            None => row_index,
+        };
+
+        let position = vector(row, columns.position, "coordinate", frame_index)?;
+        let position = match header.bounds {
+            Some(bounds) if columns.coordinates.is_scaled() => bounds.cell.to_cartesian(position),
+            _ => position - origin,
+        };
+        frame.set_position(index, position);
+
+        if let (Some(values), Some(cols)) = (velocities.as_mut(), columns.velocity) {
+            set_atom_value(values, index, vector(row, cols, "velocity", frame_index)?);
+        }
+        if let (Some(values), Some(cols)) = (forces.as_mut(), columns.force) {
+            set_atom_value(values, index, vector(row, cols, "force", frame_index)?);
+        }
+        for (values, (name, col)) in properties.iter_mut().zip(&columns.properties) {
+            set_atom_value(values, index, parse_field(&row[*col], name, frame_index)?);
+        }
+    }
+
+    frame.velocities = velocities;
+    frame.forces = forces;
+    frame.properties = columns
+        .properties
+        .iter()
+        .map(|(name, _)| name.clone())
+        .zip(properties)
+        .collect();
+    Ok(frame)
+}
+
+/// Indexed metadata for a LAMMPS dump.
+#[derive(Debug, Clone)]
+pub struct LammpsDumpIndex {
+    pub num_atoms: usize,
+    pub num_frames: usize,
+    /// MD steps between the first two snapshots
+    pub time_step: f32,
+    pub frame_offsets: Vec<u64>,
+    /// LAMMPS atom id → atom index; empty when the dump has no `id` column
+    pub id_map: HashMap<i64, u32>,
+    pub metadata: TrajectoryMetadata,
+}
+
+/// Scan a dump and record the byte offset of each snapshot.
+pub fn build_lammps_dump_index(path: &Path) -> IOResult<LammpsDumpIndex> {
+    let file = File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+    let mut reader = BufReader::new(file);
+
+    let mut frame_offsets = Vec::new();
+    let mut timesteps = Vec::new();
+    let mut num_atoms = None;
+    let mut id_map = HashMap::new();
+
+    loop {
+        let offset = reader.stream_position().map_err(IOError::Io)?;
+        let frame_index = frame_offsets.len();
+        let Some(header) = read_header(&mut reader, frame_index)? else {
+            break;
+        };
+        let expected = *num_atoms.get_or_insert(header.num_atoms);
+        if header.num_atoms != expected {
+            return Err(IOError::InvalidFormat(format!(
+                "LAMMPS dump frame {frame_index} has {} atoms, expected {expected}; atom counts must not change",
+                header.num_atoms
+            )));
+        }
+
+        let rows = match read_atom_lines(&mut reader, &header, frame_index) {
+            Ok(rows) => rows,
+            Err(_) if frame_index > 0 => {
+                warn!("LAMMPS dump: dropping truncated frame {frame_index}");
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        if frame_index == 0 {
+            if let Some(id_col) = header.columns.id {
+                id_map = id_map_from_rows(&rows, id_col)?;
+            }
+        }
+
+        frame_offsets.push(offset);
+        timesteps.push(header.timestep);
+    }
+
+    if frame_offsets.is_empty() {
+        return Err(IOError::ParseError {
+            line: 0,
+            message: "No frames found in LAMMPS dump".into(),
+        });
+    }
+
+    let time_step = match timesteps.as_slice() {
+        [first, second, ..] if second > first => (second - first) as f32,
+        _ => 1.0,
+    };
+
+    Ok(LammpsDumpIndex {
+        num_atoms: num_atoms.unwrap_or(0),
+        num_frames: frame_offsets.len(),
+        time_step,
+        frame_offsets,
+        id_map,
+        metadata: TrajectoryMetadata {
+            title: "LAMMPS dump".to_string(),
+            software: "LAMMPS".to_string(),
+            num_steps: timesteps.last().map(|&step| step.max(0) as u64),
+            ..Default::default()
+        },
+    })
+}
+
+fn parse_frame_at_offset(
+    reader: &mut BufReader<File>,
+    offset: u64,
+    frame_index: usize,
+    index: &LammpsDumpIndex,
+) -> IOResult<FrameData> {
+    reader.seek(SeekFrom::Start(offset)).map_err(IOError::Io)?;
+    let header = read_header(reader, frame_index)?.ok_or_else(|| IOError::ParseError {
+        line: frame_index,
+        message: format!("Frame {frame_index} is truncated"),
+    })?;
+    let rows = read_atom_lines(reader, &header, frame_index)?;
+    frame_from_rows(&rows, &header, frame_index, &index.id_map)
+}
+
+/// Registry entry: LAMMPS text dumps. Elements come from the dump's own
+/// columns, or from a LAMMPS data file given as topology.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::LammpsDump,
+    name: "LAMMPS dump",
+    extensions: &["lammpstrj", "lammpsdump", "dump"],
+    capabilities: FormatCapabilities {
+        topology: false,
+        trajectory: true,
+        streaming: true,
+        writable: false,
+    },
+    needs_topology: false,
+    sniff,
+    load: Some(load),
+    read_topology: None,
+};
+
+fn sniff(data: &[u8]) -> Confidence {
+    if sniff_text(data).is_some_and(LammpsDumpParser::is_lammps_dump_content) {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    let (trajectory, frame_provider) = crate::io::streaming::open_lammps_dump(path)?;
+    if let Some(topology_path) = topology_path {
+        let (atom_data, bond_data) = crate::io::load_topology(topology_path)?;
+        crate::io::topology::validate_atom_count(atom_data.len(), trajectory.num_atoms)
+            .map_err(IOError::InvalidFormat)?;
+        Ok((trajectory, atom_data, bond_data, frame_provider, false))
+    } else {
+        // Numeric atom types alone leave elements to a data file
+        let (atom_data, complete) = LammpsDumpParser::parse_topology(path)?;
+        Ok((trajectory, atom_data, Vec::new(), frame_provider, !complete))
+    }
+}
+
+/// LAMMPS dump parser for atom metadata.
+pub struct LammpsDumpParser;
+
+impl LammpsDumpParser {
+    /// Atom metadata from the first snapshot, in ascending id order.
+    ///
+    /// Elements come from an `element` column, a non-numeric type label or
+    /// a `mass` column. The flag is `false` when only numeric types were
+    /// found and atoms are placeholders awaiting a `data` file.
+    pub fn parse_topology(path: &Path) -> IOResult<(Vec<AtomData>, bool)> {
+        let mut reader = crate::io::compression::open(path)?;
+        let header = read_header(&mut reader, 0)?.ok_or_else(|| IOError::ParseError {
+            line: 0,
+            message: "No frames found in LAMMPS dump".into(),
+        })?;
+        let mut rows = read_atom_lines(&mut reader, &header, 0)?;
+        let columns = &header.columns;
+        if let Some(id_col) = columns.id {
+            let id_map = id_map_from_rows(&rows, id_col)?;
+            rows.sort_by_key(|row| row[id_col].parse::<i64>().map_or(0, |id| id_map[&id]));
+        }
+
+        let mass_col = columns
+            .properties
+            .iter()
+            .find(|(name, _)| name == "mass")
+            .map(|(_, col)| *col);
+        let charge_col = columns
+            .properties
+            .iter()
+            .find(|(name, _)| name == "q")
+            .map(|(_, col)| *col);
+
+        let mut complete = true;
+        let atoms = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let atom_type = columns.atom_type.map(|col| row[col].as_str());
+                let label = columns
+                    .element
+                    .map(|col| row[col].as_str())
+                    .or(atom_type.filter(|t| t.parse::<i64>().is_err()));
+                let mass = mass_col.and_then(|col| row[col].parse::<f32>().ok());
+                let element = element_from_label_and_mass(label, mass);
+                complete &= element != Element::Unknown;
+
+                let residue_id = columns
+                    .molecule
+                    .and_then(|col| row[col].parse::<u32>().ok())
+                    .unwrap_or(0);
+                let name = label
+                    .or(atom_type)
+                    .map_or_else(|| element.symbol().to_string(), str::to_string);
+                let mut atom = AtomData::new(
+                    i as u32,
+                    element,
+                    residue_id,
+                    "UNK".to_string(),
+                    "A".to_string(),
+                    name,
+                );
+                if let Some(mass) = mass {
+                    atom.mass = mass;
+                }
+                if let Some(q) = charge_col.and_then(|col| row[col].parse::<f32>().ok()) {
+                    atom.charge = q;
+                }
+                atom
+            })
+            .collect();
+
+        Ok((atoms, complete))
+    }
+
+    /// Check whether content looks like a LAMMPS dump.
+    pub fn is_lammps_dump_content(content: &str) -> bool {
+        content
+            .lines()
+            .next()
+            .is_some_and(|line| line.trim() == "ITEM: TIMESTEP")
+    }
+}
+
+/// Random-access LAMMPS dump frame provider backed by a seekable file handle.
+pub struct LammpsDumpFrameProvider {
+    reader: Arc<Mutex<BufReader<File>>>,
+    index: LammpsDumpIndex,
+    file_path: PathBuf,
+}
+
+impl LammpsDumpFrameProvider {
+    pub fn open(path: &Path) -> IOResult<Self> {
+        let source = crate::io::compression::seekable_path(path)?;
+        let index = build_lammps_dump_index(&source)?;
+        let file =
+            File::open(&source).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+        Ok(Self {
+            reader: Arc::new(Mutex::new(BufReader::new(file))),
+            index,
+            file_path: path.to_path_buf(),
+        })
+    }
+
+    pub fn index(&self) -> &LammpsDumpIndex {
+        &self.index
+    }
+
+    pub fn should_stream(num_atoms: usize, num_frames: usize) -> bool {
+        crate::io::streaming::should_stream_trajectory(num_atoms, num_frames)
+    }
+
+    pub fn load_all_frames(&self) -> IOResult<Vec<FrameData>> {
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("LAMMPS reader lock poisoned".to_string()))?;
+        let mut frames = Vec::with_capacity(self.index.num_frames);
+        for (i, &offset) in self.index.frame_offsets.iter().enumerate() {
+            frames.push(parse_frame_at_offset(&mut reader, offset, i, &self.index)?);
+        }
+        Ok(frames)
+    }
+}
+
+impl FrameProvider for LammpsDumpFrameProvider {
+    fn num_frames(&self) -> usize {
+        self.index.num_frames
+    }
+
+    fn num_atoms(&self) -> usize {
+        self.index.num_atoms
+    }
+
+    fn time_step(&self) -> f32 {
+        self.index.time_step
+    }
+
+    fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    fn metadata(&self) -> &TrajectoryMetadata {
+        &self.index.metadata
+    }
+
+    fn get_frame(&self, index: usize) -> IOResult<FrameData> {
+        let offset = *self
+            .index
+            .frame_offsets
+            .get(index)
+            .ok_or_else(|| IOError::ParseError {
+                line: 0,
+                message: format!(
+                    "Frame index {index} out of range ({} frames)",
+                    self.index.num_frames
+                ),
+            })?;
+
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("LAMMPS reader lock poisoned".to_string()))?;
+
+        parse_frame_at_offset(&mut reader, offset, index, &self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two orthogonal snapshots with atoms written out of id order.
+    const ORTHO_DUMP: &str = "\
+ITEM: TIMESTEP
+0
+ITEM: NUMBER OF ATOMS
+3
+ITEM: BOX BOUNDS pp pp pp
+-5.0 5.0
+-5.0 5.0
+0.0 20.0
+ITEM: ATOMS id type x y z vx vy vz c_pe q
+3 2 1.0 1.0 1.0 0.1 0.0 0.0 -1.5 0.5
+1 1 0.0 0.0 0.0 0.0 0.0 0.0 -3.0 -1.0
+2 1 -1.0 2.0 3.0 0.0 0.2 0.0 -2.5 0.5
+ITEM: TIMESTEP
+100
+ITEM: NUMBER OF ATOMS
+3
+ITEM: BOX BOUNDS pp pp pp
+-5.0 5.0
+-5.0 5.0
+0.0 20.0
+ITEM: ATOMS id type x y z vx vy vz c_pe q
+1 1 0.5 0.0 0.0 0.0 0.0 0.0 -3.1 -1.0
+2 1 -1.0 2.5 3.0 0.0 0.2 0.0 -2.4 0.5
+3 2 1.0 1.0 1.5 0.1 0.0 0.0 -1.6 0.5
+";
+
+    #[test]
+    fn test_dump_columns_ids_and_properties() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traj.lammpstrj");
+        std::fs::write(&path, ORTHO_DUMP).unwrap();
+
+        let provider = LammpsDumpFrameProvider::open(&path).unwrap();
+        assert_eq!(provider.num_frames(), 2);
+        assert_eq!(provider.num_atoms(), 3);
+        assert_eq!(provider.time_step(), 100.0);
+
+        let frame = provider.get_frame(0).unwrap();
+        // id 1 is atom 0; positions are shifted by the box origin
+        assert_eq!(frame.position(0), Some(Vec3::new(5.0, 5.0, 0.0)));
+        assert_eq!(frame.position(2), Some(Vec3::new(6.0, 6.0, 1.0)));
+        assert_eq!(
+            frame.velocities.as_ref().unwrap()[1],
+            Vec3::new(0.0, 0.2, 0.0)
+        );
+        assert_eq!(frame.properties["c_pe"][0], -3.0);
+        assert_eq!(frame.properties["q"][2], 0.5);
+        assert!(!frame.properties.contains_key("type"));
+        assert!((frame.cell.unwrap().lengths[2] - 20.0).abs() < 1e-6);
+
+        let last = provider.load_all_frames().unwrap().pop().unwrap();
+        assert_eq!(last.time, 100.0);
+        assert_eq!(last.position(2), Some(Vec3::new(6.0, 6.0, 1.5)));
+
+        // Numeric types only: placeholders that want a data file
+        let (atoms, complete) = LammpsDumpParser::parse_topology(&path).unwrap();
+        assert!(!complete);
+        assert_eq!(atoms.len(), 3);
+        assert_eq!(atoms[2].name, "2");
+        assert_eq!(atoms[0].charge, -1.0);
+    }
+
+    #[test]
+    fn test_triclinic_scaled_dump_with_elements() {
+        let dump = "\
+ITEM: TIMESTEP
+5
+ITEM: NUMBER OF ATOMS
+2
+ITEM: BOX BOUNDS xy xz yz pp pp pp
+-1.0 12.0 2.0
+0.0 10.0 -1.0
+0.0 10.0 0.0
+ITEM: ATOMS element xs ys zs
+C 0.5 0.5 0.5
+O 1.0 1.0 1.0
+";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traj.dump");
+        std::fs::write(&path, dump).unwrap();
+
+        let provider = LammpsDumpFrameProvider::open(&path).unwrap();
+        let frame = provider.get_frame(0).unwrap();
+        let cell = frame.cell.unwrap();
+        // xlo = -1 - min(0, 2, -1, 1) = 0, xhi = 12 - max(0, 2, -1, 1) = 10
+        assert_eq!(cell.vectors[0], Vec3::new(10.0, 0.0, 0.0));
+        assert_eq!(cell.vectors[1], Vec3::new(2.0, 10.0, 0.0));
+        assert_eq!(cell.vectors[2], Vec3::new(-1.0, 0.0, 10.0));
+        assert!(
+            frame
+                .position(1)
+                .unwrap()
+                .distance(Vec3::new(11.0, 10.0, 10.0))
+                < 1e-5
+        );
+        assert!(frame.properties.is_empty());
+
+        let (atoms, complete) = LammpsDumpParser::parse_topology(&path).unwrap();
+        assert!(complete);
+        assert_eq!(atoms[0].element, Element::C);
+        assert_eq!(atoms[1].element, Element::O);
+        assert!(LammpsDumpParser::is_lammps_dump_content(dump));
+    }
+
+    #[test]
+    fn test_dump_rejects_changing_atom_count_and_missing_coordinates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.lammpstrj");
+        let (first, second) = ORTHO_DUMP.split_at(ORTHO_DUMP.rfind("ITEM: TIMESTEP").unwrap());
+        let shrinking = format!("{first}{}", second.replace("ATOMS\n3", "ATOMS\n2"));
+        std::fs::write(&path, shrinking).unwrap();
+        assert!(build_lammps_dump_index(&path).is_err());
+
+        assert!(AtomColumns::parse("id type vx vy vz").is_err());
+    }
+}
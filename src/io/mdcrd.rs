@@ -0,0 +1,479 @@
+//! AMBER ASCII trajectory (`.mdcrd` / `.crd`) reader
+//!
+//! The file is a title line followed by frames of `10F8.3` coordinates in Å,
+//! each optionally followed by a box line (`3F8.3`, or six values with
+//! angles). Frames carry no atom count, so it comes from the paired topology;
+//! without one it is inferred from the first short line of the first frame.
+//! There is no time information either: frames are numbered with a 1 fs step.
+
+use crate::core::cell::UnitCell;
+use crate::core::trajectory::{FrameData, TrajectoryMetadata};
+use crate::io::registry::{
+    sniff_text, Confidence, FormatCapabilities, FormatDescriptor, LoadedFile,
+};
+use crate::io::streaming::FrameProvider;
+use crate::io::{FileFormat, IOError, IOResult};
+use bevy::prelude::*;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Width of one `F8.3` field.
+const FIELD_WIDTH: usize = 8;
+
+/// Coordinate values per full line.
+const VALUES_PER_LINE: usize = 10;
+
+/// Indexed metadata for an ASCII AMBER trajectory.
+#[derive(Debug, Clone)]
+pub struct MdcrdIndex {
+    pub num_atoms: usize,
+    pub num_frames: usize,
+    pub time_step: f32,
+    /// Whether every frame is followed by a box line.
+    pub has_box: bool,
+    pub frame_offsets: Vec<u64>,
+    pub metadata: TrajectoryMetadata,
+}
+
+impl MdcrdIndex {
+    fn lines_per_frame(&self) -> usize {
+        lines_per_frame(self.num_atoms)
+    }
+}
+
+fn lines_per_frame(num_atoms: usize) -> usize {
+    (num_atoms * 3).div_ceil(VALUES_PER_LINE)
+}
+
+/// Fixed-width fields of a line (values may touch, e.g. `-100.000-200.000`).
+fn fields(line: &str) -> impl Iterator<Item = &str> {
+    let line = line.trim_end();
+    (0..line.len().div_ceil(FIELD_WIDTH)).filter_map(move |i| {
+        let field = line
+            .get(i * FIELD_WIDTH..((i + 1) * FIELD_WIDTH).min(line.len()))?
+            .trim();
+        (!field.is_empty()).then_some(field)
+    })
+}
+
+fn read_line(reader: &mut BufReader<File>, line: &mut String) -> IOResult<usize> {
+    line.clear();
+    reader.read_line(line).map_err(IOError::Io)
+}
+
+/// Guess the atom count from the first frame: full lines up to the first
+/// short line. Exact when `3N` is not a multiple of 10; pair with a topology
+/// otherwise.
+///
+/// When every coordinate line is full, a 3-value line after them is either
+/// the last atom or a box line. That case is an error unless a second
+/// 3-value line (the box after a short coordinate line) settles it.
+pub fn infer_atom_count(path: &Path) -> IOResult<usize> {
+    let file = File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+    read_line(&mut reader, &mut line)?;
+
+    let mut values = 0;
+    let mut full_lines = 0;
+    let mut short_count = 0;
+    while read_line(&mut reader, &mut line)? > 0 {
+        let count = fields(&line).count();
+        values += count;
+        if count < VALUES_PER_LINE {
+            short_count = count;
+            break;
+        }
+        full_lines += 1;
+    }
+
+    // 3N = 10 * full_lines exactly when full_lines is a multiple of 3
+    if short_count == 3 && full_lines > 0 && full_lines % 3 == 0 {
+        let next_count = match read_line(&mut reader, &mut line)? {
+            0 => 0,
+            _ => fields(&line).count(),
+        };
+        if next_count != 3 {
+            return Err(IOError::InvalidFormat(format!(
+                "Cannot infer atom count from {}: the first frame is {} atoms with a box or {} atoms without one; load a topology",
+                path.display(),
+                values / 3 - 1,
+                values / 3
+            )));
+        }
+    }
+
+    if values == 0 || values % 3 != 0 {
+        return Err(IOError::InvalidFormat(format!(
+            "Cannot infer atom count from {} ({values} values in first frame); load a topology",
+            path.display()
+        )));
+    }
+    Ok(values / 3)
+}
+
+/// Scan an mdcrd file and record the byte offset of each frame.
+///
+/// A truncated trailing frame (e.g. from a running simulation) is dropped
+/// with a warning.
+pub fn build_mdcrd_index(path: &Path, num_atoms: usize) -> IOResult<MdcrdIndex> {
+    if num_atoms == 0 {
+        return Err(IOError::InvalidFormat(
+            "mdcrd atom count cannot be zero".to_string(),
+        ));
+    }
+    let file = File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+
+    read_line(&mut reader, &mut line)?;
+    let title = line.trim().to_string();
+
+    let frame_lines = lines_per_frame(num_atoms);
+    let expected_values = num_atoms * 3;
+    let mut frame_offsets = Vec::new();
+    let mut has_box = None;
+
+    'frames: loop {
+        let offset = reader.stream_position().map_err(IOError::Io)?;
+        let frame_index = frame_offsets.len();
+
+        let mut values = 0;
+        for line_i in 0..frame_lines {
+            if read_line(&mut reader, &mut line)? == 0 {
+                if line_i > 0 {
+                    warn!("mdcrd: dropping truncated frame {frame_index}");
+                }
+                break 'frames;
+            }
+            if line_i == 0 && line.trim().is_empty() {
+                break 'frames;
+            }
+            values += fields(&line).count();
+        }
+        if values != expected_values {
+            return Err(IOError::ParseError {
+                line: frame_index,
+                message: format!(
+                    "Frame {frame_index} has {values} coordinates, expected {expected_values} for {num_atoms} atoms"
+                ),
+            });
+        }
+
+        let has_box = *has_box.get_or_insert_with(|| {
+            // A box line has 3 or 6 values; the next frame starts with a full
+            // line unless the system is tiny
+            let Ok(position) = reader.stream_position() else {
+                return false;
+            };
+            let mut next = String::new();
+            let is_box = reader.read_line(&mut next).is_ok()
+                && matches!(fields(&next).count(), 3 | 6)
+                && expected_values > 6;
+            let _ = reader.seek(SeekFrom::Start(position));
+            is_box
+        });
+        if has_box {
+            read_line(&mut reader, &mut line)?;
+        }
+
+        frame_offsets.push(offset);
+    }
+
+    if frame_offsets.is_empty() {
+        return Err(IOError::ParseError {
+            line: 0,
+            message: "No frames found in mdcrd file".into(),
+        });
+    }
+
+    Ok(MdcrdIndex {
+        num_atoms,
+        num_frames: frame_offsets.len(),
+        time_step: 1.0,
+        has_box: has_box.unwrap_or(false),
+        frame_offsets,
+        metadata: TrajectoryMetadata {
+            title,
+            software: "AMBER".to_string(),
+            ..Default::default()
+        },
+    })
+}
+
+fn parse_value(field: &str, frame_index: usize) -> IOResult<f32> {
+    field.parse::<f32>().map_err(|_| IOError::ParseError {
+        line: frame_index,
+        message: format!("Invalid mdcrd value in frame {frame_index}: {field}"),
+    })
+}
+
+fn parse_frame_at_offset(
+    reader: &mut BufReader<File>,
+    offset: u64,
+    frame_index: usize,
+    index: &MdcrdIndex,
+) -> IOResult<FrameData> {
+    reader.seek(SeekFrom::Start(offset)).map_err(IOError::Io)?;
+
+    let mut values = Vec::with_capacity(index.num_atoms * 3);
+    let mut line = String::new();
+    for _ in 0..index.lines_per_frame() {
+        read_line(reader, &mut line)?;
+        for field in fields(&line) {
+            values.push(parse_value(field, frame_index)?);
+        }
+    }
+    if values.len() != index.num_atoms * 3 {
+        return Err(IOError::ParseError {
+            line: frame_index,
+            message: format!(
+                "Frame {frame_index} has {} coordinates, expected {}",
+                values.len(),
+                index.num_atoms * 3
+            ),
+        });
+    }
+
+    let positions = values
+        .chunks_exact(3)
+        .map(|c| Vec3::new(c[0], c[1], c[2]))
+        .collect();
+    let mut frame =
+        FrameData::with_positions(frame_index, frame_index as f32 * index.time_step, positions);
+
+    if index.has_box {
+        read_line(reader, &mut line)?;
+        let box_values = fields(&line)
+            .map(|field| parse_value(field, frame_index))
+            .collect::<IOResult<Vec<f32>>>()?;
+        frame.cell = match box_values.as_slice() {
+            [a, b, c] => Some(UnitCell::orthorhombic([*a, *b, *c])),
+            [a, b, c, alpha, beta, gamma] => Some(UnitCell::from_lengths_angles(
+                [*a, *b, *c],
+                [*alpha, *beta, *gamma],
+            )),
+            _ => None,
+        };
+    }
+
+    Ok(frame)
+}
+
+/// Registry entry: AMBER ASCII trajectories, paired with a topology.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::Mdcrd,
+    name: "AMBER mdcrd",
+    extensions: &["mdcrd", "crd"],
+    capabilities: FormatCapabilities {
+        topology: false,
+        trajectory: true,
+        streaming: true,
+        writable: false,
+    },
+    needs_topology: true,
+    sniff,
+    load: Some(load),
+    read_topology: None,
+};
+
+/// Lines after the title made of `F8.3` fields only. Other formats rarely
+/// look like this, but a title line proves nothing, so this is only
+/// [`Confidence::POSSIBLE`].
+fn sniff(data: &[u8]) -> Confidence {
+    let Some(text) = sniff_text(data) else {
+        return Confidence::NONE;
+    };
+    let is_coordinate_line = |line: &str| {
+        let line = line.trim_end();
+        !line.is_empty()
+            && line.len() <= FIELD_WIDTH * VALUES_PER_LINE
+            && line.len() % FIELD_WIDTH == 0
+            && line.as_bytes().chunks(FIELD_WIDTH).all(|field| {
+                std::str::from_utf8(field).is_ok_and(|f| f.trim().parse::<f32>().is_ok())
+            })
+    };
+    let mut lines = text.lines().skip(1).take(2).peekable();
+    if lines.peek().is_some() && lines.all(is_coordinate_line) {
+        Confidence::POSSIBLE
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    // Frames carry no atom count; the topology's is used when there is one
+    crate::io::topology::load_coordinates(topology_path, |num_atoms| {
+        crate::io::streaming::open_mdcrd(path, num_atoms)
+    })
+}
+
+/// Random-access mdcrd frame provider backed by a seekable file handle.
+pub struct MdcrdFrameProvider {
+    reader: Arc<Mutex<BufReader<File>>>,
+    index: MdcrdIndex,
+    file_path: PathBuf,
+}
+
+impl MdcrdFrameProvider {
+    /// Open an mdcrd file; `num_atoms` comes from the topology when known.
+    pub fn open(path: &Path, num_atoms: Option<usize>) -> IOResult<Self> {
+        let source = crate::io::compression::seekable_path(path)?;
+        let num_atoms = match num_atoms {
+            Some(n) => n,
+            None => infer_atom_count(&source)?,
+        };
+        let index = build_mdcrd_index(&source, num_atoms)?;
+        let file =
+            File::open(&source).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+        Ok(Self {
+            reader: Arc::new(Mutex::new(BufReader::new(file))),
+            index,
+            file_path: path.to_path_buf(),
+        })
+    }
+
+    pub fn index(&self) -> &MdcrdIndex {
+        &self.index
+    }
+
+    pub fn should_stream(num_atoms: usize, num_frames: usize) -> bool {
+        crate::io::streaming::should_stream_trajectory(num_atoms, num_frames)
+    }
+
+    pub fn load_all_frames(&self) -> IOResult<Vec<FrameData>> {
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("mdcrd reader lock poisoned".to_string()))?;
+        let mut frames = Vec::with_capacity(self.index.num_frames);
+        for (i, &offset) in self.index.frame_offsets.iter().enumerate() {
+            frames.push(parse_frame_at_offset(&mut reader, offset, i, &self.index)?);
+        }
+        Ok(frames)
+    }
+}
+
+impl FrameProvider for MdcrdFrameProvider {
+    fn num_frames(&self) -> usize {
+        self.index.num_frames
+    }
+
+    fn num_atoms(&self) -> usize {
+        self.index.num_atoms
+    }
+
+    fn time_step(&self) -> f32 {
+        self.index.time_step
+    }
+
+    fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    fn metadata(&self) -> &TrajectoryMetadata {
+        &self.index.metadata
+    }
+
+    fn get_frame(&self, index: usize) -> IOResult<FrameData> {
+        let offset = *self
+            .index
+            .frame_offsets
+            .get(index)
+            .ok_or_else(|| IOError::ParseError {
+                line: 0,
+                message: format!(
+                    "Frame index {index} out of range ({} frames)",
+                    self.index.num_frames
+                ),
+            })?;
+
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("mdcrd reader lock poisoned".to_string()))?;
+
+        parse_frame_at_offset(&mut reader, offset, index, &self.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Write `frames` frames of `num_atoms` atoms in cpptraj's layout.
+    fn write_mdcrd(path: &Path, num_atoms: usize, frames: usize, with_box: bool) {
+        let mut file = File::create(path).unwrap();
+        writeln!(file, "Cpptraj Generated trajectory").unwrap();
+        for f in 0..frames {
+            let values: Vec<f32> = (0..num_atoms * 3)
+                .map(|i| i as f32 * 1.5 - 100.0 * f as f32)
+                .collect();
+            for chunk in values.chunks(VALUES_PER_LINE) {
+                let line: String = chunk.iter().map(|v| format!("{v:8.3}")).collect();
+                writeln!(file, "{line}").unwrap();
+            }
+            if with_box {
+                writeln!(file, "{:8.3}{:8.3}{:8.3}", 30.0, 31.0, 32.0 + f as f32).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_mdcrd_with_box_and_touching_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traj.mdcrd");
+        write_mdcrd(&path, 5, 3, true);
+
+        let provider = MdcrdFrameProvider::open(&path, Some(5)).unwrap();
+        assert!(provider.index().has_box);
+        assert_eq!(provider.num_frames(), 3);
+
+        let frame = provider.get_frame(2).unwrap();
+        // -200.000 and -198.500 touch in the F8.3 layout
+        assert_eq!(frame.position(0), Some(Vec3::new(-200.0, -198.5, -197.0)));
+        assert_eq!(frame.positions.len(), 5);
+        assert!((frame.cell.unwrap().lengths[2] - 34.0).abs() < 1e-4);
+
+        assert!(MdcrdFrameProvider::open(&path, Some(4)).is_err());
+    }
+
+    #[test]
+    fn test_mdcrd_without_box_infers_atom_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traj.crd");
+        write_mdcrd(&path, 7, 4, false);
+
+        assert_eq!(infer_atom_count(&path).unwrap(), 7);
+        let provider = MdcrdFrameProvider::open(&path, None).unwrap();
+        assert!(!provider.index().has_box);
+        assert_eq!(provider.num_frames(), 4);
+        assert_eq!(provider.load_all_frames().unwrap().len(), 4);
+        assert!(provider.get_frame(3).unwrap().cell.is_none());
+    }
+
+    #[test]
+    fn test_box_line_after_full_lines_is_ambiguous() {
+        let dir = tempfile::tempdir().unwrap();
+        // 30 values fill three lines, so the box line looks like an 11th atom
+        let path = dir.path().join("ten.mdcrd");
+        write_mdcrd(&path, 10, 2, true);
+        let err = infer_atom_count(&path).unwrap_err().to_string();
+        assert!(err.contains("10 atoms with a box or 11"), "{err}");
+        assert!(MdcrdFrameProvider::open(&path, None).is_err());
+        assert_eq!(
+            MdcrdFrameProvider::open(&path, Some(10))
+                .unwrap()
+                .num_frames(),
+            2
+        );
+
+        // 11 atoms end on a 3-value line, and the box line after it settles it
+        let path = dir.path().join("eleven.mdcrd");
+        write_mdcrd(&path, 11, 2, true);
+        assert_eq!(infer_atom_count(&path).unwrap(), 11);
+    }
+}
@@ -3,402 +3,512 @@
 //! The mmCIF (macromolecular Crystallographic Information File) format is an
 //! alternative to PDB format that can store larger structures with more metadata.
 //! It uses a hierarchical key-value structure rather than fixed-width columns.
+//!
+//! Besides `_atom_site`, the parser reads `_struct_conn` (disulfides, metal
+//! coordination, covalent links) into bonds and `_struct_conf` /
+//! `_struct_sheet_range` into per-residue secondary structure, and
+//! `_pdbx_struct_assembly_gen` / `_pdbx_struct_oper_list` plus the space
+//! group operators into [`StructureSymmetry`].
 
 use crate::core::atom::{AtomData, Element};
+use crate::core::bond::{BondData, BondOrder, BondType};
+use crate::core::cell::UnitCell;
+use crate::core::molecule::SecondaryStructure;
+use crate::core::secondary_structure::{helix_class_structure, SecondaryStructureAnnotations};
+use crate::core::symmetry::{
+    expand_oper_expression, parse_xyz_operator, AssemblyGenerator, BiologicalAssembly,
+    StructureSymmetry, SymmetryOperator,
+};
 use crate::core::trajectory::{FrameData, Trajectory, TrajectoryMetadata};
-use crate::io::{IOError, IOResult};
+use crate::io::cif::{self, CifBlock, CifRow};
+use crate::io::registry::{
+    sniff_text, Confidence, FormatCapabilities, FormatDescriptor, LoadedFile,
+};
+use crate::io::{FileFormat, IOError, IOResult};
 use bevy::prelude::*;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Write};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
+/// Registry entry: PDBx/mmCIF; extra models become frames.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::MmCIF,
+    name: "mmCIF",
+    extensions: &["cif", "mmcif", "mcif"],
+    capabilities: FormatCapabilities {
+        topology: true,
+        trajectory: true,
+        streaming: false,
+        writable: true,
+    },
+    needs_topology: false,
+    sniff,
+    load: Some(load),
+    read_topology: Some(MmcifParser::parse_topology),
+};
+
+/// The first statement after comments (including the `#\#CIF_2.0` magic
+/// comment) opens a `data_` block.
+fn sniff(data: &[u8]) -> Confidence {
+    let first_statement = sniff_text(data).and_then(|text| {
+        text.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+    });
+    if first_statement.is_some_and(|line| line.starts_with("data_")) {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, _topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    let (trajectory, atom_data, bond_data) = MmcifParser::parse_file_with_atoms(path)?;
+    Ok((trajectory, atom_data, bond_data, None, false))
+}
+
 /// mmCIF format parser
 pub struct MmcifParser;
 
 impl MmcifParser {
     /// Parse an mmCIF file and return trajectory data
     pub fn parse_file(path: &Path) -> IOResult<Trajectory> {
-        let file =
-            File::open(path).map_err(|_e| IOError::FileNotFound(path.display().to_string()))?;
-        let reader = BufReader::new(file);
-        Self::parse_reader(reader, path.to_path_buf())
+        Ok(Self::parse_file_with_atoms(path)?.0)
     }
 
-    /// Parse mmCIF format from a reader
-    pub fn parse_reader<R: Read>(reader: R, file_path: PathBuf) -> IOResult<Trajectory> {
-        let reader = BufReader::new(reader);
-        let lines: Vec<String> = reader.lines().collect::<Result<_, _>>()?;
+    /// Parse an mmCIF file returning trajectory, atom metadata and `_struct_conn` bonds
+    pub fn parse_file_with_atoms(
+        path: &Path,
+    ) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        let content = crate::io::compression::read_to_string(path)?;
+        Self::parse_structure(&content, path.to_path_buf())
+    }
 
-        Self::parse_lines(&lines, file_path)
+    /// Parse mmCIF format from a reader
+    pub fn parse_reader<R: Read>(mut reader: R, file_path: PathBuf) -> IOResult<Trajectory> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        Self::parse_string(&content, file_path)
     }
 
     /// Parse mmCIF format from string content
     pub fn parse_string(content: &str, file_path: PathBuf) -> IOResult<Trajectory> {
-        let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-        Self::parse_lines(&lines, file_path)
+        Ok(Self::parse_structure(content, file_path)?.0)
     }
 
-    /// Parse mmCIF format from a vector of lines
-    fn parse_lines(lines: &[String], file_path: PathBuf) -> IOResult<Trajectory> {
-        if lines.is_empty() {
-            return Err(IOError::ParseError {
-                line: 0,
-                message: "Empty mmCIF file".to_string(),
-            });
-        }
-
-        // Parse data into categories and columns
-        let mut data = MmcifData::default();
+    /// Parse atom metadata from an mmCIF file and return AtomData for each atom.
+    /// Used by the loading system to populate atom_data when loading mmCIF files.
+    pub fn parse_atom_data_from_file(path: &Path) -> IOResult<Vec<AtomData>> {
+        Ok(Self::parse_file_with_atoms(path)?.1)
+    }
 
-        let line_iter = lines.iter();
-        let mut current_category = String::new();
-        let mut current_columns: Vec<String> = Vec::new();
-        let mut in_loop = false;
+    /// Parse atoms and `_struct_conn` bonds for use as a topology.
+    pub fn parse_topology(path: &Path) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let (_, atom_data, bond_data) = Self::parse_file_with_atoms(path)?;
+        Ok((atom_data, bond_data))
+    }
 
-        for line in line_iter {
-            let line = line.trim();
+    /// Parse mmCIF content into a trajectory, atom metadata and bonds.
+    pub fn parse_structure(
+        content: &str,
+        file_path: PathBuf,
+    ) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        let block = Self::parse_block(content)?;
+        Self::structure_from_block(&block, file_path)
+    }
 
-            // Skip comments and empty lines
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
+    /// Tokenize mmCIF content and return its first data block.
+    pub fn parse_block(content: &str) -> IOResult<CifBlock> {
+        cif::parse_cif(content)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| IOError::ParseError {
+                line: 0,
+                message: "Empty mmCIF file".to_string(),
+            })
+    }
 
-            // Data block start
-            if line.starts_with("data_") {
-                if let Some(id) = line.strip_prefix("data_") {
-                    data.id = id.to_string();
-                }
-                continue;
+    /// Build trajectory, atoms and bonds from a parsed data block.
+    ///
+    /// Atoms come from the first model in `_atom_site`; further models
+    /// (`pdbx_PDB_model_num`, e.g. NMR ensembles) become extra frames.
+    pub fn structure_from_block(
+        block: &CifBlock,
+        file_path: PathBuf,
+    ) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        let mut metadata = Self::metadata_from_block(block);
+        let cell = Self::parse_cell(block);
+
+        let Some(atom_site) = block.category("atom_site") else {
+            // Return empty trajectory if no atoms
+            let mut trajectory = Trajectory::new(file_path, 0, 1.0);
+            trajectory.metadata = metadata;
+            return Ok((trajectory, Vec::new(), Vec::new()));
+        };
+
+        let mut atom_data = Vec::new();
+        let mut index = AtomSiteIndex::default();
+        let mut frames: Vec<FrameData> = Vec::new();
+        let mut current_model: Option<&str> = None;
+        let mut atom_index = 0u32;
+
+        for row in atom_site.rows() {
+            let model = row.get("pdbx_PDB_model_num");
+            if frames.is_empty() || model != current_model {
+                let index = frames.len();
+                let mut frame = FrameData::new(index, index as f32);
+                frame.cell = cell;
+                frames.push(frame);
+                current_model = model;
+                atom_index = 0;
             }
 
-            // Loop start (multi-record data)
-            if line.starts_with("loop_") {
-                in_loop = true;
-                current_category.clear();
-                current_columns.clear();
-                continue;
+            let atom = Self::parse_atom_data(&row, atom_index);
+            let frame = frames.last_mut().expect("frame pushed above");
+            frame.positions.push(atom.position);
+            if frames.len() == 1 {
+                index.insert(&row, &atom);
+                atom_data.push(atom);
             }
+            atom_index += 1;
+        }
 
-            // Category/column definition
-            if in_loop {
-                if line.starts_with('_') {
-                    // This is a column definition
-                    // Format: _category.column
-                    if let Some(dot_pos) = line.find('.') {
-                        let category = &line[1..dot_pos];
-                        let column = &line[dot_pos + 1..];
-
-                        if current_category.is_empty() {
-                            current_category = category.to_string();
-                        } else if current_category != category {
-                            // New category, start a new block
-                            if !current_columns.is_empty() {
-                                data.categories
-                                    .insert(current_category.clone(), current_columns.clone());
-                            }
-                            current_category = category.to_string();
-                            current_columns.clear();
-                        }
-
-                        current_columns.push(column.to_string());
-                    }
-                    continue;
-                }
-
-                // Check if this is a data line (not another _ or loop_)
-                if !line.starts_with('_')
-                    && !line.starts_with("loop_")
-                    && !line.starts_with("data_")
-                {
-                    // This is data for the current loop
-                    let values: Vec<String> =
-                        line.split_whitespace().map(|s| s.to_string()).collect();
-
-                    if current_category != "atom_site" {
-                        // We only care about atom_site for now
-                        continue;
-                    }
+        if let Some(frame) = frames.iter().find(|f| f.positions.len() != atom_data.len()) {
+            return Err(IOError::InvalidFormat(format!(
+                "mmCIF model {} has {} atoms, expected {}",
+                frame.index + 1,
+                frame.positions.len(),
+                atom_data.len()
+            )));
+        }
 
-                    // Store in atom_site data
-                    let mut record: HashMap<String, String> = HashMap::new();
-                    for (i, value) in values.iter().enumerate() {
-                        if i < current_columns.len() {
-                            record.insert(current_columns[i].clone(), value.clone());
-                        }
-                    }
+        let bond_data = Self::parse_struct_conn(block, &index, &atom_data);
+        metadata.secondary_structure = Self::parse_secondary_structure(block, &index);
+        metadata.symmetry = Self::parse_symmetry(block, &index, cell.as_ref());
+        metadata.symmetry.space_group = metadata.extra.get("space_group").cloned();
 
-                    data.atom_site.push(record);
-                    continue;
-                }
+        let mut trajectory = Trajectory::new(file_path, atom_data.len(), 1.0);
+        trajectory.metadata = metadata;
+        for frame in frames {
+            trajectory.add_frame(frame);
+        }
+        Ok((trajectory, atom_data, bond_data))
+    }
 
-                // End of loop
-                in_loop = false;
-                if !current_columns.is_empty() && !current_category.is_empty() {
-                    data.categories
-                        .insert(current_category.clone(), current_columns.clone());
-                }
+    /// Read `_struct_conn` into bonds between loaded atoms.
+    ///
+    /// Links to symmetry mates (`ptnr2_symmetry` other than `1_555`) and to
+    /// atoms missing from `_atom_site` are skipped.
+    fn parse_struct_conn(
+        block: &CifBlock,
+        index: &AtomSiteIndex,
+        atom_data: &[AtomData],
+    ) -> Vec<BondData> {
+        let Some(struct_conn) = block.category("struct_conn") else {
+            return Vec::new();
+        };
+
+        let mut bonds = Vec::new();
+        for row in struct_conn.rows() {
+            let symmetry = [row.get("ptnr1_symmetry"), row.get("ptnr2_symmetry")];
+            if symmetry.iter().flatten().any(|s| *s != "1_555") {
                 continue;
             }
-
-            // Single-value record (key-value pairs outside loops)
-            if line.starts_with('_') {
-                // Format: _category.column  value
-                let parts: Vec<&str> = line.splitn(2, char::is_whitespace).collect();
-                if parts.len() >= 2 {
-                    let key = parts[0][1..].to_string(); // Remove leading _
-                    let value = parts[1].to_string();
-                    data.metadata.insert(key, value);
-                }
+            let Some(bond_type) = row.get("conn_type_id").map(bond_type_from_conn_type) else {
+                continue;
+            };
+            let (Some(a), Some(b)) = (index.partner(&row, "1"), index.partner(&row, "2")) else {
+                continue;
+            };
+            if a == b {
                 continue;
             }
-        }
-
-        // End of file - save any remaining loop data
-        if !current_columns.is_empty() && !current_category.is_empty() {
-            data.categories
-                .insert(current_category.clone(), current_columns);
-        }
 
-        // Extract metadata
-        let mut metadata = TrajectoryMetadata::default();
-        if let Some(title) = data.metadata.get("struct.title") {
-            metadata.title = title.clone();
-        }
-        if let Some(classification) = data.metadata.get("struct_keywords.pdbx_keywords") {
-            metadata.classification = classification.clone();
+            let order = match row.get("pdbx_value_order") {
+                Some(o) if o.eq_ignore_ascii_case("doub") => BondOrder::Double,
+                Some(o) if o.eq_ignore_ascii_case("trip") => BondOrder::Triple,
+                _ => BondOrder::Single,
+            };
+            let length = row
+                .get("pdbx_dist_value")
+                .and_then(parse_number)
+                .unwrap_or_else(|| {
+                    atom_data[a as usize]
+                        .position
+                        .distance(atom_data[b as usize].position)
+                });
+            bonds.push(BondData::new(a, b, bond_type, order, length));
         }
+        bonds
+    }
 
-        // Check if we have atom data
-        if data.atom_site.is_empty() {
-            // Return empty trajectory if no atoms
-            return Ok(Trajectory::new(file_path, 0, 1.0));
-        }
+    /// Read helices (`_struct_conf`) and strands (`_struct_sheet_range`).
+    fn parse_secondary_structure(
+        block: &CifBlock,
+        index: &AtomSiteIndex,
+    ) -> SecondaryStructureAnnotations {
+        let mut annotations = SecondaryStructureAnnotations::new();
 
-        // Count atoms and create frame
-        let num_atoms = data.atom_site.len();
-        let mut frame = FrameData::new(0, 0.0);
-
-        // Parse atoms
-        for (atom_id, record) in data.atom_site.iter().enumerate() {
-            // Get position
-            let x = record
-                .get("Cartn_x")
-                .and_then(|s| s.parse::<f32>().ok())
-                .unwrap_or(0.0);
-            let y = record
-                .get("Cartn_y")
-                .and_then(|s| s.parse::<f32>().ok())
-                .unwrap_or(0.0);
-            let z = record
-                .get("Cartn_z")
-                .and_then(|s| s.parse::<f32>().ok())
-                .unwrap_or(0.0);
-
-            let position = Vec3::new(x, y, z);
-            frame.set_position(atom_id as u32, position);
-
-            // Note: Atom data would be stored separately in a full implementation
-            // For now, we're just storing positions in the trajectory
+        if let Some(struct_conf) = block.category("struct_conf") {
+            for row in struct_conf.rows() {
+                let Some(conf_type) = row.get("conf_type_id") else {
+                    continue;
+                };
+                let conf_type = conf_type.to_ascii_uppercase();
+                let structure = if conf_type.starts_with("HELX") {
+                    if conf_type.contains("3T") {
+                        SecondaryStructure::ThreeTenHelix
+                    } else if conf_type.contains("PI") {
+                        SecondaryStructure::PiHelix
+                    } else {
+                        row.get("pdbx_PDB_helix_class")
+                            .and_then(|c| c.parse::<u32>().ok())
+                            .map_or(SecondaryStructure::AlphaHelix, helix_class_structure)
+                    }
+                } else if conf_type.starts_with("STRN") {
+                    SecondaryStructure::BetaStrand
+                } else if conf_type.starts_with("TURN") {
+                    SecondaryStructure::Turn
+                } else {
+                    continue;
+                };
+                index.annotate_range(&row, structure, &mut annotations);
+            }
         }
 
-        // Create trajectory
-        let mut trajectory = Trajectory::new(file_path, num_atoms, 1.0);
-        trajectory.metadata = metadata;
-        trajectory.add_frame(frame);
-
-        Ok(trajectory)
-    }
-
-    /// Parse atom metadata from an mmCIF file and return AtomData for each atom.
-    /// Used by the loading system to populate atom_data when loading mmCIF files.
-    pub fn parse_atom_data_from_file(path: &Path) -> IOResult<Vec<AtomData>> {
-        let file =
-            File::open(path).map_err(|_e| IOError::FileNotFound(path.display().to_string()))?;
-        let reader = BufReader::new(file);
-        let lines: Vec<String> = reader.lines().collect::<Result<_, _>>()?;
-
-        let data = Self::parse_mmcif_data(&lines)?;
-
-        let mut atom_data = Vec::with_capacity(data.atom_site.len());
-        for (atom_id, record) in data.atom_site.iter().enumerate() {
-            if let Some(ad) = Self::parse_atom_data(record, atom_id as u32) {
-                atom_data.push(ad);
-            } else {
-                // Fallback for records missing required fields
-                let atom_name = record
-                    .get("label_atom_id")
-                    .or_else(|| record.get("id"))
-                    .cloned()
-                    .unwrap_or_else(|| format!("ATOM{}", atom_id));
-                let element = Self::element_from_atom_name(&atom_name);
-                let residue_name = record
-                    .get("label_comp_id")
-                    .cloned()
-                    .unwrap_or_else(|| "UNK".to_string());
-                let residue_id = record
-                    .get("label_seq_id")
-                    .or_else(|| record.get("auth_seq_id"))
-                    .and_then(|s| s.parse::<i32>().ok())
-                    .unwrap_or(0);
-                let chain_id = record
-                    .get("auth_asym_id")
-                    .or_else(|| record.get("label_asym_id"))
-                    .cloned()
-                    .unwrap_or_else(|| "A".to_string());
-
-                atom_data.push(AtomData::new(
-                    atom_id as u32,
-                    element,
-                    residue_id as u32,
-                    residue_name,
-                    chain_id,
-                    atom_name,
-                ));
+        if let Some(sheet_range) = block.category("struct_sheet_range") {
+            for row in sheet_range.rows() {
+                index.annotate_range(&row, SecondaryStructure::BetaStrand, &mut annotations);
             }
         }
 
-        Ok(atom_data)
+        annotations
     }
 
-    /// Parse mmCIF data structure from lines (extracts atom_site and metadata)
-    fn parse_mmcif_data(lines: &[String]) -> IOResult<MmcifData> {
-        let mut data = MmcifData::default();
-        let line_iter = lines.iter();
-        let mut current_category = String::new();
-        let mut current_columns: Vec<String> = Vec::new();
-        let mut in_loop = false;
-
-        for line in line_iter {
-            let line = line.trim();
-
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-            if line.starts_with("data_") {
-                if let Some(id) = line.strip_prefix("data_") {
-                    data.id = id.to_string();
-                }
-                continue;
-            }
-            if line.starts_with("loop_") {
-                in_loop = true;
-                current_category.clear();
-                current_columns.clear();
-                continue;
-            }
-
-            if in_loop {
-                if line.starts_with('_') {
-                    if let Some(dot_pos) = line.find('.') {
-                        let category = &line[1..dot_pos];
-                        let column = &line[dot_pos + 1..];
-
-                        if current_category.is_empty() {
-                            current_category = category.to_string();
-                        } else if current_category != category {
-                            if !current_columns.is_empty() {
-                                data.categories
-                                    .insert(current_category.clone(), current_columns.clone());
-                            }
-                            current_category = category.to_string();
-                            current_columns.clear();
+    /// Read biological assemblies and the crystallographic operators.
+    fn parse_symmetry(
+        block: &CifBlock,
+        index: &AtomSiteIndex,
+        cell: Option<&UnitCell>,
+    ) -> StructureSymmetry {
+        let mut symmetry = StructureSymmetry::default();
+
+        let mut operators: HashMap<&str, SymmetryOperator> = HashMap::new();
+        if let Some(oper_list) = block.category("pdbx_struct_oper_list") {
+            for row in oper_list.rows() {
+                let Some(id) = row.get("id") else {
+                    continue;
+                };
+                let mut rows = [[0.0; 4]; 3];
+                let mut complete = true;
+                for (i, values) in rows.iter_mut().enumerate() {
+                    for (j, value) in values.iter_mut().enumerate() {
+                        let column = if j < 3 {
+                            format!("matrix[{}][{}]", i + 1, j + 1)
+                        } else {
+                            format!("vector[{}]", i + 1)
+                        };
+                        match row.get(&column).and_then(parse_number) {
+                            Some(v) => *value = v,
+                            None => complete = false,
                         }
-                        current_columns.push(column.to_string());
                     }
-                    continue;
                 }
+                if complete {
+                    operators.insert(id, SymmetryOperator::from_rows(rows));
+                }
+            }
+        }
 
-                if !line.starts_with('_')
-                    && !line.starts_with("loop_")
-                    && !line.starts_with("data_")
-                {
-                    if current_category == "atom_site" {
-                        let values: Vec<String> =
-                            line.split_whitespace().map(|s| s.to_string()).collect();
-
-                        let mut record: HashMap<String, String> = HashMap::new();
-                        for (i, value) in values.iter().enumerate() {
-                            if i < current_columns.len() {
-                                record.insert(current_columns[i].clone(), value.clone());
-                            }
-                        }
-                        data.atom_site.push(record);
+        if let Some(assembly_gen) = block.category("pdbx_struct_assembly_gen") {
+            for row in assembly_gen.rows() {
+                let (Some(id), Some(expression)) =
+                    (row.get("assembly_id"), row.get("oper_expression"))
+                else {
+                    continue;
+                };
+                // One copy per operator sequence, rightmost applied first
+                let Some(generator_operators) = expand_oper_expression(expression)
+                    .iter()
+                    .map(|sequence| {
+                        sequence
+                            .iter()
+                            .try_fold(SymmetryOperator::IDENTITY, |acc, id| {
+                                Some(acc.compose(operators.get(id.as_str())?))
+                            })
+                    })
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    continue;
+                };
+
+                let mut chains: Vec<String> = Vec::new();
+                for asym in row.get("asym_id_list").unwrap_or("").split(',') {
+                    let asym = asym.trim();
+                    // Files without label_asym_id use the chain ids directly
+                    let chain = index.asym_chains.get(asym).map_or(asym, String::as_str);
+                    if !chain.is_empty() && !chains.iter().any(|c| c == chain) {
+                        chains.push(chain.to_string());
                     }
+                }
+                if chains.is_empty() || generator_operators.is_empty() {
                     continue;
                 }
 
-                in_loop = false;
-                if !current_columns.is_empty() && !current_category.is_empty() {
-                    data.categories
-                        .insert(current_category.clone(), current_columns.clone());
+                if !symmetry.assemblies.iter().any(|a| a.id == id) {
+                    let details = block.category("pdbx_struct_assembly").and_then(|c| {
+                        c.rows()
+                            .find(|r| r.get("id") == Some(id))
+                            .and_then(|r| r.get_any(&["oligomeric_details", "details"]))
+                            .map(str::to_string)
+                    });
+                    symmetry.assemblies.push(BiologicalAssembly {
+                        id: id.to_string(),
+                        details: details.unwrap_or_default(),
+                        generators: Vec::new(),
+                    });
                 }
-                continue;
+                let assembly = symmetry
+                    .assemblies
+                    .iter_mut()
+                    .find(|a| a.id == id)
+                    .expect("assembly pushed above");
+                assembly.generators.push(AssemblyGenerator {
+                    chains,
+                    operators: generator_operators,
+                });
             }
+        }
 
-            if line.starts_with('_') {
-                let parts: Vec<&str> = line.splitn(2, char::is_whitespace).collect();
-                if parts.len() >= 2 {
-                    let key = parts[0][1..].to_string();
-                    let value = parts[1].to_string();
-                    data.metadata.insert(key, value);
+        if let Some(cell) = cell.filter(|c| c.is_periodic()) {
+            let symops = [
+                ("space_group_symop", "operation_xyz"),
+                ("symmetry_equiv", "pos_as_xyz"),
+            ];
+            for (category, column) in symops {
+                let Some(category) = block.category(category) else {
+                    continue;
+                };
+                symmetry.operators = category
+                    .rows()
+                    .filter_map(|row| parse_xyz_operator(row.get(column)?))
+                    .map(|(rotation, translation)| {
+                        SymmetryOperator::from_fractional(rotation, translation, cell)
+                    })
+                    .collect();
+                if !symmetry.operators.is_empty() {
+                    break;
                 }
-                continue;
             }
         }
 
-        if !current_columns.is_empty() && !current_category.is_empty() {
-            data.categories
-                .insert(current_category.clone(), current_columns);
+        symmetry
+    }
+
+    /// Collect title, classification and entry-level items.
+    fn metadata_from_block(block: &CifBlock) -> TrajectoryMetadata {
+        let mut metadata = TrajectoryMetadata::default();
+        if let Some(title) = block.value("struct", "title") {
+            metadata.title = title.to_string();
+        }
+        if let Some(classification) = block.value("struct_keywords", "pdbx_keywords") {
+            metadata.classification = classification.to_string();
         }
+        metadata.creation_date = block
+            .value("pdbx_database_status", "recvd_initial_deposition_date")
+            .map(str::to_string);
+
+        let extra = [
+            ("entry_id", "entry", "id"),
+            ("keywords", "struct_keywords", "text"),
+            ("space_group", "symmetry", "space_group_name_H-M"),
+            ("space_group", "space_group", "name_H-M_alt"),
+            ("z", "cell", "Z_PDB"),
+            ("method", "exptl", "method"),
+            ("resolution", "refine", "ls_d_res_high"),
+            ("resolution", "reflns", "d_resolution_high"),
+        ];
+        for (key, category, column) in extra {
+            if metadata.extra.contains_key(key) {
+                continue;
+            }
+            if let Some(value) = block.value(category, column) {
+                metadata.extra.insert(key.to_string(), value.to_string());
+            }
+        }
+        metadata
+    }
 
-        Ok(data)
+    /// Build the unit cell from `_cell.length_*` / `_cell.angle_*` items.
+    fn parse_cell(block: &CifBlock) -> Option<UnitCell> {
+        let cell = block.category("cell")?;
+        let value = |column: &str| parse_number(cell.first(column)?);
+
+        let lengths = [value("length_a")?, value("length_b")?, value("length_c")?];
+        let angles = [
+            value("angle_alpha").unwrap_or(90.0),
+            value("angle_beta").unwrap_or(90.0),
+            value("angle_gamma").unwrap_or(90.0),
+        ];
+        Some(UnitCell::from_lengths_angles(lengths, angles))
     }
 
-    /// Parse atom data from mmCIF record
-    fn parse_atom_data(record: &HashMap<String, String>, atom_id: u32) -> Option<AtomData> {
-        // Get atom name (try label_atom_id, then id, then type_symbol)
-        let atom_name = record
-            .get("label_atom_id")
-            .or_else(|| record.get("id"))
-            .or_else(|| record.get("type_symbol"))
-            .cloned()?;
-
-        // Get residue name
-        let residue_name = record
-            .get("label_comp_id")
-            .or_else(|| record.get("auth_comp_id"))
-            .cloned()
-            .unwrap_or_else(|| "UNK".to_string());
-
-        // Get residue number
-        let residue_id = record
-            .get("label_seq_id")
-            .or_else(|| record.get("auth_seq_id"))
-            .and_then(|s| s.parse::<i32>().ok())
+    /// Parse atom data from an `_atom_site` row
+    fn parse_atom_data(row: &CifRow, atom_id: u32) -> AtomData {
+        // Get atom name (try label_atom_id, then auth_atom_id, then type_symbol)
+        let atom_name = row
+            .get_any(&["label_atom_id", "auth_atom_id", "type_symbol"])
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("ATOM{}", atom_id));
+
+        let residue_name = row
+            .get_any(&["label_comp_id", "auth_comp_id"])
+            .unwrap_or("UNK")
+            .to_string();
+
+        // label_seq_id is `.` for ligands and waters, so fall back to auth_seq_id
+        let residue_id = ["label_seq_id", "auth_seq_id"]
+            .iter()
+            .find_map(|c| row.get(c)?.parse::<i32>().ok())
             .unwrap_or(0);
 
-        // Get chain ID
-        let chain_id = record
-            .get("auth_asym_id")
-            .or_else(|| record.get("label_asym_id"))
-            .cloned()
-            .unwrap_or_else(|| "A".to_string());
+        let chain_id = row
+            .get_any(&["auth_asym_id", "label_asym_id"])
+            .unwrap_or("A")
+            .to_string();
 
         // Determine element: prefer type_symbol if present, else from atom name
-        let element = record
+        let element = row
             .get("type_symbol")
             .and_then(|s| Element::from_symbol(s.trim()).ok())
             .unwrap_or_else(|| Self::element_from_atom_name(&atom_name));
 
-        Some(AtomData::new(
+        let coordinate = |column: &str| row.get(column).and_then(parse_number).unwrap_or(0.0);
+
+        let mut atom = AtomData::new(
             atom_id,
             element,
             residue_id as u32,
             residue_name,
             chain_id,
             atom_name,
-        ))
+        );
+        atom.position = Vec3::new(
+            coordinate("Cartn_x"),
+            coordinate("Cartn_y"),
+            coordinate("Cartn_z"),
+        );
+        if let Some(occupancy) = row.get("occupancy").and_then(parse_number) {
+            atom.occupancy = occupancy;
+        }
+        if let Some(b_factor) = row.get("B_iso_or_equiv").and_then(parse_number) {
+            atom.b_factor = b_factor;
+        }
+        if let Some(charge) = row.get("pdbx_formal_charge").and_then(parse_number) {
+            atom.charge = charge;
+        }
+        atom
     }
 
     /// Determine element from atom name (similar to PDB)
@@ -407,13 +517,135 @@ impl MmcifParser {
     }
 }
 
-/// mmCIF data structure
-#[derive(Debug, Default)]
-struct MmcifData {
-    id: String,
-    metadata: HashMap<String, String>,
-    categories: HashMap<String, Vec<String>>,
-    atom_site: Vec<HashMap<String, String>>,
+/// Map `_struct_conn.conn_type_id` to a bond type.
+fn bond_type_from_conn_type(conn_type: &str) -> BondType {
+    let conn_type = conn_type.to_ascii_lowercase();
+    match conn_type.as_str() {
+        "disulf" => BondType::Disulfide,
+        "metalc" => BondType::MetalCoord,
+        "hydrog" | "mismat" => BondType::Hydrogen,
+        "saltbr" => BondType::Ionic,
+        "modres" => BondType::Covalent,
+        _ if conn_type.starts_with("covale") => BondType::Covalent,
+        _ => BondType::Unknown,
+    }
+}
+
+/// Lookups from mmCIF atom and residue identifiers to loaded atoms.
+///
+/// Both the `label_*` and the author (`auth_*`) numbering schemes are kept,
+/// since other categories may reference either one. Atom keys use the
+/// author sequence number because `label_seq_id` is `.` for every water and
+/// ligand.
+#[derive(Default)]
+struct AtomSiteIndex {
+    label_atoms: HashMap<(String, String, String), u32>,
+    auth_atoms: HashMap<(String, String, String), u32>,
+    label_residues: HashMap<(String, i32), (String, u32)>,
+    auth_residues: HashMap<(String, i32), (String, u32)>,
+    /// `label_asym_id` → author chain id, for assembly chain lists
+    asym_chains: HashMap<String, String>,
+}
+
+impl AtomSiteIndex {
+    fn insert(&mut self, row: &CifRow, atom: &AtomData) {
+        let seq = row.get_any(&["auth_seq_id", "label_seq_id"]);
+        let residue = (atom.chain_id.clone(), atom.residue_id);
+        if let Some(asym) = row.get("label_asym_id") {
+            self.asym_chains
+                .entry(asym.to_string())
+                .or_insert_with(|| atom.chain_id.clone());
+        }
+
+        if let (Some(asym), Some(seq)) = (row.get("label_asym_id"), seq) {
+            let name = row
+                .get_any(&["label_atom_id", "auth_atom_id"])
+                .unwrap_or("");
+            // First alternate location wins
+            self.label_atoms
+                .entry((asym.to_string(), seq.to_string(), name.to_string()))
+                .or_insert(atom.id);
+        }
+        if let (Some(asym), Some(seq)) = (row.get("auth_asym_id"), seq) {
+            let name = row
+                .get_any(&["auth_atom_id", "label_atom_id"])
+                .unwrap_or("");
+            self.auth_atoms
+                .entry((asym.to_string(), seq.to_string(), name.to_string()))
+                .or_insert(atom.id);
+        }
+
+        if let (Some(asym), Some(seq)) = (
+            row.get("label_asym_id"),
+            row.get("label_seq_id").and_then(|s| s.parse().ok()),
+        ) {
+            self.label_residues
+                .entry((asym.to_string(), seq))
+                .or_insert_with(|| residue.clone());
+        }
+        if let (Some(asym), Some(seq)) = (
+            row.get("auth_asym_id"),
+            row.get("auth_seq_id").and_then(|s| s.parse().ok()),
+        ) {
+            self.auth_residues
+                .entry((asym.to_string(), seq))
+                .or_insert(residue);
+        }
+    }
+
+    /// Atom id of `_struct_conn` partner `n` ("1" or "2").
+    fn partner(&self, row: &CifRow, n: &str) -> Option<u32> {
+        let column = |name: &str| row.get(&format!("ptnr{n}_{name}"));
+        let seq = column("auth_seq_id").or_else(|| column("label_seq_id"))?;
+
+        let label = column("label_asym_id").zip(column("label_atom_id"));
+        let from_label = label.and_then(|(asym, name)| {
+            self.label_atoms
+                .get(&(asym.to_string(), seq.to_string(), name.to_string()))
+        });
+        let from_auth = || {
+            let asym = column("auth_asym_id")?;
+            let name = column("auth_atom_id").or_else(|| column("label_atom_id"))?;
+            self.auth_atoms
+                .get(&(asym.to_string(), seq.to_string(), name.to_string()))
+        };
+        from_label.or_else(from_auth).copied()
+    }
+
+    /// Annotate the residues between a row's `beg_*` and `end_*` residues.
+    fn annotate_range(
+        &self,
+        row: &CifRow,
+        structure: SecondaryStructure,
+        annotations: &mut SecondaryStructureAnnotations,
+    ) {
+        let bounds = |scheme: &str| {
+            let get = |end: &str, column: &str| row.get(&format!("{end}_{scheme}_{column}"));
+            let asym = get("beg", "asym_id")?;
+            let first = get("beg", "seq_id")?.parse::<i32>().ok()?;
+            let last = get("end", "seq_id")?.parse::<i32>().ok()?;
+            Some((asym.to_string(), first.min(last), first.max(last)))
+        };
+
+        let (residues, (asym, first, last)) = match bounds("label") {
+            Some(b) => (&self.label_residues, b),
+            None => match bounds("auth") {
+                Some(b) => (&self.auth_residues, b),
+                None => return,
+            },
+        };
+        for seq in first..=last {
+            if let Some(residue) = residues.get(&(asym.clone(), seq)) {
+                annotations.insert(residue.clone(), structure);
+            }
+        }
+    }
+}
+
+/// Parse a CIF number, stripping a standard uncertainty such as `57.230(3)`.
+fn parse_number(raw: &str) -> Option<f32> {
+    let raw = raw.trim();
+    raw.split('(').next().unwrap_or(raw).parse::<f32>().ok()
 }
 
 /// Write trajectory to mmCIF format
@@ -431,7 +663,11 @@ impl MmcifWriter {
         // Write metadata
         writeln!(writer, "#")?;
         writeln!(writer, "_entry.id {}", file_path_to_id(path))?;
-        writeln!(writer, "_struct.title {}", trajectory.metadata.title)?;
+        writeln!(
+            writer,
+            "_struct.title {}",
+            cif::format_value(&trajectory.metadata.title)
+        )?;
 
         // Write atom_site loop header
         writeln!(writer, "#")?;
@@ -457,8 +693,8 @@ impl MmcifWriter {
 
         // Write atoms
         if let Some(frame) = trajectory.frames.first() {
-            for atom_id in 0..trajectory.num_atoms as u32 {
-                if let Some(pos) = frame.get_position(atom_id) {
+            for atom_id in 0..trajectory.num_atoms {
+                if let Some(pos) = frame.position(atom_id) {
                     // Note: This is a simplified writer - in reality you'd need atom data
                     writeln!(
                         writer,
@@ -521,6 +757,227 @@ ATOM 3  H  H2 .  HOH  A  1  . -0.757 0.000 0.000
         let trajectory = result.unwrap();
         assert_eq!(trajectory.num_frames(), 1);
         assert_eq!(trajectory.num_atoms, 3);
+        assert!(trajectory.frames[0].cell.is_none());
+    }
+
+    #[test]
+    fn test_parse_cell_category() {
+        let mmcif_content = r#"data_cell
+_cell.length_a    57.230(3)
+_cell.length_b    57.230
+_cell.length_c    146.770
+_cell.angle_alpha 90.00
+_cell.angle_beta  90.00
+_cell.angle_gamma 120.00
+loop_
+_atom_site.id
+_atom_site.type_symbol
+_atom_site.label_atom_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+1 O O 0.000 0.000 0.000
+"#;
+
+        let trajectory =
+            MmcifParser::parse_string(mmcif_content, PathBuf::from("cell.cif")).unwrap();
+        let cell = trajectory.frames[0].cell.expect("cell parsed");
+        assert!((cell.lengths[0] - 57.23).abs() < 1e-3);
+        assert!((cell.angles[2] - 120.0).abs() < 1e-3);
+        assert!(!cell.is_orthorhombic());
+    }
+
+    #[test]
+    fn test_quoted_text_fields_and_key_value_categories() {
+        let mmcif_content = r#"data_1ABC
+_struct.title
+;Crystal structure of a
+nucleotide complex
+;
+_struct_keywords.pdbx_keywords 'TRANSFERASE/DNA'
+_struct_keywords.text          "TRANSFERASE, DNA, 'hinge' loop"
+_symmetry.space_group_name_H-M 'P 21 21 21'
+loop_
+_atom_site.group_PDB
+_atom_site.id
+_atom_site.type_symbol
+_atom_site.label_atom_id
+_atom_site.label_comp_id
+_atom_site.label_asym_id
+_atom_site.label_seq_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.occupancy
+_atom_site.B_iso_or_equiv
+_atom_site.pdbx_formal_charge
+_atom_site.auth_seq_id
+_atom_site.pdbx_PDB_model_num
+ATOM   1 O "O5'" DA B 1 1.0 2.0 3.0 0.50 12.5 ? 1 1
+HETATM 2 MG MG  MG C . 4.0 5.0 6.0 1.00 30.0 2 501 1
+ATOM   1 O "O5'" DA B 1 1.5 2.0 3.0 0.50 12.5 ? 1 2
+HETATM 2 MG MG  MG C . 4.5 5.0 6.0 1.00 30.0 2 501 2
+"#;
+
+        let (trajectory, atoms, _bonds) =
+            MmcifParser::parse_structure(mmcif_content, PathBuf::from("1abc.cif")).unwrap();
+        assert_eq!(
+            trajectory.metadata.title,
+            "Crystal structure of a\nnucleotide complex"
+        );
+        assert_eq!(trajectory.metadata.classification, "TRANSFERASE/DNA");
+        assert_eq!(
+            trajectory.metadata.extra.get("keywords").unwrap(),
+            "TRANSFERASE, DNA, 'hinge' loop"
+        );
+        assert_eq!(
+            trajectory.metadata.extra.get("space_group").unwrap(),
+            "P 21 21 21"
+        );
+
+        assert_eq!(atoms.len(), 2);
+        assert_eq!(atoms[0].name, "O5'");
+        assert_eq!(atoms[0].occupancy, 0.5);
+        assert_eq!(atoms[1].element, Element::Mg);
+        assert_eq!(atoms[1].residue_id, 501);
+        assert_eq!(atoms[1].charge, 2.0);
+
+        // Second model becomes a second frame
+        assert_eq!(trajectory.num_frames(), 2);
+        assert_eq!(
+            trajectory.get_frame(1).unwrap().position(0),
+            Some(Vec3::new(1.5, 2.0, 3.0))
+        );
+    }
+
+    #[test]
+    fn test_struct_conn_links_ligands_and_waters() {
+        let mmcif_content = r#"data_zn
+loop_
+_atom_site.group_PDB
+_atom_site.id
+_atom_site.type_symbol
+_atom_site.label_atom_id
+_atom_site.label_comp_id
+_atom_site.label_asym_id
+_atom_site.label_seq_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.auth_seq_id
+_atom_site.auth_asym_id
+ATOM   1 N  NE2 HIS A 12 0.0 0.0 0.0 15  A
+HETATM 2 ZN ZN  ZN  B .  2.1 0.0 0.0 301 A
+HETATM 3 O  O   HOH C .  4.0 0.0 0.0 401 A
+HETATM 4 O  O   HOH C .  9.0 0.0 0.0 402 A
+loop_
+_struct_conn.id
+_struct_conn.conn_type_id
+_struct_conn.ptnr1_label_asym_id
+_struct_conn.ptnr1_label_seq_id
+_struct_conn.ptnr1_label_atom_id
+_struct_conn.ptnr1_auth_seq_id
+_struct_conn.ptnr1_symmetry
+_struct_conn.ptnr2_label_asym_id
+_struct_conn.ptnr2_label_seq_id
+_struct_conn.ptnr2_label_atom_id
+_struct_conn.ptnr2_auth_seq_id
+_struct_conn.ptnr2_symmetry
+_struct_conn.pdbx_dist_value
+metalc1 metalc A 12 NE2 15  1_555 B . ZN 301 1_555 2.10
+metalc2 metalc B .  ZN  301 1_555 C . O  402 1_555 ?
+metalc3 metalc B .  ZN  301 1_555 C . O  401 2_655 1.90
+"#;
+
+        let (_, _, bonds) =
+            MmcifParser::parse_structure(mmcif_content, PathBuf::from("zn.cif")).unwrap();
+        assert_eq!(bonds.len(), 2);
+        assert!(bonds.iter().all(|b| b.bond_type == BondType::MetalCoord));
+        assert_eq!((bonds[0].atom_a_id, bonds[0].atom_b_id), (0, 1));
+        assert!((bonds[0].length - 2.1).abs() < 1e-6);
+        // Water 402, not 401; the symmetry-mate link is skipped
+        assert_eq!((bonds[1].atom_a_id, bonds[1].atom_b_id), (1, 3));
+        assert!((bonds[1].length - 6.9).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_assembly_gen_and_space_group_symops() {
+        let mmcif_content = r#"data_asm
+_cell.length_a 10.0
+_cell.length_b 20.0
+_cell.length_c 30.0
+_symmetry.space_group_name_H-M 'P 1 21 1'
+loop_
+_space_group_symop.id
+_space_group_symop.operation_xyz
+1 x,y,z
+2 -x,y+1/2,-z
+loop_
+_atom_site.id
+_atom_site.type_symbol
+_atom_site.label_atom_id
+_atom_site.label_comp_id
+_atom_site.label_asym_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.auth_asym_id
+1 C CA ALA A 1.0 0.0 0.0 A
+2 ZN ZN ZN B 2.0 0.0 0.0 A
+3 C CA GLY C 3.0 0.0 0.0 B
+_pdbx_struct_assembly.id 1
+_pdbx_struct_assembly.oligomeric_details tetrameric
+loop_
+_pdbx_struct_assembly_gen.assembly_id
+_pdbx_struct_assembly_gen.oper_expression
+_pdbx_struct_assembly_gen.asym_id_list
+1 '(1,2)(P)' A,B
+1 1 C
+loop_
+_pdbx_struct_oper_list.id
+_pdbx_struct_oper_list.matrix[1][1]
+_pdbx_struct_oper_list.matrix[1][2]
+_pdbx_struct_oper_list.matrix[1][3]
+_pdbx_struct_oper_list.vector[1]
+_pdbx_struct_oper_list.matrix[2][1]
+_pdbx_struct_oper_list.matrix[2][2]
+_pdbx_struct_oper_list.matrix[2][3]
+_pdbx_struct_oper_list.vector[2]
+_pdbx_struct_oper_list.matrix[3][1]
+_pdbx_struct_oper_list.matrix[3][2]
+_pdbx_struct_oper_list.matrix[3][3]
+_pdbx_struct_oper_list.vector[3]
+1 1 0 0 0 0 1 0 0 0 0 1 0
+2 -1 0 0 0 0 -1 0 0 0 0 1 0
+P 1 0 0 5 0 1 0 0 0 0 1 0
+"#;
+
+        let (trajectory, _, _) =
+            MmcifParser::parse_structure(mmcif_content, PathBuf::from("asm.cif")).unwrap();
+        let symmetry = &trajectory.metadata.symmetry;
+        assert_eq!(symmetry.space_group.as_deref(), Some("P 1 21 1"));
+        assert_eq!(symmetry.operators.len(), 2);
+        assert_eq!(symmetry.operators[1].translation, Vec3::new(0.0, 10.0, 0.0));
+
+        let assembly = symmetry.assembly("1").unwrap();
+        assert_eq!(assembly.details, "tetrameric");
+        // Label asyms A and B are both author chain A
+        assert_eq!(assembly.generators[0].chains, vec!["A".to_string()]);
+        assert_eq!(assembly.generators[1].chains, vec!["B".to_string()]);
+        assert_eq!(assembly.num_copies(), 3);
+        // (2)(P): shift by P first, then rotate
+        let op = assembly.generators[0].operators[1];
+        assert_eq!(
+            op.apply(Vec3::new(1.0, 0.0, 0.0)),
+            Vec3::new(-6.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(MmcifParser::parse_string("", PathBuf::from("empty.cif")).is_err());
+        let unterminated = "data_x\n_struct.title\n;never closed\n";
+        assert!(MmcifParser::parse_string(unterminated, PathBuf::from("x.cif")).is_err());
     }
 
     #[test]
@@ -2,13 +2,31 @@
 //!
 //! This module provides parsers for various molecular file formats.
 
+pub mod cif;
+pub mod compression;
 pub mod dcd;
+pub mod extxyz;
 pub mod gro;
+pub mod gro_stream;
+pub mod lammps_data;
+pub mod lammps_dump;
+pub mod mdcrd;
 pub mod mmcif;
+pub mod mol2;
+pub mod molecule;
+pub mod netcdf;
 pub mod pdb;
 pub mod pdb_mmap;
+pub mod pdbqt;
+pub mod pqr;
+pub mod prmtop;
+pub mod psf;
+pub mod registry;
+pub mod sdf;
 pub mod streaming;
 pub mod topology;
+pub mod trr;
+pub mod xtc;
 pub mod xyz;
 pub mod xyz_parallel;
 pub mod xyz_stream;
@@ -26,6 +44,8 @@ pub fn register(app: &mut App) {
     gro::register(app);
     dcd::register(app);
     mmcif::register(app);
+    xtc::register(app);
+    trr::register(app);
 
     info!("IO module registered");
 }
@@ -52,40 +72,70 @@ pub enum IOError {
 /// Result type for IO operations
 pub type IOResult<T> = Result<T, IOError>;
 
-/// File format detection
+/// File format identifier; see [`registry`] for extensions, detection and
+/// capabilities
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileFormat {
     XYZ,
     PDB,
     GRO,
     DCD,
+    XTC,
+    TRR,
     MmCIF,
+    /// CHARMM/X-PLOR topology (atoms and bonds, no coordinates)
+    PSF,
+    /// AMBER topology (atoms and bonds, no coordinates)
+    Prmtop,
+    /// AMBER ASCII trajectory
+    Mdcrd,
+    /// AMBER NetCDF-3 trajectory
+    NetCDF,
+    /// LAMMPS text dump trajectory
+    LammpsDump,
+    /// LAMMPS data file (atoms and bonds, used as topology)
+    LammpsData,
+    /// Tripos MOL2 (atoms, charges and bond orders)
+    Mol2,
+    /// MDL molfile / SD file, V2000 or V3000
+    Sdf,
+    /// APBS/PDB2PQR structure with per-atom charge and radius
+    Pqr,
+    /// AutoDock PDBQT (PDB with partial charges and AutoDock atom types)
+    Pdbqt,
+    /// Format registered outside this crate (see [`registry::register_format`])
+    Custom(&'static str),
     Unknown,
 }
 
 impl FileFormat {
-    /// Detect file format from file extension
+    /// Detect file format from file extension, looking through a `.gz`,
+    /// `.bz2` or `.xz` suffix (`1abc.pdb.gz` is PDB).
     pub fn from_path(path: &std::path::Path) -> Self {
-        match path.extension().and_then(|ext| ext.to_str()) {
-            Some("xyz") => FileFormat::XYZ,
-            Some("pdb") => FileFormat::PDB,
-            Some("gro") => FileFormat::GRO,
-            Some("dcd") => FileFormat::DCD,
-            Some("cif") | Some("mmcif") | Some("mcif") => FileFormat::MmCIF,
-            _ => FileFormat::Unknown,
-        }
+        registry::registry()
+            .by_extension(path)
+            .map_or(FileFormat::Unknown, |d| d.format)
+    }
+
+    /// Detect file format from the file's content, using the extension to
+    /// break ties (see [`registry`]).
+    pub fn detect(path: &std::path::Path) -> Self {
+        registry::detect(path).map_or(FileFormat::Unknown, |d| d.format)
+    }
+
+    /// Registered description of this format.
+    pub fn descriptor(&self) -> Option<registry::FormatDescriptor> {
+        registry::descriptor(*self)
     }
 
     /// Check if this format is currently loadable (parser implemented)
     pub fn is_loadable(&self) -> bool {
-        matches!(
-            self,
-            FileFormat::XYZ
-                | FileFormat::PDB
-                | FileFormat::GRO
-                | FileFormat::MmCIF
-                | FileFormat::DCD
-        )
+        self.descriptor().is_some_and(|d| d.is_loadable())
+    }
+
+    /// Coordinate-only trajectory formats that pair with a topology file.
+    pub fn needs_topology(&self) -> bool {
+        self.descriptor().is_some_and(|d| d.needs_topology)
     }
 
     /// Detect file format from content (text or binary peek).
@@ -93,67 +143,24 @@ impl FileFormat {
         Self::from_bytes(content.as_bytes())
     }
 
-    /// Detect file format from raw bytes (handles binary DCD magic).
+    /// Detect file format from raw bytes with the registered sniffers.
     pub fn from_bytes(data: &[u8]) -> Self {
-        if dcd::DcdParser::is_dcd_bytes(data) {
-            return FileFormat::DCD;
-        }
-
-        let content = std::str::from_utf8(data).unwrap_or("");
-        let first_line = content.lines().next().unwrap_or("");
-
-        // XYZ format: first line is number of atoms
-        if first_line.trim().parse::<u32>().is_ok() {
-            return FileFormat::XYZ;
-        }
-
-        // PDB format: starts with ATOM, HETATM, HEADER, etc.
-        let first_word = first_line.split_whitespace().next().unwrap_or("");
-        if matches!(
-            first_word,
-            "ATOM" | "HETATM" | "HEADER" | "TITLE" | "CRYST1" | "REMARK" | "MODEL"
-        ) {
-            return FileFormat::PDB;
-        }
-
-        // GRO format: check for typical GRO structure
-        // Look for lines with column-based format (5 chars, 5 chars, 5 chars, 5 chars, 8.3, 8.3, 8.3)
-        let lines: Vec<&str> = content.lines().take(10).collect();
-        if lines.len() >= 3 {
-            // Try to parse the second line as a number
-            if let Ok(_num_atoms) = lines[1].trim().parse::<u32>() {
-                // Check third line for GRO-like format
-                let third_line = lines[2];
-                // GRO lines typically have at least 44 characters
-                if third_line.len() >= 44 {
-                    return FileFormat::GRO;
-                }
-            }
-        }
-
-        // mmCIF format: starts with "data_" block
-        if first_line.starts_with("data_") {
-            return FileFormat::MmCIF;
-        }
-
-        FileFormat::Unknown
+        registry::registry()
+            .sniff(data)
+            .map_or(FileFormat::Unknown, |(d, _)| d.format)
     }
 }
 
-/// Load atom metadata and bonds from a topology file (PDB, GRO, mmCIF).
+/// Load atom metadata and bonds from a topology file (any registered format
+/// with a topology reader: PDB, GRO, mmCIF, PSF, prmtop, LAMMPS data, MOL2,
+/// SDF, PQR, PDBQT).
 pub fn load_topology(path: &Path) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
-    match FileFormat::from_path(path) {
-        FileFormat::PDB => pdb::PDBParser::parse_topology(path),
-        FileFormat::GRO => {
-            let (atoms, bonds) = gro::GroParser::parse_topology(path)?;
-            Ok(topology::normalize_topology(atoms, bonds))
-        }
-        FileFormat::MmCIF => {
-            let atoms = mmcif::MmcifParser::parse_atom_data_from_file(path)?;
-            Ok((atoms, Vec::new()))
-        }
-        other => Err(IOError::UnsupportedFormat(format!(
-            "Topology format not supported: {other:?}"
+    let descriptor = registry::detect(path);
+    match descriptor.and_then(|d| d.read_topology) {
+        Some(read_topology) => read_topology(path),
+        None => Err(IOError::UnsupportedFormat(format!(
+            "Topology format not supported: {}",
+            descriptor.map_or("unknown", |d| d.name)
         ))),
     }
 }
@@ -0,0 +1,412 @@
+//! Tripos MOL2 reader
+//!
+//! MOL2 files are split into `@<TRIPOS>` sections. Three are read:
+//!
+//! ```text
+//! @<TRIPOS>MOLECULE
+//! benzene
+//!  12 12 1 0 0
+//! @<TRIPOS>ATOM
+//!       1 C1    1.3870  0.0000  0.0000 C.ar  1 BEN1  -0.1150
+//! @<TRIPOS>BOND
+//!      1     1     2 ar
+//! @<TRIPOS>SUBSTRUCTURE
+//!      1 BEN1        1 RESIDUE 4 A BEN 0 ROOT
+//! ```
+//!
+//! Elements come from the SYBYL atom type (`C.ar` → C), falling back to the
+//! atom name for dummy and wildcard types. The partial charge column fills
+//! [`AtomData::charge`]. Bond types `1`, `2`, `3`, `ar`, `am`, `du` and `un`
+//! keep their order; `nc` (not connected) bonds are dropped. Substructures
+//! become residues, with the chain and residue type from the
+//! `SUBSTRUCTURE` section when present.
+//!
+//! A file may hold several molecules, each starting with
+//! `@<TRIPOS>MOLECULE`; see [`MultiRecordMode`] for how they are combined.
+
+use crate::core::atom::{AtomData, Element};
+use crate::core::bond::{BondData, BondOrder, BondType};
+use crate::core::trajectory::Trajectory;
+use crate::io::molecule::{assemble_records, MoleculeRecord, MultiRecordMode};
+use crate::io::registry::{
+    sniff_text, Confidence, FormatCapabilities, FormatDescriptor, LoadedFile,
+};
+use crate::io::{FileFormat, IOError, IOResult};
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Element from a SYBYL atom type such as `C.ar`, `N.pl3` or `Cl`.
+///
+/// Dummy (`Du`), lone pair (`LP`) and wildcard types (`Any`, `Hal`, `Het`,
+/// `Hev`) fall back to the atom name.
+pub fn element_from_sybyl_type(atom_type: &str, atom_name: &str) -> Element {
+    let symbol = atom_type.split('.').next().unwrap_or("");
+    match symbol {
+        "Du" | "LP" | "Any" | "Hal" | "Het" | "Hev" => Element::from_atom_name(atom_name),
+        _ => Element::from_symbol(symbol).unwrap_or_else(|_| Element::from_atom_name(atom_name)),
+    }
+}
+
+/// Bond type and order for a MOL2 bond code, or `None` for `nc`.
+fn bond_from_code(code: &str) -> Option<(BondType, BondOrder)> {
+    match code.to_ascii_lowercase().as_str() {
+        "1" | "am" => Some((BondType::Covalent, BondOrder::Single)),
+        "2" => Some((BondType::Covalent, BondOrder::Double)),
+        "3" => Some((BondType::Covalent, BondOrder::Triple)),
+        "ar" => Some((BondType::Covalent, BondOrder::Aromatic)),
+        "nc" => None,
+        _ => Some((BondType::Unknown, BondOrder::Single)),
+    }
+}
+
+/// Residue name from a substructure name such as `ALA12` or `LIG1`.
+fn residue_name_from_subst(subst_name: &str) -> String {
+    let name = subst_name.trim_end_matches(|c: char| c.is_ascii_digit());
+    if name.is_empty() || name.starts_with('<') || name.starts_with('*') {
+        "UNL".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn optional_field(value: Option<&&str>) -> Option<String> {
+    value
+        .filter(|v| !v.starts_with('*') && !v.is_empty())
+        .map(|v| v.to_string())
+}
+
+struct PendingAtom {
+    serial: u32,
+    subst_id: u32,
+    subst_name: Option<String>,
+    atom: AtomData,
+}
+
+#[derive(Default)]
+struct PendingRecord {
+    name: String,
+    atoms: Vec<PendingAtom>,
+    bonds: Vec<(u32, u32, String, usize)>,
+    /// subst_id -> (chain, residue type)
+    substructures: HashMap<u32, (Option<String>, Option<String>)>,
+}
+
+impl PendingRecord {
+    fn finish(self) -> IOResult<MoleculeRecord> {
+        let index: HashMap<u32, u32> = self
+            .atoms
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (a.serial, i as u32))
+            .collect();
+
+        let atoms = self
+            .atoms
+            .into_iter()
+            .enumerate()
+            .map(|(i, pending)| {
+                let mut atom = pending.atom;
+                atom.id = i as u32;
+                atom.residue_id = pending.subst_id;
+                let (chain, sub_type) = self
+                    .substructures
+                    .get(&pending.subst_id)
+                    .cloned()
+                    .unwrap_or_default();
+                atom.residue_name = sub_type.unwrap_or_else(|| {
+                    residue_name_from_subst(pending.subst_name.as_deref().unwrap_or(""))
+                });
+                atom.chain_id = chain.unwrap_or_else(|| "A".to_string());
+                atom
+            })
+            .collect();
+
+        let mut bonds = Vec::with_capacity(self.bonds.len());
+        for (a, b, code, line) in self.bonds {
+            let Some((bond_type, order)) = bond_from_code(&code) else {
+                continue;
+            };
+            let (Some(&ia), Some(&ib)) = (index.get(&a), index.get(&b)) else {
+                return Err(IOError::ParseError {
+                    line,
+                    message: format!("Bond references unknown atom ({a}, {b})"),
+                });
+            };
+            bonds.push(BondData::new(ia, ib, bond_type, order, 0.0));
+        }
+
+        Ok(MoleculeRecord {
+            name: self.name,
+            atoms,
+            bonds,
+            data: Vec::new(),
+        })
+    }
+}
+
+/// Registry entry: Tripos MOL2 molecules; multi-record files hold conformer frames.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::Mol2,
+    name: "MOL2",
+    extensions: &["mol2"],
+    capabilities: FormatCapabilities {
+        topology: true,
+        trajectory: true,
+        streaming: false,
+        writable: false,
+    },
+    needs_topology: false,
+    sniff,
+    load: Some(load),
+    read_topology: Some(Mol2Parser::parse_topology),
+};
+
+fn sniff(data: &[u8]) -> Confidence {
+    if sniff_text(data).is_some_and(Mol2Parser::is_mol2_content) {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, _topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    let (trajectory, atom_data, bond_data) = Mol2Parser::parse_file(path)?;
+    Ok((trajectory, atom_data, bond_data, None, false))
+}
+
+/// MOL2 format parser
+pub struct Mol2Parser;
+
+impl Mol2Parser {
+    /// Parse a MOL2 file; multiple molecules are combined with [`MultiRecordMode::Auto`].
+    pub fn parse_file(path: &Path) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        Self::parse_file_with_mode(path, MultiRecordMode::Auto)
+    }
+
+    /// Parse a MOL2 file, combining multiple molecules as `mode` says.
+    pub fn parse_file_with_mode(
+        path: &Path,
+        mode: MultiRecordMode,
+    ) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        let content = crate::io::compression::read_to_string(path)?;
+        Self::parse_string(&content, path.to_path_buf(), mode)
+    }
+
+    /// Parse MOL2 content.
+    pub fn parse_string(
+        content: &str,
+        file_path: PathBuf,
+        mode: MultiRecordMode,
+    ) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        let records = Self::parse_records(content)?;
+        assemble_records(records, file_path, mode, "MOL2")
+    }
+
+    /// Atoms and bonds of a MOL2 file, for use as a topology.
+    pub fn parse_topology(path: &Path) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let (_, atoms, bonds) = Self::parse_file(path)?;
+        Ok((atoms, bonds))
+    }
+
+    /// Parse every `@<TRIPOS>MOLECULE` record.
+    pub fn parse_records(content: &str) -> IOResult<Vec<MoleculeRecord>> {
+        let mut records = Vec::new();
+        let mut current: Option<PendingRecord> = None;
+        let mut section = String::new();
+        let mut section_line = 0usize;
+
+        for (line_num, raw) in content.lines().enumerate() {
+            let line = raw.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix("@<TRIPOS>") {
+                section = name.trim().to_ascii_uppercase();
+                section_line = 0;
+                if section == "MOLECULE" {
+                    if let Some(record) = current.take() {
+                        records.push(record.finish()?);
+                    }
+                    current = Some(PendingRecord::default());
+                }
+                continue;
+            }
+
+            let Some(record) = current.as_mut() else {
+                continue;
+            };
+            let parts: Vec<&str> = line.split_whitespace().collect();
+
+            match section.as_str() {
+                "MOLECULE" => {
+                    if section_line == 0 {
+                        record.name = line.to_string();
+                    }
+                    section_line += 1;
+                }
+                "ATOM" if !parts.is_empty() => {
+                    if parts.len() < 6 {
+                        return Err(IOError::ParseError {
+                            line: line_num + 1,
+                            message: format!(
+                                "Expected id name x y z type, got {} fields",
+                                parts.len()
+                            ),
+                        });
+                    }
+                    let number = |i: usize| {
+                        parts[i].parse::<f32>().map_err(|_| IOError::ParseError {
+                            line: line_num + 1,
+                            message: format!("Invalid number: {}", parts[i]),
+                        })
+                    };
+                    let serial = parts[0].parse::<u32>().map_err(|_| IOError::ParseError {
+                        line: line_num + 1,
+                        message: format!("Invalid atom id: {}", parts[0]),
+                    })?;
+                    let position = Vec3::new(number(2)?, number(3)?, number(4)?);
+                    let element = element_from_sybyl_type(parts[5], parts[1]);
+
+                    let mut atom = AtomData::new(
+                        serial,
+                        element,
+                        0,
+                        String::new(),
+                        String::new(),
+                        parts[1].to_string(),
+                    );
+                    atom.position = position;
+                    if parts.len() > 8 {
+                        atom.charge = number(8)?;
+                    }
+
+                    record.atoms.push(PendingAtom {
+                        serial,
+                        subst_id: parts.get(6).and_then(|s| s.parse().ok()).unwrap_or(1),
+                        subst_name: optional_field(parts.get(7)),
+                        atom,
+                    });
+                }
+                "BOND" if !parts.is_empty() => {
+                    if parts.len() < 4 {
+                        return Err(IOError::ParseError {
+                            line: line_num + 1,
+                            message: format!(
+                                "Expected id origin target type, got {} fields",
+                                parts.len()
+                            ),
+                        });
+                    }
+                    let serial = |i: usize| {
+                        parts[i].parse::<u32>().map_err(|_| IOError::ParseError {
+                            line: line_num + 1,
+                            message: format!("Invalid atom id in bond: {}", parts[i]),
+                        })
+                    };
+                    record
+                        .bonds
+                        .push((serial(1)?, serial(2)?, parts[3].to_string(), line_num + 1));
+                }
+                "SUBSTRUCTURE" if parts.len() >= 2 => {
+                    if let Ok(subst_id) = parts[0].parse::<u32>() {
+                        record.substructures.insert(
+                            subst_id,
+                            (optional_field(parts.get(5)), optional_field(parts.get(6))),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(record) = current.take() {
+            records.push(record.finish()?);
+        }
+        Ok(records)
+    }
+
+    /// Whether content looks like MOL2 (has a `@<TRIPOS>` section header).
+    pub fn is_mol2_content(content: &str) -> bool {
+        content
+            .lines()
+            .take(50)
+            .any(|line| line.trim_start().starts_with("@<TRIPOS>"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACETATE: &str = "\
+# generated by hand
+@<TRIPOS>MOLECULE
+acetate
+ 4 3 1 0 0
+SMALL
+USER_CHARGES
+
+@<TRIPOS>ATOM
+      1 C1    0.0000  0.0000  0.0000 C.3    1 ACT1   -0.2000
+      2 C2    1.5200  0.0000  0.0000 C.2    1 ACT1    0.7000
+      4 O1    2.1500  1.0800  0.0000 O.co2  1 ACT1   -0.7500
+      5 O2    2.1500 -1.0800  0.0000 O.co2  1 ACT1   -0.7500
+@<TRIPOS>BOND
+     1     1     2 1
+     2     2     4 ar
+     3     2     5 2
+@<TRIPOS>SUBSTRUCTURE
+     1 ACT1        1 RESIDUE 4 B ACT 0 ROOT
+";
+
+    #[test]
+    fn test_parse_mol2_atoms_bonds_and_substructure() {
+        let (trajectory, atoms, bonds) =
+            Mol2Parser::parse_string(ACETATE, PathBuf::from("a.mol2"), MultiRecordMode::Auto)
+                .unwrap();
+
+        assert_eq!(trajectory.num_frames(), 1);
+        assert_eq!(trajectory.metadata.title, "acetate");
+        assert_eq!(atoms.len(), 4);
+        assert_eq!(atoms[2].element, Element::O);
+        assert_eq!(atoms[2].id, 2);
+        assert_eq!(atoms[2].charge, -0.75);
+        assert_eq!(atoms[0].residue_name, "ACT");
+        assert_eq!(atoms[0].chain_id, "B");
+
+        assert_eq!(bonds.len(), 3);
+        assert_eq!((bonds[1].atom_a_id, bonds[1].atom_b_id), (1, 2));
+        assert_eq!(bonds[1].order, BondOrder::Aromatic);
+        assert_eq!(bonds[2].order, BondOrder::Double);
+        assert!((bonds[0].length - 1.52).abs() < 1e-5);
+
+        let frame = trajectory.get_frame(0).unwrap();
+        assert_eq!(frame.properties["charge"][1], 0.7);
+    }
+
+    #[test]
+    fn test_parse_mol2_multiple_poses_as_frames() {
+        let second = ACETATE.replace("0.0000  0.0000  0.0000 C.3", "0.1000  0.0000  0.0000 C.3");
+        let content = format!("{ACETATE}{second}");
+        let (trajectory, atoms, _) =
+            Mol2Parser::parse_string(&content, PathBuf::from("a.mol2"), MultiRecordMode::Auto)
+                .unwrap();
+        assert_eq!(trajectory.num_frames(), 2);
+        assert_eq!(atoms.len(), 4);
+        assert_eq!(
+            trajectory.get_frame(1).unwrap().position(0),
+            Some(Vec3::new(0.1, 0.0, 0.0))
+        );
+
+        assert!(Mol2Parser::is_mol2_content(&content));
+    }
+
+    #[test]
+    fn test_sybyl_type_elements() {
+        assert_eq!(element_from_sybyl_type("N.pl3", "N1"), Element::N);
+        assert_eq!(element_from_sybyl_type("Cl", "CL1"), Element::Cl);
+        assert_eq!(element_from_sybyl_type("Du", "CA"), Element::C);
+        assert!(bond_from_code("nc").is_none());
+    }
+}
@@ -0,0 +1,242 @@
+//! Shared record model for small-molecule formats (MOL2, SDF/MOL)
+//!
+//! Both formats store one molecule per record with explicit bond orders, and
+//! both allow several records per file. A file whose records all describe the
+//! same molecule (same atom count and element sequence, e.g. docking poses or
+//! conformer ensembles) loads as a trajectory with one frame per record;
+//! otherwise the records are laid out side by side as separate molecules in
+//! a single frame. [`MultiRecordMode`] forces either behaviour.
+
+use crate::core::atom::AtomData;
+use crate::core::bond::BondData;
+use crate::core::trajectory::{set_atom_value, FrameData, Trajectory, TrajectoryMetadata};
+use crate::io::{IOError, IOResult};
+use std::path::PathBuf;
+
+/// One molecule record.
+///
+/// Atom ids run `0..atoms.len()` and positions are stored in
+/// [`AtomData::position`]; bond endpoints use the same ids.
+#[derive(Debug, Clone, Default)]
+pub struct MoleculeRecord {
+    /// Molecule name (first line of an SDF record, MOL2 `@<TRIPOS>MOLECULE` name)
+    pub name: String,
+    /// Atoms with positions and charges
+    pub atoms: Vec<AtomData>,
+    /// Bonds with the file's bond orders
+    pub bonds: Vec<BondData>,
+    /// Record-level data items (SDF `> <NAME>` fields)
+    pub data: Vec<(String, String)>,
+}
+
+impl MoleculeRecord {
+    /// Whether `other` has the same atoms in the same order.
+    pub fn same_molecule(&self, other: &MoleculeRecord) -> bool {
+        self.atoms.len() == other.atoms.len()
+            && self
+                .atoms
+                .iter()
+                .zip(&other.atoms)
+                .all(|(a, b)| a.element == b.element)
+    }
+
+    /// Fill in bond lengths from atom positions.
+    pub fn compute_bond_lengths(&mut self) {
+        for bond in &mut self.bonds {
+            if let (Some(a), Some(b)) = (
+                self.atoms.get(bond.atom_a_id as usize),
+                self.atoms.get(bond.atom_b_id as usize),
+            ) {
+                bond.length = a.position.distance(b.position);
+            }
+        }
+    }
+
+    fn to_frame(&self, offset: usize, frame: &mut FrameData) {
+        let has_charges = self.atoms.iter().any(|a| a.charge != 0.0);
+        for (i, atom) in self.atoms.iter().enumerate() {
+            frame.set_position(offset + i, atom.position);
+            if has_charges {
+                let charges = frame.properties.entry("charge".to_string()).or_default();
+                set_atom_value(charges, offset + i, atom.charge);
+            }
+        }
+    }
+}
+
+/// How a file with several records is loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MultiRecordMode {
+    /// Frames when every record is the same molecule, molecules otherwise
+    #[default]
+    Auto,
+    /// One frame per record; fails if the records differ
+    Frames,
+    /// All records in one frame, one molecule (residue block) per record
+    Molecules,
+}
+
+/// Turn parsed records into a trajectory plus atom and bond tables.
+pub fn assemble_records(
+    mut records: Vec<MoleculeRecord>,
+    file_path: PathBuf,
+    mode: MultiRecordMode,
+    software: &str,
+) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+    if records.is_empty() {
+        return Err(IOError::InvalidFormat(format!(
+            "No {software} records found in {}",
+            file_path.display()
+        )));
+    }
+    for record in &mut records {
+        record.compute_bond_lengths();
+    }
+
+    let compatible = records.iter().all(|r| r.same_molecule(&records[0]));
+    let as_frames = match mode {
+        MultiRecordMode::Auto => compatible,
+        MultiRecordMode::Frames if !compatible => {
+            return Err(IOError::InvalidFormat(format!(
+                "{software} records differ in atoms and cannot be loaded as frames"
+            )));
+        }
+        MultiRecordMode::Frames => true,
+        MultiRecordMode::Molecules => false,
+    };
+
+    let metadata = TrajectoryMetadata {
+        title: records[0].name.clone(),
+        software: software.to_string(),
+        ..Default::default()
+    };
+
+    if as_frames {
+        let num_atoms = records[0].atoms.len();
+        let mut trajectory = Trajectory::new(file_path, num_atoms, 1.0);
+        trajectory.metadata = metadata;
+        for (i, record) in records.iter().enumerate() {
+            let mut frame = FrameData::new(i, i as f32);
+            record.to_frame(0, &mut frame);
+            frame
+                .metadata
+                .insert("name".to_string(), record.name.clone());
+            for (key, value) in &record.data {
+                frame.metadata.insert(key.clone(), value.clone());
+            }
+            trajectory.add_frame(frame);
+        }
+        let first = records.swap_remove(0);
+        return Ok((trajectory, first.atoms, first.bonds));
+    }
+
+    let mut atoms = Vec::new();
+    let mut bonds = Vec::new();
+    let mut frame = FrameData::new(0, 0.0);
+    let mut residue_offset = 0;
+    for record in records {
+        let id_offset = atoms.len() as u32;
+        record.to_frame(id_offset as usize, &mut frame);
+        let mut max_residue = residue_offset;
+        for mut atom in record.atoms {
+            atom.id += id_offset;
+            atom.residue_id += residue_offset;
+            max_residue = max_residue.max(atom.residue_id);
+            atoms.push(atom);
+        }
+        residue_offset = max_residue;
+        bonds.extend(record.bonds.into_iter().map(|mut bond| {
+            bond.atom_a_id += id_offset;
+            bond.atom_b_id += id_offset;
+            bond
+        }));
+    }
+
+    let mut trajectory = Trajectory::new(file_path, atoms.len(), 1.0);
+    trajectory.metadata = metadata;
+    trajectory.add_frame(frame);
+    Ok((trajectory, atoms, bonds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::atom::Element;
+    use crate::core::bond::{BondOrder, BondType};
+    use bevy::prelude::Vec3;
+
+    fn diatomic(name: &str, elements: [Element; 2], x: f32) -> MoleculeRecord {
+        let atoms = elements
+            .iter()
+            .enumerate()
+            .map(|(i, &e)| {
+                let mut atom =
+                    AtomData::new(i as u32, e, 1, "UNL".into(), "A".into(), e.symbol().into());
+                atom.position = Vec3::new(i as f32 * x, 0.0, 0.0);
+                atom
+            })
+            .collect();
+        MoleculeRecord {
+            name: name.to_string(),
+            atoms,
+            bonds: vec![BondData::new(
+                0,
+                1,
+                BondType::Covalent,
+                BondOrder::Double,
+                0.0,
+            )],
+            data: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_assemble_same_molecule_as_frames() {
+        let records = vec![
+            diatomic("co", [Element::C, Element::O], 1.2),
+            diatomic("co", [Element::C, Element::O], 1.3),
+        ];
+        let (trajectory, atoms, bonds) = assemble_records(
+            records,
+            PathBuf::from("co.sdf"),
+            MultiRecordMode::Auto,
+            "SDF",
+        )
+        .unwrap();
+        assert_eq!(trajectory.num_frames(), 2);
+        assert_eq!(atoms.len(), 2);
+        assert!((bonds[0].length - 1.2).abs() < 1e-6);
+        assert_eq!(
+            trajectory.get_frame(1).unwrap().position(1),
+            Some(Vec3::new(1.3, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_assemble_different_molecules_side_by_side() {
+        let records = vec![
+            diatomic("co", [Element::C, Element::O], 1.2),
+            diatomic("nn", [Element::N, Element::N], 1.1),
+        ];
+        assert!(assemble_records(
+            records.clone(),
+            PathBuf::from("mix.sdf"),
+            MultiRecordMode::Frames,
+            "SDF",
+        )
+        .is_err());
+
+        let (trajectory, atoms, bonds) = assemble_records(
+            records,
+            PathBuf::from("mix.sdf"),
+            MultiRecordMode::Auto,
+            "SDF",
+        )
+        .unwrap();
+        assert_eq!(trajectory.num_frames(), 1);
+        assert_eq!(trajectory.num_atoms, 4);
+        assert_eq!(atoms[3].id, 3);
+        assert_eq!(atoms[3].residue_id, 2);
+        assert_eq!((bonds[1].atom_a_id, bonds[1].atom_b_id), (2, 3));
+    }
+}
@@ -0,0 +1,714 @@
+//! AMBER NetCDF trajectory reader (NetCDF-3 classic, pure Rust)
+//!
+//! AMBER writes trajectories as NetCDF-3 classic (`CDF\x01`) or 64-bit offset
+//! (`CDF\x02`) files following the AMBER convention: a `frame` record
+//! dimension, `coordinates(frame, atom, spatial)` in Å, `time(frame)` in ps
+//! and optional `cell_lengths`/`cell_angles` and `velocities`. The header is
+//! parsed directly so no libnetcdf is needed; each frame is then read from its
+//! record offset on demand through [`FrameProvider`]. Restart files
+//! (`AMBERRESTART`, no record dimension) load as a single frame.
+
+use crate::core::cell::UnitCell;
+use crate::core::trajectory::{FrameData, TrajectoryMetadata, PS_TO_FS};
+use crate::io::registry::{Confidence, FormatCapabilities, FormatDescriptor, LoadedFile};
+use crate::io::streaming::FrameProvider;
+use crate::io::{FileFormat, IOError, IOResult};
+use bevy::prelude::*;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+const NC_DIMENSION: u32 = 0x0A;
+const NC_VARIABLE: u32 = 0x0B;
+const NC_ATTRIBUTE: u32 = 0x0C;
+const STREAMING_NUM_RECS: u32 = u32::MAX;
+
+/// NetCDF-3 external data types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NcType {
+    Byte,
+    Char,
+    Short,
+    Int,
+    Float,
+    Double,
+}
+
+impl NcType {
+    fn from_code(code: u32) -> IOResult<Self> {
+        Ok(match code {
+            1 => NcType::Byte,
+            2 => NcType::Char,
+            3 => NcType::Short,
+            4 => NcType::Int,
+            5 => NcType::Float,
+            6 => NcType::Double,
+            other => {
+                return Err(IOError::InvalidFormat(format!(
+                    "Unknown NetCDF type code {other}"
+                )))
+            }
+        })
+    }
+
+    pub fn size(self) -> u64 {
+        match self {
+            NcType::Byte | NcType::Char => 1,
+            NcType::Short => 2,
+            NcType::Int | NcType::Float => 4,
+            NcType::Double => 8,
+        }
+    }
+}
+
+/// Attribute value: text for `NC_CHAR`, numbers otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NcValue {
+    Text(String),
+    Numbers(Vec<f64>),
+}
+
+impl NcValue {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            NcValue::Text(s) => Some(s),
+            NcValue::Numbers(_) => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            NcValue::Numbers(v) => v.first().copied(),
+            NcValue::Text(_) => None,
+        }
+    }
+}
+
+/// A NetCDF dimension; length 0 marks the record (unlimited) dimension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NcDimension {
+    pub name: String,
+    pub len: u64,
+}
+
+/// A NetCDF variable and where its data starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NcVariable {
+    pub name: String,
+    pub dim_ids: Vec<usize>,
+    pub attributes: HashMap<String, NcValue>,
+    pub nc_type: NcType,
+    pub vsize: u64,
+    pub begin: u64,
+}
+
+/// Parsed NetCDF-3 header.
+#[derive(Debug, Clone)]
+pub struct NetcdfHeader {
+    /// 1 for classic, 2 for 64-bit offset
+    pub version: u8,
+    pub num_records: u64,
+    pub dimensions: Vec<NcDimension>,
+    pub attributes: HashMap<String, NcValue>,
+    pub variables: Vec<NcVariable>,
+    /// Bytes between consecutive records of one record variable.
+    pub record_size: u64,
+}
+
+impl NetcdfHeader {
+    /// Parse the header from the start of a NetCDF-3 file.
+    pub fn read<R: Read>(reader: &mut R, file_len: u64) -> IOResult<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic[..3] != b"CDF" {
+            return Err(IOError::InvalidFormat("Not a NetCDF file".to_string()));
+        }
+        let version = magic[3];
+        if version != 1 && version != 2 {
+            return Err(IOError::UnsupportedFormat(format!(
+                "NetCDF format version {version} (only classic and 64-bit offset are supported)"
+            )));
+        }
+
+        let num_recs = reader.read_u32::<BigEndian>()?;
+
+        let mut dimensions = Vec::new();
+        for _ in 0..read_list_len(reader, NC_DIMENSION)? {
+            let name = read_name(reader)?;
+            let len = reader.read_u32::<BigEndian>()? as u64;
+            dimensions.push(NcDimension { name, len });
+        }
+
+        let attributes = read_attributes(reader)?;
+
+        let mut variables = Vec::new();
+        for _ in 0..read_list_len(reader, NC_VARIABLE)? {
+            let name = read_name(reader)?;
+            let ndims = reader.read_u32::<BigEndian>()? as usize;
+            let mut dim_ids = Vec::with_capacity(ndims);
+            for _ in 0..ndims {
+                let id = reader.read_u32::<BigEndian>()? as usize;
+                if id >= dimensions.len() {
+                    return Err(IOError::InvalidFormat(format!(
+                        "NetCDF variable {name} references unknown dimension {id}"
+                    )));
+                }
+                dim_ids.push(id);
+            }
+            let attributes = read_attributes(reader)?;
+            let nc_type = NcType::from_code(reader.read_u32::<BigEndian>()?)?;
+            let vsize = reader.read_u32::<BigEndian>()? as u64;
+            let begin = if version == 1 {
+                reader.read_u32::<BigEndian>()? as u64
+            } else {
+                reader.read_u64::<BigEndian>()?
+            };
+            variables.push(NcVariable {
+                name,
+                dim_ids,
+                attributes,
+                nc_type,
+                vsize,
+                begin,
+            });
+        }
+
+        let mut header = Self {
+            version,
+            num_records: 0,
+            dimensions,
+            attributes,
+            variables,
+            record_size: 0,
+        };
+
+        let record_vars: Vec<&NcVariable> = header
+            .variables
+            .iter()
+            .filter(|v| header.is_record_variable(v))
+            .collect();
+        header.record_size = match record_vars.as_slice() {
+            // A lone record variable is stored without padding
+            [only] => header.record_element_count(only) * only.nc_type.size(),
+            vars => vars.iter().map(|v| v.vsize).sum(),
+        };
+        header.num_records = if num_recs == STREAMING_NUM_RECS {
+            let first = record_vars
+                .iter()
+                .map(|v| v.begin)
+                .min()
+                .unwrap_or(file_len);
+            file_len.saturating_sub(first) / header.record_size.max(1)
+        } else {
+            num_recs as u64
+        };
+
+        Ok(header)
+    }
+
+    pub fn variable(&self, name: &str) -> Option<&NcVariable> {
+        self.variables.iter().find(|v| v.name == name)
+    }
+
+    pub fn dimension_len(&self, name: &str) -> Option<u64> {
+        self.dimensions
+            .iter()
+            .find(|d| d.name == name)
+            .map(|d| d.len)
+    }
+
+    pub fn is_record_variable(&self, variable: &NcVariable) -> bool {
+        variable
+            .dim_ids
+            .first()
+            .is_some_and(|&id| self.dimensions[id].len == 0)
+    }
+
+    /// Values per record (or in total for non-record variables).
+    pub fn record_element_count(&self, variable: &NcVariable) -> u64 {
+        variable
+            .dim_ids
+            .iter()
+            .map(|&id| self.dimensions[id].len)
+            .filter(|&len| len > 0)
+            .product()
+    }
+
+    /// Byte offset of a variable's data for `record`.
+    pub fn data_offset(&self, variable: &NcVariable, record: usize) -> u64 {
+        if self.is_record_variable(variable) {
+            variable.begin + record as u64 * self.record_size
+        } else {
+            variable.begin
+        }
+    }
+}
+
+/// Read a list header: the expected tag and element count, or `ABSENT`.
+fn read_list_len<R: Read>(reader: &mut R, tag: u32) -> IOResult<u32> {
+    let found = reader.read_u32::<BigEndian>()?;
+    let count = reader.read_u32::<BigEndian>()?;
+    match found {
+        0 if count == 0 => Ok(0),
+        t if t == tag => Ok(count),
+        other => Err(IOError::InvalidFormat(format!(
+            "Unexpected NetCDF header tag {other:#x} (expected {tag:#x})"
+        ))),
+    }
+}
+
+/// Skip padding to the next 4-byte boundary after `len` bytes.
+fn skip_padding<R: Read>(reader: &mut R, len: u64) -> IOResult<()> {
+    let pad = (4 - len % 4) % 4;
+    let mut buf = [0u8; 3];
+    reader.read_exact(&mut buf[..pad as usize])?;
+    Ok(())
+}
+
+fn read_name<R: Read>(reader: &mut R) -> IOResult<String> {
+    let len = reader.read_u32::<BigEndian>()? as u64;
+    let mut bytes = vec![0u8; len as usize];
+    reader.read_exact(&mut bytes)?;
+    skip_padding(reader, len)?;
+    String::from_utf8(bytes).map_err(|_| IOError::InvalidFormat("Invalid NetCDF name".into()))
+}
+
+fn read_attributes<R: Read>(reader: &mut R) -> IOResult<HashMap<String, NcValue>> {
+    let mut attributes = HashMap::new();
+    for _ in 0..read_list_len(reader, NC_ATTRIBUTE)? {
+        let name = read_name(reader)?;
+        let nc_type = NcType::from_code(reader.read_u32::<BigEndian>()?)?;
+        let count = reader.read_u32::<BigEndian>()? as usize;
+        let value = if nc_type == NcType::Char {
+            let mut bytes = vec![0u8; count];
+            reader.read_exact(&mut bytes)?;
+            NcValue::Text(
+                String::from_utf8_lossy(&bytes)
+                    .trim_end_matches('\0')
+                    .to_string(),
+            )
+        } else {
+            NcValue::Numbers(read_values(reader, nc_type, count)?)
+        };
+        skip_padding(reader, count as u64 * nc_type.size())?;
+        attributes.insert(name, value);
+    }
+    Ok(attributes)
+}
+
+fn read_values<R: Read>(reader: &mut R, nc_type: NcType, count: usize) -> IOResult<Vec<f64>> {
+    (0..count)
+        .map(|_| {
+            Ok(match nc_type {
+                NcType::Byte | NcType::Char => reader.read_i8()? as f64,
+                NcType::Short => reader.read_i16::<BigEndian>()? as f64,
+                NcType::Int => reader.read_i32::<BigEndian>()? as f64,
+                NcType::Float => reader.read_f32::<BigEndian>()? as f64,
+                NcType::Double => reader.read_f64::<BigEndian>()?,
+            })
+        })
+        .collect()
+}
+
+/// Indexed metadata for an AMBER NetCDF trajectory.
+#[derive(Debug, Clone)]
+pub struct NetcdfIndex {
+    pub num_atoms: usize,
+    pub num_frames: usize,
+    /// Time between the first two frames (fs).
+    pub time_step: f32,
+    pub metadata: TrajectoryMetadata,
+    pub header: NetcdfHeader,
+}
+
+/// Parse the header of an AMBER NetCDF file and check its layout.
+pub fn build_netcdf_index(path: &Path) -> IOResult<NetcdfIndex> {
+    let file = File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+    let header = NetcdfHeader::read(&mut reader, file_len)?;
+
+    let conventions = header
+        .attributes
+        .get("Conventions")
+        .and_then(NcValue::as_text)
+        .unwrap_or("");
+    if !conventions.contains("AMBER") {
+        warn!(
+            "{}: NetCDF Conventions {:?} is not AMBER; reading anyway",
+            path.display(),
+            conventions
+        );
+    }
+
+    let coordinates = header.variable("coordinates").ok_or_else(|| {
+        IOError::InvalidFormat("NetCDF file has no coordinates variable".to_string())
+    })?;
+    let num_atoms = header.dimension_len("atom").unwrap_or(0) as usize;
+    if num_atoms == 0 || header.record_element_count(coordinates) != num_atoms as u64 * 3 {
+        return Err(IOError::InvalidFormat(
+            "NetCDF coordinates must be (frame, atom, spatial=3)".to_string(),
+        ));
+    }
+    let num_frames = if header.is_record_variable(coordinates) {
+        header.num_records as usize
+    } else {
+        1
+    };
+    if num_frames == 0 {
+        return Err(IOError::ParseError {
+            line: 0,
+            message: "No frames found in NetCDF file".into(),
+        });
+    }
+
+    let mut times = Vec::with_capacity(2);
+    if let Some(time) = header.variable("time") {
+        for record in 0..num_frames.min(2) {
+            reader.seek(SeekFrom::Start(header.data_offset(time, record)))?;
+            times.extend(read_values(&mut reader, time.nc_type, 1)?);
+        }
+    }
+    let time_step = match times.as_slice() {
+        [t0, t1] if t1 > t0 => (t1 - t0) as f32 * PS_TO_FS,
+        _ => 1.0,
+    };
+
+    let text = |name: &str| {
+        header
+            .attributes
+            .get(name)
+            .and_then(NcValue::as_text)
+            .map(str::to_string)
+    };
+    let metadata = TrajectoryMetadata {
+        title: text("title").unwrap_or_default(),
+        software: text("program").unwrap_or_else(|| "AMBER".to_string()),
+        ..Default::default()
+    };
+
+    Ok(NetcdfIndex {
+        num_atoms,
+        num_frames,
+        time_step,
+        metadata,
+        header,
+    })
+}
+
+fn read_variable<R: Read + Seek>(
+    reader: &mut R,
+    header: &NetcdfHeader,
+    variable: &NcVariable,
+    record: usize,
+) -> IOResult<Vec<f64>> {
+    reader.seek(SeekFrom::Start(header.data_offset(variable, record)))?;
+    let count = header.record_element_count(variable) as usize;
+    read_values(reader, variable.nc_type, count)
+}
+
+fn read_frame_at<R: Read + Seek>(
+    reader: &mut R,
+    index: &NetcdfIndex,
+    frame_index: usize,
+) -> IOResult<FrameData> {
+    let header = &index.header;
+
+    let time = match header.variable("time") {
+        Some(time) => read_variable(reader, header, time, frame_index)?
+            .first()
+            .map_or(frame_index as f32 * index.time_step, |t| {
+                *t as f32 * PS_TO_FS
+            }),
+        None => frame_index as f32 * index.time_step,
+    };
+    let mut frame = FrameData::new(frame_index, time);
+
+    if let Some(coordinates) = header.variable("coordinates") {
+        let values = read_variable(reader, header, coordinates, frame_index)?;
+        frame.positions = values
+            .chunks_exact(3)
+            .map(|c| Vec3::new(c[0] as f32, c[1] as f32, c[2] as f32))
+            .collect();
+    }
+
+    if let Some(velocities) = header.variable("velocities") {
+        let scale = velocities
+            .attributes
+            .get("scale_factor")
+            .and_then(NcValue::as_number)
+            .unwrap_or(1.0) as f32;
+        let values = read_variable(reader, header, velocities, frame_index)?;
+        frame.velocities = Some(
+            values
+                .chunks_exact(3)
+                .map(|v| Vec3::new(v[0] as f32, v[1] as f32, v[2] as f32) * scale)
+                .collect(),
+        );
+    }
+
+    if let Some(lengths_var) = header.variable("cell_lengths") {
+        let lengths = read_variable(reader, header, lengths_var, frame_index)?;
+        let angles = match header.variable("cell_angles") {
+            Some(angles_var) => read_variable(reader, header, angles_var, frame_index)?,
+            None => vec![90.0; 3],
+        };
+        if let ([a, b, c], [alpha, beta, gamma]) = (lengths.as_slice(), angles.as_slice()) {
+            if *a > 0.0 && *b > 0.0 && *c > 0.0 {
+                frame.cell = Some(UnitCell::from_lengths_angles(
+                    [*a as f32, *b as f32, *c as f32],
+                    [*alpha as f32, *beta as f32, *gamma as f32],
+                ));
+            }
+        }
+    }
+
+    Ok(frame)
+}
+
+/// Registry entry: AMBER NetCDF trajectories, paired with a topology.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::NetCDF,
+    name: "AMBER NetCDF",
+    extensions: &["nc", "ncdf"],
+    capabilities: FormatCapabilities {
+        topology: false,
+        trajectory: true,
+        streaming: true,
+        writable: false,
+    },
+    needs_topology: true,
+    sniff,
+    load: Some(load),
+    read_topology: None,
+};
+
+fn sniff(data: &[u8]) -> Confidence {
+    if NetcdfFrameProvider::is_netcdf_bytes(data) {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    crate::io::topology::load_coordinates(topology_path, |_| {
+        crate::io::streaming::open_netcdf(path)
+    })
+}
+
+/// Random-access AMBER NetCDF frame provider backed by a seekable file handle.
+pub struct NetcdfFrameProvider {
+    reader: Arc<Mutex<BufReader<File>>>,
+    index: NetcdfIndex,
+    file_path: PathBuf,
+}
+
+impl NetcdfFrameProvider {
+    pub fn open(path: &Path) -> IOResult<Self> {
+        let source = crate::io::compression::seekable_path(path)?;
+        let index = build_netcdf_index(&source)?;
+        let file =
+            File::open(&source).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+        Ok(Self {
+            reader: Arc::new(Mutex::new(BufReader::new(file))),
+            index,
+            file_path: path.to_path_buf(),
+        })
+    }
+
+    pub fn index(&self) -> &NetcdfIndex {
+        &self.index
+    }
+
+    pub fn should_stream(num_atoms: usize, num_frames: usize) -> bool {
+        crate::io::streaming::should_stream_trajectory(num_atoms, num_frames)
+    }
+
+    pub fn load_all_frames(&self) -> IOResult<Vec<FrameData>> {
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("NetCDF reader lock poisoned".to_string()))?;
+        (0..self.index.num_frames)
+            .map(|i| read_frame_at(&mut *reader, &self.index, i))
+            .collect()
+    }
+
+    /// Check whether bytes look like a NetCDF-3 file (`CDF\x01` or `CDF\x02`).
+    pub fn is_netcdf_bytes(data: &[u8]) -> bool {
+        data.len() >= 4 && &data[..3] == b"CDF" && matches!(data[3], 1 | 2)
+    }
+}
+
+impl FrameProvider for NetcdfFrameProvider {
+    fn num_frames(&self) -> usize {
+        self.index.num_frames
+    }
+
+    fn num_atoms(&self) -> usize {
+        self.index.num_atoms
+    }
+
+    fn time_step(&self) -> f32 {
+        self.index.time_step
+    }
+
+    fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    fn metadata(&self) -> &TrajectoryMetadata {
+        &self.index.metadata
+    }
+
+    fn get_frame(&self, index: usize) -> IOResult<FrameData> {
+        if index >= self.index.num_frames {
+            return Err(IOError::ParseError {
+                line: 0,
+                message: format!(
+                    "Frame index {index} out of range ({} frames)",
+                    self.index.num_frames
+                ),
+            });
+        }
+
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("NetCDF reader lock poisoned".to_string()))?;
+        read_frame_at(&mut *reader, &self.index, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::WriteBytesExt;
+    use std::io::Write;
+
+    fn put_name(out: &mut Vec<u8>, name: &str) {
+        out.write_u32::<BigEndian>(name.len() as u32).unwrap();
+        out.extend_from_slice(name.as_bytes());
+        out.resize(out.len().div_ceil(4) * 4, 0);
+    }
+
+    fn put_text_attr(out: &mut Vec<u8>, name: &str, value: &str) {
+        put_name(out, name);
+        out.write_u32::<BigEndian>(2).unwrap();
+        put_name(out, value);
+    }
+
+    /// Minimal AMBER trajectory (CDF1): 2 atoms, `frames` records, with box.
+    fn write_amber_netcdf(path: &Path, frames: u32, streaming: bool) {
+        // dims: frame(rec), spatial=3, atom=2, cell_spatial=3, cell_angular=3
+        // record vars: time f32 (4), coordinates f32 (24), cell_lengths f64 (24), cell_angles f64 (24)
+        let mut header = Vec::new();
+        header.extend_from_slice(b"CDF\x01");
+        header
+            .write_u32::<BigEndian>(if streaming { u32::MAX } else { frames })
+            .unwrap();
+        header.write_u32::<BigEndian>(NC_DIMENSION).unwrap();
+        header.write_u32::<BigEndian>(5).unwrap();
+        for (name, len) in [
+            ("frame", 0),
+            ("spatial", 3),
+            ("atom", 2),
+            ("cell_spatial", 3),
+            ("cell_angular", 3),
+        ] {
+            put_name(&mut header, name);
+            header.write_u32::<BigEndian>(len).unwrap();
+        }
+        header.write_u32::<BigEndian>(NC_ATTRIBUTE).unwrap();
+        header.write_u32::<BigEndian>(2).unwrap();
+        put_text_attr(&mut header, "Conventions", "AMBER");
+        put_text_attr(&mut header, "program", "sander");
+
+        let vars: [(&str, Vec<u32>, u32, u32); 4] = [
+            ("time", vec![0], 5, 4),
+            ("coordinates", vec![0, 2, 1], 5, 24),
+            ("cell_lengths", vec![0, 3], 6, 24),
+            ("cell_angles", vec![0, 4], 6, 24),
+        ];
+        // Header size: everything so far plus the variable list
+        let mut var_list = Vec::new();
+        var_list.write_u32::<BigEndian>(NC_VARIABLE).unwrap();
+        var_list.write_u32::<BigEndian>(vars.len() as u32).unwrap();
+        let var_entry_len =
+            |name: &str, ndims: usize| 4 + name.len().div_ceil(4) * 4 + 4 + 4 * ndims + 8 + 12;
+        let header_len = header.len()
+            + 8
+            + vars
+                .iter()
+                .map(|(name, dims, _, _)| var_entry_len(name, dims.len()))
+                .sum::<usize>();
+        let mut begin = header_len as u32;
+        for (name, dims, nc_type, vsize) in &vars {
+            put_name(&mut var_list, name);
+            var_list.write_u32::<BigEndian>(dims.len() as u32).unwrap();
+            for d in dims {
+                var_list.write_u32::<BigEndian>(*d).unwrap();
+            }
+            var_list.write_u32::<BigEndian>(0).unwrap();
+            var_list.write_u32::<BigEndian>(0).unwrap();
+            var_list.write_u32::<BigEndian>(*nc_type).unwrap();
+            var_list.write_u32::<BigEndian>(*vsize).unwrap();
+            var_list.write_u32::<BigEndian>(begin).unwrap();
+            begin += vsize;
+        }
+        header.extend_from_slice(&var_list);
+        assert_eq!(header.len(), header_len);
+
+        for f in 0..frames {
+            header.write_f32::<BigEndian>(f as f32 * 2.0).unwrap();
+            for v in [1.0, 2.0, 3.0, 4.0, 5.0, 6.0] {
+                header.write_f32::<BigEndian>(v + f as f32).unwrap();
+            }
+            for v in [40.0, 41.0, 42.0 + f as f64] {
+                header.write_f64::<BigEndian>(v).unwrap();
+            }
+            for v in [90.0, 90.0, 90.0] {
+                header.write_f64::<BigEndian>(v).unwrap();
+            }
+        }
+        File::create(path).unwrap().write_all(&header).unwrap();
+    }
+
+    #[test]
+    fn test_read_amber_netcdf_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traj.nc");
+        write_amber_netcdf(&path, 3, false);
+
+        let provider = NetcdfFrameProvider::open(&path).unwrap();
+        assert_eq!(provider.num_atoms(), 2);
+        assert_eq!(provider.num_frames(), 3);
+        assert!((provider.time_step() - 2000.0).abs() < 1e-3);
+        assert_eq!(provider.metadata().software, "sander");
+
+        let frame = provider.get_frame(2).unwrap();
+        assert_eq!(frame.position(1), Some(Vec3::new(6.0, 7.0, 8.0)));
+        assert!((frame.time - 4000.0).abs() < 1e-3);
+        assert!((frame.cell.unwrap().lengths[2] - 44.0).abs() < 1e-4);
+        assert!(provider.get_frame(3).is_err());
+        assert_eq!(provider.load_all_frames().unwrap().len(), 3);
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(NetcdfFrameProvider::is_netcdf_bytes(&bytes));
+    }
+
+    #[test]
+    fn test_streaming_record_count_from_file_length() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traj.nc");
+        write_amber_netcdf(&path, 4, true);
+
+        let index = build_netcdf_index(&path).unwrap();
+        assert_eq!(index.num_frames, 4);
+        assert_eq!(index.header.record_size, 76);
+    }
+}
@@ -5,13 +5,92 @@
 
 use crate::core::atom::{AtomData, Element};
 use crate::core::bond::{BondData, BondType};
+use crate::core::cell::UnitCell;
+use crate::core::molecule::SecondaryStructure;
+use crate::core::secondary_structure::{
+    annotate_residue_range, helix_class_structure, SecondaryStructureAnnotations,
+};
+use crate::core::symmetry::{
+    AssemblyGenerator, BiologicalAssembly, StructureSymmetry, SymmetryOperator,
+};
 use crate::core::trajectory::{FrameData, Trajectory, TrajectoryMetadata};
-use crate::io::{IOError, IOResult};
+use crate::io::registry::{
+    sniff_text, Confidence, FormatCapabilities, FormatDescriptor, LoadedFile,
+};
+use crate::io::{FileFormat, IOError, IOResult};
 use bevy::prelude::*;
-use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 
+/// Registry entry: PDB, including `.ent` archive files.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::PDB,
+    name: "PDB",
+    extensions: &["pdb", "ent"],
+    capabilities: FormatCapabilities {
+        topology: true,
+        trajectory: true,
+        streaming: false,
+        writable: true,
+    },
+    needs_topology: false,
+    sniff,
+    load: Some(load),
+    read_topology: Some(PDBParser::parse_topology),
+};
+
+/// Record names that may precede the first coordinate record (PDBQT adds
+/// the torsion tree records).
+const HEADER_RECORDS: &[&str] = &[
+    "HEADER", "OBSLTE", "TITLE", "SPLIT", "CAVEAT", "COMPND", "SOURCE", "KEYWDS", "EXPDTA",
+    "NUMMDL", "MDLTYP", "AUTHOR", "REVDAT", "SPRSDE", "JRNL", "REMARK", "DBREF", "DBREF1",
+    "DBREF2", "SEQADV", "SEQRES", "MODRES", "HET", "HETNAM", "HETSYN", "FORMUL", "HELIX", "SHEET",
+    "SSBOND", "LINK", "CISPEP", "SITE", "CRYST1", "MODEL", "ROOT", "ENDROOT", "BRANCH", "TORSDOF",
+    "USER",
+];
+
+/// Every line up to the first `ATOM`/`HETATM` must be a PDB record; that
+/// record having coordinates in columns 31–54 makes the file likely PDB.
+fn sniff(data: &[u8]) -> Confidence {
+    let Some(text) = sniff_text(data) else {
+        return Confidence::NONE;
+    };
+    let mut header_records = 0;
+    for line in text.lines().take(200) {
+        let record = line.get(..6).unwrap_or(line).trim_end();
+        if record == "ATOM" || record == "HETATM" {
+            let has_coordinates = [(30, 38), (38, 46), (46, 54)].iter().all(|&(start, end)| {
+                line.get(start..end)
+                    .is_some_and(|field| field.trim().parse::<f32>().is_ok())
+            });
+            return if has_coordinates {
+                Confidence::LIKELY
+            } else {
+                Confidence::WEAK
+            };
+        }
+        let is_header = HEADER_RECORDS.contains(&record)
+            || ["ORIGX", "SCALE", "MTRIX"]
+                .iter()
+                .any(|prefix| record.starts_with(prefix));
+        if is_header {
+            header_records += 1;
+        } else if !line.trim().is_empty() {
+            return Confidence::NONE;
+        }
+    }
+    if header_records > 0 {
+        Confidence::POSSIBLE
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, _topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    let (trajectory, atom_data, bond_data) = PDBParser::parse_file_with_atoms(path)?;
+    Ok((trajectory, atom_data, bond_data, None, false))
+}
+
 /// PDB format parser
 pub struct PDBParser;
 
@@ -32,9 +111,7 @@ impl PDBParser {
     pub fn parse_file_buffered(
         path: &Path,
     ) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
-        let file =
-            File::open(path).map_err(|_e| IOError::FileNotFound(path.display().to_string()))?;
-        let reader = BufReader::new(file);
+        let reader = crate::io::compression::open(path)?;
         Self::parse_reader(reader, path.to_path_buf())
     }
 
@@ -75,6 +152,9 @@ impl PDBParser {
         let mut frame_index = 0;
         // Atom metadata is only collected from the first model to avoid duplicates.
         let mut in_first_model = true;
+        // CRYST1 usually precedes the first MODEL and applies to every frame.
+        let mut cell = None;
+        let mut symmetry = SymmetryRemarks::default();
 
         let mut metadata = TrajectoryMetadata::default();
 
@@ -88,10 +168,21 @@ impl PDBParser {
             match record_name {
                 "HEADER" => Self::parse_header(line, &mut metadata),
                 "TITLE" => Self::parse_title(line, &mut metadata),
-                "CRYST1" => Self::parse_cryst1(line, &mut current_frame),
+                "HELIX" => Self::parse_helix(line, &mut metadata.secondary_structure),
+                "SHEET" => Self::parse_sheet(line, &mut metadata.secondary_structure),
+                "REMARK" => symmetry.parse_remark(line),
+                "CRYST1" => {
+                    cell = Self::parse_cryst1(line);
+                    current_frame.cell = cell;
+                    symmetry.space_group = line
+                        .get(55..66)
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string);
+                }
                 "ATOM" | "HETATM" => {
                     if let Some(atom) = Self::parse_atom(line, line_num)? {
-                        current_frame.set_position(atom.id, atom.position);
+                        current_frame.positions.push(atom.position);
                         if in_first_model {
                             atom_data.push(atom);
                         }
@@ -109,6 +200,7 @@ impl PDBParser {
                         frames.push(current_frame);
                     }
                     current_frame = FrameData::new(frame_index, frame_index as f32);
+                    current_frame.cell = cell;
                     frame_index += 1;
                 }
                 "ENDMDL" => {
@@ -116,12 +208,13 @@ impl PDBParser {
                     frames.push(current_frame.clone());
                     in_first_model = false;
                     current_frame = FrameData::new(frame_index, frame_index as f32);
+                    current_frame.cell = cell;
                 }
                 "END" | "TER" => {
                     // End of record/terminator - do nothing
                 }
                 _ => {
-                    // Other record types (REMARK, SEQRES, etc.) can be ignored for now
+                    // Other record types (SEQRES, etc.) can be ignored for now
                 }
             }
         }
@@ -135,13 +228,14 @@ impl PDBParser {
 
         // If no frames were found, create one from the ATOM records
         if frames.is_empty() && !atom_data.is_empty() {
-            let mut frame = FrameData::new(0, 0.0);
-            for atom in atom_data.iter() {
-                frame.set_position(atom.id, atom.position);
-            }
+            let positions = atom_data.iter().map(|atom| atom.position).collect();
+            let mut frame = FrameData::with_positions(0, 0.0, positions);
+            frame.cell = cell;
             frames.push(frame);
         }
 
+        metadata.symmetry = symmetry.finish();
+
         // Create trajectory
         let num_atoms = atom_data.len();
         let mut trajectory = Trajectory::new(file_path, num_atoms, 1.0);
@@ -168,19 +262,64 @@ impl PDBParser {
         }
     }
 
-    /// Parse CRYST1 record (unit cell dimensions)
-    fn parse_cryst1(line: &str, frame: &mut FrameData) {
-        if line.len() >= 54 {
-            let a = line[6..15].trim().parse::<f32>().ok();
-            let b = line[15..24].trim().parse::<f32>().ok();
-            let c = line[24..33].trim().parse::<f32>().ok();
+    /// Parse HELIX record into per-residue annotations
+    fn parse_helix(line: &str, annotations: &mut SecondaryStructureAnnotations) {
+        let Some((chain, first, last)) = Self::parse_ss_range(line, 19, 21..25, 33..37) else {
+            return;
+        };
+        let class = line
+            .get(38..40)
+            .and_then(|c| c.trim().parse::<u32>().ok())
+            .unwrap_or(1);
+        annotate_residue_range(
+            annotations,
+            &chain,
+            first,
+            last,
+            helix_class_structure(class),
+        );
+    }
 
-            if let (Some(a), Some(b), Some(c)) = (a, b, c) {
-                frame.box_size = Some([a, b, c]);
-            }
+    /// Parse SHEET record (one strand) into per-residue annotations
+    fn parse_sheet(line: &str, annotations: &mut SecondaryStructureAnnotations) {
+        if let Some((chain, first, last)) = Self::parse_ss_range(line, 21, 22..26, 33..37) {
+            annotate_residue_range(
+                annotations,
+                &chain,
+                first,
+                last,
+                SecondaryStructure::BetaStrand,
+            );
         }
     }
 
+    /// Chain and residue range of a HELIX/SHEET record
+    fn parse_ss_range(
+        line: &str,
+        chain_col: usize,
+        first: std::ops::Range<usize>,
+        last: std::ops::Range<usize>,
+    ) -> Option<(String, u32, u32)> {
+        let chain = line.get(chain_col..chain_col + 1)?.trim().to_string();
+        let first = line.get(first)?.trim().parse::<u32>().ok()?;
+        let last = line.get(last)?.trim().parse::<u32>().ok()?;
+        Some((chain, first, last))
+    }
+
+    /// Parse CRYST1 record (unit cell lengths and angles)
+    fn parse_cryst1(line: &str) -> Option<UnitCell> {
+        let field = |range: std::ops::Range<usize>| line.get(range)?.trim().parse::<f32>().ok();
+
+        let lengths = [field(6..15)?, field(15..24)?, field(24..33)?];
+        // Angles are optional in some writers; default to a rectangular box.
+        let angles = [
+            field(33..40).unwrap_or(90.0),
+            field(40..47).unwrap_or(90.0),
+            field(47..54).unwrap_or(90.0),
+        ];
+        Some(UnitCell::from_lengths_angles(lengths, angles))
+    }
+
     /// Parse ATOM or HETATM record
     fn parse_atom(line: &str, line_num: usize) -> IOResult<Option<AtomData>> {
         if line.len() < 54 {
@@ -322,6 +461,105 @@ impl PDBParser {
     }
 }
 
+/// Symmetry read from `REMARK 290` (SMTRY), `REMARK 350` (BIOMT) and CRYST1.
+#[derive(Default)]
+struct SymmetryRemarks {
+    space_group: Option<String>,
+    smtry: Vec<SymmetryOperator>,
+    assemblies: Vec<BiologicalAssembly>,
+    rows: [[f32; 4]; 3],
+}
+
+impl SymmetryRemarks {
+    fn parse_remark(&mut self, line: &str) {
+        let Some(text) = line.get(10..) else {
+            return;
+        };
+        match line.get(6..10).map(str::trim) {
+            Some("290") => {
+                if let Some(operator) = self.parse_matrix_row(text, "SMTRY") {
+                    self.smtry.push(operator);
+                }
+            }
+            Some("350") => self.parse_remark_350(text),
+            _ => {}
+        }
+    }
+
+    fn parse_remark_350(&mut self, text: &str) {
+        let text = text.trim();
+        if let Some(id) = text.strip_prefix("BIOMOLECULE:") {
+            self.assemblies.push(BiologicalAssembly {
+                id: id.trim().to_string(),
+                ..Default::default()
+            });
+            return;
+        }
+        let Some(assembly) = self.assemblies.last_mut() else {
+            return;
+        };
+
+        let details = [
+            "AUTHOR DETERMINED BIOLOGICAL UNIT:",
+            "SOFTWARE DETERMINED QUATERNARY STRUCTURE:",
+        ];
+        if let Some(value) = details.iter().find_map(|p| text.strip_prefix(p)) {
+            if assembly.details.is_empty() {
+                assembly.details = value.trim().to_string();
+            }
+        } else if let Some(chains) = text.strip_prefix("APPLY THE FOLLOWING TO CHAINS:") {
+            assembly.generators.push(AssemblyGenerator {
+                chains: Self::chain_list(chains),
+                operators: Vec::new(),
+            });
+        } else if let Some(chains) = text.strip_prefix("AND CHAINS:") {
+            if let Some(generator) = assembly.generators.last_mut() {
+                generator.chains.extend(Self::chain_list(chains));
+            }
+        } else if let Some(operator) = self.parse_matrix_row(text, "BIOMT") {
+            if let Some(generator) = self
+                .assemblies
+                .last_mut()
+                .and_then(|a| a.generators.last_mut())
+            {
+                generator.operators.push(operator);
+            }
+        }
+    }
+
+    /// Collect a `BIOMTn` / `SMTRYn` row; returns the operator after row 3.
+    fn parse_matrix_row(&mut self, text: &str, tag: &str) -> Option<SymmetryOperator> {
+        let mut fields = text.split_whitespace();
+        let row = fields.next()?.strip_prefix(tag)?.parse::<usize>().ok()?;
+        if !(1..=3).contains(&row) {
+            return None;
+        }
+        let values: Vec<f32> = fields.skip(1).filter_map(|f| f.parse().ok()).collect();
+        if values.len() < 4 {
+            return None;
+        }
+        self.rows[row - 1] = [values[0], values[1], values[2], values[3]];
+        (row == 3).then(|| SymmetryOperator::from_rows(self.rows))
+    }
+
+    fn chain_list(text: &str) -> Vec<String> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn finish(mut self) -> StructureSymmetry {
+        self.assemblies.retain(|a| !a.generators.is_empty());
+        StructureSymmetry {
+            space_group: self.space_group,
+            operators: self.smtry,
+            assemblies: self.assemblies,
+        }
+    }
+}
+
 /// Write trajectory to PDB format
 pub struct PDBWriter;
 
@@ -332,34 +570,108 @@ impl PDBWriter {
         frame: &FrameData,
         atom_data: &[AtomData],
     ) -> IOResult<()> {
-        for atom in atom_data {
-            if let Some(pos) = frame.get_position(atom.id) {
-                // Write ATOM or HETATM record
-                let record_type = if Self::is_standard_residue(&atom.residue_name) {
-                    "ATOM  "
-                } else {
-                    "HETATM"
-                };
-
-                writeln!(
-                    writer,
-                    "{:<6}{:>5} {:<4}{:1}{:>3} {:1}{:>4}    {:>8.3}{:>8.3}{:>8.3}{:>6.2}{:>6.2}          {:>2}",
-                    record_type,
-                    atom.id,
-                    atom.name,
-                    "", // altLoc
-                    atom.residue_name,
-                    atom.chain_id,
-                    atom.residue_id,
-                    pos.x, pos.y, pos.z,
-                    atom.occupancy,
-                    atom.b_factor,
-                    atom.element.symbol()
-                )?;
+        Self::write_structure(writer, frame, atom_data, &[])
+    }
+
+    /// Write a single frame with `CONECT` records for `bonds` (ids are serials).
+    pub fn write_structure<W: std::io::Write>(
+        writer: &mut W,
+        frame: &FrameData,
+        atom_data: &[AtomData],
+        bonds: &[BondData],
+    ) -> IOResult<()> {
+        if let Some(cell) = &frame.cell {
+            Self::write_cryst1(writer, cell)?;
+        }
+        Self::write_atoms(writer, frame, atom_data)?;
+        Self::write_conect(writer, bonds)?;
+        writeln!(writer, "END")?;
+        Ok(())
+    }
+
+    /// Write a frame as a `MODEL`/`ENDMDL` block of a multi-model file.
+    ///
+    /// The caller writes `CRYST1` before the first model and `END` after the last.
+    pub fn write_model<W: std::io::Write>(
+        writer: &mut W,
+        model: usize,
+        frame: &FrameData,
+        atom_data: &[AtomData],
+    ) -> IOResult<()> {
+        writeln!(writer, "MODEL     {:>4}", model)?;
+        Self::write_atoms(writer, frame, atom_data)?;
+        writeln!(writer, "ENDMDL")?;
+        Ok(())
+    }
+
+    /// Write a `CRYST1` record (space group P 1).
+    pub fn write_cryst1<W: std::io::Write>(writer: &mut W, cell: &UnitCell) -> IOResult<()> {
+        writeln!(
+            writer,
+            "CRYST1{:>9.3}{:>9.3}{:>9.3}{:>7.2}{:>7.2}{:>7.2} P 1           1",
+            cell.lengths[0],
+            cell.lengths[1],
+            cell.lengths[2],
+            cell.angles[0],
+            cell.angles[1],
+            cell.angles[2]
+        )?;
+        Ok(())
+    }
+
+    /// Write `CONECT` records, each bond listed once from its lower serial.
+    pub fn write_conect<W: std::io::Write>(writer: &mut W, bonds: &[BondData]) -> IOResult<()> {
+        let mut partners: std::collections::BTreeMap<u32, Vec<u32>> = Default::default();
+        for bond in bonds {
+            let (a, b) = if bond.atom_a_id <= bond.atom_b_id {
+                (bond.atom_a_id, bond.atom_b_id)
+            } else {
+                (bond.atom_b_id, bond.atom_a_id)
+            };
+            partners.entry(a).or_default().push(b);
+        }
+        for (atom, bonded) in partners {
+            // At most four partners per record
+            for chunk in bonded.chunks(4) {
+                write!(writer, "CONECT{:>5}", atom)?;
+                for partner in chunk {
+                    write!(writer, "{:>5}", partner)?;
+                }
+                writeln!(writer)?;
             }
         }
+        Ok(())
+    }
 
-        writeln!(writer, "END")?;
+    fn write_atoms<W: std::io::Write>(
+        writer: &mut W,
+        frame: &FrameData,
+        atom_data: &[AtomData],
+    ) -> IOResult<()> {
+        for (atom, pos) in atom_data.iter().zip(&frame.positions) {
+            // Write ATOM or HETATM record
+            let record_type = if Self::is_standard_residue(&atom.residue_name) {
+                "ATOM  "
+            } else {
+                "HETATM"
+            };
+
+            writeln!(
+                writer,
+                "{:<6}{:>5} {:<4}{:1}{:>3} {:1}{:>4}    {:>8.3}{:>8.3}{:>8.3}{:>6.2}{:>6.2}          {:>2}",
+                record_type,
+                atom.id,
+                atom.name,
+                "", // altLoc
+                atom.residue_name,
+                atom.chain_id,
+                atom.residue_id,
+                pos.x, pos.y, pos.z,
+                atom.occupancy,
+                atom.b_factor,
+                atom.element.symbol()
+            )?;
+        }
         Ok(())
     }
 
@@ -425,10 +737,11 @@ END
         assert_eq!(atom_data[3].element, Element::O);
         assert_eq!(atom_data[0].residue_name, "ALA");
 
-        // Frame positions are keyed by serial number and match atom data.
+        // Frame positions follow atom order and match atom data.
         let frame = trajectory.get_frame(0).unwrap();
-        for atom in &atom_data {
-            assert_eq!(frame.get_position(atom.id), Some(atom.position));
+        assert_eq!(frame.cell.unwrap().lengths, [10.0, 10.0, 10.0]);
+        for (i, atom) in atom_data.iter().enumerate() {
+            assert_eq!(frame.position(i), Some(atom.position));
         }
     }
 
@@ -453,14 +766,104 @@ END
         // Atom metadata comes from the first model only (no duplicates).
         assert_eq!(atom_data.len(), 2);
 
-        // Both frames carry positions keyed by serial number.
+        // Both frames carry positions for the same atoms.
         assert_eq!(
-            trajectory.get_frame(0).unwrap().get_position(1),
+            trajectory.get_frame(0).unwrap().position(0),
             Some(Vec3::new(0.0, 0.0, 0.0))
         );
         assert_eq!(
-            trajectory.get_frame(1).unwrap().get_position(1),
+            trajectory.get_frame(1).unwrap().position(0),
             Some(Vec3::new(0.1, 0.0, 0.0))
         );
     }
+
+    #[test]
+    fn test_cryst1_triclinic_cell_applies_to_all_models() {
+        let pdb_content = r#"CRYST1   50.000   60.000   70.000  90.00 100.00 120.00 P 1           1
+MODEL        1
+ATOM      1  N   ALA A   1       0.000   0.000   0.000  1.00 20.00           N  
+ENDMDL
+MODEL        2
+ATOM      1  N   ALA A   1       0.100   0.000   0.000  1.00 20.00           N  
+ENDMDL
+END
+"#;
+
+        let (trajectory, _atoms, _bonds) =
+            PDBParser::parse_string(pdb_content, PathBuf::from("test.pdb")).unwrap();
+        for frame in &trajectory.frames {
+            let cell = frame.cell.expect("CRYST1 cell");
+            assert!(!cell.is_orthorhombic());
+            assert_eq!(cell.angles, [90.0, 100.0, 120.0]);
+            assert!((cell.vectors[1].length() - 60.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_helix_and_sheet_records() {
+        let pdb_content = "\
+HELIX    1  H1 ILE A    7  PRO A   19  13/10 CONFORMATION RES 17,19       13    
+HELIX    2  H2 GLU A   23  THR A   30  5DISTORTED 3/10 AT RES 30           8    
+SHEET    1  S1 2 THR A   1  CYS A   4  0                                        
+ATOM      1  N   THR A   1      17.047  14.099   3.625  1.00 13.79           N  
+";
+        let (trajectory, _atoms, _bonds) =
+            PDBParser::parse_string(pdb_content, PathBuf::from("ss.pdb")).unwrap();
+        let ss = &trajectory.metadata.secondary_structure;
+        let at = |residue: u32| ss.get(&("A".to_string(), residue)).copied();
+        assert_eq!(at(7), Some(SecondaryStructure::AlphaHelix));
+        assert_eq!(at(19), Some(SecondaryStructure::AlphaHelix));
+        assert_eq!(at(20), None);
+        assert_eq!(at(23), Some(SecondaryStructure::ThreeTenHelix));
+        assert_eq!(at(4), Some(SecondaryStructure::BetaStrand));
+        assert_eq!(ss.len(), 13 + 8 + 4);
+    }
+
+    #[test]
+    fn test_symmetry_remarks() {
+        let pdb_content = "\
+REMARK 290   SMTRY1   1  1.000000  0.000000  0.000000        0.00000            
+REMARK 290   SMTRY2   1  0.000000  1.000000  0.000000        0.00000            
+REMARK 290   SMTRY3   1  0.000000  0.000000  1.000000        0.00000            
+REMARK 290   SMTRY1   2 -1.000000  0.000000  0.000000        0.00000            
+REMARK 290   SMTRY2   2  0.000000  1.000000  0.000000        9.32500            
+REMARK 290   SMTRY3   2  0.000000  0.000000 -1.000000        0.00000            
+REMARK 350 BIOMOLECULE: 1                                                       
+REMARK 350 AUTHOR DETERMINED BIOLOGICAL UNIT: TETRAMERIC                        
+REMARK 350 APPLY THE FOLLOWING TO CHAINS: A, B,                                 
+REMARK 350                    AND CHAINS: C                                     
+REMARK 350   BIOMT1   1  1.000000  0.000000  0.000000        0.00000            
+REMARK 350   BIOMT2   1  0.000000  1.000000  0.000000        0.00000            
+REMARK 350   BIOMT3   1  0.000000  0.000000  1.000000        0.00000            
+REMARK 350   BIOMT1   2  0.000000 -1.000000  0.000000       10.00000            
+REMARK 350   BIOMT2   2  1.000000  0.000000  0.000000        0.00000            
+REMARK 350   BIOMT3   2  0.000000  0.000000  1.000000        0.00000            
+REMARK 350 BIOMOLECULE: 2                                                       
+REMARK 350 SOFTWARE DETERMINED QUATERNARY STRUCTURE: MONOMERIC                  
+REMARK 350 APPLY THE FOLLOWING TO CHAINS: A                                     
+REMARK 350   BIOMT1   1  1.000000  0.000000  0.000000        0.00000            
+REMARK 350   BIOMT2   1  0.000000  1.000000  0.000000        0.00000            
+REMARK 350   BIOMT3   1  0.000000  0.000000  1.000000        0.00000            
+CRYST1   40.960   18.650   22.520  90.00  90.77  90.00 P 1 21 1      2          
+ATOM      1  N   THR A   1      17.047  14.099   3.625  1.00 13.79           N  
+";
+        let (trajectory, _atoms, _bonds) =
+            PDBParser::parse_string(pdb_content, PathBuf::from("sym.pdb")).unwrap();
+        let symmetry = &trajectory.metadata.symmetry;
+        assert_eq!(symmetry.space_group.as_deref(), Some("P 1 21 1"));
+        assert_eq!(symmetry.operators.len(), 2);
+        assert_eq!(
+            symmetry.operators[1].translation,
+            Vec3::new(0.0, 9.325, 0.0)
+        );
+
+        assert_eq!(symmetry.assemblies.len(), 2);
+        let tetramer = &symmetry.assemblies[0];
+        assert_eq!(tetramer.details, "TETRAMERIC");
+        assert_eq!(tetramer.generators[0].chains, vec!["A", "B", "C"]);
+        assert_eq!(tetramer.num_copies(), 6);
+        let rotated = tetramer.generators[0].operators[1].apply(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(rotated, Vec3::new(8.0, 1.0, 3.0));
+        assert_eq!(symmetry.assemblies[1].details, "MONOMERIC");
+    }
 }
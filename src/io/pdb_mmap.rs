@@ -3,6 +3,7 @@
 use crate::core::atom::AtomData;
 use crate::core::bond::BondData;
 use crate::core::trajectory::Trajectory;
+use crate::io::compression::Compression;
 use crate::io::pdb::PDBParser;
 use crate::io::{IOError, IOResult};
 use memmap2::Mmap;
@@ -21,10 +22,11 @@ pub fn parse_file_mmap(path: &Path) -> IOResult<(Trajectory, Vec<AtomData>, Vec<
     PDBParser::parse_string(content, path.to_path_buf())
 }
 
-/// Auto-select mmap parsing for large PDB files.
+/// Auto-select mmap parsing for large PDB files; compressed files are
+/// decompressed through the buffered reader.
 pub fn parse_file_optimized(path: &Path) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
     let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
-    if size >= MMAP_THRESHOLD_BYTES {
+    if size >= MMAP_THRESHOLD_BYTES && !Compression::detect(path)?.is_compressed() {
         parse_file_mmap(path)
     } else {
         PDBParser::parse_file_buffered(path)
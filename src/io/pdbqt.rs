@@ -0,0 +1,218 @@
+//! AutoDock PDBQT parser (AutoDock, Vina)
+//!
+//! PDBQT is PDB with two extra fields after the B-factor: the partial
+//! charge in columns 71–76 and the AutoDock atom type in columns 78–79.
+//! The type replaces PDB's element column and is shifted by one, so reading
+//! it as PDB turns `Cl` into C and `Br` into B.
+//!
+//! Coordinates, models and records are read by [`PDBParser`]; the charge and
+//! type of each first-model atom are then applied on top. Charges go to
+//! [`AtomData::charge`] and to the `charge` per-atom property of every frame,
+//! so docked poses can be colored by charge.
+
+use crate::core::atom::{AtomData, Element};
+use crate::core::bond::BondData;
+use crate::core::trajectory::Trajectory;
+use crate::io::pdb::PDBParser;
+use crate::io::registry::{
+    sniff_text, Confidence, FormatCapabilities, FormatDescriptor, LoadedFile,
+};
+use crate::io::{FileFormat, IOError, IOResult};
+use std::path::{Path, PathBuf};
+
+/// Registry entry: AutoDock PDBQT ligands, receptors and docked poses.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::Pdbqt,
+    name: "PDBQT",
+    extensions: &["pdbqt"],
+    capabilities: FormatCapabilities {
+        topology: true,
+        trajectory: true,
+        streaming: false,
+        writable: false,
+    },
+    needs_topology: false,
+    sniff,
+    load: Some(load),
+    read_topology: Some(PdbqtParser::parse_topology),
+};
+
+/// The first atom record has a number in the charge columns, which PDB
+/// leaves blank, and an atom type after it.
+fn sniff(data: &[u8]) -> Confidence {
+    let Some(text) = sniff_text(data) else {
+        return Confidence::NONE;
+    };
+    let Some(line) = text
+        .lines()
+        .take(200)
+        .find(|line| line.starts_with("ATOM") || line.starts_with("HETATM"))
+    else {
+        return Confidence::NONE;
+    };
+    let has_charge = line
+        .get(70..76)
+        .is_some_and(|field| field.trim().parse::<f32>().is_ok());
+    if has_charge && !autodock_type(line).is_empty() {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, _topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    let (trajectory, atom_data, bond_data) = PdbqtParser::parse_file(path)?;
+    Ok((trajectory, atom_data, bond_data, None, false))
+}
+
+/// Element of an AutoDock 4 / Vina atom type. Types that are not plain
+/// element symbols are hydrogen-bonding or aromatic variants, and `G0`–`G9`
+/// / `CG0`–`CG9` are the carbons Meeko splits for macrocycle flexibility.
+fn autodock_element(atom_type: &str) -> Option<Element> {
+    match atom_type {
+        "A" => Some(Element::C),
+        "NA" | "NS" => Some(Element::N),
+        "OA" | "OS" => Some(Element::O),
+        "SA" => Some(Element::S),
+        "HD" | "HS" => Some(Element::H),
+        // Hydrated docking waters
+        "W" => Some(Element::O),
+        t if matches!(
+            t.strip_prefix('C').unwrap_or(t).as_bytes(),
+            [b'G', b'0'..=b'9']
+        ) =>
+        {
+            Some(Element::C)
+        }
+        t => Element::from_symbol(t).ok(),
+    }
+}
+
+/// Columns 78–79 of an atom record.
+fn autodock_type(line: &str) -> &str {
+    line.get(77..).unwrap_or("").trim()
+}
+
+/// PDBQT format parser
+pub struct PdbqtParser;
+
+impl PdbqtParser {
+    /// Parse a PDBQT file; every `MODEL` (docked pose) becomes a frame.
+    pub fn parse_file(path: &Path) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        let content = crate::io::compression::read_to_string(path)?;
+        Self::parse_string(&content, path.to_path_buf())
+    }
+
+    /// Atoms and bonds of a PDBQT file, for use as a topology.
+    pub fn parse_topology(path: &Path) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let (_, atom_data, bond_data) = Self::parse_file(path)?;
+        Ok(crate::io::topology::normalize_topology(
+            atom_data, bond_data,
+        ))
+    }
+
+    /// Parse PDBQT content.
+    pub fn parse_string(
+        content: &str,
+        file_path: PathBuf,
+    ) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        let (mut trajectory, mut atom_data, bond_data) =
+            PDBParser::parse_string(content, file_path)?;
+
+        // The PDB parser keeps the atoms of the first model, one per record
+        let records = content
+            .lines()
+            .enumerate()
+            .take_while(|(_, line)| !line.starts_with("ENDMDL"))
+            .filter(|(_, line)| line.starts_with("ATOM") || line.starts_with("HETATM"));
+        for (atom, (line_num, line)) in atom_data.iter_mut().zip(records) {
+            let charge = line.get(70..76).map_or("", str::trim);
+            if !charge.is_empty() {
+                atom.charge = charge.parse().map_err(|_| IOError::ParseError {
+                    line: line_num + 1,
+                    message: format!("Invalid partial charge: {charge}"),
+                })?;
+            }
+            if let Some(element) = autodock_element(autodock_type(line)) {
+                atom.element = element;
+                atom.mass = element.mass();
+            }
+        }
+
+        let charges: Vec<f32> = atom_data.iter().map(|atom| atom.charge).collect();
+        for frame in &mut trajectory.frames {
+            frame
+                .properties
+                .insert("charge".to_string(), charges.clone());
+        }
+        Ok((trajectory, atom_data, bond_data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POSES: &str = "\
+MODEL 1
+REMARK VINA RESULT:    -6.1      0.000      0.000
+ROOT
+ATOM      1  C1  LIG A   1      -0.795   0.455   0.030  1.00  0.00    +0.044 A
+ATOM      2  CL1 LIG A   1       1.002   1.731   0.030  1.00  0.00    -0.084 Cl
+ATOM      3  O1  LIG A   1       0.300  -1.200   0.100  1.00  0.00    -0.391 OA
+ENDROOT
+BRANCH   1   4
+ATOM      4  H1  LIG A   1       0.900  -1.900   0.100  1.00  0.00    +0.210 HD
+ENDBRANCH   1   4
+TORSDOF 1
+ENDMDL
+MODEL 2
+REMARK VINA RESULT:    -5.8      1.204      2.310
+ROOT
+ATOM      1  C1  LIG A   1      -0.695   0.555   0.030  1.00  0.00    +0.044 A
+ATOM      2  CL1 LIG A   1       1.102   1.831   0.030  1.00  0.00    -0.084 Cl
+ATOM      3  O1  LIG A   1       0.400  -1.100   0.100  1.00  0.00    -0.391 OA
+ENDROOT
+BRANCH   1   4
+ATOM      4  H1  LIG A   1       1.000  -1.800   0.100  1.00  0.00    +0.210 HD
+ENDBRANCH   1   4
+TORSDOF 1
+ENDMDL
+";
+
+    #[test]
+    fn test_parse_pdbqt_types_and_charges() {
+        let (trajectory, atoms, _) =
+            PdbqtParser::parse_string(POSES, PathBuf::from("ligand.pdbqt")).unwrap();
+        assert_eq!(atoms.len(), 4);
+        let elements: Vec<Element> = atoms.iter().map(|a| a.element).collect();
+        assert_eq!(elements, [Element::C, Element::Cl, Element::O, Element::H]);
+        assert!((atoms[1].charge + 0.084).abs() < 1e-6);
+        assert!((atoms[1].mass - Element::Cl.mass()).abs() < 1e-6);
+
+        assert_eq!(trajectory.num_frames(), 2);
+        for frame in &trajectory.frames {
+            assert!((frame.properties["charge"][2] + 0.391).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_autodock_element() {
+        assert_eq!(autodock_element("Br"), Some(Element::Br));
+        assert_eq!(autodock_element("BR"), Some(Element::Br));
+        assert_eq!(autodock_element("NA"), Some(Element::N));
+        assert_eq!(autodock_element("SA"), Some(Element::S));
+        assert_eq!(autodock_element("Zn"), Some(Element::Zn));
+        assert_eq!(autodock_element("G0"), Some(Element::C));
+        assert_eq!(autodock_element("CG1"), Some(Element::C));
+        assert_eq!(autodock_element(""), None);
+    }
+
+    #[test]
+    fn test_sniff_pdbqt_not_pdb() {
+        assert_eq!(sniff(POSES.as_bytes()), Confidence::CERTAIN);
+        let pdb =
+            "ATOM      1  N   ALA A   1       0.000   0.000   0.000  1.00  0.00           N\n";
+        assert_eq!(sniff(pdb.as_bytes()), Confidence::NONE);
+    }
+}
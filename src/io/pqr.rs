@@ -0,0 +1,221 @@
+//! PQR structure parser (PDB2PQR, APBS)
+//!
+//! PQR files are PDB-like `ATOM`/`HETATM` records whose occupancy and
+//! B-factor columns hold the partial charge and atomic radius. Writers do
+//! not keep PDB's fixed columns, so fields are split on whitespace:
+//!
+//! `ATOM serial name resName [chainID] resSeq x y z charge radius`
+//!
+//! Charges go to [`AtomData::charge`]; charges and radii are also kept as
+//! the `charge` and `radius` per-atom properties for property coloring.
+//! Only the first model is read, and PQR carries no bonds.
+
+use crate::core::atom::{AtomData, Element};
+use crate::core::bond::BondData;
+use crate::core::trajectory::{FrameData, Trajectory};
+use crate::io::registry::{
+    sniff_text, Confidence, FormatCapabilities, FormatDescriptor, LoadedFile,
+};
+use crate::io::{FileFormat, IOError, IOResult};
+use bevy::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Registry entry: PQR structures with charges and radii.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::Pqr,
+    name: "PQR",
+    extensions: &["pqr"],
+    capabilities: FormatCapabilities {
+        topology: true,
+        trajectory: false,
+        streaming: false,
+        writable: false,
+    },
+    needs_topology: false,
+    sniff,
+    load: Some(load),
+    read_topology: Some(PqrParser::parse_topology),
+};
+
+/// The first atom record ends in two decimals with at least three digits
+/// after the point. PDB occupancy and B-factor use two, and PDB lines
+/// usually end with an element symbol.
+fn sniff(data: &[u8]) -> Confidence {
+    let Some(text) = sniff_text(data) else {
+        return Confidence::NONE;
+    };
+    let Some(line) = text
+        .lines()
+        .take(200)
+        .find(|line| line.starts_with("ATOM") || line.starts_with("HETATM"))
+    else {
+        return Confidence::NONE;
+    };
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let is_precise = |field: &str| {
+        field.parse::<f32>().is_ok()
+            && field
+                .split_once('.')
+                .is_some_and(|(_, decimals)| decimals.len() >= 3)
+    };
+    if (10..=11).contains(&fields.len()) && fields[fields.len() - 2..].iter().all(|f| is_precise(f))
+    {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, _topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    let (trajectory, atom_data) = PqrParser::parse_file(path)?;
+    Ok((trajectory, atom_data, Vec::new(), None, false))
+}
+
+/// PQR format parser
+pub struct PqrParser;
+
+impl PqrParser {
+    /// Parse a PQR file into a single-frame trajectory and its atoms.
+    pub fn parse_file(path: &Path) -> IOResult<(Trajectory, Vec<AtomData>)> {
+        let content = crate::io::compression::read_to_string(path)?;
+        Self::parse_string(&content, path.to_path_buf())
+    }
+
+    /// Atoms of a PQR file, for use as a topology.
+    pub fn parse_topology(path: &Path) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let (_, atoms) = Self::parse_file(path)?;
+        Ok((atoms, Vec::new()))
+    }
+
+    /// Parse PQR content.
+    pub fn parse_string(
+        content: &str,
+        file_path: PathBuf,
+    ) -> IOResult<(Trajectory, Vec<AtomData>)> {
+        let mut atoms = Vec::new();
+        let mut frame = FrameData::new(0, 0.0);
+        let mut charges = Vec::new();
+        let mut radii = Vec::new();
+
+        for (line_num, line) in content.lines().enumerate() {
+            let record = line.split_whitespace().next().unwrap_or("");
+            match record {
+                "ATOM" | "HETATM" => {
+                    let (atom, radius) = Self::parse_atom(line, line_num + 1)?;
+                    frame.positions.push(atom.position);
+                    charges.push(atom.charge);
+                    radii.push(radius);
+                    atoms.push(atom);
+                }
+                "ENDMDL" | "END" if !atoms.is_empty() => break,
+                _ => {}
+            }
+        }
+
+        if atoms.is_empty() {
+            return Err(IOError::InvalidFormat(
+                "PQR file has no ATOM or HETATM records".to_string(),
+            ));
+        }
+        frame.properties.insert("charge".to_string(), charges);
+        frame.properties.insert("radius".to_string(), radii);
+
+        let mut trajectory = Trajectory::new(file_path, atoms.len(), 1.0);
+        trajectory.add_frame(frame);
+        info!("PQR: {} atoms", atoms.len());
+        Ok((trajectory, atoms))
+    }
+
+    /// One atom record and its radius.
+    fn parse_atom(line: &str, line_num: usize) -> IOResult<(AtomData, f32)> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // The chain identifier is optional
+        let (chain_id, rest) = match fields.len() {
+            10 => ("A", &fields[4..]),
+            11 => (fields[4], &fields[5..]),
+            n => {
+                return Err(IOError::ParseError {
+                    line: line_num,
+                    message: format!("Expected 10 or 11 PQR fields, found {n}"),
+                })
+            }
+        };
+        let number = |field: &str, what: &str| {
+            field.parse::<f32>().map_err(|_| IOError::ParseError {
+                line: line_num,
+                message: format!("Invalid {what}: {field}"),
+            })
+        };
+        let serial = fields[1].parse::<u32>().map_err(|_| IOError::ParseError {
+            line: line_num,
+            message: format!("Invalid atom serial: {}", fields[1]),
+        })?;
+        let residue_id = rest[0]
+            .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+            .parse::<i64>()
+            .map_err(|_| IOError::ParseError {
+                line: line_num,
+                message: format!("Invalid residue number: {}", rest[0]),
+            })?;
+        let position = Vec3::new(
+            number(rest[1], "x coordinate")?,
+            number(rest[2], "y coordinate")?,
+            number(rest[3], "z coordinate")?,
+        );
+        let charge = number(rest[4], "charge")?;
+        let radius = number(rest[5], "radius")?;
+
+        let name = fields[2].to_string();
+        let element = Element::from_atom_name(&name);
+        let mut atom = AtomData::new(
+            serial,
+            element,
+            residue_id.max(0) as u32,
+            fields[3].to_string(),
+            chain_id.to_string(),
+            name,
+        );
+        atom.position = position;
+        atom.charge = charge;
+        Ok((atom, radius))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALA: &str = "\
+REMARK   1 PQR file generated by PDB2PQR
+ATOM      1  N   ALA     1      -0.677  -1.230  -0.491 -0.3000 1.8240
+ATOM      2  CA  ALA     1      -0.001   0.064  -0.491  0.2100 1.9080
+ATOM      3  HA  ALA A   1      -0.233   0.571  -1.422  0.0480 1.3870
+END
+";
+
+    #[test]
+    fn test_parse_pqr_with_and_without_chain() {
+        let (trajectory, atoms) = PqrParser::parse_string(ALA, PathBuf::from("ala.pqr")).unwrap();
+        assert_eq!(atoms.len(), 3);
+        assert_eq!(atoms[0].element, Element::N);
+        assert_eq!(atoms[1].element, Element::C);
+        assert_eq!(atoms[2].element, Element::H);
+        assert_eq!(atoms[2].chain_id, "A");
+        assert_eq!(atoms[0].residue_name, "ALA");
+        assert!((atoms[1].charge - 0.21).abs() < 1e-6);
+
+        let frame = trajectory.get_frame(0).unwrap();
+        assert_eq!(frame.position(1), Some(Vec3::new(-0.001, 0.064, -0.491)));
+        assert!((frame.properties["radius"][2] - 1.387).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_pqr_sniffing() {
+        assert_eq!(sniff(ALA.as_bytes()), Confidence::CERTAIN);
+        let pdb =
+            "ATOM      1  N   ALA A   1      11.104   6.134  -6.504  1.00  0.00           N\n";
+        assert_eq!(sniff(pdb.as_bytes()), Confidence::NONE);
+        let pdb_no_element = "ATOM      1  N   ALA A   1      11.104   6.134  -6.504  1.00  0.00\n";
+        assert_eq!(sniff(pdb_no_element.as_bytes()), Confidence::NONE);
+    }
+}
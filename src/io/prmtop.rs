@@ -0,0 +1,333 @@
+//! AMBER prmtop (parm7) topology parser
+//!
+//! A prmtop is a sequence of `%FLAG NAME` sections, each followed by a
+//! Fortran `%FORMAT(...)` line and fixed-width records. Only the sections
+//! needed for visualization are read: `POINTERS`, `ATOM_NAME`, `CHARGE`,
+//! `MASS`, `ATOMIC_NUMBER`, `RESIDUE_LABEL`, `RESIDUE_POINTER`, the two bond
+//! lists and, when present, `RESIDUE_CHAINID`.
+//!
+//! Charges are stored in prmtop units (electron charge × 18.2223) and are
+//! converted back to elementary charges.
+
+use crate::core::atom::{AtomData, Element};
+use crate::core::bond::{BondData, BondLengths, BondOrder, BondType};
+use crate::io::registry::{sniff_text, Confidence, FormatCapabilities, FormatDescriptor};
+use crate::io::{FileFormat, IOError, IOResult};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// AMBER's internal charge scale (√(332.0522173) kcal·Å/mol per e).
+pub const AMBER_CHARGE_SCALE: f32 = 18.2223;
+
+/// Raw fixed-width records of every `%FLAG` section, keyed by flag name.
+#[derive(Debug, Default)]
+struct PrmtopSections {
+    sections: HashMap<String, Vec<String>>,
+}
+
+impl PrmtopSections {
+    fn parse(content: &str) -> IOResult<Self> {
+        let mut sections = HashMap::new();
+        let mut current: Option<(String, usize)> = None;
+        let mut values: Vec<String> = Vec::new();
+
+        for (line_num, line) in content.lines().enumerate() {
+            if let Some(flag) = line.strip_prefix("%FLAG") {
+                if let Some((name, _)) = current.take() {
+                    sections.insert(name, std::mem::take(&mut values));
+                }
+                current = Some((flag.trim().to_string(), 0));
+            } else if let Some(format) = line.strip_prefix("%FORMAT") {
+                let Some((_, width)) = current.as_mut() else {
+                    continue;
+                };
+                *width = Self::field_width(format).ok_or_else(|| IOError::ParseError {
+                    line: line_num + 1,
+                    message: format!("Unsupported prmtop format: {}", line.trim()),
+                })?;
+            } else if line.starts_with('%') {
+                // %VERSION and %COMMENT lines
+            } else if let Some((name, width)) = &current {
+                if *width == 0 {
+                    return Err(IOError::ParseError {
+                        line: line_num + 1,
+                        message: format!("Section {name} has no %FORMAT line"),
+                    });
+                }
+                let line = line.trim_end();
+                let mut start = 0;
+                while start < line.len() {
+                    let end = (start + width).min(line.len());
+                    let field = line.get(start..end).unwrap_or("").trim();
+                    if !field.is_empty() {
+                        values.push(field.to_string());
+                    }
+                    start = end;
+                }
+            }
+        }
+        if let Some((name, _)) = current {
+            sections.insert(name, values);
+        }
+
+        Ok(Self { sections })
+    }
+
+    /// Field width of a Fortran format such as `(20a4)`, `(10I8)` or `(5E16.8)`.
+    fn field_width(format: &str) -> Option<usize> {
+        let spec = format.trim().trim_start_matches('(').trim_end_matches(')');
+        let kind = spec.find(|c: char| c.is_ascii_alphabetic())?;
+        let width: String = spec[kind + 1..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        width.parse().ok().filter(|w| *w > 0)
+    }
+
+    fn strings(&self, flag: &str) -> Option<&[String]> {
+        self.sections.get(flag).map(Vec::as_slice)
+    }
+
+    fn required(&self, flag: &str) -> IOResult<&[String]> {
+        self.strings(flag)
+            .ok_or_else(|| IOError::InvalidFormat(format!("prmtop has no %FLAG {flag} section")))
+    }
+
+    fn numbers<T: std::str::FromStr>(&self, flag: &str) -> IOResult<Option<Vec<T>>> {
+        let Some(values) = self.strings(flag) else {
+            return Ok(None);
+        };
+        values
+            .iter()
+            .map(|v| {
+                v.parse::<T>().map_err(|_| IOError::ParseError {
+                    line: 0,
+                    message: format!("Invalid value in %FLAG {flag}: {v}"),
+                })
+            })
+            .collect::<IOResult<Vec<T>>>()
+            .map(Some)
+    }
+
+    fn required_numbers<T: std::str::FromStr>(&self, flag: &str) -> IOResult<Vec<T>> {
+        self.numbers(flag)?
+            .ok_or_else(|| IOError::InvalidFormat(format!("prmtop has no %FLAG {flag} section")))
+    }
+}
+
+/// Registry entry: AMBER topologies (topology only).
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::Prmtop,
+    name: "AMBER prmtop",
+    extensions: &["prmtop", "parm7"],
+    capabilities: FormatCapabilities {
+        topology: true,
+        trajectory: false,
+        streaming: false,
+        writable: false,
+    },
+    needs_topology: false,
+    sniff,
+    load: None,
+    read_topology: Some(PrmtopParser::parse_topology),
+};
+
+fn sniff(data: &[u8]) -> Confidence {
+    if sniff_text(data).is_some_and(PrmtopParser::is_prmtop_content) {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+/// AMBER prmtop parser
+pub struct PrmtopParser;
+
+impl PrmtopParser {
+    /// Parse a prmtop file into atoms and bonds.
+    pub fn parse_topology(path: &Path) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let content = crate::io::compression::read_to_string(path)?;
+        Self::parse_string(&content)
+    }
+
+    /// Parse prmtop content into atoms and bonds.
+    pub fn parse_string(content: &str) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let sections = PrmtopSections::parse(content)?;
+
+        let pointers: Vec<usize> = sections.required_numbers("POINTERS")?;
+        // NATOM is the first pointer and NRES the twelfth
+        if pointers.len() < 12 {
+            return Err(IOError::InvalidFormat(
+                "prmtop POINTERS section is truncated".to_string(),
+            ));
+        }
+        let (num_atoms, num_residues) = (pointers[0], pointers[11]);
+
+        let names = sections.required("ATOM_NAME")?;
+        let charges: Vec<f32> = sections.required_numbers("CHARGE")?;
+        let masses: Vec<f32> = sections.required_numbers("MASS")?;
+        let atomic_numbers: Option<Vec<i32>> = sections.numbers("ATOMIC_NUMBER")?;
+        let residue_labels = sections.required("RESIDUE_LABEL")?;
+        let residue_pointers: Vec<usize> = sections.required_numbers("RESIDUE_POINTER")?;
+        let chain_ids = sections.strings("RESIDUE_CHAINID");
+
+        for (flag, len) in [
+            ("ATOM_NAME", names.len()),
+            ("CHARGE", charges.len()),
+            ("MASS", masses.len()),
+        ] {
+            if len < num_atoms {
+                return Err(IOError::InvalidFormat(format!(
+                    "prmtop %FLAG {flag} has {len} entries, expected {num_atoms}"
+                )));
+            }
+        }
+        if residue_labels.len() < num_residues || residue_pointers.len() < num_residues {
+            return Err(IOError::InvalidFormat(format!(
+                "prmtop residue sections are shorter than NRES ({num_residues})"
+            )));
+        }
+
+        let mut atoms = Vec::with_capacity(num_atoms);
+        for r in 0..num_residues {
+            let first = residue_pointers[r].saturating_sub(1);
+            let end = residue_pointers
+                .get(r + 1)
+                .filter(|_| r + 1 < num_residues)
+                .map_or(num_atoms, |next| next.saturating_sub(1))
+                .min(num_atoms);
+            let chain = chain_ids
+                .and_then(|ids| ids.get(r))
+                .cloned()
+                .unwrap_or_else(|| "A".to_string());
+
+            for i in first..end {
+                let element = atomic_numbers
+                    .as_ref()
+                    .and_then(|z| z.get(i))
+                    .filter(|z| **z > 0)
+                    .map(|z| Element::from_atomic_number(*z as u32))
+                    .unwrap_or_else(|| Element::from_name_and_mass(&names[i], masses[i]));
+                let mut atom = AtomData::new(
+                    i as u32,
+                    element,
+                    r as u32 + 1,
+                    residue_labels[r].clone(),
+                    chain.clone(),
+                    names[i].clone(),
+                );
+                atom.charge = charges[i] / AMBER_CHARGE_SCALE;
+                atom.mass = masses[i];
+                atoms.push(atom);
+            }
+        }
+
+        if atoms.len() != num_atoms {
+            return Err(IOError::InvalidFormat(format!(
+                "prmtop residues cover {} atoms, expected {num_atoms}",
+                atoms.len()
+            )));
+        }
+
+        let mut bonds = Vec::new();
+        for flag in ["BONDS_INC_HYDROGEN", "BONDS_WITHOUT_HYDROGEN"] {
+            let Some(entries) = sections.numbers::<usize>(flag)? else {
+                continue;
+            };
+            // (3 × index_a, 3 × index_b, bond type) triples
+            for triple in entries.chunks_exact(3) {
+                let (a, b) = (triple[0] / 3, triple[1] / 3);
+                if a >= num_atoms || b >= num_atoms {
+                    return Err(IOError::InvalidFormat(format!(
+                        "prmtop %FLAG {flag} references atom beyond NATOM ({num_atoms})"
+                    )));
+                }
+                bonds.push(BondData::new(
+                    a as u32,
+                    b as u32,
+                    BondType::Covalent,
+                    BondOrder::Single,
+                    BondLengths::get_length(atoms[a].element, atoms[b].element),
+                ));
+            }
+        }
+
+        Ok((atoms, bonds))
+    }
+
+    /// Check whether content looks like a prmtop (`%VERSION` or `%FLAG` header).
+    pub fn is_prmtop_content(content: &str) -> bool {
+        let first = content.lines().next().unwrap_or("");
+        first.starts_with("%VERSION") || first.starts_with("%FLAG")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Water plus a sodium ion, as written by tleap.
+    const WATER_NA_PRMTOP: &str = "\
+%VERSION  VERSION_STAMP = V0001.000  DATE = 01/01/24  12:00:00
+%FLAG TITLE
+%FORMAT(20a4)
+default_name
+%FLAG POINTERS
+%FORMAT(10I8)
+       4       3       2       0       0       0       0       0       0       0
+       5       2       0       0       0       1       0       0       1       0
+       0       0       0       0       0       0       0       0       3       0
+       0       0
+%FLAG ATOM_NAME
+%FORMAT(20a4)
+O   H1  H2  Na+
+%FLAG CHARGE
+%FORMAT(5E16.8)
+ -1.51973982E+01  7.59869910E+00  7.59869910E+00  1.82223000E+01
+%FLAG ATOMIC_NUMBER
+%FORMAT(10I8)
+       8       1       1      11
+%FLAG MASS
+%FORMAT(5E16.8)
+  1.60000000E+01  1.00800000E+00  1.00800000E+00  2.29900000E+01
+%FLAG RESIDUE_LABEL
+%FORMAT(20a4)
+WAT Na+
+%FLAG RESIDUE_POINTER
+%FORMAT(10I8)
+       1       4
+%FLAG BONDS_INC_HYDROGEN
+%FORMAT(10I8)
+       0       3       1       0       6       1
+%FLAG BONDS_WITHOUT_HYDROGEN
+%FORMAT(10I8)
+
+";
+
+    #[test]
+    fn test_parse_prmtop_atoms_residues_and_bonds() {
+        let (atoms, bonds) = PrmtopParser::parse_string(WATER_NA_PRMTOP).unwrap();
+        assert_eq!(atoms.len(), 4);
+        assert_eq!(atoms[0].name, "O");
+        assert_eq!(atoms[0].element, Element::O);
+        assert_eq!(atoms[0].residue_name, "WAT");
+        assert!((atoms[0].charge + 0.834).abs() < 1e-4);
+        assert_eq!(atoms[3].element, Element::Na);
+        assert_eq!(atoms[3].residue_id, 2);
+        assert_eq!(atoms[3].residue_name, "Na+");
+        assert!((atoms[3].charge - 1.0).abs() < 1e-4);
+
+        assert_eq!(bonds.len(), 2);
+        assert_eq!((bonds[0].atom_a_id, bonds[0].atom_b_id), (0, 1));
+        assert_eq!((bonds[1].atom_a_id, bonds[1].atom_b_id), (0, 2));
+    }
+
+    #[test]
+    fn test_field_width_and_missing_sections() {
+        assert_eq!(PrmtopSections::field_width("(20a4)"), Some(4));
+        assert_eq!(PrmtopSections::field_width("(5E16.8)"), Some(16));
+        assert_eq!(PrmtopSections::field_width("(1a80)"), Some(80));
+        assert!(PrmtopParser::parse_string("%VERSION\n%FLAG TITLE\n%FORMAT(20a4)\nx\n").is_err());
+        assert!(PrmtopParser::is_prmtop_content(WATER_NA_PRMTOP));
+    }
+}
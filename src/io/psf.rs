@@ -0,0 +1,370 @@
+//! CHARMM/X-PLOR PSF topology parser
+//!
+//! A PSF (protein structure file) carries the atoms and connectivity that
+//! NAMD/CHARMM DCD trajectories lack: segment names, atom types, partial
+//! charges, masses and the explicit bond list. Only the `!NATOM` and
+//! `!NBOND` sections are read; angles, dihedrals and the rest are skipped.
+//!
+//! Three variants are handled:
+//! - standard: `I8 1X A4 1X A4 1X A4 1X A4 1X A4 1X 2G14.6 I8`
+//! - `EXT`: wider fields, `I10 1X A8 1X A8 1X A8 1X A8 1X A6 1X 2G14.6 I8`
+//! - `CHEQ`: two extra fluctuating-charge columns after the move flag
+//!
+//! X-PLOR files use atom type names instead of CHARMM's integer types;
+//! both are accepted. Segment names become [`AtomData::chain_id`].
+
+use crate::core::atom::{AtomData, Element};
+use crate::core::bond::{BondData, BondLengths, BondOrder, BondType};
+use crate::io::registry::{sniff_text, Confidence, FormatCapabilities, FormatDescriptor};
+use crate::io::{FileFormat, IOError, IOResult};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Atom line layout, chosen from the `PSF` header flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PsfLayout {
+    Standard,
+    Extended,
+}
+
+impl PsfLayout {
+    /// Fixed-width column ranges: id, segid, resid, resname, name, type, charge, mass.
+    fn columns(self) -> [(usize, usize); 8] {
+        match self {
+            PsfLayout::Standard => [
+                (0, 8),
+                (9, 13),
+                (14, 18),
+                (19, 23),
+                (24, 28),
+                (29, 33),
+                (34, 48),
+                (48, 62),
+            ],
+            PsfLayout::Extended => [
+                (0, 10),
+                (11, 19),
+                (20, 28),
+                (29, 37),
+                (38, 46),
+                (47, 53),
+                (54, 68),
+                (68, 82),
+            ],
+        }
+    }
+}
+
+/// One atom record from the `!NATOM` section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PsfAtom {
+    pub serial: u32,
+    pub segment: String,
+    pub residue_id: u32,
+    pub residue_name: String,
+    pub atom_name: String,
+    pub atom_type: String,
+    pub charge: f32,
+    pub mass: f32,
+}
+
+/// Registry entry: CHARMM/X-PLOR PSF topologies (topology only).
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::PSF,
+    name: "PSF",
+    extensions: &["psf"],
+    capabilities: FormatCapabilities {
+        topology: true,
+        trajectory: false,
+        streaming: false,
+        writable: false,
+    },
+    needs_topology: false,
+    sniff,
+    load: None,
+    read_topology: Some(PsfParser::parse_topology),
+};
+
+/// The first line of every PSF starts with the `PSF` keyword and its flags.
+fn sniff(data: &[u8]) -> Confidence {
+    let first_word = sniff_text(data)
+        .and_then(|text| text.lines().next())
+        .and_then(|line| line.split_whitespace().next());
+    if first_word == Some("PSF") {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+/// PSF topology parser
+pub struct PsfParser;
+
+impl PsfParser {
+    /// Parse a PSF file into atoms (renumbered 0..N-1) and bonds.
+    pub fn parse_topology(path: &Path) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let content = crate::io::compression::read_to_string(path)?;
+        Self::parse_string(&content)
+    }
+
+    /// Parse PSF content into atoms (renumbered 0..N-1) and bonds.
+    pub fn parse_string(content: &str) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let lines: Vec<&str> = content.lines().collect();
+        let header = lines.first().map(|l| l.trim()).unwrap_or("");
+        if !header.starts_with("PSF") {
+            return Err(IOError::InvalidFormat(format!(
+                "Missing PSF header, got: {header}"
+            )));
+        }
+        let layout = if header.split_whitespace().any(|flag| flag == "EXT") {
+            PsfLayout::Extended
+        } else {
+            PsfLayout::Standard
+        };
+
+        let (natom_line, natom) = Self::find_section(&lines, "!NATOM")?
+            .ok_or_else(|| IOError::InvalidFormat("PSF file has no !NATOM section".to_string()))?;
+
+        let mut psf_atoms = Vec::with_capacity(natom);
+        for i in 0..natom {
+            let line_num = natom_line + 1 + i;
+            let line = lines.get(line_num).ok_or_else(|| IOError::ParseError {
+                line: line_num + 1,
+                message: format!("Expected {natom} atom lines, found {i}"),
+            })?;
+            psf_atoms.push(Self::parse_atom_line(line, line_num + 1, layout)?);
+        }
+
+        let serial_to_index: HashMap<u32, u32> = psf_atoms
+            .iter()
+            .enumerate()
+            .map(|(i, atom)| (atom.serial, i as u32))
+            .collect();
+
+        let atoms: Vec<AtomData> = psf_atoms
+            .into_iter()
+            .enumerate()
+            .map(|(i, atom)| {
+                let element = Element::from_name_and_mass(&atom.atom_name, atom.mass);
+                let mut data = AtomData::new(
+                    i as u32,
+                    element,
+                    atom.residue_id,
+                    atom.residue_name,
+                    atom.segment,
+                    atom.atom_name,
+                );
+                data.charge = atom.charge;
+                data.mass = atom.mass;
+                data
+            })
+            .collect();
+
+        let mut bonds = Vec::new();
+        if let Some((nbond_line, nbond)) = Self::find_section(&lines, "!NBOND")? {
+            let ids = Self::read_section_ints(&lines, nbond_line + 1, nbond * 2)?;
+            for (pair_i, pair) in ids.chunks_exact(2).enumerate() {
+                let (Some(&a), Some(&b)) =
+                    (serial_to_index.get(&pair[0]), serial_to_index.get(&pair[1]))
+                else {
+                    return Err(IOError::ParseError {
+                        line: nbond_line + 1,
+                        message: format!(
+                            "Bond {} references unknown atom ({}, {})",
+                            pair_i + 1,
+                            pair[0],
+                            pair[1]
+                        ),
+                    });
+                };
+                bonds.push(BondData::new(
+                    a,
+                    b,
+                    BondType::Covalent,
+                    BondOrder::Single,
+                    BondLengths::get_length(atoms[a as usize].element, atoms[b as usize].element),
+                ));
+            }
+        }
+
+        Ok((atoms, bonds))
+    }
+
+    /// Locate a section header such as `    1234 !NATOM` and return its line
+    /// index and count.
+    fn find_section(lines: &[&str], tag: &str) -> IOResult<Option<(usize, usize)>> {
+        let Some(line_num) = lines.iter().position(|line| {
+            line.split_whitespace()
+                .any(|token| token.trim_end_matches(':') == tag)
+        }) else {
+            return Ok(None);
+        };
+        let count = lines[line_num]
+            .split_whitespace()
+            .next()
+            .and_then(|token| token.parse::<usize>().ok())
+            .ok_or_else(|| IOError::ParseError {
+                line: line_num + 1,
+                message: format!("Expected count before {tag}, got: {}", lines[line_num]),
+            })?;
+        Ok(Some((line_num, count)))
+    }
+
+    /// Read `count` whitespace-separated integers following a section header.
+    fn read_section_ints(lines: &[&str], start: usize, count: usize) -> IOResult<Vec<u32>> {
+        let mut values = Vec::with_capacity(count);
+        let mut line_num = start;
+        while values.len() < count {
+            let line = lines.get(line_num).ok_or_else(|| IOError::ParseError {
+                line: line_num + 1,
+                message: format!("Expected {count} values, found {}", values.len()),
+            })?;
+            for token in line.split_whitespace() {
+                values.push(token.parse::<u32>().map_err(|_| IOError::ParseError {
+                    line: line_num + 1,
+                    message: format!("Invalid atom index: {token}"),
+                })?);
+            }
+            line_num += 1;
+        }
+        values.truncate(count);
+        Ok(values)
+    }
+
+    /// Parse one `!NATOM` record.
+    ///
+    /// Whitespace-separated fields are used when all eight are present; files
+    /// with blank segment names fall back to the fixed columns of `layout`.
+    fn parse_atom_line(line: &str, line_num: usize, layout: PsfLayout) -> IOResult<PsfAtom> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let numeric = |s: &str| s.parse::<f32>().is_ok();
+        let residue_like = |s: &str| s.starts_with(|c: char| c.is_ascii_digit() || c == '-');
+        let fields: Vec<String> = if fields.len() >= 8
+            && residue_like(fields[2])
+            && numeric(fields[6])
+            && numeric(fields[7])
+        {
+            fields[..8].iter().map(|s| s.to_string()).collect()
+        } else {
+            layout
+                .columns()
+                .iter()
+                .map(|&(start, end)| {
+                    line.get(start..end.min(line.len()))
+                        .unwrap_or("")
+                        .trim()
+                        .to_string()
+                })
+                .collect()
+        };
+
+        let parse_err = |what: &str, value: &str| IOError::ParseError {
+            line: line_num,
+            message: format!("Invalid {what}: {value:?}"),
+        };
+
+        let serial = fields[0]
+            .parse::<u32>()
+            .map_err(|_| parse_err("atom serial", &fields[0]))?;
+        // Residue IDs may carry an insertion code (`12A`)
+        let residue_digits: String = fields[2]
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '-')
+            .collect();
+        let residue_id = residue_digits
+            .parse::<i64>()
+            .map_err(|_| parse_err("residue id", &fields[2]))?
+            .max(0) as u32;
+        let charge = fields[6]
+            .parse::<f32>()
+            .map_err(|_| parse_err("charge", &fields[6]))?;
+        let mass = fields[7]
+            .parse::<f32>()
+            .map_err(|_| parse_err("mass", &fields[7]))?;
+
+        Ok(PsfAtom {
+            serial,
+            segment: fields[1].clone(),
+            residue_id,
+            residue_name: fields[3].clone(),
+            atom_name: fields[4].clone(),
+            atom_type: fields[5].clone(),
+            charge,
+            mass,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STANDARD_PSF: &str = "\
+PSF CMAP
+
+       1 !NTITLE
+ REMARKS water and ion
+
+       4 !NATOM
+       1 WAT  1    TIP3 OH2  OT    -0.834000       15.9994           0
+       2 WAT  1    TIP3 H1   HT     0.417000        1.0080           0
+       3 WAT  1    TIP3 H2   HT     0.417000        1.0080           0
+       4 ION  2    CAL  CAL  CAL    2.000000       40.0800           0
+
+       2 !NBOND: bonds
+       1       2       1       3
+
+       1 !NTHETA: angles
+       2       1       3
+";
+
+    #[test]
+    fn test_parse_standard_psf() {
+        let (atoms, bonds) = PsfParser::parse_string(STANDARD_PSF).unwrap();
+        assert_eq!(atoms.len(), 4);
+        assert_eq!(atoms[0].chain_id, "WAT");
+        assert_eq!(atoms[0].residue_name, "TIP3");
+        assert_eq!(atoms[0].element, Element::O);
+        assert!((atoms[0].charge + 0.834).abs() < 1e-6);
+        assert!((atoms[1].mass - 1.008).abs() < 1e-6);
+        assert_eq!(atoms[3].element, Element::Ca);
+        assert_eq!(atoms[3].residue_id, 2);
+
+        assert_eq!(bonds.len(), 2);
+        assert_eq!((bonds[0].atom_a_id, bonds[0].atom_b_id), (0, 1));
+        assert_eq!((bonds[1].atom_a_id, bonds[1].atom_b_id), (0, 2));
+    }
+
+    #[test]
+    fn test_parse_ext_cheq_psf_with_blank_segment() {
+        // (I10,1X,A8,1X,A8,1X,A8,1X,A8,1X,A6,1X,2G14.6,I8) plus two CHEQ columns
+        let atom_line = |serial: u32, segment: &str, name: &str, charge: f32, mass: f32| {
+            format!(
+                "{serial:>10} {segment:<8} {:<8} {:<8} {name:<8} {:<6} {charge:>14.6}{mass:>14.4}{:>8}   0.00000     -0.301140E-02",
+                "12A", "ALA", "CT1", 0
+            )
+        };
+        let psf = format!(
+            "PSF EXT CMAP CHEQ XPLOR\n\n         2 !NATOM\n{}\n{}\n\n         1 !NBOND: bonds\n         1         2\n",
+            atom_line(1, "", "CA", 0.07, 12.011),
+            atom_line(2, "PROA", "HA", 0.09, 1.008),
+        );
+
+        let (atoms, bonds) = PsfParser::parse_string(&psf).unwrap();
+        assert_eq!(atoms.len(), 2);
+        assert_eq!(atoms[0].chain_id, "");
+        assert_eq!(atoms[0].residue_id, 12);
+        assert_eq!(atoms[0].residue_name, "ALA");
+        assert_eq!(atoms[0].name, "CA");
+        assert_eq!(atoms[0].element, Element::C);
+        assert_eq!(atoms[1].chain_id, "PROA");
+        assert_eq!(atoms[1].element, Element::H);
+        assert!((atoms[1].charge - 0.09).abs() < 1e-6);
+        assert_eq!(bonds.len(), 1);
+    }
+
+    #[test]
+    fn test_rejects_missing_header() {
+        assert!(PsfParser::parse_string("       1 !NATOM\n").is_err());
+    }
+}
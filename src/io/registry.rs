@@ -0,0 +1,388 @@
+//! Format registry: extensions, content sniffers, capabilities and readers
+//!
+//! Every reader module describes itself with a [`FormatDescriptor`] (its
+//! `FORMAT` constant): the extensions it claims, a sniffer that scores the
+//! first bytes of a file, what it can do, and the functions that load it as
+//! a structure/trajectory or read it as a topology. Loading, the open and
+//! topology dialogs and the export menus all query the registry, so adding a
+//! format means adding a descriptor rather than new match arms.
+//!
+//! Detection weighs content over names. Each sniffer returns a
+//! [`Confidence`]; a matching extension adds [`EXTENSION_WEIGHT`], and
+//! without one a format needs at least [`Confidence::POSSIBLE`]. A `.pdb`
+//! holding mmCIF therefore loads as mmCIF, and a PDB saved as `model.txt`
+//! still loads as PDB.
+
+use crate::core::atom::AtomData;
+use crate::core::bond::BondData;
+use crate::core::trajectory::Trajectory;
+use crate::io::compression;
+use crate::io::streaming::FrameProvider;
+use crate::io::{FileFormat, IOResult};
+use std::path::Path;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock, RwLockReadGuard};
+
+/// Bytes of (decompressed) content handed to sniffers.
+pub const SNIFF_BYTES: usize = 8192;
+
+/// Score added when the file extension belongs to the format.
+pub const EXTENSION_WEIGHT: u8 = 50;
+
+/// A loaded file: trajectory, atoms, bonds, an optional streaming frame
+/// provider, and whether the atoms are placeholders awaiting a topology.
+pub type LoadedFile = (
+    Trajectory,
+    Vec<AtomData>,
+    Vec<BondData>,
+    Option<Arc<dyn FrameProvider>>,
+    bool,
+);
+
+/// Load a file as the displayed structure/trajectory, optionally paired
+/// with a topology file.
+pub type LoadFn = fn(&Path, Option<&Path>) -> IOResult<LoadedFile>;
+
+/// Read atoms and bonds for pairing with a coordinate-only trajectory.
+pub type TopologyFn = fn(&Path) -> IOResult<(Vec<AtomData>, Vec<BondData>)>;
+
+/// Score content against a format.
+pub type SniffFn = fn(&[u8]) -> Confidence;
+
+/// How sure a sniffer is that content belongs to its format (0–100).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Confidence(pub u8);
+
+impl Confidence {
+    pub const NONE: Confidence = Confidence(0);
+    /// Consistent with the format, but so are many others
+    pub const WEAK: Confidence = Confidence(20);
+    /// Enough to pick the format when the extension says nothing
+    pub const POSSIBLE: Confidence = Confidence(40);
+    pub const LIKELY: Confidence = Confidence(70);
+    /// Magic number or mandatory header
+    pub const CERTAIN: Confidence = Confidence(100);
+}
+
+/// What a format can be used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FormatCapabilities {
+    /// Carries atom names, residues or bonds usable as a topology
+    pub topology: bool,
+    /// Holds several frames
+    pub trajectory: bool,
+    /// Frames are read on demand from disk
+    pub streaming: bool,
+    /// Has a writer
+    pub writable: bool,
+}
+
+/// One registered file format.
+#[derive(Debug, Clone, Copy)]
+pub struct FormatDescriptor {
+    pub format: FileFormat,
+    /// Short display name ("PDB", "AMBER NetCDF")
+    pub name: &'static str,
+    /// Lower-case extensions without the dot; the first is the canonical one
+    pub extensions: &'static [&'static str],
+    pub capabilities: FormatCapabilities,
+    /// Coordinates only: atoms stay placeholders until a topology is loaded
+    pub needs_topology: bool,
+    pub sniff: SniffFn,
+    /// Open as the displayed structure/trajectory (`None`: topology only)
+    pub load: Option<LoadFn>,
+    /// Read as a topology for a coordinate-only trajectory
+    pub read_topology: Option<TopologyFn>,
+}
+
+impl FormatDescriptor {
+    /// Whether the path's extension (ignoring a compression suffix) is one
+    /// of this format's.
+    pub fn matches_extension(&self, path: &Path) -> bool {
+        let path = compression::strip_compression_extension(path);
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                self.extensions
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(ext))
+            })
+    }
+
+    pub fn is_loadable(&self) -> bool {
+        self.load.is_some()
+    }
+}
+
+/// Registered formats, in sniffing priority order.
+#[derive(Debug, Clone, Default)]
+pub struct FormatRegistry {
+    formats: Vec<FormatDescriptor>,
+}
+
+impl FormatRegistry {
+    /// Registry with every reader in this crate.
+    ///
+    /// Formats with magic numbers or mandatory headers come first so they
+    /// win ties against the looser text heuristics.
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        for descriptor in [
+            crate::io::dcd::FORMAT,
+            crate::io::xtc::FORMAT,
+            crate::io::trr::FORMAT,
+            crate::io::netcdf::FORMAT,
+            crate::io::mol2::FORMAT,
+            crate::io::sdf::FORMAT,
+            crate::io::psf::FORMAT,
+            crate::io::prmtop::FORMAT,
+            crate::io::lammps_dump::FORMAT,
+            crate::io::mmcif::FORMAT,
+            crate::io::pdbqt::FORMAT,
+            crate::io::pqr::FORMAT,
+            crate::io::pdb::FORMAT,
+            crate::io::gro::FORMAT,
+            crate::io::xyz::FORMAT,
+            crate::io::mdcrd::FORMAT,
+            crate::io::lammps_data::FORMAT,
+        ] {
+            registry.register(descriptor);
+        }
+        registry
+    }
+
+    /// Add a format, replacing any earlier descriptor for the same format.
+    pub fn register(&mut self, descriptor: FormatDescriptor) {
+        match self
+            .formats
+            .iter_mut()
+            .find(|d| d.format == descriptor.format)
+        {
+            Some(existing) => *existing = descriptor,
+            None => self.formats.push(descriptor),
+        }
+    }
+
+    pub fn formats(&self) -> &[FormatDescriptor] {
+        &self.formats
+    }
+
+    pub fn get(&self, format: FileFormat) -> Option<&FormatDescriptor> {
+        self.formats.iter().find(|d| d.format == format)
+    }
+
+    /// First format claiming the path's extension.
+    pub fn by_extension(&self, path: &Path) -> Option<&FormatDescriptor> {
+        self.formats.iter().find(|d| d.matches_extension(path))
+    }
+
+    /// Best content match, if any sniffer reaches [`Confidence::POSSIBLE`].
+    pub fn sniff(&self, data: &[u8]) -> Option<(&FormatDescriptor, Confidence)> {
+        self.formats
+            .iter()
+            .map(|d| (d, (d.sniff)(data)))
+            .filter(|(_, confidence)| *confidence >= Confidence::POSSIBLE)
+            .fold(None, |best, candidate| match best {
+                Some((_, score)) if score >= candidate.1 => best,
+                _ => Some(candidate),
+            })
+    }
+
+    /// Detect from a file name and the first bytes of its content.
+    pub fn detect_content(&self, path: &Path, data: &[u8]) -> Option<&FormatDescriptor> {
+        let mut best: Option<(u32, &FormatDescriptor)> = None;
+        for descriptor in &self.formats {
+            let confidence = (descriptor.sniff)(data);
+            let by_extension = descriptor.matches_extension(path);
+            if !by_extension && confidence < Confidence::POSSIBLE {
+                continue;
+            }
+            let score = confidence.0 as u32
+                + if by_extension {
+                    EXTENSION_WEIGHT as u32
+                } else {
+                    0
+                };
+            if best.map_or(true, |(best_score, _)| score > best_score) {
+                best = Some((score, descriptor));
+            }
+        }
+        best.map(|(_, descriptor)| descriptor)
+    }
+
+    /// Detect a file's format from its name and content.
+    pub fn detect(&self, path: &Path) -> Option<&FormatDescriptor> {
+        let data = compression::read_prefix(path, SNIFF_BYTES).unwrap_or_default();
+        self.detect_content(path, &data)
+    }
+
+    /// Extensions of the formats matching `filter`, in registry order.
+    pub fn extensions(&self, filter: impl Fn(&FormatDescriptor) -> bool) -> Vec<&'static str> {
+        self.formats
+            .iter()
+            .filter(|d| filter(d))
+            .flat_map(|d| d.extensions.iter().copied())
+            .collect()
+    }
+}
+
+static REGISTRY: OnceLock<RwLock<FormatRegistry>> = OnceLock::new();
+
+fn global() -> &'static RwLock<FormatRegistry> {
+    REGISTRY.get_or_init(|| RwLock::new(FormatRegistry::builtin()))
+}
+
+/// The process-wide registry, starting with [`FormatRegistry::builtin`].
+pub fn registry() -> RwLockReadGuard<'static, FormatRegistry> {
+    global().read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Add a format to the process-wide registry (e.g. from a plugin).
+pub fn register_format(descriptor: FormatDescriptor) {
+    global()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .register(descriptor);
+}
+
+/// Detect a file's format with the process-wide registry.
+///
+/// Returns a copy, so the registry is not locked while the file is read.
+pub fn detect(path: &Path) -> Option<FormatDescriptor> {
+    registry().detect(path).copied()
+}
+
+/// Descriptor of a registered format.
+pub fn descriptor(format: FileFormat) -> Option<FormatDescriptor> {
+    registry().get(format).copied()
+}
+
+/// Content as text for text sniffers: `None` for binary data. A multi-byte
+/// character cut off at the end of the sniffed prefix is dropped.
+pub fn sniff_text(data: &[u8]) -> Option<&str> {
+    if data.contains(&0) {
+        return None;
+    }
+    match std::str::from_utf8(data) {
+        Ok(text) => Some(text),
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&data[..e.valid_up_to()]).ok(),
+        Err(_) => None,
+    }
+}
+
+/// Sniffer for formats recognised by extension only.
+pub fn no_sniff(_data: &[u8]) -> Confidence {
+    Confidence::NONE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PDB: &str = "\
+HEADER    TEST
+ATOM      1  N   ALA A   1      11.104   6.134  -6.504  1.00  0.00           N
+ATOM      2  CA  ALA A   1      11.639   6.071  -5.147  1.00  0.00           C
+END
+";
+
+    fn custom_sniff(data: &[u8]) -> Confidence {
+        if data.starts_with(b"GUMOLTEST") {
+            Confidence::CERTAIN
+        } else {
+            Confidence::NONE
+        }
+    }
+
+    #[test]
+    fn test_builtin_descriptors_are_consistent() {
+        let registry = FormatRegistry::builtin();
+        for descriptor in registry.formats() {
+            assert_eq!(
+                descriptor.capabilities.topology,
+                descriptor.read_topology.is_some(),
+                "{}",
+                descriptor.name
+            );
+            assert!(
+                !descriptor.needs_topology || descriptor.is_loadable(),
+                "{}",
+                descriptor.name
+            );
+            assert!(!descriptor.extensions.is_empty(), "{}", descriptor.name);
+            for other in registry.formats() {
+                if other.format != descriptor.format {
+                    assert!(
+                        other
+                            .extensions
+                            .iter()
+                            .all(|e| !descriptor.extensions.contains(e)),
+                        "{} and {} share an extension",
+                        descriptor.name,
+                        other.name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_content_beats_extension_and_rescues_unknown_names() {
+        let registry = FormatRegistry::builtin();
+        let detect = |name: &str, content: &str| {
+            registry
+                .detect_content(Path::new(name), content.as_bytes())
+                .map(|d| d.format)
+        };
+
+        assert_eq!(detect("model.txt", PDB), Some(FileFormat::PDB));
+        assert_eq!(detect("model.ent", PDB), Some(FileFormat::PDB));
+        assert_eq!(
+            detect("model.pdb", "data_1ABC\n_cell.length_a 10\n"),
+            Some(FileFormat::MmCIF)
+        );
+        // An integer on the first line is not enough to call a file XYZ
+        assert_eq!(detect("numbers.txt", "42\n1 2 3\n4 5 6\n"), None);
+        assert_eq!(
+            detect("frames.txt", "2\nwater\nO 0.0 0.0 0.0\nH 0.0 0.9 0.0\n"),
+            Some(FileFormat::XYZ)
+        );
+        // Unreadable content falls back to the extension
+        assert_eq!(detect("box.gro.gz", ""), Some(FileFormat::GRO));
+        assert_eq!(detect("notes.md", ""), None);
+    }
+
+    #[test]
+    fn test_register_custom_format() {
+        let mut registry = FormatRegistry::builtin();
+        let count = registry.formats().len();
+        let custom = FormatDescriptor {
+            format: FileFormat::Custom("gumoltest"),
+            name: "Test",
+            extensions: &["gumoltest"],
+            capabilities: FormatCapabilities::default(),
+            needs_topology: false,
+            sniff: custom_sniff,
+            load: None,
+            read_topology: None,
+        };
+        registry.register(custom);
+        registry.register(custom);
+        assert_eq!(registry.formats().len(), count + 1);
+        assert_eq!(
+            registry
+                .detect_content(Path::new("run.bin"), b"GUMOLTEST v1")
+                .map(|d| d.format),
+            Some(FileFormat::Custom("gumoltest"))
+        );
+        assert!(registry
+            .extensions(|d| d.capabilities.topology)
+            .contains(&"pdb"));
+    }
+
+    #[test]
+    fn test_sniff_text_handles_binary_and_truncation() {
+        assert_eq!(sniff_text(b"ATOM"), Some("ATOM"));
+        assert_eq!(sniff_text(&[0x41, 0xc3]), Some("A"));
+        assert_eq!(sniff_text(&[0x00, 0x00, 0x00, 0x54]), None);
+    }
+}
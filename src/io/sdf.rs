@@ -0,0 +1,561 @@
+//! MDL molfile / SDF reader (V2000 and V3000)
+//!
+//! An SD file is a sequence of molfile records separated by `$$$$`. Each
+//! record has a three-line header (name, program, comment), a counts line
+//! and a connection table:
+//!
+//! ```text
+//! formaldehyde
+//!   gumol
+//!
+//!   2  1  0  0  0  0  0  0  0  0999 V2000
+//!     0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+//!     1.2100    0.0000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0
+//!   1  2  2  0
+//! M  END
+//! > <energy>
+//! -12.5
+//!
+//! $$$$
+//! ```
+//!
+//! V2000 atom and bond blocks are fixed-width, so atom indices above 99 may
+//! run together (`100101  1`). V3000 records use `M  V30` lines between
+//! `BEGIN CTAB` and `END CTAB`, with `-` continuation lines.
+//!
+//! Bond types 1–3 keep their order and type 4 is aromatic; V3000 types 9
+//! (coordination) and 10 (hydrogen) map to the matching [`BondType`]; query
+//! types fall back to single bonds. Charges come from the atom block or
+//! `M  CHG` (V2000) and `CHG=` (V3000). `> <NAME>` data items are kept per
+//! record and end up in the frame metadata when records load as frames
+//! (see [`MultiRecordMode`]).
+
+use crate::core::atom::{AtomData, Element};
+use crate::core::bond::{BondData, BondOrder, BondType};
+use crate::core::trajectory::Trajectory;
+use crate::io::molecule::{assemble_records, MoleculeRecord, MultiRecordMode};
+use crate::io::registry::{
+    sniff_text, Confidence, FormatCapabilities, FormatDescriptor, LoadedFile,
+};
+use crate::io::{FileFormat, IOError, IOResult};
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Fixed-width column of a line, trimmed; empty if the line is too short.
+fn column(line: &str, start: usize, end: usize) -> &str {
+    line.get(start.min(line.len())..end.min(line.len()))
+        .unwrap_or("")
+        .trim()
+}
+
+fn parse_column<T: std::str::FromStr>(line: &str, start: usize, end: usize) -> Option<T> {
+    column(line, start, end).parse().ok()
+}
+
+fn element_from_sdf_symbol(symbol: &str) -> Element {
+    match symbol {
+        "D" | "T" => Element::H,
+        _ => Element::from_symbol(symbol).unwrap_or_else(|_| {
+            warn!("Unknown element in SDF: {}, using Unknown", symbol);
+            Element::Unknown
+        }),
+    }
+}
+
+/// Bond type and order for an MDL bond type code.
+fn bond_from_code(code: u32) -> (BondType, BondOrder) {
+    match code {
+        1 => (BondType::Covalent, BondOrder::Single),
+        2 => (BondType::Covalent, BondOrder::Double),
+        3 => (BondType::Covalent, BondOrder::Triple),
+        4 => (BondType::Covalent, BondOrder::Aromatic),
+        9 => (BondType::Coordinate, BondOrder::Single),
+        10 => (BondType::Hydrogen, BondOrder::Single),
+        _ => (BondType::Unknown, BondOrder::Single),
+    }
+}
+
+/// Formal charge for a V2000 atom block charge code.
+fn charge_from_v2000_code(code: i32) -> f32 {
+    match code {
+        1..=3 | 5..=7 => (4 - code) as f32,
+        _ => 0.0,
+    }
+}
+
+fn new_atom(index: usize, symbol: &str, position: Vec3) -> AtomData {
+    let element = element_from_sdf_symbol(symbol);
+    let mut atom = AtomData::new(
+        index as u32,
+        element,
+        1,
+        "UNL".to_string(),
+        "A".to_string(),
+        format!("{}{}", element.symbol(), index + 1),
+    );
+    atom.position = position;
+    atom
+}
+
+/// Resolve 1-based atom numbers of a bond and append it.
+fn push_bond(
+    record: &mut MoleculeRecord,
+    index: &HashMap<u32, u32>,
+    (a, b): (u32, u32),
+    code: u32,
+    line: usize,
+) -> IOResult<()> {
+    let (Some(&ia), Some(&ib)) = (index.get(&a), index.get(&b)) else {
+        return Err(IOError::ParseError {
+            line,
+            message: format!("Bond references unknown atom ({a}, {b})"),
+        });
+    };
+    let (bond_type, order) = bond_from_code(code);
+    record
+        .bonds
+        .push(BondData::new(ia, ib, bond_type, order, 0.0));
+    Ok(())
+}
+
+/// Registry entry: MDL molfiles and SD files; multi-record files hold conformer frames.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::Sdf,
+    name: "SDF/MOL",
+    extensions: &["sdf", "sd", "mol"],
+    capabilities: FormatCapabilities {
+        topology: true,
+        trajectory: true,
+        streaming: false,
+        writable: false,
+    },
+    needs_topology: false,
+    sniff,
+    load: Some(load),
+    read_topology: Some(SdfParser::parse_topology),
+};
+
+fn sniff(data: &[u8]) -> Confidence {
+    if sniff_text(data).is_some_and(SdfParser::is_sdf_content) {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, _topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    let (trajectory, atom_data, bond_data) = SdfParser::parse_file(path)?;
+    Ok((trajectory, atom_data, bond_data, None, false))
+}
+
+/// SDF / MOL format parser
+pub struct SdfParser;
+
+impl SdfParser {
+    /// Parse an SDF or MOL file; multiple records are combined with [`MultiRecordMode::Auto`].
+    pub fn parse_file(path: &Path) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        Self::parse_file_with_mode(path, MultiRecordMode::Auto)
+    }
+
+    /// Parse an SDF or MOL file, combining multiple records as `mode` says.
+    pub fn parse_file_with_mode(
+        path: &Path,
+        mode: MultiRecordMode,
+    ) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        let content = crate::io::compression::read_to_string(path)?;
+        Self::parse_string(&content, path.to_path_buf(), mode)
+    }
+
+    /// Parse SDF content.
+    pub fn parse_string(
+        content: &str,
+        file_path: PathBuf,
+        mode: MultiRecordMode,
+    ) -> IOResult<(Trajectory, Vec<AtomData>, Vec<BondData>)> {
+        let records = Self::parse_records(content)?;
+        assemble_records(records, file_path, mode, "SDF")
+    }
+
+    /// Atoms and bonds of an SDF file, for use as a topology.
+    pub fn parse_topology(path: &Path) -> IOResult<(Vec<AtomData>, Vec<BondData>)> {
+        let (_, atoms, bonds) = Self::parse_file(path)?;
+        Ok((atoms, bonds))
+    }
+
+    /// Parse every `$$$$`-separated record.
+    pub fn parse_records(content: &str) -> IOResult<Vec<MoleculeRecord>> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut records = Vec::new();
+        let mut start = 0;
+
+        while start < lines.len() {
+            let end = lines[start..]
+                .iter()
+                .position(|l| l.trim_end() == "$$$$")
+                .map_or(lines.len(), |p| start + p);
+            if lines[start..end].iter().any(|l| !l.trim().is_empty()) {
+                records.push(Self::parse_record(&lines[start..end], start)?);
+            }
+            start = end + 1;
+        }
+
+        Ok(records)
+    }
+
+    /// Parse one molfile record; `first_line` is its 0-based line offset in the file.
+    fn parse_record(lines: &[&str], first_line: usize) -> IOResult<MoleculeRecord> {
+        if lines.len() < 4 {
+            return Err(IOError::ParseError {
+                line: first_line + lines.len(),
+                message: "Truncated molfile header".to_string(),
+            });
+        }
+
+        let mut record = MoleculeRecord {
+            name: lines[0].trim().to_string(),
+            ..Default::default()
+        };
+        let counts = lines[3];
+        let body_end = if counts.contains("V3000") {
+            Self::parse_v3000_ctab(&mut record, lines, first_line)?
+        } else {
+            Self::parse_v2000_ctab(&mut record, lines, first_line)?
+        };
+
+        // Data items: "> <NAME>" followed by value lines up to a blank line.
+        let mut i = body_end;
+        while i < lines.len() {
+            let line = lines[i];
+            i += 1;
+            if !line.starts_with('>') {
+                continue;
+            }
+            let name = line
+                .split_once('<')
+                .and_then(|(_, rest)| rest.split_once('>'))
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_default();
+            let mut value = Vec::new();
+            while i < lines.len() && !lines[i].trim().is_empty() {
+                value.push(lines[i].trim_end());
+                i += 1;
+            }
+            if !name.is_empty() {
+                record.data.push((name, value.join("\n")));
+            }
+        }
+
+        Ok(record)
+    }
+
+    /// Read a V2000 connection table; returns the index of the line after `M  END`.
+    fn parse_v2000_ctab(
+        record: &mut MoleculeRecord,
+        lines: &[&str],
+        first_line: usize,
+    ) -> IOResult<usize> {
+        let counts = lines[3];
+        let counts_error = || IOError::ParseError {
+            line: first_line + 4,
+            message: format!("Invalid counts line: {counts}"),
+        };
+        let num_atoms: usize = parse_column(counts, 0, 3).ok_or_else(counts_error)?;
+        let num_bonds: usize = parse_column(counts, 3, 6).ok_or_else(counts_error)?;
+
+        let atom_start = 4;
+        let bond_start = atom_start + num_atoms;
+        if lines.len() < bond_start + num_bonds {
+            return Err(IOError::ParseError {
+                line: first_line + lines.len(),
+                message: format!(
+                    "Expected {num_atoms} atoms and {num_bonds} bonds, record has {} lines",
+                    lines.len()
+                ),
+            });
+        }
+
+        for (i, line) in lines[atom_start..bond_start].iter().enumerate() {
+            let line_num = first_line + atom_start + i + 1;
+            // Fixed columns, with a whitespace fallback for loosely written files.
+            let fixed = (
+                parse_column::<f32>(line, 0, 10),
+                parse_column::<f32>(line, 10, 20),
+                parse_column::<f32>(line, 20, 30),
+                column(line, 31, 34),
+            );
+            let (position, symbol, charge_code) = match fixed {
+                (Some(x), Some(y), Some(z), symbol) if !symbol.is_empty() => (
+                    Vec3::new(x, y, z),
+                    symbol.to_string(),
+                    parse_column::<i32>(line, 36, 39).unwrap_or(0),
+                ),
+                _ => {
+                    let parts: Vec<&str> = line.split_whitespace().collect();
+                    let coords: Option<Vec<f32>> = parts
+                        .get(0..3)
+                        .map(|c| c.iter().filter_map(|v| v.parse().ok()).collect());
+                    match (coords, parts.get(3)) {
+                        (Some(c), Some(symbol)) if c.len() == 3 => (
+                            Vec3::new(c[0], c[1], c[2]),
+                            symbol.to_string(),
+                            parts.get(5).and_then(|v| v.parse().ok()).unwrap_or(0),
+                        ),
+                        _ => {
+                            return Err(IOError::ParseError {
+                                line: line_num,
+                                message: format!("Invalid atom line: {line}"),
+                            })
+                        }
+                    }
+                }
+            };
+            let mut atom = new_atom(i, &symbol, position);
+            atom.charge = charge_from_v2000_code(charge_code);
+            record.atoms.push(atom);
+        }
+
+        let index: HashMap<u32, u32> = (0..num_atoms as u32).map(|i| (i + 1, i)).collect();
+        for (i, line) in lines[bond_start..bond_start + num_bonds].iter().enumerate() {
+            let line_num = first_line + bond_start + i + 1;
+            let fields = (
+                parse_column::<u32>(line, 0, 3),
+                parse_column::<u32>(line, 3, 6),
+                parse_column::<u32>(line, 6, 9),
+            );
+            let (Some(a), Some(b), Some(code)) = fields else {
+                return Err(IOError::ParseError {
+                    line: line_num,
+                    message: format!("Invalid bond line: {line}"),
+                });
+            };
+            push_bond(record, &index, (a, b), code, line_num)?;
+        }
+
+        // Properties block up to "M  END"; only charges are used.
+        let mut i = bond_start + num_bonds;
+        let mut charges_reset = false;
+        while i < lines.len() {
+            let line = lines[i];
+            i += 1;
+            if line.starts_with("M  END") {
+                break;
+            }
+            if let Some(rest) = line.strip_prefix("M  CHG") {
+                // M  CHG replaces all atom-block charges.
+                if !charges_reset {
+                    record.atoms.iter_mut().for_each(|a| a.charge = 0.0);
+                    charges_reset = true;
+                }
+                let values: Vec<i32> = rest
+                    .split_whitespace()
+                    .skip(1)
+                    .filter_map(|v| v.parse().ok())
+                    .collect();
+                for pair in values.chunks(2) {
+                    if let [atom, charge] = *pair {
+                        if let Some(a) = record.atoms.get_mut((atom - 1).max(0) as usize) {
+                            a.charge = charge as f32;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(i)
+    }
+
+    /// Read a V3000 connection table; returns the index of the line after `M  END`.
+    fn parse_v3000_ctab(
+        record: &mut MoleculeRecord,
+        lines: &[&str],
+        first_line: usize,
+    ) -> IOResult<usize> {
+        let mut block = "";
+        let mut index = HashMap::new();
+        let mut pending = String::new();
+        let mut i = 4;
+
+        while i < lines.len() {
+            let line = lines[i];
+            i += 1;
+            let line_num = first_line + i;
+            if line.starts_with("M  END") {
+                break;
+            }
+            let Some(content) = line.strip_prefix("M  V30 ") else {
+                continue;
+            };
+
+            // A trailing '-' continues the entry on the next line.
+            if let Some(head) = content.trim_end().strip_suffix('-') {
+                pending.push_str(head);
+                continue;
+            }
+            pending.push_str(content);
+            let entry = std::mem::take(&mut pending);
+            let parts: Vec<&str> = entry.split_whitespace().collect();
+
+            match parts.as_slice() {
+                ["BEGIN", name, ..] => {
+                    block = match *name {
+                        "ATOM" => "ATOM",
+                        "BOND" => "BOND",
+                        _ => "",
+                    }
+                }
+                ["END", ..] => block = "",
+                _ if block == "ATOM" => {
+                    let error = || IOError::ParseError {
+                        line: line_num,
+                        message: format!("Invalid V3000 atom: {entry}"),
+                    };
+                    if parts.len() < 5 {
+                        return Err(error());
+                    }
+                    let serial: u32 = parts[0].parse().map_err(|_| error())?;
+                    let coord = |k: usize| parts[k].parse::<f32>().map_err(|_| error());
+                    let position = Vec3::new(coord(2)?, coord(3)?, coord(4)?);
+                    let mut atom = new_atom(record.atoms.len(), parts[1], position);
+                    if let Some(charge) = parts
+                        .iter()
+                        .find_map(|p| p.strip_prefix("CHG="))
+                        .and_then(|c| c.parse::<i32>().ok())
+                    {
+                        atom.charge = charge as f32;
+                    }
+                    index.insert(serial, record.atoms.len() as u32);
+                    record.atoms.push(atom);
+                }
+                _ if block == "BOND" => {
+                    let numbers: Option<Vec<u32>> = parts
+                        .get(0..4)
+                        .map(|p| p.iter().filter_map(|v| v.parse().ok()).collect());
+                    match numbers.as_deref() {
+                        Some(&[_, code, a, b]) => {
+                            push_bond(record, &index, (a, b), code, line_num)?
+                        }
+                        _ => {
+                            return Err(IOError::ParseError {
+                                line: line_num,
+                                message: format!("Invalid V3000 bond: {entry}"),
+                            })
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(i)
+    }
+
+    /// Whether content looks like a molfile (V2000/V3000 counts line on line 4).
+    pub fn is_sdf_content(content: &str) -> bool {
+        content.lines().nth(3).is_some_and(|counts| {
+            let counts = counts.trim_end();
+            counts.ends_with("V2000") || counts.ends_with("V3000")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMIC_ACID: &str = "\
+formic acid
+  gumol
+
+  3  2  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    1.2100    0.0000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0
+   -0.6700    1.1600    0.0000 O   0  5  0  0  0  0  0  0  0  0  0  0
+  1  2  2  0
+  1  3  1  0
+M  END
+> <energy>
+-12.5
+
+> <source>  (1)
+hand
+written
+
+$$$$
+";
+
+    #[test]
+    fn test_parse_v2000_record() {
+        let records = SdfParser::parse_records(FORMIC_ACID).unwrap();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.name, "formic acid");
+        assert_eq!(record.atoms.len(), 3);
+        assert_eq!(record.atoms[2].element, Element::O);
+        assert_eq!(record.atoms[2].charge, -1.0);
+        assert_eq!(record.bonds[0].order, BondOrder::Double);
+        assert_eq!(record.bonds[1].order, BondOrder::Single);
+        assert_eq!(
+            record.data,
+            vec![
+                ("energy".to_string(), "-12.5".to_string()),
+                ("source".to_string(), "hand\nwritten".to_string()),
+            ]
+        );
+        assert!(SdfParser::is_sdf_content(FORMIC_ACID));
+    }
+
+    #[test]
+    fn test_v2000_run_together_bond_indices_and_chg() {
+        let mut content = String::from("big\n\n\n101100  0  0  0  0  0  0  0  0999 V2000\n");
+        for i in 0..101 {
+            content.push_str(&format!(
+                "{:10.4}{:10.4}{:10.4} C   0  0  0  0  0  0  0  0  0  0  0  0\n",
+                i as f32 * 1.5,
+                0.0,
+                0.0
+            ));
+        }
+        for i in 1..=100 {
+            content.push_str(&format!("{:3}{:3}  4  0\n", i, i + 1));
+        }
+        content.push_str("M  CHG  1 101   1\nM  END\n");
+
+        let records = SdfParser::parse_records(&content).unwrap();
+        let record = &records[0];
+        assert_eq!(record.atoms.len(), 101);
+        assert_eq!(record.bonds.len(), 100);
+        let last = &record.bonds[99];
+        assert_eq!((last.atom_a_id, last.atom_b_id), (99, 100));
+        assert_eq!(last.order, BondOrder::Aromatic);
+        assert_eq!(record.atoms[100].charge, 1.0);
+    }
+
+    #[test]
+    fn test_parse_v3000_records_as_frames() {
+        let record = |x: f32| {
+            format!(
+                "water\n  gumol\n\n  0  0  0     0  0            999 V3000\n\
+                 M  V30 BEGIN CTAB\nM  V30 COUNTS 3 2 0 0 0\nM  V30 BEGIN ATOM\n\
+                 M  V30 1 O {x} 0.0 0.0 0\nM  V30 2 H 0.96 0.0 0.0 0\n\
+                 M  V30 3 H -0.24 0.93 0.0 0 -\nM  V30 CHG=0\nM  V30 END ATOM\n\
+                 M  V30 BEGIN BOND\nM  V30 1 1 1 2\nM  V30 2 1 1 3\nM  V30 END BOND\n\
+                 M  V30 END CTAB\nM  END\n$$$$\n"
+            )
+        };
+        let content = format!("{}{}", record(0.0), record(0.1));
+
+        let (trajectory, atoms, bonds) =
+            SdfParser::parse_string(&content, PathBuf::from("w.sdf"), MultiRecordMode::Auto)
+                .unwrap();
+        assert_eq!(trajectory.num_frames(), 2);
+        assert_eq!(atoms.len(), 3);
+        assert_eq!(atoms[1].element, Element::H);
+        assert_eq!(bonds.len(), 2);
+        assert!((bonds[0].length - 0.96).abs() < 1e-5);
+        assert_eq!(
+            trajectory.get_frame(1).unwrap().position(0),
+            Some(Vec3::new(0.1, 0.0, 0.0))
+        );
+    }
+}
@@ -11,6 +11,9 @@ use std::sync::{Arc, Mutex};
 pub const STREAMING_ATOM_FRAMES_THRESHOLD: u64 = 1_000_000;
 
 /// Provides random access to trajectory frames without holding all frames in memory.
+///
+/// Frames are returned in Å; providers for nm formats convert each frame with
+/// [`FrameData::normalize_units`] as it is read.
 pub trait FrameProvider: Send + Sync {
     fn num_frames(&self) -> usize;
     fn num_atoms(&self) -> usize;
@@ -58,8 +61,35 @@ impl FrameProvider for InMemoryFrameProvider {
     }
 
     fn get_frame(&self, index: usize) -> IOResult<FrameData> {
-        self.trajectory
-            .get_frame(index)
+        FrameProvider::get_frame(&self.trajectory, index)
+    }
+}
+
+/// A loaded trajectory serves its own frames, so shared (`Arc`) in-memory
+/// trajectories can be read like any streaming provider without copying.
+impl FrameProvider for Trajectory {
+    fn num_frames(&self) -> usize {
+        Trajectory::num_frames(self)
+    }
+
+    fn num_atoms(&self) -> usize {
+        self.num_atoms
+    }
+
+    fn time_step(&self) -> f32 {
+        self.time_step
+    }
+
+    fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    fn metadata(&self) -> &TrajectoryMetadata {
+        &self.metadata
+    }
+
+    fn get_frame(&self, index: usize) -> IOResult<FrameData> {
+        Trajectory::get_frame(self, index)
             .cloned()
             .ok_or_else(|| IOError::ParseError {
                 line: 0,
@@ -197,6 +227,83 @@ pub fn open_xyz(path: &Path) -> IOResult<(Trajectory, Option<Arc<dyn FrameProvid
     )
 }
 
+/// Open a (possibly multi-frame) GRO file, streaming when large enough to exceed memory budget.
+pub fn open_gro(path: &Path) -> IOResult<(Trajectory, Option<Arc<dyn FrameProvider>>)> {
+    let provider = crate::io::gro_stream::GroFrameProvider::open(path)?;
+    open_with_provider(
+        path,
+        provider,
+        |p| crate::io::gro_stream::GroFrameProvider::should_stream(p.num_atoms(), p.num_frames()),
+        |p| p.load_all_frames(),
+    )
+}
+
+/// Open a GROMACS XTC file, streaming when large enough to exceed memory budget.
+pub fn open_xtc(path: &Path) -> IOResult<(Trajectory, Option<Arc<dyn FrameProvider>>)> {
+    let provider = crate::io::xtc::XtcFrameProvider::open(path)?;
+    open_with_provider(
+        path,
+        provider,
+        |p| crate::io::xtc::XtcFrameProvider::should_stream(p.num_atoms(), p.num_frames()),
+        |p| p.load_all_frames(),
+    )
+}
+
+/// Open a GROMACS TRR file, streaming when large enough to exceed memory budget.
+pub fn open_trr(path: &Path) -> IOResult<(Trajectory, Option<Arc<dyn FrameProvider>>)> {
+    let provider = crate::io::trr::TrrFrameProvider::open(path)?;
+    open_with_provider(
+        path,
+        provider,
+        |p| crate::io::trr::TrrFrameProvider::should_stream(p.num_atoms(), p.num_frames()),
+        |p| p.load_all_frames(),
+    )
+}
+
+/// Open an AMBER ASCII trajectory, streaming when large enough to exceed memory budget.
+///
+/// `num_atoms` comes from the paired topology; it is inferred from the file
+/// when unknown.
+pub fn open_mdcrd(
+    path: &Path,
+    num_atoms: Option<usize>,
+) -> IOResult<(Trajectory, Option<Arc<dyn FrameProvider>>)> {
+    let provider = crate::io::mdcrd::MdcrdFrameProvider::open(path, num_atoms)?;
+    open_with_provider(
+        path,
+        provider,
+        |p| crate::io::mdcrd::MdcrdFrameProvider::should_stream(p.num_atoms(), p.num_frames()),
+        |p| p.load_all_frames(),
+    )
+}
+
+/// Open an AMBER NetCDF trajectory, streaming when large enough to exceed memory budget.
+pub fn open_netcdf(path: &Path) -> IOResult<(Trajectory, Option<Arc<dyn FrameProvider>>)> {
+    let provider = crate::io::netcdf::NetcdfFrameProvider::open(path)?;
+    open_with_provider(
+        path,
+        provider,
+        |p| crate::io::netcdf::NetcdfFrameProvider::should_stream(p.num_atoms(), p.num_frames()),
+        |p| p.load_all_frames(),
+    )
+}
+
+/// Open a LAMMPS text dump, streaming when large enough to exceed memory budget.
+pub fn open_lammps_dump(path: &Path) -> IOResult<(Trajectory, Option<Arc<dyn FrameProvider>>)> {
+    let provider = crate::io::lammps_dump::LammpsDumpFrameProvider::open(path)?;
+    open_with_provider(
+        path,
+        provider,
+        |p| {
+            crate::io::lammps_dump::LammpsDumpFrameProvider::should_stream(
+                p.num_atoms(),
+                p.num_frames(),
+            )
+        },
+        |p| p.load_all_frames(),
+    )
+}
+
 fn open_with_provider<P, LoadFn>(
     path: &Path,
     provider: P,
@@ -227,6 +334,10 @@ where
         let frames = load_all(&provider)?;
         let mut trajectory = Trajectory::new(path.to_path_buf(), num_atoms, time_step);
         trajectory.metadata = metadata;
+        // The loader converts the whole trajectory to Å from this unit.
+        if let Some(first) = frames.first() {
+            trajectory.length_unit = first.length_unit;
+        }
         for frame in frames {
             trajectory.add_frame(frame);
         }
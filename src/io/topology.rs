@@ -1,8 +1,14 @@
 //! Topology helpers for pairing coordinate trajectories (DCD) with structure files.
 
-use crate::core::atom::AtomData;
+use crate::core::atom::{AtomData, Element};
 use crate::core::bond::BondData;
+use crate::core::trajectory::Trajectory;
+use crate::io::registry::LoadedFile;
+use crate::io::streaming::FrameProvider;
+use crate::io::{load_topology, IOError, IOResult};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 
 /// Renumber atoms to sequential 0..N-1 (DCD frame order) and remap bond endpoints.
 pub fn normalize_topology(
@@ -49,11 +55,48 @@ pub fn validate_atom_count(topology_len: usize, trajectory_atoms: usize) -> Resu
     }
 }
 
+/// Unnamed atoms 0..count-1 shown until a topology is loaded.
+pub fn placeholder_atoms(count: usize) -> Vec<AtomData> {
+    (0..count)
+        .map(|atom_id| {
+            AtomData::new(
+                atom_id as u32,
+                Element::Unknown,
+                0,
+                "UNK".to_string(),
+                "A".to_string(),
+                format!("ATOM{atom_id}"),
+            )
+        })
+        .collect()
+}
+
+/// Load a coordinate-only trajectory, paired with `topology_path` when given
+/// and with placeholder atoms otherwise.
+///
+/// `open` receives the topology's atom count, for formats whose frames do
+/// not record one.
+pub fn load_coordinates(
+    topology_path: Option<&Path>,
+    open: impl FnOnce(Option<usize>) -> IOResult<(Trajectory, Option<Arc<dyn FrameProvider>>)>,
+) -> IOResult<LoadedFile> {
+    let topology = topology_path.map(load_topology).transpose()?;
+    let (trajectory, frame_provider) = open(topology.as_ref().map(|(atoms, _)| atoms.len()))?;
+
+    if let Some((atom_data, bond_data)) = topology {
+        validate_atom_count(atom_data.len(), trajectory.num_atoms)
+            .map_err(IOError::InvalidFormat)?;
+        Ok((trajectory, atom_data, bond_data, frame_provider, false))
+    } else {
+        let atom_data = placeholder_atoms(trajectory.num_atoms);
+        Ok((trajectory, atom_data, Vec::new(), frame_provider, true))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::atom::Element;
-    use crate::core::bond::{BondData, BondOrder, BondType};
+    use crate::core::bond::{BondOrder, BondType};
 
     #[test]
     fn test_normalize_topology_remaps_bonds() {
@@ -0,0 +1,758 @@
+//! GROMACS TRR full-precision trajectory reader
+//!
+//! TRR frames are big-endian XDR records with a self-describing header that
+//! lists the byte size of each block (box, virial, pressure, x, v, f). Reals
+//! are single or double precision depending on how GROMACS was built; the
+//! precision is detected per frame from the block sizes.
+//!
+//! Parsed frames keep GROMACS lengths (positions and box in nm, velocities in
+//! nm/ps, forces in kJ/mol/nm), tagged [`LengthUnit::Nanometer`], for the
+//! loader to convert to Å; [`TrrFrameProvider`] converts each streamed frame
+//! as it is read. Time is converted to fs on read. The step and free-energy
+//! lambda are kept in [`FrameData::metadata`].
+
+use crate::core::cell::UnitCell;
+use crate::core::trajectory::{FrameData, LengthUnit, Trajectory, TrajectoryMetadata, PS_TO_FS};
+use crate::io::registry::{Confidence, FormatCapabilities, FormatDescriptor, LoadedFile};
+use crate::io::streaming::FrameProvider;
+use crate::io::{FileFormat, IOError, IOResult};
+use bevy::prelude::*;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// TRR frame magic number.
+pub const TRR_MAGIC: i32 = 1993;
+
+/// Version string stored in every TRR frame header.
+const TRR_VERSION: &str = "GMX_trn_file";
+
+/// Decoded TRR frame header (block sizes in bytes).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrrFrameHeader {
+    pub ir_size: usize,
+    pub e_size: usize,
+    pub box_size: usize,
+    pub vir_size: usize,
+    pub pres_size: usize,
+    pub top_size: usize,
+    pub sym_size: usize,
+    pub x_size: usize,
+    pub v_size: usize,
+    pub f_size: usize,
+    pub num_atoms: usize,
+    pub step: i32,
+    pub nre: i32,
+    /// Simulation time in ps (as stored in the file).
+    pub time: f64,
+    pub lambda: f64,
+    /// Whether reals are stored as 8-byte doubles.
+    pub double_precision: bool,
+}
+
+impl TrrFrameHeader {
+    fn read<R: Read>(reader: &mut R) -> IOResult<Self> {
+        let magic = reader.read_i32::<BigEndian>()?;
+        if magic != TRR_MAGIC {
+            return Err(IOError::ParseError {
+                line: 0,
+                message: format!("Invalid TRR frame: expected magic {TRR_MAGIC}, got {magic}"),
+            });
+        }
+
+        let slen = reader.read_i32::<BigEndian>()?;
+        let str_len = reader.read_i32::<BigEndian>()?;
+        if slen != TRR_VERSION.len() as i32 + 1 || str_len != TRR_VERSION.len() as i32 {
+            return Err(IOError::InvalidFormat(format!(
+                "Unexpected TRR version string length {str_len}"
+            )));
+        }
+        let mut version = [0u8; 12];
+        reader.read_exact(&mut version)?;
+        if version != TRR_VERSION.as_bytes() {
+            return Err(IOError::InvalidFormat(format!(
+                "Unexpected TRR version string {:?}",
+                String::from_utf8_lossy(&version)
+            )));
+        }
+
+        let mut header = Self {
+            ir_size: read_size(reader)?,
+            e_size: read_size(reader)?,
+            box_size: read_size(reader)?,
+            vir_size: read_size(reader)?,
+            pres_size: read_size(reader)?,
+            top_size: read_size(reader)?,
+            sym_size: read_size(reader)?,
+            x_size: read_size(reader)?,
+            v_size: read_size(reader)?,
+            f_size: read_size(reader)?,
+            num_atoms: read_size(reader)?,
+            step: reader.read_i32::<BigEndian>()?,
+            nre: reader.read_i32::<BigEndian>()?,
+            ..Default::default()
+        };
+        header.double_precision = header.real_size()? == 8;
+        header.time = read_real(reader, header.double_precision)?;
+        header.lambda = read_real(reader, header.double_precision)?;
+        Ok(header)
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> IOResult<()> {
+        writer.write_i32::<BigEndian>(TRR_MAGIC)?;
+        writer.write_i32::<BigEndian>(TRR_VERSION.len() as i32 + 1)?;
+        writer.write_i32::<BigEndian>(TRR_VERSION.len() as i32)?;
+        writer.write_all(TRR_VERSION.as_bytes())?;
+        for size in [
+            self.ir_size,
+            self.e_size,
+            self.box_size,
+            self.vir_size,
+            self.pres_size,
+            self.top_size,
+            self.sym_size,
+            self.x_size,
+            self.v_size,
+            self.f_size,
+            self.num_atoms,
+        ] {
+            writer.write_i32::<BigEndian>(size as i32)?;
+        }
+        writer.write_i32::<BigEndian>(self.step)?;
+        writer.write_i32::<BigEndian>(self.nre)?;
+        write_real(writer, self.time, self.double_precision)?;
+        write_real(writer, self.lambda, self.double_precision)?;
+        Ok(())
+    }
+
+    /// Size of one real in bytes, inferred from the block sizes (xdrfile `nFloatSize`).
+    fn real_size(&self) -> IOResult<usize> {
+        let dim_atoms = self.num_atoms * 3;
+        let size = if self.box_size > 0 {
+            self.box_size / 9
+        } else if self.x_size > 0 && dim_atoms > 0 {
+            self.x_size / dim_atoms
+        } else if self.v_size > 0 && dim_atoms > 0 {
+            self.v_size / dim_atoms
+        } else if self.f_size > 0 && dim_atoms > 0 {
+            self.f_size / dim_atoms
+        } else {
+            0
+        };
+        match size {
+            4 | 8 => Ok(size),
+            _ => Err(IOError::InvalidFormat(
+                "Cannot determine TRR real precision from header".to_string(),
+            )),
+        }
+    }
+
+    /// Bytes of data blocks following the header.
+    fn body_bytes(&self) -> u64 {
+        (self.box_size + self.vir_size + self.pres_size + self.x_size + self.v_size + self.f_size)
+            as u64
+    }
+
+    pub fn has_positions(&self) -> bool {
+        self.x_size > 0
+    }
+}
+
+fn read_size<R: Read>(reader: &mut R) -> IOResult<usize> {
+    let value = reader.read_i32::<BigEndian>()?;
+    usize::try_from(value).map_err(|_| IOError::ParseError {
+        line: 0,
+        message: format!("Invalid TRR header size field: {value}"),
+    })
+}
+
+fn read_real<R: Read>(reader: &mut R, double: bool) -> IOResult<f64> {
+    Ok(if double {
+        reader.read_f64::<BigEndian>()?
+    } else {
+        f64::from(reader.read_f32::<BigEndian>()?)
+    })
+}
+
+fn write_real<W: Write>(writer: &mut W, value: f64, double: bool) -> IOResult<()> {
+    if double {
+        writer.write_f64::<BigEndian>(value)?;
+    } else {
+        writer.write_f32::<BigEndian>(value as f32)?;
+    }
+    Ok(())
+}
+
+/// Read `num_atoms` 3-vectors and scale them into engine units.
+fn read_vectors<R: Read>(reader: &mut R, num_atoms: usize, double: bool) -> IOResult<Vec<Vec3>> {
+    let mut vectors = Vec::with_capacity(num_atoms);
+    for _ in 0..num_atoms {
+        let x = read_real(reader, double)? as f32;
+        let y = read_real(reader, double)? as f32;
+        let z = read_real(reader, double)? as f32;
+        vectors.push(Vec3::new(x, y, z));
+    }
+    Ok(vectors)
+}
+
+fn skip_block<R: Read>(reader: &mut R, bytes: usize) -> IOResult<()> {
+    std::io::copy(&mut reader.take(bytes as u64), &mut std::io::sink())?;
+    Ok(())
+}
+
+/// Read one full TRR frame (in nm) from the current reader position.
+fn read_frame_at<R: Read>(reader: &mut R, frame_index: usize) -> IOResult<FrameData> {
+    let header = TrrFrameHeader::read(reader)?;
+    let double = header.double_precision;
+    let n = header.num_atoms;
+
+    let mut frame = FrameData::new(frame_index, (header.time * f64::from(PS_TO_FS)) as f32);
+    frame
+        .metadata
+        .insert("step".to_string(), header.step.to_string());
+    frame
+        .metadata
+        .insert("lambda".to_string(), header.lambda.to_string());
+
+    if header.box_size > 0 {
+        let mut box_vectors = [Vec3::ZERO; 3];
+        for row in &mut box_vectors {
+            let x = read_real(reader, double)? as f32;
+            let y = read_real(reader, double)? as f32;
+            let z = read_real(reader, double)? as f32;
+            *row = Vec3::new(x, y, z);
+        }
+        if box_vectors.iter().any(|v| *v != Vec3::ZERO) {
+            frame.cell = Some(UnitCell::from_vectors(box_vectors));
+        }
+    }
+    skip_block(reader, header.vir_size + header.pres_size)?;
+
+    if header.x_size > 0 {
+        frame.positions = read_vectors(reader, n, double)?;
+    }
+    if header.v_size > 0 {
+        frame.velocities = Some(read_vectors(reader, n, double)?);
+    }
+    if header.f_size > 0 {
+        frame.forces = Some(read_vectors(reader, n, double)?);
+    }
+    frame.length_unit = LengthUnit::Nanometer;
+
+    Ok(frame)
+}
+
+/// Indexed metadata for a TRR file.
+#[derive(Debug, Clone)]
+pub struct TrrIndex {
+    pub num_atoms: usize,
+    pub num_frames: usize,
+    /// Time between the first two indexed frames (fs).
+    pub time_step: f32,
+    /// Byte offset of every frame, including velocity- or force-only ones.
+    pub frame_offsets: Vec<u64>,
+    /// Offset of the latest frame at or before each frame that carries
+    /// coordinates; `None` before the first coordinate frame.
+    pub position_offsets: Vec<Option<u64>>,
+    pub double_precision: bool,
+    pub metadata: TrajectoryMetadata,
+}
+
+/// Scan a TRR file once and record the byte offset of each frame.
+///
+/// Frames written without positions (e.g. `nstvout` < `nstxout`) are indexed
+/// like any other; [`TrrFrameProvider`] fills their positions from the latest
+/// earlier coordinate frame. A truncated trailing frame is dropped with a
+/// warning.
+pub fn build_trr_index(path: &Path) -> IOResult<TrrIndex> {
+    let file = File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut frame_offsets = Vec::new();
+    let mut position_offsets = Vec::new();
+    let mut last_positions = None;
+    let mut times = Vec::with_capacity(2);
+    let mut num_atoms = None;
+    let mut double_precision = false;
+    let mut offset = 0u64;
+
+    while offset < file_len {
+        reader.seek(SeekFrom::Start(offset))?;
+        let header = match TrrFrameHeader::read(&mut reader) {
+            Ok(h) => h,
+            Err(IOError::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                warn!("TRR: truncated frame header at byte offset {offset}, ignoring");
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+
+        match num_atoms {
+            None => {
+                num_atoms = Some(header.num_atoms);
+                double_precision = header.double_precision;
+            }
+            Some(n) if n != header.num_atoms => {
+                return Err(IOError::ParseError {
+                    line: frame_offsets.len(),
+                    message: format!(
+                        "Atom count changed from {n} to {} at byte offset {offset}",
+                        header.num_atoms
+                    ),
+                });
+            }
+            Some(_) => {}
+        }
+
+        let next = reader.stream_position()? + header.body_bytes();
+        if next > file_len {
+            warn!("TRR: truncated frame at byte offset {offset}, ignoring");
+            break;
+        }
+
+        if header.has_positions() {
+            last_positions = Some(offset);
+        }
+        frame_offsets.push(offset);
+        position_offsets.push(last_positions);
+        if times.len() < 2 {
+            times.push(header.time);
+        }
+        offset = next;
+    }
+
+    if last_positions.is_none() {
+        return Err(IOError::ParseError {
+            line: 0,
+            message: "No coordinate frames found in TRR file".into(),
+        });
+    }
+
+    let time_step = match times.as_slice() {
+        [t0, t1] if t1 > t0 => ((t1 - t0) * f64::from(PS_TO_FS)) as f32,
+        _ => 1.0,
+    };
+
+    let mut metadata = TrajectoryMetadata {
+        software: "GROMACS".to_string(),
+        ..Default::default()
+    };
+    metadata.extra.insert(
+        "precision".to_string(),
+        if double_precision { "double" } else { "single" }.to_string(),
+    );
+
+    Ok(TrrIndex {
+        num_atoms: num_atoms.unwrap_or(0),
+        num_frames: frame_offsets.len(),
+        time_step,
+        frame_offsets,
+        position_offsets,
+        double_precision,
+        metadata,
+    })
+}
+
+/// Random-access TRR frame provider backed by a seekable file handle.
+pub struct TrrFrameProvider {
+    reader: Arc<Mutex<BufReader<File>>>,
+    index: TrrIndex,
+    file_path: PathBuf,
+}
+
+impl TrrFrameProvider {
+    pub fn open(path: &Path) -> IOResult<Self> {
+        let source = crate::io::compression::seekable_path(path)?;
+        let index = build_trr_index(&source)?;
+        let file =
+            File::open(&source).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+        Ok(Self {
+            reader: Arc::new(Mutex::new(BufReader::new(file))),
+            index,
+            file_path: path.to_path_buf(),
+        })
+    }
+
+    pub fn index(&self) -> &TrrIndex {
+        &self.index
+    }
+
+    pub fn should_stream(num_atoms: usize, num_frames: usize) -> bool {
+        crate::io::streaming::should_stream_trajectory(num_atoms, num_frames)
+    }
+
+    /// Read every frame, in nm.
+    pub fn load_all_frames(&self) -> IOResult<Vec<FrameData>> {
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("TRR reader lock poisoned".to_string()))?;
+        let mut frames: Vec<FrameData> = Vec::with_capacity(self.index.num_frames);
+        for (i, &offset) in self.index.frame_offsets.iter().enumerate() {
+            reader.seek(SeekFrom::Start(offset))?;
+            let mut frame = read_frame_at(&mut *reader, i)?;
+            if frame.positions.is_empty() {
+                if let Some(previous) = frames.last() {
+                    frame.positions = previous.positions.clone();
+                }
+            }
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+}
+
+impl FrameProvider for TrrFrameProvider {
+    fn num_frames(&self) -> usize {
+        self.index.num_frames
+    }
+
+    fn num_atoms(&self) -> usize {
+        self.index.num_atoms
+    }
+
+    fn time_step(&self) -> f32 {
+        self.index.time_step
+    }
+
+    fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    fn metadata(&self) -> &TrajectoryMetadata {
+        &self.index.metadata
+    }
+
+    fn get_frame(&self, index: usize) -> IOResult<FrameData> {
+        let offset = *self
+            .index
+            .frame_offsets
+            .get(index)
+            .ok_or_else(|| IOError::ParseError {
+                line: 0,
+                message: format!(
+                    "Frame index {index} out of range ({} frames)",
+                    self.index.num_frames
+                ),
+            })?;
+
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("TRR reader lock poisoned".to_string()))?;
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut frame = read_frame_at(&mut *reader, index)?;
+        if frame.positions.is_empty() {
+            if let Some(source) = self.index.position_offsets[index] {
+                reader.seek(SeekFrom::Start(source))?;
+                frame.positions = read_frame_at(&mut *reader, index)?.positions;
+            }
+        }
+        frame.normalize_units();
+        Ok(frame)
+    }
+}
+
+/// Registry entry: full-precision GROMACS trajectories, paired with a topology.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::TRR,
+    name: "GROMACS TRR",
+    extensions: &["trr"],
+    capabilities: FormatCapabilities {
+        topology: false,
+        trajectory: true,
+        streaming: true,
+        writable: true,
+    },
+    needs_topology: true,
+    sniff,
+    load: Some(load),
+    read_topology: None,
+};
+
+fn sniff(data: &[u8]) -> Confidence {
+    if TrrParser::is_trr_bytes(data) {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    crate::io::topology::load_coordinates(topology_path, |_| crate::io::streaming::open_trr(path))
+}
+
+/// TRR format parser
+pub struct TrrParser;
+
+impl TrrParser {
+    /// Parse a TRR file and return trajectory data in nm (loads all frames into RAM).
+    pub fn parse_file(path: &Path) -> IOResult<Trajectory> {
+        let provider = TrrFrameProvider::open(path)?;
+        info!(
+            "TRR: {} frames, {} atoms ({} precision)",
+            provider.num_frames(),
+            provider.num_atoms(),
+            if provider.index().double_precision {
+                "double"
+            } else {
+                "single"
+            }
+        );
+
+        let mut trajectory = Trajectory::new(
+            path.to_path_buf(),
+            provider.num_atoms(),
+            provider.time_step(),
+        );
+        trajectory.metadata = provider.metadata().clone();
+        trajectory.length_unit = LengthUnit::Nanometer;
+        for frame in provider.load_all_frames()? {
+            trajectory.add_frame(frame);
+        }
+        Ok(trajectory)
+    }
+
+    /// Check whether bytes look like a TRR file (big-endian magic 1993).
+    pub fn is_trr_bytes(data: &[u8]) -> bool {
+        data.len() >= 4 && i32::from_be_bytes([data[0], data[1], data[2], data[3]]) == TRR_MAGIC
+    }
+}
+
+/// Write trajectory frames to TRR format
+pub struct TrrWriter;
+
+impl TrrWriter {
+    /// Write a trajectory to a single-precision TRR file.
+    pub fn write_trajectory(path: &Path, trajectory: &Trajectory) -> IOResult<()> {
+        let mut file = std::io::BufWriter::new(File::create(path)?);
+        for (i, frame) in trajectory.frames.iter().enumerate() {
+            Self::write_frame(&mut file, frame, i as i32, false)?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Write one frame, with velocities and forces when present, converting
+    /// lengths to nm from the frame's unit.
+    ///
+    /// `step` is used unless the frame carries its own `step` metadata.
+    pub fn write_frame<W: Write>(
+        writer: &mut W,
+        frame: &FrameData,
+        step: i32,
+        double_precision: bool,
+    ) -> IOResult<()> {
+        // Velocity- or force-only frames (nstvout/nstfout < nstxout) carry no x block
+        let n = frame
+            .positions
+            .len()
+            .max(frame.velocities.as_ref().map_or(0, Vec::len))
+            .max(frame.forces.as_ref().map_or(0, Vec::len));
+        let real = if double_precision { 8 } else { 4 };
+        let block = n * 3 * real;
+
+        let header = TrrFrameHeader {
+            box_size: if frame.cell.is_some() { 9 * real } else { 0 },
+            x_size: if frame.positions.is_empty() { 0 } else { block },
+            v_size: if frame.velocities.is_some() { block } else { 0 },
+            f_size: if frame.forces.is_some() { block } else { 0 },
+            num_atoms: n,
+            step: frame
+                .metadata
+                .get("step")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(step),
+            time: f64::from(frame.time / PS_TO_FS),
+            lambda: frame
+                .metadata
+                .get("lambda")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.0),
+            double_precision,
+            ..Default::default()
+        };
+        header.write(writer)?;
+
+        let to_nm = frame.length_unit.to_angstrom() / LengthUnit::Nanometer.to_angstrom();
+        if let Some(cell) = &frame.cell {
+            for row in cell.vectors {
+                for value in (row * to_nm).to_array() {
+                    write_real(writer, f64::from(value), double_precision)?;
+                }
+            }
+        }
+
+        let mut write_block = |vectors: &[Vec3], scale: f32| -> IOResult<()> {
+            for i in 0..n {
+                let v = vectors.get(i).copied().unwrap_or(Vec3::ZERO) * scale;
+                for c in v.to_array() {
+                    write_real(writer, f64::from(c), double_precision)?;
+                }
+            }
+            Ok(())
+        };
+        if !frame.positions.is_empty() {
+            write_block(&frame.positions, to_nm)?;
+        }
+        if let Some(velocities) = &frame.velocities {
+            write_block(velocities, to_nm)?;
+        }
+        if let Some(forces) = &frame.forces {
+            write_block(forces, 1.0 / to_nm)?;
+        }
+        Ok(())
+    }
+}
+
+/// Register TRR parsing systems with Bevy
+pub fn register(_app: &mut App) {
+    info!("TRR parser registered");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_frame(index: usize, with_vf: bool) -> FrameData {
+        let mut frame = FrameData::new(index, index as f32 * 500.0);
+        frame.cell = Some(UnitCell::from_lengths_angles(
+            [25.0, 25.0, 25.0],
+            [90.0, 90.0, 60.0],
+        ));
+        frame
+            .metadata
+            .insert("lambda".to_string(), "0.25".to_string());
+        let mut velocities = Vec::new();
+        let mut forces = Vec::new();
+        for i in 0..4 {
+            frame.set_position(i, Vec3::new(i as f32 + index as f32 * 0.5, 1.0, -2.0));
+            velocities.push(Vec3::new(0.1 * i as f32, -0.2, 0.3));
+            forces.push(Vec3::new(10.0, -5.0 * i as f32, 2.5));
+        }
+        if with_vf {
+            frame.velocities = Some(velocities);
+            frame.forces = Some(forces);
+        }
+        frame
+    }
+
+    fn assert_close(a: Vec3, b: Vec3, tol: f32) {
+        assert!((a - b).abs().max_element() < tol, "{a:?} vs {b:?}");
+    }
+
+    #[test]
+    fn test_trr_magic_detection() {
+        assert!(TrrParser::is_trr_bytes(&TRR_MAGIC.to_be_bytes()));
+        assert!(!TrrParser::is_trr_bytes(&84_i32.to_le_bytes()));
+    }
+
+    #[test]
+    fn test_single_and_double_precision_frames() {
+        let frame = sample_frame(2, true);
+        for double in [false, true] {
+            let mut buffer = Vec::new();
+            TrrWriter::write_frame(&mut buffer, &frame, 40, double).unwrap();
+
+            let mut decoded = read_frame_at(&mut buffer.as_slice(), 0).unwrap();
+            assert_eq!(decoded.length_unit, LengthUnit::Nanometer);
+            assert_close(decoded.positions[1], Vec3::new(0.2, 0.1, -0.2), 1e-6);
+            decoded.normalize_units();
+            assert_eq!(decoded.metadata.get("step").map(String::as_str), Some("40"));
+            assert_eq!(
+                decoded.metadata.get("lambda").map(String::as_str),
+                Some("0.25")
+            );
+            assert!((decoded.time - 1000.0).abs() < 1e-3);
+            let cell = decoded.cell.unwrap();
+            for (a, b) in cell.vectors.iter().zip(frame.cell.unwrap().vectors) {
+                assert_close(*a, b, 1e-4);
+            }
+            for i in 0..4 {
+                assert_close(decoded.positions[i], frame.positions[i], 1e-5);
+                assert_close(
+                    decoded.velocities.as_ref().unwrap()[i],
+                    frame.velocities.as_ref().unwrap()[i],
+                    1e-5,
+                );
+                assert_close(
+                    decoded.forces.as_ref().unwrap()[i],
+                    frame.forces.as_ref().unwrap()[i],
+                    1e-4,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_header_precision_detection() {
+        let mut buffer = Vec::new();
+        TrrWriter::write_frame(&mut buffer, &sample_frame(0, false), 0, true).unwrap();
+        let header = TrrFrameHeader::read(&mut buffer.as_slice()).unwrap();
+        assert!(header.double_precision);
+        assert_eq!(header.x_size, 4 * 3 * 8);
+        assert_eq!(header.v_size, 0);
+    }
+
+    #[test]
+    fn test_trr_provider_random_access() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("traj.trr");
+        let mut trajectory = Trajectory::new(path.clone(), 4, 500.0);
+        for i in 0..5 {
+            trajectory.add_frame(sample_frame(i, i % 2 == 0));
+        }
+        TrrWriter::write_trajectory(&path, &trajectory).unwrap();
+
+        let provider = TrrFrameProvider::open(&path).unwrap();
+        assert_eq!(provider.num_frames(), 5);
+        assert_eq!(provider.num_atoms(), 4);
+        assert!((provider.time_step() - 500.0).abs() < 1e-3);
+
+        let f3 = provider.get_frame(3).unwrap();
+        assert!(f3.velocities.is_none());
+        assert_close(f3.positions[1], Vec3::new(2.5, 1.0, -2.0), 1e-5);
+        let f4 = provider.get_frame(4).unwrap();
+        assert!(f4.forces.is_some());
+        assert!(provider.get_frame(5).is_err());
+    }
+
+    #[test]
+    fn test_velocity_only_frames_are_indexed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vout.trr");
+        let mut trajectory = Trajectory::new(path.clone(), 4, 500.0);
+        for i in 0..4 {
+            let mut frame = sample_frame(i, true);
+            if i % 2 == 1 {
+                frame.positions.clear();
+            }
+            trajectory.add_frame(frame);
+        }
+        TrrWriter::write_trajectory(&path, &trajectory).unwrap();
+
+        let index = build_trr_index(&path).unwrap();
+        assert_eq!(index.num_frames, 4);
+        assert_eq!(index.position_offsets[1], Some(index.frame_offsets[0]));
+        assert_eq!(index.position_offsets[2], Some(index.frame_offsets[2]));
+
+        let provider = TrrFrameProvider::open(&path).unwrap();
+        let f3 = provider.get_frame(3).unwrap();
+        assert_eq!(f3.metadata.get("step").map(String::as_str), Some("3"));
+        assert_close(
+            f3.velocities.as_ref().unwrap()[2],
+            Vec3::new(0.2, -0.2, 0.3),
+            1e-5,
+        );
+        // Positions carry forward from frame 2
+        assert_close(f3.positions[1], Vec3::new(2.0, 1.0, -2.0), 1e-5);
+
+        let frames = provider.load_all_frames().unwrap();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[3].positions, frames[2].positions);
+        assert!(frames[1].forces.is_some());
+    }
+}
@@ -0,0 +1,1210 @@
+//! GROMACS XTC compressed trajectory reader
+//!
+//! XTC files are a sequence of big-endian XDR frames. Each frame stores a
+//! header (magic, atom count, step, time, box) followed by coordinates packed
+//! with the xdrfile 3D integer compression scheme. Frames are indexed by byte
+//! offset on open so large trajectories can be streamed through [`FrameProvider`].
+//!
+//! GROMACS writes nanometres and picoseconds. Parsed frames keep positions and
+//! box in nm, tagged [`LengthUnit::Nanometer`], for the loader to convert to
+//! Å; [`XtcFrameProvider`] converts each streamed frame as it is read. Times
+//! are converted to fs on read.
+
+use crate::core::cell::UnitCell;
+use crate::core::trajectory::{FrameData, LengthUnit, Trajectory, TrajectoryMetadata, PS_TO_FS};
+use crate::io::registry::{Confidence, FormatCapabilities, FormatDescriptor, LoadedFile};
+use crate::io::streaming::FrameProvider;
+use crate::io::{FileFormat, IOError, IOResult};
+use bevy::prelude::*;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// XTC frame magic number (GROMACS 1995 format).
+pub const XTC_MAGIC: i32 = 1995;
+
+/// XTC frame magic for GROMACS 2023+ files with a 64-bit compressed byte count.
+pub const XTC_NEW_MAGIC: i32 = 2023;
+
+/// Default compression precision used by `gmx mdrun` (1/1000 nm).
+pub const XTC_DEFAULT_PRECISION: f32 = 1000.0;
+
+/// Frames with this many atoms or fewer are stored as raw floats.
+const XTC_UNCOMPRESSED_MAX_ATOMS: usize = 9;
+
+/// Bytes in the frame header after the atom count: step, time and 3×3 box.
+const XTC_HEADER_TAIL_BYTES: i64 = 4 + 4 + 9 * 4;
+
+/// Bytes between the coordinate count and the compressed byte count.
+const XTC_COMPRESSED_PREAMBLE_BYTES: i64 = 4 + 3 * 4 + 3 * 4 + 4;
+
+const MAXABS: f32 = (i32::MAX - 2) as f32;
+
+const FIRSTIDX: usize = 9;
+
+/// Integer sizes for the small-difference encoding (xdrfile `magicints`).
+const MAGICINTS: [i32; 73] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 10, 12, 16, 20, 25, 32, 40, 50, 64, 80, 101, 128, 161, 203, 256,
+    322, 406, 512, 645, 812, 1024, 1290, 1625, 2048, 2580, 3250, 4096, 5060, 6501, 8192, 10321,
+    13003, 16384, 20642, 26007, 32768, 41285, 52015, 65536, 82570, 104031, 131072, 165140, 208063,
+    262144, 330280, 416127, 524287, 660561, 832255, 1048576, 1321122, 1664510, 2097152, 2642245,
+    3329021, 4194304, 5284491, 6658042, 8388607, 10568983, 13316085, 16777216,
+];
+
+const LASTIDX: usize = MAGICINTS.len();
+
+/// Decoded XTC frame header.
+#[derive(Debug, Clone, Copy)]
+pub struct XtcFrameHeader {
+    pub magic: i32,
+    pub num_atoms: usize,
+    pub step: i32,
+    /// Simulation time in ps (as stored in the file).
+    pub time: f32,
+    /// Box vectors in nm, one vector per row.
+    pub box_vectors: [[f32; 3]; 3],
+}
+
+impl XtcFrameHeader {
+    fn read<R: Read>(reader: &mut R) -> IOResult<Self> {
+        let magic = reader.read_i32::<BigEndian>()?;
+        if magic != XTC_MAGIC && magic != XTC_NEW_MAGIC {
+            return Err(IOError::ParseError {
+                line: 0,
+                message: format!("Invalid XTC frame: expected magic {XTC_MAGIC}, got {magic}"),
+            });
+        }
+        let num_atoms = read_count(reader)?;
+        let step = reader.read_i32::<BigEndian>()?;
+        let time = reader.read_f32::<BigEndian>()?;
+        let mut box_vectors = [[0.0f32; 3]; 3];
+        for row in &mut box_vectors {
+            for value in row.iter_mut() {
+                *value = reader.read_f32::<BigEndian>()?;
+            }
+        }
+        Ok(Self {
+            magic,
+            num_atoms,
+            step,
+            time,
+            box_vectors,
+        })
+    }
+
+    /// Unit cell in nm (full box vectors), `None` when the frame has no box.
+    pub fn cell(&self) -> Option<UnitCell> {
+        if self.box_vectors.iter().flatten().all(|v| *v == 0.0) {
+            return None;
+        }
+        let vectors = self.box_vectors.map(Vec3::from_array);
+        Some(UnitCell::from_vectors(vectors))
+    }
+}
+
+fn read_count<R: Read>(reader: &mut R) -> IOResult<usize> {
+    let value = reader.read_i32::<BigEndian>()?;
+    usize::try_from(value).map_err(|_| IOError::ParseError {
+        line: 0,
+        message: format!("Invalid XTC atom count: {value}"),
+    })
+}
+
+/// Number of bits needed to store values in `0..=size`.
+fn sizeofint(size: u32) -> u32 {
+    let mut num: u64 = 1;
+    let mut num_of_bits = 0;
+    while u64::from(size) >= num && num_of_bits < 32 {
+        num_of_bits += 1;
+        num <<= 1;
+    }
+    num_of_bits
+}
+
+/// Number of bits needed to store the mixed-radix product of `sizes`.
+fn sizeofints(sizes: [u32; 3]) -> u32 {
+    let mut bytes = [0u32; 32];
+    bytes[0] = 1;
+    let mut num_of_bytes = 1;
+    for &size in &sizes {
+        let mut tmp = 0u32;
+        let mut bytecnt = 0;
+        while bytecnt < num_of_bytes {
+            tmp += bytes[bytecnt] * size;
+            bytes[bytecnt] = tmp & 0xff;
+            tmp >>= 8;
+            bytecnt += 1;
+        }
+        while tmp != 0 {
+            bytes[bytecnt] = tmp & 0xff;
+            bytecnt += 1;
+            tmp >>= 8;
+        }
+        num_of_bytes = bytecnt;
+    }
+    let mut num = 1u32;
+    let mut num_of_bits = 0;
+    num_of_bytes -= 1;
+    while bytes[num_of_bytes] >= num {
+        num_of_bits += 1;
+        num *= 2;
+    }
+    num_of_bits + num_of_bytes as u32 * 8
+}
+
+fn bit_mask(num_of_bits: u32) -> u32 {
+    if num_of_bits >= 32 {
+        u32::MAX
+    } else {
+        (1u32 << num_of_bits) - 1
+    }
+}
+
+/// Bit-level reader over the compressed coordinate block.
+struct BitReader<'a> {
+    data: &'a [u8],
+    cnt: usize,
+    lastbits: u32,
+    lastbyte: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            cnt: 0,
+            lastbits: 0,
+            lastbyte: 0,
+        }
+    }
+
+    fn next_byte(&mut self) -> IOResult<u32> {
+        let byte =
+            self.data.get(self.cnt).copied().ok_or_else(|| {
+                IOError::InvalidFormat("XTC compressed block truncated".to_string())
+            })?;
+        self.cnt += 1;
+        Ok(u32::from(byte))
+    }
+
+    fn decode_bits(&mut self, mut num_of_bits: u32) -> IOResult<u32> {
+        let mask = bit_mask(num_of_bits);
+        let mut num = 0u32;
+        while num_of_bits >= 8 {
+            self.lastbyte = (self.lastbyte << 8) | self.next_byte()?;
+            num |= (self.lastbyte >> self.lastbits) << (num_of_bits - 8);
+            num_of_bits -= 8;
+        }
+        if num_of_bits > 0 {
+            if self.lastbits < num_of_bits {
+                self.lastbits += 8;
+                self.lastbyte = (self.lastbyte << 8) | self.next_byte()?;
+            }
+            self.lastbits -= num_of_bits;
+            num |= (self.lastbyte >> self.lastbits) & bit_mask(num_of_bits);
+        }
+        Ok(num & mask)
+    }
+
+    fn decode_ints(&mut self, mut num_of_bits: u32, sizes: [u32; 3]) -> IOResult<[u32; 3]> {
+        let mut bytes = [0u32; 32];
+        let mut num_of_bytes = 0;
+        while num_of_bits > 8 {
+            bytes[num_of_bytes] = self.decode_bits(8)?;
+            num_of_bytes += 1;
+            num_of_bits -= 8;
+        }
+        if num_of_bits > 0 {
+            bytes[num_of_bytes] = self.decode_bits(num_of_bits)?;
+            num_of_bytes += 1;
+        }
+
+        let mut nums = [0u32; 3];
+        for i in (1..3).rev() {
+            let mut num = 0u32;
+            for j in (0..num_of_bytes).rev() {
+                num = (num << 8) | bytes[j];
+                let p = num / sizes[i];
+                bytes[j] = p;
+                num -= p * sizes[i];
+            }
+            nums[i] = num;
+        }
+        nums[0] = bytes[0] | (bytes[1] << 8) | (bytes[2] << 16) | (bytes[3] << 24);
+        Ok(nums)
+    }
+}
+
+/// Bit-level writer producing the compressed coordinate block.
+#[derive(Default)]
+struct BitWriter {
+    data: Vec<u8>,
+    lastbits: u32,
+    lastbyte: u32,
+}
+
+impl BitWriter {
+    fn encode_bits(&mut self, mut num_of_bits: u32, num: u32) {
+        while num_of_bits >= 8 {
+            self.lastbyte = (self.lastbyte << 8) | ((num >> (num_of_bits - 8)) & 0xff);
+            self.data.push((self.lastbyte >> self.lastbits) as u8);
+            num_of_bits -= 8;
+        }
+        if num_of_bits > 0 {
+            self.lastbyte = (self.lastbyte << num_of_bits) | (num & bit_mask(num_of_bits));
+            self.lastbits += num_of_bits;
+            if self.lastbits >= 8 {
+                self.lastbits -= 8;
+                self.data.push((self.lastbyte >> self.lastbits) as u8);
+            }
+        }
+    }
+
+    fn encode_ints(&mut self, num_of_bits: u32, sizes: [u32; 3], nums: [u32; 3]) {
+        let mut bytes = [0u32; 32];
+        let mut num_of_bytes = 0;
+        let mut tmp = nums[0];
+        loop {
+            bytes[num_of_bytes] = tmp & 0xff;
+            num_of_bytes += 1;
+            tmp >>= 8;
+            if tmp == 0 {
+                break;
+            }
+        }
+
+        for i in 1..3 {
+            let mut tmp = nums[i];
+            let mut bytecnt = 0;
+            while bytecnt < num_of_bytes {
+                tmp += bytes[bytecnt] * sizes[i];
+                bytes[bytecnt] = tmp & 0xff;
+                tmp >>= 8;
+                bytecnt += 1;
+            }
+            while tmp != 0 {
+                bytes[bytecnt] = tmp & 0xff;
+                bytecnt += 1;
+                tmp >>= 8;
+            }
+            num_of_bytes = bytecnt;
+        }
+
+        let full_bytes = num_of_bytes as u32 * 8;
+        if num_of_bits >= full_bytes {
+            for &byte in &bytes[..num_of_bytes] {
+                self.encode_bits(8, byte);
+            }
+            self.encode_bits(num_of_bits - full_bytes, 0);
+        } else {
+            for &byte in &bytes[..num_of_bytes - 1] {
+                self.encode_bits(8, byte);
+            }
+            self.encode_bits(num_of_bits + 8 - full_bytes, bytes[num_of_bytes - 1]);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.lastbits > 0 {
+            self.data.push((self.lastbyte << (8 - self.lastbits)) as u8);
+        }
+        self.data
+    }
+}
+
+fn magicint(idx: usize) -> IOResult<i32> {
+    MAGICINTS
+        .get(idx)
+        .copied()
+        .ok_or_else(|| IOError::InvalidFormat(format!("XTC small-index {idx} out of range")))
+}
+
+/// Read the coordinate block of one frame and return raw coordinates in nm.
+fn read_coords<R: Read>(reader: &mut R, num_atoms: usize, wide_count: bool) -> IOResult<Vec<f32>> {
+    let lsize = read_count(reader)?;
+    if lsize != num_atoms {
+        return Err(IOError::ParseError {
+            line: 0,
+            message: format!("XTC coordinate count {lsize} != header atom count {num_atoms}"),
+        });
+    }
+
+    let size3 = num_atoms * 3;
+    if num_atoms <= XTC_UNCOMPRESSED_MAX_ATOMS {
+        let mut coords = vec![0.0f32; size3];
+        reader.read_f32_into::<BigEndian>(&mut coords)?;
+        return Ok(coords);
+    }
+
+    let precision = reader.read_f32::<BigEndian>()?;
+    if precision <= 0.0 {
+        return Err(IOError::InvalidFormat(format!(
+            "XTC precision must be positive, got {precision}"
+        )));
+    }
+    let mut minint = [0i32; 3];
+    let mut maxint = [0i32; 3];
+    reader.read_i32_into::<BigEndian>(&mut minint)?;
+    reader.read_i32_into::<BigEndian>(&mut maxint)?;
+
+    let mut sizeint = [0u32; 3];
+    for d in 0..3 {
+        sizeint[d] = (i64::from(maxint[d]) - i64::from(minint[d]) + 1) as u32;
+    }
+    let mut bitsizeint = [0u32; 3];
+    let bitsize = if (sizeint[0] | sizeint[1] | sizeint[2]) > 0xff_ffff {
+        for d in 0..3 {
+            bitsizeint[d] = sizeofint(sizeint[d]);
+        }
+        0
+    } else {
+        sizeofints(sizeint)
+    };
+
+    let mut smallidx = read_count(reader)?;
+    let mut smaller = magicint(FIRSTIDX.max(smallidx.saturating_sub(1)))? / 2;
+    let mut smallnum = magicint(smallidx)? / 2;
+    let mut sizesmall = [magicint(smallidx)? as u32; 3];
+
+    let byte_count = if wide_count {
+        reader.read_u64::<BigEndian>()? as usize
+    } else {
+        read_count(reader)?
+    };
+    let mut data = vec![0u8; byte_count];
+    reader.read_exact(&mut data)?;
+    let padding = (4 - byte_count % 4) % 4;
+    let mut pad = [0u8; 3];
+    reader.read_exact(&mut pad[..padding])?;
+
+    let inv_precision = 1.0 / precision;
+    let mut bits = BitReader::new(&data);
+    let mut coords = Vec::with_capacity(size3);
+    let push = |c: [i32; 3], coords: &mut Vec<f32>| {
+        coords.extend(c.iter().map(|&v| v as f32 * inv_precision));
+    };
+
+    let mut run: i32 = 0;
+    let mut i = 0;
+    while i < num_atoms {
+        let raw = if bitsize == 0 {
+            [
+                bits.decode_bits(bitsizeint[0])?,
+                bits.decode_bits(bitsizeint[1])?,
+                bits.decode_bits(bitsizeint[2])?,
+            ]
+        } else {
+            bits.decode_ints(bitsize, sizeint)?
+        };
+        i += 1;
+        let this = [
+            (raw[0] as i32).wrapping_add(minint[0]),
+            (raw[1] as i32).wrapping_add(minint[1]),
+            (raw[2] as i32).wrapping_add(minint[2]),
+        ];
+        let mut prev = this;
+
+        let mut is_smaller: i32 = 0;
+        if bits.decode_bits(1)? == 1 {
+            run = bits.decode_bits(5)? as i32;
+            is_smaller = run % 3;
+            run -= is_smaller;
+            is_smaller -= 1;
+        }
+
+        if run > 0 {
+            if i + run as usize / 3 > num_atoms {
+                return Err(IOError::InvalidFormat(
+                    "XTC run length exceeds atom count".to_string(),
+                ));
+            }
+            for k in (0..run).step_by(3) {
+                let small = bits.decode_ints(smallidx as u32, sizesmall)?;
+                i += 1;
+                let mut cur = [
+                    (small[0] as i32).wrapping_add(prev[0]) - smallnum,
+                    (small[1] as i32).wrapping_add(prev[1]) - smallnum,
+                    (small[2] as i32).wrapping_add(prev[2]) - smallnum,
+                ];
+                if k == 0 {
+                    // The encoder swaps the first two atoms of a run (water O/H).
+                    std::mem::swap(&mut cur, &mut prev);
+                    push(prev, &mut coords);
+                } else {
+                    prev = cur;
+                }
+                push(cur, &mut coords);
+            }
+        } else {
+            push(this, &mut coords);
+        }
+
+        smallidx = (smallidx as i32 + is_smaller) as usize;
+        if is_smaller < 0 {
+            smallnum = smaller;
+            smaller = if smallidx > FIRSTIDX {
+                magicint(smallidx - 1)? / 2
+            } else {
+                0
+            };
+        } else if is_smaller > 0 {
+            smaller = smallnum;
+            smallnum = magicint(smallidx)? / 2;
+        }
+        sizesmall = [magicint(smallidx)? as u32; 3];
+    }
+
+    Ok(coords)
+}
+
+/// Compress coordinates (nm) into an XTC coordinate block.
+fn write_coords<W: Write>(writer: &mut W, coords: &[[f32; 3]], precision: f32) -> IOResult<()> {
+    let size = coords.len();
+    writer.write_i32::<BigEndian>(size as i32)?;
+    if size <= XTC_UNCOMPRESSED_MAX_ATOMS {
+        for c in coords {
+            for &v in c {
+                writer.write_f32::<BigEndian>(v)?;
+            }
+        }
+        return Ok(());
+    }
+    writer.write_f32::<BigEndian>(precision)?;
+
+    let mut ints: Vec<[i32; 3]> = Vec::with_capacity(size);
+    let mut minint = [i32::MAX; 3];
+    let mut maxint = [i32::MIN; 3];
+    let mut mindiff = i64::MAX;
+    let mut old = [0i32; 3];
+    for (atom, c) in coords.iter().enumerate() {
+        let mut lint = [0i32; 3];
+        for d in 0..3 {
+            let lf = if c[d] >= 0.0 {
+                c[d] * precision + 0.5
+            } else {
+                c[d] * precision - 0.5
+            };
+            if lf.abs() > MAXABS {
+                return Err(IOError::InvalidFormat(
+                    "XTC coordinate too large for precision".to_string(),
+                ));
+            }
+            lint[d] = lf as i32;
+            minint[d] = minint[d].min(lint[d]);
+            maxint[d] = maxint[d].max(lint[d]);
+        }
+        let diff: i64 = (0..3)
+            .map(|d| (i64::from(old[d]) - i64::from(lint[d])).abs())
+            .sum();
+        if atom > 0 && diff < mindiff {
+            mindiff = diff;
+        }
+        old = lint;
+        ints.push(lint);
+    }
+    for &v in minint.iter().chain(maxint.iter()) {
+        writer.write_i32::<BigEndian>(v)?;
+    }
+
+    let mut sizeint = [0u32; 3];
+    for d in 0..3 {
+        if (maxint[d] as f32 - minint[d] as f32) >= MAXABS {
+            return Err(IOError::InvalidFormat(
+                "XTC coordinate range too large for precision".to_string(),
+            ));
+        }
+        sizeint[d] = (maxint[d] - minint[d] + 1) as u32;
+    }
+    let mut bitsizeint = [0u32; 3];
+    let bitsize = if (sizeint[0] | sizeint[1] | sizeint[2]) > 0xff_ffff {
+        for d in 0..3 {
+            bitsizeint[d] = sizeofint(sizeint[d]);
+        }
+        0
+    } else {
+        sizeofints(sizeint)
+    };
+
+    let mut smallidx = FIRSTIDX;
+    while smallidx < LASTIDX - 1 && i64::from(MAGICINTS[smallidx]) < mindiff {
+        smallidx += 1;
+    }
+    writer.write_i32::<BigEndian>(smallidx as i32)?;
+
+    let maxidx = (smallidx + 8).min(LASTIDX - 1);
+    let minidx = maxidx - 8;
+    let mut smaller = MAGICINTS[FIRSTIDX.max(smallidx - 1)] / 2;
+    let mut smallnum = MAGICINTS[smallidx] / 2;
+    let mut sizesmall = [MAGICINTS[smallidx] as u32; 3];
+    let larger = MAGICINTS[maxidx] / 2;
+
+    let within = |a: [i32; 3], b: [i32; 3], limit: i32| {
+        (0..3).all(|d| (i64::from(a[d]) - i64::from(b[d])).abs() < i64::from(limit))
+    };
+
+    let mut bits = BitWriter::default();
+    let mut prev = [0i32; 3];
+    let mut prevrun: i32 = -1;
+    let mut i = 0;
+    while i < size {
+        let mut is_smaller: i32 = if smallidx < maxidx && i >= 1 && within(ints[i], prev, larger) {
+            1
+        } else if smallidx > minidx {
+            -1
+        } else {
+            0
+        };
+        let mut is_small = false;
+        if i + 1 < size && within(ints[i], ints[i + 1], smallnum) {
+            ints.swap(i, i + 1);
+            is_small = true;
+        }
+
+        let tmp = [
+            (ints[i][0] - minint[0]) as u32,
+            (ints[i][1] - minint[1]) as u32,
+            (ints[i][2] - minint[2]) as u32,
+        ];
+        if bitsize == 0 {
+            for d in 0..3 {
+                bits.encode_bits(bitsizeint[d], tmp[d]);
+            }
+        } else {
+            bits.encode_ints(bitsize, sizeint, tmp);
+        }
+        prev = ints[i];
+        i += 1;
+
+        let mut run = 0usize;
+        let mut tmpcoord = [0u32; 24];
+        if !is_small && is_smaller == -1 {
+            is_smaller = 0;
+        }
+        while is_small && run < 24 {
+            let tmpsum: i64 = (0..3)
+                .map(|d| {
+                    let delta = i64::from(ints[i][d]) - i64::from(prev[d]);
+                    delta * delta
+                })
+                .sum();
+            if is_smaller == -1 && tmpsum >= i64::from(smaller) * i64::from(smaller) {
+                is_smaller = 0;
+            }
+            for d in 0..3 {
+                tmpcoord[run] = (ints[i][d] - prev[d] + smallnum) as u32;
+                run += 1;
+            }
+            prev = ints[i];
+            i += 1;
+            is_small = i < size && within(ints[i], prev, smallnum);
+        }
+
+        if run as i32 != prevrun || is_smaller != 0 {
+            prevrun = run as i32;
+            bits.encode_bits(1, 1);
+            bits.encode_bits(5, (run as i32 + is_smaller + 1) as u32);
+        } else {
+            bits.encode_bits(1, 0);
+        }
+        for k in (0..run).step_by(3) {
+            bits.encode_ints(
+                smallidx as u32,
+                sizesmall,
+                [tmpcoord[k], tmpcoord[k + 1], tmpcoord[k + 2]],
+            );
+        }
+
+        if is_smaller != 0 {
+            smallidx = (smallidx as i32 + is_smaller) as usize;
+            if is_smaller < 0 {
+                smallnum = smaller;
+                smaller = MAGICINTS[smallidx - 1] / 2;
+            } else {
+                smaller = smallnum;
+                smallnum = MAGICINTS[smallidx] / 2;
+            }
+            sizesmall = [MAGICINTS[smallidx] as u32; 3];
+        }
+    }
+
+    let data = bits.finish();
+    writer.write_i32::<BigEndian>(data.len() as i32)?;
+    writer.write_all(&data)?;
+    let padding = (4 - data.len() % 4) % 4;
+    writer.write_all(&[0u8; 3][..padding])?;
+    Ok(())
+}
+
+/// Read one full XTC frame (in nm) from the current reader position.
+fn read_frame_at<R: Read>(reader: &mut R, frame_index: usize) -> IOResult<FrameData> {
+    let header = XtcFrameHeader::read(reader)?;
+    let coords = read_coords(reader, header.num_atoms, header.magic == XTC_NEW_MAGIC)?;
+
+    let mut frame = FrameData::new(frame_index, header.time * PS_TO_FS);
+    frame.cell = header.cell();
+    frame
+        .metadata
+        .insert("step".to_string(), header.step.to_string());
+    frame.positions = coords
+        .chunks_exact(3)
+        .map(|c| Vec3::new(c[0], c[1], c[2]))
+        .collect();
+    frame.length_unit = LengthUnit::Nanometer;
+    Ok(frame)
+}
+
+/// Indexed metadata for an XTC file.
+#[derive(Debug, Clone)]
+pub struct XtcIndex {
+    pub num_atoms: usize,
+    pub num_frames: usize,
+    /// Time between the first two frames (fs).
+    pub time_step: f32,
+    pub frame_offsets: Vec<u64>,
+    pub metadata: TrajectoryMetadata,
+}
+
+/// Scan an XTC file once and record the byte offset of each frame.
+///
+/// Only headers and byte counts are read; compressed coordinate blocks are
+/// skipped. A truncated trailing frame (e.g. from a running simulation) is
+/// dropped with a warning.
+pub fn build_xtc_index(path: &Path) -> IOResult<XtcIndex> {
+    let file = File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut frame_offsets = Vec::new();
+    let mut times = Vec::with_capacity(2);
+    let mut num_atoms = 0usize;
+    let mut last_step = 0i32;
+    let mut offset = 0u64;
+
+    while offset < file_len {
+        reader.seek(SeekFrom::Start(offset))?;
+        let magic = match reader.read_i32::<BigEndian>() {
+            Ok(m) => m,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(IOError::Io(e)),
+        };
+        if magic != XTC_MAGIC && magic != XTC_NEW_MAGIC {
+            return Err(IOError::ParseError {
+                line: frame_offsets.len(),
+                message: format!("Invalid XTC magic {magic} at byte offset {offset}"),
+            });
+        }
+
+        let scanned = scan_frame_body(&mut reader, magic);
+        let (count, step, time) = match scanned {
+            Ok(v) => v,
+            Err(IOError::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                warn!("XTC: truncated frame at byte offset {offset}, ignoring");
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+
+        if frame_offsets.is_empty() {
+            num_atoms = count;
+        } else if count != num_atoms {
+            return Err(IOError::ParseError {
+                line: frame_offsets.len(),
+                message: format!(
+                    "Atom count changed from {num_atoms} to {count} in frame {}",
+                    frame_offsets.len()
+                ),
+            });
+        }
+
+        let next = reader.stream_position()?;
+        if next > file_len {
+            warn!("XTC: truncated frame at byte offset {offset}, ignoring");
+            break;
+        }
+
+        frame_offsets.push(offset);
+        if times.len() < 2 {
+            times.push(time);
+        }
+        last_step = step;
+        offset = next;
+    }
+
+    if frame_offsets.is_empty() {
+        return Err(IOError::ParseError {
+            line: 0,
+            message: "No frames found in XTC file".into(),
+        });
+    }
+
+    let time_step = match times.as_slice() {
+        [t0, t1] if t1 > t0 => (t1 - t0) * PS_TO_FS,
+        _ => 1.0,
+    };
+
+    Ok(XtcIndex {
+        num_atoms,
+        num_frames: frame_offsets.len(),
+        time_step,
+        frame_offsets,
+        metadata: TrajectoryMetadata {
+            software: "GROMACS".to_string(),
+            num_steps: Some(last_step.max(0) as u64),
+            ..Default::default()
+        },
+    })
+}
+
+/// Skip over one frame body (after the magic); returns atom count, step and time (ps).
+fn scan_frame_body(reader: &mut BufReader<File>, magic: i32) -> IOResult<(usize, i32, f32)> {
+    let num_atoms = read_count(reader)?;
+    let step = reader.read_i32::<BigEndian>()?;
+    let time = reader.read_f32::<BigEndian>()?;
+    reader.seek_relative(XTC_HEADER_TAIL_BYTES - 8)?;
+
+    let lsize = read_count(reader)?;
+    if lsize != num_atoms {
+        return Err(IOError::ParseError {
+            line: 0,
+            message: format!("XTC coordinate count {lsize} != header atom count {num_atoms}"),
+        });
+    }
+
+    if num_atoms <= XTC_UNCOMPRESSED_MAX_ATOMS {
+        reader.seek_relative((num_atoms * 3 * 4) as i64)?;
+    } else {
+        reader.seek_relative(XTC_COMPRESSED_PREAMBLE_BYTES)?;
+        let byte_count = if magic == XTC_NEW_MAGIC {
+            reader.read_u64::<BigEndian>()?
+        } else {
+            read_count(reader)? as u64
+        };
+        let padded = byte_count.div_ceil(4) * 4;
+        reader.seek_relative(padded as i64)?;
+    }
+
+    Ok((num_atoms, step, time))
+}
+
+/// Random-access XTC frame provider backed by a seekable file handle.
+pub struct XtcFrameProvider {
+    reader: Arc<Mutex<BufReader<File>>>,
+    index: XtcIndex,
+    file_path: PathBuf,
+}
+
+impl XtcFrameProvider {
+    pub fn open(path: &Path) -> IOResult<Self> {
+        let source = crate::io::compression::seekable_path(path)?;
+        let index = build_xtc_index(&source)?;
+        let file =
+            File::open(&source).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+        Ok(Self {
+            reader: Arc::new(Mutex::new(BufReader::new(file))),
+            index,
+            file_path: path.to_path_buf(),
+        })
+    }
+
+    pub fn index(&self) -> &XtcIndex {
+        &self.index
+    }
+
+    pub fn should_stream(num_atoms: usize, num_frames: usize) -> bool {
+        crate::io::streaming::should_stream_trajectory(num_atoms, num_frames)
+    }
+
+    /// Read every frame, in nm.
+    pub fn load_all_frames(&self) -> IOResult<Vec<FrameData>> {
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("XTC reader lock poisoned".to_string()))?;
+        let mut frames = Vec::with_capacity(self.index.num_frames);
+        for (i, &offset) in self.index.frame_offsets.iter().enumerate() {
+            reader.seek(SeekFrom::Start(offset))?;
+            frames.push(read_frame_at(&mut *reader, i)?);
+        }
+        Ok(frames)
+    }
+}
+
+impl FrameProvider for XtcFrameProvider {
+    fn num_frames(&self) -> usize {
+        self.index.num_frames
+    }
+
+    fn num_atoms(&self) -> usize {
+        self.index.num_atoms
+    }
+
+    fn time_step(&self) -> f32 {
+        self.index.time_step
+    }
+
+    fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    fn metadata(&self) -> &TrajectoryMetadata {
+        &self.index.metadata
+    }
+
+    fn get_frame(&self, index: usize) -> IOResult<FrameData> {
+        let offset = *self
+            .index
+            .frame_offsets
+            .get(index)
+            .ok_or_else(|| IOError::ParseError {
+                line: 0,
+                message: format!(
+                    "Frame index {index} out of range ({} frames)",
+                    self.index.num_frames
+                ),
+            })?;
+
+        let mut reader = self
+            .reader
+            .lock()
+            .map_err(|_| IOError::InvalidFormat("XTC reader lock poisoned".to_string()))?;
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut frame = read_frame_at(&mut *reader, index)?;
+        frame.normalize_units();
+        Ok(frame)
+    }
+}
+
+/// Registry entry: compressed GROMACS trajectories, paired with a topology.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::XTC,
+    name: "GROMACS XTC",
+    extensions: &["xtc"],
+    capabilities: FormatCapabilities {
+        topology: false,
+        trajectory: true,
+        streaming: true,
+        writable: true,
+    },
+    needs_topology: true,
+    sniff,
+    load: Some(load),
+    read_topology: None,
+};
+
+fn sniff(data: &[u8]) -> Confidence {
+    if XtcParser::is_xtc_bytes(data) {
+        Confidence::CERTAIN
+    } else {
+        Confidence::NONE
+    }
+}
+
+fn load(path: &Path, topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    crate::io::topology::load_coordinates(topology_path, |_| crate::io::streaming::open_xtc(path))
+}
+
+/// XTC format parser
+pub struct XtcParser;
+
+impl XtcParser {
+    /// Parse an XTC file and return trajectory data in nm (loads all frames into RAM).
+    pub fn parse_file(path: &Path) -> IOResult<Trajectory> {
+        let provider = XtcFrameProvider::open(path)?;
+        info!(
+            "XTC: {} frames, {} atoms",
+            provider.num_frames(),
+            provider.num_atoms()
+        );
+
+        let mut trajectory = Trajectory::new(
+            path.to_path_buf(),
+            provider.num_atoms(),
+            provider.time_step(),
+        );
+        trajectory.metadata = provider.metadata().clone();
+        trajectory.length_unit = LengthUnit::Nanometer;
+        for frame in provider.load_all_frames()? {
+            trajectory.add_frame(frame);
+        }
+        Ok(trajectory)
+    }
+
+    /// Check whether bytes look like an XTC file (big-endian magic 1995 or 2023).
+    pub fn is_xtc_bytes(data: &[u8]) -> bool {
+        data.len() >= 4 && {
+            let magic = i32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+            magic == XTC_MAGIC || magic == XTC_NEW_MAGIC
+        }
+    }
+}
+
+/// Write trajectory frames to XTC format
+pub struct XtcWriter;
+
+impl XtcWriter {
+    /// Write a trajectory to an XTC file at the default precision.
+    pub fn write_trajectory(path: &Path, trajectory: &Trajectory) -> IOResult<()> {
+        let mut file = std::io::BufWriter::new(File::create(path)?);
+        for (i, frame) in trajectory.frames.iter().enumerate() {
+            let step = frame
+                .metadata
+                .get("step")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(i as i32);
+            Self::write_frame(&mut file, frame, step, XTC_DEFAULT_PRECISION)?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Write a single frame (time in fs) as one XTC frame, converting
+    /// positions and box to nm from the frame's unit.
+    pub fn write_frame<W: Write>(
+        writer: &mut W,
+        frame: &FrameData,
+        step: i32,
+        precision: f32,
+    ) -> IOResult<()> {
+        let to_nm = frame.length_unit.to_angstrom() / LengthUnit::Nanometer.to_angstrom();
+        let coords: Vec<[f32; 3]> = frame
+            .positions
+            .iter()
+            .map(|p| (*p * to_nm).to_array())
+            .collect();
+
+        writer.write_i32::<BigEndian>(XTC_MAGIC)?;
+        writer.write_i32::<BigEndian>(coords.len() as i32)?;
+        writer.write_i32::<BigEndian>(step)?;
+        writer.write_f32::<BigEndian>(frame.time / PS_TO_FS)?;
+        let box_nm = frame
+            .cell
+            .map(|cell| cell.vectors.map(|v| v * to_nm))
+            .unwrap_or([Vec3::ZERO; 3]);
+        for row in box_nm {
+            for value in row.to_array() {
+                writer.write_f32::<BigEndian>(value)?;
+            }
+        }
+        write_coords(writer, &coords, precision)
+    }
+}
+
+/// Register XTC parsing systems with Bevy
+pub fn register(_app: &mut App) {
+    info!("XTC parser registered");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Water-like clusters: close O/H triples exercise the run-length path.
+    fn water_frame(index: usize, num_waters: usize) -> FrameData {
+        let mut frame = FrameData::new(index, index as f32 * 2000.0);
+        frame.cell = Some(UnitCell::orthorhombic([30.0, 31.0, 32.0]));
+        let shift = index as f32 * 0.25;
+        for w in 0..num_waters {
+            let base = Vec3::new(
+                (w % 7) as f32 * 3.1 + shift,
+                (w / 7) as f32 * 2.9 - 4.0,
+                (w % 3) as f32 * 3.3 + 1.5,
+            );
+            let id = w * 3;
+            frame.set_position(id, base);
+            frame.set_position(id + 1, base + Vec3::new(0.957, 0.0, 0.0));
+            frame.set_position(id + 2, base + Vec3::new(-0.24, 0.927, 0.0));
+        }
+        frame
+    }
+
+    fn write_frames(path: &Path, frames: &[FrameData]) {
+        let mut trajectory = Trajectory::new(path.to_path_buf(), frames[0].positions.len(), 2000.0);
+        for frame in frames {
+            trajectory.add_frame(frame.clone());
+        }
+        XtcWriter::write_trajectory(path, &trajectory).unwrap();
+    }
+
+    fn assert_frames_close(expected: &FrameData, actual: &FrameData) {
+        assert_eq!(expected.positions.len(), actual.positions.len());
+        for (id, a) in expected.positions.iter().enumerate() {
+            let b = actual.position(id).unwrap();
+            // Default precision is 1e-3 nm = 0.01 Å.
+            assert!(
+                (*a - b).abs().max_element() <= 0.0051,
+                "atom {id}: {a:?} vs {b:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_xtc_magic_detection() {
+        assert!(XtcParser::is_xtc_bytes(&XTC_MAGIC.to_be_bytes()));
+        assert!(XtcParser::is_xtc_bytes(&XTC_NEW_MAGIC.to_be_bytes()));
+        assert!(!XtcParser::is_xtc_bytes(&84_i32.to_le_bytes()));
+        assert!(!XtcParser::is_xtc_bytes(b"AT"));
+    }
+
+    #[test]
+    fn test_sizeofints_matches_xdrfile() {
+        assert_eq!(sizeofint(0), 0);
+        assert_eq!(sizeofint(1), 1);
+        assert_eq!(sizeofint(255), 8);
+        assert_eq!(sizeofint(256), 9);
+        assert_eq!(sizeofints([8, 8, 8]), 10);
+        assert_eq!(sizeofints([256, 256, 256]), 25);
+        assert_eq!(sizeofints([1000, 1000, 1000]), 30);
+    }
+
+    #[test]
+    fn test_bit_roundtrip() {
+        let mut writer = BitWriter::default();
+        writer.encode_bits(1, 1);
+        writer.encode_bits(5, 19);
+        writer.encode_ints(30, [1000, 1000, 1000], [999, 0, 512]);
+        writer.encode_bits(20, 0xABCDE);
+        let data = writer.finish();
+
+        let mut reader = BitReader::new(&data);
+        assert_eq!(reader.decode_bits(1).unwrap(), 1);
+        assert_eq!(reader.decode_bits(5).unwrap(), 19);
+        assert_eq!(
+            reader.decode_ints(30, [1000, 1000, 1000]).unwrap(),
+            [999, 0, 512]
+        );
+        assert_eq!(reader.decode_bits(20).unwrap(), 0xABCDE);
+    }
+
+    #[test]
+    fn test_small_frame_is_uncompressed() {
+        let mut frame = FrameData::new(0, 0.0);
+        frame.set_position(0, Vec3::new(1.0, 2.0, 3.0));
+        frame.set_position(1, Vec3::new(-4.0, 5.5, 0.25));
+
+        let mut buffer = Vec::new();
+        XtcWriter::write_frame(&mut buffer, &frame, 0, XTC_DEFAULT_PRECISION).unwrap();
+        // Header (13 words) + natoms + 6 raw floats.
+        assert_eq!(buffer.len(), (13 + 1 + 6) * 4);
+
+        let mut decoded = read_frame_at(&mut buffer.as_slice(), 0).unwrap();
+        assert_eq!(decoded.length_unit, LengthUnit::Nanometer);
+        decoded.normalize_units();
+        let second = decoded.position(1).unwrap();
+        assert!(second.distance(Vec3::new(-4.0, 5.5, 0.25)) < 1e-5);
+        assert_eq!(decoded.cell, None);
+    }
+
+    /// Bytes laid out field by field as `gmx trjconv` writes a 3-atom water
+    /// frame (xdrfile stores systems of up to 9 atoms as plain floats), built
+    /// without `XtcWriter` so reader and writer cannot share a layout bug.
+    #[test]
+    fn test_reads_gromacs_small_system_layout() {
+        let mut bytes = Vec::new();
+        for word in [XTC_MAGIC, 3, 100] {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        let coords_nm = [
+            0.126f32, 0.639, 0.322, 0.187, 0.713, 0.333, 0.145, 0.584, 0.400,
+        ];
+        let box_nm = [3.0f32, 0.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 3.0];
+        bytes.extend_from_slice(&0.2f32.to_be_bytes());
+        for value in box_nm {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        bytes.extend_from_slice(&3i32.to_be_bytes());
+        for value in coords_nm {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        assert_eq!(bytes.len(), 92);
+
+        let mut frame = read_frame_at(&mut bytes.as_slice(), 0).unwrap();
+        assert_eq!(frame.num_atoms(), 3);
+        assert_eq!(frame.metadata.get("step").map(String::as_str), Some("100"));
+        assert!((frame.time - 200.0).abs() < 1e-3);
+        assert_eq!(frame.length_unit, LengthUnit::Nanometer);
+        assert_eq!(frame.position(2), Some(Vec3::new(0.145, 0.584, 0.4)));
+        assert_eq!(frame.cell.unwrap().lengths[0], 3.0);
+
+        frame.normalize_units();
+        let hydrogen = frame.position(2).unwrap();
+        assert!(hydrogen.distance(Vec3::new(1.45, 5.84, 4.0)) < 1e-5);
+        let cell = frame.cell.unwrap();
+        assert!((cell.lengths[0] - 30.0).abs() < 1e-4);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("water.xtc");
+        std::fs::write(&path, &bytes).unwrap();
+        assert_eq!(build_xtc_index(&path).unwrap().num_frames, 1);
+    }
+
+    #[test]
+    fn test_compressed_roundtrip_and_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("water.xtc");
+        let frames: Vec<FrameData> = (0..4).map(|i| water_frame(i, 40)).collect();
+        write_frames(&path, &frames);
+
+        let index = build_xtc_index(&path).unwrap();
+        assert_eq!(index.num_atoms, 120);
+        assert_eq!(index.num_frames, 4);
+        assert_eq!(index.frame_offsets[0], 0);
+        assert!((index.time_step - 2000.0).abs() < 1e-3);
+
+        let provider = XtcFrameProvider::open(&path).unwrap();
+        for i in [3, 0, 2, 1] {
+            let frame = provider.get_frame(i).unwrap();
+            assert_frames_close(&frames[i], &frame);
+            assert_eq!(
+                frame.metadata.get("step").map(String::as_str),
+                Some(&*i.to_string())
+            );
+            let cell = frame.cell.unwrap();
+            assert!((cell.lengths[1] - 31.0).abs() < 1e-4);
+        }
+        assert!(provider.get_frame(4).is_err());
+
+        // The in-memory parser leaves unit conversion to the loader
+        let mut parsed = XtcParser::parse_file(&path).unwrap();
+        assert_eq!(parsed.length_unit, LengthUnit::Nanometer);
+        let x_nm = parsed.frames[3].position(0).unwrap().x;
+        assert!((x_nm * 10.0 - frames[3].position(0).unwrap().x).abs() < 0.01);
+        parsed.normalize_units();
+        assert_frames_close(&frames[3], &parsed.frames[3]);
+    }
+
+    #[test]
+    fn test_scattered_coordinates_roundtrip() {
+        let mut frame = FrameData::new(0, 0.0);
+        let mut state = 12345u32;
+        for id in 0..500 {
+            let mut next = || {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                ((state >> 8) % 100_000) as f32 / 100.0 - 500.0
+            };
+            frame.set_position(id, Vec3::new(next(), next(), next()));
+        }
+
+        let mut buffer = Vec::new();
+        XtcWriter::write_frame(&mut buffer, &frame, 7, XTC_DEFAULT_PRECISION).unwrap();
+        let mut decoded = read_frame_at(&mut buffer.as_slice(), 0).unwrap();
+        decoded.normalize_units();
+        assert_frames_close(&frame, &decoded);
+    }
+
+    #[test]
+    fn test_truncated_trailing_frame_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("partial.xtc");
+        let frames: Vec<FrameData> = (0..3).map(|i| water_frame(i, 20)).collect();
+        write_frames(&path, &frames);
+
+        let len = std::fs::metadata(&path).unwrap().len();
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 10).unwrap();
+
+        let index = build_xtc_index(&path).unwrap();
+        assert_eq!(index.num_frames, 2);
+    }
+}
@@ -4,16 +4,77 @@
 //! Line 1: Number of atoms
 //! Line 2: Comment line (title)
 //! Lines 3+: Element symbol X Y Z (and optional fields)
+//!
+//! Extended XYZ comment lines (`Lattice=...`, `Properties=...`, `energy=...`)
+//! are understood through [`crate::io::extxyz`].
 
 use crate::core::atom::{AtomData, Element};
 use crate::core::trajectory::{FrameData, Trajectory};
-use crate::io::{IOError, IOResult};
+use crate::io::extxyz::ExtXyzHeader;
+use crate::io::registry::{
+    sniff_text, Confidence, FormatCapabilities, FormatDescriptor, LoadedFile,
+};
+use crate::io::{FileFormat, IOError, IOResult};
 use bevy::prelude::*;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 
+/// Registry entry: XYZ and extended XYZ.
+pub const FORMAT: FormatDescriptor = FormatDescriptor {
+    format: FileFormat::XYZ,
+    name: "XYZ",
+    extensions: &["xyz", "extxyz"],
+    capabilities: FormatCapabilities {
+        topology: false,
+        trajectory: true,
+        streaming: true,
+        writable: true,
+    },
+    needs_topology: false,
+    sniff,
+    load: Some(load),
+    read_topology: None,
+};
+
+/// An atom count, a comment line, then `species x y z` lines. The count
+/// alone is weak evidence: plenty of files start with a number.
+fn sniff(data: &[u8]) -> Confidence {
+    let Some(text) = sniff_text(data) else {
+        return Confidence::NONE;
+    };
+    let mut lines = text.lines();
+    let Some(count) = lines
+        .next()
+        .and_then(|line| line.trim().parse::<usize>().ok())
+    else {
+        return Confidence::NONE;
+    };
+    let is_atom_line = |line: &str| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        fields.len() >= 4
+            && fields[0]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphanumeric())
+            && fields[1..4].iter().all(|f| f.parse::<f32>().is_ok())
+    };
+    let checked = count.min(3);
+    let atom_lines: Vec<&str> = lines.skip(1).take(checked).collect();
+    if count > 0 && atom_lines.len() == checked && atom_lines.iter().all(|l| is_atom_line(l)) {
+        Confidence::LIKELY
+    } else {
+        Confidence::WEAK
+    }
+}
+
+fn load(path: &Path, _topology_path: Option<&Path>) -> IOResult<LoadedFile> {
+    let (trajectory, frame_provider) = crate::io::streaming::open_xyz(path)?;
+    let atom_data = XYZParser::first_frame_atoms(&trajectory)?;
+    Ok((trajectory, atom_data, Vec::new(), frame_provider, false))
+}
+
 /// XYZ format parser
 pub struct XYZParser;
 
@@ -25,9 +86,7 @@ impl XYZParser {
 
     /// Parse via buffered reader (legacy path for tests and small strings).
     pub fn parse_file_buffered(path: &Path) -> IOResult<Trajectory> {
-        let file =
-            File::open(path).map_err(|_e| IOError::FileNotFound(path.display().to_string()))?;
-        let reader = BufReader::new(file);
+        let reader = crate::io::compression::open(path)?;
         Self::parse_reader(reader, path.to_path_buf())
     }
 
@@ -83,69 +142,36 @@ impl XYZParser {
                 }
             }
 
-            // Read comment line
-            if let Some((_comment_line_num, comment_line)) = line_iter.next() {
-                let comment = comment_line.trim();
-
-                // Try to extract time from comment line
-                // Common formats: "time=100.0", "t=100.0", "frame=10", "i=10"
-                if let Some(time_str) = comment
-                    .split_whitespace()
-                    .find(|s| s.starts_with("time=") || s.starts_with("t="))
-                {
-                    if let Some(time_str) = time_str.split('=').nth(1) {
-                        if let Ok(t) = time_str.parse::<f32>() {
-                            time_step = t;
-                        }
-                    }
+            // Read comment line (plain title or extxyz key=value pairs)
+            let header = match line_iter.next() {
+                Some((comment_line_num, comment_line)) => {
+                    ExtXyzHeader::parse(comment_line.trim(), comment_line_num)?
                 }
+                None => ExtXyzHeader::parse("", line_num)?,
+            };
+
+            // Common time formats: "time=100.0", "t=100.0"
+            if let Some(t) = header.time {
+                time_step = t;
             }
 
             // Read atom positions
             let mut frame = FrameData::new(frame_index, frame_index as f32 * time_step);
+            header.apply_to_frame(&mut frame);
+            frame.positions.reserve(num_atoms);
             let mut atom_data_map = HashMap::new();
 
             for i in 0..num_atoms {
                 if let Some((atom_line_num, atom_line)) = line_iter.next() {
-                    let parts: Vec<&str> = atom_line.split_whitespace().collect();
-
-                    if parts.len() < 4 {
-                        return Err(IOError::ParseError {
-                            line: atom_line_num,
-                            message: format!(
-                                "Expected at least 4 fields (element X Y Z), got {}",
-                                parts.len()
-                            ),
-                        });
-                    }
-
-                    // Parse element
-                    let element = Element::from_symbol(parts[0]).unwrap_or_else(|_| {
-                        warn!("Unknown element: {}, using Unknown", parts[0]);
-                        Element::Unknown
-                    });
-
-                    // Parse position
-                    let x = parts[1].parse::<f32>().map_err(|_| IOError::ParseError {
-                        line: atom_line_num,
-                        message: format!("Invalid X coordinate: {}", parts[1]),
-                    })?;
-                    let y = parts[2].parse::<f32>().map_err(|_| IOError::ParseError {
-                        line: atom_line_num,
-                        message: format!("Invalid Y coordinate: {}", parts[2]),
-                    })?;
-                    let z = parts[3].parse::<f32>().map_err(|_| IOError::ParseError {
-                        line: atom_line_num,
-                        message: format!("Invalid Z coordinate: {}", parts[3]),
-                    })?;
-
-                    let position = Vec3::new(x, y, z);
-
-                    // Set position in frame
-                    frame.set_position(i as u32, position);
+                    header.read_atom(&mut frame, i, atom_line, atom_line_num)?;
 
                     // Create atom data (only for first frame)
                     if frame_index == 0 {
+                        let symbol = header.species(atom_line).unwrap_or("X");
+                        let element = Element::from_symbol(symbol).unwrap_or_else(|_| {
+                            warn!("Unknown element: {}, using Unknown", symbol);
+                            Element::Unknown
+                        });
                         let atom_data = AtomData::new(
                             i as u32,
                             element,
@@ -182,6 +208,62 @@ impl XYZParser {
         Ok(trajectory)
     }
 
+    /// Atom metadata from the first frame's species column (works with
+    /// streaming trajectories, whose frames are not in memory).
+    pub fn first_frame_atoms(trajectory: &Trajectory) -> IOResult<Vec<AtomData>> {
+        let mut atom_data = Vec::new();
+
+        if trajectory.num_atoms > 0 && trajectory.file_path.exists() {
+            let reader = crate::io::compression::open(&trajectory.file_path)?;
+            let mut lines = reader.lines();
+            let mut atom_index = 0;
+
+            // Skip first line (number of atoms)
+            let _ = lines.next();
+            // The comment line may carry an extxyz Properties schema
+            let comment = lines.next().and_then(Result::ok).unwrap_or_default();
+            let header = ExtXyzHeader::parse(comment.trim(), 1)?;
+
+            // Parse atom lines from the first frame
+            while atom_index < trajectory.num_atoms {
+                if let Some(Ok(line)) = lines.next() {
+                    if let Some(symbol) = header.species(&line) {
+                        let element = Element::from_symbol(symbol).unwrap_or_else(|_| {
+                            warn!("Unknown element: {}, using Unknown", symbol);
+                            Element::Unknown
+                        });
+
+                        atom_data.push(AtomData::new(
+                            atom_index as u32,
+                            element,
+                            0,
+                            "UNK".to_string(),
+                            "A".to_string(),
+                            symbol.to_string(),
+                        ));
+
+                        atom_index += 1;
+                    }
+                } else {
+                    break;
+                }
+            }
+        } else if let Some(first_frame) = trajectory.get_frame(0) {
+            for atom_id in 0..first_frame.num_atoms() as u32 {
+                atom_data.push(AtomData::new(
+                    atom_id,
+                    Element::Unknown,
+                    0,
+                    "UNK".to_string(),
+                    "A".to_string(),
+                    format!("ATOM{atom_id}"),
+                ));
+            }
+        }
+
+        Ok(atom_data)
+    }
+
     /// Parse multiple frames from an XYZ file
     pub fn parse_multi_frame(path: &Path) -> IOResult<Trajectory> {
         Self::parse_file(path)
@@ -189,10 +271,8 @@ impl XYZParser {
 
     /// Stream frames from a large XYZ file (memory-efficient)
     pub fn stream_frames(path: &Path) -> IOResult<FrameStream> {
-        let file =
-            File::open(path).map_err(|_e| IOError::FileNotFound(path.display().to_string()))?;
         Ok(FrameStream {
-            reader: BufReader::new(file),
+            reader: crate::io::compression::open(path)?,
             _file_path: path.to_path_buf(),
             current_frame: 0,
             num_atoms: 0,
@@ -203,7 +283,7 @@ impl XYZParser {
 
 /// Stream frames from an XYZ file
 pub struct FrameStream {
-    reader: BufReader<File>,
+    reader: Box<dyn BufRead + Send>,
     _file_path: PathBuf,
     current_frame: usize,
     num_atoms: usize,
@@ -234,39 +314,21 @@ impl FrameStream {
         // Read comment line
         line.clear();
         self.reader.read_line(&mut line)?;
+        let first_line = self.current_frame * (self.num_atoms + 2);
+        let header = ExtXyzHeader::parse(line.trim(), first_line + 1)?;
 
         // Parse atoms
         let mut frame = FrameData::new(
             self.current_frame,
             self.current_frame as f32 * self.time_step,
         );
+        header.apply_to_frame(&mut frame);
+        frame.positions.reserve(num_atoms);
 
         for i in 0..num_atoms {
             line.clear();
             self.reader.read_line(&mut line)?;
-
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() < 4 {
-                return Err(IOError::ParseError {
-                    line: self.current_frame * (self.num_atoms + 2) + 2 + i,
-                    message: format!("Expected at least 4 fields, got {}", parts.len()),
-                });
-            }
-
-            let x = parts[1].parse::<f32>().map_err(|_| IOError::ParseError {
-                line: self.current_frame * (self.num_atoms + 2) + 2 + i,
-                message: format!("Invalid X coordinate: {}", parts[1]),
-            })?;
-            let y = parts[2].parse::<f32>().map_err(|_| IOError::ParseError {
-                line: self.current_frame * (self.num_atoms + 2) + 2 + i,
-                message: format!("Invalid Y coordinate: {}", parts[2]),
-            })?;
-            let z = parts[3].parse::<f32>().map_err(|_| IOError::ParseError {
-                line: self.current_frame * (self.num_atoms + 2) + 2 + i,
-                message: format!("Invalid Z coordinate: {}", parts[3]),
-            })?;
-
-            frame.set_position(i as u32, Vec3::new(x, y, z));
+            header.read_atom(&mut frame, i, &line, first_line + 2 + i)?;
         }
 
         self.current_frame += 1;
@@ -298,13 +360,50 @@ impl XYZWriter {
         writeln!(writer, "time={:.2} frame={}", frame.time, frame.index)?;
 
         // Write atom positions
-        let mut sorted_ids: Vec<_> = frame.positions.keys().copied().collect();
-        sorted_ids.sort();
+        for pos in &frame.positions {
+            writeln!(writer, "X {:.6} {:.6} {:.6}", pos.x, pos.y, pos.z)?;
+        }
 
-        for atom_id in sorted_ids {
-            if let Some(pos) = frame.get_position(atom_id) {
-                writeln!(writer, "X {:.6} {:.6} {:.6}", pos.x, pos.y, pos.z)?;
-            }
+        Ok(())
+    }
+
+    /// Write a frame with element symbols from `atom_data`, in that order.
+    ///
+    /// The comment line is extended XYZ: time, frame index and, when the
+    /// frame has a cell, `Lattice` and `pbc`.
+    pub fn write_frame_with_atoms<W: std::io::Write>(
+        writer: &mut W,
+        frame: &FrameData,
+        atom_data: &[AtomData],
+    ) -> IOResult<()> {
+        let atoms: Vec<(&AtomData, Vec3)> = atom_data
+            .iter()
+            .zip(&frame.positions)
+            .map(|(atom, pos)| (atom, *pos))
+            .collect();
+
+        writeln!(writer, "{}", atoms.len())?;
+        write!(writer, "time={:.2} frame={}", frame.time, frame.index)?;
+        if let Some(cell) = &frame.cell {
+            let lattice: Vec<String> = cell
+                .vectors
+                .iter()
+                .flat_map(|v| v.to_array())
+                .map(|x| format!("{:.6}", x))
+                .collect();
+            write!(writer, " Lattice=\"{}\" pbc=\"T T T\"", lattice.join(" "))?;
+        }
+        writeln!(writer)?;
+
+        for (atom, pos) in atoms {
+            writeln!(
+                writer,
+                "{} {:.6} {:.6} {:.6}",
+                atom.element.symbol(),
+                pos.x,
+                pos.y,
+                pos.z
+            )?;
         }
 
         Ok(())
@@ -354,6 +453,28 @@ X 1.1 0.0 0.0"#;
         assert_eq!(trajectory.num_frames(), 2);
     }
 
+    #[test]
+    fn test_parse_extxyz_frames() {
+        let xyz_content = r#"2
+Lattice="5.0 0.0 0.0 0.0 5.0 0.0 0.0 0.0 5.0" Properties=species:S:1:pos:R:3:forces:R:3:local_energy:R:1 energy=-3.5 pbc="T T T"
+O 0.0 0.0 0.0 0.1 0.0 0.0 -2.0
+H 0.9 0.0 0.0 -0.1 0.0 0.0 -1.5
+2
+Lattice="5.0 0.0 0.0 0.0 5.0 0.0 0.0 0.0 5.0" Properties=species:S:1:pos:R:3:forces:R:3:local_energy:R:1 energy=-3.7 pbc="T T T"
+O 0.1 0.0 0.0 0.2 0.0 0.0 -2.1
+H 1.0 0.0 0.0 -0.2 0.0 0.0 -1.6"#;
+
+        let trajectory = XYZParser::parse_string(xyz_content, PathBuf::from("test.xyz")).unwrap();
+        assert_eq!(trajectory.num_frames(), 2);
+
+        let frame = trajectory.get_frame(1).unwrap();
+        assert_eq!(frame.position(1), Some(Vec3::new(1.0, 0.0, 0.0)));
+        assert_eq!(frame.potential_energy, Some(-3.7));
+        assert_eq!(frame.cell.unwrap().lengths, [5.0, 5.0, 5.0]);
+        assert_eq!(frame.forces.as_ref().unwrap()[0], Vec3::new(0.2, 0.0, 0.0));
+        assert_eq!(frame.properties["local_energy"][1], -1.6);
+    }
+
     #[test]
     fn test_write_xyz() {
         let mut frame = FrameData::new(0, 0.0);
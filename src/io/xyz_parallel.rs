@@ -1,9 +1,10 @@
 //! Memory-mapped and parallel XYZ parsing for large trajectories.
 
 use crate::core::trajectory::{FrameData, Trajectory};
+use crate::io::compression::Compression;
+use crate::io::extxyz::ExtXyzHeader;
 use crate::io::xyz::XYZParser;
 use crate::io::{IOError, IOResult};
-use bevy::prelude::*;
 use memmap2::Mmap;
 use rayon::prelude::*;
 use std::fs::File;
@@ -17,6 +18,7 @@ pub const PARALLEL_FRAME_THRESHOLD: usize = 8;
 
 #[derive(Debug, Clone)]
 struct FrameSpec {
+    comment_line: usize,
     first_atom_line: usize,
     frame_index: usize,
 }
@@ -74,16 +76,9 @@ fn scan_frame_specs(lines: &[String]) -> IOResult<(usize, f32, Vec<FrameSpec>)>
             });
         }
 
-        let comment = lines[i].trim();
-        if let Some(time_str) = comment
-            .split_whitespace()
-            .find(|s| s.starts_with("time=") || s.starts_with("t="))
-        {
-            if let Some(val) = time_str.split('=').nth(1) {
-                if let Ok(t) = val.parse::<f32>() {
-                    time_step = t;
-                }
-            }
+        let comment_line = i;
+        if let Some(t) = ExtXyzHeader::parse(lines[i].trim(), i)?.time {
+            time_step = t;
         }
 
         i += 1;
@@ -96,6 +91,7 @@ fn scan_frame_specs(lines: &[String]) -> IOResult<(usize, f32, Vec<FrameSpec>)>
         }
 
         specs.push(FrameSpec {
+            comment_line,
             first_atom_line,
             frame_index,
         });
@@ -120,7 +116,10 @@ fn parse_frame_positions(
     num_atoms: usize,
     time_step: f32,
 ) -> Result<FrameData, IOError> {
+    let header = ExtXyzHeader::parse(lines[spec.comment_line].trim(), spec.comment_line)?;
     let mut frame = FrameData::new(spec.frame_index, spec.frame_index as f32 * time_step);
+    header.apply_to_frame(&mut frame);
+    frame.positions.reserve(num_atoms);
 
     for atom_i in 0..num_atoms {
         let line_num = spec.first_atom_line + atom_i;
@@ -128,29 +127,7 @@ fn parse_frame_positions(
             line: line_num,
             message: "Missing atom line".into(),
         })?;
-
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 4 {
-            return Err(IOError::ParseError {
-                line: line_num,
-                message: format!("Expected element X Y Z, got {} fields", parts.len()),
-            });
-        }
-
-        let x = parts[1].parse::<f32>().map_err(|_| IOError::ParseError {
-            line: line_num,
-            message: format!("Invalid X: {}", parts[1]),
-        })?;
-        let y = parts[2].parse::<f32>().map_err(|_| IOError::ParseError {
-            line: line_num,
-            message: format!("Invalid Y: {}", parts[2]),
-        })?;
-        let z = parts[3].parse::<f32>().map_err(|_| IOError::ParseError {
-            line: line_num,
-            message: format!("Invalid Z: {}", parts[3]),
-        })?;
-
-        frame.set_position(atom_i as u32, Vec3::new(x, y, z));
+        header.read_atom(&mut frame, atom_i, line, line_num)?;
     }
 
     Ok(frame)
@@ -179,16 +156,14 @@ pub fn parse_lines_parallel(lines: &[String], file_path: PathBuf) -> IOResult<Tr
     Ok(trajectory)
 }
 
-/// Auto-select mmap + parallel parsing for large XYZ files.
+/// Auto-select mmap + parallel parsing for large XYZ files; compressed files
+/// are decompressed through the buffered reader.
 pub fn parse_file_optimized(path: &Path) -> IOResult<Trajectory> {
     let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
-    if size >= MMAP_THRESHOLD_BYTES {
+    if size >= MMAP_THRESHOLD_BYTES && !Compression::detect(path)?.is_compressed() {
         parse_file_mmap(path)
     } else {
-        XYZParser::parse_reader(
-            File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?,
-            path.to_path_buf(),
-        )
+        XYZParser::parse_file_buffered(path)
     }
 }
 
@@ -219,10 +194,7 @@ mod tests {
         assert_eq!(parallel.num_frames(), sequential.num_frames());
         assert_eq!(parallel.num_atoms, sequential.num_atoms);
         for (a, b) in parallel.frames.iter().zip(sequential.frames.iter()) {
-            assert_eq!(a.positions.len(), b.positions.len());
-            for id in a.positions.keys() {
-                assert_eq!(a.get_position(*id), b.get_position(*id));
-            }
+            assert_eq!(a.positions, b.positions);
         }
     }
 
@@ -1,12 +1,13 @@
 //! Seek-based XYZ trajectory streaming for large multi-frame files.
 //!
 //! Builds a byte-offset index on open and loads individual frames on demand
-//! through the shared [`FrameProvider`] interface.
+//! through the shared [`FrameProvider`] interface. Extended XYZ comment lines
+//! are parsed per frame, so cells, forces and per-atom properties stream too.
 
 use crate::core::trajectory::{FrameData, TrajectoryMetadata};
+use crate::io::extxyz::ExtXyzHeader;
 use crate::io::streaming::FrameProvider;
 use crate::io::{IOError, IOResult};
-use bevy::prelude::*;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
@@ -22,18 +23,6 @@ pub struct XyzIndex {
     pub metadata: TrajectoryMetadata,
 }
 
-fn parse_time_step_from_comment(comment: &str) -> Option<f32> {
-    comment.split_whitespace().find_map(|token| {
-        if let Some(v) = token.strip_prefix("time=") {
-            v.parse().ok()
-        } else if let Some(v) = token.strip_prefix("t=") {
-            v.parse().ok()
-        } else {
-            None
-        }
-    })
-}
-
 /// Scan an XYZ file once and record the byte offset of each frame header.
 pub fn build_xyz_index(path: &Path) -> IOResult<XyzIndex> {
     let file = File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
@@ -81,7 +70,7 @@ pub fn build_xyz_index(path: &Path) -> IOResult<XyzIndex> {
         reader.read_line(&mut comment).map_err(IOError::Io)?;
         if frame_index == 0 {
             title = comment.trim().to_string();
-            if let Some(t) = parse_time_step_from_comment(&title) {
+            if let Some(t) = ExtXyzHeader::parse(&title, 1)?.time {
                 time_step = t;
             }
         }
@@ -150,37 +139,23 @@ fn parse_frame_at_offset(
 
     line.clear();
     reader.read_line(&mut line).map_err(IOError::Io)?;
+    let header = ExtXyzHeader::parse(line.trim(), frame_index)?;
 
     let mut frame = FrameData::new(frame_index, frame_index as f32 * time_step);
+    header.apply_to_frame(&mut frame);
+    frame.positions.reserve(num_atoms);
     for atom_i in 0..num_atoms {
         line.clear();
         reader.read_line(&mut line).map_err(IOError::Io)?;
-
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 4 {
-            return Err(IOError::ParseError {
-                line: frame_index,
-                message: format!(
-                    "Frame {frame_index} atom {atom_i}: expected element X Y Z, got {} fields",
-                    parts.len()
-                ),
-            });
-        }
-
-        let x = parts[1].parse::<f32>().map_err(|_| IOError::ParseError {
-            line: frame_index,
-            message: format!("Invalid X coordinate: {}", parts[1]),
-        })?;
-        let y = parts[2].parse::<f32>().map_err(|_| IOError::ParseError {
-            line: frame_index,
-            message: format!("Invalid Y coordinate: {}", parts[2]),
-        })?;
-        let z = parts[3].parse::<f32>().map_err(|_| IOError::ParseError {
-            line: frame_index,
-            message: format!("Invalid Z coordinate: {}", parts[3]),
-        })?;
-
-        frame.set_position(atom_i as u32, Vec3::new(x, y, z));
+        header
+            .read_atom(&mut frame, atom_i, &line, frame_index)
+            .map_err(|e| match e {
+                IOError::ParseError { line, message } => IOError::ParseError {
+                    line,
+                    message: format!("Frame {frame_index} atom {atom_i}: {message}"),
+                },
+                other => other,
+            })?;
     }
 
     Ok(frame)
@@ -195,9 +170,10 @@ pub struct XyzFrameProvider {
 
 impl XyzFrameProvider {
     pub fn open(path: &Path) -> IOResult<Self> {
-        let index = build_xyz_index(path)?;
+        let source = crate::io::compression::seekable_path(path)?;
+        let index = build_xyz_index(&source)?;
         let file =
-            File::open(path).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
+            File::open(&source).map_err(|_| IOError::FileNotFound(path.display().to_string()))?;
         Ok(Self {
             reader: Arc::new(Mutex::new(BufReader::new(file))),
             index,
@@ -302,6 +278,7 @@ impl XYZStreamer {
 mod tests {
     use super::*;
     use crate::io::xyz::XYZParser;
+    use bevy::prelude::Vec3;
     use std::io::Write;
 
     fn write_temp_xyz(path: &Path, frame_count: usize) -> std::io::Result<()> {
@@ -348,9 +325,9 @@ mod tests {
         for i in 0..full.num_frames() {
             let expected = full.get_frame(i).unwrap();
             let streamed = provider.get_frame(i).unwrap();
-            for atom_id in expected.atom_ids() {
-                let a = expected.get_position(*atom_id).unwrap();
-                let b = streamed.get_position(*atom_id).unwrap();
+            for atom_id in 0..expected.num_atoms() {
+                let a = expected.position(atom_id).unwrap();
+                let b = streamed.position(atom_id).unwrap();
                 assert!(
                     (a - b).length() < 1e-5,
                     "frame {i} atom {atom_id}: {a:?} vs {b:?}"
@@ -361,6 +338,36 @@ mod tests {
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn test_xyz_provider_reads_extxyz_columns() {
+        let dir = std::env::temp_dir().join(format!("gumol_xyz_stream_ext_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("traj.xyz");
+        let mut file = File::create(&path).unwrap();
+        for f in 0..3 {
+            writeln!(file, "1").unwrap();
+            writeln!(
+                file,
+                "Properties=species:S:1:pos:R:3:velo:R:3:q:R:1 energy={} Lattice=\"4 0 0 0 4 0 0 0 4\"",
+                -1.0 - f as f32
+            )
+            .unwrap();
+            writeln!(file, "Na {} 0.0 0.0 1.0 0.0 0.0 0.5", f as f32).unwrap();
+        }
+        drop(file);
+
+        let provider = XyzFrameProvider::open(&path).unwrap();
+        let frame = provider.get_frame(2).unwrap();
+        assert_eq!(frame.position(0), Some(Vec3::new(2.0, 0.0, 0.0)));
+        assert_eq!(frame.potential_energy, Some(-3.0));
+        assert_eq!(frame.velocities.as_ref().unwrap()[0], Vec3::X);
+        assert_eq!(frame.properties["q"][0], 0.5);
+        assert!(frame.cell.is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_xyz_streamer_sequential() {
         let dir = std::env::temp_dir().join(format!("gumol_xyz_stream_seq_{}", std::process::id()));
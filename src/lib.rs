@@ -74,6 +74,7 @@ impl Plugin for GumolVizPlugin {
         io::register(app);
         rendering::register(app);
         systems::register(app);
+        analysis::register(app);
         performance::register(app);
         camera::register(app);
         interaction::register(app);
@@ -111,6 +111,7 @@ pub struct ProfilingReport {
 }
 
 impl ProfilingReport {
+    #[allow(clippy::too_many_arguments)]
     pub fn evaluate(
         atom_count: usize,
         frame_count: usize,
@@ -193,6 +194,7 @@ pub fn update_frame_stats(time: Res<Time>, mut stats: ResMut<FrameStats>) {
 }
 
 /// Drive automated profiling once atoms are loaded and rendered.
+#[allow(clippy::too_many_arguments)]
 pub fn run_profiling_validation(
     time: Res<Time>,
     mut session: ResMut<ProfilingSession>,
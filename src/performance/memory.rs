@@ -5,8 +5,8 @@ use crate::systems::loading::SimulationData;
 /// Warn when estimated resident memory exceeds this threshold (bytes).
 pub const MEMORY_WARN_THRESHOLD_BYTES: u64 = 4 * 1024 * 1024 * 1024;
 
-/// Approximate bytes per atom per frame (Vec3 position + HashMap overhead).
-const BYTES_PER_ATOM_PER_FRAME: u64 = 48;
+/// Bytes per atom per frame (one dense `Vec3` position).
+const BYTES_PER_ATOM_PER_FRAME: u64 = std::mem::size_of::<bevy::prelude::Vec3>() as u64;
 
 /// Approximate bytes per static atom metadata record.
 const BYTES_PER_ATOM_METADATA: u64 = 256;
@@ -85,7 +85,7 @@ mod tests {
     #[test]
     fn test_memory_warning_threshold() {
         let atom_count = 250_000usize;
-        let frame_count = 500usize;
+        let frame_count = 1500usize;
         let sim = SimulationData::new(
             synthetic_trajectory_for_test(atom_count, frame_count),
             (0..atom_count)
@@ -112,11 +112,12 @@ mod tests {
         let mut trajectory =
             crate::core::trajectory::Trajectory::new(PathBuf::from("big.xyz"), atom_count, 1.0);
         for f in 0..frame_count {
-            let mut frame = crate::core::trajectory::FrameData::new(f, f as f32);
-            for i in 0..atom_count.min(10) {
-                frame.set_position(i as u32, Vec3::ZERO);
-            }
-            trajectory.frames.push(frame);
+            let positions = vec![Vec3::ZERO; atom_count.min(10)];
+            trajectory
+                .frames
+                .push(crate::core::trajectory::FrameData::with_positions(
+                    f, f as f32, positions,
+                ));
         }
         trajectory
     }
@@ -1,6 +1,6 @@
 //! Atom ID ↔ instanced GPU buffer mapping and position lookups.
 
-use crate::core::atom::Element;
+use crate::core::atom::{AtomData, Element};
 use crate::rendering::instanced::{InstancedAtomEntity, InstancedAtomMesh};
 use bevy::prelude::*;
 use std::collections::HashMap;
@@ -12,17 +12,22 @@ pub struct InstancedAtomIndex {
     pub atom_to_instance: HashMap<u32, (Element, u32)>,
     /// element → ordered atom IDs (instance index → atom_id)
     pub element_atom_ids: HashMap<Element, Vec<u32>>,
+    /// element → ordered atom indices into frame arrays (instance index → atom index)
+    pub element_atom_indices: HashMap<Element, Vec<usize>>,
+    /// Number of atoms in the topology the index was built for
+    pub num_atoms: usize,
 }
 
 impl InstancedAtomIndex {
-    /// Build index from grouped atom spawn order.
-    pub fn build(atoms_by_element: &HashMap<Element, Vec<u32>>) -> Self {
+    /// Build index from grouped atom spawn order (atom indices per element).
+    pub fn build(atom_data: &[AtomData], atoms_by_element: &HashMap<Element, Vec<usize>>) -> Self {
         let mut atom_to_instance = HashMap::new();
         let mut element_atom_ids = HashMap::new();
 
-        for (element, atom_ids) in atoms_by_element {
-            let mut ids = Vec::with_capacity(atom_ids.len());
-            for (idx, &atom_id) in atom_ids.iter().enumerate() {
+        for (element, indices) in atoms_by_element {
+            let mut ids = Vec::with_capacity(indices.len());
+            for (idx, &atom_index) in indices.iter().enumerate() {
+                let atom_id = atom_data[atom_index].id;
                 atom_to_instance.insert(atom_id, (*element, idx as u32));
                 ids.push(atom_id);
             }
@@ -32,12 +37,16 @@ impl InstancedAtomIndex {
         Self {
             atom_to_instance,
             element_atom_ids,
+            element_atom_indices: atoms_by_element.clone(),
+            num_atoms: atom_data.len(),
         }
     }
 
     pub fn clear(&mut self) {
         self.atom_to_instance.clear();
         self.element_atom_ids.clear();
+        self.element_atom_indices.clear();
+        self.num_atoms = 0;
     }
 
     pub fn atom_id_at(&self, element: Element, instance_idx: u32) -> Option<u32> {
@@ -46,14 +55,15 @@ impl InstancedAtomIndex {
             .and_then(|ids| ids.get(instance_idx as usize).copied())
     }
 
-    /// Collect current world positions for all indexed atoms.
+    /// Collect current world positions in atom order (atoms without an
+    /// instance stay at the origin).
     pub fn collect_positions(
         &self,
         instanced: &Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
-    ) -> HashMap<u32, Vec3> {
-        let mut positions = HashMap::with_capacity(self.atom_to_instance.len());
+    ) -> Vec<Vec3> {
+        let mut positions = vec![Vec3::ZERO; self.num_atoms];
 
-        for (element, atom_ids) in &self.element_atom_ids {
+        for (element, indices) in &self.element_atom_indices {
             let mesh = instanced
                 .iter()
                 .find(|(e, _)| e.element == *element)
@@ -63,10 +73,8 @@ impl InstancedAtomIndex {
                 continue;
             };
 
-            for (idx, &atom_id) in atom_ids.iter().enumerate() {
-                if let Some(instance) = mesh.instances.get(idx) {
-                    positions.insert(atom_id, instance.position);
-                }
+            for (instance, &atom_index) in mesh.instances.iter().zip(indices) {
+                positions[atom_index] = instance.position;
             }
         }
 
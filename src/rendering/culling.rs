@@ -1,6 +1,5 @@
 //! CPU-side frustum culling for instanced atom spheres.
 
-use crate::core::visualization::VisualizationConfig;
 use crate::performance::{PerformanceDiagnostics, PerformanceSettings};
 use crate::rendering::instanced::{InstancedAtomEntity, InstancedAtomMesh};
 use bevy::prelude::*;
@@ -10,21 +9,13 @@ use bevy::render::{
 };
 
 /// Hide off-screen instances by setting their scale to zero.
+/// Visible instances get their per-atom base scale back.
 pub fn cull_instanced_atoms(
     perf: Res<PerformanceSettings>,
-    viz: Res<VisualizationConfig>,
     mut diagnostics: ResMut<PerformanceDiagnostics>,
     camera: Query<(&Camera, &GlobalTransform, &Projection)>,
     mut instanced: Query<(&InstancedAtomEntity, &mut InstancedAtomMesh)>,
 ) {
-    let mode_scale = viz.render_mode.atom_scale() * viz.atom_scale;
-    let show_atoms = viz.show_atoms && viz.render_mode.shows_atoms();
-    let base_scale = if show_atoms {
-        mode_scale.max(0.001)
-    } else {
-        0.0
-    };
-
     let Ok((camera, transform, projection)) = camera.get_single() else {
         return;
     };
@@ -38,11 +29,11 @@ pub fn cull_instanced_atoms(
     let mut culled = 0usize;
 
     for (entity_info, mut mesh) in instanced.iter_mut() {
-        mesh.mode_scale = base_scale;
-        let world_radius = entity_info.element.vdw_radius() * 0.5 * base_scale;
+        let world_radius = entity_info.element.vdw_radius() * 0.5 * mesh.mode_scale;
         let mut mesh_changed = false;
 
-        for instance in mesh.instances.iter_mut() {
+        let mesh_data = &mut *mesh;
+        for (instance, &base_scale) in mesh_data.instances.iter_mut().zip(&mesh_data.base_scales) {
             if base_scale <= 0.0 {
                 if instance.scale != 0.0 {
                     instance.scale = 0.0;
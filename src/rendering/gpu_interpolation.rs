@@ -4,7 +4,6 @@
 //! interpolate between frames. Results are read back and applied to instanced
 //! atom meshes. Falls back to CPU lerp when disabled or unavailable.
 
-use crate::core::atom::Element;
 use crate::core::trajectory::TimelineState;
 use crate::rendering::atom_index::InstancedAtomIndex;
 use crate::rendering::instanced::{InstancedAtomEntity, InstancedAtomMesh};
@@ -26,7 +25,6 @@ use bevy::render::{
 use bytemuck::{Pod, Zeroable};
 use crossbeam_channel::{Receiver, Sender};
 use std::borrow::Cow;
-use std::collections::HashMap;
 use std::num::NonZeroU64;
 
 const SHADER_PATH: &str = "shaders/atom_interpolate.wgsl";
@@ -62,35 +60,6 @@ fn gpu_slice_to_vec3(positions: &[GpuPosition]) -> Vec<Vec3> {
     positions.iter().map(|p| Vec3::new(p.x, p.y, p.z)).collect()
 }
 
-/// Maps dense position array indices to instanced atom locations.
-#[derive(Resource, Default, Clone, Debug)]
-pub struct DenseAtomLayout {
-    /// Ordered atom IDs matching `SimulationData::atom_data` (dense index → atom_id).
-    pub dense_atom_ids: Vec<u32>,
-    /// atom_id → dense index for O(1) scatter after GPU readback.
-    pub atom_id_to_dense: HashMap<u32, u32>,
-}
-
-impl DenseAtomLayout {
-    pub fn build(atom_data: &[crate::core::atom::AtomData]) -> Self {
-        let dense_atom_ids: Vec<u32> = atom_data.iter().map(|a| a.id).collect();
-        let atom_id_to_dense = dense_atom_ids
-            .iter()
-            .enumerate()
-            .map(|(i, &id)| (id, i as u32))
-            .collect();
-        Self {
-            dense_atom_ids,
-            atom_id_to_dense,
-        }
-    }
-
-    pub fn clear(&mut self) {
-        self.dense_atom_ids.clear();
-        self.atom_id_to_dense.clear();
-    }
-}
-
 /// Extracted timeline data for the render-world compute pass.
 #[derive(Resource, Clone, ExtractResource, Default)]
 pub struct GpuInterpolationExtract {
@@ -119,7 +88,7 @@ pub fn prepare_gpu_interpolation_extract(
     sim_data: Res<SimulationData>,
     timeline: Res<TimelineState>,
     frames: Res<TimelineFrames>,
-    layout: Res<DenseAtomLayout>,
+    index: Res<InstancedAtomIndex>,
     perf: Res<crate::performance::PerformanceSettings>,
     mut extract: ResMut<GpuInterpolationExtract>,
     mut gpu_active: ResMut<GpuInterpolationActive>,
@@ -128,7 +97,7 @@ pub fn prepare_gpu_interpolation_extract(
 
     if !perf.gpu_interpolation_enabled
         || !sim_data.loaded
-        || layout.dense_atom_ids.is_empty()
+        || index.num_atoms == 0
         || frames.current.is_none()
     {
         extract.active = false;
@@ -146,43 +115,49 @@ pub fn prepare_gpu_interpolation_extract(
         0.0
     };
 
-    let needs_interpolation = alpha > 0.0 && frames.next.is_some();
+    // Frames with different atom counts are not blended (see `interpolate_frames`)
+    let next = frames
+        .next
+        .as_ref()
+        .filter(|next| next.positions.len() == current.positions.len());
+    let needs_interpolation = alpha > 0.0 && next.is_some();
     if !needs_interpolation && !timeline.is_changed() && !frames.is_changed() {
         extract.active = false;
         return;
     }
 
-    let num_atoms = layout.dense_atom_ids.len() as u32;
+    let num_atoms = index.num_atoms;
     let frames_changed =
         extract.positions_a.is_empty() || extract.last_current_index != frames.current_index;
 
-    if frames_changed || extract.positions_a.len() != layout.dense_atom_ids.len() {
-        extract.positions_a = dense_positions(current, &layout.dense_atom_ids);
-        extract.positions_b = if let Some(next) = &frames.next {
-            dense_positions(next, &layout.dense_atom_ids)
+    if frames_changed || extract.positions_a.len() != num_atoms {
+        extract.positions_a = dense_positions(current, num_atoms);
+        extract.positions_b = if let Some(next) = next {
+            dense_positions(next, num_atoms)
         } else {
             extract.positions_a.clone()
         };
         extract.frames_changed = true;
         extract.last_current_index = frames.current_index;
     } else if needs_interpolation {
-        if let Some(next) = &frames.next {
-            extract.positions_b = dense_positions(next, &layout.dense_atom_ids);
+        if let Some(next) = next {
+            extract.positions_b = dense_positions(next, num_atoms);
         }
         extract.frames_changed = false;
     }
 
     extract.active = true;
     extract.alpha = alpha;
-    extract.num_atoms = num_atoms;
+    extract.num_atoms = num_atoms as u32;
     gpu_active.0 = true;
 }
 
-fn dense_positions(frame: &crate::core::trajectory::FrameData, atom_ids: &[u32]) -> Vec<Vec3> {
-    atom_ids
-        .iter()
-        .map(|&id| frame.get_position(id).unwrap_or(Vec3::ZERO))
-        .collect()
+/// Frame positions as the upload buffer: already in atom order, padded or
+/// truncated to the topology's atom count.
+fn dense_positions(frame: &crate::core::trajectory::FrameData, num_atoms: usize) -> Vec<Vec3> {
+    let mut positions = frame.positions.clone();
+    positions.resize(num_atoms, Vec3::ZERO);
+    positions
 }
 
 /// CPU reference matching `assets/shaders/atom_interpolate.wgsl` (`mix(a, b, alpha)`).
@@ -203,7 +178,6 @@ pub fn interpolate_dense_positions(
 pub fn apply_gpu_interpolated_positions(
     gpu_active: Res<GpuInterpolationActive>,
     readback: Res<GpuInterpolationReadback>,
-    layout: Res<DenseAtomLayout>,
     index: Res<InstancedAtomIndex>,
     mut instanced: Query<(&InstancedAtomEntity, &mut InstancedAtomMesh)>,
 ) {
@@ -215,66 +189,21 @@ pub fn apply_gpu_interpolated_positions(
         return;
     };
 
-    if positions.len() != layout.dense_atom_ids.len() {
+    if positions.len() != index.num_atoms {
         return;
     }
 
-    // Group position updates by element for a single pass over instanced entities.
-    let mut by_element: HashMap<Element, Vec<(u32, Vec3)>> = HashMap::new();
-    for (atom_id, &(element, instance_idx)) in &index.atom_to_instance {
-        let Some(&dense_idx) = layout.atom_id_to_dense.get(atom_id) else {
-            continue;
-        };
-        let Some(pos) = positions.get(dense_idx as usize) else {
-            continue;
-        };
-        by_element
-            .entry(element)
-            .or_default()
-            .push((instance_idx, *pos));
-    }
-
     for (entity_info, mut mesh) in instanced.iter_mut() {
-        let Some(updates) = by_element.get(&entity_info.element) else {
+        let Some(indices) = index.element_atom_indices.get(&entity_info.element) else {
             continue;
         };
-        for &(instance_idx, pos) in updates {
-            if let Some(instance) = mesh.instances.get_mut(instance_idx as usize) {
-                instance.position = pos;
-            }
+        for (instance, &atom_index) in mesh.instances.iter_mut().zip(indices) {
+            instance.position = positions[atom_index];
         }
         mesh.mark_gpu_dirty();
     }
 }
 
-/// Clear dense layout when a new file loads.
-pub fn clear_dense_layout_on_load(
-    mut layout: ResMut<DenseAtomLayout>,
-    mut extract: ResMut<GpuInterpolationExtract>,
-    mut file_loaded_events: EventReader<crate::systems::loading::FileLoadedEvent>,
-    mut topology_events: EventReader<crate::systems::loading::TopologyAppliedEvent>,
-) {
-    if file_loaded_events.read().next().is_none() && topology_events.read().next().is_none() {
-        return;
-    }
-    layout.clear();
-    *extract = GpuInterpolationExtract::default();
-}
-
-/// Build dense layout after instanced atoms spawn.
-pub fn build_dense_layout_on_spawn(
-    sim_data: Res<SimulationData>,
-    instanced: Res<crate::rendering::instanced::InstancedAtomEntities>,
-    mut layout: ResMut<DenseAtomLayout>,
-) {
-    if !sim_data.loaded || instanced.total_atoms == 0 {
-        return;
-    }
-    if layout.dense_atom_ids.is_empty() {
-        *layout = DenseAtomLayout::build(&sim_data.atom_data);
-    }
-}
-
 // ============================================================================
 // RENDER WORLD
 // ============================================================================
@@ -620,8 +549,7 @@ impl Plugin for GpuInterpolationRenderPluginWithReadback {
 }
 
 pub fn register(app: &mut App) {
-    app.init_resource::<DenseAtomLayout>()
-        .init_resource::<GpuInterpolationExtract>()
+    app.init_resource::<GpuInterpolationExtract>()
         .init_resource::<GpuInterpolationActive>()
         .add_plugins(GpuInterpolationRenderPluginWithReadback);
 
@@ -632,17 +560,6 @@ pub fn register(app: &mut App) {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_dense_layout_build() {
-        use crate::core::atom::{AtomData, Element};
-        let atoms = vec![
-            AtomData::new(0, Element::C, 0, "UNK".into(), "A".into(), "C".into()),
-            AtomData::new(1, Element::H, 0, "UNK".into(), "A".into(), "H".into()),
-        ];
-        let layout = DenseAtomLayout::build(&atoms);
-        assert_eq!(layout.dense_atom_ids, vec![0, 1]);
-    }
-
     #[test]
     fn test_interpolation_uniforms_size() {
         assert_eq!(std::mem::size_of::<InterpolationUniformsGpu>(), 16);
@@ -650,15 +567,8 @@ mod tests {
 
     #[test]
     fn test_dense_interpolation_matches_trajectory_lerp() {
-        use crate::core::atom::{AtomData, Element};
         use crate::core::trajectory::{interpolate_frames, FrameData};
 
-        let atoms = vec![
-            AtomData::new(0, Element::O, 0, "HOH".into(), "A".into(), "O".into()),
-            AtomData::new(1, Element::H, 0, "HOH".into(), "A".into(), "H1".into()),
-        ];
-        let layout = DenseAtomLayout::build(&atoms);
-
         let mut frame_a = FrameData::new(0, 0.0);
         frame_a.set_position(0, Vec3::new(0.0, 0.0, 0.0));
         frame_a.set_position(1, Vec3::new(1.0, 0.0, 0.0));
@@ -668,17 +578,12 @@ mod tests {
         frame_b.set_position(1, Vec3::new(1.0, 0.2, 0.0));
 
         let alpha = 0.35;
-        let pos_a = dense_positions(&frame_a, &layout.dense_atom_ids);
-        let pos_b = dense_positions(&frame_b, &layout.dense_atom_ids);
+        let pos_a = dense_positions(&frame_a, 2);
+        let pos_b = dense_positions(&frame_b, 2);
         let dense = interpolate_dense_positions(&pos_a, &pos_b, alpha);
 
         let expected = interpolate_frames(&frame_a, &frame_b, alpha);
-        for (i, &atom_id) in layout.dense_atom_ids.iter().enumerate() {
-            assert_eq!(
-                dense[i],
-                expected.get_position(atom_id).unwrap(),
-                "atom {atom_id} mismatch"
-            );
-        }
+        assert_eq!(dense, expected.positions);
+        assert_eq!(dense_positions(&frame_a, 3)[2], Vec3::ZERO);
     }
 }
@@ -5,9 +5,12 @@
 //! Uses a custom WGSL shader with per-instance position, scale, and color
 //! stored in a vertex buffer with `VertexStepMode::Instance`.
 
+use crate::analysis::molecules::MoleculeTopology;
 use crate::core::atom::{AtomData, Element};
+use crate::core::groups::AtomStyles;
+use crate::core::secondary_structure::ProteinBackbone;
 use crate::core::trajectory::{FrameData, TimelineState};
-use crate::core::visualization::VisualizationConfig;
+use crate::core::visualization::{ColorContext, ColorScheme, VisualizationConfig};
 use crate::interaction::selection::SelectionState;
 use crate::performance::PerformanceSettings;
 use crate::rendering::atom_index::InstancedAtomIndex;
@@ -69,7 +72,10 @@ pub fn estimate_instanced_draw_calls(atom_data: &[AtomData]) -> usize {
 #[derive(Component, Clone, Debug)]
 pub struct InstancedAtomMesh {
     pub instances: Vec<AtomInstanceData>,
-    /// Cached visualization scale (before frustum culling).
+    /// Per-instance visualization scale (before frustum culling); differs
+    /// between instances when atom groups use different styles.
+    pub base_scales: Vec<f32>,
+    /// Largest entry of `base_scales`, used for batch LOD selection.
     pub mode_scale: f32,
     /// When true, instance data must be re-uploaded to the GPU.
     pub gpu_dirty: bool,
@@ -78,6 +84,7 @@ pub struct InstancedAtomMesh {
 impl InstancedAtomMesh {
     pub fn new(instances: Vec<AtomInstanceData>, mode_scale: f32) -> Self {
         Self {
+            base_scales: instances.iter().map(|i| i.scale).collect(),
             instances,
             mode_scale,
             gpu_dirty: true,
@@ -100,6 +107,7 @@ impl ExtractComponent for InstancedAtomMesh {
         }
         Some(InstancedAtomMesh {
             instances: item.instances.clone(),
+            base_scales: Vec::new(),
             mode_scale: item.mode_scale,
             gpu_dirty: false,
         })
@@ -131,7 +139,7 @@ pub struct InstancedAtomsSpawnedEvent {
 // ============================================================================
 
 /// Spawn one entity per element with instance data for all atoms of that element.
-/// Returns entity map and atom IDs grouped by element (for index building).
+/// Returns entity map and atom indices grouped by element (for index building).
 pub fn spawn_atoms_instanced_internal(
     commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
@@ -139,7 +147,7 @@ pub fn spawn_atoms_instanced_internal(
     frame_data: &FrameData,
     atom_data: &[AtomData],
     viz_config: &VisualizationConfig,
-) -> (HashMap<Element, Entity>, HashMap<Element, Vec<u32>>) {
+) -> (HashMap<Element, Entity>, HashMap<Element, Vec<usize>>) {
     info!(
         "Spawning {} atoms with instanced rendering",
         atom_data.len()
@@ -153,42 +161,32 @@ pub fn spawn_atoms_instanced_internal(
         0.0
     };
 
-    let mut atoms_by_element: HashMap<Element, Vec<&AtomData>> = HashMap::new();
-    for atom_info in atom_data {
-        if frame_data.get_position(atom_info.id).is_some() {
-            atoms_by_element
-                .entry(atom_info.element)
-                .or_default()
-                .push(atom_info);
-        }
+    let mut atoms_by_element: HashMap<Element, Vec<usize>> = HashMap::new();
+    for (index, atom_info) in atom_data.iter().enumerate().take(frame_data.num_atoms()) {
+        atoms_by_element
+            .entry(atom_info.element)
+            .or_default()
+            .push(index);
     }
 
     info!("Grouped into {} element types", atoms_by_element.len());
 
     let mut entity_map = HashMap::new();
-    let mut ids_by_element: HashMap<Element, Vec<u32>> = HashMap::new();
 
-    for (element, atoms) in &atoms_by_element {
+    for (element, indices) in &atoms_by_element {
         let lod = mesh_pool.current_lod();
         let mesh = mesh_pool.get_atom_mesh(meshes, *element, lod);
         let color_rgb = element.cpk_color();
 
-        let mut element_ids = Vec::with_capacity(atoms.len());
-        let instances: Vec<AtomInstanceData> = atoms
+        let instances: Vec<AtomInstanceData> = indices
             .iter()
-            .map(|a| {
-                element_ids.push(a.id);
-                let position = frame_data.get_position(a.id).unwrap_or(Vec3::ZERO);
-                AtomInstanceData {
-                    position,
-                    scale: instance_scale,
-                    color: Vec4::new(color_rgb[0], color_rgb[1], color_rgb[2], 1.0),
-                }
+            .map(|&index| AtomInstanceData {
+                position: frame_data.positions[index],
+                scale: instance_scale,
+                color: Vec4::new(color_rgb[0], color_rgb[1], color_rgb[2], 1.0),
             })
             .collect();
 
-        ids_by_element.insert(*element, element_ids);
-
         let entity = commands
             .spawn((
                 mesh,
@@ -209,7 +207,7 @@ pub fn spawn_atoms_instanced_internal(
         (1.0 - entity_map.len() as f64 / atom_data.len().max(1) as f64) * 100.0
     );
 
-    (entity_map, ids_by_element)
+    (entity_map, atoms_by_element)
 }
 
 /// System: spawn instanced atoms when a file finishes loading.
@@ -234,37 +232,31 @@ pub fn spawn_instanced_atoms_on_load(
     let topology_applied = topology_events.read().next().is_some();
     let should_spawn = (file_loaded || topology_applied)
         && sim_data.loaded
-        && !sim_data.atom_data.is_empty()
+        && !sim_data.atom_data().is_empty()
         && (instanced_entities.entities.is_empty() || topology_applied);
 
     if should_spawn {
         if let Some(first_frame) = sim_data.get_frame(0) {
-            let (new_entities, ids_by_element) = spawn_atoms_instanced_internal(
+            let (new_entities, indices_by_element) = spawn_atoms_instanced_internal(
                 &mut commands,
                 &mut meshes,
                 &mut mesh_pool,
                 &first_frame,
-                &sim_data.atom_data,
+                sim_data.atom_data(),
                 &viz_config,
             );
 
             let draw_calls = new_entities.len();
             instanced_entities.entities = new_entities;
-            instanced_entities.total_atoms = sim_data.atom_data.len();
-            *atom_index = InstancedAtomIndex::build(&ids_by_element);
-
-            let positions: HashMap<u32, Vec3> = first_frame
-                .positions
-                .iter()
-                .map(|(&id, &pos)| (id, pos))
-                .collect();
+            instanced_entities.total_atoms = sim_data.atom_data().len();
+            *atom_index = InstancedAtomIndex::build(sim_data.atom_data(), &indices_by_element);
 
             let (pick_map, selection_ok) = crate::interaction::pick_proxy::spawn_pick_proxies(
                 &mut commands,
                 &mut meshes,
                 &mut materials,
-                &sim_data.atom_data,
-                &positions,
+                sim_data.atom_data(),
+                &first_frame.positions,
                 perf.max_pick_proxies,
             );
             pick_entities.entities = pick_map;
@@ -283,7 +275,7 @@ pub fn spawn_instanced_atoms_on_load(
             diagnostics.memory_warning = crate::performance::memory::memory_warning(&sim_data);
 
             spawned_event.send(InstancedAtomsSpawnedEvent {
-                count: sim_data.atom_data.len(),
+                count: sim_data.atom_data().len(),
                 draw_calls,
             });
         }
@@ -334,14 +326,7 @@ pub fn center_camera_on_file_load_instanced(
     }
 
     if let Some(frame) = sim_data.get_frame(0) {
-        let mut sum = Vec3::ZERO;
-        let mut count = 0;
-        for pos in frame.positions.values() {
-            sum += *pos;
-            count += 1;
-        }
-        if count > 0 {
-            let center = sum / count as f32;
+        if let Some(center) = frame.centroid() {
             for mut cam in camera_query.iter_mut() {
                 cam.focus = center;
                 cam.target_focus = center;
@@ -377,8 +362,12 @@ pub fn update_instanced_positions_from_timeline(
         return;
     };
 
+    // Frames with different atom counts are not blended (see `interpolate_frames`)
     let next_frame = if timeline.interpolate && timeline.interpolation_factor > 0.0 {
-        frames.next.as_ref()
+        frames
+            .next
+            .as_ref()
+            .filter(|next| next.positions.len() == current_frame.positions.len())
     } else {
         None
     };
@@ -386,99 +375,118 @@ pub fn update_instanced_positions_from_timeline(
     for (entity_info, mut mesh) in instanced_query.iter_mut() {
         let element = entity_info.element;
 
-        let Some(atom_ids) = index.element_atom_ids.get(&element) else {
+        let Some(indices) = index.element_atom_indices.get(&element) else {
             continue;
         };
 
-        for (i, &atom_id) in atom_ids.iter().enumerate() {
-            if i >= mesh.instances.len() {
-                break;
-            }
-
-            let current_pos = match current_frame.get_position(atom_id) {
-                Some(p) => p,
-                None => continue,
+        for (instance, &atom_index) in mesh.instances.iter_mut().zip(indices) {
+            let Some(current_pos) = current_frame.position(atom_index) else {
+                continue;
             };
 
-            let position = if let Some(nf) = next_frame {
-                if let Some(next_pos) = nf.get_position(atom_id) {
-                    current_pos.lerp(next_pos, timeline.interpolation_factor)
-                } else {
-                    current_pos
-                }
-            } else {
-                current_pos
+            instance.position = match next_frame.and_then(|nf| nf.position(atom_index)) {
+                Some(next_pos) => current_pos.lerp(next_pos, timeline.interpolation_factor),
+                None => current_pos,
             };
-
-            mesh.instances[i].position = position;
         }
         mesh.mark_gpu_dirty();
     }
 }
 
-/// Update instance scales when visualization mode changes.
+/// Update per-instance scales when atom styles (mode, scale, groups) change.
 pub fn update_instanced_visualization(
-    viz_config: Res<VisualizationConfig>,
-    mut instanced_query: Query<&mut InstancedAtomMesh>,
+    styles: Res<AtomStyles>,
+    index: Res<InstancedAtomIndex>,
+    mut instanced_query: Query<(&InstancedAtomEntity, &mut InstancedAtomMesh)>,
 ) {
-    if !viz_config.is_changed() {
+    if !styles.is_changed() && !index.is_changed() {
         return;
     }
 
-    let mode_scale = viz_config.render_mode.atom_scale() * viz_config.atom_scale;
-    let show_atoms = viz_config.show_atoms && viz_config.render_mode.shows_atoms();
-    let instance_scale = if show_atoms {
-        mode_scale.max(0.001)
-    } else {
-        0.0
-    };
+    for (entity_info, mut mesh) in instanced_query.iter_mut() {
+        let Some(atom_ids) = index.element_atom_ids.get(&entity_info.element) else {
+            continue;
+        };
 
-    for mut mesh in instanced_query.iter_mut() {
-        mesh.mode_scale = instance_scale;
-        for instance in mesh.instances.iter_mut() {
-            instance.scale = instance_scale;
+        let base_scales: Vec<f32> = atom_ids
+            .iter()
+            .map(|&atom_id| styles.get(atom_id).atom_scale)
+            .collect();
+        for (instance, &scale) in mesh.instances.iter_mut().zip(&base_scales) {
+            instance.scale = scale;
         }
+        mesh.mode_scale = base_scales.iter().copied().fold(0.0, f32::max);
+        mesh.base_scales = base_scales;
         mesh.mark_gpu_dirty();
     }
 }
 
-/// Update instance colors from the active color scheme and selection state.
+/// Update instance colors from each atom's color scheme and the selection state.
+///
+/// Property coloring depends on the displayed frame, so it is refreshed
+/// whenever the current frame changes.
+#[allow(clippy::too_many_arguments)]
 pub fn update_instanced_atom_colors(
-    viz_config: Res<VisualizationConfig>,
+    styles: Res<AtomStyles>,
     selection: Res<SelectionState>,
     sim_data: Res<crate::systems::loading::SimulationData>,
     timeline: Res<TimelineState>,
+    frames: Res<crate::systems::frame_cache::TimelineFrames>,
+    config: Res<VisualizationConfig>,
     index: Res<InstancedAtomIndex>,
+    topology: Res<MoleculeTopology>,
+    backbone: Res<ProteinBackbone>,
+    mut colored_frame: Local<Option<usize>>,
     mut instanced_query: Query<(&InstancedAtomEntity, &mut InstancedAtomMesh)>,
 ) {
-    if !viz_config.is_changed() && !selection.is_changed() && !sim_data.is_changed() {
+    let property_colors = styles.any(|style| style.color_scheme == ColorScheme::Custom);
+    let frame_changed = property_colors && *colored_frame != Some(frames.current_index);
+    if !styles.is_changed()
+        && !selection.is_changed()
+        && !sim_data.is_changed()
+        && !index.is_changed()
+        && !topology.is_changed()
+        && !backbone.is_changed()
+        && !config.is_changed()
+        && !frame_changed
+    {
         return;
     }
-
-    let ctx = sim_data.color_context(timeline.current_frame);
+    *colored_frame = frames.current.as_ref().map(|_| frames.current_index);
+
+    let property = config
+        .color_property
+        .as_ref()
+        .zip(frames.current.as_ref())
+        .and_then(|(name, frame)| frame.properties.get(name))
+        .map(Vec::as_slice);
+    let secondary_structure = backbone.residue_secondary_structure(&topology);
+    let ctx = ColorContext {
+        topology: Some(&topology),
+        secondary_structure: Some(&secondary_structure),
+        atom_index: Some(sim_data.atom_index_map()),
+        ..sim_data.color_context(timeline.current_frame)
+    }
+    .with_property(property);
     let selected: std::collections::HashSet<u32> =
         selection.selected_atom_ids.iter().copied().collect();
 
     for (entity_info, mut mesh) in instanced_query.iter_mut() {
-        let Some(atom_ids) = index.element_atom_ids.get(&entity_info.element) else {
+        let Some(indices) = index.element_atom_indices.get(&entity_info.element) else {
             continue;
         };
 
-        for (i, &atom_id) in atom_ids.iter().enumerate() {
-            if i >= mesh.instances.len() {
-                break;
-            }
-
-            let color = if selected.contains(&atom_id) {
-                Color::srgb(1.0, 1.0, 0.0)
-            } else if let Some(atom) = sim_data.atom_data.iter().find(|a| a.id == atom_id) {
-                viz_config.color_scheme.atom_color(atom, &ctx)
-            } else {
-                let rgb = entity_info.element.cpk_color();
-                Color::srgb(rgb[0], rgb[1], rgb[2])
+        for (instance, &atom_index) in mesh.instances.iter_mut().zip(indices) {
+            let color = match sim_data.atom_data().get(atom_index) {
+                Some(atom) if selected.contains(&atom.id) => Color::srgb(1.0, 1.0, 0.0),
+                Some(atom) => styles.get(atom.id).color_scheme.atom_color(atom, &ctx),
+                None => {
+                    let rgb = entity_info.element.cpk_color();
+                    Color::srgb(rgb[0], rgb[1], rgb[2])
+                }
             };
 
-            mesh.instances[i].color = color.to_linear().to_vec4();
+            instance.color = color.to_linear().to_vec4();
         }
         mesh.mark_gpu_dirty();
     }
@@ -10,6 +10,7 @@ pub mod material_pool;
 pub mod mesh_pool;
 pub mod ribbon;
 pub mod surface;
+pub mod unit_cell;
 pub mod wireframe;
 
 use bevy::{
@@ -27,6 +28,7 @@ pub fn register(app: &mut App) {
     wireframe::register(app);
     ribbon::register(app);
     surface::register(app);
+    unit_cell::register(app);
     mesh_pool::register(app);
     material_pool::register(app);
     info!("Rendering module registered");
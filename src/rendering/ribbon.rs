@@ -1,8 +1,13 @@
 //! Protein backbone ribbon / tube / trace rendering.
+//!
+//! The backbone is split into runs of consecutive residues of one chain that
+//! share a ribbon mode (global or from their atom group); each run gets its
+//! own mesh entity, so a cartoon protein can sit next to a traced peptide.
 
+use crate::core::groups::AtomStyles;
 use crate::core::molecule::SecondaryStructure;
 use crate::core::secondary_structure::{BackboneResidue, ProteinBackbone};
-use crate::core::visualization::{ColorPalette, RenderMode, VisualizationConfig};
+use crate::core::visualization::{ColorPalette, RenderMode};
 use crate::rendering::atom_index::InstancedAtomIndex;
 use crate::rendering::instanced::{
     InstancedAtomEntity, InstancedAtomMesh, InstancedAtomsSpawnedEvent,
@@ -10,6 +15,7 @@ use crate::rendering::instanced::{
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
 use bevy::render::render_asset::RenderAssetUsages;
+use std::ops::Range;
 
 const RENDER_ASSET_USAGES: RenderAssetUsages = RenderAssetUsages::RENDER_WORLD;
 
@@ -17,9 +23,100 @@ const RENDER_ASSET_USAGES: RenderAssetUsages = RenderAssetUsages::RENDER_WORLD;
 #[derive(Component)]
 pub struct ProteinRibbon;
 
+/// Consecutive backbone residues drawn with one ribbon mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RibbonRun {
+    pub mode: RenderMode,
+    /// Index range into `ProteinBackbone::residues`
+    pub residues: Range<usize>,
+}
+
 #[derive(Resource, Default, Debug)]
 pub struct RibbonEntities {
+    /// One mesh entity per entry of `runs`
     pub entities: Vec<Entity>,
+    pub runs: Vec<RibbonRun>,
+    /// Shared unlit material (set once a backbone is available)
+    pub material: Option<Handle<StandardMaterial>>,
+}
+
+/// Split the backbone into ribbon runs using each CA atom's style.
+/// Runs break at chain boundaries, mode changes and residues without a ribbon.
+pub fn ribbon_runs(residues: &[BackboneResidue], styles: &AtomStyles) -> Vec<RibbonRun> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while start < residues.len() {
+        let style = styles.get(residues[start].ca_atom_id);
+        if !style.ribbon {
+            start += 1;
+            continue;
+        }
+
+        let mut end = start + 1;
+        while end < residues.len() && residues[end].chain_id == residues[start].chain_id {
+            let next = styles.get(residues[end].ca_atom_id);
+            if !next.ribbon || next.render_mode != style.render_mode {
+                break;
+            }
+            end += 1;
+        }
+
+        if end - start >= 2 {
+            runs.push(RibbonRun {
+                mode: style.render_mode,
+                residues: start..end,
+            });
+        }
+        start = end;
+    }
+    runs
+}
+
+/// Backbone residues with CA positions taken from the displayed frame.
+fn current_residues(
+    backbone: &ProteinBackbone,
+    index: &InstancedAtomIndex,
+    instanced: &Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
+) -> Vec<BackboneResidue> {
+    let mut residues = backbone.residues.clone();
+    for residue in &mut residues {
+        if let Some(pos) = index.get_position(residue.ca_atom_id, instanced) {
+            residue.position = pos;
+        }
+    }
+    residues
+}
+
+/// Replace the ribbon entities with one entity per run.
+fn respawn_ribbon_runs(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    ribbon_entities: &mut RibbonEntities,
+    residues: &[BackboneResidue],
+    runs: Vec<RibbonRun>,
+) {
+    for entity in ribbon_entities.entities.drain(..) {
+        commands.entity(entity).despawn_recursive();
+    }
+    let Some(material) = ribbon_entities.material.clone() else {
+        ribbon_entities.runs.clear();
+        return;
+    };
+
+    for run in &runs {
+        let entity = commands
+            .spawn((
+                PbrBundle {
+                    mesh: meshes.add(build_ribbon_mesh(&residues[run.residues.clone()], run.mode)),
+                    material: material.clone(),
+                    ..default()
+                },
+                ProteinRibbon,
+            ))
+            .id();
+        ribbon_entities.entities.push(entity);
+    }
+    ribbon_entities.runs = runs;
 }
 
 /// Build backbone from loaded simulation data after instanced atoms spawn.
@@ -39,8 +136,11 @@ pub fn build_backbone_on_load(
     }
 
     let positions = index.collect_positions(&instanced);
-    *backbone =
-        crate::core::secondary_structure::build_protein_backbone(&sim_data.atom_data, &positions);
+    *backbone = crate::core::secondary_structure::build_protein_backbone(
+        sim_data.atom_data(),
+        &positions,
+        &sim_data.trajectory.metadata.secondary_structure,
+    );
 
     if backbone.cartoon_available {
         info!(
@@ -50,17 +150,17 @@ pub fn build_backbone_on_load(
     }
 }
 
-/// Spawn ribbon mesh entities (hidden until cartoon/tube/trace mode is selected).
+/// Create the ribbon material and spawn runs for atoms styled as cartoon/tube/trace.
 pub fn spawn_ribbon_on_load(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     backbone: Res<ProteinBackbone>,
-    viz_config: Res<VisualizationConfig>,
+    styles: Res<AtomStyles>,
     mut ribbon_entities: ResMut<RibbonEntities>,
     mut spawned_events: EventReader<InstancedAtomsSpawnedEvent>,
 ) {
-    if spawned_events.read().next().is_none() || !ribbon_entities.entities.is_empty() {
+    if spawned_events.read().next().is_none() || ribbon_entities.material.is_some() {
         return;
     }
 
@@ -68,44 +168,40 @@ pub fn spawn_ribbon_on_load(
         return;
     }
 
-    let mesh = meshes.add(build_ribbon_mesh(
-        &backbone.residues,
-        viz_config.render_mode,
-    ));
-    let material = materials.add(StandardMaterial {
+    ribbon_entities.material = Some(materials.add(StandardMaterial {
         base_color: Color::WHITE,
         unlit: true,
         ..default()
-    });
-
-    let entity = commands
-        .spawn((
-            PbrBundle {
-                mesh,
-                material,
-                visibility: Visibility::Hidden,
-                ..default()
-            },
-            ProteinRibbon,
-        ))
-        .id();
-
-    ribbon_entities.entities.push(entity);
+    }));
+
+    let runs = ribbon_runs(&backbone.residues, &styles);
+    respawn_ribbon_runs(
+        &mut commands,
+        &mut meshes,
+        &mut ribbon_entities,
+        &backbone.residues,
+        runs,
+    );
     info!(
-        "Spawned protein ribbon mesh ({} residues)",
-        backbone.residues.len()
+        "Protein ribbon ready ({} residues, {} runs)",
+        backbone.residues.len(),
+        ribbon_entities.runs.len()
     );
 }
 
-/// Rebuild ribbon geometry when render mode switches between cartoon/tube/trace.
+/// Rebuild ribbon runs when render modes or groups change which residues
+/// are drawn as cartoon/tube/trace.
+#[allow(clippy::too_many_arguments)]
 pub fn update_ribbon_for_mode(
-    viz_config: Res<VisualizationConfig>,
+    mut commands: Commands,
+    styles: Res<AtomStyles>,
     backbone: Res<ProteinBackbone>,
-    ribbon_entities: ResMut<RibbonEntities>,
+    index: Res<InstancedAtomIndex>,
+    instanced: Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
+    mut ribbon_entities: ResMut<RibbonEntities>,
     mut meshes: ResMut<Assets<Mesh>>,
-    mesh_query: Query<&Handle<Mesh>, With<ProteinRibbon>>,
 ) {
-    if !viz_config.is_changed() || ribbon_entities.entities.is_empty() {
+    if !styles.is_changed() || ribbon_entities.material.is_none() {
         return;
     }
 
@@ -113,30 +209,28 @@ pub fn update_ribbon_for_mode(
         return;
     }
 
-    let mode = viz_config.render_mode;
-    if !mode.shows_ribbon() {
+    let runs = ribbon_runs(&backbone.residues, &styles);
+    if runs == ribbon_entities.runs {
         return;
     }
 
-    for entity in &ribbon_entities.entities {
-        let Ok(handle) = mesh_query.get(*entity) else {
-            continue;
-        };
-        if let Some(mesh) = meshes.get_mut(handle) {
-            *mesh = build_ribbon_mesh(&backbone.residues, mode);
-        }
-    }
+    let residues = current_residues(&backbone, &index, &instanced);
+    respawn_ribbon_runs(
+        &mut commands,
+        &mut meshes,
+        &mut ribbon_entities,
+        &residues,
+        runs,
+    );
 }
 
 /// Update ribbon positions when timeline advances.
-#[allow(clippy::too_many_arguments)]
 pub fn update_ribbon_positions(
     backbone: Res<ProteinBackbone>,
     index: Res<InstancedAtomIndex>,
-    viz_config: Res<VisualizationConfig>,
     timeline: Res<crate::core::trajectory::TimelineState>,
     instanced: Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
-    ribbon_entities: ResMut<RibbonEntities>,
+    ribbon_entities: Res<RibbonEntities>,
     mut meshes: ResMut<Assets<Mesh>>,
     mesh_query: Query<&Handle<Mesh>, With<ProteinRibbon>>,
 ) {
@@ -148,57 +242,35 @@ pub fn update_ribbon_positions(
         return;
     }
 
-    let mut residues = backbone.residues.clone();
-    for residue in &mut residues {
-        if let Some(pos) = index.get_position(residue.ca_atom_id, &instanced) {
-            residue.position = pos;
-        }
-    }
-
-    let mesh_data = build_ribbon_mesh(&residues, viz_config.render_mode);
-    for entity in &ribbon_entities.entities {
+    let residues = current_residues(&backbone, &index, &instanced);
+    for (entity, run) in ribbon_entities.entities.iter().zip(&ribbon_entities.runs) {
         let Ok(handle) = mesh_query.get(*entity) else {
             continue;
         };
         if let Some(mesh) = meshes.get_mut(handle) {
-            *mesh = mesh_data.clone();
+            *mesh = build_ribbon_mesh(&residues[run.residues.clone()], run.mode);
         }
     }
 }
 
-pub fn update_ribbon_visibility(
-    viz_config: Res<VisualizationConfig>,
-    backbone: Res<ProteinBackbone>,
-    mut ribbon_query: Query<&mut Visibility, With<ProteinRibbon>>,
-) {
-    if !viz_config.is_changed() {
-        return;
-    }
-
-    let show = viz_config.render_mode.shows_ribbon() && backbone.cartoon_available;
-
-    for mut visibility in ribbon_query.iter_mut() {
-        *visibility = if show {
-            Visibility::Visible
-        } else {
-            Visibility::Hidden
-        };
-    }
-}
-
 pub fn clear_ribbon_on_load(
     mut commands: Commands,
     mut ribbon_entities: ResMut<RibbonEntities>,
     mut backbone: ResMut<ProteinBackbone>,
     mut file_loaded_events: EventReader<crate::systems::loading::FileLoadedEvent>,
+    mut topology_events: EventReader<crate::systems::loading::TopologyAppliedEvent>,
 ) {
-    if file_loaded_events.read().next().is_none() {
+    let reload =
+        file_loaded_events.read().next().is_some() || topology_events.read().next().is_some();
+    if !reload {
         return;
     }
 
     for entity in ribbon_entities.entities.drain(..) {
         commands.entity(entity).despawn_recursive();
     }
+    ribbon_entities.runs.clear();
+    ribbon_entities.material = None;
     backbone.clear();
 }
 
@@ -415,4 +487,49 @@ mod tests {
         let mesh = build_ribbon_mesh(&sample_residues(), RenderMode::Trace);
         assert!(mesh.count_vertices() >= 2);
     }
+
+    #[test]
+    fn test_ribbon_runs_follow_group_styles() {
+        use crate::core::groups::{AtomGroup, AtomGroups, GroupStyle, HiddenAtoms};
+        use crate::core::visualization::VisualizationConfig;
+
+        let config = VisualizationConfig {
+            render_mode: RenderMode::Cartoon,
+            ..Default::default()
+        };
+        let mut groups = AtomGroups::default();
+        groups.add(AtomGroup {
+            name: "tail".into(),
+            query: None,
+            atom_ids: vec![3, 4],
+            style: GroupStyle::new(RenderMode::Trace),
+        });
+        let hidden = HiddenAtoms::default();
+        let mut styles = AtomStyles::default();
+        styles.update(&groups, &hidden, &config);
+
+        let runs = ribbon_runs(&sample_residues(), &styles);
+        assert_eq!(
+            runs,
+            vec![
+                RibbonRun {
+                    mode: RenderMode::Cartoon,
+                    residues: 0..3
+                },
+                RibbonRun {
+                    mode: RenderMode::Trace,
+                    residues: 3..5
+                },
+            ]
+        );
+
+        groups.add(AtomGroup {
+            name: "hidden".into(),
+            query: None,
+            atom_ids: vec![0, 1, 2, 3, 4],
+            style: GroupStyle::hidden(),
+        });
+        styles.update(&groups, &hidden, &config);
+        assert!(ribbon_runs(&sample_residues(), &styles).is_empty());
+    }
 }
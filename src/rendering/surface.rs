@@ -41,17 +41,14 @@ pub fn grid_spacing_for_system(num_atoms: usize, bbox_diagonal: f32) -> f32 {
 /// Build a solvent-accessible surface mesh (union of VdW + probe spheres, voxel shell).
 pub fn build_solvent_accessible_surface(
     atoms: &[AtomData],
-    positions: &std::collections::HashMap<u32, Vec3>,
+    positions: &[Vec3],
     spacing: f32,
     probe: f32,
 ) -> Mesh {
     let spheres: Vec<(Vec3, f32)> = atoms
         .iter()
-        .filter_map(|a| {
-            positions
-                .get(&a.id)
-                .map(|p| (*p, a.element.vdw_radius() + probe))
-        })
+        .zip(positions)
+        .map(|(a, p)| (*p, a.element.vdw_radius() + probe))
         .collect();
 
     build_voxel_shell_mesh(&spheres, spacing)
@@ -185,8 +182,11 @@ pub fn clear_surface_on_load(
     mut commands: Commands,
     mut surface_entities: ResMut<SurfaceEntities>,
     mut file_loaded_events: EventReader<crate::systems::loading::FileLoadedEvent>,
+    mut topology_events: EventReader<crate::systems::loading::TopologyAppliedEvent>,
 ) {
-    if file_loaded_events.read().next().is_none() {
+    let reload =
+        file_loaded_events.read().next().is_some() || topology_events.read().next().is_some();
+    if !reload {
         return;
     }
     if let Some(entity) = surface_entities.entity.take() {
@@ -208,7 +208,7 @@ pub fn spawn_surface_on_load(
     if spawned_events.read().next().is_none() || surface_entities.entity.is_some() {
         return;
     }
-    if !sim_data.loaded || sim_data.atom_data.is_empty() {
+    if !sim_data.loaded || sim_data.atom_data().is_empty() {
         return;
     }
 
@@ -219,7 +219,7 @@ pub fn spawn_surface_on_load(
 
     let mut min = Vec3::splat(f32::MAX);
     let mut max = Vec3::splat(f32::MIN);
-    for pos in positions.values() {
+    for pos in &positions {
         min = min.min(*pos);
         max = max.max(*pos);
     }
@@ -227,7 +227,7 @@ pub fn spawn_surface_on_load(
     let spacing = grid_spacing_for_system(sim_data.num_atoms(), diagonal);
 
     let mesh = build_solvent_accessible_surface(
-        &sim_data.atom_data,
+        sim_data.atom_data(),
         &positions,
         spacing,
         DEFAULT_PROBE_RADIUS,
@@ -300,10 +300,11 @@ mod tests {
             AtomData::new(1, Element::H, 0, "HOH".into(), "A".into(), "H1".into()),
             AtomData::new(2, Element::H, 0, "HOH".into(), "A".into(), "H2".into()),
         ];
-        let mut positions = std::collections::HashMap::new();
-        positions.insert(0, Vec3::ZERO);
-        positions.insert(1, Vec3::new(0.757, 0.0, 0.0));
-        positions.insert(2, Vec3::new(-0.757, 0.0, 0.0));
+        let positions = [
+            Vec3::ZERO,
+            Vec3::new(0.757, 0.0, 0.0),
+            Vec3::new(-0.757, 0.0, 0.0),
+        ];
 
         let mesh = build_solvent_accessible_surface(&atoms, &positions, 0.5, DEFAULT_PROBE_RADIUS);
         let verts = mesh.attribute(Mesh::ATTRIBUTE_POSITION).expect("positions");
@@ -0,0 +1,223 @@
+//! Periodic simulation cell drawn as a wireframe box.
+//!
+//! The cell is read from the resolved timeline frame (`FrameData::cell`), so
+//! NPT trajectories show the box breathing during playback. Triclinic cells
+//! are drawn as the parallelepiped spanned by their box vectors. Lines reuse
+//! the `LineList` mesh builder from wireframe bond rendering.
+
+use crate::core::cell::UnitCell;
+use crate::core::trajectory::TimelineState;
+use crate::core::visualization::VisualizationConfig;
+use crate::rendering::wireframe::generate_bond_line_mesh;
+use crate::systems::frame_cache::TimelineFrames;
+use bevy::prelude::*;
+
+/// Cell changes smaller than this (Å) keep the existing mesh.
+const CELL_EPSILON: f32 = 1e-4;
+
+/// Marker for the unit cell line entity.
+#[derive(Component)]
+pub struct UnitCellBox;
+
+#[derive(Resource, Default, Debug)]
+pub struct UnitCellEntities {
+    pub entity: Option<Entity>,
+    /// Cell the current mesh was built from.
+    pub cell: Option<UnitCell>,
+}
+
+/// The 12 edges of the parallelepiped spanned by the cell vectors, one corner at the origin.
+pub fn unit_cell_segments(cell: &UnitCell) -> Vec<(Vec3, Vec3)> {
+    let corners = cell.corners();
+
+    let mut segments = Vec::with_capacity(12);
+    for i in 0..8 {
+        for bit in [1, 2, 4] {
+            if i & bit == 0 {
+                segments.push((corners[i], corners[i | bit]));
+            }
+        }
+    }
+    segments
+}
+
+/// Cell for the displayed frame, blended towards the next frame while interpolating.
+fn displayed_cell(frames: &TimelineFrames, alpha: f32) -> Option<UnitCell> {
+    let current = frames.current.as_ref()?.cell?;
+    let next = frames.next.as_ref().and_then(|f| f.cell);
+    match next {
+        Some(next) if alpha > 0.0 => Some(current.lerp(&next, alpha)),
+        _ => Some(current),
+    }
+}
+
+fn cell_changed(old: Option<&UnitCell>, new: &UnitCell) -> bool {
+    match old {
+        Some(old) => old
+            .vectors
+            .iter()
+            .zip(new.vectors)
+            .any(|(a, b)| (*a - b).abs().max_element() > CELL_EPSILON),
+        None => true,
+    }
+}
+
+/// Spawn the cell box on first use and rebuild its lines when the cell changes.
+#[allow(clippy::too_many_arguments)]
+pub fn update_unit_cell(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    frames: Res<TimelineFrames>,
+    timeline: Res<TimelineState>,
+    viz_config: Res<VisualizationConfig>,
+    mut cell_entities: ResMut<UnitCellEntities>,
+    mesh_query: Query<&Handle<Mesh>, With<UnitCellBox>>,
+) {
+    if !frames.is_changed() && !timeline.is_changed() {
+        return;
+    }
+
+    let Some(cell) = displayed_cell(&frames, timeline.frame_alpha()) else {
+        return;
+    };
+    if !cell.is_periodic() || !cell_changed(cell_entities.cell.as_ref(), &cell) {
+        return;
+    }
+
+    let mesh = generate_bond_line_mesh(&unit_cell_segments(&cell));
+
+    if let Some(entity) = cell_entities.entity {
+        if let Some(existing) = mesh_query
+            .get(entity)
+            .ok()
+            .and_then(|handle| meshes.get_mut(handle))
+        {
+            *existing = mesh;
+        }
+    } else {
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgb(0.85, 0.85, 0.55),
+            unlit: true,
+            ..default()
+        });
+        let entity = commands
+            .spawn((
+                PbrBundle {
+                    mesh: meshes.add(mesh),
+                    material,
+                    visibility: if viz_config.show_unit_cell {
+                        Visibility::Visible
+                    } else {
+                        Visibility::Hidden
+                    },
+                    ..default()
+                },
+                UnitCellBox,
+            ))
+            .id();
+        cell_entities.entity = Some(entity);
+        info!(
+            "Spawned unit cell box ({:.2} × {:.2} × {:.2}, {:.1}° {:.1}° {:.1}°)",
+            cell.lengths[0],
+            cell.lengths[1],
+            cell.lengths[2],
+            cell.angles[0],
+            cell.angles[1],
+            cell.angles[2]
+        );
+    }
+
+    cell_entities.cell = Some(cell);
+}
+
+/// Toggle the cell box from the Visualization panel.
+pub fn update_unit_cell_visibility(
+    viz_config: Res<VisualizationConfig>,
+    cell_entities: Res<UnitCellEntities>,
+    mut visibility_query: Query<&mut Visibility, With<UnitCellBox>>,
+) {
+    if !viz_config.is_changed() {
+        return;
+    }
+
+    let Some(entity) = cell_entities.entity else {
+        return;
+    };
+
+    if let Ok(mut visibility) = visibility_query.get_mut(entity) {
+        *visibility = if viz_config.show_unit_cell {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+pub fn clear_unit_cell_on_load(
+    mut commands: Commands,
+    mut cell_entities: ResMut<UnitCellEntities>,
+    mut file_loaded_events: EventReader<crate::systems::loading::FileLoadedEvent>,
+) {
+    if file_loaded_events.read().next().is_none() {
+        return;
+    }
+
+    if let Some(entity) = cell_entities.entity.take() {
+        commands.entity(entity).despawn_recursive();
+    }
+    cell_entities.cell = None;
+}
+
+pub fn register(app: &mut App) {
+    app.init_resource::<UnitCellEntities>();
+    info!("Unit cell rendering module registered");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::trajectory::FrameData;
+
+    #[test]
+    fn test_unit_cell_has_twelve_edges() {
+        let segments = unit_cell_segments(&UnitCell::orthorhombic([10.0, 20.0, 30.0]));
+        assert_eq!(segments.len(), 12);
+        for (a, b) in &segments {
+            let edge = (*b - *a).abs();
+            assert!([10.0, 20.0, 30.0].contains(&edge.max_element()));
+            assert_eq!(edge.min_element(), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_triclinic_edges_follow_box_vectors() {
+        let cell = UnitCell::from_lengths_angles([10.0, 10.0, 10.0], [90.0, 90.0, 60.0]);
+        let segments = unit_cell_segments(&cell);
+        let b_edges = segments
+            .iter()
+            .filter(|(a, b)| (*b - *a - cell.vectors[1]).length() < 1e-4)
+            .count();
+        assert_eq!(b_edges, 4);
+    }
+
+    #[test]
+    fn test_displayed_cell_interpolates_between_frames() {
+        let mut current = FrameData::new(0, 0.0);
+        current.cell = Some(UnitCell::orthorhombic([10.0, 10.0, 10.0]));
+        let mut next = FrameData::new(1, 1.0);
+        next.cell = Some(UnitCell::orthorhombic([12.0, 10.0, 10.0]));
+        let frames = TimelineFrames {
+            current: Some(current),
+            next: Some(next),
+            ..Default::default()
+        };
+
+        let start = displayed_cell(&frames, 0.0).unwrap();
+        let mid = displayed_cell(&frames, 0.5).unwrap();
+        assert_eq!(start.lengths, [10.0, 10.0, 10.0]);
+        assert!((mid.lengths[0] - 11.0).abs() < 1e-5);
+        assert!(!cell_changed(Some(&start), &start));
+        assert!(cell_changed(Some(&start), &mid));
+    }
+}
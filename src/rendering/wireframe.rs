@@ -1,9 +1,10 @@
 //! Wireframe bond rendering using `LineList` topology.
 //!
 //! Used when `RenderMode::Wireframe` is active — atoms are hidden and bonds
-//! are drawn as thin unlit lines between connected atom pairs.
+//! are drawn as thin unlit lines between connected atom pairs. With atom
+//! groups, only bonds whose two atoms are both styled as wireframe get lines.
 
-use crate::core::visualization::VisualizationConfig;
+use crate::core::groups::AtomStyles;
 use crate::performance::PerformanceSettings;
 use crate::rendering::atom_index::InstancedAtomIndex;
 use crate::rendering::instanced::{
@@ -15,7 +16,6 @@ use crate::utils::spatial_index::AtomSpatialIndex;
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
 use bevy::render::render_asset::RenderAssetUsages;
-use std::collections::HashMap;
 
 const RENDER_ASSET_USAGES: RenderAssetUsages = RenderAssetUsages::RENDER_WORLD;
 
@@ -44,32 +44,34 @@ pub fn generate_bond_line_mesh(segments: &[(Vec3, Vec3)]) -> Mesh {
     mesh
 }
 
+#[allow(clippy::too_many_arguments)]
 fn collect_bond_segments(
     sim_data: &SimulationData,
-    positions: &HashMap<u32, Vec3>,
     index: &InstancedAtomIndex,
     instanced: &Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
     bond_config: &BondDetectionConfig,
     perf: &PerformanceSettings,
     spatial_index: Option<&AtomSpatialIndex>,
+    styles: Option<&AtomStyles>,
 ) -> Vec<(Vec3, Vec3)> {
-    let positions = if positions.is_empty() {
-        index.collect_positions(instanced)
-    } else {
-        positions.clone()
-    };
+    let positions = index.collect_positions(instanced);
 
     let bonds = resolve_bond_list(sim_data, &positions, bond_config, perf, spatial_index);
 
     let mut segments = Vec::with_capacity(bonds.len());
     for bond in bonds {
-        let Some(a) = positions.get(&bond.atom_a_id) else {
+        if let Some(styles) = styles {
+            if !styles.get(bond.atom_a_id).wire_bonds || !styles.get(bond.atom_b_id).wire_bonds {
+                continue;
+            }
+        }
+        let Some(a) = sim_data.atom_position(&positions, bond.atom_a_id) else {
             continue;
         };
-        let Some(b) = positions.get(&bond.atom_b_id) else {
+        let Some(b) = sim_data.atom_position(&positions, bond.atom_b_id) else {
             continue;
         };
-        segments.push((*a, *b));
+        segments.push((a, b));
     }
     segments
 }
@@ -99,12 +101,12 @@ pub fn spawn_wireframe_bonds(
 
     let segments = collect_bond_segments(
         &sim_data,
-        &HashMap::new(),
         &index,
         &instanced,
         &bond_config,
         &perf,
         Some(&spatial_index),
+        None,
     );
     if segments.is_empty() {
         return;
@@ -141,78 +143,125 @@ pub fn update_wireframe_bond_positions(
     perf: Res<PerformanceSettings>,
     spatial_index: Res<AtomSpatialIndex>,
     index: Res<InstancedAtomIndex>,
+    styles: Res<AtomStyles>,
     instanced: Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
-    wireframe_entities: ResMut<WireframeBondEntities>,
+    wireframe_entities: Res<WireframeBondEntities>,
     mut meshes: ResMut<Assets<Mesh>>,
-    mesh_query: Query<&Handle<Mesh>, With<WireframeBonds>>,
+    mut line_query: Query<(&Handle<Mesh>, &mut Visibility), With<WireframeBonds>>,
     timeline: Res<crate::core::trajectory::TimelineState>,
 ) {
-    let Some(entity) = wireframe_entities.entity else {
-        return;
-    };
-
-    if !sim_data.loaded {
-        return;
-    }
-
     if !timeline.is_changed() && !index.is_changed() {
         return;
     }
 
-    let Ok(mesh_handle) = mesh_query.get(entity) else {
+    // Hidden lines are rebuilt by `update_wireframe_visibility` when they reappear.
+    if !styles.any(|s| s.wire_bonds) {
         return;
-    };
+    }
 
-    let segments = collect_bond_segments(
+    refresh_wireframe_lines(
         &sim_data,
-        &HashMap::new(),
-        &index,
-        &instanced,
         &bond_config,
         &perf,
-        Some(&spatial_index),
+        &spatial_index,
+        &index,
+        &styles,
+        &instanced,
+        &wireframe_entities,
+        &mut meshes,
+        &mut line_query,
     );
-    if segments.is_empty() {
-        return;
-    }
-
-    if let Some(mesh) = meshes.get_mut(mesh_handle) {
-        *mesh = generate_bond_line_mesh(&segments);
-    }
 }
 
-/// Show wireframe lines only in wireframe mode; hide cylinder bonds separately.
+/// Show wireframe lines for atoms styled as wireframe (globally or by group)
+/// and rebuild them for the atoms that currently use lines.
+#[allow(clippy::too_many_arguments)]
 pub fn update_wireframe_visibility(
-    viz_config: Res<VisualizationConfig>,
-    wireframe_entities: ResMut<WireframeBondEntities>,
-    mut visibility_query: Query<&mut Visibility, With<WireframeBonds>>,
+    sim_data: Res<SimulationData>,
+    bond_config: Res<BondDetectionConfig>,
+    perf: Res<PerformanceSettings>,
+    spatial_index: Res<AtomSpatialIndex>,
+    index: Res<InstancedAtomIndex>,
+    styles: Res<AtomStyles>,
+    instanced: Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
+    wireframe_entities: Res<WireframeBondEntities>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut line_query: Query<(&Handle<Mesh>, &mut Visibility), With<WireframeBonds>>,
 ) {
-    if !viz_config.is_changed() {
+    if !styles.is_changed() && !wireframe_entities.is_changed() {
         return;
     }
 
+    refresh_wireframe_lines(
+        &sim_data,
+        &bond_config,
+        &perf,
+        &spatial_index,
+        &index,
+        &styles,
+        &instanced,
+        &wireframe_entities,
+        &mut meshes,
+        &mut line_query,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn refresh_wireframe_lines(
+    sim_data: &SimulationData,
+    bond_config: &BondDetectionConfig,
+    perf: &PerformanceSettings,
+    spatial_index: &AtomSpatialIndex,
+    index: &InstancedAtomIndex,
+    styles: &AtomStyles,
+    instanced: &Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
+    wireframe_entities: &WireframeBondEntities,
+    meshes: &mut Assets<Mesh>,
+    line_query: &mut Query<(&Handle<Mesh>, &mut Visibility), With<WireframeBonds>>,
+) {
     let Some(entity) = wireframe_entities.entity else {
         return;
     };
-
-    let Ok(mut visibility) = visibility_query.get_mut(entity) else {
+    let Ok((mesh_handle, mut visibility)) = line_query.get_mut(entity) else {
         return;
     };
 
-    let show = viz_config.show_bonds && viz_config.render_mode.uses_wireframe_lines();
-    *visibility = if show {
-        Visibility::Visible
+    let segments = if sim_data.loaded && styles.any(|s| s.wire_bonds) {
+        collect_bond_segments(
+            sim_data,
+            index,
+            instanced,
+            bond_config,
+            perf,
+            Some(spatial_index),
+            Some(styles),
+        )
     } else {
-        Visibility::Hidden
+        Vec::new()
     };
+
+    if !segments.is_empty() {
+        if let Some(mesh) = meshes.get_mut(mesh_handle) {
+            *mesh = generate_bond_line_mesh(&segments);
+        }
+    }
+
+    visibility.set_if_neq(if segments.is_empty() {
+        Visibility::Hidden
+    } else {
+        Visibility::Visible
+    });
 }
 
 pub fn clear_wireframe_on_load(
     mut commands: Commands,
     mut wireframe_entities: ResMut<WireframeBondEntities>,
     mut file_loaded_events: EventReader<crate::systems::loading::FileLoadedEvent>,
+    mut topology_events: EventReader<crate::systems::loading::TopologyAppliedEvent>,
 ) {
-    if file_loaded_events.read().next().is_none() {
+    let reload =
+        file_loaded_events.read().next().is_some() || topology_events.read().next().is_some();
+    if !reload {
         return;
     }
 
@@ -146,10 +146,12 @@ fn compute_bond_rotation(bond_vector: Vec3, bond_length: f32) -> Quat {
 }
 
 /// Number of parallel cylinders to draw for a bond order.
+///
+/// Aromatic bonds are drawn like double bonds.
 pub fn bond_cylinder_count(order: BondOrder) -> usize {
     match order {
         BondOrder::Single => 1,
-        BondOrder::Double => 2,
+        BondOrder::Double | BondOrder::Aromatic => 2,
         BondOrder::Triple => 3,
     }
 }
@@ -226,32 +228,23 @@ fn spawn_bond_visual(
 
 fn detect_bonds_naive(
     sim_data: &crate::systems::loading::SimulationData,
-    positions: &HashMap<u32, Vec3>,
+    positions: &[Vec3],
     config: &BondDetectionConfig,
 ) -> Vec<BondData> {
-    if sim_data.atom_data.len() > MAX_NAIVE_BOND_ATOMS {
+    if sim_data.atom_data().len() > MAX_NAIVE_BOND_ATOMS {
         warn!(
             "Skipping distance bond detection for {} atoms (limit {})",
-            sim_data.atom_data.len(),
+            sim_data.atom_data().len(),
             MAX_NAIVE_BOND_ATOMS
         );
         return Vec::new();
     }
 
     let mut bonds = Vec::new();
-    let atoms = &sim_data.atom_data;
-
-    for i in 0..atoms.len() {
-        let a = &atoms[i];
-        let Some(pos_a) = positions.get(&a.id) else {
-            continue;
-        };
-
-        for b in atoms.iter().skip(i + 1) {
-            let Some(pos_b) = positions.get(&b.id) else {
-                continue;
-            };
+    let atoms = sim_data.atom_data();
 
+    for (i, (a, pos_a)) in atoms.iter().zip(positions).enumerate() {
+        for (b, pos_b) in atoms.iter().zip(positions).skip(i + 1) {
             let distance = pos_a.distance(*pos_b);
             if config.should_bond(a.element, b.element, a.residue_id, b.residue_id, distance) {
                 bonds.push(BondData::new(
@@ -270,27 +263,20 @@ fn detect_bonds_naive(
 
 fn detect_bonds_spatial(
     sim_data: &crate::systems::loading::SimulationData,
-    positions: &HashMap<u32, Vec3>,
+    positions: &[Vec3],
     config: &BondDetectionConfig,
     spatial_index: &AtomSpatialIndex,
 ) -> Vec<BondData> {
     let mut bonds = Vec::new();
-    let atom_map: HashMap<u32, &AtomData> = sim_data.atom_data.iter().map(|a| (a.id, a)).collect();
-
-    for atom in &sim_data.atom_data {
-        let Some(pos_a) = positions.get(&atom.id) else {
-            continue;
-        };
+    let atoms = sim_data.atom_data();
 
-        let neighbors = spatial_index.neighbors_within(*pos_a, config.max_bond_distance);
-        for neighbor_id in neighbors {
-            if neighbor_id <= atom.id {
+    for (i, (atom, pos_a)) in atoms.iter().zip(positions).enumerate() {
+        let neighbors = spatial_index.neighbor_indices_within(*pos_a, config.max_bond_distance);
+        for j in neighbors {
+            if j <= i {
                 continue;
             }
-            let Some(atom_b) = atom_map.get(&neighbor_id) else {
-                continue;
-            };
-            let Some(pos_b) = positions.get(&neighbor_id) else {
+            let (Some(atom_b), Some(pos_b)) = (atoms.get(j), positions.get(j)) else {
                 continue;
             };
             let distance = pos_a.distance(*pos_b);
@@ -303,7 +289,7 @@ fn detect_bonds_spatial(
             ) {
                 bonds.push(BondData::new(
                     atom.id,
-                    neighbor_id,
+                    atom_b.id,
                     config.determine_bond_type(atom.element, atom_b.element),
                     config.determine_bond_order(atom.element, atom_b.element, distance),
                     distance,
@@ -317,13 +303,13 @@ fn detect_bonds_spatial(
 
 fn detect_bonds_from_distance(
     sim_data: &crate::systems::loading::SimulationData,
-    positions: &HashMap<u32, Vec3>,
+    positions: &[Vec3],
     config: &BondDetectionConfig,
     perf: &PerformanceSettings,
     spatial_index: Option<&AtomSpatialIndex>,
 ) -> Vec<BondData> {
     let use_spatial = perf.spatial_bond_detection
-        && sim_data.atom_data.len() >= perf.spatial_bond_threshold
+        && sim_data.atom_data().len() >= perf.spatial_bond_threshold
         && spatial_index.map(|s| s.is_built()).unwrap_or(false);
 
     if use_spatial {
@@ -342,22 +328,56 @@ fn dedupe_bonds(bonds: Vec<BondData>) -> Vec<BondData> {
     seen.into_values().collect()
 }
 
+/// Whether file bonds describe the whole structure.
+///
+/// Complete topologies (PSF, prmtop, MOL2, SDF) bond every atom that shares
+/// its residue with another atom. PDB CONECT and mmCIF `_struct_conn` only
+/// list links such as disulfides and ligand bonds, leaving the rest to
+/// distance detection. An empty list is never complete, even when every
+/// residue has a single atom (CA-only traces).
+pub fn file_bonds_complete(atom_data: &[AtomData], bonds: &[BondData]) -> bool {
+    if bonds.is_empty() {
+        return false;
+    }
+    let mut bonded = std::collections::HashSet::new();
+    for bond in bonds {
+        bonded.insert(bond.atom_a_id);
+        bonded.insert(bond.atom_b_id);
+    }
+    let mut residue_sizes: HashMap<(&str, u32), usize> = HashMap::new();
+    for atom in atom_data {
+        *residue_sizes
+            .entry((atom.chain_id.as_str(), atom.residue_id))
+            .or_default() += 1;
+    }
+    atom_data.iter().all(|atom| {
+        bonded.contains(&atom.id) || residue_sizes[&(atom.chain_id.as_str(), atom.residue_id)] == 1
+    })
+}
+
 /// Resolve the bond list from file topology or distance detection.
+///
+/// Partial file bonds are combined with distance detection; file bonds win
+/// for pairs found by both, keeping their type and order. `positions` are in
+/// `atom_data` order.
 pub fn resolve_bond_list(
     sim_data: &crate::systems::loading::SimulationData,
-    positions: &HashMap<u32, Vec3>,
+    positions: &[Vec3],
     config: &BondDetectionConfig,
     perf: &PerformanceSettings,
     spatial_index: Option<&AtomSpatialIndex>,
 ) -> Vec<BondData> {
     let start = std::time::Instant::now();
-    let bonds = if !sim_data.bond_data.is_empty() {
-        sim_data.bond_data.clone()
-    } else if config.enabled {
-        detect_bonds_from_distance(sim_data, positions, config, perf, spatial_index)
-    } else {
-        Vec::new()
-    };
+    let mut bonds = sim_data.bond_data.clone();
+    if config.enabled && !file_bonds_complete(sim_data.atom_data(), &bonds) {
+        bonds.extend(detect_bonds_from_distance(
+            sim_data,
+            positions,
+            config,
+            perf,
+            spatial_index,
+        ));
+    }
     let elapsed_ms = start.elapsed().as_secs_f32() * 1000.0;
     if elapsed_ms > 1.0 {
         debug!(
@@ -417,14 +437,14 @@ pub fn spawn_bonds(
     let base_radius = 0.1;
 
     for bond_data in bonds {
-        let Some(pos_a) = positions.get(&bond_data.atom_a_id) else {
+        let Some(pos_a) = sim_data.atom_position(&positions, bond_data.atom_a_id) else {
             continue;
         };
-        let Some(pos_b) = positions.get(&bond_data.atom_b_id) else {
+        let Some(pos_b) = sim_data.atom_position(&positions, bond_data.atom_b_id) else {
             continue;
         };
 
-        let bond_vector = *pos_b - *pos_a;
+        let bond_vector = pos_b - pos_a;
         let bond_length = bond_vector.length();
         if bond_length < config.min_bond_distance {
             continue;
@@ -441,8 +461,8 @@ pub fn spawn_bonds(
             &mut meshes,
             bond_material.clone(),
             &bond_data,
-            *pos_a,
-            *pos_b,
+            pos_a,
+            pos_b,
             bond_length,
             base_radius,
             visibility,
@@ -462,6 +482,7 @@ pub fn spawn_bonds(
 
 /// Update bond transforms from instanced atom positions.
 pub fn update_bond_positions(
+    sim_data: Res<crate::systems::loading::SimulationData>,
     index: Res<InstancedAtomIndex>,
     instanced: Query<(&InstancedAtomEntity, &InstancedAtomMesh)>,
     mut bond_query: Query<(&Bond, &mut Transform)>,
@@ -473,17 +494,17 @@ pub fn update_bond_positions(
     let positions = index.collect_positions(&instanced);
 
     for (bond, mut transform) in bond_query.iter_mut() {
-        let Some(pos_a) = positions.get(&bond.atom_a_id) else {
+        let Some(pos_a) = sim_data.atom_position(&positions, bond.atom_a_id) else {
             continue;
         };
-        let Some(pos_b) = positions.get(&bond.atom_b_id) else {
+        let Some(pos_b) = sim_data.atom_position(&positions, bond.atom_b_id) else {
             continue;
         };
 
-        let bond_vector = *pos_b - *pos_a;
+        let bond_vector = pos_b - pos_a;
         let bond_length = bond_vector.length();
 
-        transform.translation = *pos_a + bond_vector * 0.5;
+        transform.translation = pos_a + bond_vector * 0.5;
         transform.rotation = compute_bond_rotation(bond_vector, bond_length);
     }
 }
@@ -510,9 +531,12 @@ pub fn clear_bonds_on_load(
     mut commands: Commands,
     mut bond_entities: ResMut<BondEntities>,
     mut file_loaded_events: EventReader<crate::systems::loading::FileLoadedEvent>,
+    mut topology_events: EventReader<crate::systems::loading::TopologyAppliedEvent>,
     mut despawned_event: EventWriter<BondsDespawnedEvent>,
 ) {
-    if file_loaded_events.read().next().is_none() || bond_entities.entities.is_empty() {
+    let reload =
+        file_loaded_events.read().next().is_some() || topology_events.read().next().is_some();
+    if !reload || bond_entities.entities.is_empty() {
         return;
     }
 
@@ -536,7 +560,7 @@ pub fn build_spatial_index_on_spawn(
     }
 
     let positions = index.collect_positions(&instanced);
-    *spatial_index = AtomSpatialIndex::build(&sim_data.atom_data, &positions);
+    *spatial_index = AtomSpatialIndex::build(sim_data.atom_data(), &positions);
     info!(
         "Built atom spatial index ({} atoms)",
         spatial_index.atom_count
@@ -546,8 +570,11 @@ pub fn build_spatial_index_on_spawn(
 pub fn clear_spatial_index_on_load(
     mut spatial_index: ResMut<AtomSpatialIndex>,
     mut file_loaded_events: EventReader<crate::systems::loading::FileLoadedEvent>,
+    mut topology_events: EventReader<crate::systems::loading::TopologyAppliedEvent>,
 ) {
-    if file_loaded_events.read().next().is_none() {
+    let reload =
+        file_loaded_events.read().next().is_some() || topology_events.read().next().is_some();
+    if !reload {
         return;
     }
     spatial_index.clear();
@@ -593,11 +620,60 @@ mod tests {
     #[test]
     fn test_bond_order_cylinder_layout() {
         assert_eq!(bond_cylinder_count(BondOrder::Double), 2);
+        assert_eq!(bond_cylinder_count(BondOrder::Aromatic), 2);
         let offsets = bond_cylinder_local_offsets(BondOrder::Triple, 0.2);
         assert_eq!(offsets.len(), 3);
         assert!(offsets[0] < offsets[1] && offsets[1] < offsets[2]);
     }
 
+    #[test]
+    fn test_file_bonds_complete() {
+        let atom = |id, residue_id, name: &str| {
+            AtomData::new(
+                id,
+                Element::C,
+                residue_id,
+                "LIG".into(),
+                "A".into(),
+                name.into(),
+            )
+        };
+        // Two-atom residue plus a lone ion
+        let atoms = vec![atom(0, 1, "C1"), atom(1, 1, "C2"), atom(2, 2, "NA")];
+        let bond = BondData::new(0, 1, BondType::Covalent, BondOrder::Single, 1.5);
+        assert!(file_bonds_complete(&atoms, std::slice::from_ref(&bond)));
+
+        // A disulfide-only list leaves the residue atoms to distance detection
+        let mut atoms = atoms;
+        atoms.push(atom(3, 1, "C3"));
+        assert!(!file_bonds_complete(&atoms, &[bond]));
+        assert!(!file_bonds_complete(&atoms, &[]));
+    }
+
+    #[test]
+    fn test_ca_only_trace_uses_distance_detection() {
+        let atoms: Vec<AtomData> = (0..5)
+            .map(|i| AtomData::new(i, Element::C, i + 1, "ALA".into(), "A".into(), "CA".into()))
+            .collect();
+        assert!(!file_bonds_complete(&atoms, &[]));
+
+        let positions: Vec<Vec3> = (0..5)
+            .map(|i| Vec3::new(i as f32 * 2.9, 0.0, 0.0))
+            .collect();
+        let sim = crate::systems::loading::SimulationData::new(
+            crate::core::trajectory::Trajectory::new(std::path::PathBuf::from("ca.pdb"), 5, 1.0),
+            atoms,
+        );
+        let bonds = resolve_bond_list(
+            &sim,
+            &positions,
+            &BondDetectionConfig::default(),
+            &PerformanceSettings::default(),
+            None,
+        );
+        assert_eq!(bonds.len(), 4);
+    }
+
     #[test]
     fn test_should_bond_too_far() {
         let config = BondDetectionConfig::default();
@@ -616,9 +692,9 @@ mod tests {
         let atoms: Vec<AtomData> = (0..200)
             .map(|i| AtomData::new(i, Element::C, 0, "UNK".into(), "A".into(), format!("C{i}")))
             .collect();
-        let positions: HashMap<u32, Vec3> = atoms
+        let positions: Vec<Vec3> = atoms
             .iter()
-            .map(|a| (a.id, Vec3::new(a.id as f32 * 1.4, 0.0, 0.0)))
+            .map(|a| Vec3::new(a.id as f32 * 1.4, 0.0, 0.0))
             .collect();
         let spatial = AtomSpatialIndex::build(&atoms, &positions);
         let sim = crate::systems::loading::SimulationData::new(
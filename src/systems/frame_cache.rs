@@ -2,11 +2,15 @@
 //!
 //! Large DCD trajectories use on-demand frame loading (plan 04). This module
 //! keeps the last N parsed frames in memory and prefetches upcoming frames
-//! during playback so timeline scrubbing stays responsive.
+//! during playback so timeline scrubbing stays responsive. Frames are read
+//! through [`PbcFrameProvider`], so cached frames already carry the PBC
+//! transforms in `systems::pbc`; the cache is cleared when those change.
 
 use crate::core::trajectory::{FrameData, TimelineState};
 use crate::io::streaming::FrameProvider;
+use crate::systems::bonds::BondEntities;
 use crate::systems::loading::{FileLoadedEvent, SimulationData};
+use crate::systems::pbc::{PbcFrameProvider, PbcTransformSettings, PbcTransformState};
 use bevy::prelude::*;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
@@ -30,6 +34,8 @@ pub struct TimelineFrames {
     pub streaming: bool,
     /// Number of frames currently held in the LRU cache.
     pub cached_count: usize,
+    /// Memory held by the cached frames, in bytes.
+    pub cached_bytes: usize,
 }
 
 /// LRU cache of parsed trajectory frames for streaming providers.
@@ -121,6 +127,11 @@ impl FrameCache {
         self.frames.insert(index, frame);
     }
 
+    /// Heap memory held by the cached frames, in bytes.
+    pub fn memory_bytes(&self) -> usize {
+        self.frames.values().map(FrameData::heap_bytes).sum()
+    }
+
     fn touch(&mut self, index: usize) {
         if let Some(pos) = self.lru.iter().position(|&i| i == index) {
             self.lru.remove(pos);
@@ -188,11 +199,15 @@ impl FrameCache {
 }
 
 /// Resolve current and next frames for the timeline (uses cache when streaming).
+#[allow(clippy::too_many_arguments)]
 pub fn resolve_timeline_frames(
     sim_data: Res<SimulationData>,
     timeline: Res<TimelineState>,
     mut cache: ResMut<FrameCache>,
     mut frames: ResMut<TimelineFrames>,
+    pbc_settings: Res<PbcTransformSettings>,
+    mut pbc_state: ResMut<PbcTransformState>,
+    bond_entities: Res<BondEntities>,
 ) {
     if !sim_data.loaded || sim_data.num_frames() == 0 {
         *frames = TimelineFrames::default();
@@ -201,8 +216,19 @@ pub fn resolve_timeline_frames(
 
     cache.poll_prefetch();
 
+    let topology_changed = sim_data.is_changed() || bond_entities.is_changed();
+    if topology_changed || pbc_settings.is_changed() {
+        pbc_state.rebuild(&pbc_settings, &sim_data, &bond_entities);
+    }
+    let pbc_dirty = pbc_settings.is_changed() || (topology_changed && pbc_settings.is_active());
+    if pbc_dirty {
+        // Cached frames were transformed with the old settings
+        cache.clear();
+    }
+
     frames.streaming = sim_data.is_streaming();
     frames.cached_count = cache.len();
+    frames.cached_bytes = cache.memory_bytes();
 
     let current_idx = timeline
         .current_frame
@@ -214,6 +240,7 @@ pub fn resolve_timeline_frames(
         && frames.current.is_some()
         && (!timeline.interpolate || timeline.interpolation_factor <= 0.0 || frames.next.is_some())
         && !frames.loading
+        && !pbc_dirty
     {
         return;
     }
@@ -222,14 +249,10 @@ pub fn resolve_timeline_frames(
     frames.next_index = next_idx;
     frames.loading = false;
 
-    if sim_data.is_streaming() {
-        let Some(provider) = sim_data.frame_provider() else {
-            frames.current = None;
-            frames.next = None;
-            return;
-        };
+    let source = PbcFrameProvider::new(sim_data.frames(), pbc_state.transform());
 
-        match cache.get_or_load(provider.as_ref(), current_idx) {
+    if sim_data.is_streaming() {
+        match cache.get_or_load(&source, current_idx) {
             Ok(frame) => frames.current = Some(frame),
             Err(err) => {
                 error!("Failed to load frame {current_idx}: {err}");
@@ -239,7 +262,7 @@ pub fn resolve_timeline_frames(
         }
 
         if timeline.interpolate && current_idx != next_idx {
-            match cache.get_or_load(provider.as_ref(), next_idx) {
+            match cache.get_or_load(&source, next_idx) {
                 Ok(frame) => frames.next = Some(frame),
                 Err(err) => {
                     warn!("Failed to load next frame {next_idx}: {err}");
@@ -250,9 +273,9 @@ pub fn resolve_timeline_frames(
             frames.next = None;
         }
     } else {
-        frames.current = sim_data.get_frame(current_idx);
+        frames.current = source.get_frame(current_idx).ok();
         frames.next = if timeline.interpolate && current_idx != next_idx {
-            sim_data.get_frame(next_idx)
+            source.get_frame(next_idx).ok()
         } else {
             None
         };
@@ -264,6 +287,7 @@ pub fn prefetch_during_playback(
     sim_data: Res<SimulationData>,
     timeline: Res<TimelineState>,
     mut cache: ResMut<FrameCache>,
+    pbc_state: Res<PbcTransformState>,
 ) {
     if !sim_data.is_streaming() || !timeline.is_playing || sim_data.num_frames() <= 1 {
         return;
@@ -276,7 +300,8 @@ pub fn prefetch_during_playback(
     let start = timeline.current_frame + 1;
     let end = (start + PREFETCH_AHEAD).min(sim_data.num_frames());
     let indices: Vec<usize> = (start..end).collect();
-    cache.prefetch(provider, indices);
+    let source = PbcFrameProvider::new(provider, pbc_state.transform());
+    cache.prefetch(Arc::new(source), indices);
 }
 
 /// Clear cache when a new trajectory is loaded.
@@ -346,9 +371,8 @@ mod tests {
         }
 
         fn get_frame(&self, index: usize) -> crate::io::IOResult<FrameData> {
-            let mut frame = FrameData::new(index, index as f32);
-            frame.set_position(0, Vec3::new(index as f32, 0.0, 0.0));
-            Ok(frame)
+            let positions = vec![Vec3::new(index as f32, 0.0, 0.0); self.num_atoms];
+            Ok(FrameData::with_positions(index, index as f32, positions))
         }
     }
 
@@ -378,4 +402,16 @@ mod tests {
         assert_eq!(cache.cache_hits, 1);
         assert_eq!(cache.cache_misses, 1);
     }
+
+    #[test]
+    fn test_memory_bytes_counts_dense_positions() {
+        let provider = TestProvider::new(10, 1000);
+        let mut cache = FrameCache::new(4);
+
+        for i in 0..6 {
+            cache.get_or_load(&provider, i).unwrap();
+        }
+
+        assert_eq!(cache.memory_bytes(), 4 * 1000 * std::mem::size_of::<Vec3>());
+    }
 }
@@ -3,51 +3,46 @@
 //! This system handles loading molecular trajectory files and storing
 //! the parsed data in Bevy resources.
 
-use crate::core::atom::AtomData;
-use crate::core::atom::Element;
-use crate::core::trajectory::{FrameData, Trajectory};
-use crate::io::gro::GroParser;
-use crate::io::mmcif::MmcifParser;
-use crate::io::pdb::PDBParser;
-use crate::io::streaming::{self, FrameProvider};
-use crate::io::{load_topology, FileFormat, IOResult};
+use crate::core::atom::{AtomData, AtomIndexMap};
+use crate::core::trajectory::{FrameData, LengthUnit, Trajectory};
+use crate::io::registry;
+use crate::io::streaming::FrameProvider;
+use crate::io::{load_topology, FileFormat, IOError, IOResult};
 use bevy::prelude::*;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-type ParsedLoadResult = (
-    Trajectory,
-    Vec<AtomData>,
-    Vec<crate::core::bond::BondData>,
-    Option<Arc<dyn FrameProvider>>,
-    bool,
-);
+type ParsedLoadResult = crate::io::registry::LoadedFile;
 
 /// Resource containing the loaded simulation data
 #[derive(Resource, Clone)]
 pub struct SimulationData {
-    /// Trajectory metadata and in-memory frames (empty when streaming)
-    pub trajectory: Trajectory,
+    /// Trajectory metadata and in-memory frames (empty when streaming),
+    /// shared with background readers such as exports
+    pub trajectory: Arc<Trajectory>,
     /// On-demand frame access for large trajectories
     frame_provider: Option<Arc<dyn FrameProvider>>,
-    /// Atom metadata (static data that doesn't change between frames)
-    pub atom_data: Vec<AtomData>,
+    /// Atom metadata (static data that doesn't change between frames), in
+    /// the order of the frames' per-atom arrays. Private so it is only
+    /// replaced together with `atom_index`.
+    atom_data: Vec<AtomData>,
+    /// Atom ID → index into `atom_data` and frame arrays
+    atom_index: AtomIndexMap,
     /// Bond topology from file (e.g. PDB CONECT) or empty for distance detection
     pub bond_data: Vec<crate::core::bond::BondData>,
     /// Whether data is loaded
     pub loaded: bool,
-    /// DCD loaded without topology — atom metadata is placeholder until topology applied
+    /// DCD/XTC/TRR loaded without topology — atom metadata is placeholder until topology applied
     pub needs_topology: bool,
 }
 
 impl Default for SimulationData {
     fn default() -> Self {
         Self {
-            trajectory: Trajectory::new(PathBuf::new(), 0, 1.0),
+            trajectory: Arc::new(Trajectory::new(PathBuf::new(), 0, 1.0)),
             frame_provider: None,
             atom_data: Vec::new(),
+            atom_index: AtomIndexMap::default(),
             bond_data: Vec::new(),
             loaded: false,
             needs_topology: false,
@@ -58,14 +53,7 @@ impl Default for SimulationData {
 impl SimulationData {
     /// Create simulation data from a trajectory and atom data
     pub fn new(trajectory: Trajectory, atom_data: Vec<AtomData>) -> Self {
-        Self {
-            trajectory,
-            frame_provider: None,
-            atom_data,
-            bond_data: Vec::new(),
-            loaded: true,
-            needs_topology: false,
-        }
+        Self::with_bonds(trajectory, atom_data, Vec::new())
     }
 
     /// Create simulation data with explicit bond topology
@@ -75,8 +63,9 @@ impl SimulationData {
         bond_data: Vec<crate::core::bond::BondData>,
     ) -> Self {
         Self {
-            trajectory,
+            trajectory: Arc::new(trajectory),
             frame_provider: None,
+            atom_index: AtomIndexMap::build(&atom_data),
             atom_data,
             bond_data,
             loaded: true,
@@ -99,19 +88,41 @@ impl SimulationData {
         }
     }
 
-    /// Apply topology metadata to a DCD trajectory already loaded with placeholders.
+    /// Apply topology metadata to a DCD/XTC/TRR trajectory already loaded with placeholders.
     pub fn apply_topology(
         &mut self,
         atom_data: Vec<AtomData>,
         bond_data: Vec<crate::core::bond::BondData>,
     ) -> Result<(), String> {
         crate::io::topology::validate_atom_count(atom_data.len(), self.num_atoms())?;
+        self.atom_index = AtomIndexMap::build(&atom_data);
         self.atom_data = atom_data;
         self.bond_data = bond_data;
         self.needs_topology = false;
         Ok(())
     }
 
+    /// Index of the atom with `atom_id` in `atom_data` and frame arrays.
+    pub fn atom_index(&self, atom_id: u32) -> Option<usize> {
+        self.atom_index.index_of(atom_id)
+    }
+
+    /// ID → index map of `atom_data`.
+    pub fn atom_index_map(&self) -> &AtomIndexMap {
+        &self.atom_index
+    }
+
+    /// Position of the atom with `atom_id` in an array in atom order, such as
+    /// `FrameData::positions`.
+    pub fn atom_position(&self, positions: &[Vec3], atom_id: u32) -> Option<Vec3> {
+        positions.get(self.atom_index(atom_id)?).copied()
+    }
+
+    /// Atom metadata in the order of the frames' per-atom arrays.
+    pub fn atom_data(&self) -> &[AtomData] {
+        &self.atom_data
+    }
+
     /// Get the number of atoms in the simulation
     pub fn num_atoms(&self) -> usize {
         if self.trajectory.num_atoms > 0 {
@@ -151,18 +162,22 @@ impl SimulationData {
     }
 
     /// Bounds and coloring context for a trajectory frame.
-    pub fn color_context(&self, frame_idx: usize) -> crate::core::visualization::ColorContext {
+    ///
+    /// Topology and secondary structure are left unset; callers that have
+    /// them fill them in with struct update syntax.
+    pub fn color_context(
+        &self,
+        frame_idx: usize,
+    ) -> crate::core::visualization::ColorContext<'static> {
         use crate::core::visualization::ColorContext;
         let (min_b_factor, max_b_factor) = self.b_factor_range();
         let mut bounds_min = Vec3::splat(f32::MAX);
         let mut bounds_max = Vec3::splat(f32::MIN);
 
         if let Some(frame) = self.get_frame(frame_idx) {
-            for atom in &self.atom_data {
-                if let Some(p) = frame.get_position(atom.id) {
-                    bounds_min = bounds_min.min(p);
-                    bounds_max = bounds_max.max(p);
-                }
+            for p in frame.positions.iter().take(self.atom_data.len()) {
+                bounds_min = bounds_min.min(*p);
+                bounds_max = bounds_max.max(*p);
             }
         }
 
@@ -176,6 +191,7 @@ impl SimulationData {
             max_b_factor,
             bounds_min,
             bounds_max,
+            ..Default::default()
         }
     }
 
@@ -184,20 +200,27 @@ impl SimulationData {
         self.frame_provider.clone()
     }
 
+    /// All frames: the streaming provider, or the shared in-memory trajectory.
+    pub fn frames(&self) -> Arc<dyn FrameProvider> {
+        self.frame_provider
+            .clone()
+            .unwrap_or_else(|| self.trajectory.clone())
+    }
+
     /// Get the total simulation time
     pub fn total_time(&self) -> f32 {
         self.trajectory.total_time
     }
 
-    /// Contiguous positions for all `atom_data` entries in frame order (cache-friendly SoA).
+    /// Positions of a frame in `atom_data` order.
+    ///
+    /// Frames already store positions densely, so in-memory frames are
+    /// copied without going through the frame's other data.
     pub fn frame_positions_dense(&self, frame_idx: usize) -> Option<Vec<Vec3>> {
-        let frame = self.get_frame(frame_idx)?;
-        Some(
-            self.atom_data
-                .iter()
-                .map(|a| frame.get_position(a.id).unwrap_or(Vec3::ZERO))
-                .collect(),
-        )
+        match &self.frame_provider {
+            Some(provider) => provider.get_frame(frame_idx).ok().map(|f| f.positions),
+            None => Some(self.trajectory.get_frame(frame_idx)?.positions.clone()),
+        }
     }
 }
 
@@ -229,7 +252,7 @@ impl FileHandle {
 #[derive(Resource, Default, Debug)]
 pub struct CliFileArg(pub Option<PathBuf>);
 
-/// Resource holding CLI topology path from startup args (pairs with DCD)
+/// Resource holding CLI topology path from startup args (pairs with DCD/XTC/TRR)
 #[derive(Resource, Default, Debug)]
 pub struct CliTopologyArg(pub Option<PathBuf>);
 
@@ -287,7 +310,7 @@ struct ParsedCli {
     profile: ProfileCliArgs,
 }
 
-/// Tracks topology file state for DCD trajectories
+/// Tracks topology file state for DCD/XTC/TRR trajectories
 #[derive(Resource, Default, Debug)]
 pub struct TopologyState {
     pub path: Option<PathBuf>,
@@ -300,7 +323,8 @@ pub struct LoadTopologyEvent {
     pub path: PathBuf,
 }
 
-/// Event sent after topology is applied to a DCD trajectory
+/// Event sent after the atom table is replaced: a topology applied to a
+/// DCD/XTC/TRR trajectory, or a symmetry expansion (see `systems::symmetry`)
 #[derive(Event, Debug)]
 pub struct TopologyAppliedEvent {
     pub topology_path: PathBuf,
@@ -334,185 +358,36 @@ pub struct FileLoadErrorEvent {
     pub error: String,
 }
 
-/// Load a file based on its format
+/// Load a file and convert its coordinates to Å.
 fn load_file(path: &Path, topology_path: Option<&Path>) -> IOResult<ParsedLoadResult> {
-    let format = FileFormat::from_path(path);
-
-    info!("Loading file: {:?} (format: {:?})", path, format);
-
-    match format {
-        FileFormat::XYZ => {
-            let (trajectory, frame_provider) = streaming::open_xyz(path)?;
-            let atom_data = create_atom_data_from_xyz(&trajectory)?;
-            Ok((trajectory, atom_data, Vec::new(), frame_provider, false))
-        }
-        FileFormat::PDB => {
-            let (trajectory, atom_data, bond_data) = PDBParser::parse_file_with_atoms(path)?;
-            Ok((trajectory, atom_data, bond_data, None, false))
-        }
-        FileFormat::GRO => {
-            let trajectory = GroParser::parse_file(path)?;
-            let atom_data = create_atom_data_from_gro(&trajectory)?;
-            Ok((trajectory, atom_data, Vec::new(), None, false))
-        }
-        FileFormat::MmCIF => {
-            let trajectory = MmcifParser::parse_file(path)?;
-            let atom_data = create_atom_data_from_mmcif(&trajectory)?;
-            Ok((trajectory, atom_data, Vec::new(), None, false))
-        }
-        FileFormat::DCD => {
-            let (trajectory, frame_provider) = streaming::open_dcd(path)?;
-
-            if let Some(topo_path) = topology_path {
-                let (atom_data, bond_data) = load_topology(topo_path)?;
-                crate::io::topology::validate_atom_count(atom_data.len(), trajectory.num_atoms)
-                    .map_err(crate::io::IOError::InvalidFormat)?;
-                Ok((trajectory, atom_data, bond_data, frame_provider, false))
-            } else {
-                let atom_data = create_placeholder_atom_data(&trajectory)?;
-                Ok((trajectory, atom_data, Vec::new(), frame_provider, true))
-            }
-        }
-        _ => Err(crate::io::IOError::UnsupportedFormat(format!(
-            "{:?}",
-            format
-        ))),
-    }
-}
-
-/// Create atom data from XYZ trajectory (works with streaming trajectories).
-fn create_atom_data_from_xyz(trajectory: &Trajectory) -> IOResult<Vec<AtomData>> {
-    let mut atom_data = Vec::new();
-
-    if trajectory.num_atoms > 0 && trajectory.file_path.exists() {
-        let file = File::open(&trajectory.file_path)?;
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
-        let mut atom_index = 0;
-
-        // Skip first line (number of atoms)
-        let _ = lines.next();
-        // Skip comment line
-        let _ = lines.next();
-
-        // Parse atom lines from the first frame
-        while atom_index < trajectory.num_atoms {
-            if let Some(Ok(line)) = lines.next() {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-
-                if parts.len() >= 4 {
-                    let element = Element::from_symbol(parts[0]).unwrap_or_else(|_| {
-                        warn!("Unknown element: {}, using Unknown", parts[0]);
-                        Element::Unknown
-                    });
-
-                    atom_data.push(AtomData::new(
-                        atom_index as u32,
-                        element,
-                        0,
-                        "UNK".to_string(),
-                        "A".to_string(),
-                        parts[0].to_string(),
-                    ));
-
-                    atom_index += 1;
-                }
-            } else {
-                break;
-            }
-        }
-    } else if let Some(first_frame) = trajectory.get_frame(0) {
-        for atom_id in first_frame.atom_ids() {
-            atom_data.push(AtomData::new(
-                *atom_id,
-                Element::Unknown,
-                0,
-                "UNK".to_string(),
-                "A".to_string(),
-                format!("ATOM{atom_id}"),
-            ));
-        }
-    }
-
-    Ok(atom_data)
-}
-
-/// Create atom data from GRO trajectory
-fn create_atom_data_from_gro(trajectory: &Trajectory) -> IOResult<Vec<AtomData>> {
-    let mut atom_data = Vec::new();
-
-    // Parse GRO file to get element and residue information
-    if trajectory.file_path.exists() {
-        let file = File::open(&trajectory.file_path)?;
-        let reader = BufReader::new(file);
-        let lines: Vec<String> = reader.lines().collect::<Result<_, _>>()?;
-
-        if lines.len() >= 3 {
-            // Skip title line (0) and atom count line (1)
-            // Start from line 2 (first atom line)
-            for (i, line) in lines.iter().skip(2).take(trajectory.num_atoms).enumerate() {
-                let parsed = crate::io::gro::GroParser::parse_atom_line(line, i + 3, i)
-                    .unwrap_or_else(|_| {
-                        // Fallback to default values
-                        crate::io::gro::ParsedAtom {
-                            residue_id: 1,
-                            residue_name: "UNK".to_string(),
-                            atom_name: "X".to_string(),
-                            element: Element::Unknown,
-                            position: Vec3::ZERO,
-                            velocity: None,
-                        }
-                    });
-
-                atom_data.push(AtomData::new(
-                    i as u32,
-                    parsed.element,
-                    parsed.residue_id as u32,
-                    parsed.residue_name,
-                    "A".to_string(), // GRO doesn't have chain ID
-                    parsed.atom_name,
-                ));
-            }
-        }
-    } else {
-        // Fallback: create placeholder atom data
-        return create_placeholder_atom_data(trajectory);
+    let mut result = load_file_raw(path, topology_path)?;
+    let trajectory = &mut result.0;
+    if trajectory.length_unit != LengthUnit::Angstrom {
+        info!(
+            "Converting coordinates from {} to Å",
+            trajectory.length_unit.symbol()
+        );
+        trajectory.normalize_units();
     }
-
-    Ok(atom_data)
+    Ok(result)
 }
 
-/// Create atom data from mmCIF trajectory
-fn create_atom_data_from_mmcif(trajectory: &Trajectory) -> IOResult<Vec<AtomData>> {
-    // Re-parse mmCIF file to extract atom metadata (element, residue, chain, etc.)
-    if trajectory.file_path.exists() {
-        if let Ok(atom_data) = MmcifParser::parse_atom_data_from_file(&trajectory.file_path) {
-            if atom_data.len() == trajectory.num_atoms {
-                return Ok(atom_data);
-            }
-        }
-    }
-    // Fallback: create placeholder atom data
-    create_placeholder_atom_data(trajectory)
-}
+/// Load a file with the reader the format registry detects for it, with
+/// coordinates in the file's own unit.
+fn load_file_raw(path: &Path, topology_path: Option<&Path>) -> IOResult<ParsedLoadResult> {
+    let descriptor = registry::detect(path).ok_or_else(|| {
+        IOError::UnsupportedFormat(format!("Unrecognised file format: {}", path.display()))
+    })?;
 
-/// Create placeholder atom data (for formats without atom metadata)
-fn create_placeholder_atom_data(trajectory: &Trajectory) -> IOResult<Vec<AtomData>> {
-    let mut atom_data = Vec::new();
-    let count = trajectory.num_atoms;
-
-    for atom_id in 0..count {
-        atom_data.push(AtomData::new(
-            atom_id as u32,
-            Element::Unknown,
-            0,
-            "UNK".to_string(),
-            "A".to_string(),
-            format!("ATOM{atom_id}"),
-        ));
-    }
+    info!("Loading file: {:?} (format: {})", path, descriptor.name);
 
-    Ok(atom_data)
+    let load = descriptor.load.ok_or_else(|| {
+        IOError::UnsupportedFormat(format!(
+            "{} files only hold a topology; load a trajectory first",
+            descriptor.name
+        ))
+    })?;
+    load(path, topology_path)
 }
 
 fn apply_load_result(
@@ -523,8 +398,9 @@ fn apply_load_result(
     frame_provider: Option<Arc<dyn FrameProvider>>,
     needs_topology: bool,
 ) {
-    sim_data.trajectory = trajectory;
+    sim_data.trajectory = Arc::new(trajectory);
     sim_data.frame_provider = frame_provider;
+    sim_data.atom_index = AtomIndexMap::build(&atom_data);
     sim_data.atom_data = atom_data;
     sim_data.bond_data = bond_data;
     sim_data.loaded = true;
@@ -615,7 +491,7 @@ pub fn poll_async_load(
                 needs_topology,
             );
 
-            let format = FileFormat::from_path(&path);
+            let format = FileFormat::detect(&path);
             commands.insert_resource(FileHandle::new(path.clone(), format));
 
             diagnostics.estimated_bytes =
@@ -686,7 +562,7 @@ pub fn handle_load_file_events_sync(
                 );
 
                 // Update file handle - handle resource outside of event loop
-                let format = FileFormat::from_path(&event.path);
+                let format = FileFormat::detect(&event.path);
                 let handle = FileHandle::new(event.path.clone(), format);
 
                 if file_handle.is_some() {
@@ -786,7 +662,7 @@ pub fn print_simulation_data(sim_data: Res<SimulationData>) {
     }
 }
 
-/// Apply topology file to the currently loaded DCD trajectory.
+/// Apply topology file to the currently loaded DCD/XTC/TRR trajectory.
 pub fn handle_load_topology_events(
     mut events: EventReader<LoadTopologyEvent>,
     mut sim_data: ResMut<SimulationData>,
@@ -824,7 +700,7 @@ pub fn handle_load_topology_events(
     }
 }
 
-/// Track DCD loads that still need a topology file.
+/// Track DCD/XTC/TRR loads that still need a topology file.
 pub fn track_topology_requirement(
     mut load_events: EventReader<FileLoadedEvent>,
     file_handle: Option<Res<FileHandle>>,
@@ -834,7 +710,7 @@ pub fn track_topology_requirement(
     for event in load_events.read() {
         if sim_data.needs_topology {
             if let Some(handle) = file_handle.as_ref() {
-                if handle.format == FileFormat::DCD {
+                if handle.format.needs_topology() {
                     topology_state.pending_dcd = Some(event.path.clone());
                 }
             }
@@ -954,6 +830,14 @@ mod tests {
             FileFormat::from_path(Path::new("test.dcd")),
             FileFormat::DCD
         );
+        assert_eq!(
+            FileFormat::from_path(Path::new("test.xtc")),
+            FileFormat::XTC
+        );
+        assert_eq!(
+            FileFormat::from_path(Path::new("test.trr")),
+            FileFormat::TRR
+        );
     }
 
     #[test]
@@ -991,6 +875,35 @@ mod tests {
         assert_eq!(sim_data.num_atoms(), 100);
     }
 
+    #[test]
+    fn test_apply_topology_reindexes_atoms() {
+        let atoms = |first_id: u32| -> Vec<AtomData> {
+            (0..3)
+                .map(|i| {
+                    AtomData::new(
+                        first_id + i,
+                        crate::core::atom::Element::O,
+                        0,
+                        "SOL".to_string(),
+                        "A".to_string(),
+                        "OW".to_string(),
+                    )
+                })
+                .collect()
+        };
+        let trajectory = Trajectory::new(PathBuf::from("test"), 3, 1.0);
+        let mut sim_data = SimulationData::new(trajectory, atoms(0));
+        assert_eq!(sim_data.atom_index(0), Some(0));
+
+        sim_data.apply_topology(atoms(10), Vec::new()).unwrap();
+        assert_eq!(sim_data.atom_index(0), None);
+        assert_eq!(sim_data.atom_index(12), Some(2));
+        assert_eq!(sim_data.atom_data()[2].id, 12);
+        assert!(sim_data
+            .apply_topology(atoms(0)[..2].to_vec(), Vec::new())
+            .is_err());
+    }
+
     #[test]
     fn test_profile_cli_flags() {
         let parsed = parse_cli_args_from_iter([
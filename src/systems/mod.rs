@@ -11,7 +11,9 @@
 pub mod bonds;
 pub mod frame_cache;
 pub mod loading;
+pub mod pbc;
 pub mod spawning;
+pub mod symmetry;
 pub mod timeline;
 pub mod visualization;
 
@@ -27,7 +29,9 @@ pub fn register(app: &mut App) {
     spawning::register(app);
     timeline::register(app);
     frame_cache::register(app);
+    pbc::register(app);
     bonds::register(app);
+    symmetry::register(app);
     visualization::register(app);
 
     app.add_systems(Startup, loading::load_cli_file);
@@ -40,9 +44,11 @@ pub fn register(app: &mut App) {
                 loading::handle_load_file_events,
                 loading::poll_async_load,
                 loading::handle_load_topology_events,
+                symmetry::handle_symmetry_actions,
                 loading::track_topology_requirement,
                 loading::print_simulation_data,
                 timeline::handle_timeline_input,
+                visualization::handle_atom_group_files,
             ),
             // Group 2: react to file load — clear before spawn
             (
@@ -51,25 +57,31 @@ pub fn register(app: &mut App) {
                 crate::rendering::wireframe::clear_wireframe_on_load,
                 crate::rendering::ribbon::clear_ribbon_on_load,
                 crate::rendering::surface::clear_surface_on_load,
+                crate::rendering::unit_cell::clear_unit_cell_on_load,
                 bonds::clear_bonds_on_load,
+                visualization::refresh_atom_groups_on_load,
+                crate::interaction::visibility::clear_hidden_atoms_on_load,
                 timeline::update_timeline_on_load,
+                symmetry::reset_symmetry_on_load,
                 frame_cache::clear_frame_cache_on_load,
-                crate::rendering::gpu_interpolation::clear_dense_layout_on_load,
             ),
             // Group 3: spawn instanced atoms, pick proxies, and index
-            (
-                crate::rendering::instanced::spawn_instanced_atoms_on_load,
-                crate::rendering::gpu_interpolation::build_dense_layout_on_spawn,
-            )
-                .chain(),
+            crate::rendering::instanced::spawn_instanced_atoms_on_load,
             crate::rendering::instanced::center_camera_on_file_load_instanced,
             // Group 4: bonds, wireframe, ribbon after instanced atoms exist
             (
                 bonds::build_spatial_index_on_spawn,
-                bonds::spawn_bonds,
+                (
+                    bonds::spawn_bonds,
+                    crate::analysis::molecules::update_molecule_topology,
+                )
+                    .chain(),
                 crate::rendering::wireframe::spawn_wireframe_bonds,
-                crate::rendering::ribbon::build_backbone_on_load,
-                crate::rendering::ribbon::spawn_ribbon_on_load,
+                (
+                    crate::rendering::ribbon::build_backbone_on_load,
+                    crate::rendering::ribbon::spawn_ribbon_on_load,
+                )
+                    .chain(),
                 crate::rendering::surface::spawn_surface_on_load,
             ),
             // Group 5: timeline advancement
@@ -88,6 +100,7 @@ pub fn register(app: &mut App) {
                 bonds::update_bond_positions,
                 crate::rendering::wireframe::update_wireframe_bond_positions,
                 crate::rendering::ribbon::update_ribbon_positions,
+                crate::rendering::unit_cell::update_unit_cell,
             ),
             // Group 6b: performance (culling, LOD)
             (
@@ -96,18 +109,26 @@ pub fn register(app: &mut App) {
             ),
             // Group 7: visualization & selection (after UI so mode dropdown changes apply same frame)
             (
-                visualization::clamp_unavailable_render_modes,
-                visualization::sync_mode_params,
-                crate::rendering::instanced::update_instanced_visualization,
-                crate::rendering::instanced::update_instanced_atom_colors,
-                visualization::update_bond_visibility,
-                visualization::update_bond_scale,
-                visualization::update_bond_appearance,
-                crate::rendering::wireframe::update_wireframe_visibility,
-                crate::rendering::ribbon::update_ribbon_visibility,
-                crate::rendering::ribbon::update_ribbon_for_mode,
-                crate::rendering::surface::update_surface_visibility,
+                (
+                    visualization::clamp_unavailable_render_modes,
+                    visualization::sync_mode_params,
+                    visualization::resolve_atom_styles,
+                )
+                    .chain(),
+                (
+                    crate::rendering::instanced::update_instanced_visualization,
+                    crate::rendering::instanced::update_instanced_atom_colors,
+                    visualization::update_bond_visibility,
+                    visualization::update_bond_scale,
+                    visualization::update_bond_appearance,
+                    crate::rendering::wireframe::update_wireframe_visibility,
+                    crate::interaction::pick_proxy::update_pick_proxy_visibility,
+                    crate::rendering::ribbon::update_ribbon_for_mode,
+                    crate::rendering::surface::update_surface_visibility,
+                    crate::rendering::unit_cell::update_unit_cell_visibility,
+                ),
             )
+                .chain()
                 .after(crate::ui::main_ui_panel),
         )
             .chain(),
@@ -0,0 +1,605 @@
+//! Periodic-boundary trajectory transforms: make whole, unwrap and center.
+//!
+//! Raw MD frames keep every atom inside the primary cell, so molecules that
+//! straddle a face are drawn split in two and atoms crossing a face jump to
+//! the other side of the box (which frame interpolation then lerps straight
+//! through the middle). [`PbcFrameProvider`] applies these transforms on top of
+//! any frame source; the timeline (and with it instanced atoms, bonds and GPU
+//! interpolation) and trajectory exports read frames through it, so they all
+//! see the same corrected coordinates.
+
+use crate::core::atom::AtomIndexMap;
+use crate::core::cell::UnitCell;
+use crate::core::trajectory::{FrameData, TrajectoryMetadata};
+use crate::io::streaming::FrameProvider;
+use crate::io::IOResult;
+use crate::systems::bonds::BondEntities;
+use crate::systems::loading::SimulationData;
+use bevy::prelude::*;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Frames between stored unwrapped frames; a seek walks at most this many
+/// frames from the nearest earlier one.
+pub const UNWRAP_CHECKPOINT_INTERVAL: usize = 64;
+
+/// User-facing PBC transform options (Timeline panel).
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct PbcTransformSettings {
+    /// Rejoin molecules split across cell faces using bond connectivity.
+    pub make_whole: bool,
+    /// Remove jumps across cell faces by minimum image against the previous frame.
+    pub unwrap: bool,
+    /// Translate the system so `center_atoms` sits at the cell center.
+    pub center: bool,
+    /// Atom IDs whose centroid is centered (empty = whole system).
+    pub center_atoms: Vec<u32>,
+}
+
+impl PbcTransformSettings {
+    /// Whether any transform is enabled.
+    pub fn is_active(&self) -> bool {
+        self.make_whole || self.unwrap || self.center
+    }
+}
+
+/// Bond-connected molecules as breadth-first `(atom, parent)` lists of atom
+/// indices.
+///
+/// The first entry of each component is its root (no parent); every other
+/// atom follows its parent, so positions can be fixed up in a single pass.
+#[derive(Debug, Clone, Default)]
+pub struct Connectivity {
+    components: Vec<Vec<(usize, Option<usize>)>>,
+}
+
+impl Connectivity {
+    /// Build components for `atom_count` atoms from bonded index pairs.
+    /// Unbonded atoms form single-atom components.
+    pub fn from_bonds(atom_count: usize, bonds: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); atom_count];
+        for (a, b) in bonds {
+            if a < atom_count && b < atom_count {
+                neighbours[a].push(b);
+                neighbours[b].push(a);
+            }
+        }
+
+        let mut visited = vec![false; atom_count];
+        let mut components = Vec::new();
+        for root in 0..atom_count {
+            if std::mem::replace(&mut visited[root], true) {
+                continue;
+            }
+            let mut component = vec![(root, None)];
+            let mut queue = VecDeque::from([root]);
+            while let Some(atom) = queue.pop_front() {
+                for &next in &neighbours[atom] {
+                    if !std::mem::replace(&mut visited[next], true) {
+                        component.push((next, Some(atom)));
+                        queue.push_back(next);
+                    }
+                }
+            }
+            components.push(component);
+        }
+
+        Self { components }
+    }
+
+    pub fn components(&self) -> &[Vec<(usize, Option<usize>)>] {
+        &self.components
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
+/// Move every bonded atom to the periodic image nearest its parent.
+pub fn make_whole(frame: &mut FrameData, cell: &UnitCell, connectivity: &Connectivity) {
+    for component in connectivity.components() {
+        for &(atom, parent) in component {
+            let Some(parent) = parent else {
+                continue;
+            };
+            let (Some(pos), Some(parent_pos)) = (frame.position(atom), frame.position(parent))
+            else {
+                continue;
+            };
+            let whole = parent_pos + cell.minimum_image(pos - parent_pos);
+            frame.set_position(atom, whole);
+        }
+    }
+}
+
+fn centroid(frame: &FrameData, atoms: impl IntoIterator<Item = usize>) -> Option<Vec3> {
+    let mut sum = Vec3::ZERO;
+    let mut count = 0usize;
+    for atom in atoms {
+        if let Some(pos) = frame.position(atom) {
+            sum += pos;
+            count += 1;
+        }
+    }
+    (count > 0).then(|| sum / count as f32)
+}
+
+fn translate(frame: &mut FrameData, atoms: impl IntoIterator<Item = usize>, shift: Vec3) {
+    for atom in atoms {
+        if let Some(pos) = frame.positions.get_mut(atom) {
+            *pos += shift;
+        }
+    }
+}
+
+/// Shift each molecule (or atom, without connectivity) by whole cell vectors
+/// so its centroid stays within half a box of the reference frame.
+pub fn unwrap_against(
+    frame: &mut FrameData,
+    reference: &FrameData,
+    cell: &UnitCell,
+    connectivity: Option<&Connectivity>,
+) {
+    match connectivity {
+        Some(connectivity) => {
+            for component in connectivity.components() {
+                let atoms = || component.iter().map(|(atom, _)| *atom);
+                let (Some(now), Some(before)) =
+                    (centroid(frame, atoms()), centroid(reference, atoms()))
+                else {
+                    continue;
+                };
+                let delta = now - before;
+                let shift = cell.minimum_image(delta) - delta;
+                translate(frame, atoms(), shift);
+            }
+        }
+        None => {
+            for (pos, before) in frame.positions.iter_mut().zip(&reference.positions) {
+                let delta = *pos - *before;
+                *pos += cell.minimum_image(delta) - delta;
+            }
+        }
+    }
+}
+
+/// Put each molecule's centroid (or each atom, without connectivity) back
+/// into the primary cell.
+pub fn reimage_into_cell(
+    frame: &mut FrameData,
+    cell: &UnitCell,
+    connectivity: Option<&Connectivity>,
+) {
+    match connectivity {
+        Some(connectivity) => {
+            for component in connectivity.components() {
+                let atoms = || component.iter().map(|(atom, _)| *atom);
+                if let Some(center) = centroid(frame, atoms()) {
+                    translate(frame, atoms(), cell.wrap(center) - center);
+                }
+            }
+        }
+        None => {
+            for pos in &mut frame.positions {
+                *pos = cell.wrap(*pos);
+            }
+        }
+    }
+}
+
+/// Translate the whole frame so the centroid of the atoms at `atoms` (all
+/// atoms when empty) lies at the middle of the cell.
+pub fn center_in_cell(frame: &mut FrameData, cell: &UnitCell, atoms: &[usize]) {
+    let center = if atoms.is_empty() {
+        centroid(frame, 0..frame.num_atoms())
+    } else {
+        centroid(frame, atoms.iter().copied())
+    };
+    let Some(center) = center else {
+        return;
+    };
+    let shift = cell.to_cartesian(Vec3::splat(0.5)) - center;
+    for pos in &mut frame.positions {
+        *pos += shift;
+    }
+}
+
+/// Make-whole, unwrap and centering for one set of settings and molecules.
+///
+/// Unwrapping walks forward from frame 0, so a frame's unwrapped coordinates
+/// depend only on its index, not on how the timeline reached it. Unwrapped
+/// frames are kept every [`UNWRAP_CHECKPOINT_INTERVAL`] frames (and for the
+/// latest frame) so seeks only walk from the nearest earlier checkpoint.
+#[derive(Debug, Default)]
+pub struct PbcTransform {
+    settings: PbcTransformSettings,
+    connectivity: Connectivity,
+    /// `settings.center_atoms` as atom indices
+    center_indices: Vec<usize>,
+    history: Mutex<UnwrapHistory>,
+}
+
+#[derive(Debug, Default)]
+struct UnwrapHistory {
+    /// Unwrapped (not yet centered) frames at checkpoint indices
+    checkpoints: BTreeMap<usize, FrameData>,
+    /// Most recently unwrapped frame, so playback advances one frame at a time
+    latest: Option<(usize, FrameData)>,
+}
+
+impl PbcTransform {
+    pub fn new(
+        settings: PbcTransformSettings,
+        connectivity: Connectivity,
+        atom_index: &AtomIndexMap,
+    ) -> Self {
+        let center_indices = settings
+            .center_atoms
+            .iter()
+            .filter_map(|&id| atom_index.index_of(id))
+            .collect();
+        Self {
+            settings,
+            connectivity,
+            center_indices,
+            history: Mutex::default(),
+        }
+    }
+
+    /// Transform for the loaded atoms, with molecules from file bonds or,
+    /// when the file has none, from `detected` bonds (atom id pairs).
+    pub fn for_simulation(
+        settings: PbcTransformSettings,
+        sim_data: &SimulationData,
+        detected: impl IntoIterator<Item = (u32, u32)>,
+    ) -> Self {
+        let pairs: Vec<(u32, u32)> = if sim_data.bond_data.is_empty() {
+            detected.into_iter().collect()
+        } else {
+            sim_data
+                .bond_data
+                .iter()
+                .map(|b| (b.atom_a_id, b.atom_b_id))
+                .collect()
+        };
+        let connectivity = Connectivity::from_bonds(
+            sim_data.atom_data().len(),
+            pairs
+                .into_iter()
+                .filter_map(|(a, b)| Some((sim_data.atom_index(a)?, sim_data.atom_index(b)?))),
+        );
+        Self::new(settings, connectivity, sim_data.atom_index_map())
+    }
+
+    pub fn settings(&self) -> &PbcTransformSettings {
+        &self.settings
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.settings.is_active()
+    }
+
+    fn connectivity(&self) -> Option<&Connectivity> {
+        (self.settings.make_whole && !self.connectivity.is_empty()).then_some(&self.connectivity)
+    }
+
+    /// Make whole and unwrap a raw frame (no centering).
+    fn unwrap_frame(&self, mut frame: FrameData, reference: Option<&FrameData>) -> FrameData {
+        let Some(cell) = frame.cell.filter(UnitCell::is_periodic) else {
+            return frame;
+        };
+        if let Some(connectivity) = self.connectivity() {
+            make_whole(&mut frame, &cell, connectivity);
+        }
+        if let (true, Some(reference)) = (self.settings.unwrap, reference) {
+            unwrap_against(&mut frame, reference, &cell, self.connectivity());
+        }
+        frame
+    }
+
+    /// Centering and re-imaging, applied after unwrapping.
+    fn finish(&self, mut frame: FrameData) -> FrameData {
+        let Some(cell) = frame.cell.filter(UnitCell::is_periodic) else {
+            return frame;
+        };
+        // A selection of atoms that are no longer loaded centers nothing
+        if self.settings.center
+            && (self.settings.center_atoms.is_empty() || !self.center_indices.is_empty())
+        {
+            center_in_cell(&mut frame, &cell, &self.center_indices);
+        }
+        // Unwrapped trajectories are allowed to drift out of the box.
+        if !self.settings.unwrap {
+            reimage_into_cell(&mut frame, &cell, self.connectivity());
+        }
+        frame
+    }
+
+    /// Transform raw `frame`, the frame at `index` of `source`. Unwrapping
+    /// reads the earlier raw frames it needs from `source`.
+    pub fn apply(&self, index: usize, frame: FrameData, source: &dyn FrameProvider) -> FrameData {
+        let unwrapped = if self.settings.unwrap {
+            self.unwrapped(index, frame, source)
+        } else {
+            self.unwrap_frame(frame, None)
+        };
+        self.finish(unwrapped)
+    }
+
+    fn unwrapped(&self, index: usize, frame: FrameData, source: &dyn FrameProvider) -> FrameData {
+        let mut history = self.history.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((latest, unwrapped)) = &history.latest {
+            if *latest == index {
+                return unwrapped.clone();
+            }
+        }
+
+        // Resume from the closest earlier unwrapped frame on the chain from frame 0
+        let checkpoint = history.checkpoints.range(..index).next_back();
+        let latest = history.latest.as_ref().filter(|(i, _)| *i < index);
+        let start = checkpoint
+            .map(|(i, f)| (*i, f))
+            .into_iter()
+            .chain(latest.map(|(i, f)| (*i, f)))
+            .max_by_key(|(i, _)| *i);
+        let (from, mut reference) = match start {
+            Some((i, f)) => (i + 1, Some(f.clone())),
+            None => (0, None),
+        };
+
+        for i in from..index {
+            let raw = match source.get_frame(i) {
+                Ok(raw) => raw,
+                Err(err) => {
+                    warn!("PBC unwrap: cannot read frame {i} ({err}); frame {index} left wrapped");
+                    return self.unwrap_frame(frame, None);
+                }
+            };
+            let unwrapped = self.unwrap_frame(raw, reference.as_ref());
+            if i % UNWRAP_CHECKPOINT_INTERVAL == 0 {
+                history.checkpoints.insert(i, unwrapped.clone());
+            }
+            reference = Some(unwrapped);
+        }
+
+        let unwrapped = self.unwrap_frame(frame, reference.as_ref());
+        if index % UNWRAP_CHECKPOINT_INTERVAL == 0 {
+            history.checkpoints.insert(index, unwrapped.clone());
+        }
+        history.latest = Some((index, unwrapped.clone()));
+        unwrapped
+    }
+}
+
+/// The transform matching the current settings and topology.
+#[derive(Resource, Default, Debug)]
+pub struct PbcTransformState {
+    transform: Arc<PbcTransform>,
+}
+
+impl PbcTransformState {
+    /// Rebuild for new settings or topology, using spawned bonds as molecules
+    /// when the file has no bonds. Discards the unwrap history.
+    pub fn rebuild(
+        &mut self,
+        settings: &PbcTransformSettings,
+        sim_data: &SimulationData,
+        bond_entities: &BondEntities,
+    ) {
+        self.transform = Arc::new(PbcTransform::for_simulation(
+            settings.clone(),
+            sim_data,
+            bond_entities.entities.keys().copied(),
+        ));
+    }
+
+    pub fn transform(&self) -> Arc<PbcTransform> {
+        self.transform.clone()
+    }
+}
+
+/// Frames of `inner` with the PBC transforms applied.
+///
+/// The timeline, prefetch and trajectory exports all read through this
+/// adapter, so every consumer sees the same corrected coordinates.
+pub struct PbcFrameProvider {
+    inner: Arc<dyn FrameProvider>,
+    transform: Arc<PbcTransform>,
+}
+
+impl PbcFrameProvider {
+    pub fn new(inner: Arc<dyn FrameProvider>, transform: Arc<PbcTransform>) -> Self {
+        Self { inner, transform }
+    }
+}
+
+impl FrameProvider for PbcFrameProvider {
+    fn num_frames(&self) -> usize {
+        self.inner.num_frames()
+    }
+
+    fn num_atoms(&self) -> usize {
+        self.inner.num_atoms()
+    }
+
+    fn time_step(&self) -> f32 {
+        self.inner.time_step()
+    }
+
+    fn file_path(&self) -> &Path {
+        self.inner.file_path()
+    }
+
+    fn metadata(&self) -> &TrajectoryMetadata {
+        self.inner.metadata()
+    }
+
+    fn get_frame(&self, index: usize) -> IOResult<FrameData> {
+        let frame = self.inner.get_frame(index)?;
+        if !self.transform.is_active() {
+            return Ok(frame);
+        }
+        Ok(self.transform.apply(index, frame, self.inner.as_ref()))
+    }
+}
+
+pub fn register(app: &mut App) {
+    app.init_resource::<PbcTransformSettings>()
+        .init_resource::<PbcTransformState>();
+    info!("PBC trajectory transforms registered");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::atom::{AtomData, Element};
+    use crate::core::trajectory::Trajectory;
+
+    fn frame(index: usize, positions: &[Vec3]) -> FrameData {
+        let mut frame = FrameData::new(index, index as f32);
+        for (i, pos) in positions.iter().enumerate() {
+            frame.set_position(i, *pos);
+        }
+        frame.cell = Some(UnitCell::orthorhombic([10.0, 10.0, 10.0]));
+        frame
+    }
+
+    fn trajectory(frames: impl IntoIterator<Item = FrameData>) -> Trajectory {
+        let mut trajectory = Trajectory::new("test.xyz".into(), 1, 1.0);
+        for frame in frames {
+            trajectory.add_frame(frame);
+        }
+        trajectory
+    }
+
+    /// One atom drifting +3 Å per frame through a 10 Å box, wrapped.
+    fn drifting_atom(num_frames: usize) -> Trajectory {
+        trajectory((0..num_frames).map(|i| {
+            let x = (2.0 + 3.0 * i as f32).rem_euclid(10.0);
+            frame(i, &[Vec3::new(x, 5.0, 5.0)])
+        }))
+    }
+
+    fn unwrap_only() -> PbcTransformSettings {
+        PbcTransformSettings {
+            unwrap: true,
+            ..Default::default()
+        }
+    }
+
+    fn x_at(provider: &PbcFrameProvider, index: usize) -> f32 {
+        provider.get_frame(index).unwrap().position(0).unwrap().x
+    }
+
+    #[test]
+    fn test_connectivity_components() {
+        let connectivity = Connectivity::from_bonds(4, [(0, 1), (1, 2)]);
+        assert_eq!(connectivity.components().len(), 2);
+        assert_eq!(connectivity.components()[0][0], (0, None));
+        assert_eq!(connectivity.components()[0][2], (2, Some(1)));
+        assert_eq!(connectivity.components()[1], vec![(3, None)]);
+    }
+
+    #[test]
+    fn test_make_whole_rejoins_split_bond() {
+        let settings = PbcTransformSettings {
+            make_whole: true,
+            ..Default::default()
+        };
+        let transform = PbcTransform::new(
+            settings,
+            Connectivity::from_bonds(3, [(0, 1)]),
+            &AtomIndexMap::default(),
+        );
+        let raw = frame(
+            0,
+            &[
+                Vec3::new(9.5, 5.0, 5.0),
+                Vec3::new(0.5, 5.0, 5.0),
+                Vec3::splat(2.0),
+            ],
+        );
+        let source = trajectory([raw.clone()]);
+
+        let out = transform.apply(0, raw, &source);
+        let a = out.position(0).unwrap();
+        let b = out.position(1).unwrap();
+        assert!((a.distance(b) - 1.0).abs() < 1e-4);
+        // Molecule centroid (x = 10.0) is re-imaged back into the box.
+        let center_x = (a.x + b.x) / 2.0;
+        assert!((0.0..10.0).contains(&center_x));
+    }
+
+    #[test]
+    fn test_unwrap_follows_jump() {
+        let transform = PbcTransform::new(
+            unwrap_only(),
+            Connectivity::default(),
+            &AtomIndexMap::default(),
+        );
+        let provider = PbcFrameProvider::new(Arc::new(drifting_atom(4)), Arc::new(transform));
+
+        // Seeking straight to frame 3 walks frames 0..3 (raw x = 1.0 after crossing).
+        assert!((x_at(&provider, 3) - 11.0).abs() < 1e-4);
+        assert!((x_at(&provider, 2) - 8.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_unwrap_is_independent_of_seek_path() {
+        let num_frames = 3 * UNWRAP_CHECKPOINT_INTERVAL;
+        let source: Arc<dyn FrameProvider> = Arc::new(drifting_atom(num_frames));
+        let target = 2 * UNWRAP_CHECKPOINT_INTERVAL + 5;
+        let expected = 2.0 + 3.0 * target as f32;
+
+        let provider = || {
+            let transform = PbcTransform::new(
+                unwrap_only(),
+                Connectivity::default(),
+                &AtomIndexMap::default(),
+            );
+            PbcFrameProvider::new(source.clone(), Arc::new(transform))
+        };
+
+        // Direct seek, played up to, reached backwards and after a long jump.
+        let direct = provider();
+        let played = provider();
+        (0..target).for_each(|i| {
+            played.get_frame(i).unwrap();
+        });
+        let backwards = provider();
+        backwards.get_frame(num_frames - 1).unwrap();
+        let jumped = provider();
+        jumped.get_frame(3).unwrap();
+
+        for provider in [&direct, &played, &backwards, &jumped] {
+            assert!((x_at(provider, target) - expected).abs() < 1e-2);
+        }
+        // Revisiting an earlier frame gives the same answer as before.
+        assert!((x_at(&backwards, 5) - 17.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_center_selection() {
+        let settings = PbcTransformSettings {
+            center: true,
+            center_atoms: vec![11],
+            ..Default::default()
+        };
+        let atoms: Vec<AtomData> = [10, 11]
+            .map(|id| AtomData::new(id, Element::C, 1, "UNK".into(), "A".into(), "C".into()))
+            .into();
+        let transform = PbcTransform::new(
+            settings,
+            Connectivity::default(),
+            &AtomIndexMap::build(&atoms),
+        );
+        let raw = frame(0, &[Vec3::new(1.0, 1.0, 1.0), Vec3::new(2.0, 2.0, 2.0)]);
+        let source = trajectory([raw.clone()]);
+
+        let out = transform.apply(0, raw, &source);
+        assert!((out.position(1).unwrap() - Vec3::splat(5.0)).length() < 1e-4);
+        assert!((out.position(0).unwrap() - Vec3::splat(4.0)).length() < 1e-4);
+    }
+}
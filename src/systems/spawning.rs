@@ -49,8 +49,8 @@ fn spawn_atoms_from_frame_internal(
     let mut mesh_cache: HashMap<Element, Handle<Mesh>> = HashMap::new();
     let mut material_cache: HashMap<Element, Handle<StandardMaterial>> = HashMap::new();
 
-    for atom_info in atom_data {
-        if let Some(position) = frame_data.get_position(atom_info.id) {
+    for (index, atom_info) in atom_data.iter().enumerate() {
+        if let Some(position) = frame_data.position(index) {
             let mesh = mesh_cache
                 .entry(atom_info.element)
                 .or_insert_with(|| {
@@ -150,8 +150,8 @@ pub fn spawn_atoms_on_load(
     // Check if we should spawn (any file loaded event)
     let should_spawn = file_loaded_events.read().next().is_some();
 
-    if should_spawn && sim_data.loaded && !sim_data.atom_data.is_empty() {
-        info!("File loaded, spawning {} atoms", sim_data.atom_data.len());
+    if should_spawn && sim_data.loaded && !sim_data.atom_data().is_empty() {
+        info!("File loaded, spawning {} atoms", sim_data.atom_data().len());
 
         // Get the first frame
         if let Some(first_frame) = sim_data.get_frame(0) {
@@ -160,7 +160,7 @@ pub fn spawn_atoms_on_load(
                 &mut meshes,
                 &mut materials,
                 &first_frame,
-                &sim_data.atom_data,
+                sim_data.atom_data(),
             );
 
             // Store entities in the resource
@@ -205,21 +205,7 @@ pub fn center_camera_on_file_load(
     }
 
     // Compute center from first frame (atoms may not be spawned yet)
-    let center = if let Some(frame) = sim_data.get_frame(0) {
-        let mut sum = Vec3::ZERO;
-        let mut count = 0;
-        for pos in frame.positions.values() {
-            sum += *pos;
-            count += 1;
-        }
-        if count > 0 {
-            Some(sum / count as f32)
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+    let center = sim_data.get_frame(0).and_then(|frame| frame.centroid());
 
     if let Some(center) = center {
         for mut cam in camera_query.iter_mut() {
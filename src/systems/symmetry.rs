@@ -0,0 +1,347 @@
+//! Biological assembly and crystal symmetry mate generation
+//!
+//! Expansions replace the atoms, bonds and frames in [`SimulationData`] and
+//! announce the change with [`TopologyAppliedEvent`], so the instanced
+//! renderer, bonds, cartoons and exporters all see the generated copies as
+//! ordinary atoms. The loaded asymmetric unit is kept in [`SymmetryState`]
+//! so it can be restored, and every expansion starts from it.
+
+use crate::core::symmetry::{expand_structure, symmetry_mate_operators, ExpandedStructure};
+use crate::core::symmetry::{StructureCopy, SymmetryOperator};
+use crate::core::trajectory::{FrameData, TrajectoryMetadata};
+use crate::io::streaming::FrameProvider;
+use crate::io::IOResult;
+use crate::systems::loading::{
+    FileLoadErrorEvent, FileLoadedEvent, SimulationData, TopologyAppliedEvent,
+};
+use bevy::prelude::*;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Default distance (Å) for symmetry mate generation.
+pub const DEFAULT_MATE_RADIUS: f32 = 6.0;
+
+/// Request to change which copies of the asymmetric unit are shown.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum SymmetryAction {
+    /// Build the biological assembly with this id
+    ShowAssembly(String),
+    /// Add crystal neighbours with an atom within `radius` Å of the
+    /// asymmetric unit
+    ShowSymmetryMates { radius: f32 },
+    /// Restore the structure as loaded
+    ShowAsymmetricUnit,
+}
+
+/// Asymmetric unit and UI choices for symmetry expansion.
+#[derive(Resource)]
+pub struct SymmetryState {
+    /// Structure as loaded while an expansion is shown
+    original: Option<SimulationData>,
+    /// Description of the expansion currently shown
+    pub active: Option<String>,
+    /// Assembly picked in the UI
+    pub selected_assembly: Option<String>,
+    /// Distance for symmetry mates (Å)
+    pub mate_radius: f32,
+}
+
+impl Default for SymmetryState {
+    fn default() -> Self {
+        Self {
+            original: None,
+            active: None,
+            selected_assembly: None,
+            mate_radius: DEFAULT_MATE_RADIUS,
+        }
+    }
+}
+
+impl SymmetryState {
+    /// Structure as loaded: the stored asymmetric unit, or `current` when
+    /// nothing is expanded.
+    pub fn asymmetric_unit<'a>(&'a self, current: &'a SimulationData) -> &'a SimulationData {
+        self.original.as_ref().unwrap_or(current)
+    }
+
+    /// Whether an expansion replaces the loaded structure.
+    pub fn is_expanded(&self) -> bool {
+        self.original.is_some()
+    }
+}
+
+/// Frames of a streaming trajectory mapped onto expanded atoms.
+struct ExpandedFrameProvider {
+    inner: Arc<dyn FrameProvider>,
+    expansion: Arc<ExpandedStructure>,
+    metadata: TrajectoryMetadata,
+}
+
+impl FrameProvider for ExpandedFrameProvider {
+    fn num_frames(&self) -> usize {
+        self.inner.num_frames()
+    }
+
+    fn num_atoms(&self) -> usize {
+        self.expansion.atoms.len()
+    }
+
+    fn time_step(&self) -> f32 {
+        self.inner.time_step()
+    }
+
+    fn file_path(&self) -> &Path {
+        self.inner.file_path()
+    }
+
+    fn metadata(&self) -> &TrajectoryMetadata {
+        &self.metadata
+    }
+
+    fn get_frame(&self, index: usize) -> IOResult<FrameData> {
+        Ok(self.expansion.expand_frame(&self.inner.get_frame(index)?))
+    }
+}
+
+/// Build biological assembly `id` from the loaded asymmetric unit.
+pub fn build_assembly(sim_data: &SimulationData, id: &str) -> Result<SimulationData, String> {
+    let assembly = sim_data
+        .trajectory
+        .metadata
+        .symmetry
+        .assembly(id)
+        .ok_or_else(|| format!("No biological assembly '{id}' in this file"))?;
+    expand_simulation(sim_data, &assembly.copies())
+}
+
+/// Add the crystal neighbours within `radius` Å of the asymmetric unit.
+///
+/// Uses the first frame's cell and positions; the asymmetric unit itself
+/// stays first with its original chain ids.
+pub fn build_symmetry_mates(
+    sim_data: &SimulationData,
+    radius: f32,
+) -> Result<(SimulationData, usize), String> {
+    let frame = sim_data
+        .get_frame(0)
+        .ok_or_else(|| "No coordinates loaded".to_string())?;
+    let cell = frame
+        .cell
+        .filter(|c| c.is_periodic())
+        .ok_or_else(|| "Symmetry mates need a unit cell (CRYST1 or _cell)".to_string())?;
+    let symmetry = &sim_data.trajectory.metadata.symmetry;
+    let operators =
+        symmetry
+            .crystal_operators(&cell)
+            .ok_or_else(|| match &symmetry.space_group {
+                Some(group) => format!("Space group '{group}' has no known operators"),
+                None => "No space group in this file".to_string(),
+            })?;
+
+    let positions = &frame.positions[..frame.num_atoms().min(sim_data.atom_data().len())];
+    let mates = symmetry_mate_operators(&operators, &cell, positions, radius);
+    let copies: Vec<StructureCopy> = std::iter::once(SymmetryOperator::IDENTITY)
+        .chain(mates.iter().copied())
+        .map(StructureCopy::all)
+        .collect();
+    Ok((expand_simulation(sim_data, &copies)?, mates.len()))
+}
+
+/// Replace atoms, bonds and frames with the given copies.
+fn expand_simulation(
+    sim_data: &SimulationData,
+    copies: &[StructureCopy],
+) -> Result<SimulationData, String> {
+    if sim_data.needs_topology {
+        return Err("Load a topology before generating symmetry copies".to_string());
+    }
+    let expansion = expand_structure(sim_data.atom_data(), &sim_data.bond_data, copies);
+    if expansion.atoms.is_empty() {
+        return Err("The operation selects none of the loaded chains".to_string());
+    }
+
+    let mut trajectory = (*sim_data.trajectory).clone();
+    trajectory.num_atoms = expansion.atoms.len();
+    trajectory.metadata.secondary_structure =
+        expansion.expand_annotations(&sim_data.trajectory.metadata.secondary_structure);
+    trajectory.frames = trajectory
+        .frames
+        .iter()
+        .map(|frame| expansion.expand_frame(frame))
+        .collect();
+
+    let expansion = Arc::new(expansion);
+    let atoms = expansion.atoms.clone();
+    let bonds = expansion.bonds.clone();
+    let mut expanded = SimulationData::with_bonds(trajectory, atoms, bonds);
+    if let Some(inner) = sim_data.frame_provider() {
+        let metadata = expanded.trajectory.metadata.clone();
+        expanded = expanded.with_frame_provider(Arc::new(ExpandedFrameProvider {
+            inner,
+            expansion,
+            metadata,
+        }));
+    }
+    Ok(expanded)
+}
+
+/// Apply [`SymmetryAction`] requests to the loaded structure.
+pub fn handle_symmetry_actions(
+    mut actions: EventReader<SymmetryAction>,
+    mut sim_data: ResMut<SimulationData>,
+    mut state: ResMut<SymmetryState>,
+    mut applied: EventWriter<TopologyAppliedEvent>,
+    mut load_error: EventWriter<FileLoadErrorEvent>,
+) {
+    for action in actions.read() {
+        if !sim_data.loaded {
+            continue;
+        }
+        let path = sim_data.trajectory.file_path.clone();
+
+        if *action == SymmetryAction::ShowAsymmetricUnit {
+            if let Some(original) = state.original.take() {
+                *sim_data = original;
+                state.active = None;
+                applied.send(TopologyAppliedEvent {
+                    topology_path: path,
+                    num_atoms: sim_data.atom_data().len(),
+                });
+                info!("Restored asymmetric unit");
+            }
+            continue;
+        }
+
+        let base = state.asymmetric_unit(&sim_data);
+        let result = match action {
+            SymmetryAction::ShowAssembly(id) => {
+                build_assembly(base, id).map(|data| (data, format!("Assembly {id}")))
+            }
+            SymmetryAction::ShowSymmetryMates { radius } => build_symmetry_mates(base, *radius)
+                .map(|(data, mates)| {
+                    (data, format!("{mates} symmetry mates within {radius:.1} Å"))
+                }),
+            SymmetryAction::ShowAsymmetricUnit => continue,
+        };
+
+        match result {
+            Ok((expanded, description)) => {
+                let previous = std::mem::replace(&mut *sim_data, expanded);
+                if state.original.is_none() {
+                    state.original = Some(previous);
+                }
+                info!(
+                    "Showing {} ({} atoms)",
+                    description,
+                    sim_data.atom_data().len()
+                );
+                state.active = Some(description);
+                applied.send(TopologyAppliedEvent {
+                    topology_path: path,
+                    num_atoms: sim_data.atom_data().len(),
+                });
+            }
+            Err(error) => {
+                load_error.send(FileLoadErrorEvent { path, error });
+            }
+        }
+    }
+}
+
+/// Forget the previous structure's asymmetric unit when a file loads.
+pub fn reset_symmetry_on_load(
+    mut state: ResMut<SymmetryState>,
+    sim_data: Res<SimulationData>,
+    mut file_loaded_events: EventReader<FileLoadedEvent>,
+) {
+    if file_loaded_events.read().next().is_none() {
+        return;
+    }
+    state.original = None;
+    state.active = None;
+    state.selected_assembly = sim_data
+        .trajectory
+        .metadata
+        .symmetry
+        .assemblies
+        .first()
+        .map(|a| a.id.clone());
+}
+
+/// Register symmetry resources and events. Systems are registered centrally in systems::register.
+pub fn register(app: &mut App) {
+    app.init_resource::<SymmetryState>()
+        .add_event::<SymmetryAction>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::atom::{AtomData, Element};
+    use crate::core::cell::UnitCell;
+    use crate::core::symmetry::{AssemblyGenerator, BiologicalAssembly};
+    use crate::core::trajectory::Trajectory;
+    use std::path::PathBuf;
+
+    fn dimer_half() -> SimulationData {
+        let mut atom = AtomData::new(7, Element::C, 1, "ALA".into(), "A".into(), "CA".into());
+        atom.position = Vec3::new(1.0, 2.0, 3.0);
+        let mut frame = FrameData::new(0, 0.0);
+        frame.set_position(0, atom.position);
+        frame.cell = Some(UnitCell::orthorhombic([10.0, 10.0, 10.0]));
+
+        let mut trajectory = Trajectory::new(PathBuf::from("half.pdb"), 1, 1.0);
+        trajectory.metadata.symmetry.space_group = Some("P 1".into());
+        trajectory
+            .metadata
+            .symmetry
+            .assemblies
+            .push(BiologicalAssembly {
+                id: "1".into(),
+                details: "DIMERIC".into(),
+                generators: vec![AssemblyGenerator {
+                    chains: vec!["A".into()],
+                    operators: vec![
+                        SymmetryOperator::IDENTITY,
+                        SymmetryOperator::new(
+                            Mat3::from_diagonal(Vec3::new(-1.0, -1.0, 1.0)),
+                            Vec3::ZERO,
+                        ),
+                    ],
+                }],
+            });
+        trajectory.add_frame(frame);
+        SimulationData::new(trajectory, vec![atom])
+    }
+
+    #[test]
+    fn test_build_assembly() {
+        let sim_data = dimer_half();
+        let dimer = build_assembly(&sim_data, "1").unwrap();
+        assert_eq!(dimer.num_atoms(), 2);
+        assert_eq!(dimer.atom_data()[1].chain_id, "B");
+        let copy_id = dimer.atom_data()[1].id;
+        assert_eq!(dimer.atom_index(copy_id), Some(1));
+        let frame = dimer.get_frame(0).unwrap();
+        assert_eq!(frame.position(1), Some(Vec3::new(-1.0, -2.0, 3.0)));
+        assert!(build_assembly(&sim_data, "2").is_err());
+    }
+
+    #[test]
+    fn test_build_symmetry_mates_streaming() {
+        let sim_data = dimer_half();
+        let provider =
+            crate::io::streaming::InMemoryFrameProvider::new((*sim_data.trajectory).clone());
+        let mut streaming = sim_data.clone().with_frame_provider(Arc::new(provider));
+        Arc::make_mut(&mut streaming.trajectory).frames.clear();
+
+        // A lone atom in a 10 Å P1 cell only reaches its lattice neighbours.
+        let (mates, count) = build_symmetry_mates(&streaming, 10.5).unwrap();
+        assert_eq!(count, 6);
+        assert!(mates.is_streaming());
+        assert_eq!(mates.num_atoms(), 7);
+        let frame = mates.get_frame(0).unwrap();
+        assert_eq!(frame.positions.len(), 7);
+        assert_eq!(frame.position(0), Some(Vec3::new(1.0, 2.0, 3.0)));
+    }
+}
@@ -99,18 +99,18 @@ pub fn update_atom_positions_from_timeline(
     };
 
     for (spawned_atom, mut transform, mut atom) in atom_query.iter_mut() {
-        let atom_id = spawned_atom.atom_id;
+        let Some(index) = sim_data.atom_index(spawned_atom.atom_id) else {
+            continue;
+        };
 
         let position = if let (Some(current), Some(next), Some(alpha)) = (
-            current_frame_data.get_position(atom_id),
-            next_frame_data
-                .as_ref()
-                .and_then(|f| f.get_position(atom_id)),
+            current_frame_data.position(index),
+            next_frame_data.and_then(|f| f.position(index)),
             Some(timeline.interpolation_factor).filter(|_| timeline.interpolate),
         ) {
             current.lerp(next, alpha)
         } else {
-            match current_frame_data.get_position(atom_id) {
+            match current_frame_data.position(index) {
                 Some(pos) => pos,
                 None => continue,
             }
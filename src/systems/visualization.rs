@@ -4,10 +4,19 @@
 //! It uses the RenderMode enum from core::visualization and applies global settings.
 //! Systems only run when VisualizationConfig has changed to avoid per-frame iteration.
 
+use crate::analysis::molecules::MoleculeTopology;
 use crate::core::bond::Bond;
+use crate::core::groups::{AtomGroups, AtomStyles, HiddenAtoms};
 use crate::core::secondary_structure::ProteinBackbone;
-use crate::core::visualization::{RenderMode, VisualizationConfig};
+use crate::core::trajectory::TimelineState;
+use crate::core::visualization::{ColorContext, RenderMode, VisualizationConfig};
+use crate::interaction::selection_query::SelectionExpr;
+use crate::systems::bonds::BondEntities;
+use crate::systems::loading::SimulationData;
+use crate::ui::notifications::UiNotifications;
 use bevy::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Event sent when visualization mode changes
 #[derive(Event, Debug)]
@@ -16,6 +25,18 @@ pub struct VisualizationModeChangedEvent {
     pub new_mode: RenderMode,
 }
 
+/// Request writing the atom groups to a JSON groups file.
+#[derive(Event, Debug, Clone)]
+pub struct SaveAtomGroupsEvent {
+    pub path: PathBuf,
+}
+
+/// Request replacing the atom groups with those in a groups file.
+#[derive(Event, Debug, Clone)]
+pub struct LoadAtomGroupsEvent {
+    pub path: PathBuf,
+}
+
 /// Update atom visibility based on config (only when config changes)
 pub fn update_atom_visibility(
     config: Res<VisualizationConfig>,
@@ -36,26 +57,25 @@ pub fn update_atom_visibility(
     }
 }
 
-/// Update bond visibility based on config (only when config changes).
-/// Filters on `With<Bond>` so only bond entities are affected.
+/// Update bond visibility from the styles of both bonded atoms.
+/// A cylinder is shown only when both ends are drawn with cylinder bonds.
 pub fn update_bond_visibility(
-    config: Res<VisualizationConfig>,
-    mut bond_query: Query<&mut Visibility, With<Bond>>,
+    styles: Res<AtomStyles>,
+    bond_entities: Res<BondEntities>,
+    mut bond_query: Query<(&Bond, &mut Visibility)>,
 ) {
-    if !config.is_changed() {
+    if !styles.is_changed() && !bond_entities.is_changed() {
         return;
     }
 
-    let should_show = config.show_bonds
-        && config.render_mode.shows_bonds()
-        && !config.render_mode.uses_wireframe_lines();
-
-    for mut visibility in bond_query.iter_mut() {
-        *visibility = if should_show {
+    for (bond, mut visibility) in bond_query.iter_mut() {
+        let show =
+            styles.get(bond.atom_a_id).cylinder_bonds && styles.get(bond.atom_b_id).cylinder_bonds;
+        visibility.set_if_neq(if show {
             Visibility::Visible
         } else {
             Visibility::Hidden
-        };
+        });
     }
 }
 
@@ -77,21 +97,109 @@ pub fn update_atom_scale(
     }
 }
 
-/// Update bond thickness based on render mode and config (only when config changes)
+/// Update bond thickness from the styles of both bonded atoms (the thinner end wins).
 pub fn update_bond_scale(
+    styles: Res<AtomStyles>,
+    bond_entities: Res<BondEntities>,
+    mut bond_query: Query<(&Bond, &mut Transform)>,
+) {
+    if !styles.is_changed() && !bond_entities.is_changed() {
+        return;
+    }
+
+    for (bond, mut transform) in bond_query.iter_mut() {
+        let thickness = styles
+            .get(bond.atom_a_id)
+            .bond_thickness
+            .min(styles.get(bond.atom_b_id).bond_thickness);
+        transform.scale.x = thickness;
+        transform.scale.y = thickness;
+    }
+}
+
+/// Resolve per-atom styles from groups, hidden atoms and the global config.
+/// `AtomStyles` is only marked changed when a resolved style actually differs.
+pub fn resolve_atom_styles(
     config: Res<VisualizationConfig>,
-    mut bond_query: Query<&mut Transform, With<Bond>>,
+    groups: Res<AtomGroups>,
+    hidden: Res<HiddenAtoms>,
+    mut styles: ResMut<AtomStyles>,
 ) {
-    if !config.is_changed() {
+    if !config.is_changed() && !groups.is_changed() && !hidden.is_changed() {
         return;
     }
+    if styles
+        .bypass_change_detection()
+        .update(&groups, &hidden, &config)
+    {
+        styles.set_changed();
+    }
+}
 
-    let mode_thickness = config.render_mode.bond_thickness();
-    let overall_thickness = mode_thickness * config.bond_scale;
+/// Re-evaluate groups' queries and IDs against the loaded structure.
+fn refresh_groups(groups: &mut AtomGroups, sim_data: &SimulationData) {
+    let positions = sim_data
+        .get_frame(0)
+        .map(|f| f.positions)
+        .unwrap_or_default();
+    groups.refresh(sim_data.atom_data(), |query| {
+        SelectionExpr::parse(query).map(|expr| expr.select(sim_data.atom_data(), &positions))
+    });
+}
 
-    for mut transform in bond_query.iter_mut() {
-        transform.scale.x = overall_thickness;
-        transform.scale.y = overall_thickness;
+/// Re-evaluate atom groups against a newly loaded structure.
+pub fn refresh_atom_groups_on_load(
+    mut groups: ResMut<AtomGroups>,
+    sim_data: Res<crate::systems::loading::SimulationData>,
+    mut file_loaded_events: EventReader<crate::systems::loading::FileLoadedEvent>,
+    mut topology_events: EventReader<crate::systems::loading::TopologyAppliedEvent>,
+) {
+    let reload =
+        file_loaded_events.read().next().is_some() || topology_events.read().next().is_some();
+    if !reload || groups.is_empty() {
+        return;
+    }
+
+    refresh_groups(&mut groups, &sim_data);
+    info!(
+        "Re-applied {} atom groups to loaded structure",
+        groups.len()
+    );
+}
+
+/// Save and restore atom groups. Restored groups are re-evaluated against the
+/// loaded structure, like groups carried over a reload.
+pub fn handle_atom_group_files(
+    mut save_events: EventReader<SaveAtomGroupsEvent>,
+    mut load_events: EventReader<LoadAtomGroupsEvent>,
+    mut groups: ResMut<AtomGroups>,
+    sim_data: Res<SimulationData>,
+    mut notifications: ResMut<UiNotifications>,
+) {
+    for event in save_events.read() {
+        match groups.save(&event.path) {
+            Ok(()) => {
+                info!("Saved {} atom groups to {:?}", groups.len(), event.path);
+                notifications.show(format!("Saved groups to {}", event.path.display()), 180);
+            }
+            Err(e) => {
+                error!("Saving atom groups failed: {}", e);
+                notifications.show(format!("Saving groups failed: {e}"), 300);
+            }
+        }
+    }
+
+    for event in load_events.read() {
+        if let Err(e) = groups.load(&event.path) {
+            error!("Loading atom groups failed: {}", e);
+            notifications.show(format!("Loading groups failed: {e}"), 300);
+            continue;
+        }
+        if sim_data.loaded {
+            refresh_groups(&mut groups, &sim_data);
+        }
+        info!("Loaded {} atom groups from {:?}", groups.len(), event.path);
+        notifications.show(format!("Loaded {} groups", groups.len()), 180);
     }
 }
 
@@ -144,26 +252,93 @@ pub fn sync_mode_params(
     }
 }
 
-/// Update bond material color for licorice (uniform gray sticks).
+/// Bond cylinder materials shared by color (quantized to 8-bit sRGB).
+#[derive(Resource, Default, Debug)]
+pub struct BondMaterials {
+    by_color: HashMap<[u8; 3], Handle<StandardMaterial>>,
+}
+
+impl BondMaterials {
+    fn get_or_add(
+        &mut self,
+        color: Color,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Handle<StandardMaterial> {
+        let key = color.to_srgba().to_u8_array_no_alpha();
+        self.by_color
+            .entry(key)
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: Color::srgb_u8(key[0], key[1], key[2]),
+                    metallic: 0.2,
+                    perceptual_roughness: 0.4,
+                    ..default()
+                })
+            })
+            .clone()
+    }
+}
+
+/// Color bond cylinders from their atoms' styles.
+///
+/// Under residue, chain, secondary structure and molecule coloring a bond takes
+/// its first atom's color when both atoms share the scheme; other bonds are
+/// gray (uniform gray sticks for licorice).
+#[allow(clippy::too_many_arguments)]
 pub fn update_bond_appearance(
-    config: Res<VisualizationConfig>,
+    styles: Res<AtomStyles>,
+    sim_data: Res<SimulationData>,
+    topology: Res<MoleculeTopology>,
+    backbone: Res<ProteinBackbone>,
+    timeline: Res<TimelineState>,
+    bond_entities: Res<BondEntities>,
+    mut bond_materials: ResMut<BondMaterials>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    bond_query: Query<&Handle<StandardMaterial>, With<Bond>>,
+    bond_query: Query<(&Bond, &Children)>,
+    mut cylinder_query: Query<&mut Handle<StandardMaterial>>,
 ) {
-    if !config.is_changed() {
+    if !styles.is_changed()
+        && !topology.is_changed()
+        && !backbone.is_changed()
+        && !bond_entities.is_changed()
+    {
+        return;
+    }
+    if bond_entities.entities.is_empty() {
         return;
     }
 
-    let params = config.render_mode.mode_params();
-    let color = if params.uniform_bond_color {
-        Color::srgb(0.55, 0.55, 0.55)
-    } else {
-        Color::srgb(0.6, 0.6, 0.6)
+    let secondary_structure = backbone.residue_secondary_structure(&topology);
+    let ctx = ColorContext {
+        topology: Some(&topology),
+        secondary_structure: Some(&secondary_structure),
+        ..sim_data.color_context(timeline.current_frame)
     };
+    let atoms: HashMap<u32, &crate::core::atom::AtomData> =
+        sim_data.atom_data().iter().map(|a| (a.id, a)).collect();
+
+    for (bond, children) in bond_query.iter() {
+        let style_a = styles.get(bond.atom_a_id);
+        let scheme = style_a.color_scheme;
+        let color = match atoms.get(&bond.atom_a_id) {
+            Some(atom)
+                if scheme.colors_bonds() && styles.get(bond.atom_b_id).color_scheme == scheme =>
+            {
+                scheme.atom_color(atom, &ctx)
+            }
+            _ if style_a.render_mode.mode_params().uniform_bond_color => {
+                Color::srgb(0.55, 0.55, 0.55)
+            }
+            _ => Color::srgb(0.6, 0.6, 0.6),
+        };
 
-    for handle in bond_query.iter() {
-        if let Some(material) = materials.get_mut(handle) {
-            material.base_color = color;
+        let material = bond_materials.get_or_add(color, &mut materials);
+        for &child in children.iter() {
+            if let Ok(mut handle) = cylinder_query.get_mut(child) {
+                if *handle != material {
+                    *handle = material.clone();
+                }
+            }
         }
     }
 }
@@ -199,7 +374,13 @@ pub fn cycle_render_mode(
 /// Register visualization resources and events. Systems are registered centrally in systems::register.
 pub fn register(app: &mut App) {
     app.init_resource::<VisualizationConfig>()
-        .add_event::<VisualizationModeChangedEvent>();
+        .init_resource::<AtomGroups>()
+        .init_resource::<HiddenAtoms>()
+        .init_resource::<AtomStyles>()
+        .init_resource::<BondMaterials>()
+        .add_event::<VisualizationModeChangedEvent>()
+        .add_event::<SaveAtomGroupsEvent>()
+        .add_event::<LoadAtomGroupsEvent>();
 
     info!("Visualization resources registered");
 }
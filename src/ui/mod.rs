@@ -5,6 +5,8 @@ pub mod help;
 pub mod inspector;
 pub mod notifications;
 
+use crate::analysis::molecules::MoleculeTopology;
+use crate::core::groups::{AtomGroup, AtomGroups, GroupStyle, HiddenAtoms};
 use crate::core::secondary_structure::ProteinBackbone;
 use crate::core::secondary_structure::MIN_CARTOON_RESIDUES;
 use crate::core::trajectory::TimelineState;
@@ -13,9 +15,17 @@ use crate::export::gltf_export::RequestExportGltfEvent;
 use crate::export::obj::RequestExportObjEvent;
 use crate::export::povray::RequestExportPovRayEvent;
 use crate::export::screenshot::RequestScreenshotEvent;
+use crate::export::trajectory::{
+    RequestTrajectoryExportEvent, TrajectoryExportState, TrajectoryFormat,
+};
 use crate::export::video::{RequestVideoExportEvent, VideoExportSettings, VideoExportState};
 use crate::interaction::measurement::MeasurementState;
 use crate::interaction::selection::SelectionState;
+use crate::interaction::selection_query::{
+    ApplySelectionQueryEvent, SelectionExpr, SelectionQueryState,
+};
+use crate::interaction::visibility::AtomVisibilityAction;
+use crate::io::registry::{self, FormatDescriptor};
 use crate::io::FileFormat;
 use crate::performance::{memory, PerformanceUiState};
 use crate::rendering::instanced::InstancedAtomEntities;
@@ -24,6 +34,8 @@ use crate::systems::loading::{
     AsyncLoadState, CliFileArg, FileLoadErrorEvent, LoadFileEvent, LoadTopologyEvent,
     SimulationData, TopologyState,
 };
+use crate::systems::symmetry::{SymmetryAction, SymmetryState};
+use crate::systems::visualization::{LoadAtomGroupsEvent, SaveAtomGroupsEvent};
 use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy::window::FileDragAndDrop;
@@ -36,11 +48,17 @@ pub struct TopologyPickerState {
     receiver: Option<crossbeam_channel::Receiver<Option<std::path::PathBuf>>>,
 }
 
-/// Supported molecular file extensions for filtering
-const SUPPORTED_EXTENSIONS: &[&str] = &["xyz", "pdb", "gro", "dcd", "cif", "mmcif", "mcif"];
+/// Compression suffixes read transparently (`1abc.pdb.gz`)
+const COMPRESSED_EXTENSIONS: &[&str] = &["gz", "bz2", "xz"];
 
-/// Extensions that have implemented parsers (loadable)
-const LOADABLE_EXTENSIONS: &[&str] = &["xyz", "pdb", "gro", "cif", "mmcif", "mcif", "dcd"];
+/// Dialog filter list: extensions of the registered formats accepted by
+/// `filter`, plus the compression suffixes, since file dialogs only match
+/// the last extension.
+fn dialog_extensions(filter: impl Fn(&FormatDescriptor) -> bool) -> Vec<&'static str> {
+    let mut extensions = registry::registry().extensions(filter);
+    extensions.extend_from_slice(COMPRESSED_EXTENSIONS);
+    extensions
+}
 
 /// Resource holding receiver for async file picker results
 #[derive(Resource, Default)]
@@ -79,6 +97,24 @@ pub struct PovRaySaveState {
     receiver: Option<crossbeam_channel::Receiver<Option<std::path::PathBuf>>>,
 }
 
+/// Resource holding receiver for async trajectory export save path
+#[derive(Resource, Default)]
+pub struct TrajectorySaveState {
+    receiver: Option<crossbeam_channel::Receiver<Option<std::path::PathBuf>>>,
+}
+
+/// Resource holding receiver for async groups file save path
+#[derive(Resource, Default)]
+pub struct GroupsSaveState {
+    receiver: Option<crossbeam_channel::Receiver<Option<std::path::PathBuf>>>,
+}
+
+/// Resource holding receiver for async groups file open path
+#[derive(Resource, Default)]
+pub struct GroupsOpenState {
+    receiver: Option<crossbeam_channel::Receiver<Option<std::path::PathBuf>>>,
+}
+
 #[derive(SystemParam)]
 pub struct ExportSaveStates<'w> {
     pub screenshot: ResMut<'w, ScreenshotSaveState>,
@@ -86,12 +122,14 @@ pub struct ExportSaveStates<'w> {
     pub gltf: ResMut<'w, GltfSaveState>,
     pub video: ResMut<'w, VideoSaveState>,
     pub povray: ResMut<'w, PovRaySaveState>,
+    pub trajectory: ResMut<'w, TrajectorySaveState>,
 }
 
 #[derive(SystemParam)]
 pub struct ExportPanelState<'w> {
     pub saves: ExportSaveStates<'w>,
     pub video: Res<'w, VideoExportState>,
+    pub trajectory: ResMut<'w, TrajectoryExportState>,
 }
 
 #[derive(SystemParam)]
@@ -100,22 +138,14 @@ pub struct VisualizationUiState<'w> {
     pub bond_config: ResMut<'w, BondDetectionConfig>,
     pub bond_entities: Res<'w, BondEntities>,
     pub backbone: Res<'w, ProteinBackbone>,
+    pub pbc: ResMut<'w, crate::systems::pbc::PbcTransformSettings>,
 }
 
-/// Check if a path has a supported/loadable molecular format
+/// Check if a path holds a loadable molecular format, by content and extension
 fn is_loadable_molecular_file(path: &Path) -> bool {
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| {
-            let ext_lower = ext.to_lowercase();
-            LOADABLE_EXTENSIONS.iter().any(|e| *e == ext_lower)
-        })
-        .unwrap_or(false)
+    FileFormat::detect(path).is_loadable()
 }
 
-/// Topology file extensions (structure files for DCD pairing)
-const TOPOLOGY_EXTENSIONS: &[&str] = &["pdb", "gro", "cif", "mmcif", "mcif"];
-
 /// Poll for topology file picker result
 pub fn topology_picker_poll(
     mut picker_state: ResMut<TopologyPickerState>,
@@ -151,10 +181,7 @@ pub fn file_drop_handler(
                     path: path_buf.clone(),
                 });
             } else if path_buf.exists() {
-                let format = FileFormat::from_path(path_buf);
-                if !format.is_loadable() {
-                    warn!("Dropped file format not yet supported: {:?}", path_buf);
-                }
+                warn!("Dropped file format not yet supported: {:?}", path_buf);
             } else {
                 warn!("Dropped path does not exist: {:?}", path_buf);
             }
@@ -295,10 +322,81 @@ pub fn povray_save_poll(
     }
 }
 
+/// Poll for groups file save path and send SaveAtomGroupsEvent
+pub fn groups_save_poll(
+    mut save_state: ResMut<GroupsSaveState>,
+    mut save_events: EventWriter<SaveAtomGroupsEvent>,
+) {
+    if let Some(receiver) = save_state.receiver.take() {
+        match receiver.try_recv() {
+            Ok(Some(path)) => {
+                save_events.send(SaveAtomGroupsEvent { path });
+            }
+            Ok(None) => {}
+            Err(crossbeam_channel::TryRecvError::Empty) => {
+                save_state.receiver = Some(receiver);
+            }
+            Err(crossbeam_channel::TryRecvError::Disconnected) => {}
+        }
+    }
+}
+
+/// Poll for groups file open path and send LoadAtomGroupsEvent
+pub fn groups_open_poll(
+    mut open_state: ResMut<GroupsOpenState>,
+    mut load_events: EventWriter<LoadAtomGroupsEvent>,
+) {
+    if let Some(receiver) = open_state.receiver.take() {
+        match receiver.try_recv() {
+            Ok(Some(path)) => {
+                load_events.send(LoadAtomGroupsEvent { path });
+            }
+            Ok(None) => {}
+            Err(crossbeam_channel::TryRecvError::Empty) => {
+                open_state.receiver = Some(receiver);
+            }
+            Err(crossbeam_channel::TryRecvError::Disconnected) => {}
+        }
+    }
+}
+
+/// Poll for trajectory export save path and send RequestTrajectoryExportEvent
+pub fn trajectory_save_poll(
+    mut save_state: ResMut<TrajectorySaveState>,
+    export_state: Res<TrajectoryExportState>,
+    selection: Res<SelectionState>,
+    mut export_events: EventWriter<RequestTrajectoryExportEvent>,
+) {
+    if let Some(receiver) = save_state.receiver.take() {
+        match receiver.try_recv() {
+            Ok(Some(mut path)) => {
+                let mut options = export_state.options.clone();
+                match TrajectoryFormat::from_path(&path) {
+                    Some(format) => options.format = format,
+                    None => {
+                        path.set_extension(options.format.extension());
+                    }
+                }
+                if export_state.selection_only {
+                    options.atom_ids = Some(selection.atom_ids().to_vec());
+                }
+                export_events.send(RequestTrajectoryExportEvent { path, options });
+            }
+            Ok(None) => {}
+            Err(crossbeam_channel::TryRecvError::Empty) => {
+                save_state.receiver = Some(receiver);
+            }
+            Err(crossbeam_channel::TryRecvError::Disconnected) => {}
+        }
+    }
+}
+
 #[derive(SystemParam)]
 pub struct TopologyUiState<'w> {
     pub topology_state: Res<'w, TopologyState>,
     pub topology_picker: ResMut<'w, TopologyPickerState>,
+    pub symmetry: ResMut<'w, SymmetryState>,
+    pub symmetry_actions: EventWriter<'w, SymmetryAction>,
 }
 
 #[derive(SystemParam)]
@@ -306,6 +404,74 @@ pub struct SelectionPanelState<'w> {
     pub selection: ResMut<'w, SelectionState>,
     pub measurements: Res<'w, MeasurementState>,
     pub labels: ResMut<'w, atom_labels::AtomLabelSettings>,
+    pub query: ResMut<'w, SelectionQueryState>,
+    pub query_events: EventWriter<'w, ApplySelectionQueryEvent>,
+    pub groups: AtomGroupsUiState<'w>,
+    pub hidden: Res<'w, HiddenAtoms>,
+    pub visibility_events: EventWriter<'w, AtomVisibilityAction>,
+    pub topology: Res<'w, MoleculeTopology>,
+}
+
+/// Rows listed per node of the Molecules tree before eliding the rest.
+const MAX_TREE_ROWS: usize = 200;
+
+/// Query selecting indices under `keyword`, with consecutive runs as ranges
+/// (`residue 0 to 41 57`).
+fn index_query(keyword: &str, indices: &[usize]) -> String {
+    let mut query = keyword.to_string();
+    let mut i = 0;
+    while i < indices.len() {
+        let start = indices[i];
+        while i + 1 < indices.len() && indices[i + 1] == indices[i] + 1 {
+            i += 1;
+        }
+        if indices[i] == start {
+            query.push_str(&format!(" {start}"));
+        } else {
+            query.push_str(&format!(" {start} to {}", indices[i]));
+        }
+        i += 1;
+    }
+    query
+}
+
+/// Name field and last error of the Groups panel.
+#[derive(Resource, Default, Debug)]
+pub struct AtomGroupEditorState {
+    pub name: String,
+    pub error: Option<String>,
+}
+
+#[derive(SystemParam)]
+pub struct AtomGroupsUiState<'w> {
+    pub groups: ResMut<'w, AtomGroups>,
+    pub editor: ResMut<'w, AtomGroupEditorState>,
+    pub save: ResMut<'w, GroupsSaveState>,
+    pub open: ResMut<'w, GroupsOpenState>,
+}
+
+/// Build a group from a selection query evaluated on the displayed frame.
+fn query_group(
+    name: &str,
+    query: &str,
+    style: GroupStyle,
+    sim_data: &SimulationData,
+    frames: &crate::systems::frame_cache::TimelineFrames,
+    topology: &MoleculeTopology,
+) -> Result<AtomGroup, String> {
+    let expr = SelectionExpr::parse(query)?;
+    let positions = frames
+        .current
+        .as_ref()
+        .map(|f| f.positions.clone())
+        .or_else(|| sim_data.get_frame(0).map(|f| f.positions))
+        .unwrap_or_default();
+    Ok(AtomGroup {
+        name: name.to_string(),
+        query: Some(query.to_string()),
+        atom_ids: expr.select_in(sim_data.atom_data(), &positions, topology),
+        style,
+    })
 }
 
 /// Main UI panel: status, Open button, controls, error display
@@ -353,10 +519,10 @@ pub fn main_ui_panel(
                 std::thread::spawn(move || {
                     let result = rfd::FileDialog::new()
                         .add_filter(
-                            "Molecular files (XYZ, PDB, GRO, mmCIF, DCD)",
-                            LOADABLE_EXTENSIONS,
+                            "Molecular files",
+                            &dialog_extensions(FormatDescriptor::is_loadable),
                         )
-                        .add_filter("All molecular formats", SUPPORTED_EXTENSIONS)
+                        .add_filter("All molecular formats", &dialog_extensions(|_| true))
                         .add_filter("All files", &["*"])
                         .pick_file();
                     let _ = tx.send(result);
@@ -414,7 +580,7 @@ pub fn main_ui_panel(
                     ui.separator();
                     ui.label(
                         bevy_egui::egui::RichText::new(
-                            "⚠ DCD loaded without topology — element colors are placeholders",
+                            "⚠ Trajectory loaded without topology — element colors are placeholders",
                         )
                         .color(bevy_egui::egui::Color32::from_rgb(220, 140, 50)),
                     );
@@ -422,7 +588,7 @@ pub fn main_ui_panel(
                     if ui
                         .add_enabled(
                             !topo_pending,
-                            bevy_egui::egui::Button::new("Load topology (PDB/GRO/mmCIF)..."),
+                            bevy_egui::egui::Button::new("Load topology (PDB/GRO/mmCIF/PSF/prmtop/LAMMPS/MOL2/SDF)..."),
                         )
                         .clicked()
                     {
@@ -430,7 +596,10 @@ pub fn main_ui_panel(
                         topology_ui.topology_picker.receiver = Some(rx);
                         std::thread::spawn(move || {
                             let result = rfd::FileDialog::new()
-                                .add_filter("Topology files", TOPOLOGY_EXTENSIONS)
+                                .add_filter(
+                                    "Topology files",
+                                    &dialog_extensions(|d| d.read_topology.is_some()),
+                                )
                                 .pick_file();
                             let _ = tx.send(result);
                         });
@@ -560,8 +729,11 @@ pub fn main_ui_panel(
                 if timeline_frames.streaming {
                     ui.label(
                         bevy_egui::egui::RichText::new(format!(
-                            "Streaming: {} cached frames",
-                            timeline_frames.cached_count
+                            "Streaming: {} cached frames ({})",
+                            timeline_frames.cached_count,
+                            crate::performance::memory::format_bytes(
+                                timeline_frames.cached_bytes as u64
+                            )
                         ))
                         .small(),
                     );
@@ -674,11 +846,82 @@ pub fn main_ui_panel(
                 ui.label("No trajectory loaded");
             }
 
+            let periodic = timeline_frames
+                .current
+                .as_ref()
+                .and_then(|f| f.cell)
+                .is_some_and(|cell| cell.is_periodic());
+            if periodic {
+                // Edit a copy so unchanged settings don't reset the unwrap history.
+                let mut pbc = viz_ui.pbc.clone();
+                ui.label("Periodic boundaries:");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut pbc.make_whole, "Make whole");
+                    ui.checkbox(&mut pbc.unwrap, "Unwrap");
+                });
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut pbc.center, "Center").changed() {
+                        pbc.center_atoms = selection_ui.selection.atom_ids().to_vec();
+                    }
+                    if pbc.center {
+                        let target = if pbc.center_atoms.is_empty() {
+                            "whole system".to_string()
+                        } else {
+                            format!("{} atoms", pbc.center_atoms.len())
+                        };
+                        ui.label(bevy_egui::egui::RichText::new(target).small());
+                        if !selection_ui.selection.is_empty()
+                            && ui.small_button("Use selection").clicked()
+                        {
+                            pbc.center_atoms = selection_ui.selection.atom_ids().to_vec();
+                        }
+                    }
+                });
+                viz_ui.pbc.set_if_neq(pbc);
+            }
+
             ui.separator();
             ui.heading("Selection");
             ui.separator();
 
             ui.label(format!("Selected atoms: {}", selection_ui.selection.len()));
+
+            // Text selection query (e.g. "within 5 of resname LIG")
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    bevy_egui::egui::TextEdit::singleline(&mut selection_ui.query.text)
+                        .hint_text("protein and chain A")
+                        .desired_width(180.0),
+                );
+                let submitted = response.lost_focus()
+                    && ui.input(|i| i.key_pressed(bevy_egui::egui::Key::Enter));
+                let has_query = !selection_ui.query.text.trim().is_empty();
+                let select = ui
+                    .add_enabled(has_query, bevy_egui::egui::Button::new("Select"))
+                    .clicked();
+                let add = ui
+                    .add_enabled(has_query, bevy_egui::egui::Button::new("Add"))
+                    .on_hover_text("Add matches to the current selection")
+                    .clicked();
+                if has_query && (submitted || select || add) {
+                    let query = selection_ui.query.text.trim().to_string();
+                    selection_ui.query_events.send(ApplySelectionQueryEvent {
+                        query,
+                        add_to_selection: add,
+                    });
+                }
+            });
+            if let Some(err) = &selection_ui.query.error {
+                ui.label(
+                    bevy_egui::egui::RichText::new(format!("Query error: {err}"))
+                        .small()
+                        .color(bevy_egui::egui::Color32::from_rgb(220, 100, 100)),
+                );
+            } else if let Some(count) = selection_ui.query.last_count {
+                ui.label(
+                    bevy_egui::egui::RichText::new(format!("Query matched {count} atoms")).small(),
+                );
+            }
             if selection_ui.selection.len() < 2 {
                 ui.label(
                     bevy_egui::egui::RichText::new("Shift+Click to add atoms for measurements")
@@ -717,6 +960,54 @@ pub fn main_ui_panel(
                 "Show labels on selected atoms",
             );
 
+            // Per-atom visibility
+            ui.horizontal(|ui| {
+                let has_selection = !selection_ui.selection.is_empty();
+                if ui
+                    .add_enabled(has_selection, bevy_egui::egui::Button::new("Hide selection"))
+                    .clicked()
+                {
+                    selection_ui
+                        .visibility_events
+                        .send(AtomVisibilityAction::HideSelection);
+                }
+                if ui
+                    .add_enabled(has_selection, bevy_egui::egui::Button::new("Show only selection"))
+                    .clicked()
+                {
+                    selection_ui
+                        .visibility_events
+                        .send(AtomVisibilityAction::ShowOnlySelection);
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Hide solvent/ions").clicked() {
+                    selection_ui
+                        .visibility_events
+                        .send(AtomVisibilityAction::HideSolvent);
+                }
+                if ui
+                    .add_enabled(
+                        !selection_ui.hidden.is_empty(),
+                        bevy_egui::egui::Button::new("Show all"),
+                    )
+                    .clicked()
+                {
+                    selection_ui
+                        .visibility_events
+                        .send(AtomVisibilityAction::ShowAll);
+                }
+            });
+            if !selection_ui.hidden.is_empty() {
+                ui.label(
+                    bevy_egui::egui::RichText::new(format!(
+                        "Hidden atoms: {}",
+                        selection_ui.hidden.len()
+                    ))
+                    .small(),
+                );
+            }
+
             // Clear selection button
             if !selection_ui.selection.is_empty() {
                 if ui.button("Clear selection").clicked() {
@@ -732,6 +1023,380 @@ pub fn main_ui_panel(
                 ui.label("No atoms selected");
             }
 
+            ui.separator();
+            ui.heading("Groups");
+            ui.separator();
+
+            // Create named groups from the selection or the query text
+            let groups_ui = &mut selection_ui.groups;
+            let default_style = GroupStyle::from_config(&viz_ui.viz_config);
+            ui.horizontal(|ui| {
+                ui.add(
+                    bevy_egui::egui::TextEdit::singleline(&mut groups_ui.editor.name)
+                        .hint_text("Group name")
+                        .desired_width(110.0),
+                );
+                let name = match groups_ui.editor.name.trim() {
+                    "" => format!("Group {}", groups_ui.groups.len() + 1),
+                    name => name.to_string(),
+                };
+                let query = selection_ui.query.text.trim().to_string();
+
+                if ui
+                    .add_enabled(
+                        !selection_ui.selection.is_empty(),
+                        bevy_egui::egui::Button::new("From selection"),
+                    )
+                    .clicked()
+                {
+                    groups_ui.groups.add(AtomGroup {
+                        name,
+                        query: None,
+                        atom_ids: selection_ui.selection.selected_atom_ids.clone(),
+                        style: default_style,
+                    });
+                    groups_ui.editor.name.clear();
+                    groups_ui.editor.error = None;
+                } else if ui
+                    .add_enabled(!query.is_empty(), bevy_egui::egui::Button::new("From query"))
+                    .on_hover_text("Group follows the query when another file is loaded")
+                    .clicked()
+                {
+                    match query_group(
+                        &name,
+                        &query,
+                        default_style,
+                        &sim_data,
+                        &timeline_frames,
+                        &selection_ui.topology,
+                    ) {
+                        Ok(group) => {
+                            groups_ui.groups.add(group);
+                            groups_ui.editor.name.clear();
+                            groups_ui.editor.error = None;
+                        }
+                        Err(err) => groups_ui.editor.error = Some(err),
+                    }
+                }
+            });
+            if ui
+                .button("Protein / ligand / solvent")
+                .on_hover_text("Cartoon protein, ball-and-stick ligand, hidden solvent")
+                .clicked()
+            {
+                let protein_mode = if viz_ui.backbone.cartoon_available {
+                    RenderMode::Cartoon
+                } else {
+                    RenderMode::Licorice
+                };
+                for (name, query, style) in [
+                    ("Protein", "protein", GroupStyle::new(protein_mode)),
+                    ("Ligand", "ligand", GroupStyle::new(RenderMode::BallAndStick)),
+                    ("Solvent", "solvent", GroupStyle::hidden()),
+                ] {
+                    if let Ok(group) = query_group(
+                        name,
+                        query,
+                        style,
+                        &sim_data,
+                        &timeline_frames,
+                        &selection_ui.topology,
+                    ) {
+                        groups_ui.groups.add(group);
+                    }
+                }
+            }
+            if let Some(err) = &groups_ui.editor.error {
+                ui.label(
+                    bevy_egui::egui::RichText::new(format!("Group error: {err}"))
+                        .small()
+                        .color(bevy_egui::egui::Color32::from_rgb(220, 100, 100)),
+                );
+            }
+
+            // Per-group style; later groups take precedence for shared atoms
+            let mut remove_group = None;
+            for i in 0..groups_ui.groups.len() {
+                let Some(group) = groups_ui.groups.get(i) else {
+                    continue;
+                };
+                let original = group.style;
+                let label = format!("{} ({} atoms)", group.name, group.atom_ids.len());
+                let source = group
+                    .query
+                    .clone()
+                    .unwrap_or_else(|| "From selection".to_string());
+                let mut style = original;
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut style.visible, label).on_hover_text(source);
+                    if ui.small_button("✕").on_hover_text("Delete group").clicked() {
+                        remove_group = Some(i);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    bevy_egui::egui::ComboBox::from_id_source(("group_render_mode", i))
+                        .width(110.0)
+                        .selected_text(style.render_mode.name())
+                        .show_ui(ui, |ui| {
+                            for mode in RenderMode::ALL {
+                                if *mode == RenderMode::Surface {
+                                    continue;
+                                }
+                                let available =
+                                    !mode.shows_ribbon() || viz_ui.backbone.cartoon_available;
+                                ui.add_enabled_ui(available, |ui| {
+                                    ui.selectable_value(&mut style.render_mode, *mode, mode.name());
+                                });
+                            }
+                        });
+                    bevy_egui::egui::ComboBox::from_id_source(("group_color_scheme", i))
+                        .width(90.0)
+                        .selected_text(style.color_scheme.name())
+                        .show_ui(ui, |ui| {
+                            for scheme in ColorScheme::UI_SCHEMES {
+                                ui.selectable_value(&mut style.color_scheme, *scheme, scheme.name());
+                            }
+                        });
+                });
+                ui.add(
+                    bevy_egui::egui::Slider::new(&mut style.atom_scale, 0.1..=2.0)
+                        .logarithmic(true)
+                        .text("atoms"),
+                );
+                ui.add(
+                    bevy_egui::egui::Slider::new(&mut style.bond_scale, 0.1..=3.0)
+                        .logarithmic(true)
+                        .text("bonds"),
+                );
+
+                if style != original {
+                    groups_ui.groups.set_style(i, style);
+                }
+            }
+            if let Some(i) = remove_group {
+                groups_ui.groups.remove(i);
+            }
+            if groups_ui.groups.is_empty() {
+                ui.label(
+                    bevy_egui::egui::RichText::new("Ungrouped atoms use the Visualization settings")
+                        .small(),
+                );
+            }
+
+            ui.horizontal(|ui| {
+                let save_pending = groups_ui.save.receiver.is_some();
+                let open_pending = groups_ui.open.receiver.is_some();
+                if ui
+                    .add_enabled(
+                        !save_pending && !groups_ui.groups.is_empty(),
+                        bevy_egui::egui::Button::new("💾 Save groups..."),
+                    )
+                    .clicked()
+                {
+                    let (tx, rx) = crossbeam_channel::unbounded();
+                    groups_ui.save.receiver = Some(rx);
+                    std::thread::spawn(move || {
+                        let result = rfd::FileDialog::new()
+                            .add_filter("Atom groups (JSON)", &["json"])
+                            .set_file_name("groups.json")
+                            .save_file();
+                        let _ = tx.send(result);
+                    });
+                }
+                if ui
+                    .add_enabled(
+                        !open_pending,
+                        bevy_egui::egui::Button::new("📂 Load groups..."),
+                    )
+                    .clicked()
+                {
+                    let (tx, rx) = crossbeam_channel::unbounded();
+                    groups_ui.open.receiver = Some(rx);
+                    std::thread::spawn(move || {
+                        let result = rfd::FileDialog::new()
+                            .add_filter("Atom groups (JSON)", &["json"])
+                            .pick_file();
+                        let _ = tx.send(result);
+                    });
+                }
+            });
+
+            ui.separator();
+            ui.heading("Molecules");
+            ui.separator();
+
+            // Residue / chain / molecule tree; clicking a node selects it
+            // (shift-click adds to the selection)
+            let topology = &selection_ui.topology;
+            let mut tree_query = None;
+            if topology.is_empty() {
+                ui.label("No structure loaded");
+            } else {
+                ui.label(format!(
+                    "{} chains, {} residues, {} molecules",
+                    topology.chains().len(),
+                    topology.residues().len(),
+                    topology.molecules().len()
+                ));
+                bevy_egui::egui::CollapsingHeader::new("By type")
+                    .id_source("molecule_tree_types")
+                    .show(ui, |ui| {
+                        for (molecule_type, count) in topology.molecule_counts() {
+                            bevy_egui::egui::CollapsingHeader::new(format!(
+                                "{} ({count})",
+                                molecule_type.name()
+                            ))
+                            .id_source(("molecule_type", molecule_type.name()))
+                            .show(ui, |ui| {
+                                let molecules = topology
+                                    .molecules()
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(_, m)| m.molecule_type == molecule_type);
+                                for (i, molecule) in molecules.take(MAX_TREE_ROWS) {
+                                    let label = format!(
+                                        "#{i} {} ({} atoms)",
+                                        molecule.name,
+                                        molecule.atom_ids.len()
+                                    );
+                                    let mut response = ui.selectable_label(false, label);
+                                    if !molecule.sequence.is_empty() {
+                                        response = response.on_hover_text(&molecule.sequence);
+                                    }
+                                    if response.clicked() {
+                                        tree_query = Some(format!("fragment {i}"));
+                                    }
+                                }
+                                if count > MAX_TREE_ROWS {
+                                    ui.label(format!("… {} more", count - MAX_TREE_ROWS));
+                                }
+                            });
+                        }
+                    });
+                bevy_egui::egui::CollapsingHeader::new("By chain")
+                    .id_source("molecule_tree_chains")
+                    .show(ui, |ui| {
+                        for (c, chain) in topology.chains().iter().enumerate() {
+                            let id = match chain.id.trim() {
+                                "" => "(blank)",
+                                id => id,
+                            };
+                            bevy_egui::egui::CollapsingHeader::new(format!(
+                                "Chain {id} ({} residues)",
+                                chain.residues.len()
+                            ))
+                            .id_source(("molecule_tree_chain", c))
+                            .show(ui, |ui| {
+                                if ui.small_button("Select chain").clicked() {
+                                    tree_query = Some(index_query("residue", &chain.residues));
+                                }
+                                for &r in chain.residues.iter().take(MAX_TREE_ROWS) {
+                                    let residue = &topology.residues()[r];
+                                    let label = format!(
+                                        "{} {} ({} atoms)",
+                                        residue.name,
+                                        residue.residue_id,
+                                        residue.atom_ids.len()
+                                    );
+                                    if ui
+                                        .selectable_label(false, label)
+                                        .on_hover_text(residue.molecule_type.name())
+                                        .clicked()
+                                    {
+                                        tree_query = Some(format!("residue {r}"));
+                                    }
+                                }
+                                if chain.residues.len() > MAX_TREE_ROWS {
+                                    ui.label(format!(
+                                        "… {} more",
+                                        chain.residues.len() - MAX_TREE_ROWS
+                                    ));
+                                }
+                            });
+                        }
+                    });
+            }
+            if let Some(query) = tree_query {
+                let add_to_selection = ui.input(|i| i.modifiers.shift);
+                selection_ui.query.text = query.clone();
+                selection_ui.query_events.send(ApplySelectionQueryEvent {
+                    query,
+                    add_to_selection,
+                });
+            }
+
+            // Biological assemblies and crystal neighbours of the loaded structure
+            let symmetry_state = &mut topology_ui.symmetry;
+            let symmetry = symmetry_state
+                .asymmetric_unit(&sim_data)
+                .trajectory
+                .metadata
+                .symmetry
+                .clone();
+            if sim_data.loaded && !symmetry.is_empty() {
+                ui.separator();
+                ui.heading("Symmetry");
+                ui.separator();
+
+                if let Some(active) = &symmetry_state.active {
+                    ui.label(format!("Showing: {active}"));
+                }
+                if !symmetry.assemblies.is_empty() {
+                    let selected = symmetry_state
+                        .selected_assembly
+                        .as_deref()
+                        .and_then(|id| symmetry.assembly(id))
+                        .unwrap_or(&symmetry.assemblies[0]);
+                    let mut selected_id = selected.id.clone();
+                    ui.horizontal(|ui| {
+                        bevy_egui::egui::ComboBox::from_id_source("symmetry_assembly")
+                            .width(160.0)
+                            .selected_text(selected.label())
+                            .show_ui(ui, |ui| {
+                                for assembly in &symmetry.assemblies {
+                                    ui.selectable_value(
+                                        &mut selected_id,
+                                        assembly.id.clone(),
+                                        assembly.label(),
+                                    );
+                                }
+                            });
+                        if ui.button("Generate assembly").clicked() {
+                            topology_ui
+                                .symmetry_actions
+                                .send(SymmetryAction::ShowAssembly(selected_id.clone()));
+                        }
+                    });
+                    symmetry_state.selected_assembly = Some(selected_id);
+                }
+                if let Some(group) = &symmetry.space_group {
+                    ui.label(format!("Space group: {group}"));
+                }
+                if symmetry.space_group.is_some() || !symmetry.operators.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.label("Radius (Å)");
+                        ui.add(
+                            bevy_egui::egui::DragValue::new(&mut symmetry_state.mate_radius)
+                                .range(1.0..=30.0)
+                                .speed(0.1),
+                        );
+                        if ui.button("Generate symmetry mates").clicked() {
+                            topology_ui
+                                .symmetry_actions
+                                .send(SymmetryAction::ShowSymmetryMates {
+                                    radius: symmetry_state.mate_radius,
+                                });
+                        }
+                    });
+                }
+                if symmetry_state.is_expanded() && ui.button("Show asymmetric unit").clicked() {
+                    topology_ui
+                        .symmetry_actions
+                        .send(SymmetryAction::ShowAsymmetricUnit);
+                }
+            }
+
             ui.separator();
             ui.heading("Visualization");
             ui.separator();
@@ -813,6 +1478,29 @@ pub fn main_ui_panel(
                         );
                     }
                 });
+            if viz_ui.viz_config.color_scheme == ColorScheme::Custom {
+                let names = timeline_frames
+                    .current
+                    .as_ref()
+                    .map(|frame| frame.property_names())
+                    .unwrap_or_default();
+                if names.is_empty() {
+                    ui.label("No per-atom properties in this trajectory");
+                } else {
+                    let selected = viz_ui.viz_config.color_property.clone();
+                    bevy_egui::egui::ComboBox::from_id_source("viz_color_property")
+                        .selected_text(selected.as_deref().unwrap_or("Choose property"))
+                        .show_ui(ui, |ui| {
+                            for name in names {
+                                ui.selectable_value(
+                                    &mut viz_ui.viz_config.color_property,
+                                    Some(name.to_string()),
+                                    name,
+                                );
+                            }
+                        });
+                }
+            }
 
             ui.separator();
 
@@ -851,6 +1539,7 @@ pub fn main_ui_panel(
             // Visibility toggles
             ui.checkbox(&mut viz_ui.viz_config.show_atoms, "Show atoms");
             ui.checkbox(&mut viz_ui.viz_config.show_bonds, "Show bonds");
+            ui.checkbox(&mut viz_ui.viz_config.show_unit_cell, "Show unit cell");
 
             ui.separator();
             ui.heading("Bonds");
@@ -908,13 +1597,15 @@ pub fn main_ui_panel(
             let gltf_pending = export_panel.saves.gltf.receiver.is_some();
             let povray_pending = export_panel.saves.povray.receiver.is_some();
             let video_dialog_pending = export_panel.saves.video.receiver.is_some();
+            let trajectory_pending = export_panel.saves.trajectory.receiver.is_some();
             let video_recording =
                 export_panel.video.status != crate::export::video::VideoExportStatus::Idle;
             let any_export_pending = screenshot_pending
                 || obj_pending
                 || gltf_pending
                 || povray_pending
-                || video_dialog_pending;
+                || video_dialog_pending
+                || trajectory_pending;
 
             if ui
                 .add_enabled(
@@ -1034,6 +1725,93 @@ pub fn main_ui_panel(
                 }
             }
 
+            bevy_egui::egui::CollapsingHeader::new("Trajectory export")
+                .default_open(false)
+                .show(ui, |ui| {
+                    let last_index = total_frames.saturating_sub(1);
+                    let trajectory = &mut *export_panel.trajectory;
+                    let running = trajectory.is_running();
+
+                    bevy_egui::egui::ComboBox::from_label("Format")
+                        .selected_text(trajectory.options.format.label())
+                        .show_ui(ui, |ui| {
+                            for format in TrajectoryFormat::available() {
+                                ui.selectable_value(
+                                    &mut trajectory.options.format,
+                                    format,
+                                    format.label(),
+                                );
+                            }
+                        });
+
+                    let mut last = trajectory.options.last_frame.unwrap_or(last_index);
+                    ui.horizontal(|ui| {
+                        ui.label("Frames");
+                        ui.add(
+                            bevy_egui::egui::DragValue::new(&mut trajectory.options.first_frame)
+                                .range(0..=last_index),
+                        );
+                        ui.label("to");
+                        ui.add(bevy_egui::egui::DragValue::new(&mut last).range(0..=last_index));
+                    });
+                    trajectory.options.last_frame = (last < last_index).then_some(last);
+                    ui.add(
+                        bevy_egui::egui::DragValue::new(&mut trajectory.options.stride)
+                            .range(1..=total_frames.max(1))
+                            .prefix("every ")
+                            .suffix(" frames"),
+                    );
+                    let num_frames = trajectory.options.frame_indices(total_frames).len();
+
+                    let num_selected = selection_ui.selection.len();
+                    if num_selected == 0 {
+                        trajectory.selection_only = false;
+                    }
+                    ui.add_enabled(
+                        num_selected > 0,
+                        bevy_egui::egui::Checkbox::new(
+                            &mut trajectory.selection_only,
+                            format!("Selected atoms only ({num_selected})"),
+                        ),
+                    );
+                    ui.add_enabled(
+                        trajectory.options.format.needs_topology(),
+                        bevy_egui::egui::Checkbox::new(
+                            &mut trajectory.options.write_topology,
+                            "Write PDB topology alongside",
+                        ),
+                    );
+
+                    let format = trajectory.options.format;
+                    if ui
+                        .add_enabled(
+                            sim_data.loaded && num_frames > 0 && !trajectory_pending && !running,
+                            bevy_egui::egui::Button::new(format!(
+                                "💾 Export {num_frames} frames..."
+                            )),
+                        )
+                        .clicked()
+                    {
+                        let (tx, rx) = crossbeam_channel::unbounded();
+                        export_panel.saves.trajectory.receiver = Some(rx);
+
+                        std::thread::spawn(move || {
+                            let result = rfd::FileDialog::new()
+                                .add_filter(format.label(), &[format.extension()])
+                                .set_file_name(format!("trajectory.{}", format.extension()))
+                                .save_file();
+                            let _ = tx.send(result);
+                        });
+                    }
+
+                    if running {
+                        ui.add(
+                            bevy_egui::egui::ProgressBar::new(trajectory.progress)
+                                .show_percentage(),
+                        );
+                    }
+                });
+
             if any_export_pending {
                 ui.label(bevy_egui::egui::RichText::new("Choosing save location...").italics());
             }
@@ -1094,6 +1872,10 @@ pub fn register(app: &mut App) {
         .init_resource::<GltfSaveState>()
         .init_resource::<VideoSaveState>()
         .init_resource::<PovRaySaveState>()
+        .init_resource::<TrajectorySaveState>()
+        .init_resource::<AtomGroupEditorState>()
+        .init_resource::<GroupsSaveState>()
+        .init_resource::<GroupsOpenState>()
         .add_systems(
             Update,
             (
@@ -1105,6 +1887,9 @@ pub fn register(app: &mut App) {
                 export_gltf_save_poll,
                 video_save_poll,
                 povray_save_poll,
+                trajectory_save_poll,
+                groups_save_poll,
+                groups_open_poll,
                 render_mode_shortcuts,
             ),
         )
@@ -1,24 +1,18 @@
 //! Math utility functions for molecular dynamics
 
+use crate::core::cell::UnitCell;
 use bevy::prelude::*;
 use nalgebra::{Matrix3, Vector3};
 use std::f32::consts::PI;
 
-/// Apply periodic boundary conditions to a position
-pub fn apply_pbc(mut pos: Vec3, box_size: Vec3) -> Vec3 {
-    pos.x = pos.x.rem_euclid(box_size.x);
-    pos.y = pos.y.rem_euclid(box_size.y);
-    pos.z = pos.z.rem_euclid(box_size.z);
-    pos
+/// Apply periodic boundary conditions to a position (wrap into the primary cell)
+pub fn apply_pbc(pos: Vec3, cell: &UnitCell) -> Vec3 {
+    cell.wrap(pos)
 }
 
-/// Calculate minimum image distance between two positions with PBC
-pub fn minimum_image(pos_a: Vec3, pos_b: Vec3, box_size: Vec3) -> Vec3 {
-    let mut delta = pos_b - pos_a;
-    delta.x -= box_size.x * (delta.x / box_size.x).round();
-    delta.y -= box_size.y * (delta.y / box_size.y).round();
-    delta.z -= box_size.z * (delta.z / box_size.z).round();
-    delta
+/// Calculate minimum image displacement from `pos_a` to `pos_b` with PBC
+pub fn minimum_image(pos_a: Vec3, pos_b: Vec3, cell: &UnitCell) -> Vec3 {
+    cell.minimum_image(pos_b - pos_a)
 }
 
 /// Convert degrees to radians
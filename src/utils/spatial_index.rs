@@ -3,12 +3,12 @@
 use crate::core::atom::{AtomData, Element};
 use bevy::prelude::*;
 use rstar::{RTree, RTreeObject, AABB};
-use std::collections::HashMap;
 
 /// One atom entry in the spatial index.
 #[derive(Clone, Copy, Debug)]
 pub struct IndexedAtom {
     pub atom_id: u32,
+    pub atom_index: usize,
     pub element: Element,
     pub residue_id: u32,
     pub position: [f32; 3],
@@ -30,17 +30,18 @@ pub struct AtomSpatialIndex {
 }
 
 impl AtomSpatialIndex {
-    pub fn build(atom_data: &[AtomData], positions: &HashMap<u32, Vec3>) -> Self {
+    /// Index atoms at `positions`, given in `atom_data` order.
+    pub fn build(atom_data: &[AtomData], positions: &[Vec3]) -> Self {
         let entries: Vec<IndexedAtom> = atom_data
             .iter()
-            .filter_map(|a| {
-                let pos = positions.get(&a.id)?;
-                Some(IndexedAtom {
-                    atom_id: a.id,
-                    element: a.element,
-                    residue_id: a.residue_id,
-                    position: [pos.x, pos.y, pos.z],
-                })
+            .zip(positions)
+            .enumerate()
+            .map(|(index, (a, pos))| IndexedAtom {
+                atom_id: a.id,
+                atom_index: index,
+                element: a.element,
+                residue_id: a.residue_id,
+                position: pos.to_array(),
             })
             .collect();
 
@@ -64,24 +65,33 @@ impl AtomSpatialIndex {
 
     /// Find all atom IDs within `radius` of `center`.
     pub fn neighbors_within(&self, center: Vec3, radius: f32) -> Vec<u32> {
-        let Some(tree) = &self.tree else {
-            return Vec::new();
-        };
+        self.atoms_within(center, radius)
+            .map(|a| a.atom_id)
+            .collect()
+    }
+
+    /// Find the `atom_data` indices of all atoms within `radius` of `center`.
+    pub fn neighbor_indices_within(&self, center: Vec3, radius: f32) -> Vec<usize> {
+        self.atoms_within(center, radius)
+            .map(|a| a.atom_index)
+            .collect()
+    }
 
+    fn atoms_within(&self, center: Vec3, radius: f32) -> impl Iterator<Item = &IndexedAtom> {
         let envelope = AABB::from_corners(
             [center.x - radius, center.y - radius, center.z - radius],
             [center.x + radius, center.y + radius, center.z + radius],
         );
 
-        tree.locate_in_envelope_intersecting(&envelope)
-            .filter(|a| {
+        self.tree
+            .iter()
+            .flat_map(move |tree| tree.locate_in_envelope_intersecting(&envelope))
+            .filter(move |a| {
                 let dx = a.position[0] - center.x;
                 let dy = a.position[1] - center.y;
                 let dz = a.position[2] - center.z;
                 (dx * dx + dy * dy + dz * dz).sqrt() <= radius
             })
-            .map(|a| a.atom_id)
-            .collect()
     }
 
     pub fn is_built(&self) -> bool {
@@ -93,9 +103,9 @@ impl AtomSpatialIndex {
 mod tests {
     use super::*;
 
-    fn sample_atoms(n: usize) -> (Vec<AtomData>, HashMap<u32, Vec3>) {
+    fn sample_atoms(n: usize) -> (Vec<AtomData>, Vec<Vec3>) {
         let mut atoms = Vec::new();
-        let mut positions = HashMap::new();
+        let mut positions = Vec::new();
         for i in 0..n {
             let id = i as u32;
             atoms.push(AtomData::new(
@@ -106,7 +116,7 @@ mod tests {
                 "A".into(),
                 format!("C{i}"),
             ));
-            positions.insert(id, Vec3::new(i as f32 * 1.5, 0.0, 0.0));
+            positions.push(Vec3::new(i as f32 * 1.5, 0.0, 0.0));
         }
         (atoms, positions)
     }
@@ -118,5 +128,6 @@ mod tests {
         let neighbors = index.neighbors_within(Vec3::ZERO, 2.0);
         assert!(!neighbors.is_empty());
         assert!(neighbors.contains(&0));
+        assert_eq!(index.neighbor_indices_within(Vec3::X * 3.0, 0.1), vec![2]);
     }
 }
@@ -3,7 +3,6 @@
 use crate::core::atom::{AtomData, Element};
 use crate::core::trajectory::{FrameData, Trajectory};
 use bevy::prelude::*;
-use std::collections::HashMap;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
@@ -43,22 +42,21 @@ pub fn synthetic_atom_data(count: usize) -> Vec<AtomData> {
         .collect()
 }
 
-/// Build a position map for `count` atoms at frame 0.
-pub fn synthetic_positions(count: usize) -> HashMap<u32, Vec3> {
-    (0..count)
-        .map(|i| (i as u32, position_for_atom(i, 0)))
-        .collect()
+/// Frame 0 positions for `count` atoms, in atom order.
+pub fn synthetic_positions(count: usize) -> Vec<Vec3> {
+    (0..count).map(|i| position_for_atom(i, 0)).collect()
 }
 
 /// Build an in-memory trajectory for benchmarks.
 pub fn synthetic_trajectory(atom_count: usize, frame_count: usize) -> Trajectory {
     let mut trajectory = Trajectory::new(PathBuf::from("synthetic.xyz"), atom_count, 1.0);
     for frame in 0..frame_count {
-        let mut frame_data = FrameData::new(frame, frame as f32);
-        for atom in 0..atom_count {
-            frame_data.set_position(atom as u32, position_for_atom(atom, frame));
-        }
-        trajectory.frames.push(frame_data);
+        let positions = (0..atom_count)
+            .map(|atom| position_for_atom(atom, frame))
+            .collect();
+        trajectory
+            .frames
+            .push(FrameData::with_positions(frame, frame as f32, positions));
     }
     trajectory
 }
@@ -42,7 +42,7 @@ fn main() {
                     }
 
                     // Check for box dimensions
-                    if let Some(box_size) = frame.box_size {
+                    if let Some(box_size) = frame.cell.map(|c| c.lengths) {
                         println!("\n--- Box Dimensions ---");
                         println!("Box: {:.4} x {:.4} x {:.4} nm",
                             box_size[0], box_size[1], box_size[2]);
@@ -20,14 +20,16 @@ pub fn write_minimal_dcd(path: &Path, num_atoms: u32, num_frames: u32) -> std::i
     for _ in 0..5 {
         file.write_i32::<LittleEndian>(0)?;
     }
-    file.write_i32::<LittleEndian>(0)?;
-    file.write_f64::<LittleEndian>(0.02)?;
+    file.write_f32::<LittleEndian>(0.02)?; // DELTA (REAL*4)
     for _ in 0..9 {
         file.write_i32::<LittleEndian>(0)?;
     }
-    file.write_i32::<LittleEndian>(0)?;
-    file.write_i32::<LittleEndian>(0)?;
+    file.write_i32::<LittleEndian>(24)?; // CHARMM version
+    file.write_i32::<LittleEndian>(84)?;
+
+    file.write_i32::<LittleEndian>(4)?;
     file.write_i32::<LittleEndian>(0)?; // n_title = 0
+    file.write_i32::<LittleEndian>(4)?;
 
     file.write_i32::<LittleEndian>(4)?;
     file.write_i32::<LittleEndian>(num_atoms as i32)?;
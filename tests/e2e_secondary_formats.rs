@@ -1,10 +1,11 @@
-//! End-to-end load pipeline tests for GRO, DCD, and mmCIF (UI-equivalent paths).
+//! End-to-end load pipeline tests for GRO, DCD, mmCIF, LAMMPS, MOL2 and SDF (UI-equivalent paths).
 
 mod common;
 
 use bevy::prelude::*;
 use common::{dcd_fixture, example, fixture, require_path};
 use gumol_viz_engine::core::atom::Element;
+use gumol_viz_engine::core::bond::BondOrder;
 use gumol_viz_engine::io::load_topology;
 use gumol_viz_engine::io::pdb::PDBParser;
 use gumol_viz_engine::io::pdb_mmap;
@@ -85,7 +86,7 @@ fn test_pdb_optimized_load_pipeline() {
     assert!(sim.loaded);
     assert_eq!(sim.num_atoms(), 327);
     assert!(!sim.bond_data.is_empty());
-    assert!(sim.atom_data.iter().any(|a| a.element == Element::S));
+    assert!(sim.atom_data().iter().any(|a| a.element == Element::S));
 }
 
 #[test]
@@ -101,7 +102,10 @@ fn test_dcd_load_pipeline_needs_topology() {
     assert!(sim.needs_topology);
     assert_eq!(sim.num_atoms(), 3);
     assert_eq!(sim.num_frames(), 2);
-    assert!(sim.atom_data.iter().all(|a| a.element == Element::Unknown));
+    assert!(sim
+        .atom_data()
+        .iter()
+        .all(|a| a.element == Element::Unknown));
 
     let _ = std::fs::remove_dir_all(&dir);
 }
@@ -130,3 +134,125 @@ fn test_dcd_load_with_topology_via_load_file() {
 
     let _ = std::fs::remove_dir_all(&dir);
 }
+
+#[test]
+fn test_dcd_load_with_psf_topology() {
+    let dir = std::env::temp_dir().join(format!("gumol_e2e_dcd_psf_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let dcd_path = dir.join("water.dcd");
+    dcd_fixture::write_minimal_dcd(&dcd_path, 3, 2).unwrap();
+    let psf_path = dir.join("water.psf");
+    std::fs::write(
+        &psf_path,
+        "PSF\n\n       1 !NTITLE\n REMARKS water\n\n       3 !NATOM\n\
+       1 WAT  1    TIP3 OH2  OT    -0.834000       15.9994           0\n\
+       2 WAT  1    TIP3 H1   HT     0.417000        1.0080           0\n\
+       3 WAT  1    TIP3 H2   HT     0.417000        1.0080           0\n\n\
+       2 !NBOND: bonds\n       1       2       1       3\n",
+    )
+    .unwrap();
+
+    let (trajectory, _frame_provider) = streaming::open_dcd(&dcd_path).expect("open dcd");
+    let (atom_data, bond_data) = load_topology(&psf_path).expect("load psf topology");
+    validate_atom_count(atom_data.len(), trajectory.num_atoms).expect("atom count match");
+
+    assert_eq!(atom_data[0].element, Element::O);
+    assert_eq!(atom_data[0].chain_id, "WAT");
+    assert!((atom_data[0].charge + 0.834).abs() < 1e-6);
+    assert_eq!(bond_data.len(), 2);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_lammps_dump_pipeline_with_data_topology() {
+    let dir = std::env::temp_dir().join(format!("gumol_e2e_lammps_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let dump_path = dir.join("water.lammpstrj");
+    let mut dump = String::new();
+    for step in [0, 50] {
+        dump.push_str(&format!(
+            "ITEM: TIMESTEP\n{step}\nITEM: NUMBER OF ATOMS\n3\nITEM: BOX BOUNDS pp pp pp\n\
+             0.0 10.0\n0.0 10.0\n0.0 10.0\nITEM: ATOMS id type x y z c_pe\n\
+             2 2 1.9 1.0 1.0 -0.5\n1 1 1.0 1.0 1.0 -2.0\n3 2 0.8 1.9 1.0 -0.4\n"
+        ));
+    }
+    std::fs::write(&dump_path, dump).unwrap();
+    let data_path = dir.join("water.data");
+    std::fs::write(
+        &data_path,
+        "water\n\n3 atoms\n2 atom types\n2 bonds\n\nMasses\n\n1 15.9994\n2 1.008\n\n\
+         Atoms # full\n\n1 1 1 -0.834 1.0 1.0 1.0\n2 1 2 0.417 1.9 1.0 1.0\n\
+         3 1 2 0.417 0.8 1.9 1.0\n\nBonds\n\n1 1 1 2\n2 1 1 3\n",
+    )
+    .unwrap();
+
+    let mut sim = run_load_pipeline(dump_path);
+    assert!(sim.loaded);
+    assert!(sim.needs_topology);
+    assert_eq!(sim.num_frames(), 2);
+    let frame = sim.get_frame(1).unwrap();
+    assert_eq!(frame.properties["c_pe"][0], -2.0);
+
+    let (atom_data, bond_data) = load_topology(&data_path).expect("load data topology");
+    sim.apply_topology(atom_data, bond_data)
+        .expect("atom count match");
+    assert_eq!(sim.atom_data()[0].element, Element::O);
+    assert_eq!(sim.atom_data()[1].element, Element::H);
+    assert_eq!(sim.bond_data.len(), 2);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_mol2_and_sdf_pipelines_keep_file_bond_orders() {
+    let dir = std::env::temp_dir().join(format!("gumol_e2e_ligand_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut mol2 = String::from(
+        "@<TRIPOS>MOLECULE\nbenzene\n 6 6 1 0 0\nSMALL\nNO_CHARGES\n\n@<TRIPOS>ATOM\n",
+    );
+    for i in 0..6 {
+        let angle = i as f32 * std::f32::consts::PI / 3.0;
+        mol2.push_str(&format!(
+            "{} C{} {:.4} {:.4} 0.0000 C.ar 1 BEN1 0.0000\n",
+            i + 1,
+            i + 1,
+            1.39 * angle.cos(),
+            1.39 * angle.sin()
+        ));
+    }
+    mol2.push_str("@<TRIPOS>BOND\n");
+    for i in 0..6 {
+        mol2.push_str(&format!("{} {} {} ar\n", i + 1, i + 1, (i + 1) % 6 + 1));
+    }
+    let mol2_path = dir.join("benzene.mol2");
+    std::fs::write(&mol2_path, mol2).unwrap();
+
+    let sim = run_load_pipeline(mol2_path);
+    assert!(sim.loaded);
+    assert_eq!(sim.num_atoms(), 6);
+    assert_eq!(sim.bond_data.len(), 6);
+    assert!(sim.bond_data.iter().all(|b| b.order == BondOrder::Aromatic));
+
+    let sdf_path = dir.join("co2.sdf");
+    std::fs::write(
+        &sdf_path,
+        "CO2\n  gumol\n\n  3  2  0  0  0  0  0  0  0  0999 V2000\n\
+         \x20   0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0\n\
+         \x20   1.1600    0.0000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0\n\
+         \x20  -1.1600    0.0000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0\n\
+         \x20 1  2  2  0\n\x20 1  3  2  0\nM  END\n$$$$\n",
+    )
+    .unwrap();
+
+    let sim = run_load_pipeline(sdf_path);
+    assert!(sim.loaded);
+    assert_eq!(sim.num_atoms(), 3);
+    assert!(sim.bond_data.iter().all(|b| b.order == BondOrder::Double));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
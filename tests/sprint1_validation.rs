@@ -12,9 +12,7 @@ use gumol_viz_engine::export::obj::write_obj_to_path;
 use gumol_viz_engine::export::scene_snapshot::{AtomSnapshot, SceneSnapshot};
 use gumol_viz_engine::io::pdb::PDBParser;
 use gumol_viz_engine::performance::PerformanceSettings;
-use gumol_viz_engine::rendering::gpu_interpolation::{
-    interpolate_dense_positions, DenseAtomLayout,
-};
+use gumol_viz_engine::rendering::gpu_interpolation::interpolate_dense_positions;
 use gumol_viz_engine::rendering::instanced::{
     estimate_instanced_draw_calls, spawn_atoms_instanced_internal, MAX_INSTANCED_DRAW_CALLS,
 };
@@ -25,7 +23,6 @@ use gumol_viz_engine::systems::loading::{
     SimulationData,
 };
 use gumol_viz_engine::utils::spatial_index::AtomSpatialIndex;
-use std::collections::HashMap;
 use std::path::PathBuf;
 
 fn load_test_app() -> App {
@@ -55,7 +52,7 @@ fn test_1crn_load_pipeline() {
     assert!(sim.loaded);
     assert_eq!(sim.num_atoms(), 327);
     assert_eq!(sim.num_frames(), 1);
-    assert_eq!(sim.atom_data.len(), 327);
+    assert_eq!(sim.atom_data().len(), 327);
 }
 
 #[test]
@@ -78,12 +75,7 @@ fn test_1crn_bond_data_and_spatial_detection() {
     );
 
     let frame = sim.get_frame(0).expect("first frame");
-    let mut positions = HashMap::new();
-    for atom in &sim.atom_data {
-        if let Some(pos) = frame.get_position(atom.id) {
-            positions.insert(atom.id, pos);
-        }
-    }
+    assert_eq!(frame.num_atoms(), sim.atom_data().len());
 
     // Water has no CONECT — spatial distance detection should infer O–H bonds.
     let water_path = fixture("water.xyz");
@@ -95,25 +87,19 @@ fn test_1crn_bond_data_and_spatial_detection() {
     let water = water_app.world().resource::<SimulationData>();
     assert!(water.bond_data.is_empty());
 
-    let water_frame = water.get_frame(0).unwrap();
-    let mut water_positions = HashMap::new();
-    for atom in &water.atom_data {
-        if let Some(pos) = water_frame.get_position(atom.id) {
-            water_positions.insert(atom.id, pos);
-        }
-    }
+    let water_positions = &water.get_frame(0).unwrap().positions;
 
-    let spatial = AtomSpatialIndex::build(&water.atom_data, &water_positions);
+    let spatial = AtomSpatialIndex::build(water.atom_data(), water_positions);
     let config = BondDetectionConfig::default();
     let perf = PerformanceSettings::default();
-    let water_bonds = resolve_bond_list(water, &water_positions, &config, &perf, Some(&spatial));
+    let water_bonds = resolve_bond_list(water, water_positions, &config, &perf, Some(&spatial));
     assert!(
         water_bonds.len() >= 2,
         "water should infer at least two O–H bonds, got {}",
         water_bonds.len()
     );
 
-    let _ = (sim, positions);
+    let _ = sim;
 }
 
 #[test]
@@ -133,7 +119,6 @@ fn test_multi_frame_timeline_interpolation() {
 
     let frame_a = sim.get_frame(0).expect("frame 0");
     let frame_b = sim.get_frame(1).expect("frame 1");
-    let layout = DenseAtomLayout::build(&sim.atom_data);
 
     let timeline = TimelineState {
         interpolate: true,
@@ -142,27 +127,18 @@ fn test_multi_frame_timeline_interpolation() {
         ..Default::default()
     };
 
-    let pos_a: Vec<Vec3> = layout
-        .dense_atom_ids
-        .iter()
-        .map(|&id| frame_a.get_position(id).unwrap())
-        .collect();
-    let pos_b: Vec<Vec3> = layout
-        .dense_atom_ids
-        .iter()
-        .map(|&id| frame_b.get_position(id).unwrap())
-        .collect();
-
-    let dense = interpolate_dense_positions(&pos_a, &pos_b, timeline.interpolation_factor);
+    let dense = interpolate_dense_positions(
+        &frame_a.positions,
+        &frame_b.positions,
+        timeline.interpolation_factor,
+    );
     let expected = interpolate_frames(&frame_a, &frame_b, timeline.interpolation_factor);
 
-    for (i, &atom_id) in layout.dense_atom_ids.iter().enumerate() {
-        let exp = expected.get_position(atom_id).unwrap();
+    assert_eq!(dense.len(), expected.num_atoms());
+    for (i, (got, exp)) in dense.iter().zip(&expected.positions).enumerate() {
         assert!(
-            dense[i].distance(exp) < 1e-5,
-            "atom {atom_id}: dense={:?} expected={:?}",
-            dense[i],
-            exp
+            got.distance(*exp) < 1e-5,
+            "atom {i}: dense={got:?} expected={exp:?}"
         );
     }
 }
@@ -215,7 +191,7 @@ fn test_100k_position_dense_sync_smoke() {
     for f in 0..5 {
         let mut frame = FrameData::new(f, f as f32);
         for a in &atoms {
-            frame.set_position(a.id, Vec3::new(a.id as f32 * 0.01, 0.0, f as f32));
+            frame.set_position(a.id as usize, Vec3::new(a.id as f32 * 0.01, 0.0, f as f32));
         }
         trajectory.add_frame(frame);
     }
@@ -252,7 +228,7 @@ fn test_100k_position_dense_sync_under_60fps_budget() {
     for f in 0..5 {
         let mut frame = FrameData::new(f, f as f32);
         for a in &atoms {
-            frame.set_position(a.id, Vec3::new(a.id as f32 * 0.01, 0.0, f as f32));
+            frame.set_position(a.id as usize, Vec3::new(a.id as f32 * 0.01, 0.0, f as f32));
         }
         trajectory.add_frame(frame);
     }
@@ -331,7 +307,7 @@ fn test_instanced_spawn_1crn_scale() {
         .resource_scope(|world, mut meshes: Mut<Assets<Mesh>>| {
             let mut mesh_pool = AtomMeshPool::default();
             let mut commands = world.commands();
-            let (entities, ids_by_element) = spawn_atoms_instanced_internal(
+            let (entities, indices_by_element) = spawn_atoms_instanced_internal(
                 &mut commands,
                 &mut meshes,
                 &mut mesh_pool,
@@ -339,7 +315,7 @@ fn test_instanced_spawn_1crn_scale() {
                 &atom_data,
                 &viz,
             );
-            let instance_count: usize = ids_by_element.values().map(|v| v.len()).sum();
+            let instance_count: usize = indices_by_element.values().map(|v| v.len()).sum();
             assert_eq!(instance_count, atom_data.len());
             assert_eq!(entities.len(), estimate_instanced_draw_calls(&atom_data));
             assert!(entities.len() <= MAX_INSTANCED_DRAW_CALLS);
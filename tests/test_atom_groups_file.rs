@@ -0,0 +1,103 @@
+//! Atom groups saved to a groups file and restored into a running app.
+
+mod common;
+
+use bevy::prelude::*;
+use common::fixture;
+use gumol_viz_engine::core::groups::{AtomGroup, AtomGroups, GroupStyle};
+use gumol_viz_engine::core::visualization::RenderMode;
+use gumol_viz_engine::systems::loading::{
+    handle_load_file_events_sync, FileLoadErrorEvent, FileLoadedEvent, LoadFileEvent,
+    SimulationData,
+};
+use gumol_viz_engine::systems::visualization::{
+    handle_atom_group_files, LoadAtomGroupsEvent, SaveAtomGroupsEvent,
+};
+use gumol_viz_engine::ui::notifications::UiNotifications;
+
+fn groups_test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.init_resource::<SimulationData>();
+    app.init_resource::<AtomGroups>();
+    app.init_resource::<UiNotifications>();
+    app.add_event::<LoadFileEvent>();
+    app.add_event::<FileLoadedEvent>();
+    app.add_event::<FileLoadErrorEvent>();
+    app.add_event::<SaveAtomGroupsEvent>();
+    app.add_event::<LoadAtomGroupsEvent>();
+    app.add_systems(
+        Update,
+        (handle_load_file_events_sync, handle_atom_group_files).chain(),
+    );
+    app
+}
+
+#[test]
+fn test_groups_file_roundtrip_through_events() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("groups.json");
+    let mut app = groups_test_app();
+    app.world_mut().send_event(LoadFileEvent {
+        path: fixture("water.xyz"),
+    });
+    app.update();
+
+    {
+        let mut groups = app.world_mut().resource_mut::<AtomGroups>();
+        groups.add(AtomGroup {
+            name: "hydrogens".into(),
+            query: Some("element H".into()),
+            atom_ids: vec![1, 2],
+            style: GroupStyle::new(RenderMode::Licorice),
+        });
+        groups.add(AtomGroup {
+            name: "picked".into(),
+            query: None,
+            atom_ids: vec![0, 99],
+            style: GroupStyle::hidden(),
+        });
+    }
+    app.world_mut()
+        .send_event(SaveAtomGroupsEvent { path: path.clone() });
+    app.update();
+    assert!(path.exists());
+
+    app.world_mut().resource_mut::<AtomGroups>().clear();
+    app.world_mut().send_event(LoadAtomGroupsEvent { path });
+    app.update();
+
+    let groups = app.world().resource::<AtomGroups>();
+    let names: Vec<_> = groups.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(names, ["hydrogens", "picked"]);
+    assert_eq!(groups.get(0).unwrap().query.as_deref(), Some("element H"));
+    assert_eq!(
+        groups.get(0).unwrap().style,
+        GroupStyle::new(RenderMode::Licorice)
+    );
+    // Restored against the loaded water: atom 99 does not exist
+    assert_eq!(groups.get(1).unwrap().atom_ids, vec![0]);
+    assert_eq!(groups.group_of(2), Some(0));
+    assert_eq!(groups.group_of(0), Some(1));
+}
+
+#[test]
+fn test_loading_missing_groups_file_keeps_groups() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut app = groups_test_app();
+    app.world_mut().resource_mut::<AtomGroups>().add(AtomGroup {
+        name: "kept".into(),
+        query: None,
+        atom_ids: vec![0],
+        style: GroupStyle::hidden(),
+    });
+
+    app.world_mut().send_event(LoadAtomGroupsEvent {
+        path: dir.path().join("missing.json"),
+    });
+    app.update();
+
+    assert_eq!(app.world().resource::<AtomGroups>().len(), 1);
+    let message = app.world().resource::<UiNotifications>().message.clone();
+    assert!(message.unwrap().starts_with("Loading groups failed"));
+}
@@ -0,0 +1,100 @@
+//! Compressed inputs: `.pdb.gz`, `.cif.xz` and a gzipped streamed DCD load
+//! like their uncompressed originals.
+
+mod common;
+
+use bevy::prelude::*;
+use common::dcd_fixture::write_minimal_dcd;
+use common::fixture;
+use gumol_viz_engine::io::compression::{self, Compression};
+use gumol_viz_engine::io::load_topology;
+use gumol_viz_engine::io::streaming::{DcdFrameProvider, FrameProvider};
+use gumol_viz_engine::systems::loading::{
+    handle_load_file_events_sync, FileLoadErrorEvent, FileLoadedEvent, LoadFileEvent,
+    SimulationData,
+};
+use std::io::Write;
+use std::path::Path;
+
+fn gzip(source: &Path, target: &Path) {
+    let mut encoder = flate2::write::GzEncoder::new(
+        std::fs::File::create(target).unwrap(),
+        flate2::Compression::default(),
+    );
+    encoder.write_all(&std::fs::read(source).unwrap()).unwrap();
+    encoder.finish().unwrap();
+}
+
+fn xz(source: &Path, target: &Path) {
+    let mut encoder = xz2::write::XzEncoder::new(std::fs::File::create(target).unwrap(), 6);
+    encoder.write_all(&std::fs::read(source).unwrap()).unwrap();
+    encoder.finish().unwrap();
+}
+
+fn load(path: &Path) -> SimulationData {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.init_resource::<SimulationData>();
+    app.add_event::<LoadFileEvent>();
+    app.add_event::<FileLoadedEvent>();
+    app.add_event::<FileLoadErrorEvent>();
+    app.add_systems(Update, handle_load_file_events_sync);
+
+    app.world_mut().send_event(LoadFileEvent {
+        path: path.to_path_buf(),
+    });
+    app.update();
+
+    let errors: Vec<_> = app
+        .world_mut()
+        .resource_mut::<Events<FileLoadErrorEvent>>()
+        .drain()
+        .collect();
+    assert!(errors.is_empty(), "load failed: {:?}", errors[0].error);
+    app.world_mut().remove_resource::<SimulationData>().unwrap()
+}
+
+#[test]
+fn test_gzipped_pdb_and_xz_mmcif_load() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let pdb_gz = dir.path().join("1CRN.pdb.gz");
+    gzip(&fixture("1CRN.pdb"), &pdb_gz);
+    assert_eq!(Compression::detect(&pdb_gz).unwrap(), Compression::Gzip);
+    let pdb = load(&pdb_gz);
+    assert_eq!(pdb.num_atoms(), 327);
+    assert!(!pdb.bond_data.is_empty());
+    assert!(pdb.get_frame(0).unwrap().cell.is_some());
+
+    let cif_xz = dir.path().join("1CRN.cif.xz");
+    xz(&fixture("1CRN.cif"), &cif_xz);
+    let cif = load(&cif_xz);
+    assert_eq!(cif.num_atoms(), 327);
+
+    // Compressed structures also serve as topologies
+    let (atoms, _) = load_topology(&pdb_gz).unwrap();
+    assert_eq!(atoms.len(), 327);
+}
+
+#[test]
+fn test_gzipped_dcd_streams_from_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let dcd = dir.path().join("run.dcd");
+    write_minimal_dcd(&dcd, 5, 4).unwrap();
+    let dcd_gz = dir.path().join("run.dcd.gz");
+    gzip(&dcd, &dcd_gz);
+
+    let provider = DcdFrameProvider::open(&dcd_gz).expect("stream gzipped DCD");
+    assert_eq!(provider.num_frames(), 4);
+    assert_eq!(provider.file_path(), dcd_gz.as_path());
+    let frame = provider.get_frame(3).unwrap();
+    assert!((frame.position(2).unwrap().x - 2.3).abs() < 1e-4);
+
+    let cached = compression::seekable_path(&dcd_gz).unwrap();
+    assert_eq!(
+        std::fs::read(&cached).unwrap(),
+        std::fs::read(&dcd).unwrap()
+    );
+
+    let _ = std::fs::remove_file(cached);
+}
@@ -1,19 +1,54 @@
-//! File format detection for all supported extensions.
+//! File format detection for all supported extensions, and content
+//! sniffing through the format registry.
 
+mod common;
+
+use bevy::prelude::*;
+use common::fixture;
+use gumol_viz_engine::core::atom::Element;
 use gumol_viz_engine::io::FileFormat;
+use gumol_viz_engine::systems::loading::{
+    handle_load_file_events_sync, FileLoadErrorEvent, FileLoadedEvent, LoadFileEvent,
+    SimulationData,
+};
 use std::path::Path;
 
 #[test]
 fn test_format_from_path_extensions() {
     let cases = [
         ("molecule.xyz", FileFormat::XYZ),
+        ("frames.extxyz", FileFormat::XYZ),
         ("protein.pdb", FileFormat::PDB),
+        ("pdb1crn.ent", FileFormat::PDB),
+        ("ligand.pdbqt", FileFormat::Pdbqt),
+        ("protein.pqr", FileFormat::Pqr),
         ("box.gro", FileFormat::GRO),
         ("traj.dcd", FileFormat::DCD),
+        ("traj.xtc", FileFormat::XTC),
+        ("traj.trr", FileFormat::TRR),
         ("structure.cif", FileFormat::MmCIF),
         ("structure.mmcif", FileFormat::MmCIF),
         ("structure.mcif", FileFormat::MmCIF),
+        ("system.psf", FileFormat::PSF),
+        ("system.prmtop", FileFormat::Prmtop),
+        ("system.parm7", FileFormat::Prmtop),
+        ("traj.mdcrd", FileFormat::Mdcrd),
+        ("traj.crd", FileFormat::Mdcrd),
+        ("traj.nc", FileFormat::NetCDF),
+        ("traj.lammpstrj", FileFormat::LammpsDump),
+        ("run.dump", FileFormat::LammpsDump),
+        ("system.data", FileFormat::LammpsData),
+        ("system.lmp", FileFormat::LammpsData),
+        ("ligand.mol2", FileFormat::Mol2),
+        ("ligands.sdf", FileFormat::Sdf),
+        ("ligand.mol", FileFormat::Sdf),
+        ("1crn.pdb.gz", FileFormat::PDB),
+        ("1crn.cif.gz", FileFormat::MmCIF),
+        ("box.gro.bz2", FileFormat::GRO),
+        ("traj.xtc.xz", FileFormat::XTC),
+        ("frames.xyz.gz", FileFormat::XYZ),
         ("unknown.dat", FileFormat::Unknown),
+        ("archive.gz", FileFormat::Unknown),
     ];
 
     for (name, expected) in cases {
@@ -33,6 +68,15 @@ fn test_loadable_formats() {
         FileFormat::GRO,
         FileFormat::MmCIF,
         FileFormat::DCD,
+        FileFormat::XTC,
+        FileFormat::TRR,
+        FileFormat::Mdcrd,
+        FileFormat::NetCDF,
+        FileFormat::LammpsDump,
+        FileFormat::Mol2,
+        FileFormat::Sdf,
+        FileFormat::Pqr,
+        FileFormat::Pdbqt,
     ] {
         assert!(
             FileFormat::is_loadable(&format),
@@ -41,6 +85,9 @@ fn test_loadable_formats() {
     }
 
     assert!(!FileFormat::is_loadable(&FileFormat::Unknown));
+    assert!(!FileFormat::is_loadable(&FileFormat::PSF));
+    assert!(!FileFormat::is_loadable(&FileFormat::Prmtop));
+    assert!(!FileFormat::is_loadable(&FileFormat::LammpsData));
 }
 
 #[test]
@@ -54,4 +101,138 @@ fn test_format_from_content() {
     let gro =
         "Water\n    3\n    1SOL    OW    1   0.126   0.639   0.322   0.0001   0.0002   0.0003";
     assert_eq!(FileFormat::from_content(gro), FileFormat::GRO);
+
+    let psf = "PSF EXT CMAP\n\n       1 !NTITLE\n REMARKS test\n";
+    assert_eq!(FileFormat::from_content(psf), FileFormat::PSF);
+
+    let prmtop = "%VERSION  VERSION_STAMP = V0001.000\n%FLAG TITLE\n";
+    assert_eq!(FileFormat::from_content(prmtop), FileFormat::Prmtop);
+    assert_eq!(
+        FileFormat::from_bytes(b"CDF\x02\0\0\0\x03"),
+        FileFormat::NetCDF
+    );
+
+    let dump = "ITEM: TIMESTEP\n0\nITEM: NUMBER OF ATOMS\n1\n";
+    assert_eq!(FileFormat::from_content(dump), FileFormat::LammpsDump);
+    let data = "LAMMPS data file\n\n3 atoms\n1 atom types\n";
+    assert_eq!(FileFormat::from_content(data), FileFormat::LammpsData);
+
+    let mol2 = "# ligand\n@<TRIPOS>MOLECULE\nlig\n 1 0 1 0 0\n";
+    assert_eq!(FileFormat::from_content(mol2), FileFormat::Mol2);
+    // Numeric molfile names must not be taken for an XYZ atom count
+    let sdf = "2244\n  gumol\n\n  1  0  0  0  0  0  0  0  0  0999 V2000\n";
+    assert_eq!(FileFormat::from_content(sdf), FileFormat::Sdf);
+}
+
+fn load(path: &Path) -> SimulationData {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.init_resource::<SimulationData>();
+    app.add_event::<LoadFileEvent>();
+    app.add_event::<FileLoadedEvent>();
+    app.add_event::<FileLoadErrorEvent>();
+    app.add_systems(Update, handle_load_file_events_sync);
+
+    app.world_mut().send_event(LoadFileEvent {
+        path: path.to_path_buf(),
+    });
+    app.update();
+
+    let errors: Vec<_> = app
+        .world_mut()
+        .resource_mut::<Events<FileLoadErrorEvent>>()
+        .drain()
+        .collect();
+    assert!(errors.is_empty(), "load failed: {:?}", errors[0].error);
+    app.world_mut().remove_resource::<SimulationData>().unwrap()
+}
+
+#[test]
+fn test_detect_by_content() {
+    let dir = tempfile::tempdir().unwrap();
+
+    // A GRO title that is a number used to be read as an XYZ atom count
+    let gro = dir.path().join("box.gro");
+    std::fs::write(
+        &gro,
+        "1000\n    1\n    1SOL     OW    1   0.126   0.639   0.322\n   1.0   1.0   1.0\n",
+    )
+    .unwrap();
+    assert_eq!(FileFormat::detect(&gro), FileFormat::GRO);
+    let untitled = dir.path().join("box.txt");
+    std::fs::copy(&gro, &untitled).unwrap();
+    assert_eq!(FileFormat::detect(&untitled), FileFormat::GRO);
+
+    // Content wins over an unknown or wrong extension
+    let pdb = dir.path().join("download.dat");
+    std::fs::copy(fixture("1CRN.pdb"), &pdb).unwrap();
+    assert_eq!(FileFormat::detect(&pdb), FileFormat::PDB);
+    let cif = dir.path().join("1crn.pdb");
+    std::fs::copy(fixture("1CRN.cif"), &cif).unwrap();
+    assert_eq!(FileFormat::detect(&cif), FileFormat::MmCIF);
+
+    let notes = dir.path().join("notes.txt");
+    std::fs::write(&notes, "42\nnot a molecule\n").unwrap();
+    assert_eq!(FileFormat::detect(&notes), FileFormat::Unknown);
+}
+
+#[test]
+fn test_ent_and_pqr_load() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let ent = dir.path().join("pdb1crn.ent");
+    std::fs::copy(fixture("1CRN.pdb"), &ent).unwrap();
+    assert_eq!(load(&ent).num_atoms(), 327);
+
+    let pqr = dir.path().join("ala.pqr");
+    std::fs::write(
+        &pqr,
+        "REMARK   1 PQR file generated by PDB2PQR\n\
+         ATOM      1  N   ALA     1      -0.677  -1.230  -0.491 -0.3000 1.8240\n\
+         ATOM      2  CA  ALA     1      -0.001   0.064  -0.491  0.2100 1.9080\n\
+         END\n",
+    )
+    .unwrap();
+    let sim_data = load(&pqr);
+    assert_eq!(sim_data.num_atoms(), 2);
+    assert!((sim_data.atom_data()[0].charge + 0.3).abs() < 1e-6);
+}
+
+#[test]
+fn test_pdbqt_halogens_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let ligand = dir.path().join("ligand.pdbqt");
+    std::fs::write(
+        &ligand,
+        "REMARK  Name = halogenated ligand\n\
+         ROOT\n\
+         ATOM      1  C1  LIG A   1      -0.795   0.455   0.030  1.00  0.00    +0.044 A\n\
+         ATOM      2  CL1 LIG A   1       1.002   1.731   0.030  1.00  0.00    -0.084 Cl\n\
+         ATOM      3  BR1 LIG A   1      -2.640  -0.170   0.030  1.00  0.00    -0.045 Br\n\
+         ATOM      4  O1  LIG A   1       0.300  -1.200   0.100  1.00  0.00    -0.391 OA\n\
+         ATOM      5  H1  LIG A   1       0.900  -1.900   0.100  1.00  0.00    +0.210 HD\n\
+         ATOM      6  N1  LIG A   1      -1.500   1.500   0.200  1.00  0.00    -0.240 NA\n\
+         ENDROOT\n\
+         TORSDOF 0\n",
+    )
+    .unwrap();
+    assert_eq!(FileFormat::detect(&ligand), FileFormat::Pdbqt);
+
+    let sim_data = load(&ligand);
+    let elements: Vec<Element> = sim_data.atom_data().iter().map(|a| a.element).collect();
+    assert_eq!(
+        elements,
+        [
+            Element::C,
+            Element::Cl,
+            Element::Br,
+            Element::O,
+            Element::H,
+            Element::N
+        ]
+    );
+    let charges: Vec<f32> = sim_data.atom_data().iter().map(|a| a.charge).collect();
+    assert!((charges[1] + 0.084).abs() < 1e-6);
+    assert!((charges[2] + 0.045).abs() < 1e-6);
+    assert!((charges[4] - 0.210).abs() < 1e-6);
 }
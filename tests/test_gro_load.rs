@@ -47,7 +47,7 @@ fn test_load_actual_gro_file() {
     let frame = trajectory.get_frame(0).expect("Should have frame 0");
 
     // Verify positions
-    let pos_0 = frame.get_position(0).expect("Should have atom 0 position");
+    let pos_0 = frame.position(0).expect("Should have atom 0 position");
     assert!((pos_0.x - 0.126).abs() < 0.001, "Oxygen X coordinate");
     assert!((pos_0.y - 0.639).abs() < 0.001, "Oxygen Y coordinate");
     assert!((pos_0.z - 0.322).abs() < 0.001, "Oxygen Z coordinate");
@@ -58,15 +58,15 @@ fn test_load_actual_gro_file() {
     let velocities = frame.velocities.as_ref().unwrap();
     assert_eq!(velocities.len(), 3, "Should have 3 velocities");
 
-    let vel_0 = velocities.get(&0).expect("Should have atom 0 velocity");
+    let vel_0 = velocities.first().expect("Should have atom 0 velocity");
     assert!((vel_0.x - 0.0001).abs() < 0.0001, "Oxygen X velocity");
     assert!((vel_0.y - 0.0002).abs() < 0.0001, "Oxygen Y velocity");
     assert!((vel_0.z - 0.0003).abs() < 0.0001, "Oxygen Z velocity");
 
     // Verify box dimensions
-    assert!(frame.box_size.is_some(), "Should have box dimensions");
+    assert!(frame.cell.is_some(), "Should have box dimensions");
 
-    let box_size = frame.box_size.unwrap();
+    let box_size = frame.cell.unwrap().lengths;
     assert_eq!(box_size[0], 0.0, "Box X should be 0.0");
     assert_eq!(box_size[1], 0.0, "Box Y should be 0.0");
     assert_eq!(box_size[2], 0.0, "Box Z should be 0.0");
@@ -4,6 +4,10 @@ mod common;
 
 use bevy::prelude::*;
 use common::fixture;
+use gumol_viz_engine::core::cell::UnitCell;
+use gumol_viz_engine::core::trajectory::{FrameData, Trajectory};
+use gumol_viz_engine::io::trr::TrrWriter;
+use gumol_viz_engine::io::xtc::XtcWriter;
 use gumol_viz_engine::systems::loading::{
     handle_load_file_events_sync, FileLoadErrorEvent, FileLoadedEvent, LoadFileEvent,
     SimulationData,
@@ -58,3 +62,45 @@ fn test_load_missing_file_emits_error_event() {
     assert_eq!(errors.len(), 1);
     assert_eq!(errors[0].path, path);
 }
+
+#[test]
+fn test_nm_trajectories_load_in_angstrom() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut trajectory = Trajectory::new(dir.path().join("source"), 3, 1000.0);
+    for i in 0..2 {
+        let mut frame = FrameData::new(i, i as f32 * 1000.0);
+        frame.cell = Some(UnitCell::from_lengths_angles(
+            [20.0, 20.0, 20.0],
+            [90.0, 90.0, 90.0],
+        ));
+        for atom in 0..3 {
+            frame.set_position(atom, Vec3::new(atom as f32 * 1.5, 2.0 + i as f32, -3.0));
+        }
+        trajectory.add_frame(frame);
+    }
+
+    let xtc = dir.path().join("traj.xtc");
+    let trr = dir.path().join("traj.trr");
+    XtcWriter::write_trajectory(&xtc, &trajectory).unwrap();
+    TrrWriter::write_trajectory(&trr, &trajectory).unwrap();
+
+    for path in [xtc, trr] {
+        let mut app = load_test_app();
+        app.world_mut()
+            .send_event(LoadFileEvent { path: path.clone() });
+        app.update();
+
+        let sim = app.world().resource::<SimulationData>();
+        assert!(sim.loaded, "{} should load", path.display());
+        let frame = sim.get_frame(1).unwrap();
+        let expected = Vec3::new(3.0, 3.0, -3.0);
+        assert!(
+            (frame.positions[2] - expected).abs().max_element() < 1e-3,
+            "{}: {:?}",
+            path.display(),
+            frame.positions[2]
+        );
+        let cell = frame.cell.unwrap();
+        assert!((cell.vectors[0].x - 20.0).abs() < 1e-3);
+    }
+}
@@ -2,8 +2,10 @@
 
 mod common;
 
-use common::{example, require_path};
+use common::{example, fixture, require_path};
 use gumol_viz_engine::core::atom::Element;
+use gumol_viz_engine::core::bond::BondType;
+use gumol_viz_engine::core::molecule::SecondaryStructure;
 use gumol_viz_engine::io::mmcif::MmcifParser;
 
 #[test]
@@ -18,7 +20,7 @@ fn test_load_water_cif() {
     assert_eq!(trajectory.num_atoms, 3);
 
     let frame = trajectory.get_frame(0).expect("frame 0");
-    let o = frame.get_position(0).expect("oxygen");
+    let o = frame.position(0).expect("oxygen");
     assert!((o.x - 0.0).abs() < 1e-3);
 }
 
@@ -30,10 +32,92 @@ fn test_mmcif_atom_site_loop_from_string() {
 
     assert_eq!(trajectory.num_atoms, 3);
     let frame = trajectory.get_frame(0).unwrap();
-    assert!(frame.get_position(0).is_some());
-    assert!(frame.get_position(1).is_some());
-    assert!(frame.get_position(2).is_some());
+    assert!(frame.position(0).is_some());
+    assert!(frame.position(1).is_some());
+    assert!(frame.position(2).is_some());
 
     // Sanity check element parsing path used by mmCIF loader metadata.
     assert_eq!(Element::from_symbol("O").unwrap(), Element::O);
 }
+
+#[test]
+fn test_load_pdbx_entry_1crn() {
+    let path = fixture("1CRN.cif");
+    if !require_path(&path) {
+        return;
+    }
+
+    let (trajectory, atoms, bonds) =
+        MmcifParser::parse_file_with_atoms(&path).expect("1CRN.cif should parse");
+    assert_eq!(trajectory.num_atoms, 327);
+    assert_eq!(atoms.len(), 327);
+    assert_eq!(
+        trajectory.metadata.title,
+        "WATER STRUCTURE OF A HYDROPHOBIC PROTEIN AT ATOMIC RESOLUTION. \
+         PENTAGON RINGS OF WATER MOLECULES IN CRYSTALS OF CRAMBIN"
+    );
+    assert_eq!(trajectory.metadata.classification, "PLANT PROTEIN");
+    let extra = &trajectory.metadata.extra;
+    assert_eq!(
+        extra.get("space_group").map(String::as_str),
+        Some("P 1 21 1")
+    );
+    assert_eq!(
+        extra.get("keywords").map(String::as_str),
+        Some("PLANT SEED PROTEIN, PLANT PROTEIN")
+    );
+    assert_eq!(
+        extra.get("method").map(String::as_str),
+        Some("X-RAY DIFFRACTION")
+    );
+
+    // _struct_conn: three disulfides between SG atoms
+    assert_eq!(bonds.len(), 3);
+    for bond in &bonds {
+        assert_eq!(bond.bond_type, BondType::Disulfide);
+        let (a, b) = (
+            &atoms[bond.atom_a_id as usize],
+            &atoms[bond.atom_b_id as usize],
+        );
+        assert_eq!((a.name.as_str(), b.name.as_str()), ("SG", "SG"));
+        assert!((a.position.distance(b.position) - bond.length).abs() < 0.05);
+    }
+    assert_eq!(
+        (
+            atoms[bonds[0].atom_a_id as usize].residue_id,
+            atoms[bonds[0].atom_b_id as usize].residue_id
+        ),
+        (3, 40)
+    );
+
+    // _struct_conf helices and _struct_sheet_range strands
+    let ss = &trajectory.metadata.secondary_structure;
+    let at = |residue: u32| ss.get(&("A".to_string(), residue)).copied();
+    assert_eq!(at(7), Some(SecondaryStructure::AlphaHelix));
+    assert_eq!(at(30), Some(SecondaryStructure::AlphaHelix));
+    assert_eq!(at(33), Some(SecondaryStructure::BetaStrand));
+    assert_eq!(at(21), None);
+    assert_eq!(ss.len(), 13 + 8 + 4 + 4);
+
+    // Same annotations as the PDB HELIX/SHEET records of the entry
+    let (pdb_trajectory, _, _) =
+        gumol_viz_engine::io::pdb::PDBParser::parse_file_with_atoms(&fixture("1CRN.pdb")).unwrap();
+    assert_eq!(&pdb_trajectory.metadata.secondary_structure, ss);
+
+    let cell = trajectory.frames[0].cell.expect("_cell parsed");
+    assert!((cell.lengths[1] - 18.65).abs() < 1e-3);
+    assert!((cell.angles[1] - 90.77).abs() < 1e-3);
+
+    // Same atoms as the PDB version of the entry
+    let pdb_atoms =
+        gumol_viz_engine::io::pdb::PDBParser::parse_file_with_atoms(&fixture("1CRN.pdb"))
+            .expect("1CRN.pdb should parse")
+            .1;
+    for (cif_atom, pdb_atom) in atoms.iter().zip(&pdb_atoms) {
+        assert_eq!(cif_atom.name, pdb_atom.name);
+        assert_eq!(cif_atom.element, pdb_atom.element);
+        assert_eq!(cif_atom.residue_id, pdb_atom.residue_id);
+        assert!(cif_atom.position.distance(pdb_atom.position) < 1e-3);
+        assert!((cif_atom.b_factor - pdb_atom.b_factor).abs() < 1e-3);
+    }
+}
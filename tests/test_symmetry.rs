@@ -0,0 +1,83 @@
+//! Biological assembly and symmetry mate integration tests (1CRN).
+
+mod common;
+
+use common::fixture;
+use gumol_viz_engine::io::mmcif::MmcifParser;
+use gumol_viz_engine::io::pdb::PDBParser;
+use gumol_viz_engine::systems::loading::SimulationData;
+use gumol_viz_engine::systems::symmetry::{build_assembly, build_symmetry_mates};
+
+fn load_pdb() -> SimulationData {
+    let (trajectory, atoms, bonds) =
+        PDBParser::parse_file_with_atoms(&fixture("1CRN.pdb")).expect("1CRN.pdb should parse");
+    SimulationData::with_bonds(trajectory, atoms, bonds)
+}
+
+#[test]
+fn test_1crn_symmetry_from_pdb_and_mmcif_agree() {
+    let pdb = load_pdb();
+    let (cif_trajectory, _, _) =
+        MmcifParser::parse_file_with_atoms(&fixture("1CRN.cif")).expect("1CRN.cif should parse");
+
+    let pdb_symmetry = &pdb.trajectory.metadata.symmetry;
+    let cif_symmetry = &cif_trajectory.metadata.symmetry;
+    assert_eq!(pdb_symmetry.space_group.as_deref(), Some("P 1 21 1"));
+    assert_eq!(cif_symmetry.space_group.as_deref(), Some("P 1 21 1"));
+    assert_eq!(pdb_symmetry.assemblies.len(), cif_symmetry.assemblies.len());
+    for (pdb_assembly, cif_assembly) in pdb_symmetry.assemblies.iter().zip(&cif_symmetry.assemblies)
+    {
+        assert_eq!(pdb_assembly.id, cif_assembly.id);
+        assert_eq!(pdb_assembly.generators, cif_assembly.generators);
+        assert!(pdb_assembly
+            .details
+            .eq_ignore_ascii_case(&cif_assembly.details));
+    }
+
+    // REMARK 290 SMTRY matches the operators derived from the space group
+    let cell = pdb.get_frame(0).unwrap().cell.expect("CRYST1 cell");
+    let from_table = cif_symmetry.crystal_operators(&cell).unwrap();
+    let from_file = pdb_symmetry.crystal_operators(&cell).unwrap();
+    assert_eq!(from_table.len(), from_file.len());
+    for (a, b) in from_table.iter().zip(&from_file) {
+        assert!(a.rotation.abs_diff_eq(b.rotation, 1e-3));
+        assert!(a.translation.abs_diff_eq(b.translation, 1e-3));
+    }
+}
+
+#[test]
+fn test_1crn_assembly_and_symmetry_mates() {
+    let pdb = load_pdb();
+    let monomer = build_assembly(&pdb, "1").unwrap();
+    assert_eq!(monomer.num_atoms(), 327);
+    assert_eq!(monomer.bond_data.len(), pdb.bond_data.len());
+
+    let (crystal, mates) = build_symmetry_mates(&pdb, 5.0).unwrap();
+    assert!(mates > 0);
+    assert_eq!(crystal.num_atoms(), 327 * (mates + 1));
+    assert_eq!(crystal.trajectory.num_atoms, crystal.num_atoms());
+
+    // The asymmetric unit comes first, unchanged and still chain A
+    let original = pdb.get_frame(0).unwrap();
+    let expanded = crystal.get_frame(0).unwrap();
+    for i in 0..pdb.atom_data().len() {
+        assert_eq!(crystal.atom_data()[i].chain_id, "A");
+        assert_eq!(expanded.position(i), original.position(i));
+    }
+    // Each mate is a new chain with its own secondary structure annotations
+    assert_eq!(crystal.atom_data()[327].chain_id, "B");
+    let helix_residues = |data: &SimulationData, chain: &str| {
+        let ss = &data.trajectory.metadata.secondary_structure;
+        ss.keys().filter(|(c, _)| c == chain).count()
+    };
+    assert_eq!(helix_residues(&crystal, "B"), helix_residues(&pdb, "A"));
+
+    // Every mate has an atom within 5 Å of the asymmetric unit
+    let asu = &expanded.positions[..327];
+    for mate in 1..=mates {
+        let touches = expanded.positions[mate * 327..(mate + 1) * 327]
+            .iter()
+            .any(|p| asu.iter().any(|q| q.distance(*p) <= 5.0));
+        assert!(touches, "mate {mate} is out of range");
+    }
+}
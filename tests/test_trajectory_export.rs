@@ -0,0 +1,65 @@
+//! Trajectory export integration tests: a streamed DCD trimmed to a few
+//! residues and frames, written back as DCD with a PDB topology.
+
+mod common;
+
+use common::dcd_fixture::write_minimal_dcd;
+use common::fixture;
+use gumol_viz_engine::core::trajectory::Trajectory;
+use gumol_viz_engine::export::trajectory::{
+    export_simulation, TrajectoryExportOptions, TrajectoryFormat,
+};
+use gumol_viz_engine::io::dcd::DcdReader;
+use gumol_viz_engine::io::load_topology;
+use gumol_viz_engine::io::streaming::{DcdFrameProvider, FrameProvider};
+use gumol_viz_engine::io::topology::normalize_topology;
+use gumol_viz_engine::systems::loading::SimulationData;
+use gumol_viz_engine::systems::pbc::PbcTransformSettings;
+use std::sync::Arc;
+
+#[test]
+fn test_streamed_dcd_excerpt_with_topology() {
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+
+    let source = dir.join("run.dcd");
+    write_minimal_dcd(&source, 327, 10).unwrap();
+    let (atoms, bonds) = load_topology(&fixture("1CRN.pdb")).expect("1CRN topology");
+    let (atoms, bonds) = normalize_topology(atoms, bonds);
+
+    let provider = DcdFrameProvider::open(&source).expect("stream DCD");
+    let trajectory = Trajectory::new(source.clone(), 327, provider.time_step());
+    let sim_data = SimulationData::with_bonds(trajectory, atoms, bonds)
+        .with_frame_provider(Arc::new(provider));
+
+    let first_residues: Vec<u32> = sim_data
+        .atom_data()
+        .iter()
+        .filter(|a| a.residue_id <= 3)
+        .map(|a| a.id)
+        .collect();
+    let out = dir.join("excerpt.dcd");
+    let options = TrajectoryExportOptions {
+        format: TrajectoryFormat::Dcd,
+        first_frame: 2,
+        stride: 3,
+        atom_ids: Some(first_residues.clone()),
+        ..Default::default()
+    };
+    let summary = export_simulation(&sim_data, &PbcTransformSettings::default(), &out, &options)
+        .expect("export");
+    assert_eq!(summary.frames, 3);
+    assert_eq!(summary.atoms, first_residues.len());
+
+    // The excerpt pairs with its topology like any DCD + PDB
+    let reader = DcdReader::open(&out).unwrap();
+    assert_eq!(reader.num_frames(), 3);
+    let (topology, _) = load_topology(&summary.topology_path.unwrap()).unwrap();
+    assert_eq!(topology.len(), reader.num_atoms());
+    assert!(topology.iter().all(|a| a.residue_id <= 3));
+
+    // Source frame 8: atom i sits at x = i + 0.8
+    let last = reader.read_frame(2).unwrap();
+    let expected = first_residues[4] as f32 + 0.8;
+    assert!((last.position(4).unwrap().x - expected).abs() < 1e-4);
+}
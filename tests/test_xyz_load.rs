@@ -15,7 +15,7 @@ fn test_load_water_xyz_fixture() {
     assert_eq!(trajectory.num_atoms, 3);
 
     let frame = trajectory.get_frame(0).expect("frame 0");
-    let o_pos = frame.get_position(0).expect("oxygen position");
+    let o_pos = frame.position(0).expect("oxygen position");
     assert!((o_pos.x - 0.0).abs() < 1e-6);
     assert!((o_pos.y - 0.0).abs() < 1e-6);
     assert!((o_pos.z - 0.0).abs() < 1e-6);
@@ -44,9 +44,9 @@ fn test_xyz_elements_from_string() {
     // Element symbols are recovered when loading through the full pipeline;
     // parser stores positions keyed by atom index.
     let frame = trajectory.get_frame(0).unwrap();
-    assert!(frame.get_position(0).is_some());
-    assert!(frame.get_position(1).is_some());
-    assert!(frame.get_position(2).is_some());
+    assert!(frame.position(0).is_some());
+    assert!(frame.position(1).is_some());
+    assert!(frame.position(2).is_some());
 
     assert_eq!(Element::from_symbol("O").unwrap(), Element::O);
 }